@@ -11,6 +11,7 @@ use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use clap::builder::styling::AnsiColor;
 use clap::builder::FalseyValueParser;
@@ -43,6 +44,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::args::resolve_no_prompt;
+use crate::npm::LifecycleScriptsPolicy;
 use crate::util::fs::canonicalize_path;
 
 use super::flags_net;
@@ -115,6 +117,9 @@ pub struct CheckFlags {
   pub files: Vec<String>,
   pub doc: bool,
   pub doc_only: bool,
+  /// Keeps checking on changes, reusing the type checker of the previous
+  /// check.
+  pub daemon: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -549,6 +554,15 @@ impl TypeCheckMode {
   }
 }
 
+/// How errors ending the process are printed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ErrorFormat {
+  #[default]
+  Pretty,
+  /// A JSON object per error, see `crate::errors::JsonErrorFormatter`.
+  Json,
+}
+
 impl Default for TypeCheckMode {
   fn default() -> Self {
     Self::None
@@ -565,13 +579,27 @@ pub enum CaData {
 }
 
 // Info needed to run NPM lifecycle scripts
-#[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[derive(Clone, Default)]
 pub struct LifecycleScriptsConfig {
   pub allowed: PackagesAllowedScripts,
   pub initial_cwd: PathBuf,
   pub root_dir: PathBuf,
   /// Part of an explicit `deno install`
   pub explicit_install: bool,
+  /// Decides instead of `allowed`, set by embedders.
+  pub policy: Option<Arc<dyn LifecycleScriptsPolicy>>,
+}
+
+impl std::fmt::Debug for LifecycleScriptsConfig {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("LifecycleScriptsConfig")
+      .field("allowed", &self.allowed)
+      .field("initial_cwd", &self.initial_cwd)
+      .field("root_dir", &self.root_dir)
+      .field("explicit_install", &self.explicit_install)
+      .field("policy", &self.policy.is_some())
+      .finish()
+  }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
@@ -625,6 +653,10 @@ pub struct Flags {
   pub cache_blocklist: Vec<String>,
   pub cached_only: bool,
   pub type_check_mode: TypeCheckMode,
+  /// Type checks independent parts of the module graph in parallel, in up
+  /// to this many isolates.
+  pub type_check_jobs: Option<NonZeroUsize>,
+  pub error_format: ErrorFormat,
   pub config_flag: ConfigFlag,
   pub node_modules_dir: Option<NodeModulesDirMode>,
   pub vendor: Option<bool>,
@@ -1903,6 +1935,15 @@ Unless --reload is specified, this command will not re-download already cached d
             .required_unless_present("help")
             .value_hint(ValueHint::FilePath),
         )
+        .arg(parallel_arg("type checking of independent modules"))
+        .arg(
+          Arg::new("daemon")
+            .long("daemon")
+            .help("Keep the type checker running and check again when files change, only checking the changed modules and their dependents")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("parallel")
+        )
+        .arg(error_format_arg())
         .arg(frozen_lockfile_arg())
         .arg(allow_import_arg())
       }
@@ -2966,6 +3007,7 @@ fn run_args(command: Command, top_level: bool) -> Command {
     })
     .arg(env_file_arg())
     .arg(no_code_cache_arg())
+    .arg(error_format_arg())
 }
 
 fn run_subcommand() -> Command {
@@ -3234,6 +3276,7 @@ or <c>**/__tests__/**</>:
       )
       .arg(env_file_arg())
       .arg(executable_ext_arg())
+      .arg(error_format_arg())
     )
 }
 
@@ -3244,6 +3287,13 @@ fn parallel_arg(descr: &str) -> Arg {
     .action(ArgAction::SetTrue)
 }
 
+fn error_format_arg() -> Arg {
+  Arg::new("error-format")
+    .long("error-format")
+    .help("Print errors as 'pretty' text or as 'json' objects for other programs to consume. Defaults to 'pretty'")
+    .value_parser(["pretty", "json"])
+}
+
 fn types_subcommand() -> Command {
   command(
     "types",
@@ -4585,10 +4635,13 @@ fn check_parse(
   if matches.get_flag("all") || matches.get_flag("remote") {
     flags.type_check_mode = TypeCheckMode::All;
   }
+  flags.type_check_jobs = parallel_arg_parse(matches);
+  error_format_arg_parse(flags, matches);
   flags.subcommand = DenoSubcommand::Check(CheckFlags {
     files,
     doc: matches.get_flag("doc"),
     doc_only: matches.get_flag("doc-only"),
+    daemon: matches.get_flag("daemon"),
   });
   allow_import_parse(flags, matches);
   Ok(())
@@ -5180,6 +5233,7 @@ fn run_parse(
   ext_arg_parse(flags, matches);
 
   flags.code_cache_enabled = !matches.get_flag("no-code-cache");
+  error_format_arg_parse(flags, matches);
 
   if let Some(mut script_arg) = matches.remove_many::<String>("script_arg") {
     let script = script_arg.next().unwrap();
@@ -5311,6 +5365,15 @@ fn task_parse(
   Ok(())
 }
 
+fn error_format_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(error_format) = matches.remove_one::<String>("error-format") {
+    flags.error_format = match error_format.as_str() {
+      "json" => ErrorFormat::Json,
+      _ => ErrorFormat::Pretty,
+    };
+  }
+}
+
 fn parallel_arg_parse(matches: &mut ArgMatches) -> Option<NonZeroUsize> {
   if matches.get_flag("parallel") {
     if let Ok(value) = env::var("DENO_JOBS") {
@@ -5375,6 +5438,7 @@ fn test_parse(
   }
 
   let concurrent_jobs = parallel_arg_parse(matches);
+  error_format_arg_parse(flags, matches);
 
   let include = if let Some(files) = matches.remove_many::<String>("files") {
     files.collect()
@@ -6127,6 +6191,29 @@ mod tests {
     );
   }
 
+  #[test]
+  fn run_error_format() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--error-format=json", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string()
+        )),
+        error_format: ErrorFormat::Json,
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "check", "--error-format=json", "a.ts"]);
+    assert_eq!(r.unwrap().error_format, ErrorFormat::Json);
+    let r = flags_from_vec(svec!["deno", "run", "--error-format=xml", "a.ts"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_watch() {
     let r = flags_from_vec(svec!["deno", "run", "--watch", "script.ts"]);
@@ -7416,6 +7503,7 @@ mod tests {
           files: svec!["script.ts"],
           doc: false,
           doc_only: false,
+          daemon: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -7430,6 +7518,7 @@ mod tests {
           files: svec!["script.ts"],
           doc: true,
           doc_only: false,
+          daemon: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -7444,6 +7533,7 @@ mod tests {
           files: svec!["markdown.md"],
           doc: false,
           doc_only: true,
+          daemon: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -7472,6 +7562,7 @@ mod tests {
             files: svec!["script.ts"],
             doc: false,
             doc_only: false,
+            daemon: false,
           }),
           type_check_mode: TypeCheckMode::All,
           ..Flags::default()
@@ -7490,6 +7581,24 @@ mod tests {
         clap::error::ErrorKind::ArgumentConflict
       );
     }
+
+    let r = flags_from_vec(svec!["deno", "check", "--parallel", "script.ts"]);
+    assert!(r.unwrap().type_check_jobs.is_some());
+
+    let r = flags_from_vec(svec!["deno", "check", "--daemon", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+          doc: false,
+          doc_only: false,
+          daemon: true,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
   }
 
   #[test]
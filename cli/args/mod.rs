@@ -642,6 +642,86 @@ pub fn create_default_npmrc() -> Arc<ResolvedNpmRc> {
   })
 }
 
+/// An npm registry and the credentials to authenticate with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmRegistry {
+  pub url: Url,
+  /// Sent as a bearer token, like `_authToken` in an `.npmrc`.
+  pub auth_token: Option<String>,
+  /// Base64 encoded `username:password`, like `_auth` in an `.npmrc`.
+  pub auth: Option<String>,
+}
+
+impl NpmRegistry {
+  pub fn new(url: Url) -> Self {
+    Self {
+      url,
+      auth_token: None,
+      auth: None,
+    }
+  }
+
+  fn to_registry_config(
+    &self,
+  ) -> (String, deno_npm::npm_rc::RegistryConfigWithUrl) {
+    let mut url = self.url.clone();
+    if !url.path().ends_with('/') {
+      url.set_path(&format!("{}/", url.path()));
+    }
+    // registries are keyed like in `.npmrc` files, eg. `//registry.npmjs.org/`
+    let key = url.as_str()[url.scheme().len() + 1..].to_string();
+    let config = Arc::new(deno_npm::npm_rc::RegistryConfig {
+      auth_token: self.auth_token.clone(),
+      auth: self.auth.clone(),
+      ..Default::default()
+    });
+    (
+      key,
+      deno_npm::npm_rc::RegistryConfigWithUrl {
+        registry_url: url,
+        config,
+      },
+    )
+  }
+}
+
+/// npm registries to use instead of the ones configured in `.npmrc` files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NpmRegistriesConfig {
+  /// Registry of packages without a scope of their own. Defaults to
+  /// `NPM_CONFIG_REGISTRY`, or the public npm registry.
+  pub default_registry: Option<NpmRegistry>,
+  /// Registries by package scope, eg. `"@my-org"`.
+  pub scopes: HashMap<String, NpmRegistry>,
+}
+
+impl NpmRegistriesConfig {
+  pub fn resolve(&self) -> Result<Arc<ResolvedNpmRc>, AnyError> {
+    let default_registry = self
+      .default_registry
+      .clone()
+      .unwrap_or_else(|| NpmRegistry::new(npm_registry_url().clone()));
+    let (key, default_config) = default_registry.to_registry_config();
+    let mut registry_configs = HashMap::new();
+    registry_configs.insert(key, default_config.config.clone());
+    let mut scopes = HashMap::with_capacity(self.scopes.len());
+    for (scope, registry) in &self.scopes {
+      let name = scope.strip_prefix('@').unwrap_or(scope);
+      if name.is_empty() || name.contains('/') {
+        bail!("Invalid npm scope '{}'.", scope);
+      }
+      let (key, config) = registry.to_registry_config();
+      registry_configs.insert(key, config.config.clone());
+      scopes.insert(name.to_string(), config);
+    }
+    Ok(Arc::new(ResolvedNpmRc {
+      default_config,
+      scopes,
+      registry_configs,
+    }))
+  }
+}
+
 #[derive(Error, Debug, Clone)]
 pub enum RootCertStoreLoadError {
   #[error(
@@ -1299,6 +1379,11 @@ impl CliOptions {
     &self.npmrc
   }
 
+  /// Uses `npmrc` instead of the discovered `.npmrc` file.
+  pub fn set_npmrc(&mut self, npmrc: Arc<ResolvedNpmRc>) {
+    self.npmrc = npmrc;
+  }
+
   pub fn resolve_fmt_options_for_members(
     &self,
     fmt_flags: &FmtFlags,
@@ -1597,6 +1682,10 @@ impl CliOptions {
     self.flags.type_check_mode
   }
 
+  pub fn type_check_jobs(&self) -> Option<NonZeroUsize> {
+    self.flags.type_check_jobs
+  }
+
   pub fn unsafely_ignore_certificate_errors(&self) -> &Option<Vec<String>> {
     &self.flags.unsafely_ignore_certificate_errors
   }
@@ -1759,6 +1848,7 @@ impl CliOptions {
           | DenoSubcommand::Cache(_)
           | DenoSubcommand::Add(_)
       ),
+      policy: None,
     }
   }
 }
@@ -2065,4 +2155,39 @@ mod test {
     let reg_api_url = jsr_api_url();
     assert!(reg_api_url.as_str().ends_with('/'));
   }
+
+  #[test]
+  fn npm_registries_config_resolve() {
+    let mut config = NpmRegistriesConfig::default();
+    config.scopes.insert(
+      "@my-org".to_string(),
+      NpmRegistry {
+        url: Url::parse("https://npm.example.com/registry").unwrap(),
+        auth_token: Some("token".to_string()),
+        auth: None,
+      },
+    );
+    let npmrc = config.resolve().unwrap();
+    assert_eq!(
+      npmrc.get_registry_url("@my-org/pkg").as_str(),
+      "https://npm.example.com/registry/"
+    );
+    assert_eq!(
+      npmrc
+        .get_registry_config("@my-org/pkg")
+        .auth_token
+        .as_deref(),
+      Some("token")
+    );
+    assert_eq!(npmrc.get_registry_url("chalk"), npm_registry_url());
+    assert!(npmrc
+      .registry_configs
+      .contains_key("//npm.example.com/registry/"));
+
+    config.scopes.insert(
+      "@".to_string(),
+      NpmRegistry::new(Url::parse("https://npm.example.com/").unwrap()),
+    );
+    assert!(config.resolve().is_err());
+  }
 }
@@ -1,7 +1,10 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use async_trait::async_trait;
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
 use deno_core::ModuleSpecifier;
 use log::debug;
 use log::error;
@@ -25,9 +28,9 @@ pub struct AuthToken {
   token: AuthTokenData,
 }
 
-impl fmt::Display for AuthToken {
+impl fmt::Display for AuthTokenData {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    match &self.token {
+    match self {
       AuthTokenData::Bearer(token) => write!(f, "Bearer {token}"),
       AuthTokenData::Basic { username, password } => {
         let credentials = format!("{username}:{password}");
@@ -37,6 +40,30 @@ impl fmt::Display for AuthToken {
   }
 }
 
+impl fmt::Display for AuthToken {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.token.fmt(f)
+  }
+}
+
+/// Supplies the tokens of the requests for remote modules, JSR packages and
+/// npm packages, eg. short-lived tokens from a vault. The tokens take
+/// precedence over `DENO_AUTH_TOKENS` and the `.npmrc`.
+#[async_trait(?Send)]
+pub trait TokenProvider: Send + Sync {
+  /// The token to send to `url`, `None` to fall back to the configured one.
+  async fn token(&self, url: &Url) -> Result<Option<AuthTokenData>, AnyError>;
+
+  /// Called when the server rejected the token of a request to `url` with a
+  /// 401 response. The request is retried once with the returned token.
+  async fn refresh_token(
+    &self,
+    _url: &Url,
+  ) -> Result<Option<AuthTokenData>, AnyError> {
+    Ok(None)
+  }
+}
+
 /// A structure which contains bearer tokens that can be used when sending
 /// requests to websites, intended to authorize access to private resources
 /// such as remote modules.
@@ -1,9 +1,11 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use deno_ast::ModuleSpecifier;
 use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
 use deno_runtime::code_cache;
 use deno_runtime::deno_webstorage::rusqlite::params;
 
@@ -13,6 +15,8 @@ use super::cache_db::CacheDB;
 use super::cache_db::CacheDBConfiguration;
 use super::cache_db::CacheDBHash;
 use super::cache_db::CacheFailure;
+use super::ModuleCache;
+use super::ModuleCacheKey;
 
 pub static CODE_CACHE_DB: CacheDBConfiguration = CacheDBConfiguration {
   table_initializer: concat!(
@@ -31,12 +35,19 @@ pub static CODE_CACHE_DB: CacheDBConfiguration = CacheDBConfiguration {
 
 pub struct CodeCache {
   inner: CodeCacheInner,
+  module_cache: Option<Arc<ModuleCache>>,
+  /// The module cache keys of the modules whose source was known when their
+  /// code cache was looked up, as V8 only hands back the source hash once the
+  /// code cache is ready.
+  module_cache_keys: Mutex<HashMap<(ModuleSpecifier, u64), ModuleCacheKey>>,
 }
 
 impl CodeCache {
-  pub fn new(db: CacheDB) -> Self {
+  pub fn new(db: CacheDB, module_cache: Option<Arc<ModuleCache>>) -> Self {
     Self {
       inner: CodeCacheInner::new(db),
+      module_cache,
+      module_cache_keys: Default::default(),
     }
   }
 
@@ -57,17 +68,39 @@ impl CodeCache {
     }
   }
 
+  /// Looks up the code cache of a module. The shared module cache is only
+  /// used when the `source` of the module is provided.
   pub fn get_sync(
     &self,
     specifier: &ModuleSpecifier,
     code_cache_type: code_cache::CodeCacheType,
     source_hash: u64,
+    source: Option<&[u8]>,
   ) -> Option<Vec<u8>> {
-    Self::ensure_ok(self.inner.get_sync(
+    let (Some(module_cache), Some(source)) = (&self.module_cache, source)
+    else {
+      return Self::ensure_ok(self.inner.get_sync(
+        specifier.as_str(),
+        code_cache_type,
+        CacheDBHash::new(source_hash),
+      ));
+    };
+    let key = ModuleCacheKey::new(specifier, source, ());
+    let cached = module_cache.get_code_cache(code_cache_type, &key);
+    self
+      .module_cache_keys
+      .lock()
+      .insert((specifier.clone(), source_hash), key.clone());
+    if let Some(data) = cached {
+      return Some(data);
+    }
+    let data = Self::ensure_ok(self.inner.get_sync(
       specifier.as_str(),
       code_cache_type,
       CacheDBHash::new(source_hash),
-    ))
+    ))?;
+    module_cache.set_code_cache(code_cache_type, &key, &data);
+    Some(data)
   }
 
   pub fn set_sync(
@@ -77,6 +110,15 @@ impl CodeCache {
     source_hash: u64,
     data: &[u8],
   ) {
+    if let Some(module_cache) = &self.module_cache {
+      let key = self
+        .module_cache_keys
+        .lock()
+        .remove(&(specifier.clone(), source_hash));
+      if let Some(key) = key {
+        module_cache.set_code_cache(code_cache_type, &key, data);
+      }
+    }
     Self::ensure_ok(self.inner.set_sync(
       specifier.as_str(),
       code_cache_type,
@@ -99,7 +141,7 @@ impl code_cache::CodeCache for CodeCache {
     code_cache_type: code_cache::CodeCacheType,
     source_hash: u64,
   ) -> Option<Vec<u8>> {
-    self.get_sync(specifier, code_cache_type, source_hash)
+    self.get_sync(specifier, code_cache_type, source_hash, None)
   }
 
   fn set_sync(
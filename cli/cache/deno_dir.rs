@@ -121,6 +121,18 @@ impl DenoDir {
     self.root.join("check_cache_v2")
   }
 
+  /// Path to the shared module cache, see `ModuleCache`.
+  pub fn module_cache_folder_path(&self) -> PathBuf {
+    // bump this version name to invalidate the entire cache
+    self.root.join("module_cache_v2")
+  }
+
+  /// Path to the content addressed npm store, see `NpmStore`.
+  pub fn npm_store_folder_path(&self) -> PathBuf {
+    // bump this version name to invalidate the entire store
+    self.root.join("npm_store_v1")
+  }
+
   /// Path to the registries cache, used for the lps.
   pub fn registries_folder_path(&self) -> PathBuf {
     self.root.join("registries")
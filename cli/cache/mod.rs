@@ -40,6 +40,7 @@ mod disk_cache;
 mod emit;
 mod fast_check;
 mod incremental;
+mod module_cache;
 mod module_info;
 mod node;
 mod parsed_source;
@@ -56,6 +57,10 @@ pub use disk_cache::DiskCache;
 pub use emit::EmitCache;
 pub use fast_check::FastCheckCache;
 pub use incremental::IncrementalCache;
+pub use module_cache::ModuleCache;
+pub use module_cache::ModuleCacheKey;
+pub use module_cache::ModuleCachePruneOptions;
+pub use module_cache::ModuleCachePruneReport;
 pub use module_info::ModuleInfoCache;
 pub use node::NodeAnalysisCache;
 pub use parsed_source::LazyGraphSourceParser;
@@ -179,11 +184,25 @@ pub type LocalLspHttpCache =
   deno_cache_dir::LocalLspHttpCache<RealDenoCacheEnv>;
 pub use deno_cache_dir::HttpCache;
 
+/// Loads the WebAssembly components of a module graph as JavaScript modules,
+/// see `cli/wasi.rs`.
+pub trait WasmComponentLoader: Send + Sync {
+  /// Returns the source of the JavaScript module that replaces `source`,
+  /// or `None` when it isn't a component.
+  fn load_component(
+    &self,
+    specifier: &ModuleSpecifier,
+    source: &Arc<[u8]>,
+  ) -> Option<Arc<[u8]>>;
+}
+
 pub struct FetchCacherOptions {
   pub file_header_overrides: HashMap<ModuleSpecifier, HashMap<String, String>>,
   pub permissions: PermissionsContainer,
   /// If we're publishing for `deno publish`.
   pub is_deno_publish: bool,
+  /// Loads WASI components as JavaScript modules that run them.
+  pub wasm_component_loader: Option<Arc<dyn WasmComponentLoader>>,
 }
 
 /// A "wrapper" for the FileFetcher and DiskCache for the Deno CLI that provides
@@ -197,6 +216,7 @@ pub struct FetchCacher {
   module_info_cache: Arc<ModuleInfoCache>,
   permissions: PermissionsContainer,
   is_deno_publish: bool,
+  wasm_component_loader: Option<Arc<dyn WasmComponentLoader>>,
   cache_info_enabled: bool,
 }
 
@@ -218,6 +238,7 @@ impl FetchCacher {
       file_header_overrides: options.file_header_overrides,
       permissions: options.permissions,
       is_deno_publish: options.is_deno_publish,
+      wasm_component_loader: options.wasm_component_loader,
       cache_info_enabled: false,
     }
   }
@@ -303,6 +324,7 @@ impl Loader for FetchCacher {
     let file_fetcher = self.file_fetcher.clone();
     let file_header_overrides = self.file_header_overrides.clone();
     let permissions = self.permissions.clone();
+    let wasm_component_loader = self.wasm_component_loader.clone();
     let specifier = specifier.clone();
     let is_statically_analyzable = !options.was_dynamic_root;
 
@@ -347,6 +369,22 @@ impl Loader for FetchCacher {
                 (None, Some(overrides)) => Some(overrides.clone()),
                 (None, None) => None,
               };
+            let maybe_component_module = wasm_component_loader
+              .as_ref()
+              .and_then(|loader| {
+                loader.load_component(&file.specifier, &file.source)
+              });
+            if let Some(content) = maybe_component_module {
+              // the media type of the module follows the content type
+              return Ok(Some(LoadResponse::Module {
+                specifier: file.specifier,
+                maybe_headers: Some(HashMap::from([(
+                  "content-type".to_string(),
+                  "application/javascript".to_string(),
+                )])),
+                content,
+              }));
+            }
             Ok(Some(LoadResponse::Module {
               specifier: file.specifier,
               maybe_headers,
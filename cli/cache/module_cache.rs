@@ -0,0 +1,396 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::fs;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::unsync::sync::AtomicFlag;
+use deno_core::ModuleSpecifier;
+use deno_runtime::code_cache::CodeCacheType;
+use sha2::Digest;
+
+use super::DenoDir;
+use super::CACHE_PERM;
+use crate::util::fs::atomic_write_file_with_retries;
+
+/// A cache of transpiled modules, their V8 code cache and the machine code
+/// of WebAssembly components that can be shared by the workers of many
+/// projects and populated ahead of time. Entries are keyed by the sha256 of
+/// the specifier and the source of a module and the options it was emitted
+/// with, so a project can't make another one load its entries.
+///
+/// It's consulted before the emit and code caches of the `DENO_DIR`. Entries
+/// are never invalidated, use [`ModuleCache::prune`] to bound the size of
+/// the cache.
+#[derive(Debug)]
+pub struct ModuleCache {
+  location: PathBuf,
+  write_failed_flag: AtomicFlag,
+}
+
+/// Limits for [`ModuleCache::prune`]. Entries that were used least recently
+/// are removed first.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleCachePruneOptions {
+  /// Removes entries that weren't used for longer than this.
+  pub max_age: Option<Duration>,
+  /// Removes entries until the cache takes at most this many bytes.
+  pub max_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleCachePruneReport {
+  pub removed_entries: usize,
+  pub removed_bytes: u64,
+  pub remaining_entries: usize,
+  pub remaining_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EntryKind {
+  Emit,
+  CodeCache(CodeCacheType),
+  WasmComponent,
+}
+
+impl EntryKind {
+  fn extension(&self) -> &'static str {
+    match self {
+      EntryKind::Emit => "js",
+      EntryKind::CodeCache(CodeCacheType::EsModule) => "esm",
+      EntryKind::CodeCache(CodeCacheType::Script) => "script",
+      EntryKind::WasmComponent => "cwasm",
+    }
+  }
+}
+
+struct Entry {
+  path: PathBuf,
+  size: u64,
+  last_used: SystemTime,
+}
+
+/// Identifies the entries of a module. The hash is cryptographic as the
+/// cache may be shared by projects that don't trust each other.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleCacheKey(String);
+
+impl ModuleCacheKey {
+  /// `options` are the options the entry depends on besides the source, eg.
+  /// the transpile options of emits.
+  pub fn new(
+    specifier: &ModuleSpecifier,
+    source: &[u8],
+    options: impl Hash,
+  ) -> Self {
+    Self::from_parts(Some(specifier), source, options)
+  }
+
+  /// Identifies the machine code of a WebAssembly component, which only
+  /// depends on its bytes and on the compiler settings in `options`, so
+  /// components with the same content share it.
+  pub fn wasm(source: &[u8], options: impl Hash) -> Self {
+    Self::from_parts(None, source, options)
+  }
+
+  fn from_parts(
+    specifier: Option<&ModuleSpecifier>,
+    source: &[u8],
+    options: impl Hash,
+  ) -> Self {
+    let mut hasher = Sha256Hasher(sha2::Sha256::new());
+    // emits and code caches of other versions may differ
+    crate::version::DENO_VERSION_INFO.deno.hash(&mut hasher);
+    specifier
+      .map(|specifier| specifier.as_str())
+      .hash(&mut hasher);
+    source.hash(&mut hasher);
+    options.hash(&mut hasher);
+    Self(faster_hex::hex_string(&hasher.0.finalize()))
+  }
+}
+
+/// Feeds the `Hash` implementations of the key parts into sha256.
+struct Sha256Hasher(sha2::Sha256);
+
+impl Hasher for Sha256Hasher {
+  fn write(&mut self, bytes: &[u8]) {
+    self.0.update(bytes);
+  }
+
+  fn finish(&self) -> u64 {
+    let digest = self.0.clone().finalize();
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
+  }
+}
+
+impl ModuleCache {
+  /// `location` must be an absolute path.
+  pub fn new(location: PathBuf) -> Result<Self, AnyError> {
+    if !location.is_absolute() {
+      bail!(
+        "The location of the module cache must be an absolute path: {}",
+        location.display()
+      );
+    }
+    Ok(Self {
+      location,
+      write_failed_flag: Default::default(),
+    })
+  }
+
+  /// The cache in the `module_cache_v2` folder of `deno_dir`, or of the
+  /// default `DENO_DIR` when `None`.
+  pub fn in_deno_dir(deno_dir: Option<PathBuf>) -> Result<Self, AnyError> {
+    let deno_dir = DenoDir::new(deno_dir)?;
+    Self::new(deno_dir.module_cache_folder_path())
+  }
+
+  pub fn location(&self) -> &Path {
+    &self.location
+  }
+
+  /// Compiles a WebAssembly component into the cache, eg. while installing
+  /// an application, so workers running it with `WasiOptions` load its
+  /// machine code instead of compiling it. Core modules can't be compiled
+  /// ahead of time, V8 compiles them when they are imported.
+  ///
+  /// Returns whether the component was already compiled.
+  pub fn precompile_wasm(&self, source: &[u8]) -> Result<bool, AnyError> {
+    if !crate::wasi::is_wasm_component(source) {
+      bail!("Only WebAssembly components can be compiled ahead of time.");
+    }
+    let engine = crate::wasi::create_engine();
+    let (_, cached) =
+      crate::wasi::compile_component(&engine, Some(self), source)?;
+    Ok(cached)
+  }
+
+  /// Removes entries exceeding the limits of `options`.
+  pub fn prune(
+    &self,
+    options: &ModuleCachePruneOptions,
+  ) -> std::io::Result<ModuleCachePruneReport> {
+    let mut entries = self.read_entries()?;
+    // most recently used first
+    entries.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+    let now = SystemTime::now();
+    let mut report = ModuleCachePruneReport::default();
+    for entry in entries {
+      let expired = options.max_age.is_some_and(|max_age| {
+        now
+          .duration_since(entry.last_used)
+          .is_ok_and(|age| age > max_age)
+      });
+      let too_large = options
+        .max_size
+        .is_some_and(|max_size| report.remaining_bytes + entry.size > max_size);
+      if expired || too_large {
+        match fs::remove_file(&entry.path) {
+          Ok(()) => {}
+          Err(err) if err.kind() == ErrorKind::NotFound => {}
+          Err(err) => return Err(err),
+        }
+        report.removed_entries += 1;
+        report.removed_bytes += entry.size;
+      } else {
+        report.remaining_entries += 1;
+        report.remaining_bytes += entry.size;
+      }
+    }
+    Ok(report)
+  }
+
+  /// Removes every entry.
+  pub fn clear(&self) -> std::io::Result<()> {
+    match fs::remove_dir_all(&self.location) {
+      Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+      _ => Ok(()),
+    }
+  }
+
+  pub(crate) fn get_emit(&self, key: &ModuleCacheKey) -> Option<String> {
+    let bytes = self.get(EntryKind::Emit, key)?;
+    String::from_utf8(bytes).ok()
+  }
+
+  pub(crate) fn set_emit(&self, key: &ModuleCacheKey, code: &str) {
+    self.set(EntryKind::Emit, key, code.as_bytes());
+  }
+
+  pub(crate) fn get_code_cache(
+    &self,
+    code_cache_type: CodeCacheType,
+    key: &ModuleCacheKey,
+  ) -> Option<Vec<u8>> {
+    self.get(EntryKind::CodeCache(code_cache_type), key)
+  }
+
+  pub(crate) fn set_code_cache(
+    &self,
+    code_cache_type: CodeCacheType,
+    key: &ModuleCacheKey,
+    data: &[u8],
+  ) {
+    self.set(EntryKind::CodeCache(code_cache_type), key, data);
+  }
+
+  pub(crate) fn get_wasm_component(
+    &self,
+    key: &ModuleCacheKey,
+  ) -> Option<Vec<u8>> {
+    self.get(EntryKind::WasmComponent, key)
+  }
+
+  pub(crate) fn set_wasm_component(&self, key: &ModuleCacheKey, code: &[u8]) {
+    self.set(EntryKind::WasmComponent, key, code);
+  }
+
+  fn get(&self, kind: EntryKind, key: &ModuleCacheKey) -> Option<Vec<u8>> {
+    let path = self.entry_path(kind, key);
+    let bytes = fs::read(&path).ok()?;
+    // the modification time tracks when the entry was last used, for pruning
+    let _ = fs::File::options()
+      .write(true)
+      .open(&path)
+      .and_then(|file| file.set_modified(SystemTime::now()));
+    Some(bytes)
+  }
+
+  fn set(&self, kind: EntryKind, key: &ModuleCacheKey, data: &[u8]) {
+    if self.write_failed_flag.is_raised() {
+      return;
+    }
+    let path = self.entry_path(kind, key);
+    if let Err(err) = atomic_write_file_with_retries(&path, data, CACHE_PERM) {
+      // might error in cases such as a readonly file system
+      log::debug!(
+        "Error saving module cache entry ({}): {}",
+        path.display(),
+        err
+      );
+      self.write_failed_flag.raise();
+    }
+  }
+
+  /// Entries are spread over folders named after the first two hex digits of
+  /// their key.
+  fn entry_path(&self, kind: EntryKind, key: &ModuleCacheKey) -> PathBuf {
+    self.location.join(&key.0[..2]).join(format!(
+      "{}.{}",
+      key.0,
+      kind.extension()
+    ))
+  }
+
+  fn read_entries(&self) -> std::io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let shards = match fs::read_dir(&self.location) {
+      Ok(shards) => shards,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(entries),
+      Err(err) => return Err(err),
+    };
+    for shard in shards {
+      let shard = shard?;
+      if !shard.file_type()?.is_dir() {
+        continue;
+      }
+      for file in fs::read_dir(shard.path())? {
+        let file = file?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+          continue;
+        }
+        entries.push(Entry {
+          path: file.path(),
+          size: metadata.len(),
+          last_used: metadata.modified()?,
+        });
+      }
+    }
+    Ok(entries)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  fn module_cache_general_use() {
+    let temp_dir = TempDir::new();
+    let cache =
+      ModuleCache::new(temp_dir.path().join("module_cache").to_path_buf())
+        .unwrap();
+    let specifier = ModuleSpecifier::parse("file:///main.ts").unwrap();
+    let key = ModuleCacheKey::new(&specifier, b"export {};", ());
+    let other_key = ModuleCacheKey::new(&specifier, b"export {}", ());
+
+    assert_eq!(cache.get_emit(&key), None);
+    cache.set_emit(&key, "export {};");
+    assert_eq!(cache.get_emit(&key), Some("export {};".to_string()));
+    assert_eq!(cache.get_emit(&other_key), None);
+    assert_eq!(cache.get_code_cache(CodeCacheType::EsModule, &key), None);
+    cache.set_code_cache(CodeCacheType::EsModule, &key, &[1, 2, 3]);
+    assert_eq!(
+      cache.get_code_cache(CodeCacheType::EsModule, &key),
+      Some(vec![1, 2, 3])
+    );
+    assert_eq!(cache.get_code_cache(CodeCacheType::Script, &key), None);
+
+    let report = cache
+      .prune(&ModuleCachePruneOptions {
+        max_age: None,
+        max_size: Some(10),
+      })
+      .unwrap();
+    assert_eq!(report.removed_entries, 1);
+    assert_eq!(report.remaining_entries, 1);
+    assert!(report.remaining_bytes <= 10);
+
+    cache.clear().unwrap();
+    assert_eq!(cache.get_emit(&key), None);
+    let report = cache.prune(&ModuleCachePruneOptions::default()).unwrap();
+    assert_eq!(report, ModuleCachePruneReport::default());
+  }
+
+  #[test]
+  fn module_cache_key() {
+    let a = ModuleSpecifier::parse("file:///a/main.ts").unwrap();
+    let b = ModuleSpecifier::parse("file:///b/main.ts").unwrap();
+    let key = ModuleCacheKey::new(&a, b"export {};", 1);
+    assert_eq!(key, ModuleCacheKey::new(&a, b"export {};", 1));
+    // emits embed the specifier in their source map
+    assert_ne!(key, ModuleCacheKey::new(&b, b"export {};", 1));
+    assert_ne!(key, ModuleCacheKey::new(&a, b"export {}", 1));
+    assert_ne!(key, ModuleCacheKey::new(&a, b"export {};", 2));
+    assert_eq!(key.0.len(), 64);
+    // compiled WebAssembly is shared by modules with the same bytes
+    assert_eq!(
+      ModuleCacheKey::wasm(b"\0asm", 1),
+      ModuleCacheKey::wasm(b"\0asm", 1)
+    );
+    assert_ne!(
+      ModuleCacheKey::wasm(b"\0asm", 1),
+      ModuleCacheKey::wasm(b"\0asm", 2)
+    );
+    assert_ne!(
+      ModuleCacheKey::wasm(b"\0asm", 1),
+      ModuleCacheKey::new(&a, b"\0asm", 1)
+    );
+  }
+
+  #[test]
+  fn module_cache_relative_location() {
+    assert!(ModuleCache::new(PathBuf::from("module_cache")).is_err());
+  }
+}
@@ -367,11 +367,100 @@ pub struct TakePreciseCoverageResponse {
   pub timestamp: f64,
 }
 
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler/#method-setSamplingInterval>
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSamplingIntervalArgs {
+  /// Sampling interval in microseconds.
+  pub interval: u64,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler/#method-stop>
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopProfilerResponse {
+  pub profile: Profile,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler/#type-Profile>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+  pub nodes: Vec<ProfileNode>,
+  /// Profiling start timestamp in microseconds.
+  pub start_time: f64,
+  /// Profiling end timestamp in microseconds.
+  pub end_time: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub samples: Option<Vec<i64>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub time_deltas: Option<Vec<i64>>,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler/#type-ProfileNode>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileNode {
+  pub id: i64,
+  pub call_frame: CallFrame,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub hit_count: Option<i64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub children: Option<Vec<i64>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub deopt_reason: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub position_ticks: Option<Vec<PositionTickInfo>>,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Runtime/#type-CallFrame>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+  pub function_name: String,
+  pub script_id: ScriptId,
+  pub url: String,
+  /// Zero based line number.
+  pub line_number: i64,
+  /// Zero based column number.
+  pub column_number: i64,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Profiler/#type-PositionTickInfo>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionTickInfo {
+  pub line: i64,
+  pub ticks: i64,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/HeapProfiler/#method-takeHeapSnapshot>
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TakeHeapSnapshotArgs {
+  pub report_progress: bool,
+}
+
+/// <https://chromedevtools.github.io/devtools-protocol/tot/HeapProfiler/#event-addHeapSnapshotChunk>
+#[derive(Debug, Deserialize)]
+pub struct AddHeapSnapshotChunk {
+  pub chunk: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Notification {
   pub method: String,
   pub params: Value,
 }
+/// <https://chromedevtools.github.io/devtools-protocol/tot/Runtime/#event-consoleAPICalled>
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleApiCalled {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub args: Vec<RemoteObject>,
+}
+
 /// <https://chromedevtools.github.io/devtools-protocol/tot/Runtime/#event-exceptionThrown>
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
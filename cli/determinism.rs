@@ -0,0 +1,121 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Deterministic execution of a main worker, for embedders that replay
+//! workflows and need scripts to behave the same on every run.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use deno_core::op2;
+use deno_core::Extension;
+use deno_core::OpState;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use serde::Serialize;
+
+deno_core::extension!(deno_determinism,
+  ops = [
+    op_determinism_config,
+    op_determinism_random,
+  ],
+  options = {
+    options: DeterminismOptions,
+  },
+  state = |state, options| {
+    state.put(DeterministicRng(StdRng::seed_from_u64(options.options.seed)));
+    state.put(options.options);
+  },
+);
+
+/// How time passes for a deterministic worker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeterministicClock {
+  /// `Date.now()` and `performance.now()` always return the start time.
+  /// Timers still wait for their delay.
+  Frozen,
+  /// Time only advances when a timer fires, to the time it was due at.
+  /// Timers run in the order they are due without waiting for their delay.
+  #[default]
+  Virtual,
+  /// Like [`DeterministicClock::Virtual`], but timers are paused until the
+  /// host resumes them or advances the clock with
+  /// [`CliMainWorker::advance_time`](crate::worker::CliMainWorker::advance_time).
+  Manual,
+}
+
+/// A timer of a deterministic worker that hasn't fired yet, see
+/// [`CliMainWorker::pending_timers`](crate::worker::CliMainWorker::pending_timers).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTimer {
+  /// The id returned by `setTimeout()` or `setInterval()`.
+  pub id: u32,
+  /// How far the clock has to advance for the timer to fire.
+  pub due_in: Duration,
+  /// Whether the timer was created with `setInterval()`.
+  pub repeat: bool,
+}
+
+/// Makes a main worker behave the same on every run, given the same
+/// inputs.
+#[derive(Debug, Clone)]
+pub struct DeterminismOptions {
+  /// Seeds `Math.random()` and the random values of the Web Crypto API.
+  pub seed: u64,
+  /// The time reported by `Date` when the worker starts.
+  pub start_time: SystemTime,
+  pub clock: DeterministicClock,
+  /// Whether APIs that report on the host, such as `Deno.memoryUsage()` or
+  /// `Deno.hostname()`, throw instead of returning values that differ
+  /// between runs.
+  pub deny_nondeterministic_apis: bool,
+}
+
+impl Default for DeterminismOptions {
+  fn default() -> Self {
+    Self {
+      seed: 0,
+      start_time: SystemTime::UNIX_EPOCH,
+      clock: DeterministicClock::default(),
+      deny_nondeterministic_apis: true,
+    }
+  }
+}
+
+struct DeterministicRng(StdRng);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeterminismConfig {
+  start_time: f64,
+  virtual_timers: bool,
+  paused: bool,
+  deny_nondeterministic_apis: bool,
+}
+
+/// Creates the extension that has to be registered on the worker for
+/// `js/40_determinism.js`.
+pub fn create_determinism_extension(options: DeterminismOptions) -> Extension {
+  deno_determinism::init_ops(options)
+}
+
+#[op2]
+#[serde]
+fn op_determinism_config(state: &mut OpState) -> DeterminismConfig {
+  let options = state.borrow::<DeterminismOptions>();
+  let start_time = options
+    .start_time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap_or_default();
+  DeterminismConfig {
+    start_time: start_time.as_millis() as f64,
+    virtual_timers: options.clock != DeterministicClock::Frozen,
+    paused: options.clock == DeterministicClock::Manual,
+    deny_nondeterministic_apis: options.deny_nondeterministic_apis,
+  }
+}
+
+#[op2(fast)]
+fn op_determinism_random(state: &mut OpState) -> f64 {
+  state.borrow_mut::<DeterministicRng>().0.gen::<f64>()
+}
@@ -2,10 +2,26 @@
 
 use crate::cache::EmitCache;
 use crate::cache::FastInsecureHasher;
+use crate::cache::ModuleCache;
+use crate::cache::ModuleCacheKey;
 use crate::cache::ParsedSourceCache;
 use crate::resolver::CjsTracker;
 
+use deno_ast::swc::ast::Decl;
+use deno_ast::swc::ast::ExportDecl;
+use deno_ast::swc::ast::ModuleDecl;
+use deno_ast::swc::ast::ModuleItem;
+use deno_ast::swc::ast::Stmt;
+use deno_ast::swc::ast::TsEnumDecl;
+use deno_ast::swc::ast::TsExportAssignment;
+use deno_ast::swc::ast::TsImportEqualsDecl;
+use deno_ast::swc::ast::TsModuleDecl;
+use deno_ast::swc::ast::TsNamespaceBody;
+use deno_ast::swc::ast::TsParamProp;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
 use deno_ast::ModuleKind;
+use deno_ast::ParsedSource;
 use deno_ast::SourceMapOption;
 use deno_ast::SourceRange;
 use deno_ast::SourceRanged;
@@ -17,18 +33,71 @@ use deno_core::futures::stream::FuturesUnordered;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::ModuleSpecifier;
+use deno_graph::DefaultEsParser;
+use deno_graph::EsParser;
 use deno_graph::MediaType;
 use deno_graph::Module;
 use deno_graph::ModuleGraph;
+use deno_graph::ParseOptions;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+/// How the modules of a media type are transpiled to JavaScript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TranspileStrategy {
+  /// Transforms every TypeScript construct, eg. enums and namespaces.
+  #[default]
+  Transform,
+  /// Only erases the types and keeps the rest of the module as written.
+  /// Errors for TypeScript syntax with runtime semantics, such as enums or
+  /// parameter properties. Imports are kept unless they're marked with
+  /// `type`, like with `verbatimModuleSyntax`.
+  StripOnly,
+}
+
+/// A transform applied to modules before they're transpiled, eg. to process
+/// decorators.
+pub trait TransformPass: std::fmt::Debug + Send + Sync {
+  /// Identifies the output of the pass in the keys of the emit caches, so it
+  /// must change whenever the pass transforms modules differently.
+  fn cache_key(&self) -> &str;
+
+  /// Returns the new source of the module, or `None` to keep it.
+  fn transform(
+    &self,
+    parsed_source: &ParsedSource,
+  ) -> Result<Option<String>, AnyError>;
+}
+
+/// How modules are transpiled, in addition to the compiler options.
+#[derive(Debug, Clone, Default)]
+pub struct TranspileConfig {
+  /// The strategy for the modules of a media type, defaults to
+  /// [`TranspileStrategy::Transform`].
+  pub strategies: BTreeMap<MediaType, TranspileStrategy>,
+  /// Passes applied in order to every module before it's transpiled.
+  pub passes: Vec<Arc<dyn TransformPass>>,
+}
+
+impl TranspileConfig {
+  pub fn strategy(&self, media_type: MediaType) -> TranspileStrategy {
+    self
+      .strategies
+      .get(&media_type)
+      .copied()
+      .unwrap_or_default()
+  }
+}
+
 #[derive(Debug)]
 pub struct Emitter {
   cjs_tracker: Arc<CjsTracker>,
   emit_cache: Arc<EmitCache>,
+  module_cache: Option<Arc<ModuleCache>>,
   parsed_source_cache: Arc<ParsedSourceCache>,
   transpile_and_emit_options:
     Arc<(deno_ast::TranspileOptions, deno_ast::EmitOptions)>,
+  transpile_config: Arc<TranspileConfig>,
   // cached hash of the transpile and emit options
   transpile_and_emit_options_hash: u64,
 }
@@ -37,21 +106,28 @@ impl Emitter {
   pub fn new(
     cjs_tracker: Arc<CjsTracker>,
     emit_cache: Arc<EmitCache>,
+    module_cache: Option<Arc<ModuleCache>>,
     parsed_source_cache: Arc<ParsedSourceCache>,
     transpile_options: deno_ast::TranspileOptions,
     emit_options: deno_ast::EmitOptions,
+    transpile_config: TranspileConfig,
   ) -> Self {
     let transpile_and_emit_options_hash = {
       let mut hasher = FastInsecureHasher::new_without_deno_version();
       hasher.write_hashable(&transpile_options);
       hasher.write_hashable(&emit_options);
+      for pass in &transpile_config.passes {
+        hasher.write_str(pass.cache_key());
+      }
       hasher.finish()
     };
     Self {
       cjs_tracker,
       emit_cache,
+      module_cache,
       parsed_source_cache,
       transpile_and_emit_options: Arc::new((transpile_options, emit_options)),
+      transpile_config: Arc::new(transpile_config),
       transpile_and_emit_options_hash,
     }
   }
@@ -97,11 +173,14 @@ impl Emitter {
   pub fn maybe_cached_emit(
     &self,
     specifier: &ModuleSpecifier,
+    media_type: MediaType,
     module_kind: deno_ast::ModuleKind,
     source: &str,
   ) -> Option<String> {
-    let source_hash = self.get_source_hash(module_kind, source);
-    self.emit_cache.get_emit_code(specifier, source_hash)
+    let source_hash = self.get_source_hash(media_type, module_kind, source);
+    let module_cache_key =
+      self.module_cache_key(specifier, media_type, module_kind, source);
+    self.get_cached_emit(specifier, source_hash, module_cache_key.as_ref())
   }
 
   pub async fn emit_parsed_source(
@@ -113,12 +192,21 @@ impl Emitter {
   ) -> Result<String, AnyError> {
     // Note: keep this in sync with the sync version below
     let helper = EmitParsedSourceHelper(self);
-    match helper.pre_emit_parsed_source(specifier, module_kind, source) {
+    match helper.pre_emit_parsed_source(
+      specifier,
+      media_type,
+      module_kind,
+      source,
+    ) {
       PreEmitResult::Cached(emitted_text) => Ok(emitted_text),
-      PreEmitResult::NotCached { source_hash } => {
+      PreEmitResult::NotCached {
+        source_hash,
+        module_cache_key,
+      } => {
         let parsed_source_cache = self.parsed_source_cache.clone();
         let transpile_and_emit_options =
           self.transpile_and_emit_options.clone();
+        let transpile_config = self.transpile_config.clone();
         let transpiled_source = deno_core::unsync::spawn_blocking({
           let specifier = specifier.clone();
           let source = source.clone();
@@ -131,6 +219,7 @@ impl Emitter {
               source.clone(),
               &transpile_and_emit_options.0,
               &transpile_and_emit_options.1,
+              &transpile_config,
             )
           }
         })
@@ -140,6 +229,7 @@ impl Emitter {
           specifier,
           &transpiled_source,
           source_hash,
+          module_cache_key.as_ref(),
         );
         Ok(transpiled_source)
       }
@@ -155,9 +245,17 @@ impl Emitter {
   ) -> Result<String, AnyError> {
     // Note: keep this in sync with the async version above
     let helper = EmitParsedSourceHelper(self);
-    match helper.pre_emit_parsed_source(specifier, module_kind, source) {
+    match helper.pre_emit_parsed_source(
+      specifier,
+      media_type,
+      module_kind,
+      source,
+    ) {
       PreEmitResult::Cached(emitted_text) => Ok(emitted_text),
-      PreEmitResult::NotCached { source_hash } => {
+      PreEmitResult::NotCached {
+        source_hash,
+        module_cache_key,
+      } => {
         let transpiled_source = EmitParsedSourceHelper::transpile(
           &self.parsed_source_cache,
           specifier,
@@ -166,11 +264,13 @@ impl Emitter {
           source.clone(),
           &self.transpile_and_emit_options.0,
           &self.transpile_and_emit_options.1,
+          &self.transpile_config,
         )?;
         helper.post_emit_parsed_source(
           specifier,
           &transpiled_source,
           source_hash,
+          module_cache_key.as_ref(),
         );
         Ok(transpiled_source)
       }
@@ -182,11 +282,20 @@ impl Emitter {
     &self,
     specifier: &ModuleSpecifier,
   ) -> Result<String, AnyError> {
-    let media_type = MediaType::from_specifier(specifier);
     let source_code = tokio::fs::read_to_string(
       ModuleSpecifier::to_file_path(specifier).unwrap(),
     )
     .await?;
+    self.emit_for_hmr(specifier, source_code)
+  }
+
+  /// Emits `source_code` as the new source of the module at `specifier`.
+  pub fn emit_for_hmr(
+    &self,
+    specifier: &ModuleSpecifier,
+    source_code: String,
+  ) -> Result<String, AnyError> {
+    let media_type = MediaType::from_specifier(specifier);
     match media_type {
       MediaType::TypeScript
       | MediaType::Mts
@@ -207,15 +316,16 @@ impl Emitter {
           media_type,
           parsed_source.compute_is_script(),
         )?;
-        let transpiled_source = parsed_source
-          .transpile(
-            &self.transpile_and_emit_options.0,
-            &deno_ast::TranspileModuleOptions {
-              module_kind: Some(ModuleKind::from_is_cjs(is_cjs)),
-            },
-            &options,
-          )?
-          .into_source();
+        let transpiled_source = transpile_with_config(
+          parsed_source,
+          &self.transpile_config,
+          &self.transpile_and_emit_options.0,
+          &deno_ast::TranspileModuleOptions {
+            module_kind: Some(ModuleKind::from_is_cjs(is_cjs)),
+          },
+          &options,
+        )?
+        .into_source();
         Ok(transpiled_source.text)
       }
       MediaType::JavaScript
@@ -236,21 +346,80 @@ impl Emitter {
     }
   }
 
+  /// Looks up the emit in the module cache first, as it's shared by modules
+  /// with the same source, then in the emit cache of `specifier`.
+  fn get_cached_emit(
+    &self,
+    specifier: &ModuleSpecifier,
+    source_hash: u64,
+    module_cache_key: Option<&ModuleCacheKey>,
+  ) -> Option<String> {
+    let (Some(module_cache), Some(key)) =
+      (&self.module_cache, module_cache_key)
+    else {
+      return self.emit_cache.get_emit_code(specifier, source_hash);
+    };
+    if let Some(emit_code) = module_cache.get_emit(key) {
+      return Some(emit_code);
+    }
+    let emit_code = self.emit_cache.get_emit_code(specifier, source_hash)?;
+    module_cache.set_emit(key, &emit_code);
+    Some(emit_code)
+  }
+
+  /// The key of the emit in the module cache, when there's one.
+  fn module_cache_key(
+    &self,
+    specifier: &ModuleSpecifier,
+    media_type: MediaType,
+    module_kind: ModuleKind,
+    source_text: &str,
+  ) -> Option<ModuleCacheKey> {
+    self.module_cache.as_ref()?;
+    let (transpile_options, emit_options) = &*self.transpile_and_emit_options;
+    let passes = self
+      .transpile_config
+      .passes
+      .iter()
+      .map(|pass| pass.cache_key())
+      .collect::<Vec<_>>();
+    Some(ModuleCacheKey::new(
+      specifier,
+      source_text.as_bytes(),
+      (
+        transpile_options,
+        emit_options,
+        passes,
+        module_kind,
+        self.transpile_config.strategy(media_type),
+      ),
+    ))
+  }
+
   /// A hashing function that takes the source code and uses the global emit
   /// options then generates a string hash which can be stored to
   /// determine if the cached emit is valid or not.
-  fn get_source_hash(&self, module_kind: ModuleKind, source_text: &str) -> u64 {
+  fn get_source_hash(
+    &self,
+    media_type: MediaType,
+    module_kind: ModuleKind,
+    source_text: &str,
+  ) -> u64 {
     FastInsecureHasher::new_without_deno_version() // stored in the transpile_and_emit_options_hash
       .write_str(source_text)
       .write_u64(self.transpile_and_emit_options_hash)
       .write_hashable(module_kind)
+      .write_hashable(self.transpile_config.strategy(media_type))
       .finish()
   }
 }
 
 enum PreEmitResult {
   Cached(String),
-  NotCached { source_hash: u64 },
+  NotCached {
+    source_hash: u64,
+    module_cache_key: Option<ModuleCacheKey>,
+  },
 }
 
 /// Helper to share code between async and sync emit_parsed_source methods.
@@ -260,20 +429,31 @@ impl<'a> EmitParsedSourceHelper<'a> {
   pub fn pre_emit_parsed_source(
     &self,
     specifier: &ModuleSpecifier,
+    media_type: MediaType,
     module_kind: deno_ast::ModuleKind,
     source: &Arc<str>,
   ) -> PreEmitResult {
-    let source_hash = self.0.get_source_hash(module_kind, source);
+    let source_hash = self.0.get_source_hash(media_type, module_kind, source);
+    let module_cache_key =
+      self
+        .0
+        .module_cache_key(specifier, media_type, module_kind, source);
 
     if let Some(emit_code) =
-      self.0.emit_cache.get_emit_code(specifier, source_hash)
+      self
+        .0
+        .get_cached_emit(specifier, source_hash, module_cache_key.as_ref())
     {
       PreEmitResult::Cached(emit_code)
     } else {
-      PreEmitResult::NotCached { source_hash }
+      PreEmitResult::NotCached {
+        source_hash,
+        module_cache_key,
+      }
     }
   }
 
+  #[allow(clippy::too_many_arguments)]
   pub fn transpile(
     parsed_source_cache: &ParsedSourceCache,
     specifier: &ModuleSpecifier,
@@ -282,13 +462,16 @@ impl<'a> EmitParsedSourceHelper<'a> {
     source: Arc<str>,
     transpile_options: &deno_ast::TranspileOptions,
     emit_options: &deno_ast::EmitOptions,
+    transpile_config: &TranspileConfig,
   ) -> Result<String, AnyError> {
     // nothing else needs the parsed source at this point, so remove from
     // the cache in order to not transpile owned
     let parsed_source = parsed_source_cache
       .remove_or_parse_module(specifier, source, media_type)?;
     ensure_no_import_assertion(&parsed_source)?;
-    let transpile_result = parsed_source.transpile(
+    let transpile_result = transpile_with_config(
+      parsed_source,
+      transpile_config,
       transpile_options,
       &TranspileModuleOptions {
         module_kind: Some(module_kind),
@@ -311,12 +494,152 @@ impl<'a> EmitParsedSourceHelper<'a> {
     specifier: &ModuleSpecifier,
     transpiled_source: &str,
     source_hash: u64,
+    module_cache_key: Option<&ModuleCacheKey>,
   ) {
     self.0.emit_cache.set_emit_code(
       specifier,
       source_hash,
       transpiled_source.as_bytes(),
     );
+    if let (Some(module_cache), Some(key)) =
+      (&self.0.module_cache, module_cache_key)
+    {
+      module_cache.set_emit(key, transpiled_source);
+    }
+  }
+}
+
+/// Applies the transform passes and the strategy for the media type of the
+/// module while transpiling it.
+fn transpile_with_config(
+  mut parsed_source: ParsedSource,
+  transpile_config: &TranspileConfig,
+  transpile_options: &deno_ast::TranspileOptions,
+  transpile_module_options: &TranspileModuleOptions,
+  emit_options: &deno_ast::EmitOptions,
+) -> Result<TranspileResult, AnyError> {
+  for pass in &transpile_config.passes {
+    if let Some(source) = pass.transform(&parsed_source)? {
+      let transformed = DefaultEsParser.parse_program(ParseOptions {
+        specifier: parsed_source.specifier(),
+        source: source.into(),
+        media_type: parsed_source.media_type(),
+        scope_analysis: false,
+      })?;
+      parsed_source = transformed;
+    }
+  }
+  let transpile_result =
+    match transpile_config.strategy(parsed_source.media_type()) {
+      TranspileStrategy::Transform => parsed_source.transpile(
+        transpile_options,
+        transpile_module_options,
+        emit_options,
+      )?,
+      TranspileStrategy::StripOnly => {
+        ensure_erasable_syntax(&parsed_source)?;
+        parsed_source.transpile(
+          &deno_ast::TranspileOptions {
+            verbatim_module_syntax: true,
+            ..transpile_options.clone()
+          },
+          transpile_module_options,
+          emit_options,
+        )?
+      }
+    };
+  Ok(transpile_result)
+}
+
+/// Errors for TypeScript syntax that can't be erased, because it has runtime
+/// semantics.
+fn ensure_erasable_syntax(
+  parsed_source: &ParsedSource,
+) -> Result<(), AnyError> {
+  let mut collector = NonErasableSyntaxCollector::default();
+  match parsed_source.program_ref() {
+    deno_ast::ProgramRef::Module(module) => module.visit_with(&mut collector),
+    deno_ast::ProgramRef::Script(script) => script.visit_with(&mut collector),
+  }
+  let Some((kind, range)) = collector.first else {
+    return Ok(());
+  };
+  let loc = parsed_source
+    .text_info_lazy()
+    .line_and_column_display(range.start);
+  Err(deno_core::anyhow::anyhow!(
+    "{} is not supported when only stripping types.\n\n  at {}:{}:{}",
+    kind,
+    parsed_source.specifier(),
+    loc.line_number,
+    loc.column_number,
+  ))
+}
+
+#[derive(Default)]
+struct NonErasableSyntaxCollector {
+  first: Option<(&'static str, SourceRange)>,
+}
+
+impl NonErasableSyntaxCollector {
+  fn found(&mut self, kind: &'static str, range: SourceRange) {
+    if self.first.is_none() {
+      self.first = Some((kind, range));
+    }
+  }
+}
+
+impl Visit for NonErasableSyntaxCollector {
+  fn visit_ts_enum_decl(&mut self, n: &TsEnumDecl) {
+    if !n.declare {
+      self.found("An enum declaration", n.range());
+    }
+  }
+
+  fn visit_ts_module_decl(&mut self, n: &TsModuleDecl) {
+    // ambient declarations are erased as a whole, so skip their bodies
+    if !n.declare && !n.body.as_ref().is_none_or(is_type_only_namespace) {
+      self.found("A namespace with values", n.range());
+    }
+  }
+
+  fn visit_ts_param_prop(&mut self, n: &TsParamProp) {
+    self.found("A parameter property", n.range());
+  }
+
+  fn visit_ts_import_equals_decl(&mut self, n: &TsImportEqualsDecl) {
+    if !n.is_type_only {
+      self.found("An `import =` declaration", n.range());
+    }
+  }
+
+  fn visit_ts_export_assignment(&mut self, n: &TsExportAssignment) {
+    self.found("An `export =` assignment", n.range());
+  }
+}
+
+fn is_type_only_namespace(body: &TsNamespaceBody) -> bool {
+  match body {
+    TsNamespaceBody::TsModuleBlock(block) => {
+      block.body.iter().all(|item| match item {
+        ModuleItem::Stmt(Stmt::Decl(decl))
+        | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+          decl,
+          ..
+        })) => match decl {
+          Decl::TsInterface(_) | Decl::TsTypeAlias(_) => true,
+          Decl::TsModule(module) => {
+            module.declare
+              || module.body.as_ref().is_none_or(is_type_only_namespace)
+          }
+          _ => false,
+        },
+        _ => false,
+      })
+    }
+    TsNamespaceBody::TsNamespaceDecl(decl) => {
+      decl.declare || is_type_only_namespace(&decl.body)
+    }
   }
 }
 
@@ -389,3 +712,47 @@ fn ensure_no_import_assertion(
 
   Ok(())
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn parse(source: &str) -> ParsedSource {
+    DefaultEsParser
+      .parse_program(ParseOptions {
+        specifier: &ModuleSpecifier::parse("file:///mod.ts").unwrap(),
+        source: source.into(),
+        media_type: MediaType::TypeScript,
+        scope_analysis: false,
+      })
+      .unwrap()
+  }
+
+  #[test]
+  fn test_ensure_erasable_syntax() {
+    for source in [
+      "const a: number = 1;",
+      "declare enum A { B }",
+      "namespace A { export type B = string; }",
+      "declare namespace A { const b: string; }",
+      "import type A = require('a');",
+    ] {
+      assert!(ensure_erasable_syntax(&parse(source)).is_ok(), "{source}");
+    }
+    for (source, message) in [
+      ("enum A { B }", "An enum declaration"),
+      (
+        "namespace A { export const b = 1; }",
+        "A namespace with values",
+      ),
+      (
+        "class A { constructor(private b: string) {} }",
+        "A parameter property",
+      ),
+      ("import A = require('a');", "An `import =` declaration"),
+    ] {
+      let err = ensure_erasable_syntax(&parse(source)).unwrap_err();
+      assert!(err.to_string().starts_with(message), "{source}");
+    }
+  }
+}
@@ -9,15 +9,26 @@
 //!   Diagnostics are compile-time type errors, whereas JsErrors are runtime
 //!   exceptions.
 
+use std::sync::Arc;
+use std::sync::RwLock;
+
 use deno_ast::ParseDiagnostic;
 use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::ModuleSpecifier;
 use deno_graph::source::ResolveError;
 use deno_graph::ModuleError;
 use deno_graph::ModuleGraphError;
 use deno_graph::ModuleLoadError;
 use deno_graph::ResolutionError;
+use deno_terminal::colors;
 use import_map::ImportMapError;
 
+static ERROR_FORMATTER: RwLock<Option<Arc<dyn ErrorFormatter>>> =
+  RwLock::new(None);
+
 fn get_import_map_error_class(_: &ImportMapError) -> &'static str {
   "URIError"
 }
@@ -93,6 +104,20 @@ fn get_try_from_int_error_class(_: &std::num::TryFromIntError) -> &'static str {
   "TypeError"
 }
 
+/// A module or npm package that can't be loaded because it isn't cached and
+/// fetching is disabled, ie. `--cached-only`. Its class is "NotCached".
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CachedOnlyError {
+  #[error(
+    "Specifier not found in cache: \"{0}\", --cached-only is specified."
+  )]
+  Specifier(ModuleSpecifier),
+  #[error(
+    "An npm specifier not found in cache: \"{0}\", --cached-only is specified."
+  )]
+  NpmPackage(String),
+}
+
 pub fn get_error_class_name(e: &AnyError) -> &'static str {
   deno_runtime::errors::get_error_class_name(e)
     .or_else(|| {
@@ -115,5 +140,120 @@ pub fn get_error_class_name(e: &AnyError) -> &'static str {
       e.downcast_ref::<std::num::TryFromIntError>()
         .map(get_try_from_int_error_class)
     })
+    .or_else(|| e.downcast_ref::<CachedOnlyError>().map(|_| "NotCached"))
     .unwrap_or("Error")
 }
+
+/// Formats the errors the CLI prints, ie. uncaught exceptions of `deno run`,
+/// exceptions failing tests and the errors ending the process, eg. type
+/// checking errors of `deno check`.
+pub trait ErrorFormatter: Send + Sync {
+  fn format_js_error(&self, error: &JsError) -> String;
+
+  /// Formats an error ending the process that isn't an exception.
+  fn format_error(&self, error: &AnyError) -> String {
+    format!("{error:?}")
+  }
+}
+
+/// Formats the errors of the process with `formatter`, or in the default
+/// format again when `None`.
+pub fn set_error_formatter(formatter: Option<Arc<dyn ErrorFormatter>>) {
+  *ERROR_FORMATTER.write().unwrap() = formatter;
+}
+
+fn current_error_formatter() -> Option<Arc<dyn ErrorFormatter>> {
+  ERROR_FORMATTER.read().unwrap().clone()
+}
+
+/// Formats `error` with the formatter set with [`set_error_formatter`], or
+/// with `deno_runtime`'s formatter when none is set.
+/// Prints `message` prefixed with a single `error: ` and exits the process.
+pub fn exit_with_message(message: &str, code: i32) -> ! {
+  log::error!(
+    "{}: {}",
+    colors::red_bold("error"),
+    message.strip_prefix("error: ").unwrap_or(message)
+  );
+  deno_runtime::exit(code);
+}
+
+pub fn format_js_error(error: &JsError) -> String {
+  match current_error_formatter() {
+    Some(formatter) => formatter.format_js_error(error),
+    None => deno_runtime::fmt_errors::format_js_error(error),
+  }
+}
+
+/// Formats an error ending the process with the formatter set with
+/// [`set_error_formatter`]. Returns `None` when none is set, as the caller
+/// decides on the default format.
+pub fn format_error_with_formatter(error: &AnyError) -> Option<String> {
+  let formatter = current_error_formatter()?;
+  Some(match error.downcast_ref::<JsError>() {
+    Some(js_error) => formatter.format_js_error(js_error),
+    None => formatter.format_error(error),
+  })
+}
+
+/// Formats errors as JSON objects on a single line, used for
+/// `--error-format=json`.
+#[derive(Debug, Default)]
+pub struct JsonErrorFormatter;
+
+impl ErrorFormatter for JsonErrorFormatter {
+  fn format_js_error(&self, error: &JsError) -> String {
+    js_error_to_json(error).to_string()
+  }
+
+  fn format_error(&self, error: &AnyError) -> String {
+    let message = format!("{error:#}");
+    json!({
+      "type": "error",
+      "class": get_error_class_name(error),
+      "message": console_static_text::ansi::strip_ansi_codes(&message),
+    })
+    .to_string()
+  }
+}
+
+fn js_error_to_json(error: &JsError) -> serde_json::Value {
+  let frames = error
+    .frames
+    .iter()
+    .map(|frame| {
+      json!({
+        "functionName": frame.function_name,
+        "fileName": frame.file_name,
+        "lineNumber": frame.line_number,
+        "columnNumber": frame.column_number,
+      })
+    })
+    .collect::<Vec<_>>();
+  json!({
+    "type": "jsError",
+    "name": error.name,
+    "message": error.message,
+    "frames": frames,
+    "cause": error.cause.as_deref().map(js_error_to_json),
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use deno_core::anyhow::anyhow;
+
+  use super::*;
+
+  #[test]
+  fn json_error_formatter() {
+    let value: serde_json::Value = serde_json::from_str(
+      &JsonErrorFormatter.format_error(&anyhow!("\x1b[31mfailed\x1b[0m")),
+    )
+    .unwrap();
+    assert_eq!(
+      value,
+      json!({ "type": "error", "class": "Error", "message": "failed" })
+    );
+  }
+}
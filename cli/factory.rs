@@ -6,9 +6,12 @@ use crate::args::CaData;
 use crate::args::CliOptions;
 use crate::args::DenoSubcommand;
 use crate::args::Flags;
+use crate::args::LifecycleScriptsConfig;
 use crate::args::NpmInstallDepsProvider;
+use crate::args::NpmRegistriesConfig;
 use crate::args::StorageKeyResolver;
 use crate::args::TsConfigType;
+use crate::auth_tokens::TokenProvider;
 use crate::cache::Caches;
 use crate::cache::CodeCache;
 use crate::cache::DenoCacheEnvFsAdapter;
@@ -18,16 +21,29 @@ use crate::cache::EmitCache;
 use crate::cache::GlobalHttpCache;
 use crate::cache::HttpCache;
 use crate::cache::LocalHttpCache;
+use crate::cache::ModuleCache;
 use crate::cache::ModuleInfoCache;
 use crate::cache::NodeAnalysisCache;
 use crate::cache::ParsedSourceCache;
+use crate::determinism::DeterminismOptions;
 use crate::emit::Emitter;
+use crate::emit::TranspileConfig;
 use crate::file_fetcher::FileFetcher;
 use crate::graph_container::MainModuleGraphContainer;
+use crate::graph_util::graph_valid;
 use crate::graph_util::FileWatcherReporter;
+use crate::graph_util::GraphValidOptions;
 use crate::graph_util::ModuleGraphBuilder;
 use crate::graph_util::ModuleGraphCreator;
+use crate::host_fn::HostFn;
 use crate::http_util::HttpClientProvider;
+use crate::http_util::HttpNetworkOptions;
+use crate::http_util::RetryOptions;
+use crate::import_policy::ImportPolicy;
+use crate::inspector::InspectMode;
+use crate::inspector::InspectorController;
+use crate::integrity::IntegrityChecker;
+use crate::integrity::IntegrityOptions;
 use crate::module_loader::CliModuleLoaderFactory;
 use crate::module_loader::ModuleLoadPreparer;
 use crate::node::CliCjsCodeAnalyzer;
@@ -41,6 +57,10 @@ use crate::npm::CliNpmResolver;
 use crate::npm::CliNpmResolverCreateOptions;
 use crate::npm::CliNpmResolverManagedSnapshotOption;
 use crate::npm::CreateInNpmPkgCheckerOptions;
+use crate::npm::LifecycleScriptsPolicy;
+use crate::npm::ManagedNodeModulesPackages;
+use crate::npm::NpmStore;
+use crate::npm::VirtualNodeModulesFs;
 use crate::resolver::CjsTracker;
 use crate::resolver::CliDenoResolver;
 use crate::resolver::CliDenoResolverFs;
@@ -48,20 +68,33 @@ use crate::resolver::CliNpmReqResolver;
 use crate::resolver::CliResolver;
 use crate::resolver::CliResolverOptions;
 use crate::resolver::CliSloppyImportsResolver;
+use crate::resolver::HostModuleResolver;
+use crate::resolver::HostModuleResolverOptions;
 use crate::resolver::NpmModuleLoader;
 use crate::resolver::SloppyImportsCachedFs;
 use crate::standalone::DenoCompileBinaryWriter;
 use crate::tools::check::TypeChecker;
 use crate::tools::coverage::CoverageCollector;
 use crate::tools::lint::LintRuleProvider;
+use crate::tools::run::hmr::EmbedderHmrRunner;
+use crate::tools::run::hmr::HmrController;
 use crate::tools::run::hmr::HmrRunner;
+use crate::tsc::TscDaemon;
 use crate::tsc::TypeCheckingCjsTracker;
 use crate::util::file_watcher::WatcherCommunicator;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
+use crate::util::progress_bar::ProgressReporter;
+use crate::wasi::create_wasi_extension;
+use crate::wasi::WasiComponents;
+use crate::wasi::WasiOptions;
 use crate::worker::CliMainWorkerFactory;
 use crate::worker::CliMainWorkerOptions;
+use crate::worker::CreateWasiExtensionCb;
+use crate::worker::ExecutionLimits;
+use crate::worker::WebWorkerExtensionsFactory;
+use crate::worker::WorkerObserver;
 use std::path::PathBuf;
 
 use deno_cache_dir::npm::NpmCacheDir;
@@ -69,22 +102,37 @@ use deno_config::workspace::PackageJsonDepResolution;
 use deno_config::workspace::WorkspaceResolver;
 use deno_core::error::AnyError;
 use deno_core::futures::FutureExt;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
 use deno_core::FeatureChecker;
+use deno_core::ModuleSpecifier;
+use deno_graph::GraphKind;
+use deno_graph::ModuleGraph;
 
 use deno_resolver::cjs::IsCjsResolutionMode;
 use deno_resolver::npm::NpmReqResolverOptions;
 use deno_resolver::DenoResolverOptions;
 use deno_resolver::NodeAndNpmReqResolver;
+use deno_runtime::deno_broadcast_channel::InMemoryBroadcastChannel;
+use deno_runtime::deno_fetch::FetchInterceptor;
+use deno_runtime::deno_ffi::DlopenInterceptor;
 use deno_runtime::deno_fs;
+use deno_runtime::deno_kv::dynamic::DynamicDbHandler;
+use deno_runtime::deno_net::ConnectInterceptor;
 use deno_runtime::deno_node::DenoFsNodeResolverEnv;
 use deno_runtime::deno_node::NodeResolver;
 use deno_runtime::deno_node::PackageJsonResolver;
 use deno_runtime::deno_permissions::Permissions;
 use deno_runtime::deno_permissions::PermissionsContainer;
+use deno_runtime::deno_permissions::ResourceQuotas;
 use deno_runtime::deno_tls::rustls::RootCertStore;
 use deno_runtime::deno_tls::RootCertStoreProvider;
 use deno_runtime::deno_web::BlobStore;
 use deno_runtime::inspector_server::InspectorServer;
+use deno_runtime::ops::os::VirtualEnv;
+use deno_runtime::ops::process::SpawnInterceptor;
+use deno_runtime::ops::signal::SignalForwarder;
+use deno_runtime::ops::worker_host::WorkerCreationPolicy;
 use deno_runtime::permissions::RuntimePermissionDescriptorParser;
 use log::warn;
 use node_resolver::analyze::NodeCodeTranslator;
@@ -187,9 +235,11 @@ struct CliFactoryServices {
   feature_checker: Deferred<Arc<FeatureChecker>>,
   file_fetcher: Deferred<Arc<FileFetcher>>,
   fs: Deferred<Arc<dyn deno_fs::FileSystem>>,
+  virtual_node_modules_fs: Deferred<Option<Arc<VirtualNodeModulesFs>>>,
   global_http_cache: Deferred<Arc<GlobalHttpCache>>,
   http_cache: Deferred<Arc<dyn HttpCache>>,
   http_client_provider: Deferred<Arc<HttpClientProvider>>,
+  import_policy: Deferred<Option<Arc<ImportPolicy>>>,
   in_npm_pkg_checker: Deferred<Arc<dyn InNpmPackageChecker>>,
   main_graph_container: Deferred<Arc<MainModuleGraphContainer>>,
   maybe_file_watcher_reporter: Deferred<Option<FileWatcherReporter>>,
@@ -212,12 +262,114 @@ struct CliFactoryServices {
   sloppy_imports_resolver: Deferred<Option<Arc<CliSloppyImportsResolver>>>,
   text_only_progress_bar: Deferred<ProgressBar>,
   type_checker: Deferred<Arc<TypeChecker>>,
+  wasi_components: Deferred<Option<Arc<WasiComponents>>>,
   workspace_resolver: Deferred<Arc<WorkspaceResolver>>,
 }
 
+/// Options only available when running the CLI as a library.
+#[derive(Default)]
+pub struct EmbedderOptions {
+  /// Startup snapshot created with `crate::js::create_snapshot` to use
+  /// instead of the CLI's own snapshot for the main worker.
+  pub startup_snapshot: Option<&'static [u8]>,
+  /// Temporary directory to use as `DENO_DIR` instead of the global one, so
+  /// that nothing is cached across runs. It is deleted once the last worker
+  /// created by the factory is dropped.
+  pub ephemeral_deno_dir: Option<Arc<tempfile::TempDir>>,
+  /// Resolves specifiers before the default resolver.
+  pub host_module_resolver: Option<Arc<dyn HostModuleResolver>>,
+  /// Restricts where modules may be imported from, instead of the
+  /// `importPolicy` of the `deno.json`.
+  pub import_policy: Option<ImportPolicy>,
+  /// Verifies the sources of modules before they are loaded.
+  pub integrity: Option<IntegrityOptions>,
+  /// Supplies the auth tokens of remote modules, JSR and npm requests,
+  /// instead of `DENO_AUTH_TOKENS`.
+  pub token_provider: Option<Arc<dyn TokenProvider>>,
+  /// Proxies, CA certificates and client certificate of the requests of
+  /// remote modules, JSR and npm packages.
+  pub network_options: Option<HttpNetworkOptions>,
+  /// Retries and mirrors of the requests of remote modules and npm
+  /// packages.
+  pub retry_options: Option<RetryOptions>,
+  /// Receives the progress of module and npm package downloads, instead of
+  /// the progress bars of the terminal.
+  pub progress_reporter: Option<Arc<dyn ProgressReporter>>,
+  /// Links the packages of `node_modules` directories from a shared store
+  /// instead of copying them from the npm cache.
+  pub npm_store: Option<Arc<NpmStore>>,
+  /// Decides whether the lifecycle scripts of npm packages run, instead of
+  /// the `--allow-scripts` flag.
+  pub lifecycle_scripts_policy: Option<Arc<dyn LifecycleScriptsPolicy>>,
+  /// Serves the `node_modules` directory of the project from the global npm
+  /// cache, see [`VirtualNodeModulesFs`].
+  pub virtual_node_modules: bool,
+  /// Notified about the lifecycle of main workers.
+  pub worker_observer: Option<Arc<dyn WorkerObserver>>,
+  /// Limits enforced on every main worker created by the factory.
+  pub execution_limits: Option<ExecutionLimits>,
+  /// File system backing the `Deno` and `node:fs` file APIs of workers.
+  /// Module loading and npm resolution keep using the real file system.
+  pub file_system: Option<Arc<dyn deno_fs::FileSystem>>,
+  /// Sees the `fetch()` requests made by workers.
+  pub fetch_interceptor: Option<Arc<dyn FetchInterceptor>>,
+  /// Decides whether workers may open TCP and TLS connections.
+  pub connect_interceptor: Option<Arc<dyn ConnectInterceptor>>,
+  /// Decides whether workers may spawn subprocesses.
+  pub spawn_interceptor: Option<Arc<dyn SpawnInterceptor>>,
+  /// Decides which dynamic libraries workers may open.
+  pub dlopen_interceptor: Option<Arc<dyn DlopenInterceptor>>,
+  /// Opens the local `Deno.openKv()` databases of the workers.
+  pub kv_backend: Option<Arc<dyn DynamicDbHandler + Send + Sync>>,
+  /// The `BroadcastChannel` bus of the workers.
+  pub broadcast_channel: Option<InMemoryBroadcastChannel>,
+  /// Decides whether workers may create web workers.
+  pub worker_creation_policy: Option<Arc<dyn WorkerCreationPolicy>>,
+  /// Creates the extensions of every web worker.
+  pub web_worker_extensions: Option<WebWorkerExtensionsFactory>,
+  /// Replaces the process environment of the workers.
+  pub virtual_env: Option<VirtualEnv>,
+  /// Delivers signals to the signal listeners of the workers.
+  pub signal_forwarder: Option<SignalForwarder>,
+  /// Limits on the files, subprocesses and sockets of each worker.
+  pub resource_quotas: Option<ResourceQuotas>,
+  /// Hot replaces modules of the main worker with sources from the host.
+  pub hmr_controller: Option<HmrController>,
+  /// npm registries to use instead of the ones from `.npmrc` files.
+  pub npm_registries: Option<NpmRegistriesConfig>,
+  /// Directory the main workers write V8 coverage profiles to.
+  pub coverage_dir: Option<PathBuf>,
+  /// Opens inspector sessions with the main worker from the host.
+  pub inspector_controller: Option<InspectorController>,
+  /// Whether the main worker waits for a session of the inspector
+  /// controller before running.
+  pub inspect_mode: InspectMode,
+  /// Makes the main workers behave the same on every run.
+  pub determinism: Option<DeterminismOptions>,
+  /// Functions of the host exposed to the scripts of main workers.
+  pub host_fns: Vec<HostFn>,
+  /// Lets the host serve requests with the `fetch` handler of main workers.
+  pub serve_adapter: bool,
+  /// State of a paused worker that main workers resume from.
+  pub restored_state: Option<serde_json::Value>,
+  /// Counts the op calls of the main workers, so they're exported as
+  /// telemetry once the main module ran.
+  pub op_metrics: bool,
+  /// Content addressed cache of transpiled modules and their V8 code cache,
+  /// consulted before the specifier keyed caches of the `DENO_DIR`.
+  pub module_cache: Option<Arc<ModuleCache>>,
+  /// Transpile strategies and transform passes of the emitter.
+  pub transpile_config: Option<TranspileConfig>,
+  /// Runs the WASI components imported by main workers with this context,
+  /// see [`crate::wasi`].
+  pub wasi: Option<WasiOptions>,
+}
+
 pub struct CliFactory {
   watcher_communicator: Option<Arc<WatcherCommunicator>>,
   flags: Arc<Flags>,
+  embedder_options: Option<EmbedderOptions>,
+  tsc_daemon: Option<TscDaemon>,
   services: CliFactoryServices,
 }
 
@@ -226,6 +378,27 @@ impl CliFactory {
     Self {
       flags,
       watcher_communicator: None,
+      embedder_options: None,
+      tsc_daemon: None,
+      services: Default::default(),
+    }
+  }
+
+  /// Creates a factory for running the CLI as a library, which sets up the
+  /// `Deno.host` message channel on main workers.
+  pub fn from_flags_for_embedder(
+    mut flags: Arc<Flags>,
+    embedder_options: EmbedderOptions,
+  ) -> Self {
+    if let Some(deno_dir) = &embedder_options.ephemeral_deno_dir {
+      Arc::make_mut(&mut flags).internal.cache_path =
+        Some(deno_dir.path().to_path_buf());
+    }
+    Self {
+      flags,
+      watcher_communicator: None,
+      embedder_options: Some(embedder_options),
+      tsc_daemon: None,
       services: Default::default(),
     }
   }
@@ -235,6 +408,8 @@ impl CliFactory {
     CliFactory {
       watcher_communicator: None,
       flags,
+      embedder_options: None,
+      tsc_daemon: None,
       services: CliFactoryServices {
         cli_options: Deferred::from_value(cli_options),
         ..Default::default()
@@ -249,13 +424,30 @@ impl CliFactory {
     CliFactory {
       watcher_communicator: Some(watcher_communicator),
       flags,
+      embedder_options: None,
+      tsc_daemon: None,
       services: Default::default(),
     }
   }
 
+  /// Type checks on `tsc_daemon` instead of a new tsc isolate for every
+  /// check.
+  pub fn with_tsc_daemon(mut self, tsc_daemon: TscDaemon) -> Self {
+    self.tsc_daemon = Some(tsc_daemon);
+    self
+  }
+
   pub fn cli_options(&self) -> Result<&Arc<CliOptions>, AnyError> {
     self.services.cli_options.get_or_try_init(|| {
-      CliOptions::from_flags(self.flags.clone()).map(Arc::new)
+      let mut cli_options = CliOptions::from_flags(self.flags.clone())?;
+      if let Some(npm_registries) = self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.npm_registries.as_ref())
+      {
+        cli_options.set_npmrc(npm_registries.resolve()?);
+      }
+      Ok(Arc::new(cli_options))
     })
   }
 
@@ -309,10 +501,16 @@ impl CliFactory {
   }
 
   pub fn text_only_progress_bar(&self) -> &ProgressBar {
-    self
-      .services
-      .text_only_progress_bar
-      .get_or_init(|| ProgressBar::new(ProgressBarStyle::TextOnly))
+    self.services.text_only_progress_bar.get_or_init(|| {
+      match self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.progress_reporter.clone())
+      {
+        Some(reporter) => ProgressBar::with_reporter(reporter),
+        None => ProgressBar::new(ProgressBarStyle::TextOnly),
+      }
+    })
   }
 
   pub fn global_http_cache(&self) -> Result<&Arc<GlobalHttpCache>, AnyError> {
@@ -343,29 +541,135 @@ impl CliFactory {
 
   pub fn http_client_provider(&self) -> &Arc<HttpClientProvider> {
     self.services.http_client_provider.get_or_init(|| {
-      Arc::new(HttpClientProvider::new(
+      let mut http_client_provider = HttpClientProvider::new(
         Some(self.root_cert_store_provider().clone()),
         self.flags.unsafely_ignore_certificate_errors.clone(),
-      ))
+      );
+      if let Some(token_provider) = self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.token_provider.clone())
+      {
+        http_client_provider.set_token_provider(token_provider);
+      }
+      if let Some(network_options) = self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.network_options.clone())
+      {
+        http_client_provider.set_network_options(network_options);
+      }
+      if let Some(retry_options) = self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.retry_options.clone())
+      {
+        http_client_provider.set_retry_options(retry_options);
+      }
+      Arc::new(http_client_provider)
     })
   }
 
   pub fn file_fetcher(&self) -> Result<&Arc<FileFetcher>, AnyError> {
     self.services.file_fetcher.get_or_try_init(|| {
       let cli_options = self.cli_options()?;
-      Ok(Arc::new(FileFetcher::new(
+      let mut file_fetcher = FileFetcher::new(
         self.http_cache()?.clone(),
         cli_options.cache_setting(),
         !cli_options.no_remote(),
         self.http_client_provider().clone(),
         self.blob_store().clone(),
         Some(self.text_only_progress_bar().clone()),
-      )))
+      );
+      if let Some(import_policy) = self.import_policy()? {
+        file_fetcher.set_import_policy(import_policy.clone());
+      }
+      if let Some(integrity) = self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.integrity.clone())
+      {
+        file_fetcher.set_integrity_checker(Arc::new(IntegrityChecker::new(
+          integrity,
+          cli_options.maybe_lockfile().cloned(),
+        )));
+      }
+      Ok(Arc::new(file_fetcher))
+    })
+  }
+
+  /// The import policy of the embedder, or the `importPolicy` of the root
+  /// `deno.json`.
+  pub fn import_policy(&self) -> Result<&Option<Arc<ImportPolicy>>, AnyError> {
+    self.services.import_policy.get_or_try_init(|| {
+      if let Some(import_policy) = self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.import_policy.clone())
+      {
+        return Ok(Some(Arc::new(import_policy)));
+      }
+      let cli_options = self.cli_options()?;
+      let Some(config_file) = cli_options.workspace().root_deno_json() else {
+        return Ok(None);
+      };
+      let import_policy = ImportPolicy::from_config_file(
+        config_file,
+        &deno_config::fs::RealDenoConfigFs,
+      )?;
+      Ok(import_policy.map(Arc::new))
     })
   }
 
   pub fn fs(&self) -> &Arc<dyn deno_fs::FileSystem> {
-    self.services.fs.get_or_init(|| Arc::new(deno_fs::RealFs))
+    self
+      .services
+      .fs
+      .get_or_init(|| match self.virtual_node_modules_fs() {
+        Some(fs) => fs.clone(),
+        None => Arc::new(deno_fs::RealFs),
+      })
+  }
+
+  fn virtual_node_modules_fs(&self) -> Option<&Arc<VirtualNodeModulesFs>> {
+    self
+      .services
+      .virtual_node_modules_fs
+      .get_or_init(|| {
+        let enabled = self
+          .embedder_options
+          .as_ref()
+          .is_some_and(|options| options.virtual_node_modules);
+        enabled.then(Default::default)
+      })
+      .as_ref()
+  }
+
+  fn wasi_components(&self) -> Option<&Arc<WasiComponents>> {
+    self
+      .services
+      .wasi_components
+      .get_or_init(|| {
+        let options = self
+          .embedder_options
+          .as_ref()
+          .and_then(|options| options.wasi.clone())?;
+        Some(Arc::new(WasiComponents::new(
+          options,
+          self.module_cache().cloned(),
+        )))
+      })
+      .as_ref()
+  }
+
+  /// The file system exposed to JavaScript, which differs from
+  /// [`CliFactory::fs`] when an embedder provided its own.
+  pub fn worker_fs(&self) -> &Arc<dyn deno_fs::FileSystem> {
+    self
+      .embedder_options
+      .as_ref()
+      .and_then(|options| options.file_system.as_ref())
+      .unwrap_or_else(|| self.fs())
   }
 
   pub fn in_npm_pkg_checker(
@@ -412,7 +716,7 @@ impl CliFactory {
         async {
           let fs = self.fs();
           let cli_options = self.cli_options()?;
-          create_cli_npm_resolver(if cli_options.use_byonm() {
+          let resolver = create_cli_npm_resolver(if cli_options.use_byonm() {
             CliNpmResolverCreateOptions::Byonm(
               CliByonmNpmResolverCreateOptions {
                 fs: CliDenoResolverFs(fs.clone()),
@@ -466,11 +770,29 @@ impl CliFactory {
                 ),
                 npm_system_info: cli_options.npm_system_info(),
                 npmrc: cli_options.npmrc().clone(),
-                lifecycle_scripts: cli_options.lifecycle_scripts_config(),
+                lifecycle_scripts: LifecycleScriptsConfig {
+                  policy: self.embedder_options.as_ref().and_then(|options| {
+                    options.lifecycle_scripts_policy.clone()
+                  }),
+                  ..cli_options.lifecycle_scripts_config()
+                },
+                npm_store: self
+                  .embedder_options
+                  .as_ref()
+                  .and_then(|options| options.npm_store.clone()),
               },
             )
           })
-          .await
+          .await?;
+          if let Some(fs) = self.virtual_node_modules_fs() {
+            let root_dir_path = cli_options.workspace().root_dir_path();
+            fs.set_packages(
+              canonicalize_path_maybe_not_exists(&root_dir_path)?
+                .join("node_modules"),
+              Arc::new(ManagedNodeModulesPackages(Arc::downgrade(&resolver))),
+            );
+          }
+          Ok(resolver)
         }
         .boxed_local(),
       )
@@ -574,6 +896,18 @@ impl CliFactory {
             bare_node_builtins_enabled: cli_options
               .unstable_bare_node_builtins(),
             deno_resolver: self.deno_resolver().await?.clone(),
+            host_module_resolver: match self
+              .embedder_options
+              .as_ref()
+              .and_then(|options| options.host_module_resolver.clone())
+            {
+              Some(resolver) => Some(HostModuleResolverOptions {
+                resolver,
+                file_fetcher: self.file_fetcher()?.clone(),
+              }),
+              None => None,
+            },
+            import_policy: self.import_policy()?.clone(),
           })))
         }
         .boxed_local(),
@@ -609,10 +943,20 @@ impl CliFactory {
 
   pub fn code_cache(&self) -> Result<&Arc<CodeCache>, AnyError> {
     self.services.code_cache.get_or_try_init(|| {
-      Ok(Arc::new(CodeCache::new(self.caches()?.code_cache_db())))
+      Ok(Arc::new(CodeCache::new(
+        self.caches()?.code_cache_db(),
+        self.module_cache().cloned(),
+      )))
     })
   }
 
+  pub fn module_cache(&self) -> Option<&Arc<ModuleCache>> {
+    self
+      .embedder_options
+      .as_ref()
+      .and_then(|options| options.module_cache.as_ref())
+  }
+
   pub fn parsed_source_cache(&self) -> &Arc<ParsedSourceCache> {
     self
       .services
@@ -633,9 +977,15 @@ impl CliFactory {
       Ok(Arc::new(Emitter::new(
         self.cjs_tracker()?.clone(),
         self.emit_cache()?.clone(),
+        self.module_cache().cloned(),
         self.parsed_source_cache().clone(),
         transpile_options,
         emit_options,
+        self
+          .embedder_options
+          .as_ref()
+          .and_then(|options| options.transpile_config.clone())
+          .unwrap_or_default(),
       )))
     })
   }
@@ -746,6 +1096,7 @@ impl CliFactory {
           self.module_graph_builder().await?.clone(),
           self.node_resolver().await?.clone(),
           self.npm_resolver().await?.clone(),
+          self.tsc_daemon.clone(),
         )))
       })
       .await
@@ -774,6 +1125,7 @@ impl CliFactory {
           self.parsed_source_cache().clone(),
           self.resolver().await?.clone(),
           self.root_permissions_container()?.clone(),
+          self.wasi_components().cloned(),
         )))
       })
       .await
@@ -797,6 +1149,53 @@ impl CliFactory {
       .await
   }
 
+  /// Builds and validates the graph of `entrypoints` for the embedding APIs.
+  /// Integrity check failures are returned like other graph errors instead
+  /// of exiting the process.
+  pub async fn create_graph_for_embedder(
+    &self,
+    kind: GraphKind,
+    entrypoints: &[String],
+  ) -> Result<ModuleGraph, AnyError> {
+    let cli_options = self.cli_options()?;
+    let roots = entrypoints
+      .iter()
+      .map(|entrypoint| {
+        resolve_url_or_path(entrypoint, cli_options.initial_cwd())
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    self.create_graph_for_embedder_roots(kind, roots).await
+  }
+
+  /// Like [`Self::create_graph_for_embedder`], with resolved roots.
+  pub async fn create_graph_for_embedder_roots(
+    &self,
+    kind: GraphKind,
+    roots: Vec<ModuleSpecifier>,
+  ) -> Result<ModuleGraph, AnyError> {
+    let cli_options = self.cli_options()?;
+    let graph = self
+      .module_graph_creator()
+      .await?
+      .create_graph(kind, roots.clone())
+      .await?;
+    graph_valid(
+      &graph,
+      self.fs(),
+      &roots,
+      GraphValidOptions {
+        kind: if cli_options.type_check_mode().is_true() {
+          GraphKind::All
+        } else {
+          GraphKind::CodeOnly
+        },
+        check_js: cli_options.check_js(),
+        exit_integrity_errors: false,
+      },
+    )?;
+    Ok(graph)
+  }
+
   pub async fn main_module_graph_container(
     &self,
   ) -> Result<&Arc<MainModuleGraphContainer>, AnyError> {
@@ -946,7 +1345,7 @@ impl CliFactory {
         None
       },
       self.feature_checker()?.clone(),
-      fs.clone(),
+      self.worker_fs().clone(),
       maybe_file_watcher_communicator,
       self.maybe_inspector_server()?.clone(),
       cli_options.maybe_lockfile().cloned(),
@@ -1002,12 +1401,30 @@ impl CliFactory {
         ))
       });
       Some(fn_)
+    } else if let Some(controller) = self
+      .embedder_options
+      .as_ref()
+      .and_then(|options| options.hmr_controller.clone())
+    {
+      let emitter = self.emitter()?.clone();
+      let fn_: crate::worker::CreateHmrRunnerCb = Box::new(move |session| {
+        Box::new(EmbedderHmrRunner::new(
+          emitter.clone(),
+          session,
+          &controller,
+        ))
+      });
+      Some(fn_)
     } else {
       None
     };
+    let maybe_coverage_dir = self
+      .embedder_options
+      .as_ref()
+      .and_then(|options| options.coverage_dir.clone())
+      .or_else(|| cli_options.coverage_dir().map(PathBuf::from));
     let create_coverage_collector =
-      if let Some(coverage_dir) = cli_options.coverage_dir() {
-        let coverage_dir = PathBuf::from(coverage_dir);
+      if let Some(coverage_dir) = maybe_coverage_dir {
         let fn_: crate::worker::CreateCoverageCollectorCb =
           Box::new(move |session| {
             Box::new(CoverageCollector::new(coverage_dir.clone(), session))
@@ -1016,6 +1433,16 @@ impl CliFactory {
       } else {
         None
       };
+    let inspector_controller = self
+      .embedder_options
+      .as_ref()
+      .and_then(|options| options.inspector_controller.clone());
+    let embedder_inspect_mode = self
+      .embedder_options
+      .as_ref()
+      .filter(|_| inspector_controller.is_some())
+      .map(|options| options.inspect_mode)
+      .unwrap_or_default();
 
     Ok(CliMainWorkerOptions {
       argv: cli_options.argv().clone(),
@@ -1023,15 +1450,88 @@ impl CliFactory {
       // because we need to register new ops for testing and jupyter
       // integration.
       skip_op_registration: cli_options.sub_command().is_run(),
+      host_channel: self.embedder_options.is_some(),
+      serve_adapter: self
+        .embedder_options
+        .as_ref()
+        .is_some_and(|options| options.serve_adapter),
+      startup_snapshot: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.startup_snapshot),
+      ephemeral_deno_dir: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.ephemeral_deno_dir.clone()),
+      worker_observer: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.worker_observer.clone()),
+      execution_limits: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.execution_limits.clone()),
+      unstable_api_errors: self.embedder_options.is_some(),
+      fetch_interceptor: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.fetch_interceptor.clone()),
+      connect_interceptor: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.connect_interceptor.clone()),
+      spawn_interceptor: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.spawn_interceptor.clone()),
+      dlopen_interceptor: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.dlopen_interceptor.clone()),
+      kv_backend: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.kv_backend.clone()),
+      broadcast_channel: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.broadcast_channel.clone()),
+      worker_creation_policy: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.worker_creation_policy.clone()),
+      web_worker_extensions: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.web_worker_extensions.clone()),
+      virtual_env: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.virtual_env.clone()),
+      signal_forwarder: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.signal_forwarder.clone()),
+      resource_quotas: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.resource_quotas.clone()),
       log_level: cli_options.log_level().unwrap_or(log::Level::Info).into(),
-      enable_op_summary_metrics: cli_options.enable_op_summary_metrics(),
+      enable_op_summary_metrics: cli_options.enable_op_summary_metrics()
+        || self
+          .embedder_options
+          .as_ref()
+          .is_some_and(|options| options.op_metrics),
       enable_testing_features: cli_options.enable_testing_features(),
       has_node_modules_dir: cli_options.has_node_modules_dir(),
-      hmr: cli_options.has_hmr(),
-      inspect_brk: cli_options.inspect_brk().is_some(),
-      inspect_wait: cli_options.inspect_wait().is_some(),
+      hmr: create_hmr_runner.is_some(),
+      inspect_brk: cli_options.inspect_brk().is_some()
+        || embedder_inspect_mode == InspectMode::BreakOnFirstStatement,
+      inspect_wait: cli_options.inspect_wait().is_some()
+        || embedder_inspect_mode == InspectMode::WaitForSession,
       strace_ops: cli_options.strace_ops().clone(),
-      is_inspecting: cli_options.is_inspecting(),
+      is_inspecting: cli_options.is_inspecting()
+        || inspector_controller.is_some(),
       location: cli_options.location_flag().clone(),
       // if the user ran a binary command, we'll need to set process.argv[0]
       // to be the name of the binary command instead of deno
@@ -1046,9 +1546,65 @@ impl CliFactory {
         .clone(),
       create_hmr_runner,
       create_coverage_collector,
+      inspector_controller,
+      determinism: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.determinism.clone()),
+      host_fns: self
+        .embedder_options
+        .as_ref()
+        .map(|options| options.host_fns.clone())
+        .unwrap_or_default(),
+      create_wasi_extension: self.wasi_components().cloned().map(
+        |components| -> CreateWasiExtensionCb {
+          Box::new(move || create_wasi_extension(components.clone()))
+        },
+      ),
+      restored_state: self
+        .embedder_options
+        .as_ref()
+        .and_then(|options| options.restored_state.clone()),
       node_ipc: cli_options.node_ipc_fd(),
       serve_port: cli_options.serve_port(),
       serve_host: cli_options.serve_host(),
     })
   }
 }
+
+// denort shares the cache module without the factory
+impl ModuleCache {
+  /// Transpiles the modules of the graph of `entrypoint`, resolved with the
+  /// configuration from `flags`, into the cache ahead of running it. The V8
+  /// code cache of the modules is only stored once they run.
+  ///
+  /// WebAssembly modules of the graph are downloaded, but compiled each time
+  /// a worker imports them, as the V8 bindings can't serialize compiled
+  /// modules. Components run by WASI can be compiled ahead of time with
+  /// [`ModuleCache::precompile_wasm`].
+  ///
+  /// Returns the number of modules that were transpiled or already cached.
+  pub async fn populate(
+    self: &Arc<Self>,
+    flags: Arc<Flags>,
+    entrypoint: &str,
+  ) -> Result<usize, AnyError> {
+    let factory = CliFactory::from_flags_for_embedder(
+      flags,
+      EmbedderOptions {
+        module_cache: Some(self.clone()),
+        ..Default::default()
+      },
+    );
+    let graph = factory
+      .create_graph_for_embedder(GraphKind::All, &[entrypoint.to_string()])
+      .await?;
+    factory.emitter()?.cache_module_emits(&graph).await?;
+    let count = graph
+      .modules()
+      .filter_map(|module| module.js())
+      .filter(|module| module.media_type.is_emittable())
+      .count();
+    Ok(count)
+  }
+}
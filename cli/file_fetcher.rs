@@ -4,10 +4,14 @@ use crate::args::CacheSetting;
 use crate::auth_tokens::AuthTokens;
 use crate::cache::HttpCache;
 use crate::colors;
+use crate::errors::CachedOnlyError;
 use crate::http_util::CacheSemantics;
 use crate::http_util::FetchOnceArgs;
 use crate::http_util::FetchOnceResult;
 use crate::http_util::HttpClientProvider;
+use crate::http_util::RetryAttempts;
+use crate::import_policy::ImportPolicy;
+use crate::integrity::IntegrityChecker;
 use crate::util::progress_bar::ProgressBar;
 
 use deno_ast::MediaType;
@@ -217,6 +221,8 @@ pub struct FileFetcher {
   blob_store: Arc<BlobStore>,
   download_log_level: log::Level,
   progress_bar: Option<ProgressBar>,
+  import_policy: Option<Arc<ImportPolicy>>,
+  integrity_checker: Option<Arc<IntegrityChecker>>,
 }
 
 impl FileFetcher {
@@ -238,6 +244,8 @@ impl FileFetcher {
       blob_store,
       download_log_level: log::Level::Info,
       progress_bar,
+      import_policy: None,
+      integrity_checker: None,
     }
   }
 
@@ -250,6 +258,21 @@ impl FileFetcher {
     self.download_log_level = level;
   }
 
+  /// Checks remote specifiers against `import_policy` before fetching them,
+  /// including the ones redirected to.
+  pub fn set_import_policy(&mut self, import_policy: Arc<ImportPolicy>) {
+    self.import_policy = Some(import_policy);
+  }
+
+  /// Verifies the fetched files with `integrity_checker` before returning
+  /// them.
+  pub fn set_integrity_checker(
+    &mut self,
+    integrity_checker: Arc<IntegrityChecker>,
+  ) {
+    self.integrity_checker = Some(integrity_checker);
+  }
+
   /// Fetch cached remote file.
   ///
   /// This is a recursive operation if source file has redirections.
@@ -379,12 +402,7 @@ impl FileFetcher {
     }
 
     if *cache_setting == CacheSetting::Only {
-      return Err(custom_error(
-        "NotCached",
-        format!(
-          "Specifier not found in cache: \"{specifier}\", --cached-only is specified."
-        ),
-      ));
+      return Err(CachedOnlyError::Specifier(specifier.clone()).into());
     }
 
     let mut maybe_progress_guard = None;
@@ -427,9 +445,19 @@ impl FileFetcher {
 
     async fn handle_request_or_server_error(
       retried: &mut bool,
+      maybe_attempts: Option<&mut RetryAttempts<'_>>,
       specifier: &Url,
       err_str: String,
     ) -> Result<(), AnyError> {
+      if let Some(attempts) = maybe_attempts {
+        if attempts.next(&err_str).await {
+          return Ok(());
+        }
+        return Err(generic_error(format!(
+          "Import '{}' failed: {}",
+          specifier, err_str
+        )));
+      }
       // Retry once, and bail otherwise.
       if !*retried {
         *retried = true;
@@ -445,18 +473,29 @@ impl FileFetcher {
     }
 
     let mut retried = false; // retry intermittent failures
+    let mut maybe_attempts = self
+      .http_client_provider
+      .retry_options()
+      .map(|options| options.attempts(specifier.clone()));
     let result = loop {
+      let (url, maybe_auth_token, maybe_auth) = match &maybe_attempts {
+        // the auth of the origin isn't sent to its mirror
+        Some(attempts) if attempts.is_mirrored() => {
+          (attempts.url(), self.auth_tokens.get(attempts.url()), None)
+        }
+        _ => (specifier, maybe_auth_token.clone(), maybe_auth.clone()),
+      };
       let result = match self
         .http_client_provider
         .get_or_create()?
         .fetch_no_follow(FetchOnceArgs {
-          url: specifier.clone(),
+          url: url.clone(),
           maybe_accept: maybe_accept.map(ToOwned::to_owned),
           maybe_etag: maybe_etag_cache_entry
             .as_ref()
             .map(|(_, etag)| etag.clone()),
-          maybe_auth_token: maybe_auth_token.clone(),
-          maybe_auth: maybe_auth.clone(),
+          maybe_auth_token,
+          maybe_auth,
           maybe_progress_guard: maybe_progress_guard.as_ref(),
         })
         .await?
@@ -481,12 +520,19 @@ impl FileFetcher {
           }))
         }
         FetchOnceResult::RequestError(err) => {
-          handle_request_or_server_error(&mut retried, specifier, err).await?;
+          handle_request_or_server_error(
+            &mut retried,
+            maybe_attempts.as_mut(),
+            specifier,
+            err,
+          )
+          .await?;
           continue;
         }
         FetchOnceResult::ServerError(status) => {
           handle_request_or_server_error(
             &mut retried,
+            maybe_attempts.as_mut(),
             specifier,
             status.to_string(),
           )
@@ -649,6 +695,19 @@ impl FileFetcher {
   pub async fn fetch_no_follow_with_options(
     &self,
     options: FetchNoFollowOptions<'_>,
+  ) -> Result<FileOrRedirect, AnyError> {
+    let file_or_redirect = self.fetch_no_follow_unverified(options).await?;
+    if let (Some(integrity_checker), FileOrRedirect::File(file)) =
+      (&self.integrity_checker, &file_or_redirect)
+    {
+      integrity_checker.verify(&file.specifier, &file.source)?;
+    }
+    Ok(file_or_redirect)
+  }
+
+  async fn fetch_no_follow_unverified(
+    &self,
+    options: FetchNoFollowOptions<'_>,
   ) -> Result<FileOrRedirect, AnyError> {
     let maybe_checksum = options.maybe_checksum;
     let options = options.fetch_options;
@@ -658,6 +717,13 @@ impl FileFetcher {
       "FileFetcher::fetch_no_follow_with_options - specifier: {}",
       specifier
     );
+    if !SUPPORTED_SCHEMES.contains(&specifier.scheme()) {
+      // modules provided by a `HostModuleResolver` may use custom schemes
+      // and were already vetted by the host
+      if let Some(file) = self.memory_files.get(specifier) {
+        return Ok(FileOrRedirect::File(file));
+      }
+    }
     let scheme = get_validated_scheme(specifier)?;
     match options.permissions {
       FetchPermissionsOptionRef::AllowAll => {
@@ -695,6 +761,9 @@ impl FileFetcher {
         format!("A remote specifier was requested: \"{specifier}\", but --no-remote is specified."),
       ))
     } else {
+      if let Some(import_policy) = &self.import_policy {
+        import_policy.check_remote(specifier)?;
+      }
       self
         .fetch_remote_no_follow(
           specifier,
@@ -1413,7 +1482,7 @@ mod tests {
     assert!(result.is_err());
     let err = result.unwrap_err();
     assert_eq!(err.to_string(), "Specifier not found in cache: \"http://localhost:4545/run/002_hello.ts\", --cached-only is specified.");
-    assert_eq!(get_custom_error_class(&err), Some("NotCached"));
+    assert_eq!(crate::errors::get_error_class_name(&err), "NotCached");
 
     let result = file_fetcher_02.fetch_bypass_permissions(&specifier).await;
     assert!(result.is_ok());
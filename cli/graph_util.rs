@@ -10,8 +10,10 @@ use crate::cache::FetchCacher;
 use crate::cache::GlobalHttpCache;
 use crate::cache::ModuleInfoCache;
 use crate::cache::ParsedSourceCache;
+use crate::cache::WasmComponentLoader;
 use crate::colors;
 use crate::errors::get_error_class_name;
+use crate::errors::CachedOnlyError;
 use crate::file_fetcher::FileFetcher;
 use crate::npm::CliNpmResolver;
 use crate::resolver::CjsTracker;
@@ -22,6 +24,8 @@ use crate::tools::check;
 use crate::tools::check::TypeChecker;
 use crate::util::file_watcher::WatcherCommunicator;
 use crate::util::fs::canonicalize_path;
+use crate::wasi::is_wasm_component;
+use crate::wasi::WasiComponents;
 use deno_config::deno_json::JsxImportSourceConfig;
 use deno_config::workspace::JsrPackageConfig;
 use deno_core::anyhow::bail;
@@ -155,6 +159,7 @@ pub fn graph_walk_errors<'a>(
         ModuleGraphError::ModuleError(error) => {
           enhanced_integrity_error_message(error)
             .or_else(|| enhanced_sloppy_imports_error_message(fs, error))
+            .or_else(|| enhanced_wasm_component_error_message(fs, error))
             .unwrap_or_else(|| format_deno_graph_error(error))
         }
       };
@@ -186,6 +191,81 @@ pub fn graph_exit_integrity_errors(graph: &ModuleGraph) {
   }
 }
 
+/// Messages of the integrity check failures in the graph, for callers that
+/// shouldn't exit the process like [`graph_exit_integrity_errors`] does.
+pub fn graph_integrity_error_messages(graph: &ModuleGraph) -> Vec<String> {
+  graph
+    .module_errors()
+    .filter_map(enhanced_integrity_error_message)
+    .collect()
+}
+
+/// Modules and npm packages that are missing from the cache while fetching
+/// is disabled, eg. with `--cached-only`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+  "Not found in the cache, and fetching is disabled: {}",
+  .specifiers.join(", ")
+)]
+pub struct NotCachedError {
+  /// Module urls, `jsr:` packages and `npm:` packages.
+  pub specifiers: Vec<String>,
+}
+
+impl NotCachedError {
+  /// Collects what failed to load in `graph` because it isn't cached.
+  pub fn from_graph(graph: &ModuleGraph) -> Option<Self> {
+    let mut specifiers = Vec::new();
+    for error in graph.module_errors() {
+      let specifier = match error {
+        ModuleError::LoadingErr(specifier, _, ModuleLoadError::Loader(err))
+          if is_cached_only_error(err) =>
+        {
+          specifier.to_string()
+        }
+        ModuleError::LoadingErr(
+          _,
+          _,
+          ModuleLoadError::Jsr(JsrLoadError::PackageManifestLoad(name, err)),
+        ) if is_cached_only_error(err) => format!("jsr:{name}"),
+        ModuleError::LoadingErr(
+          _,
+          _,
+          ModuleLoadError::Jsr(JsrLoadError::PackageVersionManifestLoad(
+            nv,
+            err,
+          )),
+        ) if is_cached_only_error(err) => format!("jsr:{nv}"),
+        _ => continue,
+      };
+      if !specifiers.contains(&specifier) {
+        specifiers.push(specifier);
+      }
+    }
+    if let Err(err) = &graph.npm_dep_graph_result {
+      if let Some(error) = Self::from_error(err) {
+        specifiers.extend(error.specifiers);
+      }
+    }
+    (!specifiers.is_empty()).then_some(Self { specifiers })
+  }
+
+  /// Converts the [`CachedOnlyError`] of the file fetcher or the npm cache.
+  pub fn from_error(err: &AnyError) -> Option<Self> {
+    let specifier = match err.downcast_ref::<CachedOnlyError>()? {
+      CachedOnlyError::Specifier(specifier) => specifier.to_string(),
+      CachedOnlyError::NpmPackage(name) => format!("npm:{name}"),
+    };
+    Some(Self {
+      specifiers: vec![specifier],
+    })
+  }
+}
+
+fn is_cached_only_error(err: &AnyError) -> bool {
+  err.downcast_ref::<CachedOnlyError>().is_some()
+}
+
 fn exit_for_integrity_error(err: &ModuleError) {
   if let Some(err_message) = enhanced_integrity_error_message(err) {
     log::error!("{} {}", colors::red("error:"), err_message);
@@ -416,6 +496,7 @@ pub struct ModuleGraphBuilder {
   parsed_source_cache: Arc<ParsedSourceCache>,
   resolver: Arc<CliResolver>,
   root_permissions_container: PermissionsContainer,
+  wasi_components: Option<Arc<WasiComponents>>,
 }
 
 impl ModuleGraphBuilder {
@@ -435,6 +516,7 @@ impl ModuleGraphBuilder {
     parsed_source_cache: Arc<ParsedSourceCache>,
     resolver: Arc<CliResolver>,
     root_permissions_container: PermissionsContainer,
+    wasi_components: Option<Arc<WasiComponents>>,
   ) -> Self {
     Self {
       caches,
@@ -451,6 +533,7 @@ impl ModuleGraphBuilder {
       parsed_source_cache,
       resolver,
       root_permissions_container,
+      wasi_components,
     }
   }
 
@@ -732,6 +815,10 @@ impl ModuleGraphBuilder {
           self.cli_options.sub_command(),
           crate::args::DenoSubcommand::Publish { .. }
         ),
+        wasm_component_loader: self
+          .wasi_components
+          .clone()
+          .map(|components| components as Arc<dyn WasmComponentLoader>),
       },
     )
   }
@@ -828,6 +915,25 @@ fn enhanced_sloppy_imports_error_message(
   }
 }
 
+fn enhanced_wasm_component_error_message(
+  fs: &Arc<dyn FileSystem>,
+  error: &ModuleError,
+) -> Option<String> {
+  let ModuleError::WasmParseErr(specifier, _) = error else {
+    return None;
+  };
+  let path = specifier.to_file_path().ok()?;
+  let bytes = fs.read_file_sync(&path, None).ok()?;
+  if !is_wasm_component(&bytes) {
+    return None;
+  }
+  Some(format!(
+    "{}\n  {} WebAssembly components can only be imported when WASI components are enabled by the embedder, otherwise only core modules can. Transpile the component to a JavaScript module with WASI shims instead, eg. with `jco transpile`.",
+    format_deno_graph_error(error),
+    colors::cyan("hint:"),
+  ))
+}
+
 fn enhanced_integrity_error_message(err: &ModuleError) -> Option<String> {
   match err {
     ModuleError::LoadingErr(
@@ -1311,4 +1417,23 @@ mod test {
       assert_eq!(get_resolution_error_bare_node_specifier(&err), output,);
     }
   }
+
+  #[test]
+  fn not_cached_error_from_error() {
+    let err: AnyError = CachedOnlyError::Specifier(
+      ModuleSpecifier::parse("https://deno.land/x/a.ts").unwrap(),
+    )
+    .into();
+    assert_eq!(
+      NotCachedError::from_error(&err).unwrap().specifiers,
+      vec!["https://deno.land/x/a.ts".to_string()]
+    );
+    let err: AnyError = CachedOnlyError::NpmPackage("chalk".to_string()).into();
+    assert_eq!(
+      NotCachedError::from_error(&err).unwrap().specifiers,
+      vec!["npm:chalk".to_string()]
+    );
+    let err = custom_error("NotFound", "Module not found \"file:///a.ts\".");
+    assert!(NotCachedError::from_error(&err).is_none());
+  }
 }
@@ -0,0 +1,105 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Message channel between an embedding Rust host and the main worker,
+//! exposed to JavaScript as `Deno.host.send()` and `Deno.host.onMessage()`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::op2;
+use deno_core::serde_json;
+use deno_core::Extension;
+use deno_core::OpState;
+use tokio::sync::mpsc;
+
+deno_core::extension!(deno_host,
+  ops = [
+    op_host_send,
+    op_host_recv,
+    op_host_restored_state,
+  ],
+  options = {
+    to_host_tx: mpsc::UnboundedSender<serde_json::Value>,
+    from_host_rx: mpsc::UnboundedReceiver<serde_json::Value>,
+    restored_state: Option<serde_json::Value>,
+  },
+  state = |state, options| {
+    state.put(HostChannelState {
+      to_host_tx: options.to_host_tx,
+      from_host_rx: Rc::new(tokio::sync::Mutex::new(options.from_host_rx)),
+      restored_state: options.restored_state,
+    });
+  },
+);
+
+struct HostChannelState {
+  to_host_tx: mpsc::UnboundedSender<serde_json::Value>,
+  from_host_rx:
+    Rc<tokio::sync::Mutex<mpsc::UnboundedReceiver<serde_json::Value>>>,
+  restored_state: Option<serde_json::Value>,
+}
+
+/// The Rust side of the message channel of a main worker.
+pub struct HostChannel {
+  /// Delivers messages to the listeners registered with
+  /// `Deno.host.onMessage()`. Messages sent while no listener is registered
+  /// are kept until one is. Dropping it lets the worker's receive loop end.
+  pub sender: mpsc::UnboundedSender<serde_json::Value>,
+  /// Yields the messages passed to `Deno.host.send()`.
+  pub receiver: mpsc::UnboundedReceiver<serde_json::Value>,
+}
+
+/// Creates the host side of the channel along with the extension that has to
+/// be registered on the worker. `restored_state` is exposed to JavaScript as
+/// `Deno.host.restoredState`.
+pub fn create_host_channel(
+  restored_state: Option<serde_json::Value>,
+) -> (HostChannel, Extension) {
+  let (to_host_tx, to_host_rx) = mpsc::unbounded_channel();
+  let (from_host_tx, from_host_rx) = mpsc::unbounded_channel();
+  let extension = deno_host::init_ops(to_host_tx, from_host_rx, restored_state);
+  let channel = HostChannel {
+    sender: from_host_tx,
+    receiver: to_host_rx,
+  };
+  (channel, extension)
+}
+
+#[op2]
+fn op_host_send(
+  state: &mut OpState,
+  #[serde] message: serde_json::Value,
+) -> Result<(), AnyError> {
+  state
+    .borrow::<HostChannelState>()
+    .to_host_tx
+    .send(message)
+    .map_err(|_| generic_error("The host is no longer receiving messages."))
+}
+
+/// Resolves with the next message wrapped in a single element array, so that
+/// a `null` message can be told apart from the host dropping its sender.
+#[op2(async)]
+#[serde]
+async fn op_host_recv(
+  state: Rc<RefCell<OpState>>,
+) -> Option<(serde_json::Value,)> {
+  let receiver = state
+    .borrow()
+    .borrow::<HostChannelState>()
+    .from_host_rx
+    .clone();
+  let mut receiver = receiver.lock().await;
+  receiver.recv().await.map(|message| (message,))
+}
+
+/// The state of the
+/// [`WorkerStateSnapshot`](crate::worker::WorkerStateSnapshot) the worker
+/// was resumed from.
+#[op2]
+#[serde]
+fn op_host_restored_state(state: &mut OpState) -> Option<serde_json::Value> {
+  state.borrow_mut::<HostChannelState>().restored_state.take()
+}
@@ -0,0 +1,342 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Host functions: Rust closures that scripts call as plain JavaScript
+//! functions, eg. `storage.get({ key })`, without writing ops and their
+//! JavaScript glue. See the [`host_fn!`](crate::host_fn!) macro.
+//!
+//! Every host function can also be called by name with
+//! `Deno.host.call(name, args)`, including the ones registered after the
+//! worker was created with [`register_host_fn`].
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use deno_core::error::generic_error;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::FutureExt;
+use deno_core::op2;
+use deno_core::serde_json;
+use deno_core::Extension;
+use deno_core::OpState;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+deno_core::extension!(deno_host_fn,
+  ops = [
+    op_host_fn_list,
+    op_host_fn_is_async,
+    op_host_fn_call,
+    op_host_fn_call_async,
+  ],
+  options = {
+    host_fns: Vec<HostFn>,
+  },
+  state = |state, options| {
+    state.put(HostFns(options.host_fns));
+  },
+);
+
+pub type HostFnResult<T> = Result<T, AnyError>;
+
+type AsyncResult = LocalBoxFuture<'static, HostFnResult<serde_json::Value>>;
+type SyncCall =
+  dyn Fn(serde_json::Value) -> HostFnResult<serde_json::Value> + Send + Sync;
+type AsyncCall = dyn Fn(serde_json::Value) -> AsyncResult + Send + Sync;
+
+#[derive(Clone)]
+enum HostFnCall {
+  Sync(Arc<SyncCall>),
+  Async(Arc<AsyncCall>),
+}
+
+/// A Rust function exposed to the scripts of a main worker under a dotted
+/// name, eg. `"storage.get"` for `globalThis.storage.get()`. The objects of
+/// the namespace are created as needed.
+///
+/// Scripts pass a single argument, deserialized from JSON into the
+/// arguments of the function, and get its result as JSON. Async functions
+/// return a promise. Errors are thrown to the script.
+#[derive(Clone)]
+pub struct HostFn {
+  name: String,
+  call: HostFnCall,
+}
+
+impl HostFn {
+  pub fn new<A, R>(
+    name: impl Into<String>,
+    f: impl Fn(A) -> HostFnResult<R> + Send + Sync + 'static,
+  ) -> Self
+  where
+    A: DeserializeOwned,
+    R: Serialize,
+  {
+    let name = name.into();
+    let call = {
+      let name = name.clone();
+      move |args| -> HostFnResult<serde_json::Value> {
+        let result = f(deserialize_args(&name, args)?)?;
+        serde_json::to_value(result).map_err(AnyError::from)
+      }
+    };
+    Self {
+      name,
+      call: HostFnCall::Sync(Arc::new(call)),
+    }
+  }
+
+  /// Like [`HostFn::new`], but for a function returning a future. The future
+  /// is polled on the thread of the worker, so it doesn't have to be `Send`.
+  pub fn new_async<A, R, F>(
+    name: impl Into<String>,
+    f: impl Fn(A) -> F + Send + Sync + 'static,
+  ) -> Self
+  where
+    A: DeserializeOwned,
+    R: Serialize,
+    F: Future<Output = HostFnResult<R>> + 'static,
+  {
+    let name = name.into();
+    let call = {
+      let name = name.clone();
+      move |args| -> AsyncResult {
+        let args = match deserialize_args(&name, args) {
+          Ok(args) => args,
+          Err(err) => return std::future::ready(Err(err)).boxed_local(),
+        };
+        let result = f(args);
+        async move {
+          let result = result.await?;
+          serde_json::to_value(result).map_err(AnyError::from)
+        }
+        .boxed_local()
+      }
+    };
+    Self {
+      name,
+      call: HostFnCall::Async(Arc::new(call)),
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+}
+
+/// Creates a [`HostFn`] from a closure taking a single typed argument. The
+/// body evaluates to the result, and can return errors early with `?`. The
+/// body of an async function can't use captured variables, use
+/// [`HostFn::new_async`] for those.
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct GetArgs {
+///   key: String,
+/// }
+///
+/// let get = host_fn!("storage.get", |args: GetArgs| -> Option<String> {
+///   STORE.lock().get(&args.key).cloned()
+/// });
+/// let fetch_user = host_fn!("users.fetch", async |id: u64| -> User {
+///   load_user(id).await?
+/// });
+/// let worker = DenoRuntimeBuilder::new("./main.ts")
+///   .host_fns(vec![get, fetch_user])
+///   .build()
+///   .await?;
+/// ```
+#[macro_export]
+macro_rules! host_fn {
+  ($name:literal, async |$arg:ident: $arg_ty:ty| -> $ret:ty $body:block) => {
+    $crate::HostFn::new_async($name, move |$arg: $arg_ty| async move {
+      $crate::HostFnResult::<$ret>::Ok($body)
+    })
+  };
+  ($name:literal, |$arg:ident: $arg_ty:ty| -> $ret:ty $body:block) => {
+    $crate::HostFn::new(
+      $name,
+      move |$arg: $arg_ty| -> $crate::HostFnResult<$ret> { Ok($body) },
+    )
+  };
+}
+
+/// Creates the extension that has to be registered on the worker for
+/// `js/40_host_fn.js` to expose `host_fns`.
+pub fn create_host_fn_extension(host_fns: Vec<HostFn>) -> Extension {
+  deno_host_fn::init_ops(host_fns)
+}
+
+struct HostFns(Vec<HostFn>);
+
+/// Registers `host_fn` on a worker created with the host function extension,
+/// replacing a function registered under the same name. Only
+/// `Deno.host.call()` sees functions registered this way, the globals are
+/// defined when the worker is created.
+pub fn register_host_fn(
+  state: &mut OpState,
+  host_fn: HostFn,
+) -> Result<(), AnyError> {
+  let Some(host_fns) = state.try_borrow_mut::<HostFns>() else {
+    return Err(generic_error(
+      "Host functions are not enabled for this worker.",
+    ));
+  };
+  match host_fns.0.iter_mut().find(|f| f.name == host_fn.name) {
+    Some(existing) => *existing = host_fn,
+    None => host_fns.0.push(host_fn),
+  }
+  Ok(())
+}
+
+/// Removes the host function registered under `name`. Calling it afterwards
+/// throws. Returns whether there was such a function.
+pub fn unregister_host_fn(state: &mut OpState, name: &str) -> bool {
+  let Some(host_fns) = state.try_borrow_mut::<HostFns>() else {
+    return false;
+  };
+  let len = host_fns.0.len();
+  host_fns.0.retain(|host_fn| host_fn.name != name);
+  host_fns.0.len() != len
+}
+
+fn deserialize_args<A: DeserializeOwned>(
+  name: &str,
+  args: serde_json::Value,
+) -> Result<A, AnyError> {
+  serde_json::from_value(args).map_err(|err| {
+    type_error(format!("Invalid arguments passed to '{name}': {err}"))
+  })
+}
+
+fn find_host_fn<'a>(state: &'a OpState, name: &str) -> Option<&'a HostFn> {
+  state
+    .borrow::<HostFns>()
+    .0
+    .iter()
+    .find(|host_fn| host_fn.name == name)
+}
+
+fn host_fn_call(state: &OpState, name: &str) -> Result<HostFnCall, AnyError> {
+  find_host_fn(state, name)
+    .map(|host_fn| host_fn.call.clone())
+    .ok_or_else(|| type_error(format!("Unknown host function '{name}'.")))
+}
+
+/// The names of the host functions and whether they are async.
+#[op2]
+#[serde]
+fn op_host_fn_list(state: &mut OpState) -> Vec<(String, bool)> {
+  state
+    .borrow::<HostFns>()
+    .0
+    .iter()
+    .map(|host_fn| {
+      let is_async = matches!(host_fn.call, HostFnCall::Async(_));
+      (host_fn.name.clone(), is_async)
+    })
+    .collect()
+}
+
+/// Whether the host function `name` is async, `None` if there's none.
+#[op2]
+fn op_host_fn_is_async(
+  state: &mut OpState,
+  #[string] name: &str,
+) -> Option<bool> {
+  find_host_fn(state, name)
+    .map(|host_fn| matches!(host_fn.call, HostFnCall::Async(_)))
+}
+
+#[op2]
+#[serde]
+fn op_host_fn_call(
+  state: &mut OpState,
+  #[string] name: &str,
+  #[serde] args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+  match host_fn_call(state, name)? {
+    HostFnCall::Sync(call) => call(args),
+    HostFnCall::Async(_) => {
+      Err(type_error(format!("The host function '{name}' is async.")))
+    }
+  }
+}
+
+#[op2(async)]
+#[serde]
+async fn op_host_fn_call_async(
+  state: Rc<RefCell<OpState>>,
+  #[string] name: String,
+  #[serde] args: serde_json::Value,
+) -> Result<serde_json::Value, AnyError> {
+  let call = host_fn_call(&state.borrow(), &name)?;
+  match call {
+    HostFnCall::Sync(call) => call(args),
+    HostFnCall::Async(call) => call(args).await,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn call_sync(host_fn: &HostFn, args: serde_json::Value) -> String {
+    let HostFnCall::Sync(call) = &host_fn.call else {
+      unreachable!();
+    };
+    match call(args) {
+      Ok(value) => value.to_string(),
+      Err(err) => err.to_string(),
+    }
+  }
+
+  #[test]
+  fn host_fn_call_sync() {
+    #[derive(serde::Deserialize)]
+    struct AddArgs {
+      a: i32,
+      b: i32,
+    }
+
+    let add = HostFn::new("math.add", |args: AddArgs| {
+      if args.b < 0 {
+        deno_core::anyhow::bail!("Negative");
+      }
+      Ok(args.a + args.b)
+    });
+    assert_eq!(add.name(), "math.add");
+    assert_eq!(call_sync(&add, serde_json::json!({ "a": 1, "b": 2 })), "3");
+    assert_eq!(
+      call_sync(&add, serde_json::json!({ "a": 1, "b": -2 })),
+      "Negative"
+    );
+    assert!(call_sync(&add, serde_json::json!("1"))
+      .starts_with("Invalid arguments passed to 'math.add'"));
+  }
+
+  #[test]
+  fn register_and_unregister() {
+    let mut state = OpState::new(None, None);
+    let double = |n: i32| -> HostFnResult<i32> { Ok(n * 2) };
+    assert!(
+      register_host_fn(&mut state, HostFn::new("double", double)).is_err()
+    );
+
+    state.put(HostFns(vec![]));
+    register_host_fn(&mut state, HostFn::new("double", double)).unwrap();
+    let triple = |n: i32| -> HostFnResult<i32> { Ok(n * 3) };
+    register_host_fn(&mut state, HostFn::new("double", triple)).unwrap();
+    let HostFnCall::Sync(call) = host_fn_call(&state, "double").unwrap() else {
+      unreachable!();
+    };
+    assert_eq!(call(serde_json::json!(2)).unwrap(), serde_json::json!(6));
+
+    assert!(unregister_host_fn(&mut state, "double"));
+    assert!(!unregister_host_fn(&mut state, "double"));
+    assert!(host_fn_call(&state, "double").is_err());
+  }
+}
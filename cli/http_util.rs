@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use crate::auth_tokens::AuthToken;
+use crate::auth_tokens::TokenProvider;
 use crate::util::progress_bar::UpdateGuard;
 use crate::version;
 
@@ -18,7 +19,11 @@ use deno_core::url::Url;
 use deno_runtime::deno_fetch;
 use deno_runtime::deno_fetch::create_http_client;
 use deno_runtime::deno_fetch::CreateHttpClientOptions;
+use deno_runtime::deno_fetch::ProxyConfig;
+use deno_runtime::deno_tls::load_certs;
+use deno_runtime::deno_tls::load_private_keys;
 use deno_runtime::deno_tls::RootCertStoreProvider;
+use deno_runtime::deno_tls::TlsKey;
 use http::header;
 use http::header::HeaderName;
 use http::header::HeaderValue;
@@ -209,6 +214,207 @@ pub struct FetchOnceArgs<'a> {
   pub maybe_progress_guard: Option<&'a UpdateGuard>,
 }
 
+/// Network settings of the HTTP clients fetching modules and packages, set
+/// in code instead of with environment variables.
+#[derive(Debug, Clone, Default)]
+pub struct HttpNetworkOptions {
+  /// Proxies, used instead of the `ALL_PROXY`, `HTTPS_PROXY`, `HTTP_PROXY`
+  /// and `NO_PROXY` environment variables.
+  pub proxy: Option<ProxyConfig>,
+  /// PEM encoded CA certificates trusted in addition to the root
+  /// certificates, eg. the bundle of a corporate network.
+  pub ca_certs: Vec<Vec<u8>>,
+  /// Presented to the servers requiring client authentication (mTLS).
+  pub client_cert: Option<ClientCertificate>,
+}
+
+/// A certificate chain and private key authenticating the client to
+/// servers.
+#[derive(Debug, Clone)]
+pub struct ClientCertificate(TlsKey);
+
+impl ClientCertificate {
+  /// Reads a PEM encoded certificate chain and private key, the first key
+  /// is used.
+  pub fn from_pem(
+    cert_chain: &[u8],
+    private_key: &[u8],
+  ) -> Result<Self, AnyError> {
+    let cert_chain = load_certs(&mut &*cert_chain)?;
+    // never empty, an error is returned instead
+    let private_key = load_private_keys(private_key)?.remove(0);
+    Ok(Self(TlsKey(cert_chain, private_key)))
+  }
+}
+
+/// How failed requests are retried: after `initial_backoff`, doubled for
+/// each following retry up to `max_backoff`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+  /// Retries after the first attempt, `0` disables retrying.
+  pub max_retries: u32,
+  pub initial_backoff: Duration,
+  pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_retries: 3,
+      initial_backoff: Duration::from_millis(100),
+      max_backoff: Duration::from_secs(5),
+    }
+  }
+}
+
+impl RetryPolicy {
+  fn backoff(&self, retry: u32) -> Duration {
+    self
+      .initial_backoff
+      .saturating_mul(2u32.saturating_pow(retry))
+      .min(self.max_backoff)
+  }
+}
+
+/// Requests the urls starting with `origin` from `mirror` instead, once the
+/// retries of the origin are exhausted, eg. `https://registry.npmjs.org/`
+/// to `https://npm.mirror.example.com/`.
+#[derive(Debug, Clone)]
+pub struct Mirror {
+  pub origin: Url,
+  pub mirror: Url,
+}
+
+impl Mirror {
+  fn rewrite(&self, url: &Url) -> Option<Url> {
+    let path = url.as_str().strip_prefix(self.origin.as_str())?;
+    Url::parse(&format!("{}{}", self.mirror, path)).ok()
+  }
+}
+
+/// An event of the requests of remote modules and npm packages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchEvent {
+  /// Requesting `url` failed, it's requested again after `backoff`.
+  Retry {
+    url: Url,
+    retry: u32,
+    backoff: Duration,
+    error: String,
+  },
+  /// The retries of `url` are exhausted, `mirror_url` is requested instead.
+  MirrorFallback {
+    url: Url,
+    mirror_url: Url,
+    error: String,
+  },
+}
+
+/// Notified about the retries and mirror fallbacks of requests.
+pub trait FetchObserver: Send + Sync {
+  fn on_fetch_event(&self, event: &FetchEvent);
+}
+
+impl<F: Fn(&FetchEvent) + Send + Sync> FetchObserver for F {
+  fn on_fetch_event(&self, event: &FetchEvent) {
+    self(event)
+  }
+}
+
+/// Retries and mirrors of the requests of remote modules and npm packages,
+/// used instead of the default of retrying a few times.
+#[derive(Clone, Default)]
+pub struct RetryOptions {
+  pub policy: RetryPolicy,
+  /// The first mirror with a matching origin is used.
+  pub mirrors: Vec<Mirror>,
+  pub observer: Option<Arc<dyn FetchObserver>>,
+}
+
+impl std::fmt::Debug for RetryOptions {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RetryOptions")
+      .field("policy", &self.policy)
+      .field("mirrors", &self.mirrors)
+      .field("observer", &self.observer.is_some())
+      .finish()
+  }
+}
+
+impl RetryOptions {
+  pub fn attempts(&self, url: Url) -> RetryAttempts {
+    RetryAttempts {
+      options: self,
+      url,
+      retry: 0,
+      mirrored: false,
+    }
+  }
+
+  fn notify(&self, event: FetchEvent) {
+    if let Some(observer) = &self.observer {
+      observer.on_fetch_event(&event);
+    }
+  }
+}
+
+/// The attempts of a request under [`RetryOptions`].
+pub struct RetryAttempts<'a> {
+  options: &'a RetryOptions,
+  url: Url,
+  retry: u32,
+  mirrored: bool,
+}
+
+impl RetryAttempts<'_> {
+  /// The url to request, the one of the mirror after a fallback.
+  pub fn url(&self) -> &Url {
+    &self.url
+  }
+
+  pub fn is_mirrored(&self) -> bool {
+    self.mirrored
+  }
+
+  /// Waits before retrying a failed request, or falls back to the mirror
+  /// once the retries are exhausted. Returns `false` when the request
+  /// should fail with `error`.
+  pub async fn next(&mut self, error: &str) -> bool {
+    if self.retry < self.options.policy.max_retries {
+      let backoff = self.options.policy.backoff(self.retry);
+      self.retry += 1;
+      self.options.notify(FetchEvent::Retry {
+        url: self.url.clone(),
+        retry: self.retry,
+        backoff,
+        error: error.to_string(),
+      });
+      tokio::time::sleep(backoff).await;
+      return true;
+    }
+    if self.mirrored {
+      return false;
+    }
+    let Some(mirror_url) = self
+      .options
+      .mirrors
+      .iter()
+      .find_map(|mirror| mirror.rewrite(&self.url))
+    else {
+      return false;
+    };
+    self.options.notify(FetchEvent::MirrorFallback {
+      url: self.url.clone(),
+      mirror_url: mirror_url.clone(),
+      error: error.to_string(),
+    });
+    self.url = mirror_url;
+    self.retry = 0;
+    self.mirrored = true;
+    true
+  }
+}
+
 pub struct HttpClientProvider {
   options: CreateHttpClientOptions,
   root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
@@ -216,6 +422,8 @@ pub struct HttpClientProvider {
   // so we store these Clients keyed by thread id
   // https://github.com/seanmonstar/reqwest/issues/1148#issuecomment-910868788
   clients_by_thread_id: Mutex<HashMap<ThreadId, deno_fetch::Client>>,
+  token_provider: Option<Arc<dyn TokenProvider>>,
+  retry_options: Option<Arc<RetryOptions>>,
 }
 
 impl std::fmt::Debug for HttpClientProvider {
@@ -238,16 +446,47 @@ impl HttpClientProvider {
       },
       root_cert_store_provider,
       clients_by_thread_id: Default::default(),
+      token_provider: None,
+      retry_options: None,
     }
   }
 
+  /// Authorizes the requests of the created clients with the tokens of
+  /// `token_provider`.
+  pub fn set_token_provider(&mut self, token_provider: Arc<dyn TokenProvider>) {
+    self.token_provider = Some(token_provider);
+  }
+
+  /// Retries the failed requests of the created clients, and the ones of
+  /// the `FileFetcher`, with `retry_options`.
+  pub fn set_retry_options(&mut self, retry_options: RetryOptions) {
+    self.retry_options = Some(Arc::new(retry_options));
+  }
+
+  pub fn retry_options(&self) -> Option<&Arc<RetryOptions>> {
+    self.retry_options.as_ref()
+  }
+
+  /// Configures the proxies, CA certificates and client certificate of the
+  /// created clients.
+  pub fn set_network_options(&mut self, options: HttpNetworkOptions) {
+    self.options.proxy_config = options.proxy;
+    self.options.ca_certs = options.ca_certs;
+    self.options.client_cert_chain_and_key =
+      options.client_cert.map(|cert| cert.0);
+  }
+
   pub fn get_or_create(&self) -> Result<HttpClient, AnyError> {
     use std::collections::hash_map::Entry;
     let thread_id = std::thread::current().id();
     let mut clients = self.clients_by_thread_id.lock();
     let entry = clients.entry(thread_id);
     match entry {
-      Entry::Occupied(entry) => Ok(HttpClient::new(entry.get().clone())),
+      Entry::Occupied(entry) => Ok(HttpClient::new(
+        entry.get().clone(),
+        self.token_provider.clone(),
+        self.retry_options.clone(),
+      )),
       Entry::Vacant(entry) => {
         let client = create_http_client(
           version::DENO_VERSION_INFO.user_agent,
@@ -260,7 +499,11 @@ impl HttpClientProvider {
           },
         )?;
         entry.insert(client.clone());
-        Ok(HttpClient::new(client))
+        Ok(HttpClient::new(
+          client,
+          self.token_provider.clone(),
+          self.retry_options.clone(),
+        ))
       }
     }
   }
@@ -293,9 +536,10 @@ pub enum DownloadError {
   BadResponse(#[from] BadResponseError),
 }
 
-#[derive(Debug)]
 pub struct HttpClient {
   client: deno_fetch::Client,
+  token_provider: Option<Arc<dyn TokenProvider>>,
+  retry_options: Option<Arc<RetryOptions>>,
   // don't allow sending this across threads because then
   // it might be shared accidentally across tokio runtimes
   // which will cause issues
@@ -303,12 +547,28 @@ pub struct HttpClient {
   _unsend_marker: deno_core::unsync::UnsendMarker,
 }
 
+impl std::fmt::Debug for HttpClient {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("HttpClient")
+      .field("client", &self.client)
+      .field("token_provider", &self.token_provider.is_some())
+      .field("retry_options", &self.retry_options)
+      .finish()
+  }
+}
+
 impl HttpClient {
   // DO NOT make this public. You should always be creating one of these from
   // the HttpClientProvider
-  fn new(client: deno_fetch::Client) -> Self {
+  fn new(
+    client: deno_fetch::Client,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    retry_options: Option<Arc<RetryOptions>>,
+  ) -> Self {
     Self {
       client,
+      token_provider,
+      retry_options,
       _unsend_marker: deno_core::unsync::UnsendMarker::default(),
     }
   }
@@ -367,31 +627,13 @@ impl HttpClient {
     &self,
     args: FetchOnceArgs<'a>,
   ) -> Result<FetchOnceResult, AnyError> {
-    let body = http_body_util::Empty::new()
-      .map_err(|never| match never {})
-      .boxed();
-    let mut request = http::Request::new(body);
-    *request.uri_mut() = args.url.as_str().parse()?;
-
-    if let Some(etag) = args.maybe_etag {
-      let if_none_match_val = HeaderValue::from_str(&etag)?;
-      request
-        .headers_mut()
-        .insert(IF_NONE_MATCH, if_none_match_val);
-    }
-    if let Some(auth_token) = args.maybe_auth_token {
-      let authorization_val = HeaderValue::from_str(&auth_token.to_string())?;
-      request
-        .headers_mut()
-        .insert(AUTHORIZATION, authorization_val);
-    } else if let Some((header, value)) = args.maybe_auth {
-      request.headers_mut().insert(header, value);
-    }
-    if let Some(accept) = args.maybe_accept {
-      let accepts_val = HeaderValue::from_str(&accept)?;
-      request.headers_mut().insert(ACCEPT, accepts_val);
-    }
-    let response = match self.client.clone().send(request).await {
+    let maybe_provided_auth = self.provided_auth(&args.url, false).await?;
+    let mut response = match self
+      .client
+      .clone()
+      .send(no_follow_request(&args, maybe_provided_auth)?)
+      .await
+    {
       Ok(resp) => resp,
       Err(err) => {
         if err.is_connect_error() {
@@ -400,6 +642,24 @@ impl HttpClient {
         return Err(err.into());
       }
     };
+    if response.status() == StatusCode::UNAUTHORIZED {
+      if let Some(auth) = self.provided_auth(&args.url, true).await? {
+        response = match self
+          .client
+          .clone()
+          .send(no_follow_request(&args, Some(auth))?)
+          .await
+        {
+          Ok(resp) => resp,
+          Err(err) => {
+            if err.is_connect_error() {
+              return Ok(FetchOnceResult::RequestError(err.to_string()));
+            }
+            return Err(err.into());
+          }
+        };
+      }
+    }
 
     if response.status() == StatusCode::NOT_MODIFIED {
       return Ok(FetchOnceResult::NotModified);
@@ -480,6 +740,34 @@ impl HttpClient {
     maybe_header: Option<(HeaderName, HeaderValue)>,
     progress_guard: &UpdateGuard,
   ) -> Result<Option<Vec<u8>>, DownloadError> {
+    if let Some(retry_options) = &self.retry_options {
+      let mut attempts = retry_options.attempts(url);
+      loop {
+        // the header authorizes the origin, not the mirror
+        let maybe_header = if attempts.is_mirrored() {
+          None
+        } else {
+          maybe_header.clone()
+        };
+        match self
+          .download_inner(
+            attempts.url().clone(),
+            maybe_header,
+            Some(progress_guard),
+          )
+          .await
+        {
+          Err(
+            err @ (DownloadError::BadResponse(_) | DownloadError::Fetch(_)),
+          ) => {
+            if !attempts.next(&err.to_string()).await {
+              return Err(err);
+            }
+          }
+          result => return result,
+        }
+      }
+    }
     crate::util::retry::retry(
       || {
         self.download_inner(
@@ -508,7 +796,23 @@ impl HttpClient {
     maybe_header: Option<(HeaderName, HeaderValue)>,
     progress_guard: Option<&UpdateGuard>,
   ) -> Result<Option<Vec<u8>>, DownloadError> {
-    let (response, _) = self.get_redirected_response(url, maybe_header).await?;
+    let maybe_header = self
+      .provided_auth(&url, false)
+      .await
+      .map_err(DownloadError::Fetch)?
+      .or(maybe_header);
+    let (mut response, _) = self
+      .get_redirected_response(url.clone(), maybe_header)
+      .await?;
+    if response.status() == StatusCode::UNAUTHORIZED {
+      if let Some(auth) = self
+        .provided_auth(&url, true)
+        .await
+        .map_err(DownloadError::Fetch)?
+      {
+        (response, _) = self.get_redirected_response(url, Some(auth)).await?;
+      }
+    }
 
     if response.status() == 404 {
       return Ok(None);
@@ -529,6 +833,30 @@ impl HttpClient {
       .map_err(DownloadError::Fetch)
   }
 
+  /// The authorization header with the token of the token provider for
+  /// `url`, or a refreshed one after it was rejected.
+  async fn provided_auth(
+    &self,
+    url: &Url,
+    refresh: bool,
+  ) -> Result<Option<(HeaderName, HeaderValue)>, AnyError> {
+    let Some(token_provider) = &self.token_provider else {
+      return Ok(None);
+    };
+    let maybe_token = if refresh {
+      token_provider.refresh_token(url).await?
+    } else {
+      token_provider.token(url).await?
+    };
+    match maybe_token {
+      Some(token) => Ok(Some((
+        AUTHORIZATION,
+        HeaderValue::from_str(&token.to_string())?,
+      ))),
+      None => Ok(None),
+    }
+  }
+
   async fn get_redirected_response(
     &self,
     mut url: Url,
@@ -638,6 +966,41 @@ fn resolve_url_from_location(base_url: &Url, location: &str) -> Url {
   }
 }
 
+/// Builds the request of `HttpClient::fetch_no_follow`, authorized with
+/// `maybe_provided_auth` over the auth of `args` when given.
+fn no_follow_request(
+  args: &FetchOnceArgs,
+  maybe_provided_auth: Option<(HeaderName, HeaderValue)>,
+) -> Result<http::Request<deno_fetch::ReqBody>, AnyError> {
+  let body = http_body_util::Empty::new()
+    .map_err(|never| match never {})
+    .boxed();
+  let mut request = http::Request::new(body);
+  *request.uri_mut() = args.url.as_str().parse()?;
+
+  if let Some(etag) = &args.maybe_etag {
+    let if_none_match_val = HeaderValue::from_str(etag)?;
+    request
+      .headers_mut()
+      .insert(IF_NONE_MATCH, if_none_match_val);
+  }
+  if let Some((header, value)) = maybe_provided_auth {
+    request.headers_mut().insert(header, value);
+  } else if let Some(auth_token) = &args.maybe_auth_token {
+    let authorization_val = HeaderValue::from_str(&auth_token.to_string())?;
+    request
+      .headers_mut()
+      .insert(AUTHORIZATION, authorization_val);
+  } else if let Some((header, value)) = args.maybe_auth.clone() {
+    request.headers_mut().insert(header, value);
+  }
+  if let Some(accept) = &args.maybe_accept {
+    let accepts_val = HeaderValue::from_str(accept)?;
+    request.headers_mut().insert(ACCEPT, accepts_val);
+  }
+  Ok(request)
+}
+
 fn resolve_redirect_from_response<B>(
   request_url: &Url,
   response: &http::Response<B>,
@@ -707,6 +1070,8 @@ mod test {
 
   use deno_runtime::deno_tls::rustls::RootCertStore;
 
+  use crate::auth_tokens::AuthTokenData;
+  use crate::auth_tokens::AuthTokens;
   use crate::version;
 
   use super::*;
@@ -738,6 +1103,70 @@ mod test {
     assert_eq!(err.to_string(), "Too many redirects.");
   }
 
+  /// Hands out a token the private test registry rejects, and the one it
+  /// accepts when refreshed.
+  #[derive(Default)]
+  struct StaleTokenProvider {
+    calls: Mutex<Vec<(String, bool)>>,
+  }
+
+  #[async_trait::async_trait(?Send)]
+  impl TokenProvider for StaleTokenProvider {
+    async fn token(
+      &self,
+      url: &Url,
+    ) -> Result<Option<AuthTokenData>, AnyError> {
+      self.calls.lock().push((url.to_string(), false));
+      Ok(Some(AuthTokenData::Bearer("stale-token".to_string())))
+    }
+
+    async fn refresh_token(
+      &self,
+      url: &Url,
+    ) -> Result<Option<AuthTokenData>, AnyError> {
+      self.calls.lock().push((url.to_string(), true));
+      Ok(Some(AuthTokenData::Bearer("private-reg-token".to_string())))
+    }
+  }
+
+  #[tokio::test]
+  async fn test_token_provider_refreshes_rejected_tokens() {
+    let _http_server_guard = test_util::http_server();
+    let token_provider = Arc::new(StaleTokenProvider::default());
+    let mut provider = HttpClientProvider::new(None, None);
+    provider.set_token_provider(token_provider.clone());
+    let client = provider.get_or_create().unwrap();
+    let url = Url::parse("http://localhost:4261/@denotest/basic").unwrap();
+
+    let text = client.download_text(url.clone()).await.unwrap();
+    assert!(text.contains("\"@denotest/basic\""), "{text}");
+    assert_eq!(
+      token_provider.calls.lock().drain(..).collect::<Vec<_>>(),
+      vec![(url.to_string(), false), (url.to_string(), true)]
+    );
+
+    // the token of the provider is sent instead of the configured one
+    let result = client
+      .fetch_no_follow(FetchOnceArgs {
+        url: url.clone(),
+        maybe_accept: None,
+        maybe_etag: None,
+        maybe_auth_token: AuthTokens::new(Some(
+          "configured-token@localhost:4261".to_string(),
+        ))
+        .get(&url),
+        maybe_progress_guard: None,
+        maybe_auth: None,
+      })
+      .await
+      .unwrap();
+    assert!(matches!(result, FetchOnceResult::Code(..)));
+    assert_eq!(
+      token_provider.calls.lock().drain(..).collect::<Vec<_>>(),
+      vec![(url.to_string(), false), (url.to_string(), true)]
+    );
+  }
+
   #[test]
   fn test_resolve_url_from_location_full_1() {
     let url = "http://deno.land".parse::<Url>().unwrap();
@@ -776,10 +1205,70 @@ mod test {
     assert_eq!(new_uri.path(), "/z");
   }
 
+  #[test]
+  fn test_retry_policy_backoff() {
+    let policy = RetryPolicy {
+      max_retries: 5,
+      initial_backoff: Duration::from_millis(100),
+      max_backoff: Duration::from_millis(300),
+    };
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff(2), Duration::from_millis(300));
+    assert_eq!(policy.backoff(40), Duration::from_millis(300));
+  }
+
+  #[tokio::test]
+  async fn test_retry_attempts_mirror_fallback() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let options = RetryOptions {
+      policy: RetryPolicy {
+        max_retries: 1,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+      },
+      mirrors: vec![Mirror {
+        origin: Url::parse("https://registry.npmjs.org/").unwrap(),
+        mirror: Url::parse("https://npm.mirror.example/npm/").unwrap(),
+      }],
+      observer: Some(Arc::new({
+        let events = events.clone();
+        move |event: &FetchEvent| events.lock().push(event.clone())
+      })),
+    };
+    let url = Url::parse("https://registry.npmjs.org/chalk").unwrap();
+    let mirror_url =
+      Url::parse("https://npm.mirror.example/npm/chalk").unwrap();
+    let mut attempts = options.attempts(url.clone());
+    assert!(attempts.next("timed out").await);
+    assert_eq!(attempts.url(), &url);
+    assert!(attempts.next("timed out").await);
+    assert_eq!(attempts.url(), &mirror_url);
+    assert!(attempts.is_mirrored());
+    assert!(attempts.next("timed out").await);
+    assert!(!attempts.next("timed out").await);
+    assert_eq!(
+      events.lock()[1],
+      FetchEvent::MirrorFallback {
+        url: url.clone(),
+        mirror_url,
+        error: "timed out".to_string(),
+      }
+    );
+    assert_eq!(events.lock().len(), 3);
+
+    let mut attempts =
+      options.attempts(Url::parse("https://deno.land/x/mod.ts").unwrap());
+    assert!(attempts.next("timed out").await);
+    assert!(!attempts.next("timed out").await);
+  }
+
   fn create_test_client() -> HttpClient {
     HttpClient::new(
       create_http_client("test_client", CreateHttpClientOptions::default())
         .unwrap(),
+      None,
+      None,
     )
   }
 
@@ -977,6 +1466,8 @@ mod test {
         },
       )
       .unwrap(),
+      None,
+      None,
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
@@ -1024,6 +1515,8 @@ mod test {
           CreateHttpClientOptions::default(),
         )
         .unwrap(),
+        None,
+        None,
       );
 
       let result = client
@@ -1087,6 +1580,8 @@ mod test {
         },
       )
       .unwrap(),
+      None,
+      None,
     );
 
     let result = client
@@ -1142,6 +1637,8 @@ mod test {
         },
       )
       .unwrap(),
+      None,
+      None,
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
@@ -1184,6 +1681,8 @@ mod test {
         },
       )
       .unwrap(),
+      None,
+      None,
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
@@ -1242,6 +1741,8 @@ mod test {
         },
       )
       .unwrap(),
+      None,
+      None,
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
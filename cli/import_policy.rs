@@ -0,0 +1,328 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+
+use deno_config::deno_json::ConfigFile;
+use deno_config::fs::DenoConfigFs;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use deno_semver::jsr::JsrPackageReqReference;
+use deno_semver::npm::NpmPackageReqReference;
+use serde::Deserialize;
+
+use crate::args::jsr_url;
+
+/// Restricts where modules may be imported from. Set with
+/// `DenoRuntimeBuilder::import_policy`, or the `importPolicy` key of the
+/// root `deno.json`:
+///
+/// ```json
+/// {
+///   "importPolicy": {
+///     "allowedHosts": ["deno.land", "*.example.com"],
+///     "denyHttp": true,
+///     "allowedJsr": ["@std"],
+///     "allowedNpm": ["@types", "chalk"]
+///   }
+/// }
+/// ```
+///
+/// The policy applies to the imports of modules and to the redirects of
+/// remote modules. The dependencies of npm packages are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ImportPolicy {
+  /// Hosts remote modules may be imported from, eg. `"deno.land"`, or
+  /// `"*.example.com"` for its subdomains. `None` allows every host.
+  pub allowed_hosts: Option<Vec<String>>,
+  /// Hosts remote modules may not be imported from, even when allowed by
+  /// `allowed_hosts`.
+  #[serde(default)]
+  pub denied_hosts: Vec<String>,
+  /// Forbids `http:` imports, only `https:` is allowed.
+  #[serde(default)]
+  pub deny_http: bool,
+  /// Scopes (`"@std"`) or packages (`"@luca/flag"`) that may be imported
+  /// from JSR. `None` allows every package.
+  pub allowed_jsr: Option<Vec<String>>,
+  /// Scopes (`"@types"`) or packages (`"chalk"`) that may be imported from
+  /// npm. `None` allows every package.
+  pub allowed_npm: Option<Vec<String>>,
+}
+
+/// An import denied by an [`ImportPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Import of \"{specifier}\" denied by the import policy: {reason}")]
+pub struct ImportPolicyError {
+  pub specifier: ModuleSpecifier,
+  pub reason: String,
+}
+
+impl ImportPolicy {
+  /// Reads the `importPolicy` of a `deno.json`, `None` if there's none.
+  /// [`ConfigFile`] drops the keys `deno_config` doesn't know, so the file
+  /// is read again through `fs`, like the workspace discovery does.
+  pub fn from_config_file(
+    config_file: &ConfigFile,
+    fs: &dyn DenoConfigFs,
+  ) -> Result<Option<Self>, AnyError> {
+    let Ok(path) = config_file.specifier.to_file_path() else {
+      return Ok(None);
+    };
+    let text = fs
+      .read_to_string_lossy(&path)
+      .with_context(|| format!("Reading {}", path.display()))?;
+    Self::from_config_text(&text, &path)
+  }
+
+  fn from_config_text(
+    text: &str,
+    path: &Path,
+  ) -> Result<Option<Self>, AnyError> {
+    let value = jsonc_parser::parse_to_serde_value(text, &Default::default())?;
+    let Some(policy) = value
+      .and_then(|mut value| value.get_mut("importPolicy").map(|v| v.take()))
+    else {
+      return Ok(None);
+    };
+    let policy = serde_json::from_value(policy).with_context(|| {
+      format!("Invalid \"importPolicy\" in {}", path.display())
+    })?;
+    Ok(Some(policy))
+  }
+
+  /// Checks a resolved specifier.
+  pub fn check(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<(), ImportPolicyError> {
+    match specifier.scheme() {
+      "http" | "https" => self.check_remote(specifier),
+      "jsr" => match JsrPackageReqReference::from_specifier(specifier) {
+        Ok(req_ref) => self.check_jsr(specifier, &req_ref.req().name),
+        Err(_) => Ok(()),
+      },
+      "npm" => match NpmPackageReqReference::from_specifier(specifier) {
+        Ok(req_ref) => self.check_npm(specifier, &req_ref.req().name),
+        Err(_) => Ok(()),
+      },
+      _ => Ok(()),
+    }
+  }
+
+  /// Checks a remote specifier before it's fetched.
+  pub fn check_remote(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<(), ImportPolicyError> {
+    let deny = |reason: String| {
+      Err(ImportPolicyError {
+        specifier: specifier.clone(),
+        reason,
+      })
+    };
+    if self.deny_http && specifier.scheme() == "http" {
+      return deny(
+        "\"http:\" imports are not allowed, import it over \"https:\" instead."
+          .to_string(),
+      );
+    }
+    // the modules of JSR packages are checked by package instead of host
+    if let Some(name) = jsr_package_name(specifier) {
+      return self.check_jsr(specifier, &name);
+    }
+    let host = specifier.host_str().unwrap_or_default();
+    if self
+      .denied_hosts
+      .iter()
+      .any(|pattern| host_matches(pattern, host))
+    {
+      return deny(format!("the host \"{host}\" is in \"deniedHosts\"."));
+    }
+    if let Some(allowed_hosts) = &self.allowed_hosts {
+      if !allowed_hosts
+        .iter()
+        .any(|pattern| host_matches(pattern, host))
+      {
+        return deny(format!(
+          "the host \"{host}\" is not in \"allowedHosts\", add it to allow the import."
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  fn check_jsr(
+    &self,
+    specifier: &ModuleSpecifier,
+    name: &str,
+  ) -> Result<(), ImportPolicyError> {
+    match &self.allowed_jsr {
+      Some(allowed) if !package_matches(allowed, name) => {
+        Err(ImportPolicyError {
+          specifier: specifier.clone(),
+          reason: format!(
+            "the JSR package \"{name}\" is not in \"allowedJsr\", add it or its scope to allow the import."
+          ),
+        })
+      }
+      _ => Ok(()),
+    }
+  }
+
+  fn check_npm(
+    &self,
+    specifier: &ModuleSpecifier,
+    name: &str,
+  ) -> Result<(), ImportPolicyError> {
+    match &self.allowed_npm {
+      Some(allowed) if !package_matches(allowed, name) => {
+        Err(ImportPolicyError {
+          specifier: specifier.clone(),
+          reason: format!(
+            "the npm package \"{name}\" is not in \"allowedNpm\", add it or its scope to allow the import."
+          ),
+        })
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+  match pattern.strip_prefix("*.") {
+    Some(domain) => host
+      .strip_suffix(domain)
+      .is_some_and(|subdomain| subdomain.ends_with('.')),
+    None => pattern.eq_ignore_ascii_case(host),
+  }
+}
+
+/// Whether `name` is one of `allowed`, or in one of the scopes of it.
+fn package_matches(allowed: &[String], name: &str) -> bool {
+  allowed.iter().any(|entry| {
+    entry == name
+      || (!entry.contains('/')
+        && name
+          .strip_prefix(entry.as_str())
+          .is_some_and(|rest| rest.starts_with('/')))
+  })
+}
+
+/// The name of the JSR package a module of the registry belongs to, eg.
+/// `@std/path` for `https://jsr.io/@std/path/1.0.0/mod.ts`.
+fn jsr_package_name(specifier: &ModuleSpecifier) -> Option<String> {
+  let path = specifier.as_str().strip_prefix(jsr_url().as_str())?;
+  let mut parts = path.split('/');
+  let scope = parts.next().filter(|scope| scope.starts_with('@'))?;
+  let name = parts.next().filter(|name| !name.is_empty())?;
+  Some(format!("{scope}/{name}"))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn check(policy: &ImportPolicy, specifier: &str) -> Result<(), String> {
+    policy
+      .check(&ModuleSpecifier::parse(specifier).unwrap())
+      .map_err(|err| err.reason)
+  }
+
+  #[test]
+  fn import_policy_check() {
+    let policy = ImportPolicy {
+      allowed_hosts: Some(vec![
+        "deno.land".to_string(),
+        "*.example.com".to_string(),
+      ]),
+      denied_hosts: vec!["evil.example.com".to_string()],
+      deny_http: true,
+      allowed_jsr: Some(vec!["@std".to_string()]),
+      allowed_npm: Some(vec!["@types".to_string(), "chalk".to_string()]),
+    };
+    assert!(check(&policy, "https://deno.land/x/mod.ts").is_ok());
+    assert!(check(&policy, "https://cdn.example.com/mod.ts").is_ok());
+    assert!(check(&policy, "https://example.com/mod.ts").is_err());
+    assert_eq!(
+      check(&policy, "https://evil.example.com/mod.ts").unwrap_err(),
+      "the host \"evil.example.com\" is in \"deniedHosts\"."
+    );
+    assert!(check(&policy, "http://deno.land/x/mod.ts").is_err());
+    assert!(check(&policy, "jsr:@std/path@1").is_ok());
+    assert!(check(&policy, "https://jsr.io/@std/path/1.0.0/mod.ts").is_ok());
+    assert!(check(&policy, "jsr:@std2/path").is_err());
+    assert!(check(&policy, "https://jsr.io/@luca/flag/1.0.0/mod.ts").is_err());
+    assert!(check(&policy, "npm:chalk@5").is_ok());
+    assert!(check(&policy, "npm:@types/node").is_ok());
+    assert!(check(&policy, "npm:chalk-extra").is_err());
+    assert!(check(&policy, "file:///main.ts").is_ok());
+  }
+
+  #[test]
+  fn import_policy_from_config_text() {
+    let path = Path::new("/deno.json");
+    let policy = ImportPolicy::from_config_text(
+      r#"{
+        // comments are allowed
+        "importPolicy": { "allowedHosts": ["deno.land"], "denyHttp": true }
+      }"#,
+      path,
+    )
+    .unwrap();
+    assert_eq!(
+      policy,
+      Some(ImportPolicy {
+        allowed_hosts: Some(vec!["deno.land".to_string()]),
+        deny_http: true,
+        ..Default::default()
+      })
+    );
+    assert_eq!(ImportPolicy::from_config_text("{}", path).unwrap(), None);
+    assert!(ImportPolicy::from_config_text(
+      r#"{ "importPolicy": { "allowHosts": [] } }"#,
+      path
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn import_policy_matches_config_schema() {
+    let schema: serde_json::Value =
+      serde_json::from_str(include_str!("./schemas/config-file.v1.json"))
+        .unwrap();
+    let properties = schema["properties"]["importPolicy"]["properties"]
+      .as_object()
+      .unwrap();
+    let config = serde_json::json!({
+      "importPolicy": properties
+        .iter()
+        .map(|(key, value)| {
+          let value = match value["type"].as_str().unwrap() {
+            "boolean" => serde_json::json!(true),
+            _ => serde_json::json!([]),
+          };
+          (key.clone(), value)
+        })
+        .collect::<serde_json::Map<_, _>>(),
+    });
+    let policy = ImportPolicy::from_config_text(
+      &config.to_string(),
+      Path::new("/deno.json"),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+      policy,
+      ImportPolicy {
+        allowed_hosts: Some(vec![]),
+        denied_hosts: vec![],
+        deny_http: true,
+        allowed_jsr: Some(vec![]),
+        allowed_npm: Some(vec![]),
+      }
+    );
+  }
+}
@@ -0,0 +1,204 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Chrome DevTools Protocol sessions between an embedding Rust host and the
+//! V8 inspector of a main worker, without going through the WebSocket
+//! server of `--inspect`.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc;
+use deno_core::futures::StreamExt;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::InspectorMsg;
+use deno_core::InspectorMsgKind;
+use deno_core::InspectorSessionKind;
+use deno_core::InspectorSessionOptions;
+use deno_core::InspectorSessionProxy;
+use deno_core::JsRuntime;
+use serde::Deserialize;
+use tokio::sync::watch;
+
+/// How a main worker waits for debugger sessions before running its main
+/// module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InspectMode {
+  /// Runs right away, like `--inspect`. Debuggers can attach at any time.
+  #[default]
+  Run,
+  /// Waits for a session before running, like `--inspect-wait`.
+  WaitForSession,
+  /// Waits for a session and pauses on the first statement, like
+  /// `--inspect-brk`.
+  BreakOnFirstStatement,
+}
+
+type SessionSender = mpsc::UnboundedSender<InspectorSessionProxy>;
+
+/// Opens Chrome DevTools Protocol sessions with the inspector of a main
+/// worker, eg. to implement a "debug this script" button.
+///
+/// Clones control the same worker.
+#[derive(Clone)]
+pub struct InspectorController {
+  // set once the worker was created
+  session_sender: Arc<watch::Sender<Option<SessionSender>>>,
+}
+
+impl Default for InspectorController {
+  fn default() -> Self {
+    Self {
+      session_sender: Arc::new(watch::Sender::new(None)),
+    }
+  }
+}
+
+impl InspectorController {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Connects a new session, waiting for the worker to be created first.
+  /// The session is closed when it's dropped.
+  pub async fn connect(&self) -> Result<InspectorSession, AnyError> {
+    let mut receiver = self.session_sender.subscribe();
+    let session_sender = receiver
+      .wait_for(|sender| sender.is_some())
+      .await?
+      .clone()
+      .unwrap();
+
+    // outbound messages are sent by the inspector, inbound ones by the host
+    let (outbound_tx, outbound_rx) = mpsc::unbounded();
+    let (inbound_tx, inbound_rx) = mpsc::unbounded();
+    session_sender
+      .unbounded_send(InspectorSessionProxy {
+        tx: outbound_tx,
+        rx: inbound_rx,
+        options: InspectorSessionOptions {
+          kind: InspectorSessionKind::NonBlocking {
+            wait_for_disconnect: false,
+          },
+        },
+      })
+      .map_err(|_| worker_stopped_error())?;
+    Ok(InspectorSession {
+      tx: inbound_tx,
+      rx: outbound_rx,
+      next_id: 1,
+      notifications: VecDeque::new(),
+    })
+  }
+
+  /// Accepts sessions for the inspector of `js_runtime`. Only the first
+  /// worker created with the controller is registered.
+  pub(crate) fn register(&self, js_runtime: &mut JsRuntime) {
+    let session_sender = js_runtime.inspector().borrow().get_session_sender();
+    self.session_sender.send_if_modified(|sender| {
+      if sender.is_some() {
+        return false;
+      }
+      *sender = Some(session_sender);
+      true
+    });
+  }
+}
+
+/// An event sent by the inspector, eg. `Debugger.paused`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InspectorNotification {
+  pub method: String,
+  #[serde(default)]
+  pub params: serde_json::Value,
+}
+
+/// A Chrome DevTools Protocol session with the inspector of a main worker.
+pub struct InspectorSession {
+  tx: mpsc::UnboundedSender<String>,
+  rx: mpsc::UnboundedReceiver<InspectorMsg>,
+  next_id: i32,
+  notifications: VecDeque<InspectorNotification>,
+}
+
+impl InspectorSession {
+  /// Sends the `method` command, eg. `Debugger.enable`, and waits for its
+  /// result. Notifications received in the meantime are queued for
+  /// [`InspectorSession::next_notification`].
+  pub async fn post_message(
+    &mut self,
+    method: &str,
+    params: Option<serde_json::Value>,
+  ) -> Result<serde_json::Value, AnyError> {
+    let id = self.next_id;
+    self.next_id += 1;
+    let message = json!({
+      "id": id,
+      "method": method,
+      "params": params,
+    });
+    self
+      .tx
+      .unbounded_send(message.to_string())
+      .map_err(|_| worker_stopped_error())?;
+
+    loop {
+      let message = self.rx.next().await.ok_or_else(worker_stopped_error)?;
+      match message.kind {
+        InspectorMsgKind::Message(message_id) if message_id == id => {
+          let mut response: serde_json::Value =
+            serde_json::from_str(&message.content)?;
+          if let Some(error) = response.get("error") {
+            return Err(generic_error(format!(
+              "{} failed: {}",
+              method,
+              error.get("message").unwrap_or(error)
+            )));
+          }
+          return Ok(response["result"].take());
+        }
+        InspectorMsgKind::Message(_) => {}
+        InspectorMsgKind::Notification => {
+          self
+            .notifications
+            .push_back(serde_json::from_str(&message.content)?);
+        }
+      }
+    }
+  }
+
+  /// Takes the queued notifications of `method`, keeping the other ones.
+  pub(crate) fn take_notifications(
+    &mut self,
+    method: &str,
+  ) -> Vec<InspectorNotification> {
+    let (taken, kept): (VecDeque<_>, VecDeque<_>) =
+      std::mem::take(&mut self.notifications)
+        .into_iter()
+        .partition(|notification| notification.method == method);
+    self.notifications = kept;
+    taken.into()
+  }
+
+  /// Waits for the next notification. Returns `None` once the worker
+  /// stopped.
+  pub async fn next_notification(&mut self) -> Option<InspectorNotification> {
+    if let Some(notification) = self.notifications.pop_front() {
+      return Some(notification);
+    }
+    loop {
+      let message = self.rx.next().await?;
+      if matches!(message.kind, InspectorMsgKind::Notification) {
+        if let Ok(notification) = serde_json::from_str(&message.content) {
+          return Some(notification);
+        }
+      }
+    }
+  }
+}
+
+fn worker_stopped_error() -> AnyError {
+  generic_error("The worker is no longer running.")
+}
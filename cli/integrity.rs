@@ -0,0 +1,230 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use sha2::Digest;
+
+use crate::args::jsr_url;
+use crate::args::CliLockfile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityAlgorithm {
+  Sha256,
+  Sha384,
+  Sha512,
+}
+
+impl IntegrityAlgorithm {
+  fn prefix(&self) -> &'static str {
+    match self {
+      IntegrityAlgorithm::Sha256 => "sha256",
+      IntegrityAlgorithm::Sha384 => "sha384",
+      IntegrityAlgorithm::Sha512 => "sha512",
+    }
+  }
+
+  fn digest(&self, bytes: &[u8]) -> Vec<u8> {
+    match self {
+      IntegrityAlgorithm::Sha256 => sha2::Sha256::digest(bytes).to_vec(),
+      IntegrityAlgorithm::Sha384 => sha2::Sha384::digest(bytes).to_vec(),
+      IntegrityAlgorithm::Sha512 => sha2::Sha512::digest(bytes).to_vec(),
+    }
+  }
+}
+
+/// The expected hash of a module, written like the subresource integrity
+/// of browsers: `"sha384-<base64 digest>"`. sha256 and sha512 are supported
+/// too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Integrity {
+  algorithm: IntegrityAlgorithm,
+  digest: Vec<u8>,
+}
+
+impl Integrity {
+  /// Hashes `bytes` with `algorithm`, eg. to create the integrity map of a
+  /// deployment.
+  pub fn of(algorithm: IntegrityAlgorithm, bytes: &[u8]) -> Self {
+    Self {
+      algorithm,
+      digest: algorithm.digest(bytes),
+    }
+  }
+
+  pub fn matches(&self, bytes: &[u8]) -> bool {
+    self.algorithm.digest(bytes) == self.digest
+  }
+}
+
+impl FromStr for Integrity {
+  type Err = AnyError;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let Some((prefix, digest)) = value.split_once('-') else {
+      bail!("Invalid integrity \"{value}\", expected eg. \"sha384-<base64>\".");
+    };
+    let algorithm = match prefix {
+      "sha256" => IntegrityAlgorithm::Sha256,
+      "sha384" => IntegrityAlgorithm::Sha384,
+      "sha512" => IntegrityAlgorithm::Sha512,
+      _ => bail!(
+        "Unsupported integrity algorithm \"{prefix}\", use sha256, sha384 or sha512."
+      ),
+    };
+    let Ok(digest) = BASE64_STANDARD.decode(digest) else {
+      bail!("Invalid base64 digest in integrity \"{value}\".");
+    };
+    if digest.len() != algorithm.digest(&[]).len() {
+      bail!("Invalid {prefix} digest length in integrity \"{value}\".");
+    }
+    Ok(Self { algorithm, digest })
+  }
+}
+
+impl fmt::Display for Integrity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}-{}",
+      self.algorithm.prefix(),
+      BASE64_STANDARD.encode(&self.digest)
+    )
+  }
+}
+
+/// Verifies the sources of modules against expected hashes before they are
+/// loaded.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityOptions {
+  /// The expected hashes of modules, by specifier.
+  pub hashes: HashMap<ModuleSpecifier, Integrity>,
+  /// Refuses to load modules without a hash in `hashes`, or in the remote
+  /// checksums of the lockfile. The modules of JSR packages are verified by
+  /// their package manifest instead, and npm packages by the lockfile.
+  pub strict: bool,
+}
+
+/// A module refused by the [`IntegrityOptions`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IntegrityError {
+  #[error("Integrity check failed for \"{specifier}\".\n  Expected: {expected}\n  Actual: {actual}")]
+  Mismatch {
+    specifier: ModuleSpecifier,
+    expected: String,
+    actual: String,
+  },
+  #[error("Refusing to load \"{specifier}\" in strict integrity mode as it has no integrity hash. Add it to the integrity hashes or the lockfile.")]
+  Missing { specifier: ModuleSpecifier },
+}
+
+#[derive(Debug)]
+pub struct IntegrityChecker {
+  options: IntegrityOptions,
+  maybe_lockfile: Option<Arc<CliLockfile>>,
+}
+
+impl IntegrityChecker {
+  pub fn new(
+    options: IntegrityOptions,
+    maybe_lockfile: Option<Arc<CliLockfile>>,
+  ) -> Self {
+    Self {
+      options,
+      maybe_lockfile,
+    }
+  }
+
+  #[allow(clippy::result_large_err)]
+  pub fn verify(
+    &self,
+    specifier: &ModuleSpecifier,
+    source: &[u8],
+  ) -> Result<(), IntegrityError> {
+    if let Some(expected) = self.options.hashes.get(specifier) {
+      if expected.matches(source) {
+        return Ok(());
+      }
+      return Err(IntegrityError::Mismatch {
+        specifier: specifier.clone(),
+        expected: expected.to_string(),
+        actual: Integrity::of(expected.algorithm, source).to_string(),
+      });
+    }
+    let lockfile_checksum = self.maybe_lockfile.as_ref().and_then(|lockfile| {
+      lockfile.lock().remote().get(specifier.as_str()).cloned()
+    });
+    if let Some(expected) = lockfile_checksum {
+      let actual = faster_hex::hex_string(&sha2::Sha256::digest(source));
+      if actual == expected {
+        return Ok(());
+      }
+      return Err(IntegrityError::Mismatch {
+        specifier: specifier.clone(),
+        expected: format!("{expected} (lockfile)"),
+        actual,
+      });
+    }
+    if self.options.strict
+      && !specifier.as_str().starts_with(jsr_url().as_str())
+    {
+      return Err(IntegrityError::Missing {
+        specifier: specifier.clone(),
+      });
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn integrity_parse() {
+    let integrity = Integrity::of(IntegrityAlgorithm::Sha384, b"export {};");
+    let text = integrity.to_string();
+    assert!(text.starts_with("sha384-"));
+    assert_eq!(text.parse::<Integrity>().unwrap(), integrity);
+    assert!(integrity.matches(b"export {};"));
+    assert!(!integrity.matches(b"export {}"));
+
+    assert!("sha1-AAAA".parse::<Integrity>().is_err());
+    assert!("sha256-AAAA".parse::<Integrity>().is_err());
+    assert!("sha256".parse::<Integrity>().is_err());
+  }
+
+  #[test]
+  fn integrity_verify() {
+    let local = ModuleSpecifier::parse("file:///main.ts").unwrap();
+    let remote = ModuleSpecifier::parse("https://deno.land/x/a.ts").unwrap();
+    let jsr = jsr_url().join("@std/path/1.0.0/mod.ts").unwrap();
+    let checker = IntegrityChecker::new(
+      IntegrityOptions {
+        hashes: HashMap::from([(
+          local.clone(),
+          Integrity::of(IntegrityAlgorithm::Sha256, b"a"),
+        )]),
+        strict: true,
+      },
+      None,
+    );
+    assert!(checker.verify(&local, b"a").is_ok());
+    assert!(matches!(
+      checker.verify(&local, b"b"),
+      Err(IntegrityError::Mismatch { .. })
+    ));
+    assert_eq!(
+      checker.verify(&remote, b"a"),
+      Err(IntegrityError::Missing { specifier: remote })
+    );
+    assert!(checker.verify(&jsr, b"a").is_ok());
+  }
+}
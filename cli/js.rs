@@ -1,5 +1,8 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::path::PathBuf;
+
+use deno_core::Extension;
 use log::debug;
 
 #[cfg(not(feature = "hmr"))]
@@ -17,3 +20,25 @@ pub fn deno_isolate_init() -> Option<&'static [u8]> {
     None
   }
 }
+
+/// Creates a startup snapshot of the runtime that also contains the ESM of
+/// `extensions`. Meant to be called from an embedder's build script, so that
+/// the extensions' JavaScript isn't evaluated on every startup.
+///
+/// Workers started from this snapshot must be given the same extensions, in
+/// the same order, created with `init_ops` instead of `init_ops_and_esm`.
+pub fn create_snapshot(snapshot_path: PathBuf, extensions: Vec<Extension>) {
+  use deno_runtime::ops::bootstrap::SnapshotOptions;
+
+  let snapshot_options = SnapshotOptions {
+    ts_version: crate::version::DENO_VERSION_INFO.typescript.to_string(),
+    v8_version: deno_core::v8::VERSION_STRING,
+    target: env!("TARGET").to_string(),
+  };
+
+  deno_runtime::snapshot::create_runtime_snapshot(
+    snapshot_path,
+    snapshot_options,
+    extensions,
+  );
+}
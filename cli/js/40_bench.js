@@ -19,6 +19,7 @@ const {
   ArrayPrototypePush,
   Error,
   MathCeil,
+  MathSqrt,
   SymbolToStringTag,
   TypeError,
 } = primordials;
@@ -200,6 +201,11 @@ function benchStats(
   max,
   all,
 ) {
+  const mean = avg / n;
+  let variance = 0;
+  for (let i = 0; i < n; i++) {
+    variance += (all[i] - mean) ** 2;
+  }
   return {
     n,
     min,
@@ -211,6 +217,7 @@ function benchStats(
     avg: !highPrecision ? (avg / n) : MathCeil(avg / n),
     highPrecision,
     usedExplicitTimers,
+    stddev: MathSqrt(variance / n),
   };
 }
 
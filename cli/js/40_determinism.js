@@ -0,0 +1,226 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+import { core, primordials } from "ext:core/mod.js";
+const {
+  ArrayPrototypeFindIndex,
+  ArrayPrototypeMap,
+  ArrayPrototypeShift,
+  ArrayPrototypeSplice,
+  DatePrototype,
+  DatePrototypeToString,
+  MathMax,
+  NumberIsFinite,
+  ObjectDefineProperty,
+  ObjectSetPrototypeOf,
+  ReflectApply,
+  ReflectConstruct,
+  SafeArrayIterator,
+} = primordials;
+
+const { op_determinism_config, op_determinism_random } = core.ops;
+
+const config = op_determinism_config();
+const startTime = config.startTime;
+let now = startTime;
+
+function replace(object, name, value) {
+  ObjectDefineProperty(object, name, {
+    __proto__: null,
+    value,
+    writable: true,
+    enumerable: false,
+    configurable: true,
+  });
+}
+
+// Math.random()
+
+replace(Math, "random", () => op_determinism_random());
+
+// Date
+
+const OriginalDate = globalThis.Date;
+
+function Date(...args) {
+  if (new.target === undefined) {
+    return DatePrototypeToString(new OriginalDate(now));
+  }
+  return ReflectConstruct(
+    OriginalDate,
+    args.length === 0 ? [now] : args,
+    new.target,
+  );
+}
+ObjectSetPrototypeOf(Date, OriginalDate);
+Date.prototype = DatePrototype;
+replace(Date, "now", () => now);
+replace(DatePrototype, "constructor", Date);
+replace(globalThis, "Date", Date);
+
+// performance
+
+replace(globalThis.performance, "now", () => now - startTime);
+ObjectDefineProperty(globalThis.performance, "timeOrigin", {
+  __proto__: null,
+  value: startTime,
+  enumerable: true,
+  configurable: true,
+});
+
+// Unless the clock is frozen, timers run in the order they are due,
+// advancing the clock to the time they were due at instead of waiting.
+// Paused timers only run when the host advances the clock.
+
+const originalSetTimeout = globalThis.setTimeout;
+const originalClearTimeout = globalThis.clearTimeout;
+// sorted by due time, then by id
+const timers = [];
+let nextId = 1;
+let pumpId = null;
+let paused = config.paused;
+
+function normalizeDelay(delay) {
+  delay = +delay;
+  return NumberIsFinite(delay) ? MathMax(delay, 0) : 0;
+}
+
+function insert(timer) {
+  const index = ArrayPrototypeFindIndex(
+    timers,
+    (other) =>
+      other.due > timer.due ||
+      (other.due === timer.due && other.id > timer.id),
+  );
+  ArrayPrototypeSplice(
+    timers,
+    index === -1 ? timers.length : index,
+    0,
+    timer,
+  );
+  schedulePump();
+}
+
+function schedulePump() {
+  if (!paused && pumpId === null && timers.length > 0) {
+    pumpId = originalSetTimeout(pump, 0);
+  }
+}
+
+function pump() {
+  pumpId = null;
+  if (paused) {
+    return;
+  }
+  const timer = ArrayPrototypeShift(timers);
+  if (timer === undefined) {
+    return;
+  }
+  schedulePump();
+  fire(timer);
+}
+
+function fire(timer) {
+  now = MathMax(now, timer.due);
+  if (timer.repeat) {
+    timer.due = now + MathMax(timer.delay, 1);
+    insert(timer);
+  }
+  if (typeof timer.callback === "function") {
+    ReflectApply(timer.callback, globalThis, timer.args);
+  } else {
+    // like the web timers, strings are evaluated as code
+    (0, eval)(`${timer.callback}`);
+  }
+}
+
+function createTimer(callback, delay, args, repeat) {
+  delay = normalizeDelay(delay);
+  const id = nextId++;
+  insert({ id, callback, delay, due: now + delay, args, repeat });
+  return id;
+}
+
+function clearTimer(id) {
+  const index = ArrayPrototypeFindIndex(timers, (timer) => timer.id === id);
+  if (index !== -1) {
+    ArrayPrototypeSplice(timers, index, 1);
+  }
+  if (timers.length === 0 && pumpId !== null) {
+    originalClearTimeout(pumpId);
+    pumpId = null;
+  }
+}
+
+if (config.virtualTimers) {
+  replace(
+    globalThis,
+    "setTimeout",
+    (callback, delay = 0, ...args) =>
+      createTimer(callback, delay, args, false),
+  );
+  replace(
+    globalThis,
+    "setInterval",
+    (callback, delay = 0, ...args) => createTimer(callback, delay, args, true),
+  );
+  replace(globalThis, "clearTimeout", (id = 0) => clearTimer(id));
+  replace(globalThis, "clearInterval", (id = 0) => clearTimer(id));
+}
+
+// Called by the host, see `CliMainWorker::advance_time`.
+
+function advance(ms) {
+  const target = now + ms;
+  while (timers.length > 0 && timers[0].due <= target) {
+    fire(ArrayPrototypeShift(timers));
+    core.runMicrotasks();
+  }
+  now = MathMax(now, target);
+}
+
+function pause() {
+  paused = true;
+  if (pumpId !== null) {
+    originalClearTimeout(pumpId);
+    pumpId = null;
+  }
+}
+
+function resume() {
+  paused = false;
+  schedulePump();
+}
+
+function pendingTimers() {
+  return ArrayPrototypeMap(timers, (timer) => ({
+    id: timer.id,
+    dueIn: timer.due - now,
+    repeat: timer.repeat,
+  }));
+}
+
+function currentTime() {
+  return now;
+}
+
+// APIs reporting on the host
+
+if (config.denyNondeterministicApis) {
+  const names = [
+    "hostname",
+    "loadavg",
+    "memoryUsage",
+    "networkInterfaces",
+    "osUptime",
+    "systemMemoryInfo",
+  ];
+  for (const name of new SafeArrayIterator(names)) {
+    replace(globalThis.Deno, name, () => {
+      throw new globalThis.Deno.errors.NotSupported(
+        `Deno.${name}() is not available in deterministic mode`,
+      );
+    });
+  }
+}
+
+export { advance, currentTime, pause, pendingTimers, resume };
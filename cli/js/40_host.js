@@ -0,0 +1,128 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+import { core, primordials } from "ext:core/mod.js";
+const {
+  ArrayPrototypeIndexOf,
+  ArrayPrototypePush,
+  ArrayPrototypeShift,
+  ArrayPrototypeSlice,
+  ArrayPrototypeSplice,
+  ObjectDefineProperty,
+  ObjectFreeze,
+  ObjectPrototypeIsPrototypeOf,
+  PromisePrototype,
+  SafeArrayIterator,
+  TypeError,
+} = primordials;
+
+const { op_host_recv, op_host_restored_state, op_host_send } = core.ops;
+
+const listeners = [];
+// messages received after the last listener was removed, delivered once a
+// listener is registered again
+const queuedMessages = [];
+let receiving = false;
+let pendingRecv = null;
+let snapshotCallback = null;
+
+function send(message) {
+  op_host_send(message);
+}
+
+function dispatch(message) {
+  for (
+    const listener of new SafeArrayIterator(ArrayPrototypeSlice(listeners))
+  ) {
+    listener(message);
+  }
+}
+
+async function receiveLoop() {
+  try {
+    // listeners are never called before `onMessage()` returns
+    await null;
+    while (queuedMessages.length > 0 && listeners.length > 0) {
+      dispatch(ArrayPrototypeShift(queuedMessages));
+    }
+    while (listeners.length > 0) {
+      pendingRecv = op_host_recv();
+      const result = await pendingRecv;
+      pendingRecv = null;
+      // the host dropped its sender
+      if (result === null) {
+        return;
+      }
+      if (listeners.length === 0) {
+        ArrayPrototypePush(queuedMessages, result[0]);
+        return;
+      }
+      dispatch(result[0]);
+    }
+  } finally {
+    receiving = false;
+  }
+}
+
+function onMessage(listener) {
+  if (typeof listener !== "function") {
+    throw new TypeError("Listener must be a function");
+  }
+  ArrayPrototypePush(listeners, listener);
+  if (!receiving) {
+    receiving = true;
+    receiveLoop();
+  } else if (listeners.length === 1 && pendingRecv !== null) {
+    core.refOpPromise(pendingRecv);
+  }
+  return () => {
+    const index = ArrayPrototypeIndexOf(listeners, listener);
+    if (index === -1) {
+      return;
+    }
+    ArrayPrototypeSplice(listeners, index, 1);
+    // don't keep the event loop alive without anyone listening
+    if (listeners.length === 0 && pendingRecv !== null) {
+      core.unrefOpPromise(pendingRecv);
+    }
+  };
+}
+
+function onSnapshot(callback) {
+  if (typeof callback !== "function") {
+    throw new TypeError("Callback must be a function");
+  }
+  snapshotCallback = callback;
+}
+
+// Called by the host when the worker is paused, see
+// `CliMainWorker::run_until_paused`. The event loop of a paused worker isn't
+// polled anymore, so the callback can't be async.
+function takeSnapshot() {
+  if (snapshotCallback === null) {
+    return null;
+  }
+  const state = snapshotCallback();
+  if (ObjectPrototypeIsPrototypeOf(PromisePrototype, state)) {
+    throw new TypeError("Snapshot callback must not return a promise");
+  }
+  return state;
+}
+
+// keeps `Deno.host.call()` of `ext:cli/40_host_fn.js`
+const hostFns = globalThis.Deno.host;
+
+ObjectDefineProperty(globalThis.Deno, "host", {
+  __proto__: null,
+  value: ObjectFreeze({
+    ...hostFns,
+    send,
+    onMessage,
+    onSnapshot,
+    restoredState: op_host_restored_state() ?? null,
+  }),
+  enumerable: true,
+  configurable: false,
+  writable: false,
+});
+
+export { takeSnapshot };
@@ -0,0 +1,73 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+import { core, primordials } from "ext:core/mod.js";
+const {
+  ArrayPrototypePop,
+  ObjectDefineProperty,
+  ObjectFreeze,
+  StringPrototypeSplit,
+  TypeError,
+} = primordials;
+
+const {
+  op_host_fn_call,
+  op_host_fn_call_async,
+  op_host_fn_is_async,
+  op_host_fn_list,
+} = core.ops;
+
+// Calls the host function registered under `name`, including the ones
+// registered after the worker was created.
+function call(name, args) {
+  const isAsync = op_host_fn_is_async(name);
+  if (isAsync === null) {
+    throw new TypeError(`Unknown host function '${name}'`);
+  }
+  return isAsync
+    ? op_host_fn_call_async(name, args ?? null)
+    : op_host_fn_call(name, args ?? null);
+}
+
+function defineHostFn(name, isAsync) {
+  const path = StringPrototypeSplit(name, ".");
+  const key = ArrayPrototypePop(path);
+  let target = globalThis;
+  for (let i = 0; i < path.length; ++i) {
+    const part = path[i];
+    if (target[part] === undefined) {
+      target[part] = {};
+    } else if (
+      typeof target[part] !== "object" && typeof target[part] !== "function"
+    ) {
+      throw new TypeError(
+        `Cannot define host function '${name}', '${part}' isn't an object`,
+      );
+    }
+    target = target[part];
+  }
+  const hostFn = isAsync
+    ? (args) => op_host_fn_call_async(name, args ?? null)
+    : (args) => op_host_fn_call(name, args ?? null);
+  ObjectDefineProperty(target, key, {
+    __proto__: null,
+    value: hostFn,
+    enumerable: true,
+    configurable: true,
+    writable: true,
+  });
+}
+
+const hostFns = op_host_fn_list();
+for (let i = 0; i < hostFns.length; ++i) {
+  const { 0: name, 1: isAsync } = hostFns[i];
+  defineHostFn(name, isAsync);
+}
+
+// `ext:cli/40_host.js` adds the message channel to the namespace
+ObjectDefineProperty(globalThis.Deno, "host", {
+  __proto__: null,
+  value: ObjectFreeze({ call }),
+  enumerable: true,
+  configurable: true,
+  writable: false,
+});
@@ -0,0 +1,115 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+import { core, primordials } from "ext:core/mod.js";
+import { Request } from "ext:deno_fetch/23_request.js";
+import {
+  ResponsePrototype,
+  toInnerResponse,
+} from "ext:deno_fetch/23_response.js";
+import { ReadableStream } from "ext:deno_web/06_streams.js";
+const {
+  FunctionPrototypeCall,
+  ObjectPrototypeIsPrototypeOf,
+  TypeError,
+} = primordials;
+
+const {
+  op_serve_adapter_next,
+  op_serve_adapter_read,
+  op_serve_adapter_respond,
+  op_serve_adapter_write,
+} = core.ops;
+
+function requestBody(rid) {
+  return new ReadableStream({
+    async pull(controller) {
+      const chunk = await op_serve_adapter_read(rid);
+      if (chunk === null) {
+        controller.close();
+      } else {
+        controller.enqueue(chunk);
+      }
+    },
+  });
+}
+
+async function callHandler(handler, request) {
+  const { rid, method, url, headers, hasBody } = request;
+  const response = await FunctionPrototypeCall(
+    handler.fetch,
+    handler,
+    new Request(url, {
+      method,
+      headers,
+      body: hasBody ? requestBody(rid) : null,
+    }),
+  );
+  if (!ObjectPrototypeIsPrototypeOf(ResponsePrototype, response)) {
+    throw new TypeError(
+      "Return value from fetch handler must be a response or a promise resolving to a response",
+    );
+  }
+  return response;
+}
+
+async function handleRequest(handler, request) {
+  const rid = request.rid;
+  try {
+    let response;
+    try {
+      response = await callHandler(handler, request);
+    } catch (error) {
+      // deno-lint-ignore no-console
+      console.error(error);
+      op_serve_adapter_respond(rid, 500, []);
+      return;
+    }
+    const inner = toInnerResponse(response);
+    op_serve_adapter_respond(rid, inner.status, inner.headerList);
+    if (response.body === null) {
+      return;
+    }
+    const reader = response.body.getReader();
+    while (true) {
+      const { value, done } = await reader.read();
+      if (done) {
+        return;
+      }
+      // the host dropped the body
+      if (!(await op_serve_adapter_write(rid, value))) {
+        await reader.cancel();
+        return;
+      }
+    }
+  } catch (error) {
+    // deno-lint-ignore no-console
+    console.error(error);
+  } finally {
+    core.tryClose(rid);
+  }
+}
+
+async function serveRequests(handler) {
+  while (true) {
+    const request = await op_serve_adapter_next();
+    // every `ServeAdapter` of the host was dropped
+    if (request === null) {
+      return;
+    }
+    handleRequest(handler, request);
+  }
+}
+
+// Called by the host once the main module was evaluated, see
+// `CliMainWorker::execute_main_module`.
+function serve(namespace) {
+  const handler = namespace.default;
+  if (typeof handler?.fetch !== "function") {
+    throw new TypeError(
+      "The main module must have a default export with a fetch method to serve requests",
+    );
+  }
+  serveRequests(handler);
+}
+
+export { serve };
@@ -0,0 +1,21 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+import { core, primordials } from "ext:core/mod.js";
+const {
+  ObjectDefineProperty,
+  SymbolFor,
+} = primordials;
+
+const {
+  op_wasi_run_component,
+} = core.ops;
+
+// the modules WASI components are loaded as call this with their
+// `import.meta.url`, see `cli/wasi.rs`
+ObjectDefineProperty(globalThis, SymbolFor("Deno.runWasiComponent"), {
+  __proto__: null,
+  value: (specifier) => op_wasi_run_component(specifier),
+  enumerable: false,
+  configurable: false,
+  writable: false,
+});
@@ -3,11 +3,18 @@
 use crate::args::jsr_url;
 use crate::file_fetcher::FileFetcher;
 use dashmap::DashMap;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_graph::packages::JsrPackageInfo;
 use deno_graph::packages::JsrPackageVersionInfo;
+use deno_semver::jsr::JsrPackageReqReference;
 use deno_semver::package::PackageNv;
 use deno_semver::package::PackageReq;
+use deno_semver::Version;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// This is similar to a subset of `JsrCacheResolver` which fetches rather than
@@ -120,3 +127,84 @@ pub fn partial_jsr_package_version_info_from_slice(
     module_graph_2: None,
   })
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsrPackageVersion {
+  pub version: Version,
+  pub yanked: bool,
+}
+
+/// Metadata of a JSR package, see [`jsr_package_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsrPackageMetadata {
+  /// Eg. `"@std/path"`.
+  pub name: String,
+  /// All published versions, newest first.
+  pub versions: Vec<JsrPackageVersion>,
+  /// The version the specifier resolved to.
+  pub version: Version,
+  /// Exports of the resolved version, eg. `"./posix"` to
+  /// `"./posix/mod.ts"`.
+  pub exports: BTreeMap<String, String>,
+  /// Files of the resolved version, eg. `"/posix/mod.ts"`.
+  pub modules: Vec<String>,
+}
+
+/// Fetches the metadata of the JSR package `specifier` refers to, eg.
+/// `"jsr:@std/path@^1"` or `"@std/path"`, from the registry.
+pub async fn jsr_package_metadata(
+  file_fetcher: &Arc<FileFetcher>,
+  specifier: &str,
+) -> Result<JsrPackageMetadata, AnyError> {
+  let req_ref = if specifier.starts_with("jsr:") {
+    JsrPackageReqReference::from_str(specifier)
+  } else {
+    JsrPackageReqReference::from_str(&format!("jsr:{specifier}"))
+  }
+  .with_context(|| format!("Invalid JSR specifier '{}'.", specifier))?;
+  let req = req_ref.req();
+
+  let resolver = JsrFetchResolver::new(file_fetcher.clone());
+  let package_info = resolver
+    .package_info(&req.name)
+    .await
+    .ok_or_else(|| anyhow!("JSR package not found: {}", req.name))?;
+  let nv = resolver
+    .req_to_nv(req)
+    .await
+    .ok_or_else(|| anyhow!("No version of {} matches '{}'.", req.name, req))?;
+
+  // the resolver drops the module graph and the manifest, so fetch the
+  // complete version info
+  let meta_url = jsr_url()
+    .join(&format!("{}/{}_meta.json", &nv.name, &nv.version))
+    .context("Invalid JSR package name.")?;
+  let file = file_fetcher.fetch_bypass_permissions(&meta_url).await?;
+  let version_info =
+    serde_json::from_slice::<JsrPackageVersionInfo>(&file.source)
+      .with_context(|| format!("Invalid version metadata of {}.", nv))?;
+
+  let mut versions = package_info
+    .versions
+    .iter()
+    .map(|(version, info)| JsrPackageVersion {
+      version: version.clone(),
+      yanked: info.yanked,
+    })
+    .collect::<Vec<_>>();
+  versions.sort_by(|a, b| b.version.cmp(&a.version));
+  let mut modules = version_info.manifest.keys().cloned().collect::<Vec<_>>();
+  modules.sort();
+  Ok(JsrPackageMetadata {
+    name: nv.name,
+    versions,
+    version: nv.version,
+    exports: version_info
+      .exports()
+      .map(|(export, path)| (export.to_string(), path.to_string()))
+      .collect(),
+    modules,
+  })
+}
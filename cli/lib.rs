@@ -0,0 +1,3284 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Library target of the Deno CLI. It allows embedding the same module
+//! loading, npm resolution and worker setup used by `deno run` into other
+//! Rust applications.
+//!
+//! The async functions exposed here must be driven by a current thread tokio
+//! runtime, for example the one created by
+//! [`deno_runtime::tokio_util::create_and_run_current_thread`].
+
+mod args;
+mod auth_tokens;
+mod cache;
+mod cdp;
+mod determinism;
+mod emit;
+mod entrypoint;
+mod errors;
+mod factory;
+mod file_fetcher;
+mod graph_container;
+mod graph_util;
+mod host;
+mod host_fn;
+mod http_util;
+mod import_policy;
+mod inspector;
+mod integrity;
+mod js;
+mod jsr;
+mod lsp;
+mod module_loader;
+mod node;
+mod npm;
+mod ops;
+mod remote_eval;
+mod resolver;
+mod serve_adapter;
+mod shared;
+mod source_map;
+mod standalone;
+mod task_runner;
+mod tools;
+mod tsc;
+mod util;
+mod version;
+mod wasi;
+mod worker;
+
+use crate::args::flags_from_vec;
+use crate::args::CacheSetting;
+use crate::args::DenoSubcommand;
+use crate::args::ReplFlags;
+use crate::args::RunFlags;
+use crate::args::TypeCheckMode;
+use crate::errors::exit_with_message;
+use crate::errors::format_js_error;
+use crate::factory::CliFactory;
+use crate::factory::EmbedderOptions;
+use crate::file_fetcher::File;
+use crate::graph_container::ModuleGraphContainer;
+use crate::tools::run::ExtensionsFactory;
+use crate::util::display;
+use crate::util::stdio::pipe_from_async_reader;
+use crate::util::stdio::pipe_to_async_writer;
+use crate::util::stdio::pipe_to_writer;
+use crate::util::sync::AsyncFlag;
+use crate::util::v8::get_v8_flags_from_env;
+use crate::util::v8::init_v8_flags;
+use crate::worker::CliMainWorkerFactory;
+use crate::worker::WebWorkerExtensionsFactory;
+
+pub use crate::args::ConfigFlag;
+pub use crate::args::ErrorFormat;
+pub use crate::args::Flags;
+pub use crate::args::FmtOptions;
+pub use crate::args::FmtOptionsConfig;
+pub use crate::args::NpmRegistriesConfig;
+pub use crate::args::NpmRegistry;
+pub use crate::args::PermissionFlags;
+pub use crate::args::WatchFlagsWithPaths;
+pub use crate::auth_tokens::AuthTokenData;
+pub use crate::auth_tokens::TokenProvider;
+pub use crate::cache::ModuleCache;
+pub use crate::cache::ModuleCachePruneOptions;
+pub use crate::cache::ModuleCachePruneReport;
+pub use crate::cdp::CallFrame;
+pub use crate::cdp::PositionTickInfo;
+pub use crate::cdp::Profile;
+pub use crate::cdp::ProfileNode;
+pub use crate::determinism::DeterminismOptions;
+pub use crate::determinism::DeterministicClock;
+pub use crate::determinism::PendingTimer;
+pub use crate::emit::TransformPass;
+pub use crate::emit::TranspileConfig;
+pub use crate::emit::TranspileStrategy;
+pub use crate::entrypoint::main;
+pub use crate::errors::set_error_formatter;
+pub use crate::errors::ErrorFormatter;
+pub use crate::errors::JsonErrorFormatter;
+pub use crate::graph_util::NotCachedError;
+pub use crate::host::HostChannel;
+pub use crate::host_fn::HostFn;
+pub use crate::host_fn::HostFnResult;
+pub use crate::http_util::ClientCertificate;
+pub use crate::http_util::FetchEvent;
+pub use crate::http_util::FetchObserver;
+pub use crate::http_util::HttpNetworkOptions;
+pub use crate::http_util::Mirror;
+pub use crate::http_util::RetryOptions;
+pub use crate::http_util::RetryPolicy;
+pub use crate::import_policy::ImportPolicy;
+pub use crate::import_policy::ImportPolicyError;
+pub use crate::inspector::InspectMode;
+pub use crate::inspector::InspectorController;
+pub use crate::inspector::InspectorNotification;
+pub use crate::inspector::InspectorSession;
+pub use crate::integrity::Integrity;
+pub use crate::integrity::IntegrityAlgorithm;
+pub use crate::integrity::IntegrityError;
+pub use crate::integrity::IntegrityOptions;
+pub use crate::js::create_snapshot;
+pub use crate::jsr::JsrPackageMetadata;
+pub use crate::jsr::JsrPackageVersion;
+pub use crate::lsp::create_lsp_service;
+pub use crate::lsp::start_lsp_with_io;
+pub use crate::lsp::HeadlessLanguageServer;
+pub use crate::lsp::LanguageServer;
+pub use crate::lsp::SourceLocation;
+pub use crate::lsp::TextEdit;
+pub use crate::lsp::TextPosition;
+pub use crate::lsp::TextRange;
+pub use crate::lsp::WorkspaceEdit;
+pub use crate::npm::LifecycleScriptsDecision;
+pub use crate::npm::LifecycleScriptsOutcome;
+pub use crate::npm::LifecycleScriptsPackage;
+pub use crate::npm::LifecycleScriptsPolicy;
+pub use crate::npm::LifecycleScriptsReport;
+pub use crate::npm::LifecycleScriptsReportEntry;
+pub use crate::npm::NpmStore;
+pub use crate::npm::NpmStoreGcReport;
+pub use crate::remote_eval::RemoteEvalOptions;
+pub use crate::remote_eval::RemoteEvalServer;
+pub use crate::resolver::HostModuleResolution;
+pub use crate::resolver::HostModuleResolver;
+pub use crate::serve_adapter::ServeAdapter;
+pub use crate::serve_adapter::ServeBody;
+pub use crate::serve_adapter::ServeListener;
+pub use crate::serve_adapter::ServeServer;
+pub use crate::source_map::MappedJsError;
+pub use crate::source_map::MappedStackFrame;
+pub use crate::source_map::SourceMapService;
+pub use crate::standalone::inspect_binary;
+pub use crate::standalone::Aes256GcmCipher;
+pub use crate::standalone::BinaryFile;
+pub use crate::standalone::BinaryMetadata;
+pub use crate::standalone::PayloadCipher;
+pub use crate::tools::bench::run_benchmarks_for_embedder;
+pub use crate::tools::bench::run_benchmarks_with_custom_reporter;
+pub use crate::tools::bench::BenchDescription;
+pub use crate::tools::bench::BenchFailureReport;
+pub use crate::tools::bench::BenchMeasurement;
+pub use crate::tools::bench::BenchMeasurementStats;
+pub use crate::tools::bench::BenchPlan;
+pub use crate::tools::bench::BenchReport;
+pub use crate::tools::bench::BenchReporter;
+pub use crate::tools::bench::BenchResult;
+pub use crate::tools::bench::BenchRunReport;
+pub use crate::tools::bench::BenchStats;
+pub use crate::tools::bundle::bundle;
+pub use crate::tools::bundle::BundleOptions;
+pub use crate::tools::bundle::BundleOutput;
+pub use crate::tools::bundle::BundleSourceMap;
+pub use crate::tools::check::check_specifiers;
+pub use crate::tools::check::TscDiagnostic;
+pub use crate::tools::check::TscDiagnosticRange;
+pub use crate::tools::check::TypeCheckDaemon;
+pub use crate::tools::compile::create_archive;
+pub use crate::tools::compile::create_archive_with_options;
+pub use crate::tools::compile::create_binary;
+pub use crate::tools::compile::CompileOptions;
+pub use crate::tools::coverage::coverage_report;
+pub use crate::tools::coverage::BranchCoverageItem;
+pub use crate::tools::coverage::CodeCoverage;
+pub use crate::tools::coverage::CoverageReport;
+pub use crate::tools::coverage::CoverageReportOptions;
+pub use crate::tools::coverage::FunctionCoverageItem;
+pub use crate::tools::doc::generate_docs;
+pub use crate::tools::doc::generate_html_docs;
+pub use crate::tools::doc::DocOptions;
+pub use crate::tools::doc::HtmlDocOptions;
+pub use crate::tools::fmt::format_source;
+pub use crate::tools::import_map::pin_remote_specifiers;
+pub use crate::tools::import_map::ImportMapJson;
+pub use crate::tools::info::build_graph_for_embedder;
+pub use crate::tools::info::ModuleGraphDependency;
+pub use crate::tools::info::ModuleGraphInfo;
+pub use crate::tools::info::ModuleGraphModule;
+pub use crate::tools::kernel::CompleteReply;
+pub use crate::tools::kernel::ExecuteOutcome;
+pub use crate::tools::kernel::ExecuteValue;
+pub use crate::tools::kernel::InspectReply;
+pub use crate::tools::kernel::InterruptHandle;
+pub use crate::tools::kernel::Kernel;
+pub use crate::tools::kernel::KernelError;
+pub use crate::tools::kernel::ReplKernel;
+pub use crate::tools::lint::lint_source;
+pub use crate::tools::lint::source::LintDiagnostic;
+pub use crate::tools::lint::source::LintFix;
+pub use crate::tools::lint::source::LintFixChange;
+pub use crate::tools::lint::source::LintPosition;
+pub use crate::tools::lint::source::LintRange;
+pub use crate::tools::lockfile::Lockfile;
+pub use crate::tools::lockfile::LockfileAdditions;
+pub use crate::tools::lockfile::LockfileError;
+pub use crate::tools::lockfile::LockfileVerification;
+pub use crate::tools::npm::install_packages;
+pub use crate::tools::npm::NpmInstallEvent;
+pub use crate::tools::npm::NpmInstallOptions;
+pub use crate::tools::npm::NpmInstallReport;
+pub use crate::tools::permissions_analyze::analyze_permissions;
+pub use crate::tools::permissions_analyze::ApiUsage;
+pub use crate::tools::permissions_analyze::PermissionDomain;
+pub use crate::tools::permissions_analyze::PermissionsAnalysis;
+pub use crate::tools::registry::outdated_dependencies;
+pub use crate::tools::registry::publish_packages;
+pub use crate::tools::registry::update_dependencies;
+pub use crate::tools::registry::ConfigFileChange;
+pub use crate::tools::registry::DependencyUpdate;
+pub use crate::tools::registry::OutdatedDependency;
+pub use crate::tools::registry::OutdatedOptions;
+pub use crate::tools::registry::PublishDiagnosticLevel;
+pub use crate::tools::registry::PublishError;
+pub use crate::tools::registry::PublishOptions;
+pub use crate::tools::registry::PublishReport;
+pub use crate::tools::registry::PublishReportDiagnostic;
+pub use crate::tools::registry::PublishedFile;
+pub use crate::tools::registry::PublishedPackage;
+pub use crate::tools::registry::UpdateDependenciesOptions;
+pub use crate::tools::registry::UpdateReport;
+pub use crate::tools::repl::ReplCompletions;
+pub use crate::tools::repl::ReplOutput;
+pub use crate::tools::repl::ReplSession;
+pub use crate::tools::run::hmr::HmrController;
+pub use crate::tools::run::hmr::HmrObserver;
+pub use crate::tools::run::profiler::CpuProfile;
+pub use crate::tools::run::profiler::Profiler;
+pub use crate::tools::task::list_tasks;
+pub use crate::tools::task::spawn_task;
+pub use crate::tools::task::TaskExitStatus;
+pub use crate::tools::task::TaskHandle;
+pub use crate::tools::task::TaskInfo;
+pub use crate::tools::task::TaskRunOptions;
+pub use crate::tools::task::TaskSource;
+pub use crate::tools::test::run_tests_for_embedder;
+pub use crate::tools::test::TestCaseReport;
+pub use crate::tools::test::TestCaseStatus;
+pub use crate::tools::test::TestFailureReport;
+pub use crate::tools::test::TestReport;
+pub use crate::tools::test::TestSuiteReport;
+pub use crate::tools::vendor::vendor_dependencies;
+pub use crate::tools::vendor::VendorEvent;
+pub use crate::tsc::DiagnosticCategory;
+pub use crate::util::file_watcher::FileWatcher;
+pub use crate::util::file_watcher::FileWatcherBackend;
+pub use crate::util::file_watcher::FileWatcherBuilder;
+pub use crate::util::logger::set_log_sink;
+pub use crate::util::logger::LogEvent;
+pub use crate::util::logger::LogSink;
+pub use crate::util::progress_bar::ProgressEvent;
+pub use crate::util::progress_bar::ProgressMessagePrompt;
+pub use crate::util::progress_bar::ProgressReporter;
+pub use crate::util::stdio::ChannelWriter;
+pub use crate::wasi::WasiOptions;
+pub use crate::wasi::WasiPreopenedDir;
+pub use crate::worker::CliMainWorker;
+pub use crate::worker::ExecutionLimits;
+pub use crate::worker::ModuleHandle;
+pub use crate::worker::PauseHandle;
+pub use crate::worker::PoolWorkerId;
+pub use crate::worker::RunOutcome;
+pub use crate::worker::UnstableApiError;
+pub use crate::worker::WorkerLimitError;
+pub use crate::worker::WorkerLimits;
+pub use crate::worker::WorkerObserver;
+pub use crate::worker::WorkerPool;
+pub use crate::worker::WorkerProgress;
+pub use crate::worker::WorkerStateSnapshot;
+pub use crate::worker::WorkerStats;
+pub use crate::worker::WorkerStatsHandle;
+
+pub use bytes::Bytes;
+pub use deno_ast::MediaType;
+pub use deno_config::deno_json::LintRulesConfig;
+pub use deno_config::deno_json::NodeModulesDirMode;
+use deno_core::anyhow::bail;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_core::v8;
+use deno_core::Extension;
+use deno_core::ModuleSpecifier;
+pub use deno_doc::DocNode;
+pub use deno_doc::DocNodeKind;
+pub use deno_doc::Location;
+use deno_graph::GraphKind;
+use deno_graph::ModuleGraph;
+use deno_npm::resolution::SnapshotFromLockfileError;
+pub use deno_runtime::deno_broadcast_channel::HostSubscription;
+pub use deno_runtime::deno_broadcast_channel::InMemoryBroadcastChannel;
+pub use deno_runtime::deno_fetch::FetchInterceptFuture;
+pub use deno_runtime::deno_fetch::FetchInterception;
+pub use deno_runtime::deno_fetch::FetchInterceptor;
+pub use deno_runtime::deno_fetch::ProxyConfig;
+pub use deno_runtime::deno_fetch::ProxyRule;
+pub use deno_runtime::deno_ffi::DlopenInterceptor;
+pub use deno_runtime::deno_ffi::DlopenTarget;
+pub use deno_runtime::deno_ffi::VirtualLibrary;
+pub use deno_runtime::deno_fs::FileSystem;
+pub use deno_runtime::deno_fs::InMemoryFs;
+pub use deno_runtime::deno_fs::RealFs;
+use deno_runtime::deno_io::Stdio;
+use deno_runtime::deno_io::StdioPipe;
+use deno_runtime::deno_kv::dynamic::DynamicDbHandler;
+pub use deno_runtime::deno_kv::DatabaseHandler;
+pub use deno_runtime::deno_net::ConnectInterceptor;
+pub use deno_runtime::deno_net::ConnectTarget;
+pub use deno_runtime::deno_permissions::audit::set_auditor;
+pub use deno_runtime::deno_permissions::rules::NetProtocol;
+pub use deno_runtime::deno_permissions::rules::NetRule;
+pub use deno_runtime::deno_permissions::rules::NetRuleError;
+pub use deno_runtime::deno_permissions::rules::PathRule;
+pub use deno_runtime::deno_permissions::rules::PathRuleError;
+pub use deno_runtime::deno_permissions::set_prompter;
+pub use deno_runtime::deno_permissions::AsyncPermissionPrompter;
+pub use deno_runtime::deno_permissions::AsyncPromptAdapter;
+pub use deno_runtime::deno_permissions::PermissionAuditEntry;
+pub use deno_runtime::deno_permissions::PermissionAuditor;
+pub use deno_runtime::deno_permissions::PermissionPrompter;
+pub use deno_runtime::deno_permissions::PermissionRules;
+use deno_runtime::deno_permissions::Permissions;
+pub use deno_runtime::deno_permissions::PermissionsContainer;
+pub use deno_runtime::deno_permissions::PermissionsOptions;
+pub use deno_runtime::deno_permissions::PromptCache;
+pub use deno_runtime::deno_permissions::PromptCachePolicy;
+pub use deno_runtime::deno_permissions::PromptRequest;
+pub use deno_runtime::deno_permissions::PromptResponse;
+pub use deno_runtime::deno_permissions::QuotaExceededError;
+pub use deno_runtime::deno_permissions::QuotaKind;
+pub use deno_runtime::deno_permissions::QuotaObserver;
+pub use deno_runtime::deno_permissions::RememberedPrompt;
+pub use deno_runtime::deno_permissions::ResourceQuotas;
+pub use deno_runtime::deno_tls::BasicAuth;
+pub use deno_runtime::deno_tls::Proxy;
+pub use deno_runtime::ops::os::VirtualEnv;
+pub use deno_runtime::ops::process::SpawnInterceptor;
+pub use deno_runtime::ops::process::SpawnRequest;
+pub use deno_runtime::ops::signal::SignalForwarder;
+pub use deno_runtime::ops::worker_host::WorkerCreationPolicy;
+pub use deno_runtime::ops::worker_host::WorkerCreationRequest;
+use deno_runtime::tokio_util::create_and_run_current_thread;
+use deno_runtime::WorkerExecutionMode;
+pub use deno_runtime::UNSTABLE_GRANULAR_FLAGS;
+pub use deno_semver::package::PackageNv;
+pub use deno_semver::package::PackageReq;
+pub use deno_semver::Version;
+pub use deno_semver::VersionReq;
+use deno_terminal::colors;
+pub use deno_telemetry::ResourceMetrics;
+pub use deno_telemetry::SpanData;
+pub use deno_telemetry::TelemetryExporter;
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::future::Future;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+
+/// Name of the export read by [`run_file_with_result`] when no export name
+/// is provided.
+pub const DEFAULT_RESULT_EXPORT: &str = "default";
+
+/// What happens when running a module through [`DenoRuntimeBuilder`] fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExitMode {
+  /// Return a [`DenoRunError`] to the caller.
+  #[default]
+  ReturnError,
+  /// Print the error and exit the process with the exit code the `deno`
+  /// binary would use.
+  Exit,
+}
+
+/// Where remote modules and npm packages are loaded from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CachePolicy {
+  /// Uses cached modules and packages, fetching the missing ones.
+  #[default]
+  Default,
+  /// Fetches everything again, like `--reload`.
+  Reload,
+  /// Never touches the network, like `--cached-only`. Running a module
+  /// with missing dependencies fails with [`DenoRunError::NotCached`].
+  OfflineOnly,
+}
+
+/// Error of an embedded run, classified the same way the `deno` binary
+/// classifies errors when picking its exit code.
+#[derive(Debug, thiserror::Error)]
+pub enum DenoRunError {
+  /// An uncaught JavaScript exception.
+  #[error("{}", format_js_error(.0))]
+  Js(JsError),
+  /// The integrity check of the lockfile failed.
+  #[error("{0}")]
+  LockfileIntegrity(String),
+  /// The arguments passed to [`DenoRuntimeBuilder::from_args`] are invalid.
+  #[error(transparent)]
+  FlagParse(#[from] clap::Error),
+  /// The worker was terminated because it exceeded one of its
+  /// [`ExecutionLimits`].
+  #[error(transparent)]
+  LimitExceeded(WorkerLimitError),
+  /// The worker was terminated because it used an unstable API without its
+  /// `--unstable-*` flag.
+  #[error(transparent)]
+  UnstableApi(UnstableApiError),
+  /// Modules or npm packages are missing from the cache with
+  /// [`CachePolicy::OfflineOnly`].
+  #[error(transparent)]
+  NotCached(NotCachedError),
+  #[error(transparent)]
+  Other(AnyError),
+}
+
+impl DenoRunError {
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      DenoRunError::LockfileIntegrity(_) => 10,
+      DenoRunError::UnstableApi(_) => 70,
+      _ => 1,
+    }
+  }
+
+  /// Prints the error and exits the process, like the `deno` binary does.
+  pub fn exit(self) -> ! {
+    let message = match &self {
+      DenoRunError::Other(err) => format!("{err:?}"),
+      err => err.to_string(),
+    };
+    exit_with_message(&message, self.exit_code());
+  }
+}
+
+impl From<AnyError> for DenoRunError {
+  fn from(error: AnyError) -> Self {
+    let error = match error.downcast::<JsError>() {
+      Ok(js_error) => return DenoRunError::Js(js_error),
+      Err(error) => error,
+    };
+    if let Some(SnapshotFromLockfileError::IntegrityCheckFailed(e)) =
+      error.downcast_ref::<SnapshotFromLockfileError>()
+    {
+      return DenoRunError::LockfileIntegrity(e.to_string());
+    }
+    let error = match error.downcast::<WorkerLimitError>() {
+      Ok(err) => return DenoRunError::LimitExceeded(err),
+      Err(error) => error,
+    };
+    let error = match error.downcast::<UnstableApiError>() {
+      Ok(err) => return DenoRunError::UnstableApi(err),
+      Err(error) => error,
+    };
+    let error = match error.downcast::<NotCachedError>() {
+      Ok(err) => return DenoRunError::NotCached(err),
+      Err(error) => error,
+    };
+    if let Some(err) = NotCachedError::from_error(&error) {
+      return DenoRunError::NotCached(err);
+    }
+    match error.downcast::<clap::Error>() {
+      Ok(err) => DenoRunError::FlagParse(err),
+      Err(error) => DenoRunError::Other(error),
+    }
+  }
+}
+
+pub(crate) fn unstable_exit_cb(feature: &str, api_name: &str) {
+  log::error!(
+    "Unstable API '{api_name}'. The `--unstable-{}` flag must be provided.",
+    feature
+  );
+  deno_runtime::exit(70);
+}
+
+/// What [`DenoRuntimeBuilder::prepare`] loaded into the cache.
+#[derive(Debug, Clone)]
+pub struct PreparedModule {
+  pub main_module: ModuleSpecifier,
+  /// The modules of the module graph, including remote ones.
+  pub modules: Vec<ModuleSpecifier>,
+  /// Whether the module graph was type checked.
+  pub type_checked: bool,
+}
+
+/// Permission state of an embedded main worker.
+pub enum WorkerPermissions {
+  /// Typed permission options, resolved with the CLI's permission
+  /// descriptor parser.
+  Options(Box<PermissionsOptions>),
+  /// A fully constructed container, used as is.
+  Container(PermissionsContainer),
+}
+
+impl From<PermissionsOptions> for WorkerPermissions {
+  fn from(options: PermissionsOptions) -> Self {
+    WorkerPermissions::Options(Box::new(options))
+  }
+}
+
+impl From<PermissionsContainer> for WorkerPermissions {
+  fn from(container: PermissionsContainer) -> Self {
+    WorkerPermissions::Container(container)
+  }
+}
+
+/// Telemetry recorded for embedded runs, see
+/// [`DenoRuntimeBuilder::telemetry`].
+#[derive(Clone, Default)]
+pub struct TelemetryOptions {
+  /// Receives the spans and metrics in the process, in addition to the
+  /// OTLP exporter configured with the `OTEL_EXPORTER_OTLP_*` variables.
+  /// The exporter is process wide, so the last one set is used.
+  pub exporter: Option<Arc<dyn TelemetryExporter>>,
+  /// Whether the number of calls of each op is exported as the
+  /// `deno.op.calls` metric once the main module ran.
+  pub op_metrics: bool,
+}
+
+/// Builds a [`CliMainWorker`] from typed options, as an alternative to
+/// constructing a `deno run` argument vector.
+///
+/// ```ignore
+/// let mut worker = DenoRuntimeBuilder::new("./main.ts")
+///   .permissions(PermissionFlags {
+///     allow_net: Some(vec![]),
+///     ..Default::default()
+///   })
+///   .unstable_feature("kv")
+///   .extension(my_extension::init_ops_and_esm())
+///   .build()
+///   .await?;
+/// let exit_code = worker.run().await?;
+/// ```
+pub struct DenoRuntimeBuilder {
+  flags: Flags,
+  extensions: Vec<Extension>,
+  extensions_factory: Option<ExtensionsFactory>,
+  permissions: Option<WorkerPermissions>,
+  permission_rules: Option<PermissionRules>,
+  prompt_cache: Option<PromptCache>,
+  stdin: Option<Box<dyn AsyncRead + Send + Unpin>>,
+  stdout: Option<OutputWriter>,
+  stderr: Option<OutputWriter>,
+  startup_snapshot: Option<&'static [u8]>,
+  ephemeral_deno_dir: bool,
+  virtual_files: Vec<(String, Arc<[u8]>)>,
+  host_module_resolver: Option<Arc<dyn HostModuleResolver>>,
+  import_policy: Option<ImportPolicy>,
+  integrity: Option<IntegrityOptions>,
+  token_provider: Option<Arc<dyn TokenProvider>>,
+  network_options: Option<HttpNetworkOptions>,
+  retry_options: Option<RetryOptions>,
+  progress_reporter: Option<Arc<dyn ProgressReporter>>,
+  npm_store: Option<Arc<NpmStore>>,
+  lifecycle_scripts_policy: Option<Arc<dyn LifecycleScriptsPolicy>>,
+  virtual_node_modules: bool,
+  worker_observer: Option<Arc<dyn WorkerObserver>>,
+  execution_limits: Option<ExecutionLimits>,
+  file_system: Option<Arc<dyn FileSystem>>,
+  fetch_interceptor: Option<Arc<dyn FetchInterceptor>>,
+  connect_interceptor: Option<Arc<dyn ConnectInterceptor>>,
+  spawn_interceptor: Option<Arc<dyn SpawnInterceptor>>,
+  dlopen_interceptor: Option<Arc<dyn DlopenInterceptor>>,
+  kv_backend: Option<Arc<dyn DynamicDbHandler + Send + Sync>>,
+  broadcast_channel: Option<InMemoryBroadcastChannel>,
+  worker_creation_policy: Option<Arc<dyn WorkerCreationPolicy>>,
+  web_worker_extensions: Option<WebWorkerExtensionsFactory>,
+  virtual_env: Option<VirtualEnv>,
+  signal_forwarder: Option<SignalForwarder>,
+  resource_quotas: Option<ResourceQuotas>,
+  progress: Option<broadcast::Sender<WorkerProgress>>,
+  hmr_controller: Option<HmrController>,
+  npm_registries: Option<NpmRegistriesConfig>,
+  coverage_dir: Option<PathBuf>,
+  inspector_controller: Option<InspectorController>,
+  inspect_mode: InspectMode,
+  telemetry: Option<TelemetryOptions>,
+  determinism: Option<DeterminismOptions>,
+  host_fns: Vec<HostFn>,
+  serve_adapter: bool,
+  resume_from: Option<WorkerStateSnapshot>,
+  exit_mode: ExitMode,
+  module_cache: Option<Arc<ModuleCache>>,
+  transpile_config: Option<TranspileConfig>,
+  wasi: Option<WasiOptions>,
+}
+
+impl DenoRuntimeBuilder {
+  /// Creates a builder for running `main_module`, which can either be a
+  /// path relative to the current working directory or a URL.
+  pub fn new(main_module: impl Into<String>) -> Self {
+    Self {
+      flags: Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: main_module.into(),
+          watch: None,
+          bare: false,
+        }),
+        code_cache_enabled: true,
+        ..Default::default()
+      },
+      extensions: vec![],
+      extensions_factory: None,
+      permissions: None,
+      permission_rules: None,
+      prompt_cache: None,
+      stdin: None,
+      stdout: None,
+      stderr: None,
+      startup_snapshot: None,
+      ephemeral_deno_dir: false,
+      virtual_files: vec![],
+      host_module_resolver: None,
+      import_policy: None,
+      integrity: None,
+      token_provider: None,
+      network_options: None,
+      retry_options: None,
+      progress_reporter: None,
+      npm_store: None,
+      lifecycle_scripts_policy: None,
+      virtual_node_modules: false,
+      worker_observer: None,
+      execution_limits: None,
+      file_system: None,
+      fetch_interceptor: None,
+      connect_interceptor: None,
+      spawn_interceptor: None,
+      dlopen_interceptor: None,
+      kv_backend: None,
+      broadcast_channel: None,
+      worker_creation_policy: None,
+      web_worker_extensions: None,
+      virtual_env: None,
+      signal_forwarder: None,
+      resource_quotas: None,
+      progress: None,
+      hmr_controller: None,
+      npm_registries: None,
+      coverage_dir: None,
+      inspector_controller: None,
+      inspect_mode: InspectMode::default(),
+      telemetry: None,
+      determinism: None,
+      host_fns: vec![],
+      serve_adapter: false,
+      resume_from: None,
+      exit_mode: ExitMode::default(),
+      module_cache: None,
+      transpile_config: None,
+      wasi: None,
+    }
+  }
+
+  /// Creates a builder from a `deno run` argument vector, eg.
+  /// `["deno", "run", "--allow-net", "main.ts"]`.
+  pub fn from_args(args: Vec<OsString>) -> Result<Self, DenoRunError> {
+    let flags = flags_from_vec(args)?;
+    if !matches!(flags.subcommand, DenoSubcommand::Run(_)) {
+      return Err(DenoRunError::Other(generic_error(
+        "Only the `run` subcommand is supported.",
+      )));
+    }
+    Ok(Self {
+      flags,
+      ..Self::new("")
+    })
+  }
+
+  /// Serves `source` for `specifier` instead of reading it from disk or the
+  /// network. Relative paths are resolved against the current working
+  /// directory, so a virtual file with the same name as the main module
+  /// replaces it and relative imports between virtual files work.
+  pub fn virtual_file(
+    mut self,
+    specifier: impl Into<String>,
+    source: impl Into<Arc<[u8]>>,
+  ) -> Self {
+    self.virtual_files.push((specifier.into(), source.into()));
+    self
+  }
+
+  /// Adds several files, see [`DenoRuntimeBuilder::virtual_file`].
+  pub fn virtual_files<S: Into<Arc<[u8]>>>(
+    mut self,
+    files: impl IntoIterator<Item = (String, S)>,
+  ) -> Self {
+    self.virtual_files.extend(
+      files
+        .into_iter()
+        .map(|(specifier, source)| (specifier, source.into())),
+    );
+    self
+  }
+
+  /// Lets `resolver` resolve specifiers, eg. `app:config`, before the
+  /// default resolver does.
+  pub fn module_resolver(
+    mut self,
+    resolver: impl HostModuleResolver + 'static,
+  ) -> Self {
+    self.host_module_resolver = Some(Arc::new(resolver));
+    self
+  }
+
+  /// Restricts where modules may be imported from, instead of the
+  /// `importPolicy` of the `deno.json`. Denied imports fail the resolution
+  /// with an [`ImportPolicyError`] naming the rule to change.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .import_policy(ImportPolicy {
+  ///     allowed_hosts: Some(vec!["deno.land".to_string()]),
+  ///     deny_http: true,
+  ///     allowed_jsr: Some(vec!["@std".to_string()]),
+  ///     allowed_npm: Some(vec![]),
+  ///     ..Default::default()
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn import_policy(mut self, import_policy: ImportPolicy) -> Self {
+    self.import_policy = Some(import_policy);
+    self
+  }
+
+  /// Verifies the sources of the modules against `integrity` before they
+  /// are loaded, failing the import with an [`IntegrityError`]. The remote
+  /// checksums of the lockfile are used for modules without a hash.
+  ///
+  /// ```ignore
+  /// let hashes = HashMap::from([(
+  ///   ModuleSpecifier::parse("file:///app/main.ts")?,
+  ///   MAIN_INTEGRITY.parse()?, // eg. "sha384-oqVuAfXRKap7fdgc..."
+  /// )]);
+  /// let worker = DenoRuntimeBuilder::new("/app/main.ts")
+  ///   .integrity(IntegrityOptions {
+  ///     hashes,
+  ///     strict: true,
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn integrity(mut self, integrity: IntegrityOptions) -> Self {
+    self.integrity = Some(integrity);
+    self
+  }
+
+  /// Authorizes the requests of remote modules, JSR and npm packages with
+  /// the tokens of `token_provider`, instead of `DENO_AUTH_TOKENS`. When a
+  /// request is rejected with a 401 it is retried once with the token of
+  /// [`TokenProvider::refresh_token`].
+  ///
+  /// ```ignore
+  /// struct VaultTokens(Vault);
+  ///
+  /// #[async_trait(?Send)]
+  /// impl TokenProvider for VaultTokens {
+  ///   async fn token(
+  ///     &self,
+  ///     url: &Url,
+  ///   ) -> Result<Option<AuthTokenData>, AnyError> {
+  ///     if url.host_str() != Some("registry.example.com") {
+  ///       return Ok(None);
+  ///     }
+  ///     let token = self.0.read("registry-token").await?;
+  ///     Ok(Some(AuthTokenData::Bearer(token)))
+  ///   }
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .token_provider(VaultTokens(vault))
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn token_provider(
+    mut self,
+    token_provider: impl TokenProvider + 'static,
+  ) -> Self {
+    self.token_provider = Some(Arc::new(token_provider));
+    self
+  }
+
+  /// Sets the proxies, CA certificates and client certificate of the
+  /// requests of remote modules, JSR and npm packages, instead of the
+  /// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` environment variables.
+  /// `fetch()` calls of the workers are not affected.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .network_options(HttpNetworkOptions {
+  ///     proxy: Some(ProxyConfig {
+  ///       rules: vec![ProxyRule {
+  ///         hosts: vec![".corp.example.com".to_string()],
+  ///         proxy: Proxy {
+  ///           url: "http://internal-proxy:3128".to_string(),
+  ///           basic_auth: None,
+  ///         },
+  ///       }],
+  ///       https: Some(Proxy {
+  ///         url: "http://egress-proxy:3128".to_string(),
+  ///         basic_auth: Some(BasicAuth {
+  ///           username: "deno".to_string(),
+  ///           password: proxy_password,
+  ///         }),
+  ///       }),
+  ///       http: None,
+  ///       no_proxy: vec!["localhost".to_string()],
+  ///     }),
+  ///     ca_certs: vec![std::fs::read("/etc/corp/ca-bundle.pem")?],
+  ///     client_cert: Some(ClientCertificate::from_pem(
+  ///       &std::fs::read("/etc/corp/client.crt")?,
+  ///       &std::fs::read("/etc/corp/client.key")?,
+  ///     )?),
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn network_options(
+    mut self,
+    network_options: HttpNetworkOptions,
+  ) -> Self {
+    self.network_options = Some(network_options);
+    self
+  }
+
+  /// Retries the failed requests of remote modules, JSR and npm packages
+  /// with `retry_options`, falling back to a mirror once the retries are
+  /// exhausted. Retries and fallbacks are reported to the observer.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .retry_options(RetryOptions {
+  ///     policy: RetryPolicy {
+  ///       max_retries: 2,
+  ///       ..Default::default()
+  ///     },
+  ///     mirrors: vec![Mirror {
+  ///       origin: Url::parse("https://registry.npmjs.org/")?,
+  ///       mirror: Url::parse("https://npm.internal.example.com/")?,
+  ///     }],
+  ///     observer: Some(Arc::new(|event: &FetchEvent| {
+  ///       log::warn!("{event:?}");
+  ///     })),
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn retry_options(mut self, retry_options: RetryOptions) -> Self {
+    self.retry_options = Some(retry_options);
+    self
+  }
+
+  /// Sends the progress of module and npm package downloads to `reporter`
+  /// instead of drawing progress bars, eg. to show it in the UI of the
+  /// host while the module graph is built and npm packages are installed.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .progress_reporter(move |event: &ProgressEvent| match event {
+  ///     ProgressEvent::Start { id, message, .. } => ui.add_row(*id, message),
+  ///     ProgressEvent::Update {
+  ///       id,
+  ///       position,
+  ///       total_size,
+  ///     } => ui.set_progress(*id, *position, *total_size),
+  ///     ProgressEvent::Finish { id } => ui.remove_row(*id),
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn progress_reporter(
+    mut self,
+    reporter: impl ProgressReporter + 'static,
+  ) -> Self {
+    self.progress_reporter = Some(Arc::new(reporter));
+    self
+  }
+
+  /// Links the npm packages of the `node_modules` directory from a shared
+  /// [`NpmStore`] instead of copying them, so projects embedded by the same
+  /// host store each file once. Only used when the project has a
+  /// `node_modules` directory. The packages no project uses anymore are
+  /// removed with [`NpmStore::gc`].
+  ///
+  /// ```ignore
+  /// let store = Arc::new(NpmStore::in_deno_dir(None)?);
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   // with `"nodeModulesDir": "auto"` in the deno.json
+  ///   .config_file("./deno.json")
+  ///   .npm_store(store.clone())
+  ///   .build()
+  ///   .await?;
+  /// // later, eg. after projects were deleted
+  /// let report = store.gc().await?;
+  /// ```
+  pub fn npm_store(mut self, store: Arc<NpmStore>) -> Self {
+    self.npm_store = Some(store);
+    self
+  }
+
+  /// Decides whether the `preinstall`, `install` and `postinstall` scripts
+  /// of npm packages run when the `node_modules` directory is set up,
+  /// instead of the `--allow-scripts` flag. Scripts are denied unless
+  /// `policy` allows them, in which case they run with all permissions, or
+  /// sandboxes them, see [`LifecycleScriptsDecision::Sandbox`].
+  /// [`LifecycleScriptsPolicy::report`] receives the packages that requested
+  /// scripts and whether they ran.
+  ///
+  /// ```ignore
+  /// struct ScriptsPolicy;
+  ///
+  /// impl LifecycleScriptsPolicy for ScriptsPolicy {
+  ///   fn decide(
+  ///     &self,
+  ///     package: &LifecycleScriptsPackage,
+  ///   ) -> LifecycleScriptsDecision {
+  ///     match package.package.name.as_str() {
+  ///       "esbuild" => LifecycleScriptsDecision::Allow,
+  ///       "sharp" => LifecycleScriptsDecision::Sandbox,
+  ///       _ => LifecycleScriptsDecision::Deny,
+  ///     }
+  ///   }
+  ///
+  ///   fn report(&self, report: &LifecycleScriptsReport) {
+  ///     for entry in &report.packages {
+  ///       println!("{}: {:?}", entry.package.package, entry.outcome);
+  ///     }
+  ///   }
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .lifecycle_scripts_policy(ScriptsPolicy)
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn lifecycle_scripts_policy(
+    mut self,
+    policy: impl LifecycleScriptsPolicy + 'static,
+  ) -> Self {
+    self.lifecycle_scripts_policy = Some(Arc::new(policy));
+    self
+  }
+
+  /// Notifies `observer` when the main module starts and finishes loading,
+  /// when the event loop starts and when the worker exits.
+  pub fn observer(mut self, observer: impl WorkerObserver + 'static) -> Self {
+    self.worker_observer = Some(Arc::new(observer));
+    self
+  }
+
+  /// Terminates the main worker once it exceeds one of `limits`, failing
+  /// the run with [`DenoRunError::LimitExceeded`]. Not supported in watch
+  /// mode.
+  pub fn execution_limits(mut self, limits: ExecutionLimits) -> Self {
+    self.execution_limits = Some(limits);
+    self
+  }
+
+  /// Backs `Deno.readTextFile`, `node:fs` and the other file APIs with
+  /// `file_system`, eg. an [`InMemoryFs`], instead of the real disk. Modules
+  /// are still loaded from disk, use [`DenoRuntimeBuilder::virtual_files`]
+  /// to serve them from memory.
+  pub fn file_system(mut self, file_system: impl FileSystem + 'static) -> Self {
+    self.file_system = Some(Arc::new(file_system));
+    self
+  }
+
+  /// Passes every HTTP(S) request made with `fetch()` or `node:http` to
+  /// `interceptor`, which can inspect, rewrite, mock or deny it.
+  pub fn fetch_interceptor(
+    mut self,
+    interceptor: impl FetchInterceptor + 'static,
+  ) -> Self {
+    self.fetch_interceptor = Some(Arc::new(interceptor));
+    self
+  }
+
+  /// Lets `interceptor` deny outbound TCP, TLS, WebSocket and Unix socket
+  /// connections and UDP datagrams, eg. from `Deno.connect()`,
+  /// `Deno.startTls()`, `node:net` or `node:dgram`.
+  pub fn connect_interceptor(
+    mut self,
+    interceptor: impl ConnectInterceptor + 'static,
+  ) -> Self {
+    self.connect_interceptor = Some(Arc::new(interceptor));
+    self
+  }
+
+  /// Passes every subprocess the script is about to spawn, eg. with
+  /// `Deno.Command` or `node:child_process`, to `interceptor`, which can
+  /// allow, rewrite or deny it.
+  ///
+  /// ```ignore
+  /// struct Sandbox;
+  ///
+  /// impl SpawnInterceptor for Sandbox {
+  ///   fn intercept_spawn(
+  ///     &self,
+  ///     mut request: SpawnRequest,
+  ///     _api_name: &str,
+  ///   ) -> Result<SpawnRequest, AnyError> {
+  ///     if request.cmd.ends_with("rm") {
+  ///       bail!("Spawning rm is not allowed.");
+  ///     }
+  ///     let cmd = request.cmd.to_string_lossy().to_string();
+  ///     request.args.insert(0, cmd);
+  ///     request.cmd = PathBuf::from("/usr/bin/bwrap-wrapper");
+  ///     Ok(request)
+  ///   }
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .spawn_interceptor(Sandbox)
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn spawn_interceptor(
+    mut self,
+    interceptor: impl SpawnInterceptor + 'static,
+  ) -> Self {
+    self.spawn_interceptor = Some(Arc::new(interceptor));
+    self
+  }
+
+  /// Passes every library the script opens with `Deno.dlopen()` to
+  /// `interceptor`, which can deny it, redirect it to a vetted library or
+  /// provide its symbols from Rust with a [`VirtualLibrary`].
+  ///
+  /// ```ignore
+  /// extern "C" fn add(a: i32, b: i32) -> i32 {
+  ///   a + b
+  /// }
+  ///
+  /// struct HostMath;
+  ///
+  /// impl DlopenInterceptor for HostMath {
+  ///   fn intercept_dlopen(
+  ///     &self,
+  ///     path: &Path,
+  ///   ) -> Result<DlopenTarget, AnyError> {
+  ///     if !path.ends_with("libmath.so") {
+  ///       bail!("Only libmath.so may be opened.");
+  ///     }
+  ///     // SAFETY: `add` matches the definition scripts use.
+  ///     let lib =
+  ///       unsafe { VirtualLibrary::new().with_symbol("add", add as _) };
+  ///     Ok(DlopenTarget::Virtual(lib))
+  ///   }
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .dlopen_interceptor(HostMath)
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn dlopen_interceptor(
+    mut self,
+    interceptor: impl DlopenInterceptor + 'static,
+  ) -> Self {
+    self.dlopen_interceptor = Some(Arc::new(interceptor));
+    self
+  }
+
+  /// Opens the databases of `Deno.openKv()` with `handler` instead of
+  /// sqlite, so scripts keep their data in the storage of the host.
+  /// Remote databases, opened with a URL, still connect to the KV Connect
+  /// server.
+  ///
+  /// The databases the handler opens implement `denokv_proto::Database`,
+  /// re-exported as `deno_runtime::deno_kv::denokv_proto`, which serves
+  /// the reads, atomic writes, watches and queue of `Deno.Kv`.
+  ///
+  /// ```ignore
+  /// struct PostgresKv {
+  ///   pool: PgPool,
+  /// }
+  ///
+  /// #[async_trait(?Send)]
+  /// impl DatabaseHandler for PostgresKv {
+  ///   type DB = PostgresDatabase;
+  ///
+  ///   async fn open(
+  ///     &self,
+  ///     _state: Rc<RefCell<OpState>>,
+  ///     path: Option<String>,
+  ///   ) -> Result<PostgresDatabase, AnyError> {
+  ///     let schema = path.unwrap_or_else(|| "default".to_string());
+  ///     PostgresDatabase::connect(self.pool.clone(), &schema).await
+  ///   }
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .kv_backend(PostgresKv { pool })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn kv_backend(
+    mut self,
+    handler: impl DatabaseHandler + Send + Sync + 'static,
+  ) -> Self {
+    self.kv_backend = Some(Arc::new(handler));
+    self
+  }
+
+  /// Connects the `BroadcastChannel`s of the script and its web workers to
+  /// `channel`, so the host can publish and subscribe on the same channels
+  /// with [`InMemoryBroadcastChannel::publish`] and
+  /// [`InMemoryBroadcastChannel::subscribe_host`]. Share a channel between
+  /// several builders for their scripts to reach each other.
+  ///
+  /// Messages are exchanged with the host as JSON.
+  ///
+  /// ```ignore
+  /// let bus = InMemoryBroadcastChannel::default();
+  /// let mut jobs = bus.subscribe_host("jobs", 16);
+  /// let worker = DenoRuntimeBuilder::new("./producer.ts")
+  ///   .broadcast_channel(bus.clone())
+  ///   .build()
+  ///   .await?;
+  /// bus.publish("config", &json!({ "batchSize": 10 })).await;
+  /// while let Some(job) = jobs.recv().await {
+  ///   handle_job(job).await?;
+  /// }
+  /// ```
+  pub fn broadcast_channel(
+    mut self,
+    channel: InMemoryBroadcastChannel,
+  ) -> Self {
+    self.broadcast_channel = Some(channel);
+    self
+  }
+
+  /// Passes every `new Worker()` call of the script and its web workers to
+  /// `policy`, which can deny it, eg. to cap the number of workers, or
+  /// change the permissions and main module of the worker.
+  ///
+  /// ```ignore
+  /// struct AtMostFourWorkers;
+  ///
+  /// impl WorkerCreationPolicy for AtMostFourWorkers {
+  ///   fn check_worker_creation(
+  ///     &self,
+  ///     mut request: WorkerCreationRequest,
+  ///   ) -> Result<WorkerCreationRequest, AnyError> {
+  ///     if request.running_workers >= 4 {
+  ///       bail!("At most 4 workers may run at the same time.");
+  ///     }
+  ///     if request.specifier.as_str() == "host:renderer.js" {
+  ///       request.module_source = Some(RENDERER_SOURCE.to_string());
+  ///     }
+  ///     Ok(request)
+  ///   }
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .worker_creation_policy(AtMostFourWorkers)
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn worker_creation_policy(
+    mut self,
+    policy: impl WorkerCreationPolicy + 'static,
+  ) -> Self {
+    self.worker_creation_policy = Some(Arc::new(policy));
+    self
+  }
+
+  /// Sets a closure that creates extensions for every web worker the script
+  /// creates. It's called on the thread of the web worker. Use
+  /// [`DenoRuntimeBuilder::all_workers_extensions_factory`] for web workers
+  /// to have the ops of the main worker.
+  pub fn web_worker_extensions_factory(
+    mut self,
+    factory: impl Fn() -> Vec<Extension> + Send + Sync + 'static,
+  ) -> Self {
+    self.web_worker_extensions = Some(Arc::new(factory));
+    self
+  }
+
+  /// Sets a closure that creates extensions for the main worker and for every
+  /// web worker it and its web workers create, so scripts have the same ops
+  /// in `new Worker()` children. Replaces
+  /// [`DenoRuntimeBuilder::extensions_factory`] and
+  /// [`DenoRuntimeBuilder::web_worker_extensions_factory`].
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .all_workers_extensions_factory(|| vec![my_extension::init_ops()])
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn all_workers_extensions_factory(
+    mut self,
+    factory: impl Fn() -> Vec<Extension> + Send + Sync + 'static,
+  ) -> Self {
+    let factory = Arc::new(factory);
+    self.extensions_factory = Some(Rc::new({
+      let factory = factory.clone();
+      move || factory()
+    }));
+    self.web_worker_extensions = Some(factory);
+    self
+  }
+
+  /// Gives the script `env` instead of the process environment, both for
+  /// `Deno.env` and `process.env` and for the subprocesses it spawns.
+  /// Variables the script sets or deletes are applied to `env`, keep a
+  /// clone of it to read them after the run.
+  pub fn env(mut self, env: impl Into<VirtualEnv>) -> Self {
+    self.virtual_env = Some(env.into());
+    self
+  }
+
+  /// Delivers the signals sent through `forwarder` to the
+  /// `Deno.addSignalListener` listeners of the script, instead of binding
+  /// them to the signals of the process, so the host keeps its own signal
+  /// handling. Use [`SignalForwarder::with_os_signals`] for signals that
+  /// should still reach the script from the OS.
+  ///
+  /// ```ignore
+  /// let forwarder = SignalForwarder::new();
+  /// let handle = spawn_worker({
+  ///   let forwarder = forwarder.clone();
+  ///   move || {
+  ///     DenoRuntimeBuilder::new("./server.ts").signal_forwarder(forwarder)
+  ///   }
+  /// })
+  /// .await?;
+  /// tokio::signal::ctrl_c().await?;
+  /// forwarder.send("SIGTERM")?;
+  /// ```
+  pub fn signal_forwarder(mut self, forwarder: SignalForwarder) -> Self {
+    self.signal_forwarder = Some(forwarder);
+    self
+  }
+
+  /// Limits the files, sockets and subprocesses the script and each of its
+  /// web workers may use. Exceeding a quota throws a
+  /// `Deno.errors.QuotaExceeded` error in the script and notifies the
+  /// observer of the quotas.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./tenant.ts")
+  ///   .resource_quotas(ResourceQuotas {
+  ///     max_open_files: Some(64),
+  ///     max_subprocesses: Some(0),
+  ///     max_sockets: Some(16),
+  ///     observer: Some(Arc::new(|error: &QuotaExceededError| {
+  ///       log::warn!("tenant exceeded its quota: {error}");
+  ///     })),
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn resource_quotas(mut self, quotas: ResourceQuotas) -> Self {
+    self.resource_quotas = Some(quotas);
+    self
+  }
+
+  /// Sends [`WorkerProgress`] events to `sender` while the main module is
+  /// evaluated and the event loop runs, eg. to tell whether a script is
+  /// still running or waiting on the network.
+  pub fn progress(mut self, sender: broadcast::Sender<WorkerProgress>) -> Self {
+    self.progress = Some(sender);
+    self
+  }
+
+  /// Lets `controller` hot replace modules of the running worker, eg. with
+  /// the contents of an in-app editor.
+  pub fn hmr(mut self, controller: HmrController) -> Self {
+    self.hmr_controller = Some(controller);
+    self
+  }
+
+  /// Starts the inspector server on `addr`, like `--inspect`, so debuggers
+  /// such as Chrome DevTools can attach to the main worker.
+  pub fn inspect(mut self, addr: SocketAddr, mode: InspectMode) -> Self {
+    self.flags.inspect = None;
+    self.flags.inspect_wait = None;
+    self.flags.inspect_brk = None;
+    match mode {
+      InspectMode::Run => self.flags.inspect = Some(addr),
+      InspectMode::WaitForSession => self.flags.inspect_wait = Some(addr),
+      InspectMode::BreakOnFirstStatement => self.flags.inspect_brk = Some(addr),
+    }
+    self
+  }
+
+  /// Lets `controller` open Chrome DevTools Protocol sessions with the main
+  /// worker from the host, without starting an inspector server.
+  ///
+  /// ```ignore
+  /// let controller = InspectorController::new();
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .inspector(controller.clone(), InspectMode::BreakOnFirstStatement)
+  ///   .build()
+  ///   .await?;
+  /// // on another thread, while the worker runs
+  /// let mut session = controller.connect().await?;
+  /// session.post_message("Debugger.enable", None).await?;
+  /// session.post_message("Runtime.runIfWaitingForDebugger", None).await?;
+  /// ```
+  pub fn inspector(
+    mut self,
+    controller: InspectorController,
+    mode: InspectMode,
+  ) -> Self {
+    self.inspector_controller = Some(controller);
+    self.inspect_mode = mode;
+    self
+  }
+
+  /// Records spans for module resolution, npm installs, type checking and
+  /// the execution of the main module, along with the telemetry of the
+  /// `--unstable-otel` APIs, so slow startups can be traced in production.
+  ///
+  /// ```ignore
+  /// struct Exporter;
+  ///
+  /// impl TelemetryExporter for Exporter {
+  ///   fn export_span(&self, span: &SpanData) {
+  ///     let duration = span.end_time.duration_since(span.start_time);
+  ///     println!("{}: {:?}", span.name, duration);
+  ///   }
+  ///   fn export_metrics(&self, _metrics: &ResourceMetrics) {}
+  /// }
+  ///
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .telemetry(TelemetryOptions {
+  ///     exporter: Some(Arc::new(Exporter)),
+  ///     op_metrics: true,
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn telemetry(mut self, options: TelemetryOptions) -> Self {
+    let features = &mut self.flags.unstable_config.features;
+    if !features.iter().any(|feature| feature == "otel") {
+      features.push("otel".to_string());
+    }
+    self.telemetry = Some(options);
+    self
+  }
+
+  /// Makes the main worker behave the same on every run: `Math.random()`
+  /// and the Web Crypto API are seeded, `Date` and timers follow a
+  /// deterministic clock, and APIs reporting on the host can be disabled.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./workflow.ts")
+  ///   .determinism(DeterminismOptions {
+  ///     seed: 42,
+  ///     start_time: recorded_start_time,
+  ///     ..Default::default()
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn determinism(mut self, options: DeterminismOptions) -> Self {
+    self.flags.seed = Some(options.seed);
+    self.determinism = Some(options);
+    self
+  }
+
+  /// Exposes a Rust function to the scripts of the main worker, see the
+  /// [`host_fn!`] macro.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .host_fn(host_fn!("math.add", |args: (i32, i32)| -> i32 {
+  ///     args.0 + args.1
+  ///   }))
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn host_fn(mut self, host_fn: HostFn) -> Self {
+    self.host_fns.push(host_fn);
+    self
+  }
+
+  /// Like [`DenoRuntimeBuilder::host_fn`], for several functions at once.
+  pub fn host_fns(
+    mut self,
+    host_fns: impl IntoIterator<Item = HostFn>,
+  ) -> Self {
+    self.host_fns.extend(host_fns);
+    self
+  }
+
+  /// Serves HTTP requests received by the host's own server with the
+  /// `fetch` handler of the main module's default export, like
+  /// `deno serve`. Take the adapter with
+  /// [`CliMainWorker::take_serve_adapter`] before running the worker.
+  ///
+  /// ```ignore
+  /// let mut worker = DenoRuntimeBuilder::new("./server.ts")
+  ///   .serve_adapter()
+  ///   .build()
+  ///   .await?;
+  /// let adapter = worker.take_serve_adapter().unwrap();
+  /// let app = axum::Router::new().fallback(move |request| async move {
+  ///   adapter.handle(request).await.map_err(|_| StatusCode::BAD_GATEWAY)
+  /// });
+  /// tokio::task::spawn_local(async move { worker.run().await });
+  /// axum::serve(listener, app).await?;
+  /// ```
+  pub fn serve_adapter(mut self) -> Self {
+    self.serve_adapter = true;
+    self
+  }
+
+  /// Resumes a worker paused with [`CliMainWorker::run_until_paused`]: the
+  /// main module is loaded again and runs from the start, with the state of
+  /// `snapshot` in `Deno.host.restoredState`.
+  ///
+  /// ```ignore
+  /// let snapshot: WorkerStateSnapshot = serde_json::from_slice(&saved)?;
+  /// let mut worker = DenoRuntimeBuilder::new(snapshot.main_module.as_str())
+  ///   .resume_from(snapshot)
+  ///   .build()
+  ///   .await?;
+  /// let outcome = worker.run_until_paused().await?;
+  /// ```
+  pub fn resume_from(mut self, snapshot: WorkerStateSnapshot) -> Self {
+    self.resume_from = Some(snapshot);
+    self
+  }
+
+  /// Loads npm packages from the given registries, authenticating with
+  /// their credentials, instead of the ones configured in `.npmrc` files.
+  ///
+  /// ```ignore
+  /// let mut registries = NpmRegistriesConfig::default();
+  /// registries.scopes.insert("@my-org".to_string(), NpmRegistry {
+  ///   url: Url::parse("https://npm.my-org.com/")?,
+  ///   auth_token: Some(token),
+  ///   auth: None,
+  /// });
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .npm_registries(registries)
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn npm_registries(mut self, registries: NpmRegistriesConfig) -> Self {
+    self.npm_registries = Some(registries);
+    self
+  }
+
+  /// Collects the code coverage of the main worker into `dir`, which can
+  /// then be turned into a report with [`coverage_report`].
+  pub fn coverage_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.coverage_dir = Some(dir.into());
+    self
+  }
+
+  /// Uses a new temporary directory as `DENO_DIR`, so that remote modules,
+  /// npm packages and storage don't end up in or come from the global
+  /// cache. The directory is deleted once the worker is dropped.
+  pub fn ephemeral_deno_dir(mut self) -> Self {
+    self.ephemeral_deno_dir = true;
+    self
+  }
+
+  /// Serves the `node_modules` directory of the project from the global npm
+  /// cache instead of setting one up, even when the deno.json has
+  /// `"nodeModulesDir": "auto"` or the project only has a package.json. The
+  /// directory is a read-only overlay: code that reads
+  /// `node_modules/<package>` sees the package folder of the cache, and
+  /// nothing is written next to the project. This is meant for read-only
+  /// deployments whose `DENO_DIR` is populated ahead of time and loaded with
+  /// [`CachePolicy::OfflineOnly`]. Lifecycle scripts don't run in this mode.
+  pub fn virtual_node_modules(mut self) -> Self {
+    self.flags.node_modules_dir = Some(NodeModulesDirMode::None);
+    self.virtual_node_modules = true;
+    self
+  }
+
+  /// Loads transpiled modules and their V8 code cache from `cache` by the
+  /// hash of their specifier and source, before the caches of the
+  /// `DENO_DIR`. The cache can be shared by workers of different projects and
+  /// populated ahead of time.
+  ///
+  /// ```ignore
+  /// let cache = Arc::new(ModuleCache::in_deno_dir(None)?);
+  /// cache.populate(Arc::new(Flags::default()), "./main.ts").await?;
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .module_cache(cache.clone())
+  ///   .build()
+  ///   .await?;
+  /// cache.prune(&ModuleCachePruneOptions {
+  ///   max_size: Some(512 * 1024 * 1024),
+  ///   ..Default::default()
+  /// })?;
+  /// ```
+  pub fn module_cache(mut self, cache: Arc<ModuleCache>) -> Self {
+    self.module_cache = Some(cache);
+    self
+  }
+
+  /// Sets how the modules are transpiled, eg. to only strip the types of
+  /// TypeScript modules or to process decorators with a custom pass before
+  /// the modules are transpiled.
+  ///
+  /// ```ignore
+  /// let worker = DenoRuntimeBuilder::new("./main.ts")
+  ///   .transpile_config(TranspileConfig {
+  ///     strategies: BTreeMap::from([(
+  ///       MediaType::TypeScript,
+  ///       TranspileStrategy::StripOnly,
+  ///     )]),
+  ///     passes: vec![Arc::new(DecoratorsPass)],
+  ///   })
+  ///   .build()
+  ///   .await?;
+  /// ```
+  pub fn transpile_config(mut self, config: TranspileConfig) -> Self {
+    self.transpile_config = Some(config);
+    self
+  }
+
+  /// Runs the WebAssembly components of the `wasi:cli/command` world
+  /// imported by the main worker with a WASI context. A component is
+  /// imported as a module whose default export runs it:
+  ///
+  /// ```ignore
+  /// let builder = DenoRuntimeBuilder::new("./main.ts").wasi_components(
+  ///   WasiOptions {
+  ///     preopened_dirs: vec![WasiPreopenedDir {
+  ///       host_path: PathBuf::from("/srv/app/data"),
+  ///       guest_path: "/data".to_string(),
+  ///       writable: false,
+  ///     }],
+  ///     args: vec!["tool".to_string(), "--verbose".to_string()],
+  ///     ..Default::default()
+  ///   },
+  /// );
+  /// // main.ts: `import run from "./tool.wasm"; const code = await run();`
+  /// ```
+  ///
+  /// Components use the stdio of the worker. Their directories, environment
+  /// variables and sockets are checked against the permissions of the worker
+  /// each time a component runs. Directories can't be preopened along with a
+  /// custom [`DenoRuntimeBuilder::file_system`]. Components are compiled
+  /// once per [`DenoRuntimeBuilder::module_cache`], or ahead of time with
+  /// [`ModuleCache::precompile_wasm`].
+  pub fn wasi_components(mut self, options: WasiOptions) -> Self {
+    self.wasi = Some(options);
+    self
+  }
+
+  /// Sets whether [`DenoRuntimeBuilder::build`], [`DenoRuntimeBuilder::run`]
+  /// and [`DenoRuntimeBuilder::build_pool`] return errors or exit the
+  /// process.
+  pub fn exit_mode(mut self, exit_mode: ExitMode) -> Self {
+    self.exit_mode = exit_mode;
+    self
+  }
+
+  /// Sets where remote modules and npm packages are loaded from. See
+  /// [`CachePolicy`].
+  pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+    self.flags.reload = policy == CachePolicy::Reload;
+    self.flags.cached_only = policy == CachePolicy::OfflineOnly;
+    self
+  }
+
+  /// Arguments exposed to the script as `Deno.args`.
+  pub fn args(mut self, args: Vec<String>) -> Self {
+    self.flags.argv = args;
+    self
+  }
+
+  pub fn permissions(mut self, permissions: PermissionFlags) -> Self {
+    self.flags.permissions = permissions;
+    self
+  }
+
+  /// Uses already resolved permissions for the main worker instead of the
+  /// ones derived from [`DenoRuntimeBuilder::permissions`].
+  pub fn root_permissions(
+    mut self,
+    permissions: impl Into<WorkerPermissions>,
+  ) -> Self {
+    self.permissions = Some(permissions.into());
+    self
+  }
+
+  /// Grants or denies network and file system access with typed rules,
+  /// before the permissions of the main worker are checked.
+  ///
+  /// ```ignore
+  /// let builder = DenoRuntimeBuilder::new("./main.ts").permission_rules(
+  ///   PermissionRules {
+  ///     allow_net: vec![NetRule::new("*.example.com")?
+  ///       .with_ports(443..=443)?
+  ///       .with_protocols([NetProtocol::Https])],
+  ///     allow_read: vec![PathRule::new("/srv/app/data")?],
+  ///     ..Default::default()
+  ///   },
+  /// );
+  /// ```
+  pub fn permission_rules(mut self, rules: PermissionRules) -> Self {
+    self.permission_rules = Some(rules);
+    self
+  }
+
+  /// Remembers the answers to permission prompts of the main worker in
+  /// `prompt_cache`, eg. to persist them after the run. Workers of a
+  /// [`RuntimeTemplate`] each get a copy of it.
+  pub fn prompt_cache(mut self, prompt_cache: PromptCache) -> Self {
+    self.prompt_cache = Some(prompt_cache);
+    self
+  }
+
+  pub fn import_map(mut self, path: impl Into<String>) -> Self {
+    self.flags.import_map_path = Some(path.into());
+    self
+  }
+
+  /// Uses the given `deno.json` instead of discovering one from the
+  /// current working directory.
+  pub fn config_file(mut self, path: impl Into<String>) -> Self {
+    self.flags.config_flag = ConfigFlag::Path(path.into());
+    self
+  }
+
+  /// Disables discovery of a `deno.json` file.
+  pub fn no_config(mut self) -> Self {
+    self.flags.config_flag = ConfigFlag::Disabled;
+    self
+  }
+
+  /// Enables a granular unstable feature, eg. `"kv"` for `--unstable-kv`.
+  pub fn unstable_feature(mut self, name: impl Into<String>) -> Self {
+    self.flags.unstable_config.features.push(name.into());
+    self
+  }
+
+  pub fn v8_flags(mut self, v8_flags: Vec<String>) -> Self {
+    self.flags.v8_flags = v8_flags;
+    self
+  }
+
+  pub fn log_level(mut self, log_level: log::Level) -> Self {
+    self.flags.log_level = Some(log_level);
+    self
+  }
+
+  pub fn extension(mut self, extension: Extension) -> Self {
+    self.extensions.push(extension);
+    self
+  }
+
+  pub fn extensions(mut self, extensions: Vec<Extension>) -> Self {
+    self.extensions.extend(extensions);
+    self
+  }
+
+  /// Redirects the script's stdout (`Deno.stdout`, `console.log`, etc.)
+  /// into `writer` instead of the process' stdout. Use [`ChannelWriter`] to
+  /// receive the output as chunks on an async channel.
+  pub fn stdout(mut self, writer: impl Write + Send + 'static) -> Self {
+    self.stdout = Some(OutputWriter::Sync(Box::new(writer)));
+    self
+  }
+
+  /// Redirects the script's stderr (`Deno.stderr`, `console.error`, etc.)
+  /// into `writer` instead of the process' stderr.
+  pub fn stderr(mut self, writer: impl Write + Send + 'static) -> Self {
+    self.stderr = Some(OutputWriter::Sync(Box::new(writer)));
+    self
+  }
+
+  /// Feeds the script's stdin (`Deno.stdin`, `prompt()`, etc.) from
+  /// `reader` instead of the process' stdin. The script reads EOF once
+  /// `reader` ends.
+  ///
+  /// ```ignore
+  /// let (stdin, mut input) = tokio::io::duplex(4096);
+  /// let (output, stdout) = tokio::io::duplex(4096);
+  /// let mut worker = DenoRuntimeBuilder::new("./ask.ts")
+  ///   .stdin_reader(stdin)
+  ///   .stdout_writer(output)
+  ///   .build()
+  ///   .await?;
+  /// input.write_all(b"yes\n").await?;
+  /// ```
+  pub fn stdin_reader(
+    mut self,
+    reader: impl AsyncRead + Send + Unpin + 'static,
+  ) -> Self {
+    self.stdin = Some(Box::new(reader));
+    self
+  }
+
+  /// Like [`DenoRuntimeBuilder::stdout`], for an async `writer`.
+  pub fn stdout_writer(
+    mut self,
+    writer: impl AsyncWrite + Send + Unpin + 'static,
+  ) -> Self {
+    self.stdout = Some(OutputWriter::Async(Box::new(writer)));
+    self
+  }
+
+  /// Like [`DenoRuntimeBuilder::stderr`], for an async `writer`.
+  pub fn stderr_writer(
+    mut self,
+    writer: impl AsyncWrite + Send + Unpin + 'static,
+  ) -> Self {
+    self.stderr = Some(OutputWriter::Async(Box::new(writer)));
+    self
+  }
+
+  /// Sets a closure that creates extensions for the main worker. Unlike
+  /// [`DenoRuntimeBuilder::extensions`] this can be used in watch mode,
+  /// because the closure is called again on every restart.
+  pub fn extensions_factory(
+    mut self,
+    factory: impl Fn() -> Vec<Extension> + 'static,
+  ) -> Self {
+    self.extensions_factory = Some(Rc::new(factory));
+    self
+  }
+
+  /// Starts the main worker from a snapshot created with [`create_snapshot`]
+  /// instead of the CLI's own snapshot. The extensions that were passed to
+  /// [`create_snapshot`] have to be added with
+  /// [`DenoRuntimeBuilder::extensions`], created with `init_ops`.
+  pub fn startup_snapshot(mut self, snapshot: &'static [u8]) -> Self {
+    self.startup_snapshot = Some(snapshot);
+    self
+  }
+
+  /// Restarts the main module whenever it or one of the `watch` paths
+  /// changes. Only applies to [`DenoRuntimeBuilder::run`].
+  pub fn watch(mut self, watch: WatchFlagsWithPaths) -> Self {
+    if let DenoSubcommand::Run(run_flags) = &mut self.flags.subcommand {
+      run_flags.watch = Some(watch);
+    }
+    self
+  }
+
+  /// Resolves the configuration, installs npm dependencies if necessary and
+  /// creates the main worker. The worker does not start executing the main
+  /// module until one of its `run` methods is called.
+  pub async fn build(self) -> Result<CliMainWorker, DenoRunError> {
+    let exit_mode = self.exit_mode;
+    handle_run_error(exit_mode, self.build_worker().await)
+  }
+
+  async fn build_worker(mut self) -> Result<CliMainWorker, AnyError> {
+    self.validate(BuildMode::Worker)?;
+    init_runtime(self.flags.log_level, &self.flags.v8_flags);
+    self.init_telemetry()?;
+
+    let mut extensions = std::mem::take(&mut self.extensions);
+    if let Some(extensions_factory) = &self.extensions_factory {
+      extensions.extend(extensions_factory());
+    }
+
+    let embedder_options = self.embedder_options()?;
+    let factory = CliFactory::from_flags_for_embedder(
+      Arc::new(self.flags),
+      embedder_options,
+    );
+    insert_virtual_files(&factory, self.virtual_files)?;
+    let cli_options = factory.cli_options()?;
+    let main_module = cli_options.resolve_main_module()?;
+    if let Some(snapshot) = &self.resume_from {
+      if &snapshot.main_module != main_module {
+        bail!(
+          "The snapshot was taken from a worker running {}, not {}.",
+          snapshot.main_module,
+          main_module
+        );
+      }
+    }
+
+    if cli_options.cache_setting() == CacheSetting::Only {
+      // report everything that's missing at once, instead of the first
+      // module that fails to load
+      let graph = factory
+        .module_graph_creator()
+        .await?
+        .create_graph(GraphKind::CodeOnly, vec![main_module.clone()])
+        .await?;
+      if let Some(err) = NotCachedError::from_graph(&graph) {
+        return Err(err.into());
+      }
+    }
+    tools::run::maybe_npm_install(&factory).await?;
+
+    let permissions = resolve_permissions(
+      &factory,
+      self.permissions,
+      self.permission_rules,
+      self.prompt_cache,
+    )?;
+    let stdio = create_stdio(self.stdin, self.stdout, self.stderr)?;
+
+    let worker_factory = factory.create_cli_main_worker_factory().await?;
+    let mut worker = worker_factory
+      .create_custom_worker(
+        WorkerExecutionMode::Run,
+        main_module.clone(),
+        permissions,
+        extensions,
+        stdio,
+      )
+      .await?;
+    if let Some(sender) = self.progress {
+      worker.set_progress_sender(sender);
+    }
+    Ok(worker)
+  }
+
+  /// Creates a [`SourceMapService`] with the configuration of the builder,
+  /// to map the errors of the runs of the main module back to their
+  /// original sources.
+  ///
+  /// ```ignore
+  /// let builder = DenoRuntimeBuilder::new("./main.ts");
+  /// let source_maps = builder.source_map_service()?;
+  /// if let Err(DenoRunError::Js(error)) = builder.run().await {
+  ///   render_error(source_maps.map_js_error(&error));
+  /// }
+  /// ```
+  pub fn source_map_service(&self) -> Result<SourceMapService, DenoRunError> {
+    let factory = CliFactory::from_flags_for_embedder(
+      Arc::new(self.flags.clone()),
+      self.embedder_options()?,
+    );
+    insert_virtual_files(&factory, self.virtual_files.clone())?;
+    Ok(SourceMapService::from_factory(&factory)?)
+  }
+
+  /// Downloads the dependencies of the main module, installs its npm
+  /// packages and caches the transpiled sources, optionally type checking
+  /// them, like `deno cache` does. Nothing is executed. Building a worker
+  /// with the same configuration afterwards doesn't need to load anything
+  /// from the network.
+  pub async fn prepare(
+    self,
+    type_check: bool,
+  ) -> Result<PreparedModule, DenoRunError> {
+    let exit_mode = self.exit_mode;
+    handle_run_error(exit_mode, self.prepare_module(type_check).await)
+  }
+
+  async fn prepare_module(
+    mut self,
+    type_check: bool,
+  ) -> Result<PreparedModule, AnyError> {
+    self.validate(BuildMode::Worker)?;
+    if type_check {
+      self.flags.type_check_mode = TypeCheckMode::Local;
+    }
+    init_runtime(self.flags.log_level, &self.flags.v8_flags);
+    self.init_telemetry()?;
+
+    let embedder_options = self.embedder_options()?;
+    let factory = CliFactory::from_flags_for_embedder(
+      Arc::new(self.flags),
+      embedder_options,
+    );
+    insert_virtual_files(&factory, self.virtual_files)?;
+    let cli_options = factory.cli_options()?;
+    let main_module = cli_options.resolve_main_module()?.clone();
+
+    let graph = prepare_main_module(&factory, &main_module).await?;
+    Ok(PreparedModule {
+      main_module,
+      modules: graph
+        .modules()
+        .map(|module| module.specifier().clone())
+        .collect(),
+      type_checked: cli_options.type_check_mode().is_true(),
+    })
+  }
+
+  /// Resolves the configuration and prepares the main module once, like
+  /// [`DenoRuntimeBuilder::prepare`], and returns a [`RuntimeTemplate`]
+  /// that creates main workers for it without redoing that work.
+  pub async fn build_template(self) -> Result<RuntimeTemplate, DenoRunError> {
+    let exit_mode = self.exit_mode;
+    handle_run_error(exit_mode, self.build_runtime_template().await)
+  }
+
+  async fn build_runtime_template(self) -> Result<RuntimeTemplate, AnyError> {
+    self.validate(BuildMode::Template)?;
+    init_runtime(self.flags.log_level, &self.flags.v8_flags);
+    self.init_telemetry()?;
+
+    let embedder_options = self.embedder_options()?;
+    let factory = CliFactory::from_flags_for_embedder(
+      Arc::new(self.flags),
+      embedder_options,
+    );
+    insert_virtual_files(&factory, self.virtual_files)?;
+    let main_module = factory.cli_options()?.resolve_main_module()?.clone();
+    prepare_main_module(&factory, &main_module).await?;
+
+    let permissions = resolve_permissions(
+      &factory,
+      self.permissions,
+      self.permission_rules,
+      self.prompt_cache,
+    )?;
+    let worker_factory = factory.create_cli_main_worker_factory().await?;
+    Ok(RuntimeTemplate {
+      _factory: factory,
+      worker_factory,
+      main_module,
+      permissions,
+      extensions_factory: self.extensions_factory,
+      progress: self.progress,
+      exit_mode: self.exit_mode,
+    })
+  }
+
+  /// Creates a [`ReplSession`] with this builder's configuration, like
+  /// `deno repl` does. The main module passed to
+  /// [`DenoRuntimeBuilder::new`] is not used.
+  pub async fn build_repl(self) -> Result<ReplSession, DenoRunError> {
+    let exit_mode = self.exit_mode;
+    handle_run_error(exit_mode, self.build_repl_session().await)
+  }
+
+  async fn build_repl_session(mut self) -> Result<ReplSession, AnyError> {
+    self.validate(BuildMode::Repl)?;
+    init_runtime(self.flags.log_level, &self.flags.v8_flags);
+    self.init_telemetry()?;
+
+    let mut extensions = std::mem::take(&mut self.extensions);
+    if let Some(extensions_factory) = &self.extensions_factory {
+      extensions.extend(extensions_factory());
+    }
+
+    let embedder_options = self.embedder_options()?;
+    self.flags.subcommand = DenoSubcommand::Repl(ReplFlags {
+      eval_files: None,
+      eval: None,
+      is_default_command: false,
+    });
+    let factory = CliFactory::from_flags_for_embedder(
+      Arc::new(self.flags),
+      embedder_options,
+    );
+    insert_virtual_files(&factory, self.virtual_files)?;
+    let permissions = resolve_permissions(
+      &factory,
+      self.permissions,
+      self.permission_rules,
+      self.prompt_cache,
+    )?;
+    let stdio = create_stdio(self.stdin, self.stdout, self.stderr)?;
+    ReplSession::with_worker_options(&factory, permissions, extensions, stdio)
+      .await
+  }
+
+  /// Creates a [`WorkerPool`] that shares this builder's configuration, such
+  /// as the config file, import map and unstable features. The permissions
+  /// become the pool's root permissions, while the main module, permissions
+  /// and extensions of every worker are passed to [`WorkerPool::spawn`].
+  pub async fn build_pool(self) -> Result<WorkerPool, DenoRunError> {
+    let exit_mode = self.exit_mode;
+    handle_run_error(exit_mode, self.build_worker_pool().await)
+  }
+
+  async fn build_worker_pool(self) -> Result<WorkerPool, AnyError> {
+    self.validate(BuildMode::Pool)?;
+    init_runtime(self.flags.log_level, &self.flags.v8_flags);
+    self.init_telemetry()?;
+
+    let embedder_options = self.embedder_options()?;
+    let factory = CliFactory::from_flags_for_embedder(
+      Arc::new(self.flags),
+      embedder_options,
+    );
+    insert_virtual_files(&factory, self.virtual_files)?;
+    tools::run::maybe_npm_install(&factory).await?;
+    let worker_factory = factory.create_cli_main_worker_factory().await?;
+    Ok(WorkerPool::new(worker_factory))
+  }
+
+  /// Runs the main module to completion, restarting it on changes when
+  /// [`DenoRuntimeBuilder::watch`] was set, and returns the exit code.
+  pub async fn run(self) -> Result<i32, DenoRunError> {
+    let exit_mode = self.exit_mode;
+    handle_run_error(exit_mode, self.run_worker().await)
+  }
+
+  async fn run_worker(self) -> Result<i32, AnyError> {
+    let watch = match &self.flags.subcommand {
+      DenoSubcommand::Run(run_flags) => run_flags.watch.clone(),
+      _ => unreachable!(),
+    };
+    let Some(watch) = watch else {
+      let mut worker = self.build_worker().await?;
+      return worker.run().await;
+    };
+
+    self.validate(BuildMode::Watch)?;
+    init_runtime(self.flags.log_level, &self.flags.v8_flags);
+    self.init_telemetry()?;
+
+    let mut flags = self.flags;
+    // kept alive for all restarts
+    let maybe_deno_dir = if self.ephemeral_deno_dir {
+      let deno_dir = tempfile::TempDir::new()?;
+      flags.internal.cache_path = Some(deno_dir.path().to_path_buf());
+      Some(deno_dir)
+    } else {
+      None
+    };
+    let result = tools::run::run_script_with_extensions(
+      WorkerExecutionMode::Run,
+      Arc::new(flags),
+      Some(watch),
+      self.extensions_factory.unwrap_or_else(|| Rc::new(Vec::new)),
+    )
+    .await;
+    drop(maybe_deno_dir);
+    result
+  }
+
+  fn embedder_options(&self) -> Result<EmbedderOptions, AnyError> {
+    let ephemeral_deno_dir = if self.ephemeral_deno_dir {
+      Some(Arc::new(tempfile::TempDir::new()?))
+    } else {
+      None
+    };
+    Ok(EmbedderOptions {
+      startup_snapshot: self.startup_snapshot,
+      ephemeral_deno_dir,
+      host_module_resolver: self.host_module_resolver.clone(),
+      import_policy: self.import_policy.clone(),
+      integrity: self.integrity.clone(),
+      token_provider: self.token_provider.clone(),
+      network_options: self.network_options.clone(),
+      retry_options: self.retry_options.clone(),
+      progress_reporter: self.progress_reporter.clone(),
+      npm_store: self.npm_store.clone(),
+      lifecycle_scripts_policy: self.lifecycle_scripts_policy.clone(),
+      virtual_node_modules: self.virtual_node_modules,
+      worker_observer: self.worker_observer.clone(),
+      execution_limits: self.execution_limits.clone(),
+      file_system: self.file_system.clone(),
+      fetch_interceptor: self.fetch_interceptor.clone(),
+      connect_interceptor: self.connect_interceptor.clone(),
+      spawn_interceptor: self.spawn_interceptor.clone(),
+      dlopen_interceptor: self.dlopen_interceptor.clone(),
+      kv_backend: self.kv_backend.clone(),
+      broadcast_channel: self.broadcast_channel.clone(),
+      worker_creation_policy: self.worker_creation_policy.clone(),
+      web_worker_extensions: self.web_worker_extensions.clone(),
+      virtual_env: self.virtual_env.clone(),
+      signal_forwarder: self.signal_forwarder.clone(),
+      resource_quotas: self.resource_quotas.clone(),
+      hmr_controller: self.hmr_controller.clone(),
+      npm_registries: self.npm_registries.clone(),
+      coverage_dir: self.coverage_dir.clone(),
+      inspector_controller: self.inspector_controller.clone(),
+      inspect_mode: self.inspect_mode,
+      determinism: self.determinism.clone(),
+      host_fns: self.host_fns.clone(),
+      serve_adapter: self.serve_adapter,
+      restored_state: self
+        .resume_from
+        .as_ref()
+        .map(|snapshot| snapshot.state.clone()),
+      op_metrics: self
+        .telemetry
+        .as_ref()
+        .is_some_and(|telemetry| telemetry.op_metrics),
+      module_cache: self.module_cache.clone(),
+      transpile_config: self.transpile_config.clone(),
+      wasi: self.wasi.clone(),
+    })
+  }
+
+  fn init_telemetry(&self) -> Result<(), AnyError> {
+    let Some(telemetry) = &self.telemetry else {
+      return Ok(());
+    };
+    if let Some(exporter) = &telemetry.exporter {
+      deno_telemetry::set_exporter(Some(exporter.clone()));
+    }
+    // the exporters are process wide, so only initialize them once
+    static INITIALIZED: AtomicBool = AtomicBool::new(false);
+    if !INITIALIZED.swap(true, Ordering::SeqCst) {
+      if let Some(otel_config) = self.flags.otel_config() {
+        deno_telemetry::init(otel_config)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Checks that the options set on the builder can be used in `mode`.
+  fn validate(&self, mode: BuildMode) -> Result<(), AnyError> {
+    for feature in &self.flags.unstable_config.features {
+      if !UNSTABLE_GRANULAR_FLAGS
+        .iter()
+        .any(|f| f.name == feature.as_str())
+      {
+        bail!("Unknown unstable feature '{}'.", feature);
+      }
+    }
+    if let Some(option) = self
+      .configured_options()
+      .into_iter()
+      .find(|option| !option.is_supported(mode))
+    {
+      bail!("{}", option.unsupported_message(mode));
+    }
+    // wasmtime opens preopened directories on the disk of the host
+    let has_preopened_dirs = self
+      .wasi
+      .as_ref()
+      .is_some_and(|wasi| !wasi.preopened_dirs.is_empty());
+    if has_preopened_dirs && self.file_system.is_some() {
+      bail!(
+        "WASI components can't preopen directories of a custom file system."
+      );
+    }
+    Ok(())
+  }
+
+  /// The options that are set among the ones some [`BuildMode`]s don't
+  /// support.
+  fn configured_options(&self) -> Vec<BuilderOption> {
+    use BuilderOption::*;
+    let options = [
+      (Extensions, !self.extensions.is_empty()),
+      (ExtensionsFactory, self.extensions_factory.is_some()),
+      (RootPermissions, self.permissions.is_some()),
+      (PermissionRules, self.permission_rules.is_some()),
+      (PromptCache, self.prompt_cache.is_some()),
+      (
+        Stdio,
+        self.stdin.is_some() || self.stdout.is_some() || self.stderr.is_some(),
+      ),
+      (StartupSnapshot, self.startup_snapshot.is_some()),
+      (VirtualFiles, !self.virtual_files.is_empty()),
+      (ModuleResolver, self.host_module_resolver.is_some()),
+      (Integrity, self.integrity.is_some()),
+      (TokenProvider, self.token_provider.is_some()),
+      (NetworkOptions, self.network_options.is_some()),
+      (RetryOptions, self.retry_options.is_some()),
+      (ProgressReporter, self.progress_reporter.is_some()),
+      (NpmStore, self.npm_store.is_some()),
+      (
+        LifecycleScriptsPolicy,
+        self.lifecycle_scripts_policy.is_some(),
+      ),
+      (ImportPolicy, self.import_policy.is_some()),
+      (WorkerObserver, self.worker_observer.is_some()),
+      (ExecutionLimits, self.execution_limits.is_some()),
+      (FileSystem, self.file_system.is_some()),
+      (
+        NetworkInterceptors,
+        self.fetch_interceptor.is_some() || self.connect_interceptor.is_some(),
+      ),
+      (
+        ProcessInterceptors,
+        self.spawn_interceptor.is_some() || self.dlopen_interceptor.is_some(),
+      ),
+      (VirtualEnv, self.virtual_env.is_some()),
+      (KvBackend, self.kv_backend.is_some()),
+      (BroadcastChannel, self.broadcast_channel.is_some()),
+      (
+        WebWorkerOptions,
+        self.worker_creation_policy.is_some()
+          || self.web_worker_extensions.is_some(),
+      ),
+      (Progress, self.progress.is_some()),
+      (HmrController, self.hmr_controller.is_some()),
+      (NpmRegistries, self.npm_registries.is_some()),
+      (CoverageDir, self.coverage_dir.is_some()),
+      (InspectorController, self.inspector_controller.is_some()),
+      (
+        InspectWait,
+        self.flags.inspect_wait.is_some() || self.flags.inspect_brk.is_some(),
+      ),
+      (Determinism, self.determinism.is_some()),
+      (HostFns, !self.host_fns.is_empty()),
+      (ServeAdapter, self.serve_adapter),
+      (ResumeFrom, self.resume_from.is_some()),
+      (
+        OpMetrics,
+        self.telemetry.as_ref().is_some_and(|t| t.op_metrics),
+      ),
+      (Wasi, self.wasi.is_some()),
+    ];
+    options
+      .into_iter()
+      .filter_map(|(option, is_set)| is_set.then_some(option))
+      .collect()
+  }
+}
+
+/// What a [`DenoRuntimeBuilder`] is built into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuildMode {
+  /// A main worker, or the modules it loads with
+  /// [`DenoRuntimeBuilder::prepare`].
+  Worker,
+  Template,
+  Repl,
+  Pool,
+  /// A main worker restarted on changes, see [`DenoRuntimeBuilder::watch`].
+  Watch,
+}
+
+impl BuildMode {
+  fn description(self) -> &'static str {
+    match self {
+      BuildMode::Worker => "for main workers",
+      BuildMode::Template => "for templates",
+      BuildMode::Repl => "for REPL sessions",
+      BuildMode::Pool => "for worker pools",
+      BuildMode::Watch => "in watch mode",
+    }
+  }
+}
+
+/// An option of [`DenoRuntimeBuilder`] that isn't supported by every
+/// [`BuildMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuilderOption {
+  Extensions,
+  ExtensionsFactory,
+  RootPermissions,
+  PermissionRules,
+  PromptCache,
+  Stdio,
+  StartupSnapshot,
+  VirtualFiles,
+  ModuleResolver,
+  Integrity,
+  TokenProvider,
+  NetworkOptions,
+  RetryOptions,
+  ProgressReporter,
+  NpmStore,
+  LifecycleScriptsPolicy,
+  ImportPolicy,
+  WorkerObserver,
+  ExecutionLimits,
+  FileSystem,
+  NetworkInterceptors,
+  ProcessInterceptors,
+  VirtualEnv,
+  KvBackend,
+  BroadcastChannel,
+  WebWorkerOptions,
+  Progress,
+  HmrController,
+  NpmRegistries,
+  CoverageDir,
+  InspectorController,
+  InspectWait,
+  Determinism,
+  HostFns,
+  ServeAdapter,
+  ResumeFrom,
+  OpMetrics,
+  Wasi,
+}
+
+impl BuilderOption {
+  fn is_supported(self, mode: BuildMode) -> bool {
+    use BuilderOption::*;
+    match mode {
+      BuildMode::Worker => true,
+      BuildMode::Template => !matches!(
+        self,
+        Extensions
+          | Stdio
+          | HmrController
+          | ResumeFrom
+          | InspectorController
+          | InspectWait
+      ),
+      BuildMode::Repl => {
+        !matches!(self, ExecutionLimits | Progress | HmrController)
+      }
+      BuildMode::Pool => !matches!(
+        self,
+        Extensions
+          | ExtensionsFactory
+          | RootPermissions
+          | PermissionRules
+          | PromptCache
+          | ExecutionLimits
+          | Stdio
+          | VirtualEnv
+          | Progress
+          | HmrController
+          | InspectorController
+          | ResumeFrom
+      ),
+      // the restarted workers are created from flags only
+      BuildMode::Watch => matches!(self, ExtensionsFactory | InspectWait),
+    }
+  }
+
+  fn unsupported_message(self, mode: BuildMode) -> String {
+    use BuilderOption::*;
+    let message = match (self, mode) {
+      (Extensions, BuildMode::Template) => {
+        "Extensions can't be recreated for every run of a template. Use `extensions_factory` instead."
+      }
+      (Extensions, BuildMode::Watch) => {
+        "Extensions can't be recreated when restarting in watch mode. Use `extensions_factory` instead."
+      }
+      (Extensions | ExtensionsFactory, BuildMode::Pool) => {
+        "Extensions of pooled workers are passed to `WorkerPool::spawn`."
+      }
+      (RootPermissions, BuildMode::Pool) => {
+        "Permissions of pooled workers are passed to `WorkerPool::spawn`."
+      }
+      (PermissionRules, BuildMode::Pool) => {
+        "Permission rules of pooled workers are set on the permissions passed to `WorkerPool::spawn`."
+      }
+      (PromptCache, BuildMode::Pool) => {
+        "Prompt caches of pooled workers are set on the permissions passed to `WorkerPool::spawn`."
+      }
+      (ExecutionLimits, BuildMode::Pool) => {
+        "Limits of pooled workers are passed to `WorkerPool::spawn`."
+      }
+      (InspectWait, BuildMode::Template) => {
+        "Every worker of a template would wait for an inspector session, use `InspectMode::Run` instead."
+      }
+      (ImportPolicy, BuildMode::Watch) => {
+        "An import policy is not supported in watch mode, set \"importPolicy\" in the deno.json instead."
+      }
+      (HmrController, BuildMode::Watch) => {
+        "An HMR controller is not supported in watch mode. Use `--watch-hmr` instead."
+      }
+      (InspectorController, BuildMode::Watch) => {
+        "An inspector controller is not supported in watch mode. Use `inspect` instead."
+      }
+      _ => {
+        return format!(
+          "{} not supported {}.",
+          self.subject(),
+          mode.description()
+        )
+      }
+    };
+    message.to_string()
+  }
+
+  fn subject(self) -> &'static str {
+    use BuilderOption::*;
+    match self {
+      Extensions => "Extensions are",
+      ExtensionsFactory => "An extensions factory is",
+      RootPermissions => "Resolved root permissions are",
+      PermissionRules => "Permission rules are",
+      PromptCache => "A prompt cache is",
+      Stdio => "Redirecting stdio is",
+      StartupSnapshot => "A custom startup snapshot is",
+      VirtualFiles => "Virtual files are",
+      ModuleResolver => "A custom module resolver is",
+      Integrity => "Integrity checks are",
+      TokenProvider => "A token provider is",
+      NetworkOptions => "Network options are",
+      RetryOptions => "Retry options are",
+      ProgressReporter => "A progress reporter is",
+      NpmStore => "A shared npm store is",
+      LifecycleScriptsPolicy => "A lifecycle scripts policy is",
+      ImportPolicy => "An import policy is",
+      WorkerObserver => "A worker observer is",
+      ExecutionLimits => "Execution limits are",
+      FileSystem => "A custom file system is",
+      NetworkInterceptors => "Network interceptors are",
+      ProcessInterceptors => "Spawn and dlopen interceptors are",
+      VirtualEnv => "A virtual environment is",
+      KvBackend => "A custom KV backend is",
+      BroadcastChannel => "A shared broadcast channel is",
+      WebWorkerOptions => "Web worker options are",
+      Progress => "Progress events are",
+      HmrController => "An HMR controller is",
+      NpmRegistries => "Configuring npm registries is",
+      CoverageDir => "Collecting coverage is",
+      InspectorController => "An inspector controller is",
+      InspectWait => "Waiting for an inspector session is",
+      Determinism => "Deterministic execution is",
+      HostFns => "Host functions are",
+      ServeAdapter => "The serve adapter is",
+      ResumeFrom => "Resuming from a snapshot is",
+      OpMetrics => "Op metrics are",
+      Wasi => "WASI components are",
+    }
+  }
+}
+
+/// Installs the npm packages, builds the module graph of `main_module`
+/// (type checking it when enabled) and caches the emitted sources.
+async fn prepare_main_module(
+  factory: &CliFactory,
+  main_module: &ModuleSpecifier,
+) -> Result<Arc<ModuleGraph>, AnyError> {
+  tools::run::maybe_npm_install(factory).await?;
+  let main_graph_container = factory.main_module_graph_container().await?;
+  main_graph_container
+    .check_specifiers(&[main_module.clone()], None)
+    .await?;
+  let graph = main_graph_container.graph();
+  factory.emitter()?.cache_module_emits(&graph).await?;
+  Ok(graph)
+}
+
+fn insert_virtual_files(
+  factory: &CliFactory,
+  virtual_files: Vec<(String, Arc<[u8]>)>,
+) -> Result<(), AnyError> {
+  if virtual_files.is_empty() {
+    return Ok(());
+  }
+  let initial_cwd = factory.cli_options()?.initial_cwd();
+  let file_fetcher = factory.file_fetcher()?;
+  for (specifier, source) in virtual_files {
+    file_fetcher.insert_memory_files(File {
+      specifier: resolve_url_or_path(&specifier, initial_cwd)?,
+      maybe_headers: None,
+      source,
+    });
+  }
+  Ok(())
+}
+
+fn resolve_permissions(
+  factory: &CliFactory,
+  permissions: Option<WorkerPermissions>,
+  rules: Option<PermissionRules>,
+  prompt_cache: Option<PromptCache>,
+) -> Result<PermissionsContainer, AnyError> {
+  let container = match permissions {
+    Some(WorkerPermissions::Container(container)) => container,
+    Some(WorkerPermissions::Options(options)) => {
+      let desc_parser = factory.permission_desc_parser()?.clone();
+      let permissions =
+        Permissions::from_options(desc_parser.as_ref(), &options)?;
+      PermissionsContainer::new(desc_parser, permissions)
+    }
+    None => factory.root_permissions_container()?.clone(),
+  };
+  let container = match rules {
+    Some(rules) => container.with_rules(rules),
+    None => container,
+  };
+  Ok(match prompt_cache {
+    Some(prompt_cache) => container.with_prompt_cache(prompt_cache),
+    None => container,
+  })
+}
+
+enum OutputWriter {
+  Sync(Box<dyn Write + Send>),
+  Async(Box<dyn AsyncWrite + Send + Unpin>),
+}
+
+fn create_stdio(
+  stdin: Option<Box<dyn AsyncRead + Send + Unpin>>,
+  stdout: Option<OutputWriter>,
+  stderr: Option<OutputWriter>,
+) -> Result<Stdio, AnyError> {
+  fn output_pipe(writer: Option<OutputWriter>) -> Result<StdioPipe, AnyError> {
+    Ok(match writer {
+      Some(OutputWriter::Sync(writer)) => {
+        StdioPipe::file(pipe_to_writer(writer)?)
+      }
+      Some(OutputWriter::Async(writer)) => {
+        StdioPipe::file(pipe_to_async_writer(writer)?)
+      }
+      None => StdioPipe::inherit(),
+    })
+  }
+
+  Ok(Stdio {
+    stdin: match stdin {
+      Some(reader) => StdioPipe::file(pipe_from_async_reader(reader)?),
+      None => StdioPipe::inherit(),
+    },
+    stdout: output_pipe(stdout)?,
+    stderr: output_pipe(stderr)?,
+  })
+}
+
+fn handle_run_error<T>(
+  exit_mode: ExitMode,
+  result: Result<T, AnyError>,
+) -> Result<T, DenoRunError> {
+  match result {
+    Ok(value) => Ok(value),
+    Err(err) => match exit_mode {
+      ExitMode::ReturnError => Err(err.into()),
+      ExitMode::Exit => DenoRunError::from(err).exit(),
+    },
+  }
+}
+
+/// Runs the module at `path` like `deno run <path>` would, with the provided
+/// extensions added to the main worker, and returns the exit code. Web
+/// workers don't get the extensions, see
+/// [`DenoRuntimeBuilder::all_workers_extensions_factory`]. The other options
+/// are set with a [`DenoRuntimeBuilder`]:
+///
+/// ```ignore
+/// let exit_code = DenoRuntimeBuilder::new("./main.ts")
+///   .execution_limits(limits)
+///   .env(env)
+///   .run()
+///   .await?;
+/// ```
+pub async fn run_file(
+  path: &str,
+  extensions: Vec<Extension>,
+) -> Result<i32, DenoRunError> {
+  DenoRuntimeBuilder::new(path)
+    .extensions(extensions)
+    .run()
+    .await
+}
+
+/// Runs `source` as the main module without touching the disk. `name` is
+/// the module's path relative to the current working directory and its
+/// extension determines the media type, eg. `"main.ts"`. Use
+/// [`DenoRuntimeBuilder::virtual_files`] to provide modules it imports.
+pub async fn run_source(
+  name: &str,
+  source: &str,
+  extensions: Vec<Extension>,
+) -> Result<i32, DenoRunError> {
+  DenoRuntimeBuilder::new(name)
+    .virtual_file(name, source.as_bytes())
+    .extensions(extensions)
+    .run()
+    .await
+}
+
+/// Caches everything the module at `path` needs without running it, see
+/// [`DenoRuntimeBuilder::prepare`]. Useful when installing an application,
+/// so that its first [`run_file`] doesn't have to wait on downloads.
+pub async fn prepare_file(
+  path: &str,
+  type_check: bool,
+) -> Result<PreparedModule, DenoRunError> {
+  DenoRuntimeBuilder::new(path).prepare(type_check).await
+}
+
+/// Evaluates the module at `path` and returns a [`ModuleHandle`] that can be
+/// used to call its exported functions repeatedly.
+///
+/// ```ignore
+/// let mut module = load_file("./math.ts", vec![]).await?;
+/// let sum = module.call_export("add", vec![json!(1), json!(2)]).await?;
+/// ```
+pub async fn load_file(
+  path: &str,
+  extensions: Vec<Extension>,
+) -> Result<ModuleHandle, DenoRunError> {
+  let worker = DenoRuntimeBuilder::new(path)
+    .extensions(extensions)
+    .build()
+    .await?;
+  Ok(worker.into_module_handle().await?)
+}
+
+/// Same as [`run_file`], but with the permission state provided directly
+/// instead of being resolved from permission flags.
+pub async fn run_file_with_permissions(
+  path: &str,
+  permissions: impl Into<WorkerPermissions>,
+  extensions: Vec<Extension>,
+) -> Result<i32, DenoRunError> {
+  DenoRuntimeBuilder::new(path)
+    .root_permissions(permissions)
+    .extensions(extensions)
+    .run()
+    .await
+}
+
+/// Creates main workers for the same main module and configuration, created
+/// with [`DenoRuntimeBuilder::build_template`]. The module graph, resolvers,
+/// npm state and emitted sources are shared by all workers, so that running
+/// the main module repeatedly doesn't pay for them every time.
+///
+/// ```ignore
+/// let template = DenoRuntimeBuilder::new("./handler.ts")
+///   .extensions_factory(|| vec![my_extension::init_ops_and_esm()])
+///   .build_template()
+///   .await?;
+/// for _ in 0..10 {
+///   template.run().await?;
+/// }
+/// ```
+pub struct RuntimeTemplate {
+  // owns the services the worker factory was created from
+  _factory: CliFactory,
+  worker_factory: CliMainWorkerFactory,
+  main_module: ModuleSpecifier,
+  permissions: PermissionsContainer,
+  extensions_factory: Option<ExtensionsFactory>,
+  progress: Option<broadcast::Sender<WorkerProgress>>,
+  exit_mode: ExitMode,
+}
+
+impl RuntimeTemplate {
+  pub fn main_module(&self) -> &ModuleSpecifier {
+    &self.main_module
+  }
+
+  /// Creates a new main worker with a copy of the template's permissions, so
+  /// permissions granted while one worker runs, eg. through a prompt, aren't
+  /// granted to the workers created after.
+  pub async fn create_worker(&self) -> Result<CliMainWorker, DenoRunError> {
+    handle_run_error(self.exit_mode, self.create_main_worker().await)
+  }
+
+  async fn create_main_worker(&self) -> Result<CliMainWorker, AnyError> {
+    let extensions = match &self.extensions_factory {
+      Some(extensions_factory) => extensions_factory(),
+      None => Vec::new(),
+    };
+    let mut worker = self
+      .worker_factory
+      .create_custom_worker(
+        WorkerExecutionMode::Run,
+        self.main_module.clone(),
+        self.permissions.deep_clone(),
+        extensions,
+        Default::default(),
+      )
+      .await?;
+    if let Some(sender) = &self.progress {
+      worker.set_progress_sender(sender.clone());
+    }
+    Ok(worker)
+  }
+
+  /// Runs the main module in a new main worker and returns the exit code.
+  pub async fn run(&self) -> Result<i32, DenoRunError> {
+    let result = match self.create_main_worker().await {
+      Ok(mut worker) => worker.run().await,
+      Err(err) => Err(err),
+    };
+    handle_run_error(self.exit_mode, result)
+  }
+
+  async fn create_module_handle(&self) -> Result<ModuleHandle, AnyError> {
+    self.create_main_worker().await?.into_module_handle().await
+  }
+}
+
+/// Calls an exported function of the main module in a fresh worker every
+/// time, for serverless platforms. Created with [`spawn_function_pool`].
+///
+/// Each worker of the pool runs on its own thread, which builds a
+/// [`RuntimeTemplate`] once, so the module graph and the emitted sources
+/// are shared by the workers it creates, and evaluates the main module
+/// ahead of the next call. Every worker serves a single call and is dropped
+/// afterwards, so calls never see the state of each other. The
+/// [`ExecutionLimits`] of the builder apply to every call. Calls made while
+/// all the workers are busy wait for the first one to be ready.
+///
+/// When creating a worker fails, eg. because the main module throws, the
+/// call that would have used it returns the error.
+pub struct FunctionPool {
+  calls_tx: mpsc::UnboundedSender<PoolCall>,
+  warm: Arc<AtomicUsize>,
+  exit_mode: ExitMode,
+}
+
+struct PoolCall {
+  args: Vec<serde_json::Value>,
+  result_tx: oneshot::Sender<Result<serde_json::Value, AnyError>>,
+}
+
+impl FunctionPool {
+  /// Calls the function with `args` in the next worker that is ready and
+  /// returns its (awaited) return value deserialized as JSON.
+  pub async fn invoke(
+    &self,
+    args: Vec<serde_json::Value>,
+  ) -> Result<serde_json::Value, DenoRunError> {
+    let (result_tx, result_rx) = oneshot::channel();
+    let result = match self.calls_tx.send(PoolCall { args, result_tx }) {
+      Ok(()) => result_rx.await.unwrap_or_else(|_| {
+        Err(generic_error("The worker thread exited unexpectedly."))
+      }),
+      Err(_) => Err(generic_error("All the worker threads exited.")),
+    };
+    handle_run_error(self.exit_mode, result)
+  }
+
+  /// The number of workers ready for a call.
+  pub fn warm_workers(&self) -> usize {
+    self.warm.load(Ordering::SeqCst)
+  }
+}
+
+/// Spawns the `size` threads of a [`FunctionPool`] calling the
+/// `export_name` function of the main module, each with a worker built by
+/// `create_builder`. Returns once every thread evaluated the main module in
+/// its first worker. Set [`DenoRuntimeBuilder::execution_limits`] to limit
+/// every call.
+///
+/// ```ignore
+/// let pool = spawn_function_pool(
+///   || {
+///     DenoRuntimeBuilder::new("./handler.ts").execution_limits(
+///       ExecutionLimits {
+///         cpu_time: Some(Duration::from_millis(50)),
+///         heap_bytes: Some(64 * 1024 * 1024),
+///         ..Default::default()
+///       },
+///     )
+///   },
+///   "handler",
+///   4,
+/// )
+/// .await?;
+/// let response = pool.invoke(vec![json!({ "path": "/users" })]).await?;
+/// ```
+pub async fn spawn_function_pool(
+  create_builder: impl Fn() -> DenoRuntimeBuilder + Send + Sync + 'static,
+  export_name: &str,
+  size: usize,
+) -> Result<FunctionPool, DenoRunError> {
+  let create_builder = Arc::new(create_builder);
+  let (calls_tx, calls_rx) = mpsc::unbounded_channel();
+  let calls_rx = Arc::new(tokio::sync::Mutex::new(calls_rx));
+  let warm = Arc::new(AtomicUsize::new(0));
+  let mut exit_mode = ExitMode::default();
+  for _ in 0..size.max(1) {
+    let create_builder = create_builder.clone();
+    let export_name = export_name.to_string();
+    let calls_rx = calls_rx.clone();
+    let warm = warm.clone();
+    let (ready_tx, ready_rx) = oneshot::channel();
+    spawn_runtime_thread(
+      move || create_builder(),
+      move |builder| {
+        serve_pool_calls(builder, export_name, calls_rx, warm, ready_tx)
+      },
+    )
+    .await;
+    let result = match ready_rx.await {
+      Ok((mode, result)) => {
+        exit_mode = mode;
+        result
+      }
+      Err(_) => Err(generic_error(
+        "The worker thread exited before the worker was created.",
+      )),
+    };
+    // dropping `calls_tx` stops the threads spawned so far
+    handle_run_error(exit_mode, result)?;
+  }
+  Ok(FunctionPool {
+    calls_tx,
+    warm,
+    exit_mode,
+  })
+}
+
+/// Serves the calls of a [`FunctionPool`] on a thread of its own, with one
+/// worker at a time, until the pool is dropped. `ready_tx` gets whether the
+/// first worker could be created.
+async fn serve_pool_calls(
+  builder: DenoRuntimeBuilder,
+  export_name: String,
+  calls_rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<PoolCall>>>,
+  warm: Arc<AtomicUsize>,
+  ready_tx: oneshot::Sender<(ExitMode, Result<(), AnyError>)>,
+) {
+  let exit_mode = builder.exit_mode;
+  let template = match builder.build_runtime_template().await {
+    Ok(template) => template,
+    Err(err) => {
+      let _ = ready_tx.send((exit_mode, Err(err)));
+      return;
+    }
+  };
+  let mut ready_tx = Some(ready_tx);
+  loop {
+    // the worker of the previous call was dropped already, as the isolates
+    // of a thread have to be dropped in the reverse order of their creation
+    let handle = template.create_module_handle().await;
+    if handle.is_ok() {
+      warm.fetch_add(1, Ordering::SeqCst);
+    }
+    let handle = match (handle, ready_tx.take()) {
+      (Err(err), Some(ready_tx)) => {
+        let _ = ready_tx.send((exit_mode, Err(err)));
+        return;
+      }
+      (handle, ready_tx) => {
+        if let Some(ready_tx) = ready_tx {
+          let _ = ready_tx.send((exit_mode, Ok(())));
+        }
+        handle
+      }
+    };
+    let call = calls_rx.lock().await.recv().await;
+    if handle.is_ok() {
+      warm.fetch_sub(1, Ordering::SeqCst);
+    }
+    // the pool was dropped
+    let Some(call) = call else {
+      return;
+    };
+    let result = match handle {
+      Ok(mut handle) => handle.call_export(&export_name, call.args).await,
+      Err(err) => Err(err),
+    };
+    let _ = call.result_tx.send(result);
+  }
+}
+
+/// Error returned by [`RunHandle::join`] when the run was stopped with
+/// [`RunHandle::cancel`] or [`RunHandle::terminate`].
+#[derive(Debug, thiserror::Error)]
+#[error("Script execution was cancelled.")]
+pub struct RunCancelledError;
+
+/// Handle to a main worker running on its own thread, created by
+/// [`spawn_file`] or [`spawn_worker`].
+pub struct RunHandle {
+  isolate_handle: v8::IsolateHandle,
+  cancel_flag: AsyncFlag,
+  shutdown_tx: oneshot::Sender<Duration>,
+  result_rx: oneshot::Receiver<Result<i32, AnyError>>,
+}
+
+impl RunHandle {
+  /// Stops the worker's event loop at the next await point and tears the
+  /// worker down. Synchronously running JavaScript is not interrupted, use
+  /// [`RunHandle::terminate`] for that.
+  pub fn cancel(&self) {
+    self.cancel_flag.raise();
+  }
+
+  /// Interrupts JavaScript execution in the isolate, even if it is stuck in
+  /// a synchronous loop, and then tears the worker down.
+  pub fn terminate(&self) {
+    self.cancel_flag.raise();
+    self.isolate_handle.terminate_execution();
+  }
+
+  /// Stops the worker gracefully, see [`CliMainWorker::shutdown`]: the
+  /// `beforeunload` and `unload` events are dispatched and pending ops, eg.
+  /// file writes started by the handlers, get until `grace` passed to
+  /// complete. JavaScript that still runs then is terminated. Returns the
+  /// exit code.
+  ///
+  /// ```ignore
+  /// let handle = spawn_file("./server.ts", Vec::new).await?;
+  /// tokio::signal::ctrl_c().await?;
+  /// let exit_code = handle.shutdown(Duration::from_secs(5)).await?;
+  /// ```
+  pub async fn shutdown(self, grace: Duration) -> Result<i32, AnyError> {
+    let Self {
+      isolate_handle,
+      cancel_flag,
+      shutdown_tx,
+      mut result_rx,
+    } = self;
+    // fails when the worker already finished, its result is returned then
+    let _ = shutdown_tx.send(grace);
+    if let Ok(result) = tokio::time::timeout(grace, &mut result_rx).await {
+      return worker_thread_result(result);
+    }
+    // the worker is stuck, eg. in a synchronous loop of an unload handler
+    cancel_flag.raise();
+    isolate_handle.terminate_execution();
+    worker_thread_result(result_rx.await)
+  }
+
+  /// Waits for the worker to finish and returns its exit code.
+  pub async fn join(self) -> Result<i32, AnyError> {
+    worker_thread_result(self.result_rx.await)
+  }
+}
+
+fn worker_thread_result(
+  result: Result<Result<i32, AnyError>, oneshot::error::RecvError>,
+) -> Result<i32, AnyError> {
+  match result {
+    Ok(result) => result,
+    Err(_) => Err(generic_error("The worker thread exited unexpectedly.")),
+  }
+}
+
+/// Spawns a thread that runs the module at `path`, returning once the main
+/// worker was created. `extensions` is called on the new thread, because
+/// extensions can't be sent between threads.
+pub async fn spawn_file(
+  path: impl Into<String>,
+  extensions: impl FnOnce() -> Vec<Extension> + Send + 'static,
+) -> Result<RunHandle, AnyError> {
+  let path = path.into();
+  spawn_worker(move || DenoRuntimeBuilder::new(path).extensions(extensions()))
+    .await
+}
+
+/// Spawns a thread that builds a main worker from the builder returned by
+/// `create_builder` and runs it to completion. The log level and V8 flags
+/// of the builder apply to the process if it's the first worker created.
+pub async fn spawn_worker(
+  create_builder: impl FnOnce() -> DenoRuntimeBuilder + Send + 'static,
+) -> Result<RunHandle, AnyError> {
+  spawn_main_worker(create_builder, |_| {}).await
+}
+
+/// Like [`spawn_worker`], calling `on_created` on the new thread with the
+/// worker before it runs.
+async fn spawn_main_worker(
+  create_builder: impl FnOnce() -> DenoRuntimeBuilder + Send + 'static,
+  on_created: impl FnOnce(&mut CliMainWorker) + Send + 'static,
+) -> Result<RunHandle, AnyError> {
+  let cancel_flag = AsyncFlag::default();
+  let (isolate_handle_tx, isolate_handle_rx) = oneshot::channel();
+  let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+  let (result_tx, result_rx) = oneshot::channel();
+  spawn_runtime_thread(create_builder, {
+    let cancel_flag = cancel_flag.clone();
+    move |builder| async move {
+      let mut worker = match builder.build().await {
+        Ok(worker) => worker,
+        Err(err) => {
+          let _ = isolate_handle_tx.send(Err(AnyError::from(err)));
+          return;
+        }
+      };
+      on_created(&mut worker);
+      if isolate_handle_tx.send(Ok(worker.isolate_handle())).is_err() {
+        return;
+      }
+      // the run future borrows the worker, so it's dropped before the
+      // worker is shut down
+      let stopped = tokio::select! {
+        biased;
+        _ = cancel_flag.wait_raised() => Err(None),
+        Ok(grace) = &mut shutdown_rx => Err(Some(grace)),
+        result = worker.run() => Ok(result),
+      };
+      let result = match stopped {
+        Ok(result) => result,
+        Err(None) => Err(RunCancelledError.into()),
+        Err(Some(grace)) => tokio::select! {
+          biased;
+          _ = cancel_flag.wait_raised() => Err(RunCancelledError.into()),
+          result = worker.shutdown(grace) => result,
+        },
+      };
+      drop(worker);
+      let _ = result_tx.send(result);
+    }
+  })
+  .await;
+  let isolate_handle = isolate_handle_rx.await.map_err(|_| {
+    generic_error("The worker thread exited before the worker was created.")
+  })??;
+  Ok(RunHandle {
+    isolate_handle,
+    cancel_flag,
+    shutdown_tx,
+    result_rx,
+  })
+}
+
+/// Spawns a thread that runs the future returned by `run` with the builder
+/// returned by `create_builder`.
+async fn spawn_runtime_thread<F>(
+  create_builder: impl FnOnce() -> DenoRuntimeBuilder + Send + 'static,
+  run: impl FnOnce(DenoRuntimeBuilder) -> F + Send + 'static,
+) where
+  F: Future<Output = ()> + 'static,
+{
+  let (runtime_flags_tx, runtime_flags_rx) = oneshot::channel();
+  let (initialized_tx, initialized_rx) = oneshot::channel::<()>();
+  std::thread::spawn(move || {
+    create_and_run_current_thread(async move {
+      let builder = create_builder();
+      let runtime_flags =
+        (builder.flags.log_level, builder.flags.v8_flags.clone());
+      if runtime_flags_tx.send(runtime_flags).is_err()
+        || initialized_rx.await.is_err()
+      {
+        return;
+      }
+      run(builder).await;
+    })
+  });
+
+  // The V8 platform needs to be initialized on a parent thread of all the
+  // threads that create isolates, with the log level and V8 flags of the
+  // builder of the first worker.
+  if let Ok((log_level, v8_flags)) = runtime_flags_rx.await {
+    init_runtime(log_level, &v8_flags);
+    let _ = initialized_tx.send(());
+  }
+}
+
+/// Serves the `fetch` handler of the main module's default export, like
+/// `deno serve`, on sockets bound by the host instead of a port of its own.
+/// Each of the `worker_count` workers is built by `create_builder` and runs
+/// on its own thread, the requests are passed to them in turn.
+///
+/// ```ignore
+/// let listener = std::net::TcpListener::bind("0.0.0.0:8080")?;
+/// let handle = serve_with_listeners(
+///   || DenoRuntimeBuilder::new("./server.ts"),
+///   vec![listener.into()],
+///   4,
+/// )
+/// .await?;
+/// tokio::signal::ctrl_c().await?;
+/// let exit_code = handle.drain(Duration::from_secs(10)).await?;
+/// ```
+pub async fn serve_with_listeners(
+  create_builder: impl Fn() -> DenoRuntimeBuilder + Send + Sync + 'static,
+  listeners: Vec<ServeListener>,
+  worker_count: usize,
+) -> Result<ServeHandle, AnyError> {
+  let create_builder = Arc::new(create_builder);
+  let mut workers = Vec::with_capacity(worker_count);
+  let mut adapters = Vec::with_capacity(worker_count);
+  for _ in 0..worker_count.max(1) {
+    let create_builder = create_builder.clone();
+    let (adapter_tx, adapter_rx) = oneshot::channel();
+    workers.push(
+      spawn_main_worker(
+        move || create_builder().serve_adapter(),
+        move |worker| {
+          let _ = adapter_tx.send(worker.take_serve_adapter());
+        },
+      )
+      .await?,
+    );
+    let adapter = adapter_rx.await.ok().flatten().ok_or_else(|| {
+      generic_error("The worker was created without a serve adapter.")
+    })?;
+    adapters.push(adapter);
+  }
+  Ok(ServeHandle {
+    server: ServeServer::start(listeners, adapters)?,
+    workers,
+  })
+}
+
+/// Handle to the workers serving the sockets of the host, created by
+/// [`serve_with_listeners`].
+pub struct ServeHandle {
+  server: ServeServer,
+  workers: Vec<RunHandle>,
+}
+
+impl ServeHandle {
+  /// Stops accepting connections, lets the requests in flight complete and
+  /// then shuts the workers down, see [`RunHandle::shutdown`]. Both get up
+  /// to `grace`. Returns the first non-zero exit code of the workers.
+  pub async fn drain(self, grace: Duration) -> Result<i32, AnyError> {
+    self.server.drain(grace).await;
+    let shutdowns = self
+      .workers
+      .into_iter()
+      .map(|worker| worker.shutdown(grace));
+    let results = deno_core::futures::future::join_all(shutdowns).await;
+    let mut exit_code = 0;
+    for result in results {
+      let worker_exit_code = result?;
+      if worker_exit_code != 0 && exit_code == 0 {
+        exit_code = worker_exit_code;
+      }
+    }
+    Ok(exit_code)
+  }
+}
+
+/// Runs the module at `path` and returns the value of its `export_name`
+/// export (or the default export when `None`) deserialized as JSON.
+///
+/// When the export is a function, it is called without arguments after the
+/// module was evaluated and its (awaited) return value is returned instead.
+pub async fn run_file_with_result(
+  path: &str,
+  export_name: Option<&str>,
+  extensions: Vec<Extension>,
+) -> Result<serde_json::Value, DenoRunError> {
+  let mut worker = DenoRuntimeBuilder::new(path)
+    .extensions(extensions)
+    .build()
+    .await?;
+  Ok(
+    worker
+      .run_with_result(export_name.unwrap_or(DEFAULT_RESULT_EXPORT))
+      .await?,
+  )
+}
+
+/// Runs the program that `deno compile` embedded in the current executable,
+/// like `denort` does. `payload_cipher` decrypts the program if it was
+/// encrypted, see [`CompileOptions::payload_cipher`]. Returns the exit code,
+/// or `None` if the executable contains no program.
+///
+/// This is meant for the `main` of executables passed as
+/// [`CompileOptions::base_binary`].
+pub async fn run_standalone(
+  payload_cipher: Option<&dyn PayloadCipher>,
+) -> Result<Option<i32>, DenoRunError> {
+  deno_runtime::deno_permissions::mark_standalone();
+  let args = std::env::args_os().collect::<Vec<_>>();
+  let Some(data) =
+    standalone::extract_standalone(Cow::Owned(args), payload_cipher)?
+  else {
+    return Ok(None);
+  };
+  if let Some(otel_config) = data.metadata.otel_config.clone() {
+    deno_telemetry::init(otel_config)?;
+  }
+  util::logger::init(data.metadata.log_level);
+  for (key, value) in &data.metadata.env_vars_from_env_file {
+    if std::env::var(key).is_err() {
+      std::env::set_var(key, value);
+    }
+  }
+  Ok(Some(standalone::run(data, vec![]).await?))
+}
+
+/// Runs a program archive created by [`create_archive`] or
+/// [`create_archive_with_options`] and returns the exit code. The modules,
+/// npm packages and included files are loaded from the archive, nothing is
+/// fetched or read from the disk, and the program gets the permissions and
+/// the configuration it was archived with. `extensions` are registered on
+/// its main worker.
+///
+/// ```ignore
+/// // in build.rs
+/// let archive = create_archive("./main.ts").await?;
+/// std::fs::write(out_dir.join("main.archive"), archive)?;
+/// // in the application
+/// static ARCHIVE: &[u8] =
+///   include_bytes!(concat!(env!("OUT_DIR"), "/main.archive"));
+/// let exit_code = run_archive(ARCHIVE, vec![my_ext::init_ops()]).await?;
+/// ```
+pub async fn run_archive(
+  bytes: &[u8],
+  extensions: Vec<Extension>,
+) -> Result<i32, DenoRunError> {
+  let data = standalone::extract_archive(bytes)?;
+  init_runtime(data.metadata.log_level, &data.metadata.v8_flags);
+  if let Some(otel_config) = data.metadata.otel_config.clone() {
+    deno_telemetry::init(otel_config)?;
+  }
+  Ok(standalone::run(data, extensions).await?)
+}
+
+/// Fetches the metadata of the JSR package `specifier` refers to, eg.
+/// `"jsr:@std/path@^1"` or `"@std/path"`, from the registry configured by
+/// `flags`.
+pub async fn jsr_package_metadata(
+  flags: Arc<Flags>,
+  specifier: &str,
+) -> Result<JsrPackageMetadata, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  jsr::jsr_package_metadata(factory.file_fetcher()?, specifier).await
+}
+
+fn init_runtime(log_level: Option<log::Level>, v8_flags: &[String]) {
+  // The logger and the V8 platform are process wide, so only initialize
+  // them for the first worker created in this process.
+  static INIT: std::sync::Once = std::sync::Once::new();
+  INIT.call_once(|| {
+    util::logger::init(log_level);
+    init_v8_flags(
+      &["--no-harmony-import-assertions".to_string()],
+      v8_flags,
+      get_v8_flags_from_env(),
+    );
+    deno_core::JsRuntime::init_platform(
+      None, /* import assertions enabled */ false,
+    );
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn validate_options_per_build_mode() {
+    let builder = DenoRuntimeBuilder::new("./main.ts")
+      .execution_limits(ExecutionLimits::default());
+    assert!(builder.validate(BuildMode::Worker).is_ok());
+    assert!(builder.validate(BuildMode::Template).is_ok());
+    assert_eq!(
+      builder.validate(BuildMode::Repl).unwrap_err().to_string(),
+      "Execution limits are not supported for REPL sessions."
+    );
+    assert_eq!(
+      builder.validate(BuildMode::Pool).unwrap_err().to_string(),
+      "Limits of pooled workers are passed to `WorkerPool::spawn`."
+    );
+    assert_eq!(
+      builder.validate(BuildMode::Watch).unwrap_err().to_string(),
+      "Execution limits are not supported in watch mode."
+    );
+
+    let builder =
+      DenoRuntimeBuilder::new("./main.ts").extensions_factory(Vec::new);
+    assert!(builder.validate(BuildMode::Template).is_ok());
+    assert!(builder.validate(BuildMode::Watch).is_ok());
+    assert!(builder.validate(BuildMode::Pool).is_err());
+  }
+
+  #[test]
+  fn validate_wasi_preopens_with_a_custom_file_system() {
+    let builder = DenoRuntimeBuilder::new("./main.ts")
+      .file_system(InMemoryFs::default())
+      .wasi_components(WasiOptions {
+        preopened_dirs: vec![WasiPreopenedDir {
+          host_path: PathBuf::from("./data"),
+          guest_path: "/data".to_string(),
+          writable: false,
+        }],
+        ..Default::default()
+      });
+    assert_eq!(
+      builder.validate(BuildMode::Worker).unwrap_err().to_string(),
+      "WASI components can't preopen directories of a custom file system."
+    );
+  }
+
+  #[test]
+  fn validate_unstable_features() {
+    let builder =
+      DenoRuntimeBuilder::new("./main.ts").unstable_feature("not-a-feature");
+    assert_eq!(
+      builder.validate(BuildMode::Worker).unwrap_err().to_string(),
+      "Unknown unstable feature 'not-a-feature'."
+    );
+  }
+}
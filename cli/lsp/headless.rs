@@ -0,0 +1,275 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::anyhow;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use tower_lsp::lsp_types as lsp;
+use tower_lsp::LanguageServer;
+
+use super::client::Client;
+use super::config::WorkspaceSettings;
+use super::urls::uri_to_url;
+use super::urls::url_to_uri;
+
+/// A zero based position in a document. Like in the language server
+/// protocol, `character` is counted in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TextPosition {
+  pub line: u32,
+  pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TextRange {
+  pub start: TextPosition,
+  pub end: TextPosition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+  pub range: TextRange,
+  pub new_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+  pub specifier: ModuleSpecifier,
+  pub range: TextRange,
+}
+
+/// The edits of a refactoring, grouped by the document they apply to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceEdit {
+  pub changes: BTreeMap<ModuleSpecifier, Vec<TextEdit>>,
+}
+
+/// Runs the analysis of the language server for a workspace without a
+/// client, so tools like codemods can find references and rename symbols
+/// across files.
+///
+/// Documents are read from disk, so changes to files are picked up by later
+/// requests.
+pub struct HeadlessLanguageServer {
+  language_server: super::language_server::LanguageServer,
+}
+
+impl HeadlessLanguageServer {
+  pub async fn new_initialized(
+    root_dir: &Path,
+  ) -> Result<HeadlessLanguageServer, AnyError> {
+    super::logging::set_lsp_log_level(log::Level::Debug);
+    super::logging::set_lsp_warn_level(log::Level::Debug);
+
+    let root_uri = ModuleSpecifier::from_directory_path(root_dir)
+      .map_err(|_| anyhow!("Could not get URI from {}", root_dir.display()))?;
+
+    // the repl client ignores all notifications, which is what we want here
+    let language_server = super::language_server::LanguageServer::new(
+      Client::new_for_repl(),
+      Default::default(),
+    );
+
+    #[allow(deprecated)]
+    language_server
+      .initialize(lsp::InitializeParams {
+        process_id: None,
+        root_path: None,
+        root_uri: Some(url_to_uri(&root_uri)?),
+        initialization_options: Some(serde_json::to_value(
+          get_headless_workspace_settings(),
+        )?),
+        capabilities: lsp::ClientCapabilities::default(),
+        trace: None,
+        workspace_folders: None,
+        client_info: Some(lsp::ClientInfo {
+          name: "Deno headless".to_string(),
+          version: None,
+        }),
+        locale: None,
+        work_done_progress_params: Default::default(),
+      })
+      .await?;
+
+    language_server.initialized(lsp::InitializedParams {}).await;
+
+    Ok(HeadlessLanguageServer { language_server })
+  }
+
+  /// Finds the references to the symbol at `position`.
+  pub async fn references(
+    &self,
+    specifier: &ModuleSpecifier,
+    position: TextPosition,
+    include_declaration: bool,
+  ) -> Result<Vec<SourceLocation>, AnyError> {
+    let locations = self
+      .language_server
+      .references(lsp::ReferenceParams {
+        text_document_position: text_document_position(specifier, position)?,
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: lsp::ReferenceContext {
+          include_declaration,
+        },
+      })
+      .await?
+      .unwrap_or_default();
+    Ok(
+      locations
+        .into_iter()
+        .map(|location| SourceLocation {
+          specifier: uri_to_url(&location.uri),
+          range: location.range.into(),
+        })
+        .collect(),
+    )
+  }
+
+  /// Computes the edits that rename the symbol at `position` to `new_name`
+  /// in every file of the workspace. Returns `None` when there's nothing to
+  /// rename at the position.
+  pub async fn rename(
+    &self,
+    specifier: &ModuleSpecifier,
+    position: TextPosition,
+    new_name: &str,
+  ) -> Result<Option<WorkspaceEdit>, AnyError> {
+    let maybe_edit = self
+      .language_server
+      .rename(lsp::RenameParams {
+        text_document_position: text_document_position(specifier, position)?,
+        new_name: new_name.to_string(),
+        work_done_progress_params: Default::default(),
+      })
+      .await?;
+    Ok(maybe_edit.map(WorkspaceEdit::from))
+  }
+}
+
+fn text_document_position(
+  specifier: &ModuleSpecifier,
+  position: TextPosition,
+) -> Result<lsp::TextDocumentPositionParams, AnyError> {
+  Ok(lsp::TextDocumentPositionParams {
+    text_document: lsp::TextDocumentIdentifier {
+      uri: url_to_uri(specifier)?,
+    },
+    position: lsp::Position {
+      line: position.line,
+      character: position.character,
+    },
+  })
+}
+
+impl From<lsp::Range> for TextRange {
+  fn from(range: lsp::Range) -> Self {
+    TextRange {
+      start: TextPosition {
+        line: range.start.line,
+        character: range.start.character,
+      },
+      end: TextPosition {
+        line: range.end.line,
+        character: range.end.character,
+      },
+    }
+  }
+}
+
+impl From<lsp::TextEdit> for TextEdit {
+  fn from(edit: lsp::TextEdit) -> Self {
+    TextEdit {
+      range: edit.range.into(),
+      new_text: edit.new_text,
+    }
+  }
+}
+
+impl From<lsp::WorkspaceEdit> for WorkspaceEdit {
+  fn from(edit: lsp::WorkspaceEdit) -> Self {
+    let mut changes: BTreeMap<ModuleSpecifier, Vec<TextEdit>> = BTreeMap::new();
+    for (uri, edits) in edit.changes.into_iter().flatten() {
+      changes
+        .entry(uri_to_url(&uri))
+        .or_default()
+        .extend(edits.into_iter().map(TextEdit::from));
+    }
+    let document_edits = match edit.document_changes {
+      Some(lsp::DocumentChanges::Edits(edits)) => edits,
+      Some(lsp::DocumentChanges::Operations(operations)) => operations
+        .into_iter()
+        .filter_map(|operation| match operation {
+          lsp::DocumentChangeOperation::Edit(edit) => Some(edit),
+          // renames only produce text edits
+          lsp::DocumentChangeOperation::Op(_) => None,
+        })
+        .collect(),
+      None => Vec::new(),
+    };
+    for document_edit in document_edits {
+      changes
+        .entry(uri_to_url(&document_edit.text_document.uri))
+        .or_default()
+        .extend(document_edit.edits.into_iter().map(|edit| match edit {
+          lsp::OneOf::Left(edit) => edit.into(),
+          lsp::OneOf::Right(edit) => edit.text_edit.into(),
+        }));
+    }
+    for edits in changes.values_mut() {
+      edits.sort_by_key(|edit| edit.range);
+    }
+    WorkspaceEdit { changes }
+  }
+}
+
+fn get_headless_workspace_settings() -> WorkspaceSettings {
+  WorkspaceSettings {
+    enable: Some(true),
+    lint: false,
+    ..Default::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lsp::urls::uri_parse_unencoded;
+
+  fn range(start: u32, end: u32) -> lsp::Range {
+    lsp::Range {
+      start: lsp::Position::new(0, start),
+      end: lsp::Position::new(0, end),
+    }
+  }
+
+  #[test]
+  fn workspace_edit_from_document_changes() {
+    let uri = uri_parse_unencoded("file:///a.ts").unwrap();
+    let edit = lsp::WorkspaceEdit {
+      changes: None,
+      document_changes: Some(lsp::DocumentChanges::Edits(vec![
+        lsp::TextDocumentEdit {
+          text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+            uri,
+            version: None,
+          },
+          edits: vec![
+            lsp::OneOf::Left(lsp::TextEdit::new(range(10, 13), "b".into())),
+            lsp::OneOf::Left(lsp::TextEdit::new(range(0, 3), "b".into())),
+          ],
+        },
+      ])),
+      change_annotations: None,
+    };
+    let edit = WorkspaceEdit::from(edit);
+    let specifier = ModuleSpecifier::parse("file:///a.ts").unwrap();
+    let edits = &edit.changes[&specifier];
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].range, TextRange::from(range(0, 3)));
+    assert_eq!(edits[1].range, TextRange::from(range(10, 13)));
+  }
+}
@@ -2,11 +2,20 @@
 
 use deno_core::error::AnyError;
 use deno_core::unsync::spawn;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tower_lsp::ClientSocket;
 use tower_lsp::LspService;
 use tower_lsp::Server;
 
-use crate::lsp::language_server::LanguageServer;
 use crate::util::sync::AsyncFlag;
+pub use headless::HeadlessLanguageServer;
+pub use headless::SourceLocation;
+pub use headless::TextEdit;
+pub use headless::TextPosition;
+pub use headless::TextRange;
+pub use headless::WorkspaceEdit;
+pub use language_server::LanguageServer;
 pub use repl::ReplCompletionItem;
 pub use repl::ReplLanguageServer;
 
@@ -21,6 +30,7 @@ mod completions;
 mod config;
 mod diagnostics;
 mod documents;
+mod headless;
 mod jsr;
 pub mod language_server;
 mod logging;
@@ -41,14 +51,52 @@ mod tsc;
 mod urls;
 
 pub async fn start() -> Result<(), AnyError> {
-  let stdin = tokio::io::stdin();
-  let stdout = tokio::io::stdout();
+  start_lsp_with_io(tokio::io::stdin(), tokio::io::stdout()).await
+}
 
+/// Runs the language server on the given streams until the client exits,
+/// for example on a socket or on the two halves of a `tokio::io::duplex`.
+///
+/// Like the rest of the language server this must be polled on a current
+/// thread runtime.
+pub async fn start_lsp_with_io(
+  input: impl AsyncRead + Unpin,
+  output: impl AsyncWrite,
+) -> Result<(), AnyError> {
   let shutdown_flag = AsyncFlag::default();
+  let (service, socket) = build_service(shutdown_flag.clone());
+
+  // TODO(nayeemrmn): This shutdown flag is a workaround for
+  // https://github.com/denoland/deno/issues/20700. Remove when
+  // https://github.com/ebkalderon/tower-lsp/issues/399 is fixed.
+  // Force end the server 8 seconds after receiving a shutdown request.
+  tokio::select! {
+    biased;
+    _ = Server::new(input, output, socket).serve(service) => {}
+    _ = spawn(async move {
+      shutdown_flag.wait_raised().await;
+      tokio::time::sleep(std::time::Duration::from_secs(8)).await;
+    }) => {}
+  }
+
+  Ok(())
+}
+
+/// Creates the language server as a `tower-lsp` service, so a host can
+/// exchange messages with it in process instead of over a byte stream.
+/// Requests are sent through the service and the messages the server sends
+/// to the client are received from the socket.
+pub fn create_lsp_service() -> (LspService<LanguageServer>, ClientSocket) {
+  build_service(Default::default())
+}
+
+fn build_service(
+  shutdown_flag: AsyncFlag,
+) -> (LspService<LanguageServer>, ClientSocket) {
   let builder = LspService::build(|client| {
     language_server::LanguageServer::new(
       client::Client::from_tower(client),
-      shutdown_flag.clone(),
+      shutdown_flag,
     )
   })
   .custom_method(
@@ -75,20 +123,5 @@ pub async fn start() -> Result<(), AnyError> {
     builder
   };
 
-  let (service, socket) = builder.finish();
-
-  // TODO(nayeemrmn): This shutdown flag is a workaround for
-  // https://github.com/denoland/deno/issues/20700. Remove when
-  // https://github.com/ebkalderon/tower-lsp/issues/399 is fixed.
-  // Force end the server 8 seconds after receiving a shutdown request.
-  tokio::select! {
-    biased;
-    _ = Server::new(stdin, stdout, socket).serve(service) => {}
-    _ = spawn(async move {
-      shutdown_flag.wait_raised().await;
-      tokio::time::sleep(std::time::Duration::from_secs(8)).await;
-    }) => {}
-  }
-
-  Ok(())
+  builder.finish()
 }
@@ -676,6 +676,7 @@ impl<'a> ResolverFactory<'a> {
         npmrc,
         npm_system_info: NpmSystemInfo::default(),
         lifecycle_scripts: Default::default(),
+        npm_store: None,
       })
     };
     self.set_npm_resolver(create_cli_npm_resolver_for_lsp(options).await);
@@ -728,6 +729,8 @@ impl<'a> ResolverFactory<'a> {
         bare_node_builtins_enabled: self
           .config_data
           .is_some_and(|d| d.unstable.contains("bare-node-builtins")),
+        host_module_resolver: None,
+        import_policy: None,
       }))
     })
   }
@@ -10,24 +10,31 @@ mod standalone;
 mod args;
 mod auth_tokens;
 mod cache;
+mod determinism;
 mod emit;
 mod errors;
 mod file_fetcher;
+mod host;
+mod host_fn;
 mod http_util;
+mod import_policy;
+mod inspector;
+mod integrity;
 mod js;
 mod node;
 mod npm;
 mod resolver;
+mod serve_adapter;
 mod shared;
 mod task_runner;
 mod util;
 mod version;
+mod wasi;
 mod worker;
 
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
-use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::tokio_util::create_and_run_current_thread_with_maybe_metrics;
 pub use deno_runtime::UNSTABLE_GRANULAR_FLAGS;
 use deno_terminal::colors;
@@ -48,15 +55,6 @@ pub(crate) fn unstable_exit_cb(feature: &str, api_name: &str) {
   deno_runtime::exit(70);
 }
 
-fn exit_with_message(message: &str, code: i32) -> ! {
-  log::error!(
-    "{}: {}",
-    colors::red_bold("error"),
-    message.trim_start_matches("error: ")
-  );
-  deno_runtime::exit(code);
-}
-
 fn unwrap_or_exit<T>(result: Result<T, AnyError>) -> T {
   match result {
     Ok(value) => value,
@@ -64,10 +62,10 @@ fn unwrap_or_exit<T>(result: Result<T, AnyError>) -> T {
       let mut error_string = format!("{:?}", error);
 
       if let Some(e) = error.downcast_ref::<JsError>() {
-        error_string = format_js_error(e);
+        error_string = errors::format_js_error(e);
       }
 
-      exit_with_message(&error_string, 1);
+      errors::exit_with_message(&error_string, 1);
     }
   }
 }
@@ -83,7 +81,7 @@ fn load_env_vars(env_vars: &IndexMap<String, String>) {
 fn main() {
   deno_runtime::deno_permissions::mark_standalone();
   let args: Vec<_> = env::args_os().collect();
-  let standalone = standalone::extract_standalone(Cow::Owned(args));
+  let standalone = standalone::extract_standalone(Cow::Owned(args), None);
   let future = async move {
     match standalone {
       Ok(Some(data)) => {
@@ -92,7 +90,7 @@ fn main() {
         }
         util::logger::init(data.metadata.log_level);
         load_env_vars(&data.metadata.env_vars_from_env_file);
-        let exit_code = standalone::run(data).await?;
+        let exit_code = standalone::run(data, vec![]).await?;
         deno_runtime::exit(exit_code);
       }
       Ok(None) => Ok(()),
@@ -72,6 +72,7 @@ use deno_runtime::deno_node::NodeRequireLoader;
 use deno_runtime::deno_node::NodeResolver;
 use deno_runtime::deno_permissions::PermissionsContainer;
 use deno_semver::npm::NpmPackageReqReference;
+use deno_telemetry::HostSpan;
 use node_resolver::errors::ClosestPkgJsonError;
 use node_resolver::InNpmPackageChecker;
 use node_resolver::NodeResolutionKind;
@@ -144,7 +145,8 @@ impl ModuleLoadPreparer {
     log::debug!("Building module graph.");
     let has_type_checked = !graph.roots.is_empty();
 
-    self
+    let mut span = HostSpan::start("deno.module_resolution");
+    let result = self
       .module_graph_builder
       .build_graph_with_npm_resolution(
         graph,
@@ -155,9 +157,11 @@ impl ModuleLoadPreparer {
           loader: Some(&mut cache),
         },
       )
-      .await?;
-
-    self.graph_roots_valid(graph, roots)?;
+      .await
+      .and_then(|()| self.graph_roots_valid(graph, roots));
+    span.set_result(&result);
+    drop(span);
+    result?;
 
     // write the lockfile if there is one
     if let Some(lockfile) = &self.lockfile {
@@ -168,7 +172,8 @@ impl ModuleLoadPreparer {
 
     // type check if necessary
     if self.options.type_check_mode().is_true() && !has_type_checked {
-      self
+      let mut span = HostSpan::start("deno.type_check");
+      let result = self
         .type_checker
         .check(
           // todo(perf): since this is only done the first time the graph is
@@ -184,7 +189,9 @@ impl ModuleLoadPreparer {
             type_check_mode: self.options.type_check_mode(),
           },
         )
-        .await?;
+        .await;
+      span.set_result(&result);
+      result?;
     }
 
     log::debug!("Prepared module load.");
@@ -398,7 +405,12 @@ impl<TGraphContainer: ModuleGraphContainer>
           .write_hashable(&code)
           .finish();
         let data = cache
-          .get_sync(specifier, code_cache::CodeCacheType::EsModule, code_hash)
+          .get_sync(
+            specifier,
+            code_cache::CodeCacheType::EsModule,
+            code_hash,
+            Some(code.as_bytes()),
+          )
           .map(Cow::from)
           .inspect(|_| {
             // This log line is also used by tests.
@@ -0,0 +1,92 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::PathBuf;
+
+use deno_semver::package::PackageNv;
+
+/// An npm package that requested to run lifecycle scripts while the
+/// packages of a `node_modules` directory were set up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleScriptsPackage {
+  pub package: PackageNv,
+  /// The names of the scripts in the order they run, eg. `["postinstall"]`.
+  pub scripts: Vec<String>,
+}
+
+/// How the lifecycle scripts of a package are handled, decided by a
+/// [`LifecycleScriptsPolicy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LifecycleScriptsDecision {
+  /// The scripts are not run.
+  #[default]
+  Deny,
+  /// The scripts are run like with `--allow-scripts`, with all permissions.
+  Allow,
+  /// The scripts are run by a deno process without the `net`, `run`, `ffi`
+  /// and `sys` permissions, that may only read the `node_modules` directory,
+  /// write to the folder of the package and read the `npm_*` environment
+  /// variables. Only scripts running a file with `node`, eg.
+  /// `node install.js`, can be confined like this, the others are refused.
+  Sandbox,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleScriptsOutcome {
+  /// Denied, or not supported as there's no `node_modules` directory.
+  NotRun,
+  Succeeded,
+  Failed {
+    script: String,
+    exit_code: i32,
+  },
+  /// Sandboxed, but `script` doesn't only run a file with `node`. None of
+  /// the scripts of the package ran.
+  Refused {
+    script: String,
+  },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleScriptsReportEntry {
+  pub package: LifecycleScriptsPackage,
+  pub decision: LifecycleScriptsDecision,
+  pub outcome: LifecycleScriptsOutcome,
+}
+
+/// The packages that requested to run lifecycle scripts during an install,
+/// by a [`LifecycleScriptsPolicy`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleScriptsReport {
+  pub packages: Vec<LifecycleScriptsReportEntry>,
+}
+
+/// Decides whether the lifecycle scripts (`preinstall`, `install` and
+/// `postinstall`) of npm packages run, instead of the `--allow-scripts`
+/// flag. Packages whose scripts ran before are not asked again.
+pub trait LifecycleScriptsPolicy: Send + Sync {
+  fn decide(
+    &self,
+    package: &LifecycleScriptsPackage,
+  ) -> LifecycleScriptsDecision;
+
+  /// Called once the scripts ran, when packages requested scripts.
+  fn report(&self, _report: &LifecycleScriptsReport) {}
+
+  /// The `deno` executable running sandboxed scripts. Defaults to the
+  /// current executable, so it must be set when the host isn't `deno`.
+  fn deno_executable(&self) -> Option<PathBuf> {
+    None
+  }
+}
+
+impl<F> LifecycleScriptsPolicy for F
+where
+  F: Fn(&LifecycleScriptsPackage) -> LifecycleScriptsDecision + Send + Sync,
+{
+  fn decide(
+    &self,
+    package: &LifecycleScriptsPackage,
+  ) -> LifecycleScriptsDecision {
+    self(package)
+  }
+}
@@ -24,13 +24,17 @@ use deno_semver::Version;
 use crate::args::CacheSetting;
 use crate::cache::CACHE_PERM;
 use crate::util::fs::atomic_write_file_with_retries;
+use crate::util::fs::clone_dir_recursive;
 use crate::util::fs::hard_link_dir_recursive;
 
 pub mod registry_info;
+mod store;
 mod tarball;
 mod tarball_extract;
 
 pub use registry_info::RegistryInfoDownloader;
+pub use store::NpmStore;
+pub use store::NpmStoreGcReport;
 pub use tarball::TarballCache;
 
 /// Stores a single copy of npm packages in a cache.
@@ -41,6 +45,7 @@ pub struct NpmCache {
   npmrc: Arc<ResolvedNpmRc>,
   /// ensures a package is only downloaded once per run
   previously_reloaded_packages: Mutex<HashSet<PackageNv>>,
+  store: Option<Arc<NpmStore>>,
 }
 
 impl NpmCache {
@@ -54,9 +59,20 @@ impl NpmCache {
       cache_setting,
       previously_reloaded_packages: Default::default(),
       npmrc,
+      store: None,
     }
   }
 
+  /// Links the packages of `node_modules` directories from `store` instead
+  /// of copying them from the cache.
+  pub fn set_store(&mut self, store: Arc<NpmStore>) {
+    self.store = Some(store);
+  }
+
+  pub fn store(&self) -> Option<&Arc<NpmStore>> {
+    self.store.as_ref()
+  }
+
   pub fn cache_setting(&self) -> &CacheSetting {
     &self.cache_setting
   }
@@ -123,6 +139,29 @@ impl NpmCache {
     Ok(())
   }
 
+  /// Clones the folder of `package` in the cache to `to`, or links its
+  /// files from the store when one is set.
+  pub fn clone_package_folder(
+    &self,
+    package: &PackageNv,
+    to: &Path,
+  ) -> Result<(), AnyError> {
+    let registry_url = self.npmrc.get_registry_url(&package.name);
+    let package_folder =
+      self.package_folder_for_nv_and_url(package, registry_url);
+    match &self.store {
+      Some(store) => {
+        store.link_package(registry_url, package, &package_folder, to)
+      }
+      None => clone_dir_recursive(&package_folder, to),
+    }
+  }
+
+  /// The registry url of the package named `name`.
+  pub fn registry_url(&self, name: &str) -> &Url {
+    self.npmrc.get_registry_url(name)
+  }
+
   pub fn package_folder_for_id(&self, id: &NpmPackageCacheFolderId) -> PathBuf {
     let registry_url = self.npmrc.get_registry_url(&id.nv.name);
     self.cache_dir.package_folder_for_id(
@@ -0,0 +1,422 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_semver::package::PackageNv;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::cache::DenoDir;
+use crate::cache::CACHE_PERM;
+use crate::util::checksum;
+use crate::util::fs::atomic_write_file_with_retries;
+use crate::util::fs::LaxSingleProcessFsFlag;
+
+/// A content addressable store of the files of npm packages, shared by the
+/// `node_modules` directories of many projects like the store of pnpm. The
+/// files are hard linked from the store into `node_modules`, so a file is
+/// stored once no matter how many projects and package versions contain it.
+///
+/// Only used for projects with a `node_modules` directory. Each project
+/// records the package versions it links, the ones no project references
+/// anymore are removed with [`NpmStore::gc`].
+#[derive(Debug)]
+pub struct NpmStore {
+  location: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NpmStoreGcReport {
+  /// Projects whose `node_modules` directory was removed.
+  pub removed_projects: usize,
+  pub removed_packages: usize,
+  pub removed_files: usize,
+  pub removed_bytes: u64,
+}
+
+/// The content keys of the files of a package version, by path relative to
+/// the package folder.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PackageIndex {
+  files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectRecord {
+  node_modules_dir: PathBuf,
+  packages: Vec<String>,
+}
+
+impl NpmStore {
+  /// `location` must be an absolute path.
+  pub fn new(location: PathBuf) -> Result<Self, AnyError> {
+    if !location.is_absolute() {
+      bail!(
+        "The location of the npm store must be an absolute path: {}",
+        location.display()
+      );
+    }
+    Ok(Self { location })
+  }
+
+  /// The store in the `npm_store_v1` folder of `deno_dir`, or of the default
+  /// `DENO_DIR` when `None`.
+  pub fn in_deno_dir(deno_dir: Option<PathBuf>) -> Result<Self, AnyError> {
+    let deno_dir = DenoDir::new(deno_dir)?;
+    Self::new(deno_dir.npm_store_folder_path())
+  }
+
+  pub fn location(&self) -> &Path {
+    &self.location
+  }
+
+  /// Held while projects are linked and while collecting garbage, so the
+  /// collection doesn't remove files a project is about to reference.
+  pub async fn lock(&self) -> Result<LaxSingleProcessFsFlag, AnyError> {
+    fs::create_dir_all(&self.location)
+      .with_context(|| format!("Creating '{}'", self.location.display()))?;
+    Ok(
+      LaxSingleProcessFsFlag::lock(
+        self.location.join("store.lock"),
+        "waiting for file lock on the npm store",
+      )
+      .await,
+    )
+  }
+
+  /// Hard links the files of the package extracted to `package_folder` into
+  /// `to`, adding them to the store first if needed.
+  pub fn link_package(
+    &self,
+    registry_url: &Url,
+    package: &PackageNv,
+    package_folder: &Path,
+    to: &Path,
+  ) -> Result<(), AnyError> {
+    let index_path = self.index_path(&package_key(registry_url, package));
+    let index = match read_json::<PackageIndex>(&index_path)? {
+      Some(index) => index,
+      None => {
+        let index = self.add_package(package_folder)?;
+        fs::create_dir_all(index_path.parent().unwrap())?;
+        atomic_write_file_with_retries(
+          &index_path,
+          serde_json::to_string(&index)?,
+          CACHE_PERM,
+        )?;
+        index
+      }
+    };
+    for (relative_path, key) in &index.files {
+      let to = to.join(relative_path);
+      if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      link_file(&self.file_path(key), &to).with_context(|| {
+        format!("Linking '{}' from the npm store", to.display())
+      })?;
+    }
+    Ok(())
+  }
+
+  /// Records the package versions linked into `node_modules_dir`, replacing
+  /// the previous record of the project.
+  pub fn record_project(
+    &self,
+    node_modules_dir: &Path,
+    packages: impl Iterator<Item = (Url, PackageNv)>,
+  ) -> Result<(), AnyError> {
+    let mut packages = packages
+      .map(|(registry_url, package)| package_key(&registry_url, &package))
+      .collect::<Vec<_>>();
+    packages.sort();
+    packages.dedup();
+    let record = ProjectRecord {
+      node_modules_dir: node_modules_dir.to_path_buf(),
+      packages,
+    };
+    let path = self.location.join("projects").join(format!(
+      "{}.json",
+      checksum::gen(&[node_modules_dir.to_string_lossy().as_bytes()])
+    ));
+    fs::create_dir_all(path.parent().unwrap())?;
+    atomic_write_file_with_retries(
+      &path,
+      serde_json::to_string(&record)?,
+      CACHE_PERM,
+    )?;
+    Ok(())
+  }
+
+  /// Removes the projects whose `node_modules` directory doesn't exist
+  /// anymore, then the package versions and files no project references.
+  pub async fn gc(&self) -> Result<NpmStoreGcReport, AnyError> {
+    let _lock = self.lock().await?;
+    let mut report = NpmStoreGcReport::default();
+
+    let mut referenced_packages = HashSet::new();
+    for path in read_dir_files(&self.location.join("projects"))? {
+      let Some(record) = read_json::<ProjectRecord>(&path)? else {
+        continue;
+      };
+      if record.node_modules_dir.is_dir() {
+        referenced_packages.extend(record.packages);
+      } else {
+        remove_file_if_exists(&path)?;
+        report.removed_projects += 1;
+      }
+    }
+
+    let index_dir = self.location.join("index");
+    let mut referenced_files = HashSet::new();
+    for path in read_dir_files_recursive(&index_dir)? {
+      let path_without_extension = path.with_extension("");
+      let Ok(relative_path) = path_without_extension.strip_prefix(&index_dir)
+      else {
+        continue;
+      };
+      let key = relative_path.to_string_lossy().replace('\\', "/");
+      if referenced_packages.contains(&key) {
+        if let Some(index) = read_json::<PackageIndex>(&path)? {
+          referenced_files.extend(index.files.into_values());
+        }
+      } else {
+        remove_file_if_exists(&path)?;
+        report.removed_packages += 1;
+      }
+    }
+
+    let files_dir = self.location.join("files");
+    for path in read_dir_files_recursive(&files_dir)? {
+      let Ok(relative_path) = path.strip_prefix(&files_dir) else {
+        continue;
+      };
+      let key = relative_path.to_string_lossy().replace(['\\', '/'], "");
+      if !referenced_files.contains(&key) {
+        let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+        remove_file_if_exists(&path)?;
+        report.removed_files += 1;
+        report.removed_bytes += size;
+      }
+    }
+    Ok(report)
+  }
+
+  fn add_package(
+    &self,
+    package_folder: &Path,
+  ) -> Result<PackageIndex, AnyError> {
+    let mut index = PackageIndex::default();
+    for path in read_dir_files_recursive(package_folder)? {
+      let relative_path = path.strip_prefix(package_folder)?;
+      let data = fs::read(&path)
+        .with_context(|| format!("Reading '{}'", path.display()))?;
+      let executable = is_executable(&path)?;
+      let mut key = checksum::gen(&[&data]);
+      if executable {
+        // the mode is shared by the hard links of a file
+        key.push_str("-x");
+      }
+      let file_path = self.file_path(&key);
+      if !file_path.exists() {
+        fs::create_dir_all(file_path.parent().unwrap())?;
+        atomic_write_file_with_retries(
+          &file_path,
+          &data,
+          if executable { 0o755 } else { CACHE_PERM },
+        )?;
+      }
+      index
+        .files
+        .insert(relative_path.to_string_lossy().replace('\\', "/"), key);
+    }
+    Ok(index)
+  }
+
+  fn index_path(&self, package_key: &str) -> PathBuf {
+    self
+      .location
+      .join("index")
+      .join(format!("{package_key}.json"))
+  }
+
+  fn file_path(&self, key: &str) -> PathBuf {
+    self.location.join("files").join(&key[..2]).join(&key[2..])
+  }
+}
+
+/// `<registry host>/<name>/<version>`, eg. `registry.npmjs.org/chalk/5.3.0`.
+fn package_key(registry_url: &Url, package: &PackageNv) -> String {
+  let host = match registry_url.port() {
+    Some(port) => format!("{}_{port}", registry_url.host_str().unwrap_or("")),
+    None => registry_url.host_str().unwrap_or("").to_string(),
+  };
+  format!("{host}/{}/{}", package.name, package.version)
+}
+
+fn link_file(from: &Path, to: &Path) -> std::io::Result<()> {
+  remove_file_if_exists(to)?;
+  if let Err(err) = fs::hard_link(from, to) {
+    // eg. the store is on another device than the project
+    log::debug!(
+      "Failed to hard link {} to {}: {:#}",
+      from.display(),
+      to.display(),
+      err
+    );
+    fs::copy(from, to)?;
+  }
+  Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> std::io::Result<bool> {
+  use std::os::unix::fs::PermissionsExt;
+  Ok(path.metadata()?.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> std::io::Result<bool> {
+  Ok(false)
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(
+  path: &Path,
+) -> Result<Option<T>, AnyError> {
+  match fs::read_to_string(path) {
+    Ok(text) => Ok(Some(serde_json::from_str(&text).with_context(|| {
+      format!("Reading '{}' of the npm store", path.display())
+    })?)),
+    Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(err.into()),
+  }
+}
+
+fn read_dir_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(err) => return Err(err),
+  };
+  let mut files = Vec::new();
+  for entry in entries {
+    let entry = entry?;
+    if entry.file_type()?.is_file() {
+      files.push(entry.path());
+    }
+  }
+  Ok(files)
+}
+
+fn read_dir_files_recursive(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+  let mut files = Vec::new();
+  let mut pending = vec![dir.to_path_buf()];
+  while let Some(dir) = pending.pop() {
+    let entries = match fs::read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(err) if err.kind() == ErrorKind::NotFound => continue,
+      Err(err) => return Err(err),
+    };
+    for entry in entries {
+      let entry = entry?;
+      let file_type = entry.file_type()?;
+      if file_type.is_dir() {
+        pending.push(entry.path());
+      } else if file_type.is_file() {
+        files.push(entry.path());
+      }
+    }
+  }
+  Ok(files)
+}
+
+fn remove_file_if_exists(path: &Path) -> std::io::Result<()> {
+  match fs::remove_file(path) {
+    Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+    _ => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[tokio::test]
+  async fn npm_store_link_and_gc() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let store = NpmStore::new(root.join("store")).unwrap();
+    let registry_url = Url::parse("https://registry.npmjs.org/").unwrap();
+    let package_folder = root.join("cache/chalk/5.3.0");
+    fs::create_dir_all(package_folder.join("source")).unwrap();
+    fs::write(package_folder.join("package.json"), "{}").unwrap();
+    fs::write(package_folder.join("source/index.js"), "export {};").unwrap();
+    fs::write(package_folder.join("source/other.js"), "export {};").unwrap();
+    let chalk = PackageNv::from_str("chalk@5.3.0").unwrap();
+
+    let project_a = root.join("a/node_modules");
+    let project_b = root.join("b/node_modules");
+    for project in [&project_a, &project_b] {
+      store
+        .link_package(
+          &registry_url,
+          &chalk,
+          &package_folder,
+          &project.join("chalk"),
+        )
+        .unwrap();
+      store
+        .record_project(
+          project,
+          [(registry_url.clone(), chalk.clone())].into_iter(),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+      fs::read_to_string(project_b.join("chalk/source/index.js")).unwrap(),
+      "export {};"
+    );
+    // files with the same content are stored once
+    assert_eq!(
+      read_dir_files_recursive(&root.join("store/files"))
+        .unwrap()
+        .len(),
+      2
+    );
+
+    assert_eq!(store.gc().await.unwrap(), NpmStoreGcReport::default());
+    fs::remove_dir_all(root.join("a")).unwrap();
+    assert_eq!(
+      store.gc().await.unwrap(),
+      NpmStoreGcReport {
+        removed_projects: 1,
+        ..Default::default()
+      }
+    );
+    fs::remove_dir_all(root.join("b")).unwrap();
+    assert_eq!(
+      store.gc().await.unwrap(),
+      NpmStoreGcReport {
+        removed_projects: 1,
+        removed_packages: 1,
+        removed_files: 2,
+        removed_bytes: 12,
+      }
+    );
+  }
+  #[test]
+  fn npm_store_relative_location() {
+    assert!(NpmStore::new(PathBuf::from("store")).is_err());
+  }
+}
@@ -6,7 +6,6 @@ use std::sync::Arc;
 use deno_core::anyhow::anyhow;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
-use deno_core::error::custom_error;
 use deno_core::error::AnyError;
 use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::FutureExt;
@@ -19,6 +18,7 @@ use deno_semver::package::PackageNv;
 use http::StatusCode;
 
 use crate::args::CacheSetting;
+use crate::errors::CachedOnlyError;
 use crate::http_util::DownloadError;
 use crate::http_util::HttpClientProvider;
 use crate::npm::common::maybe_auth_header_for_npm_registry;
@@ -148,13 +148,8 @@ impl TarballCache {
       if should_use_cache && package_folder_exists {
         return Ok(());
       } else if tarball_cache.cache.cache_setting() == &CacheSetting::Only {
-        return Err(custom_error(
-          "NotCached",
-          format!(
-            "An npm specifier not found in cache: \"{}\", --cached-only is specified.",
-            &package_nv.name
-          )
-        )
+        return Err(
+          CachedOnlyError::NpmPackage(package_nv.name.clone()).into(),
         );
       }
 
@@ -48,6 +48,7 @@ use crate::util::progress_bar::ProgressBar;
 use crate::util::sync::AtomicFlag;
 
 use self::cache::NpmCache;
+use self::cache::NpmStore;
 use self::registry::CliNpmRegistryApi;
 use self::resolution::NpmResolution;
 use self::resolvers::create_npm_fs_resolver;
@@ -80,6 +81,8 @@ pub struct CliManagedNpmResolverCreateOptions {
   pub npm_install_deps_provider: Arc<NpmInstallDepsProvider>,
   pub npmrc: Arc<ResolvedNpmRc>,
   pub lifecycle_scripts: LifecycleScriptsConfig,
+  /// Links the packages of the `node_modules` directory from this store.
+  pub npm_store: Option<Arc<NpmStore>>,
 }
 
 pub async fn create_managed_npm_resolver_for_lsp(
@@ -191,11 +194,15 @@ fn create_inner(
 }
 
 fn create_cache(options: &CliManagedNpmResolverCreateOptions) -> Arc<NpmCache> {
-  Arc::new(NpmCache::new(
+  let mut npm_cache = NpmCache::new(
     options.npm_cache_dir.clone(),
     options.cache_setting.clone(),
     options.npmrc.clone(),
-  ))
+  );
+  if let Some(npm_store) = &options.npm_store {
+    npm_cache.set_store(npm_store.clone());
+  }
+  Arc::new(npm_cache)
 }
 
 fn create_api(
@@ -422,6 +429,19 @@ impl ManagedCliNpmResolver {
   pub async fn add_package_reqs_raw(
     &self,
     packages: &[PackageReq],
+  ) -> AddPkgReqsResult {
+    let mut result = self.resolve_package_reqs(packages).await;
+    if result.dependencies_result.is_ok() && !packages.is_empty() {
+      result.dependencies_result = self.cache_packages().await;
+    }
+    result
+  }
+
+  /// Adds package requirements to the resolver without caching any package
+  /// files. Use [`ManagedCliNpmResolver::cache_packages`] afterwards.
+  pub async fn resolve_package_reqs(
+    &self,
+    packages: &[PackageReq],
   ) -> AddPkgReqsResult {
     if packages.is_empty() {
       return AddPkgReqsResult {
@@ -437,9 +457,6 @@ impl ManagedCliNpmResolver {
         result.dependencies_result = lockfile.error_if_changed();
       }
     }
-    if result.dependencies_result.is_ok() {
-      result.dependencies_result = self.cache_packages().await;
-    }
 
     result
   }
@@ -2,6 +2,12 @@
 
 use super::bin_entries::BinEntries;
 use crate::args::LifecycleScriptsConfig;
+use crate::npm::LifecycleScriptsDecision;
+use crate::npm::LifecycleScriptsOutcome;
+use crate::npm::LifecycleScriptsPackage;
+use crate::npm::LifecycleScriptsPolicy;
+use crate::npm::LifecycleScriptsReport;
+use crate::npm::LifecycleScriptsReportEntry;
 use crate::task_runner::TaskStdio;
 use crate::util::progress_bar::ProgressBar;
 use deno_core::anyhow::Context;
@@ -11,6 +17,7 @@ use deno_semver::package::PackageNv;
 use deno_semver::Version;
 use deno_task_shell::KillSignal;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::rc::Rc;
 
@@ -44,6 +51,9 @@ pub trait LifecycleScriptsStrategy {
 pub struct LifecycleScripts<'a> {
   packages_with_scripts: Vec<(&'a NpmResolutionPackage, PathBuf)>,
   packages_with_scripts_not_run: Vec<(&'a NpmResolutionPackage, PathBuf)>,
+  /// The decisions of the policy of the config.
+  decisions: HashMap<PackageNv, LifecycleScriptsDecision>,
+  report: LifecycleScriptsReport,
 
   config: &'a LifecycleScriptsConfig,
   strategy: Box<dyn LifecycleScriptsStrategy + 'a>,
@@ -58,6 +68,8 @@ impl<'a> LifecycleScripts<'a> {
       config,
       packages_with_scripts: Vec::new(),
       packages_with_scripts_not_run: Vec::new(),
+      decisions: HashMap::new(),
+      report: LifecycleScriptsReport::default(),
       strategy: Box::new(strategy),
     }
   }
@@ -77,6 +89,86 @@ pub fn has_lifecycle_scripts(
     || package.scripts.contains_key("postinstall")
 }
 
+/// The names of the lifecycle scripts of a package, in the order they run.
+fn lifecycle_script_names<'a>(
+  package: &'a NpmResolutionPackage,
+  package_path: &'a Path,
+) -> impl Iterator<Item = (&'static str, &'a String)> + 'a {
+  ["preinstall", "install", "postinstall"]
+    .into_iter()
+    .filter_map(move |script_name| {
+      let script = package.scripts.get(script_name)?;
+      if script_name == "install"
+        && is_broken_default_install_script(script, package_path)
+      {
+        return None;
+      }
+      Some((script_name, script))
+    })
+}
+
+/// Whether `script` only runs a file with `node`, eg. `node install.js`, so
+/// it can be confined to the permissions of a deno process. Shell syntax
+/// could run other executables, so it's refused.
+fn runs_node_file_only(script: &str) -> bool {
+  const SHELL_SYNTAX: &[char] = &[
+    '\n', '\r', ';', '&', '|', '<', '>', '$', '`', '\'', '"', '\\', '(', ')',
+    '{', '}', '*', '?', '~', '#', '!',
+  ];
+  let mut words = script.split_whitespace();
+  words.next() == Some("node")
+    && words.next().is_some_and(|file| !file.starts_with('-'))
+    && !script.contains(SHELL_SYNTAX)
+}
+
+/// The permissions of the deno processes running sandboxed scripts: they
+/// may read the `node_modules` directory, but only write to the folder of
+/// the package and not access the network.
+fn sandbox_permission_args(
+  package_path: &Path,
+  root_node_modules_dir_path: &Path,
+) -> Vec<String> {
+  vec![
+    format!(
+      "--allow-read={},{}",
+      root_node_modules_dir_path.display(),
+      package_path.display()
+    ),
+    format!("--allow-write={}", package_path.display()),
+    "--allow-env=npm_*".to_string(),
+    "--deny-net".to_string(),
+    "--deny-run".to_string(),
+    "--deny-ffi".to_string(),
+    "--deny-sys".to_string(),
+    "--no-prompt".to_string(),
+  ]
+}
+
+fn set_outcome(
+  report: &mut LifecycleScriptsReport,
+  package_nv: &PackageNv,
+  outcome: LifecycleScriptsOutcome,
+) {
+  if let Some(entry) = report
+    .packages
+    .iter_mut()
+    .find(|entry| &entry.package.package == package_nv)
+  {
+    entry.outcome = outcome;
+  }
+}
+
+fn report_to_policy(
+  config: &LifecycleScriptsConfig,
+  report: &LifecycleScriptsReport,
+) {
+  if let Some(policy) = &config.policy {
+    if !report.packages.is_empty() {
+      policy.report(report);
+    }
+  }
+}
+
 // npm defaults to running `node-gyp rebuild` if there is a `binding.gyp` file
 // but it always fails if the package excludes the `binding.gyp` file when they publish.
 // (for example, `fsevents` hits this)
@@ -89,6 +181,9 @@ impl<'a> LifecycleScripts<'a> {
     if !self.strategy.can_run_scripts() {
       return false;
     }
+    if self.config.policy.is_some() {
+      return self.decision(package_nv) != LifecycleScriptsDecision::Deny;
+    }
     use crate::args::PackagesAllowedScripts;
     match &self.config.allowed {
       PackagesAllowedScripts::All => true,
@@ -103,6 +198,31 @@ impl<'a> LifecycleScripts<'a> {
   pub fn has_run_scripts(&self, package: &NpmResolutionPackage) -> bool {
     self.strategy.has_run(package)
   }
+
+  fn decision(&self, package_nv: &PackageNv) -> LifecycleScriptsDecision {
+    self.decisions.get(package_nv).copied().unwrap_or_default()
+  }
+
+  fn decide(
+    &mut self,
+    policy: &dyn LifecycleScriptsPolicy,
+    package: &NpmResolutionPackage,
+    package_path: &Path,
+  ) {
+    let request = LifecycleScriptsPackage {
+      package: package.id.nv.clone(),
+      scripts: lifecycle_script_names(package, package_path)
+        .map(|(script_name, _)| script_name.to_string())
+        .collect(),
+    };
+    let decision = policy.decide(&request);
+    self.decisions.insert(package.id.nv.clone(), decision);
+    self.report.packages.push(LifecycleScriptsReportEntry {
+      package: request,
+      decision,
+      outcome: LifecycleScriptsOutcome::NotRun,
+    });
+  }
   /// Register a package for running lifecycle scripts, if applicable.
   ///
   /// `package_path` is the path containing the package's code (its root dir).
@@ -114,6 +234,12 @@ impl<'a> LifecycleScripts<'a> {
     package_path: Cow<Path>,
   ) {
     if has_lifecycle_scripts(package, &package_path) {
+      let config = self.config;
+      if let Some(policy) = &config.policy {
+        if !self.has_run_scripts(package) {
+          self.decide(policy.as_ref(), package, &package_path);
+        }
+      }
       if self.can_run_scripts(&package.id.nv) {
         if !self.has_run_scripts(package) {
           self
@@ -121,6 +247,9 @@ impl<'a> LifecycleScripts<'a> {
             .push((package, package_path.into_owned()));
         }
       } else if !self.has_run_scripts(package)
+        // the host learns about the packages denied by its policy from the report
+        && (self.config.policy.is_none()
+          || self.decision(&package.id.nv) != LifecycleScriptsDecision::Deny)
         && (self.config.explicit_install || !self.strategy.has_warned(package))
       {
         // Skip adding `esbuild` as it is known that it can work properly without lifecycle script
@@ -150,6 +279,12 @@ impl<'a> LifecycleScripts<'a> {
     Ok(())
   }
 
+  /// Sends the report to the policy of the config, for strategies that
+  /// can't run scripts.
+  pub fn report_not_run_scripts(&self) {
+    report_to_policy(self.config, &self.report);
+  }
+
   pub async fn finish(
     self,
     snapshot: &NpmResolutionSnapshot,
@@ -184,6 +319,7 @@ impl<'a> LifecycleScripts<'a> {
     let get_package_path =
       |p: &NpmResolutionPackage| self.strategy.package_path(p);
     let mut failed_packages = Vec::new();
+    let mut report = self.report;
     let mut bin_entries = BinEntries::new();
     if !self.packages_with_scripts.is_empty() {
       let package_ids = self
@@ -232,79 +368,131 @@ impl<'a> LifecycleScripts<'a> {
         (temp_file_fd as usize).to_string(),
       );
       for (package, package_path) in self.packages_with_scripts {
-        // add custom commands for binaries from the package's dependencies. this will take precedence over the
-        // baseline commands, so if the package relies on a bin that conflicts with one higher in the dependency tree, the
-        // correct bin will be used.
-        let custom_commands = resolve_custom_commands_from_deps(
-          base.clone(),
-          package,
-          snapshot,
-          get_package_path,
-        )?;
-        for script_name in ["preinstall", "install", "postinstall"] {
-          if let Some(script) = package.scripts.get(script_name) {
-            if script_name == "install"
-              && is_broken_default_install_script(script, &package_path)
-            {
-              continue;
-            }
-            let _guard = progress_bar.update_with_prompt(
-              crate::util::progress_bar::ProgressMessagePrompt::Initialize,
-              &format!("{}: running '{script_name}' script", package.id.nv),
+        let sandboxed = self.decisions.get(&package.id.nv)
+          == Some(&LifecycleScriptsDecision::Sandbox);
+        let custom_commands = if sandboxed {
+          // the scripts are checked before any of them runs, so the package
+          // isn't left half set up
+          if let Some((script_name, script)) =
+            lifecycle_script_names(package, &package_path)
+              .find(|(_, script)| !runs_node_file_only(script))
+          {
+            log::warn!(
+              "{} script '{}' in '{}' was not run, as only scripts running a file with `node` can be sandboxed: {}",
+              crate::colors::yellow("Warning"),
+              script_name,
+              package.id.nv,
+              script,
             );
-            let crate::task_runner::TaskResult {
-              exit_code,
-              stderr,
-              stdout,
-            } = crate::task_runner::run_task(
-              crate::task_runner::RunTaskOptions {
-                task_name: script_name,
-                script,
-                cwd: &package_path,
-                env_vars: env_vars.clone(),
-                custom_commands: custom_commands.clone(),
-                init_cwd,
-                argv: &[],
-                root_node_modules_dir: Some(root_node_modules_dir_path),
-                stdio: Some(crate::task_runner::TaskIo {
-                  stderr: TaskStdio::piped(),
-                  stdout: TaskStdio::piped(),
-                }),
-                kill_signal: kill_signal.clone(),
+            set_outcome(
+              &mut report,
+              &package.id.nv,
+              LifecycleScriptsOutcome::Refused {
+                script: script_name.to_string(),
               },
-            )
+            );
+            continue;
+          }
+          // only `node` is available, other commands are not found
+          let deno_executable = match self
+            .config
+            .policy
+            .as_ref()
+            .and_then(|policy| policy.deno_executable())
+          {
+            Some(deno_executable) => deno_executable,
+            None => std::env::current_exe()?,
+          };
+          let mut custom_commands =
+            crate::task_runner::TaskCustomCommands::new();
+          custom_commands.insert(
+            "node".to_string(),
+            Rc::new(crate::task_runner::SandboxedNodeCommand {
+              deno_executable,
+              permission_args: sandbox_permission_args(
+                &package_path,
+                root_node_modules_dir_path,
+              ),
+            }),
+          );
+          custom_commands
+        } else {
+          // add custom commands for binaries from the package's dependencies. this will take precedence over the
+          // baseline commands, so if the package relies on a bin that conflicts with one higher in the dependency tree, the
+          // correct bin will be used.
+          resolve_custom_commands_from_deps(
+            base.clone(),
+            package,
+            snapshot,
+            get_package_path,
+          )?
+        };
+        let mut outcome = LifecycleScriptsOutcome::Succeeded;
+        for (script_name, script) in
+          lifecycle_script_names(package, &package_path)
+        {
+          let _guard = progress_bar.update_with_prompt(
+            crate::util::progress_bar::ProgressMessagePrompt::Initialize,
+            &format!("{}: running '{script_name}' script", package.id.nv),
+          );
+          let crate::task_runner::TaskResult {
+            exit_code,
+            stderr,
+            stdout,
+          } =
+            crate::task_runner::run_task(crate::task_runner::RunTaskOptions {
+              task_name: script_name,
+              script,
+              cwd: &package_path,
+              env_vars: env_vars.clone(),
+              custom_commands: custom_commands.clone(),
+              init_cwd,
+              argv: &[],
+              // the `.bin` folder isn't added to the `PATH` when sandboxed
+              root_node_modules_dir: (!sandboxed)
+                .then_some(root_node_modules_dir_path),
+              stdio: Some(crate::task_runner::TaskIo {
+                stderr: TaskStdio::piped(),
+                stdout: TaskStdio::piped(),
+              }),
+              kill_signal: kill_signal.clone(),
+            })
             .await?;
-            let stdout = stdout.unwrap();
-            let stderr = stderr.unwrap();
-            if exit_code != 0 {
-              log::warn!(
-                "error: script '{}' in '{}' failed with exit code {}{}{}",
-                script_name,
-                package.id.nv,
-                exit_code,
-                if !stdout.trim_ascii().is_empty() {
-                  format!(
-                    "\nstdout:\n{}\n",
-                    String::from_utf8_lossy(&stdout).trim()
-                  )
-                } else {
-                  String::new()
-                },
-                if !stderr.trim_ascii().is_empty() {
-                  format!(
-                    "\nstderr:\n{}\n",
-                    String::from_utf8_lossy(&stderr).trim()
-                  )
-                } else {
-                  String::new()
-                },
-              );
-              failed_packages.push(&package.id.nv);
-              // assume if earlier script fails, later ones will fail too
-              break;
-            }
+          let stdout = stdout.unwrap();
+          let stderr = stderr.unwrap();
+          if exit_code != 0 {
+            log::warn!(
+              "error: script '{}' in '{}' failed with exit code {}{}{}",
+              script_name,
+              package.id.nv,
+              exit_code,
+              if !stdout.trim_ascii().is_empty() {
+                format!(
+                  "\nstdout:\n{}\n",
+                  String::from_utf8_lossy(&stdout).trim()
+                )
+              } else {
+                String::new()
+              },
+              if !stderr.trim_ascii().is_empty() {
+                format!(
+                  "\nstderr:\n{}\n",
+                  String::from_utf8_lossy(&stderr).trim()
+                )
+              } else {
+                String::new()
+              },
+            );
+            failed_packages.push(&package.id.nv);
+            outcome = LifecycleScriptsOutcome::Failed {
+              script: script_name.to_string(),
+              exit_code,
+            };
+            // assume if earlier script fails, later ones will fail too
+            break;
           }
         }
+        set_outcome(&mut report, &package.id.nv, outcome);
         self.strategy.did_run_scripts(package)?;
       }
 
@@ -319,6 +507,7 @@ impl<'a> LifecycleScripts<'a> {
         &package_ids,
       )?;
     }
+    report_to_policy(self.config, &report);
     if failed_packages.is_empty() {
       Ok(())
     } else {
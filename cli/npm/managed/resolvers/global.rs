@@ -174,6 +174,7 @@ impl NpmPackageFsResolver for GlobalNpmPackageResolver {
     }
 
     lifecycle_scripts.warn_not_run_scripts()?;
+    lifecycle_scripts.report_not_run_scripts();
 
     Ok(())
   }
@@ -243,7 +244,15 @@ impl<'a> super::common::lifecycle_scripts::LifecycleScriptsStrategy
     log::warn!("┖─ {}", colors::bold("\"nodeModulesDir\": \"auto\""));
 
     for (package, _) in packages {
-      std::fs::write(self.warned_scripts_file(package), "")?;
+      // the cache may be read-only, eg. with virtual node_modules
+      let path = self.warned_scripts_file(package);
+      if let Err(err) = std::fs::write(&path, "") {
+        log::debug!(
+          "Failed to write warned scripts file {}: {:#}",
+          path.display(),
+          err
+        );
+      }
     }
     Ok(())
   }
@@ -321,6 +321,12 @@ async fn sync_resolution_with_fs(
   )
   .await;
 
+  // prevents a garbage collection of the store while packages are linked
+  let _store_lock = match cache.store() {
+    Some(store) => Some(store.lock().await?),
+    None => None,
+  };
+
   // load this after we get the directory lock
   let mut setup_cache =
     SetupCache::load(deno_local_registry_dir.join(".setup-cache.bin"));
@@ -411,12 +417,12 @@ async fn sync_resolution_with_fs(
         let sub_node_modules = folder_path.join("node_modules");
         let package_path =
           join_package_name(&sub_node_modules, &package.id.nv.name);
-        let cache_folder = cache.package_folder_for_nv(&package.id.nv);
-
         deno_core::unsync::spawn_blocking({
+          let cache = cache.clone();
+          let package_nv = package.id.nv.clone();
           let package_path = package_path.clone();
           move || {
-            clone_dir_recursive(&cache_folder, &package_path)?;
+            cache.clone_package_folder(&package_nv, &package_path)?;
             // write out a file that indicates this folder has been initialized
             fs::write(initialized_file, tags)?;
 
@@ -453,6 +459,18 @@ async fn sync_resolution_with_fs(
     result?; // surface the first error
   }
 
+  if let Some(store) = cache.store() {
+    store.record_project(
+      root_node_modules_dir_path,
+      package_partitions.packages.iter().map(|package| {
+        (
+          cache.registry_url(&package.id.nv.name).clone(),
+          package.id.nv.clone(),
+        )
+      }),
+    )?;
+  }
+
   // 2. Create any "copy" packages, which are used for peer dependencies
   for package in &package_partitions.copy_packages {
     let package_cache_folder_id = package.get_package_cache_folder_id();
@@ -2,7 +2,9 @@
 
 mod byonm;
 mod common;
+mod lifecycle_scripts_policy;
 mod managed;
+mod virtual_node_modules;
 
 use std::borrow::Cow;
 use std::path::Path;
@@ -31,10 +33,20 @@ use crate::file_fetcher::FileFetcher;
 
 pub use self::byonm::CliByonmNpmResolver;
 pub use self::byonm::CliByonmNpmResolverCreateOptions;
+pub use self::lifecycle_scripts_policy::LifecycleScriptsDecision;
+pub use self::lifecycle_scripts_policy::LifecycleScriptsOutcome;
+pub use self::lifecycle_scripts_policy::LifecycleScriptsPackage;
+pub use self::lifecycle_scripts_policy::LifecycleScriptsPolicy;
+pub use self::lifecycle_scripts_policy::LifecycleScriptsReport;
+pub use self::lifecycle_scripts_policy::LifecycleScriptsReportEntry;
+pub use self::managed::cache::NpmStore;
+pub use self::managed::cache::NpmStoreGcReport;
 pub use self::managed::CliManagedInNpmPkgCheckerCreateOptions;
 pub use self::managed::CliManagedNpmResolverCreateOptions;
 pub use self::managed::CliNpmResolverManagedSnapshotOption;
 pub use self::managed::ManagedCliNpmResolver;
+pub use self::virtual_node_modules::ManagedNodeModulesPackages;
+pub use self::virtual_node_modules::VirtualNodeModulesFs;
 
 pub enum CliNpmResolverCreateOptions {
   Managed(CliManagedNpmResolverCreateOptions),
@@ -0,0 +1,686 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::Weak;
+
+use deno_runtime::deno_fs::AccessCheckCb;
+use deno_runtime::deno_fs::FileSystem;
+use deno_runtime::deno_fs::FsDirEntry;
+use deno_runtime::deno_fs::FsFileType;
+use deno_runtime::deno_fs::OpenOptions;
+use deno_runtime::deno_fs::RealFs;
+use deno_runtime::deno_io::fs::File;
+use deno_runtime::deno_io::fs::FsError;
+use deno_runtime::deno_io::fs::FsResult;
+use deno_runtime::deno_io::fs::FsStat;
+
+use super::CliNpmResolver;
+
+/// The top-level npm packages served in a virtual `node_modules` directory.
+pub trait NodeModulesPackages: std::fmt::Debug + Send + Sync {
+  /// The names of the packages, eg. `["@std/path", "chalk"]`.
+  fn package_names(&self) -> Vec<String>;
+
+  /// The folder of the package in the global npm cache.
+  fn package_folder(&self, name: &str) -> Option<PathBuf>;
+}
+
+/// The top-level packages of the resolution of a managed npm resolver. It's
+/// weak as the npm resolver uses the file system the packages are served by.
+#[derive(Debug)]
+pub struct ManagedNodeModulesPackages(pub Weak<dyn CliNpmResolver>);
+
+impl NodeModulesPackages for ManagedNodeModulesPackages {
+  fn package_names(&self) -> Vec<String> {
+    let Some(npm_resolver) = self.0.upgrade() else {
+      return Vec::new();
+    };
+    let Some(npm_resolver) = npm_resolver.as_managed() else {
+      return Vec::new();
+    };
+    npm_resolver
+      .snapshot()
+      .package_reqs()
+      .values()
+      .map(|nv| nv.name.to_string())
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .collect()
+  }
+
+  fn package_folder(&self, name: &str) -> Option<PathBuf> {
+    let npm_resolver = self.0.upgrade()?;
+    let npm_resolver = npm_resolver.as_managed()?;
+    let req = npm_resolver.top_package_req_for_name(name)?;
+    let id = npm_resolver.resolve_pkg_id_from_pkg_req(&req).ok()?;
+    npm_resolver.resolve_pkg_folder_from_pkg_id(&id).ok()
+  }
+}
+
+#[derive(Debug)]
+struct VirtualNodeModulesRoot {
+  node_modules_dir: PathBuf,
+  packages: Arc<dyn NodeModulesPackages>,
+}
+
+/// Where a path of the file system leads to.
+#[derive(Debug)]
+enum VirtualPath {
+  /// A path outside of the virtual `node_modules` directory.
+  Real(PathBuf),
+  /// A path in a package, mapped to the global npm cache.
+  Package(PathBuf),
+  /// The `node_modules` directory or the folder of a scope.
+  Dir(Vec<String>),
+  NotFound,
+}
+
+/// A file system that serves the `node_modules` directory of a project from
+/// the global npm cache without creating it. `node_modules/<name>` is the
+/// folder of the top-level package `<name>` in the cache and the directory
+/// is read-only. Other paths are on the real file system.
+#[derive(Debug, Default)]
+pub struct VirtualNodeModulesFs {
+  root: OnceLock<VirtualNodeModulesRoot>,
+}
+
+impl VirtualNodeModulesFs {
+  /// Serves `node_modules_dir` from `packages`. Until then the file system
+  /// is the real one. Only the first call has an effect.
+  pub fn set_packages(
+    &self,
+    node_modules_dir: PathBuf,
+    packages: Arc<dyn NodeModulesPackages>,
+  ) {
+    let _ = self.root.set(VirtualNodeModulesRoot {
+      node_modules_dir,
+      packages,
+    });
+  }
+
+  fn resolve(&self, path: &Path) -> VirtualPath {
+    let Some(root) = self.root.get() else {
+      return VirtualPath::Real(path.to_path_buf());
+    };
+    let Ok(relative) = path.strip_prefix(&root.node_modules_dir) else {
+      return VirtualPath::Real(path.to_path_buf());
+    };
+    let mut components = relative.components().filter_map(|c| match c {
+      Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+      _ => None,
+    });
+    let Some(first) = components.next() else {
+      // the top-level entries, with scoped packages grouped by scope
+      let entries = root
+        .packages
+        .package_names()
+        .into_iter()
+        .map(|name| match name.split_once('/') {
+          Some((scope, _)) => scope.to_string(),
+          None => name,
+        })
+        .collect::<BTreeSet<_>>();
+      return VirtualPath::Dir(entries.into_iter().collect());
+    };
+    let name = if first.starts_with('@') {
+      let Some(second) = components.next() else {
+        let prefix = format!("{first}/");
+        let entries = root
+          .packages
+          .package_names()
+          .into_iter()
+          .filter_map(|name| Some(name.strip_prefix(&prefix)?.to_string()))
+          .collect::<Vec<_>>();
+        return if entries.is_empty() {
+          VirtualPath::NotFound
+        } else {
+          VirtualPath::Dir(entries)
+        };
+      };
+      format!("{first}/{second}")
+    } else {
+      first
+    };
+    match root.packages.package_folder(&name) {
+      Some(folder) => VirtualPath::Package(folder.join(
+        components.fold(PathBuf::new(), |path, component| path.join(component)),
+      )),
+      None => VirtualPath::NotFound,
+    }
+  }
+
+  fn error_if_virtual(&self, path: &Path) -> FsResult<()> {
+    match self.resolve(path) {
+      VirtualPath::Real(_) => Ok(()),
+      _ => Err(read_only_error()),
+    }
+  }
+
+  fn dir_stat(&self) -> FsResult<FsStat> {
+    // the project directory stands in for the virtual directories
+    let cache_dir = self
+      .root
+      .get()
+      .and_then(|root| root.node_modules_dir.parent())
+      .map(|path| path.to_path_buf())
+      .unwrap_or_else(std::env::temp_dir);
+    RealFs.stat_sync(&cache_dir)
+  }
+
+  fn read_real_path(&self, path: &Path) -> FsResult<PathBuf> {
+    match self.resolve(path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => Ok(path),
+      VirtualPath::Dir(_) => Err(FsError::Io(std::io::Error::new(
+        ErrorKind::Other,
+        "Is a directory",
+      ))),
+      VirtualPath::NotFound => Err(not_found_error(path)),
+    }
+  }
+
+  fn dir_entries(names: Vec<String>) -> Vec<FsDirEntry> {
+    names
+      .into_iter()
+      .map(|name| FsDirEntry {
+        name,
+        is_file: false,
+        is_directory: true,
+        is_symlink: false,
+      })
+      .collect()
+  }
+}
+
+fn read_only_error() -> FsError {
+  FsError::Io(std::io::Error::new(
+    ErrorKind::PermissionDenied,
+    "The virtual node_modules directory is read-only",
+  ))
+}
+
+fn not_found_error(path: &Path) -> FsError {
+  FsError::Io(std::io::Error::new(
+    ErrorKind::NotFound,
+    format!("No such file or directory: {}", path.display()),
+  ))
+}
+
+fn is_write(options: &OpenOptions) -> bool {
+  options.write
+    || options.append
+    || options.create
+    || options.create_new
+    || options.truncate
+}
+
+#[async_trait::async_trait(?Send)]
+impl FileSystem for VirtualNodeModulesFs {
+  fn cwd(&self) -> FsResult<PathBuf> {
+    RealFs.cwd()
+  }
+
+  fn tmp_dir(&self) -> FsResult<PathBuf> {
+    RealFs.tmp_dir()
+  }
+
+  fn chdir(&self, path: &Path) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.chdir(path)
+  }
+
+  fn umask(&self, mask: Option<u32>) -> FsResult<u32> {
+    RealFs.umask(mask)
+  }
+
+  fn open_sync(
+    &self,
+    path: &Path,
+    options: OpenOptions,
+    access_check: Option<AccessCheckCb>,
+  ) -> FsResult<Rc<dyn File>> {
+    match self.resolve(path) {
+      VirtualPath::Real(path) => RealFs.open_sync(&path, options, access_check),
+      VirtualPath::Package(path) if !is_write(&options) => {
+        RealFs.open_sync(&path, options, access_check)
+      }
+      VirtualPath::NotFound if !is_write(&options) => {
+        Err(not_found_error(path))
+      }
+      _ => Err(read_only_error()),
+    }
+  }
+  async fn open_async<'a>(
+    &'a self,
+    path: PathBuf,
+    options: OpenOptions,
+    access_check: Option<AccessCheckCb<'a>>,
+  ) -> FsResult<Rc<dyn File>> {
+    match self.resolve(&path) {
+      VirtualPath::Real(path) => {
+        RealFs.open_async(path, options, access_check).await
+      }
+      VirtualPath::Package(path) if !is_write(&options) => {
+        RealFs.open_async(path, options, access_check).await
+      }
+      VirtualPath::NotFound if !is_write(&options) => {
+        Err(not_found_error(&path))
+      }
+      _ => Err(read_only_error()),
+    }
+  }
+
+  fn mkdir_sync(
+    &self,
+    path: &Path,
+    recursive: bool,
+    mode: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.mkdir_sync(path, recursive, mode)
+  }
+  async fn mkdir_async(
+    &self,
+    path: PathBuf,
+    recursive: bool,
+    mode: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs.mkdir_async(path, recursive, mode).await
+  }
+
+  fn chmod_sync(&self, path: &Path, mode: u32) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.chmod_sync(path, mode)
+  }
+  async fn chmod_async(&self, path: PathBuf, mode: u32) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs.chmod_async(path, mode).await
+  }
+
+  fn chown_sync(
+    &self,
+    path: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.chown_sync(path, uid, gid)
+  }
+  async fn chown_async(
+    &self,
+    path: PathBuf,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs.chown_async(path, uid, gid).await
+  }
+
+  fn lchown_sync(
+    &self,
+    path: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.lchown_sync(path, uid, gid)
+  }
+  async fn lchown_async(
+    &self,
+    path: PathBuf,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs.lchown_async(path, uid, gid).await
+  }
+
+  fn remove_sync(&self, path: &Path, recursive: bool) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.remove_sync(path, recursive)
+  }
+  async fn remove_async(&self, path: PathBuf, recursive: bool) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs.remove_async(path, recursive).await
+  }
+
+  fn copy_file_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    self.error_if_virtual(newpath)?;
+    RealFs.copy_file_sync(&self.read_real_path(oldpath)?, newpath)
+  }
+  async fn copy_file_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&newpath)?;
+    let oldpath = self.read_real_path(&oldpath)?;
+    RealFs.copy_file_async(oldpath, newpath).await
+  }
+
+  fn cp_sync(&self, from: &Path, to: &Path) -> FsResult<()> {
+    self.error_if_virtual(to)?;
+    RealFs.cp_sync(&self.read_real_path(from)?, to)
+  }
+  async fn cp_async(&self, from: PathBuf, to: PathBuf) -> FsResult<()> {
+    self.error_if_virtual(&to)?;
+    let from = self.read_real_path(&from)?;
+    RealFs.cp_async(from, to).await
+  }
+
+  fn stat_sync(&self, path: &Path) -> FsResult<FsStat> {
+    match self.resolve(path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.stat_sync(&path)
+      }
+      VirtualPath::Dir(_) => self.dir_stat(),
+      VirtualPath::NotFound => Err(not_found_error(path)),
+    }
+  }
+  async fn stat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    match self.resolve(&path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.stat_async(path).await
+      }
+      VirtualPath::Dir(_) => self.dir_stat(),
+      VirtualPath::NotFound => Err(not_found_error(&path)),
+    }
+  }
+
+  fn lstat_sync(&self, path: &Path) -> FsResult<FsStat> {
+    match self.resolve(path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.lstat_sync(&path)
+      }
+      VirtualPath::Dir(_) => self.dir_stat(),
+      VirtualPath::NotFound => Err(not_found_error(path)),
+    }
+  }
+  async fn lstat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    match self.resolve(&path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.lstat_async(path).await
+      }
+      VirtualPath::Dir(_) => self.dir_stat(),
+      VirtualPath::NotFound => Err(not_found_error(&path)),
+    }
+  }
+
+  fn realpath_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    match self.resolve(path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.realpath_sync(&path)
+      }
+      VirtualPath::Dir(_) => Ok(path.to_path_buf()),
+      VirtualPath::NotFound => Err(not_found_error(path)),
+    }
+  }
+  async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    match self.resolve(&path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.realpath_async(path).await
+      }
+      VirtualPath::Dir(_) => Ok(path),
+      VirtualPath::NotFound => Err(not_found_error(&path)),
+    }
+  }
+
+  fn read_dir_sync(&self, path: &Path) -> FsResult<Vec<FsDirEntry>> {
+    match self.resolve(path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.read_dir_sync(&path)
+      }
+      VirtualPath::Dir(names) => Ok(Self::dir_entries(names)),
+      VirtualPath::NotFound => Err(not_found_error(path)),
+    }
+  }
+  async fn read_dir_async(&self, path: PathBuf) -> FsResult<Vec<FsDirEntry>> {
+    match self.resolve(&path) {
+      VirtualPath::Real(path) | VirtualPath::Package(path) => {
+        RealFs.read_dir_async(path).await
+      }
+      VirtualPath::Dir(names) => Ok(Self::dir_entries(names)),
+      VirtualPath::NotFound => Err(not_found_error(&path)),
+    }
+  }
+
+  fn rename_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    self.error_if_virtual(oldpath)?;
+    self.error_if_virtual(newpath)?;
+    RealFs.rename_sync(oldpath, newpath)
+  }
+  async fn rename_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&oldpath)?;
+    self.error_if_virtual(&newpath)?;
+    RealFs.rename_async(oldpath, newpath).await
+  }
+
+  fn link_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    self.error_if_virtual(newpath)?;
+    RealFs.link_sync(&self.read_real_path(oldpath)?, newpath)
+  }
+  async fn link_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&newpath)?;
+    let oldpath = self.read_real_path(&oldpath)?;
+    RealFs.link_async(oldpath, newpath).await
+  }
+
+  fn symlink_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(newpath)?;
+    RealFs.symlink_sync(oldpath, newpath, file_type)
+  }
+  async fn symlink_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&newpath)?;
+    RealFs.symlink_async(oldpath, newpath, file_type).await
+  }
+
+  fn read_link_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    RealFs.read_link_sync(&self.read_real_path(path)?)
+  }
+  async fn read_link_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    let path = self.read_real_path(&path)?;
+    RealFs.read_link_async(path).await
+  }
+
+  fn truncate_sync(&self, path: &Path, len: u64) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.truncate_sync(path, len)
+  }
+  async fn truncate_async(&self, path: PathBuf, len: u64) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs.truncate_async(path, len).await
+  }
+
+  fn utime_sync(
+    &self,
+    path: &Path,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.utime_sync(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+  }
+  async fn utime_async(
+    &self,
+    path: PathBuf,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs
+      .utime_async(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+      .await
+  }
+
+  fn lutime_sync(
+    &self,
+    path: &Path,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_virtual(path)?;
+    RealFs.lutime_sync(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+  }
+  async fn lutime_async(
+    &self,
+    path: PathBuf,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_virtual(&path)?;
+    RealFs
+      .lutime_async(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+      .await
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::collections::HashMap;
+
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[derive(Debug)]
+  struct TestPackages(HashMap<String, PathBuf>);
+
+  impl NodeModulesPackages for TestPackages {
+    fn package_names(&self) -> Vec<String> {
+      let mut names = self.0.keys().cloned().collect::<Vec<_>>();
+      names.sort();
+      names
+    }
+
+    fn package_folder(&self, name: &str) -> Option<PathBuf> {
+      self.0.get(name).cloned()
+    }
+  }
+
+  fn create_fs(temp_dir: &TempDir) -> (VirtualNodeModulesFs, PathBuf) {
+    let cache = temp_dir.path().join("cache");
+    let chalk = cache.join("chalk/5.3.0");
+    chalk.create_dir_all();
+    chalk.join("package.json").write(r#"{ "name": "chalk" }"#);
+    let path = cache.join("@std/path/1.0.8");
+    path.create_dir_all();
+    path.join("mod.js").write("export {};");
+    let fs = VirtualNodeModulesFs::default();
+    let node_modules_dir = temp_dir.path().join("project/node_modules");
+    fs.set_packages(
+      node_modules_dir.to_path_buf(),
+      Arc::new(TestPackages(HashMap::from([
+        ("chalk".to_string(), chalk.to_path_buf()),
+        ("@std/path".to_string(), path.to_path_buf()),
+      ]))),
+    );
+    (fs, node_modules_dir.to_path_buf())
+  }
+
+  #[test]
+  fn reads_packages_from_the_cache() {
+    let temp_dir = TempDir::new();
+    let (fs, node_modules_dir) = create_fs(&temp_dir);
+    let text = fs
+      .read_text_file_lossy_sync(
+        &node_modules_dir.join("chalk/package.json"),
+        None,
+      )
+      .unwrap();
+    assert_eq!(text, r#"{ "name": "chalk" }"#);
+    assert!(fs.is_file_sync(&node_modules_dir.join("@std/path/mod.js")));
+    assert!(!fs.exists_sync(&node_modules_dir.join("lodash")));
+    // the directory isn't created
+    assert!(!node_modules_dir.exists());
+  }
+
+  #[test]
+  fn lists_top_level_packages() {
+    let temp_dir = TempDir::new();
+    let (fs, node_modules_dir) = create_fs(&temp_dir);
+    assert!(fs.is_dir_sync(&node_modules_dir));
+    let names = |path: &Path| {
+      fs.read_dir_sync(path)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&node_modules_dir), vec!["@std", "chalk"]);
+    assert_eq!(names(&node_modules_dir.join("@std")), vec!["path"]);
+    assert!(!fs.exists_sync(&node_modules_dir.join("@other")));
+  }
+
+  #[test]
+  fn is_read_only() {
+    let temp_dir = TempDir::new();
+    let (fs, node_modules_dir) = create_fs(&temp_dir);
+    assert!(fs
+      .write_file_sync(
+        &node_modules_dir.join("chalk/package.json"),
+        OpenOptions::write(true, false, false, None),
+        None,
+        b"{}",
+      )
+      .is_err());
+    assert!(fs
+      .mkdir_sync(&node_modules_dir.join("lodash"), true, None)
+      .is_err());
+    assert!(fs
+      .remove_sync(&node_modules_dir.join("chalk"), true)
+      .is_err());
+    // other paths are writable
+    let file = temp_dir.path().join("project/a.txt");
+    fs.mkdir_sync(file.parent().as_path(), true, None).unwrap();
+    fs.write_file_sync(
+      file.as_path(),
+      OpenOptions::write(true, false, false, None),
+      None,
+      b"a",
+    )
+    .unwrap();
+    assert_eq!(file.read_to_string(), "a");
+  }
+
+  #[test]
+  fn real_fs_until_packages_are_set() {
+    let temp_dir = TempDir::new();
+    let fs = VirtualNodeModulesFs::default();
+    let dir = temp_dir.path().join("node_modules");
+    fs.mkdir_sync(dir.as_path(), true, None).unwrap();
+    assert!(dir.exists());
+  }
+}
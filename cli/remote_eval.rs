@@ -0,0 +1,411 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! A WebSocket endpoint to evaluate expressions in a running main worker
+//! and receive its console output, eg. to debug long-running embedded
+//! workers.
+//!
+//! Clients authenticate with a token in an `Authorization: Bearer` header
+//! and exchange JSON text frames:
+//!
+//! ```text
+//! -> { "id": 1, "expression": "await Deno.readTextFile('./state.json')" }
+//! <- { "id": 1, "result": "'{\"ready\":true}'" }
+//! <- { "id": 2, "error": "Uncaught ReferenceError: foo is not defined" }
+//! <- { "console": { "level": "log", "text": "tick 42" } }
+//! ```
+//!
+//! Connections are not encrypted, so the server only binds to loopback
+//! addresses. Reach it from other hosts through an SSH tunnel or a proxy
+//! terminating TLS.
+
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::pin::pin;
+use std::sync::Arc;
+use std::thread;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::future;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_runtime::tokio_util::create_basic_runtime;
+use fastwebsockets::Frame;
+use fastwebsockets::OpCode;
+use fastwebsockets::WebSocket;
+use hyper::body::Bytes;
+use hyper_util::rt::TokioIo;
+use serde::Deserialize;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+use crate::cdp;
+use crate::inspector::InspectorController;
+use crate::inspector::InspectorSession;
+
+type Response = http::Response<http_body_util::Full<Bytes>>;
+
+/// Configuration of a [`RemoteEvalServer`].
+#[derive(Debug, Clone)]
+pub struct RemoteEvalOptions {
+  /// A loopback address. Use port `0` to pick a free port, see
+  /// [`RemoteEvalServer::local_addr`].
+  pub addr: SocketAddr,
+  /// The secret clients have to present. Anyone with it can run code with
+  /// the permissions of the worker.
+  pub token: String,
+}
+
+impl RemoteEvalOptions {
+  /// Serves on a free port of `127.0.0.1`.
+  pub fn new(token: impl Into<String>) -> Self {
+    Self {
+      addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+      token: token.into(),
+    }
+  }
+}
+
+/// Serves the remote evaluation endpoint for the worker of an
+/// [`InspectorController`] on a background thread, until it's dropped.
+///
+/// ```no_run
+/// # use deno::DenoRuntimeBuilder;
+/// # use deno::InspectMode;
+/// # use deno::InspectorController;
+/// # use deno::RemoteEvalOptions;
+/// # use deno::RemoteEvalServer;
+/// # async fn serve() -> Result<(), deno_core::error::AnyError> {
+/// let controller = InspectorController::new();
+/// let mut worker = DenoRuntimeBuilder::new("./server.ts")
+///   .inspector(controller.clone(), InspectMode::Run)
+///   .build()
+///   .await?;
+/// // clients connect to `ws://{server.local_addr()}`
+/// let _server = RemoteEvalServer::start(
+///   controller,
+///   RemoteEvalOptions::new(std::env::var("REMOTE_EVAL_TOKEN")?),
+/// )?;
+/// worker.run().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RemoteEvalServer {
+  local_addr: SocketAddr,
+  shutdown_tx: Option<broadcast::Sender<()>>,
+  thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl RemoteEvalServer {
+  pub fn start(
+    controller: InspectorController,
+    options: RemoteEvalOptions,
+  ) -> Result<Self, AnyError> {
+    if options.token.is_empty() {
+      deno_core::anyhow::bail!("The remote eval token must not be empty.");
+    }
+    if !options.addr.ip().is_loopback() {
+      deno_core::anyhow::bail!(
+        "The remote eval server only binds to loopback addresses, as its connections are not encrypted."
+      );
+    }
+    let listener =
+      std::net::TcpListener::bind(options.addr).with_context(|| {
+        format!("Failed to start remote eval server at \"{}\"", options.addr)
+      })?;
+    listener.set_nonblocking(true)?;
+    let local_addr = listener.local_addr()?;
+    let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    let token: Arc<str> = options.token.into();
+
+    let thread_handle = thread::spawn(move || {
+      let rt = create_basic_runtime();
+      let local = tokio::task::LocalSet::new();
+      local.block_on(&rt, server(listener, controller, token, shutdown_rx));
+    });
+
+    Ok(Self {
+      local_addr,
+      shutdown_tx: Some(shutdown_tx),
+      thread_handle: Some(thread_handle),
+    })
+  }
+
+  pub fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+}
+
+impl Drop for RemoteEvalServer {
+  fn drop(&mut self) {
+    if let Some(shutdown_tx) = self.shutdown_tx.take() {
+      let _ = shutdown_tx.send(());
+    }
+    if let Some(thread_handle) = self.thread_handle.take() {
+      let _ = thread_handle.join();
+    }
+  }
+}
+
+async fn server(
+  listener: std::net::TcpListener,
+  controller: InspectorController,
+  token: Arc<str>,
+  mut shutdown_rx: broadcast::Receiver<()>,
+) {
+  let listener = match TcpListener::from_std(listener) {
+    Ok(listener) => listener,
+    Err(err) => {
+      log::error!("Cannot start remote eval server: {:?}", err);
+      return;
+    }
+  };
+
+  loop {
+    let stream = tokio::select! {
+      result = listener.accept() => match result {
+        Ok((stream, _)) => stream,
+        Err(err) => {
+          log::error!("Failed to accept remote eval connection: {:?}", err);
+          continue;
+        }
+      },
+      _ = shutdown_rx.recv() => break,
+    };
+
+    let controller = controller.clone();
+    let token = token.clone();
+    let service = hyper::service::service_fn(
+      move |req: http::Request<hyper::body::Incoming>| {
+        future::ready(handle_request(req, &controller, &token))
+      },
+    );
+    let mut shutdown_rx = shutdown_rx.resubscribe();
+    deno_core::unsync::spawn(async move {
+      let server = hyper::server::conn::http1::Builder::new();
+      let mut conn = pin!(server
+        .serve_connection(TokioIo::new(stream), service)
+        .with_upgrades());
+      tokio::select! {
+        result = conn.as_mut() => {
+          if let Err(err) = result {
+            log::debug!("Failed to serve remote eval connection: {:?}", err);
+          }
+        }
+        _ = shutdown_rx.recv() => {
+          conn.as_mut().graceful_shutdown();
+          let _ = conn.await;
+        }
+      }
+    });
+  }
+}
+
+fn handle_request(
+  mut req: http::Request<hyper::body::Incoming>,
+  controller: &InspectorController,
+  token: &str,
+) -> http::Result<Response> {
+  if !is_authorized(&req, token) {
+    return http::Response::builder()
+      .status(http::StatusCode::UNAUTHORIZED)
+      .body(Bytes::from("Invalid remote eval token").into());
+  }
+  let Ok((resp, fut)) = fastwebsockets::upgrade::upgrade(&mut req) else {
+    return http::Response::builder()
+      .status(http::StatusCode::BAD_REQUEST)
+      .body(Bytes::from("Not a valid Websocket Request").into());
+  };
+
+  let controller = controller.clone();
+  deno_core::unsync::spawn(async move {
+    let websocket = match fut.await {
+      Ok(websocket) => websocket,
+      Err(err) => {
+        log::error!("Remote eval failed to upgrade to WS connection: {err:?}");
+        return;
+      }
+    };
+    if let Err(err) = serve_client(websocket, &controller).await {
+      log::debug!("Remote eval session ended: {err}");
+    }
+  });
+
+  let (parts, _) = resp.into_parts();
+  Ok(http::Response::from_parts(parts, Default::default()))
+}
+
+// The token isn't accepted in the query string, as URLs end up in logs.
+fn is_authorized<B>(req: &http::Request<B>, token: &str) -> bool {
+  req
+    .headers()
+    .get(http::header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .is_some_and(|candidate| {
+      constant_time_eq(candidate.as_bytes(), token.as_bytes())
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  a.len() == b.len()
+    && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[derive(Deserialize)]
+struct EvalRequest {
+  id: u64,
+  expression: String,
+}
+
+async fn serve_client(
+  mut websocket: WebSocket<TokioIo<hyper::upgrade::Upgraded>>,
+  controller: &InspectorController,
+) -> Result<(), AnyError> {
+  let mut session = controller.connect().await?;
+  session.post_message("Runtime.enable", None).await?;
+
+  loop {
+    let messages = tokio::select! {
+      frame = websocket.read_frame() => {
+        let frame = frame?;
+        match frame.opcode {
+          OpCode::Text => {
+            match serde_json::from_slice::<EvalRequest>(&frame.payload) {
+              Ok(request) => {
+                let result = evaluate(&mut session, request).await?;
+                // console calls made while evaluating were queued by the
+                // session, they are sent before the result
+                let mut messages = session
+                  .take_notifications("Runtime.consoleAPICalled")
+                  .into_iter()
+                  .filter_map(console_message)
+                  .collect::<Vec<_>>();
+                messages.push(result);
+                messages
+              }
+              Err(err) => {
+                vec![json!({ "error": format!("Invalid request: {err}") })]
+              }
+            }
+          }
+          OpCode::Close => return Ok(()),
+          _ => continue,
+        }
+      }
+      notification = session.next_notification() => {
+        let Some(notification) = notification else {
+          return Ok(());
+        };
+        match console_message(notification) {
+          Some(message) => vec![message],
+          None => continue,
+        }
+      }
+    };
+    for message in messages {
+      websocket
+        .write_frame(Frame::text(message.to_string().into_bytes().into()))
+        .await?;
+    }
+  }
+}
+
+async fn evaluate(
+  session: &mut InspectorSession,
+  request: EvalRequest,
+) -> Result<serde_json::Value, AnyError> {
+  let args = cdp::EvaluateArgs {
+    expression: request.expression,
+    object_group: Some("remote-eval".to_string()),
+    include_command_line_api: None,
+    silent: None,
+    context_id: None,
+    return_by_value: None,
+    generate_preview: None,
+    user_gesture: None,
+    await_promise: Some(true),
+    throw_on_side_effect: None,
+    timeout: None,
+    disable_breaks: None,
+    repl_mode: Some(true),
+    allow_unsafe_eval_blocked_by_csp: None,
+    unique_context_id: None,
+  };
+  let result = session
+    .post_message("Runtime.evaluate", Some(serde_json::to_value(args)?))
+    .await?;
+  let response: cdp::EvaluateResponse = serde_json::from_value(result)?;
+  Ok(match response.exception_details {
+    Some(details) => {
+      let (message, description) = details.get_message_and_description();
+      json!({ "id": request.id, "error": format!("{message} {description}") })
+    }
+    None => {
+      let result = display_remote_object(&response.result, true);
+      json!({ "id": request.id, "result": result })
+    }
+  })
+}
+
+fn console_message(
+  notification: crate::inspector::InspectorNotification,
+) -> Option<serde_json::Value> {
+  if notification.method != "Runtime.consoleAPICalled" {
+    return None;
+  }
+  let call: cdp::ConsoleApiCalled =
+    serde_json::from_value(notification.params).ok()?;
+  let text = call
+    .args
+    .iter()
+    .map(|arg| display_remote_object(arg, false))
+    .collect::<Vec<_>>()
+    .join(" ");
+  Some(json!({ "console": { "level": call.kind, "text": text } }))
+}
+
+/// Formats a value like the REPL would. Strings are quoted unless they are
+/// console arguments.
+fn display_remote_object(object: &cdp::RemoteObject, quote: bool) -> String {
+  if let Some(value) = &object.unserializable_value {
+    return value.to_string();
+  }
+  match &object.value {
+    Some(serde_json::Value::String(text)) if !quote => text.clone(),
+    Some(value) if object.kind != "object" || value.is_null() => {
+      value.to_string()
+    }
+    _ => object
+      .description
+      .clone()
+      .unwrap_or_else(|| object.kind.clone()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn authorizes_header_token_only() {
+    let request = |uri: &str, header: Option<&str>| {
+      let mut builder = http::Request::builder().uri(uri);
+      if let Some(header) = header {
+        builder = builder.header(http::header::AUTHORIZATION, header);
+      }
+      builder.body(()).unwrap()
+    };
+    assert!(is_authorized(
+      &request("/", Some("Bearer s3cret")),
+      "s3cret"
+    ));
+    assert!(!is_authorized(
+      &request("/", Some("Bearer s3cre")),
+      "s3cret"
+    ));
+    assert!(!is_authorized(&request("/?token=s3cret", None), "s3cret"));
+    assert!(!is_authorized(&request("/", Some("s3cret")), "s3cret"));
+    assert!(!is_authorized(&request("/", None), "s3cret"));
+  }
+}
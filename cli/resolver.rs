@@ -27,12 +27,16 @@ use deno_semver::package::PackageReq;
 use node_resolver::NodeResolutionKind;
 use node_resolver::ResolutionMode;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use thiserror::Error;
 
 use crate::args::DENO_DISABLE_PEDANTIC_NODE_WARNINGS;
+use crate::file_fetcher::File;
+use crate::file_fetcher::FileFetcher;
+use crate::import_policy::ImportPolicy;
 use crate::node::CliNodeCodeTranslator;
 use crate::npm::CliNpmResolver;
 use crate::npm::InnerCliNpmResolverRef;
@@ -210,10 +214,39 @@ impl NpmModuleLoader {
   }
 }
 
+/// Module provided by a [`HostModuleResolver`].
+pub enum HostModuleResolution {
+  /// Resolve to this URL, which is then loaded like any other module.
+  Redirect(ModuleSpecifier),
+  /// Serve `code` as the module. The requested specifier must be a valid
+  /// URL, eg. `app:config`, and is used as the module's specifier.
+  Source { code: String, media_type: MediaType },
+}
+
+/// Hook for embedders to resolve specifiers before the default resolver,
+/// eg. to provide `app:config` from the host application.
+pub trait HostModuleResolver: std::fmt::Debug + Send + Sync {
+  /// Returns `None` to fall back to the default resolution.
+  fn resolve(
+    &self,
+    raw_specifier: &str,
+    referrer: &ModuleSpecifier,
+  ) -> Option<HostModuleResolution>;
+}
+
+#[derive(Debug)]
+pub struct HostModuleResolverOptions {
+  pub resolver: Arc<dyn HostModuleResolver>,
+  /// Serves the sources returned by the resolver from its memory files.
+  pub file_fetcher: Arc<FileFetcher>,
+}
+
 pub struct CliResolverOptions {
   pub deno_resolver: Arc<CliDenoResolver>,
   pub npm_resolver: Option<Arc<dyn CliNpmResolver>>,
   pub bare_node_builtins_enabled: bool,
+  pub host_module_resolver: Option<HostModuleResolverOptions>,
+  pub import_policy: Option<Arc<ImportPolicy>>,
 }
 
 /// A resolver that takes care of resolution, taking into account loaded
@@ -225,6 +258,8 @@ pub struct CliResolver {
   found_package_json_dep_flag: AtomicFlag,
   bare_node_builtins_enabled: bool,
   warned_pkgs: DashSet<PackageReq>,
+  host_module_resolver: Option<HostModuleResolverOptions>,
+  import_policy: Option<Arc<ImportPolicy>>,
 }
 
 impl CliResolver {
@@ -235,6 +270,8 @@ impl CliResolver {
       found_package_json_dep_flag: Default::default(),
       bare_node_builtins_enabled: options.bare_node_builtins_enabled,
       warned_pkgs: Default::default(),
+      host_module_resolver: options.host_module_resolver,
+      import_policy: options.import_policy,
     }
   }
 
@@ -256,6 +293,12 @@ impl CliResolver {
     resolution_mode: ResolutionMode,
     resolution_kind: NodeResolutionKind,
   ) -> Result<ModuleSpecifier, ResolveError> {
+    if let Some(host) = &self.host_module_resolver {
+      if let Some(resolution) = host.resolver.resolve(raw_specifier, referrer) {
+        return resolve_host_module(host, raw_specifier, resolution);
+      }
+    }
+
     let resolution = self
       .deno_resolver
       .resolve(raw_specifier, referrer, resolution_mode, resolution_kind)
@@ -271,6 +314,12 @@ impl CliResolver {
         err => ResolveError::Other(err.into()),
       })?;
 
+    if let Some(import_policy) = &self.import_policy {
+      import_policy
+        .check(&resolution.url)
+        .map_err(|err| ResolveError::Other(err.into()))?;
+    }
+
     if resolution.found_package_json_dep {
       // mark that we need to do an "npm install" later
       self.found_package_json_dep_flag.raise();
@@ -299,6 +348,42 @@ impl CliResolver {
   }
 }
 
+fn resolve_host_module(
+  host: &HostModuleResolverOptions,
+  raw_specifier: &str,
+  resolution: HostModuleResolution,
+) -> Result<ModuleSpecifier, ResolveError> {
+  match resolution {
+    HostModuleResolution::Redirect(specifier) => Ok(specifier),
+    HostModuleResolution::Source { code, media_type } => {
+      let specifier = ModuleSpecifier::parse(raw_specifier).map_err(|_| {
+        ResolveError::Other(anyhow!(
+          "Module \"{}\" provided by the host must be a valid URL.",
+          raw_specifier
+        ))
+      })?;
+      let content_type = match media_type {
+        MediaType::TypeScript | MediaType::Mts | MediaType::Cts => {
+          "application/typescript"
+        }
+        MediaType::Tsx => "text/tsx",
+        MediaType::Jsx => "text/jsx",
+        MediaType::Json => "application/json",
+        _ => "application/javascript",
+      };
+      host.file_fetcher.insert_memory_files(File {
+        specifier: specifier.clone(),
+        maybe_headers: Some(HashMap::from([(
+          "content-type".to_string(),
+          content_type.to_string(),
+        )])),
+        source: code.into_bytes().into(),
+      });
+      Ok(specifier)
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct WorkerCliNpmGraphResolver<'a> {
   npm_resolver: Option<&'a Arc<dyn CliNpmResolver>>,
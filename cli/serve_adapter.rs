@@ -0,0 +1,538 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Lets an embedding host serve HTTP requests received by its own server,
+//! eg. with hyper or axum, with the `fetch` handler exported by the main
+//! module of a worker, like `deno serve` does with its own listener.
+//!
+//! [`ServeServer`] serves the connections of sockets bound by the host
+//! with a set of workers.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::pin::pin;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use bytes::Bytes;
+use deno_core::anyhow::bail;
+use deno_core::error::generic_error;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op2;
+use deno_core::AsyncRefCell;
+use deno_core::ByteString;
+use deno_core::Extension;
+use deno_core::JsBuffer;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_core::ToJsBuffer;
+use http::HeaderName;
+use http::HeaderValue;
+use http::StatusCode;
+use http_body::Body;
+use http_body::Frame;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::BodyExt;
+use hyper_util::rt::TokioExecutor;
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+deno_core::extension!(deno_serve_adapter,
+  ops = [
+    op_serve_adapter_next,
+    op_serve_adapter_read,
+    op_serve_adapter_respond,
+    op_serve_adapter_write,
+  ],
+  options = {
+    request_rx: mpsc::UnboundedReceiver<PendingRequest>,
+  },
+  state = |state, options| {
+    state.put(ServeAdapterState {
+      request_rx: Rc::new(tokio::sync::Mutex::new(options.request_rx)),
+    });
+  },
+);
+
+/// Chunks of a response body buffered before the worker waits for the host
+/// to read them.
+const RESPONSE_BODY_CAPACITY: usize = 16;
+
+type RequestBody = UnsyncBoxBody<Bytes, AnyError>;
+
+pub(crate) struct PendingRequest {
+  parts: http::request::Parts,
+  body: RequestBody,
+  response_tx: oneshot::Sender<http::Response<ServeBody>>,
+}
+
+struct ServeAdapterState {
+  request_rx: Rc<tokio::sync::Mutex<mpsc::UnboundedReceiver<PendingRequest>>>,
+}
+
+/// The Rust side of a main worker serving the requests of the host. Cloning
+/// it is cheap and clones can be moved to other threads, eg. into the
+/// handlers of a hyper or axum server.
+///
+/// Once the main module was evaluated, requests are passed to the `fetch`
+/// method of its default export, like with `deno serve`. The worker keeps
+/// running until the adapter and all its clones are dropped.
+#[derive(Clone)]
+pub struct ServeAdapter {
+  request_tx: mpsc::UnboundedSender<PendingRequest>,
+}
+
+impl ServeAdapter {
+  /// Passes `request` to the `fetch` handler of the worker. Resolves as soon
+  /// as the handler responded, the body of the response is streamed while
+  /// the handler writes it.
+  pub async fn handle<B>(
+    &self,
+    request: http::Request<B>,
+  ) -> Result<http::Response<ServeBody>, AnyError>
+  where
+    B: Body<Data = Bytes> + Send + 'static,
+    B::Error: std::error::Error + Send + Sync + 'static,
+  {
+    let (parts, body) = request.into_parts();
+    let (response_tx, response_rx) = oneshot::channel();
+    self
+      .request_tx
+      .send(PendingRequest {
+        parts,
+        body: body.map_err(AnyError::from).boxed_unsync(),
+        response_tx,
+      })
+      .map_err(|_| generic_error("The worker stopped serving requests."))?;
+    response_rx.await.map_err(|_| {
+      generic_error("The worker stopped before responding to the request.")
+    })
+  }
+}
+
+/// The streamed body of a response of the worker.
+pub struct ServeBody(mpsc::Receiver<Bytes>);
+
+impl ServeBody {
+  fn empty() -> Self {
+    let (_, body_rx) = mpsc::channel(1);
+    Self(body_rx)
+  }
+}
+
+impl Body for ServeBody {
+  type Data = Bytes;
+  type Error = Infallible;
+
+  fn poll_frame(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+    self
+      .0
+      .poll_recv(cx)
+      .map(|chunk| chunk.map(|chunk| Ok(Frame::data(chunk))))
+  }
+}
+
+/// Creates the adapter along with the extension that has to be registered on
+/// the worker.
+pub fn create_serve_adapter() -> (ServeAdapter, Extension) {
+  let (request_tx, request_rx) = mpsc::unbounded_channel();
+  let extension = deno_serve_adapter::init_ops(request_rx);
+  (ServeAdapter { request_tx }, extension)
+}
+
+/// A socket bound by the host for a [`ServeServer`] to accept connections
+/// on. Sockets inherited as file descriptors, eg. from systemd, can be
+/// wrapped with `TcpListener::from_raw_fd`.
+pub enum ServeListener {
+  Tcp(std::net::TcpListener),
+  #[cfg(unix)]
+  Unix(std::os::unix::net::UnixListener),
+}
+
+impl From<std::net::TcpListener> for ServeListener {
+  fn from(listener: std::net::TcpListener) -> Self {
+    ServeListener::Tcp(listener)
+  }
+}
+
+#[cfg(unix)]
+impl From<std::os::unix::net::UnixListener> for ServeListener {
+  fn from(listener: std::os::unix::net::UnixListener) -> Self {
+    ServeListener::Unix(listener)
+  }
+}
+
+enum Listener {
+  Tcp(tokio::net::TcpListener),
+  #[cfg(unix)]
+  Unix(tokio::net::UnixListener),
+}
+
+enum Stream {
+  Tcp(tokio::net::TcpStream),
+  #[cfg(unix)]
+  Unix(tokio::net::UnixStream),
+}
+
+impl Listener {
+  fn from_std(listener: ServeListener) -> std::io::Result<Self> {
+    match listener {
+      ServeListener::Tcp(listener) => {
+        listener.set_nonblocking(true)?;
+        Ok(Listener::Tcp(tokio::net::TcpListener::from_std(listener)?))
+      }
+      #[cfg(unix)]
+      ServeListener::Unix(listener) => {
+        listener.set_nonblocking(true)?;
+        Ok(Listener::Unix(tokio::net::UnixListener::from_std(
+          listener,
+        )?))
+      }
+    }
+  }
+
+  async fn accept(&self) -> std::io::Result<Stream> {
+    match self {
+      Listener::Tcp(listener) => {
+        let (stream, _) = listener.accept().await?;
+        stream.set_nodelay(true)?;
+        Ok(Stream::Tcp(stream))
+      }
+      #[cfg(unix)]
+      Listener::Unix(listener) => {
+        let (stream, _) = listener.accept().await?;
+        Ok(Stream::Unix(stream))
+      }
+    }
+  }
+}
+
+/// Passes the requests to the adapters in turn.
+#[derive(Clone)]
+struct Dispatcher {
+  adapters: Arc<[ServeAdapter]>,
+  next: Arc<AtomicUsize>,
+}
+
+impl Dispatcher {
+  fn next_adapter(&self) -> ServeAdapter {
+    let index = self.next.fetch_add(1, Ordering::Relaxed);
+    self.adapters[index % self.adapters.len()].clone()
+  }
+}
+
+/// Accepts HTTP/1.1 and HTTP/2 connections on sockets bound by the host and
+/// passes their requests to the [`ServeAdapter`]s of several workers in
+/// turn, until it's drained or dropped.
+///
+/// To let the kernel balance the connections, like `deno serve` does for
+/// its workers, bind several sockets to the same address with
+/// `SO_REUSEPORT` and pass all of them.
+pub struct ServeServer {
+  shutdown_tx: watch::Sender<bool>,
+  accept_tasks: JoinSet<()>,
+}
+
+impl ServeServer {
+  /// Starts accepting connections on the tasks of the current Tokio
+  /// runtime.
+  pub fn start(
+    listeners: Vec<ServeListener>,
+    adapters: Vec<ServeAdapter>,
+  ) -> Result<Self, AnyError> {
+    if listeners.is_empty() {
+      bail!("At least one listener is required to serve requests.");
+    }
+    if adapters.is_empty() {
+      bail!("At least one serve adapter is required to serve requests.");
+    }
+    let dispatcher = Dispatcher {
+      adapters: adapters.into(),
+      next: Default::default(),
+    };
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut accept_tasks = JoinSet::new();
+    for listener in listeners {
+      accept_tasks.spawn(accept_connections(
+        Listener::from_std(listener)?,
+        dispatcher.clone(),
+        shutdown_rx.clone(),
+      ));
+    }
+    Ok(Self {
+      shutdown_tx,
+      accept_tasks,
+    })
+  }
+
+  /// Stops accepting connections and closes the open ones once their
+  /// requests in flight were responded to. Connections still open after
+  /// `grace` are dropped.
+  pub async fn drain(mut self, grace: Duration) {
+    let _ = self.shutdown_tx.send(true);
+    let _ = tokio::time::timeout(grace, async {
+      while self.accept_tasks.join_next().await.is_some() {}
+    })
+    .await;
+  }
+}
+
+async fn accept_connections(
+  listener: Listener,
+  dispatcher: Dispatcher,
+  mut shutdown_rx: watch::Receiver<bool>,
+) {
+  let mut connections = JoinSet::new();
+  loop {
+    tokio::select! {
+      result = listener.accept() => match result {
+        Ok(Stream::Tcp(stream)) => {
+          connections.spawn(serve_connection(
+            TokioIo::new(stream),
+            dispatcher.clone(),
+            shutdown_rx.clone(),
+          ));
+        }
+        #[cfg(unix)]
+        Ok(Stream::Unix(stream)) => {
+          connections.spawn(serve_connection(
+            TokioIo::new(stream),
+            dispatcher.clone(),
+            shutdown_rx.clone(),
+          ));
+        }
+        Err(err) => log::debug!("Failed to accept connection: {:?}", err),
+      },
+      Some(_) = connections.join_next() => {}
+      _ = shutdown_rx.changed() => break,
+    }
+  }
+  while connections.join_next().await.is_some() {}
+}
+
+async fn serve_connection<I>(
+  io: I,
+  dispatcher: Dispatcher,
+  mut shutdown_rx: watch::Receiver<bool>,
+) where
+  I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+{
+  let service = hyper::service::service_fn(
+    move |request: http::Request<hyper::body::Incoming>| {
+      let adapter = dispatcher.next_adapter();
+      async move {
+        match adapter.handle(request).await {
+          Ok(response) => Ok::<_, Infallible>(response),
+          Err(err) => {
+            log::debug!("Failed to serve request: {:?}", err);
+            let mut response = http::Response::new(ServeBody::empty());
+            *response.status_mut() = StatusCode::BAD_GATEWAY;
+            Ok(response)
+          }
+        }
+      }
+    },
+  );
+  let builder =
+    hyper_util::server::conn::auto::Builder::new(TokioExecutor::new());
+  let mut conn = pin!(builder.serve_connection_with_upgrades(io, service));
+  tokio::select! {
+    result = conn.as_mut() => {
+      if let Err(err) = result {
+        log::debug!("Failed to serve connection: {:?}", err);
+      }
+    }
+    _ = shutdown_rx.changed() => {
+      conn.as_mut().graceful_shutdown();
+      let _ = conn.await;
+    }
+  }
+}
+
+/// A request being handled by the worker.
+struct ServeExchange {
+  request_body: AsyncRefCell<Option<RequestBody>>,
+  response_tx: RefCell<Option<oneshot::Sender<http::Response<ServeBody>>>>,
+  response_body_tx: RefCell<Option<mpsc::Sender<Bytes>>>,
+}
+
+impl Resource for ServeExchange {
+  fn name(&self) -> Cow<str> {
+    "serveAdapterExchange".into()
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NextRequest {
+  rid: ResourceId,
+  method: String,
+  url: String,
+  headers: Vec<(ByteString, ByteString)>,
+  has_body: bool,
+}
+
+/// The URL of the request, as the request target of HTTP/1.1 requests is
+/// usually only the path.
+fn request_url(parts: &http::request::Parts) -> String {
+  if parts.uri.scheme().is_some() {
+    return parts.uri.to_string();
+  }
+  let host = parts
+    .headers
+    .get(http::header::HOST)
+    .and_then(|host| host.to_str().ok())
+    .unwrap_or("localhost");
+  let path = parts
+    .uri
+    .path_and_query()
+    .map(|path| path.as_str())
+    .unwrap_or("/");
+  format!("http://{host}{path}")
+}
+
+/// Waits for the next request of the host, `None` once every
+/// [`ServeAdapter`] was dropped.
+#[op2(async)]
+#[serde]
+async fn op_serve_adapter_next(
+  state: Rc<RefCell<OpState>>,
+) -> Option<NextRequest> {
+  let receiver = state
+    .borrow()
+    .borrow::<ServeAdapterState>()
+    .request_rx
+    .clone();
+  let PendingRequest {
+    parts,
+    body,
+    response_tx,
+  } = receiver.lock().await.recv().await?;
+  let has_body = !body.is_end_stream();
+  let rid = state.borrow_mut().resource_table.add(ServeExchange {
+    request_body: AsyncRefCell::new(Some(body)),
+    response_tx: RefCell::new(Some(response_tx)),
+    response_body_tx: RefCell::new(None),
+  });
+  Some(NextRequest {
+    rid,
+    method: parts.method.to_string(),
+    url: request_url(&parts),
+    headers: parts
+      .headers
+      .iter()
+      .map(|(name, value)| (name.as_str().into(), value.as_bytes().into()))
+      .collect(),
+    has_body,
+  })
+}
+
+/// Reads the next chunk of the request body, `None` at its end.
+#[op2(async)]
+#[serde]
+async fn op_serve_adapter_read(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<Option<ToJsBuffer>, AnyError> {
+  let exchange = state.borrow().resource_table.get::<ServeExchange>(rid)?;
+  let mut request_body = RcRef::map(&exchange, |r| &r.request_body)
+    .borrow_mut()
+    .await;
+  let Some(body) = request_body.as_mut() else {
+    return Ok(None);
+  };
+  while let Some(frame) = body.frame().await {
+    // trailers are dropped
+    if let Ok(data) = frame?.into_data() {
+      return Ok(Some(data.to_vec().into()));
+    }
+  }
+  *request_body = None;
+  Ok(None)
+}
+
+/// Sends the status and headers of the response to the host. The body is
+/// written with `op_serve_adapter_write` and ends when the resource is
+/// closed.
+#[op2]
+fn op_serve_adapter_respond(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[smi] status: u16,
+  #[serde] headers: Vec<(ByteString, ByteString)>,
+) -> Result<(), AnyError> {
+  let exchange = state.resource_table.get::<ServeExchange>(rid)?;
+  let Some(response_tx) = exchange.response_tx.borrow_mut().take() else {
+    return Err(type_error("The request was already responded to."));
+  };
+  let (body_tx, body_rx) = mpsc::channel(RESPONSE_BODY_CAPACITY);
+  let mut response = http::Response::new(ServeBody(body_rx));
+  *response.status_mut() = StatusCode::from_u16(status)
+    .map_err(|_| type_error(format!("Invalid status code {status}.")))?;
+  for (name, value) in headers {
+    let name = HeaderName::from_bytes(&name)
+      .map_err(|err| type_error(err.to_string()))?;
+    let value = HeaderValue::from_bytes(&value)
+      .map_err(|err| type_error(err.to_string()))?;
+    response.headers_mut().append(name, value);
+  }
+  *exchange.response_body_tx.borrow_mut() = Some(body_tx);
+  // the host may have stopped waiting, eg. because the client disconnected
+  let _ = response_tx.send(response);
+  Ok(())
+}
+
+/// Writes a chunk of the response body. Resolves to `false` if the host
+/// dropped the body.
+#[op2(async)]
+async fn op_serve_adapter_write(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[buffer] chunk: JsBuffer,
+) -> Result<bool, AnyError> {
+  let exchange = state.borrow().resource_table.get::<ServeExchange>(rid)?;
+  let Some(body_tx) = exchange.response_body_tx.borrow().clone() else {
+    return Err(type_error("The request wasn't responded to yet."));
+  };
+  Ok(body_tx.send(Bytes::from(chunk.to_vec())).await.is_ok())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn url_of(request: http::Request<()>) -> String {
+    request_url(&request.into_parts().0)
+  }
+
+  #[test]
+  fn test_request_url() {
+    let request = http::Request::get("/users?id=1")
+      .header("host", "example.com:8080")
+      .body(())
+      .unwrap();
+    assert_eq!(url_of(request), "http://example.com:8080/users?id=1");
+    let request = http::Request::get("/").body(()).unwrap();
+    assert_eq!(url_of(request), "http://localhost/");
+    let request = http::Request::get("https://example.com/a")
+      .body(())
+      .unwrap();
+    assert_eq!(url_of(request), "https://example.com/a");
+  }
+}
@@ -0,0 +1,234 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Maps the stack frames of errors from embedded runs back to the original
+//! sources of the modules, so hosts can render errors themselves.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use deno_ast::MediaType;
+use deno_ast::ModuleKind;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::error::JsStackFrame;
+use deno_core::parking_lot::Mutex;
+use deno_core::sourcemap::SourceMap;
+use deno_core::ModuleSpecifier;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::cdp;
+use crate::emit::Emitter;
+use crate::factory::CliFactory;
+use crate::file_fetcher::FileFetcher;
+use crate::resolver::CjsTracker;
+use crate::util::text_encoding::source_map_from_code;
+
+/// An error with its stack frames in the original sources of the modules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappedJsError {
+  /// The class of the error, eg. `TypeError`.
+  pub name: Option<String>,
+  pub message: Option<String>,
+  pub frames: Vec<MappedStackFrame>,
+  pub cause: Option<Box<MappedJsError>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappedStackFrame {
+  pub function_name: Option<String>,
+  /// Specifier of the module, `None` for native frames.
+  pub file: Option<String>,
+  /// One based line number in the original source.
+  pub line: Option<u32>,
+  /// One based column number in the original source.
+  pub column: Option<u32>,
+  /// The text of the original source at `line`, `None` when the source
+  /// isn't available, eg. for internal modules.
+  pub source_line: Option<String>,
+}
+
+/// The original source of a module and the source map of its emit.
+struct ModuleSources {
+  source: Arc<str>,
+  source_map: Option<SourceMap>,
+}
+
+/// Maps stack frames back to the original sources of the modules, reading
+/// the emitted code and its source map from the emit cache.
+///
+/// The frames of [`JsError`]s thrown by workers already point at the
+/// original sources, as workers apply the source maps themselves, so only
+/// the source lines are added to them. Positions reported by V8 directly,
+/// eg. the call frames of CPU profiles, are mapped through the source maps.
+///
+/// Use `DenoRuntimeBuilder::source_map_service` to create one with the
+/// configuration of an embedded run.
+pub struct SourceMapService {
+  cjs_tracker: Arc<CjsTracker>,
+  emitter: Arc<Emitter>,
+  file_fetcher: Arc<FileFetcher>,
+  modules: Mutex<HashMap<ModuleSpecifier, Option<Arc<ModuleSources>>>>,
+}
+
+impl SourceMapService {
+  /// The flags must resolve the same configuration as the run, as the
+  /// emits are cached per compiler options.
+  pub fn new(flags: Arc<Flags>) -> Result<Self, AnyError> {
+    Self::from_factory(&CliFactory::from_flags(flags))
+  }
+
+  pub(crate) fn from_factory(factory: &CliFactory) -> Result<Self, AnyError> {
+    Ok(Self {
+      cjs_tracker: factory.cjs_tracker()?.clone(),
+      emitter: factory.emitter()?.clone(),
+      file_fetcher: factory.file_fetcher()?.clone(),
+      modules: Default::default(),
+    })
+  }
+
+  pub fn map_js_error(&self, error: &JsError) -> MappedJsError {
+    MappedJsError {
+      name: error.name.clone(),
+      message: error.message.clone(),
+      frames: error
+        .frames
+        .iter()
+        .map(|frame| self.map_js_stack_frame(frame))
+        .collect(),
+      cause: error
+        .cause
+        .as_ref()
+        .map(|cause| Box::new(self.map_js_error(cause))),
+    }
+  }
+
+  /// Maps a call frame with a position in the emitted code of its module.
+  pub fn map_call_frame(&self, frame: &cdp::CallFrame) -> MappedStackFrame {
+    let function_name =
+      (!frame.function_name.is_empty()).then(|| frame.function_name.clone());
+    let Some(sources) = self.module_sources(&frame.url) else {
+      return MappedStackFrame {
+        function_name,
+        file: Some(frame.url.clone()),
+        line: Some(frame.line_number as u32 + 1),
+        column: Some(frame.column_number as u32 + 1),
+        source_line: None,
+      };
+    };
+    let (line_index, column_index) = sources
+      .source_map
+      .as_ref()
+      .and_then(|source_map| {
+        source_map
+          .lookup_token(frame.line_number as u32, frame.column_number as u32)
+      })
+      .map(|token| (token.get_src_line(), token.get_src_col()))
+      .unwrap_or((frame.line_number as u32, frame.column_number as u32));
+    MappedStackFrame {
+      function_name,
+      file: Some(frame.url.clone()),
+      line: Some(line_index + 1),
+      column: Some(column_index + 1),
+      source_line: source_line(&sources.source, line_index),
+    }
+  }
+
+  fn map_js_stack_frame(&self, frame: &JsStackFrame) -> MappedStackFrame {
+    let line = frame.line_number.map(|line| line as u32);
+    let source_line =
+      frame
+        .file_name
+        .as_ref()
+        .zip(line)
+        .and_then(|(file_name, line)| {
+          let sources = self.module_sources(file_name)?;
+          source_line(&sources.source, line.checked_sub(1)?)
+        });
+    MappedStackFrame {
+      function_name: frame.function_name.clone(),
+      file: frame.file_name.clone(),
+      line,
+      column: frame.column_number.map(|column| column as u32),
+      source_line,
+    }
+  }
+
+  fn module_sources(&self, file_name: &str) -> Option<Arc<ModuleSources>> {
+    let specifier = ModuleSpecifier::parse(file_name).ok()?;
+    if let Some(sources) = self.modules.lock().get(&specifier) {
+      return sources.clone();
+    }
+    let sources = self.load_module_sources(&specifier).map(Arc::new);
+    self.modules.lock().insert(specifier, sources.clone());
+    sources
+  }
+
+  fn load_module_sources(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<ModuleSources> {
+    let file = match specifier.scheme() {
+      "file" => self.file_fetcher.get_source(specifier)?,
+      "http" | "https" => {
+        self.file_fetcher.fetch_cached(specifier, 10).ok()??
+      }
+      _ => return None,
+    };
+    let file = file.into_text_decoded().ok()?;
+    let source_map = match file.media_type {
+      MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Jsx
+      | MediaType::Tsx => {
+        let module_kind = ModuleKind::from_is_cjs(
+          self
+            .cjs_tracker
+            .is_maybe_cjs(&file.specifier, file.media_type)
+            .ok()?,
+        );
+        self
+          .emitter
+          .maybe_cached_emit(
+            &file.specifier,
+            file.media_type,
+            module_kind,
+            &file.source,
+          )
+          .and_then(|code| source_map_from_code(code.as_bytes()))
+          .and_then(|source_map| SourceMap::from_slice(&source_map).ok())
+      }
+      _ => None,
+    };
+    Some(ModuleSources {
+      source: file.source,
+      source_map,
+    })
+  }
+}
+
+fn source_line(source: &str, line_index: u32) -> Option<String> {
+  // split on '\n' as .lines() skips the terminating empty line
+  let line = source.split('\n').nth(line_index as usize)?;
+  Some(line.strip_suffix('\r').unwrap_or(line).to_string())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_source_line() {
+    let source = "const a = 1;\r\nthrow new Error();\n";
+    assert_eq!(source_line(source, 0), Some("const a = 1;".to_string()));
+    assert_eq!(
+      source_line(source, 1),
+      Some("throw new Error();".to_string())
+    );
+    assert_eq!(source_line(source, 2), Some(String::new()));
+    assert_eq!(source_line(source, 3), None);
+  }
+}
@@ -8,7 +8,6 @@ use std::env;
 use std::env::current_exe;
 use std::ffi::OsString;
 use std::fs;
-use std::fs::File;
 use std::future::Future;
 use std::io::ErrorKind;
 use std::io::Read;
@@ -39,7 +38,6 @@ use deno_core::serde_json;
 use deno_core::url::Url;
 use deno_graph::source::RealFileSystem;
 use deno_graph::ModuleGraph;
-use deno_npm::resolution::SerializedNpmResolutionSnapshot;
 use deno_npm::resolution::SerializedNpmResolutionSnapshotPackage;
 use deno_npm::resolution::ValidSerializedNpmResolutionSnapshot;
 use deno_npm::NpmPackageId;
@@ -80,13 +78,17 @@ use crate::util::fs::canonicalize_path_maybe_not_exists;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
 
+use super::encryption::PayloadCipher;
 use super::file_system::DenoCompileFileSystem;
 use super::serialization::deserialize_binary_data_section;
+use super::serialization::encrypt_binary_data_section;
+use super::serialization::maybe_decrypt_binary_data_section;
 use super::serialization::serialize_binary_data_section;
 use super::serialization::DenoCompileModuleData;
 use super::serialization::DeserializedDataSection;
 use super::serialization::RemoteModulesStore;
 use super::serialization::RemoteModulesStoreBuilder;
+use super::serialization::SectionBytes;
 use super::virtual_fs::FileBackedVfs;
 use super::virtual_fs::VfsBuilder;
 use super::virtual_fs::VfsFileSubDataKind;
@@ -176,6 +178,9 @@ pub struct SerializedWorkspaceResolver {
 // is deterministic.
 #[derive(Deserialize, Serialize)]
 pub struct Metadata {
+  /// Version of Deno that created the executable.
+  #[serde(default)]
+  pub deno_version: Option<String>,
   pub argv: Vec<String>,
   pub seed: Option<u64>,
   pub code_cache_key: Option<u64>,
@@ -195,18 +200,11 @@ pub struct Metadata {
 }
 
 fn write_binary_bytes(
-  mut file_writer: File,
+  mut file_writer: impl Write,
   original_bin: Vec<u8>,
-  metadata: &Metadata,
-  npm_snapshot: Option<SerializedNpmResolutionSnapshot>,
-  remote_modules: &RemoteModulesStoreBuilder,
-  vfs: VfsBuilder,
+  data_section_bytes: Vec<u8>,
   compile_flags: &CompileFlags,
 ) -> Result<(), AnyError> {
-  let data_section_bytes =
-    serialize_binary_data_section(metadata, npm_snapshot, remote_modules, vfs)
-      .context("Serializing binary data section.")?;
-
   let target = compile_flags.resolve_target();
   if target.contains("linux") {
     libsui::Elf::new(&original_bin).append(
@@ -303,28 +301,20 @@ impl StandaloneModules {
   }
 }
 
-/// This function will try to run this binary as a standalone binary
-/// produced by `deno compile`. It determines if this is a standalone
-/// binary by skipping over the trailer width at the end of the file,
-/// then checking for the magic trailer string `d3n0l4nd`. If found,
-/// the bundle is executed. If not, this function exits with `Ok(None)`.
+/// Reads the program embedded by `deno compile` in the `d3n0l4nd` section
+/// of the current executable, or returns `Ok(None)` when there's none.
+/// `payload_cipher` decrypts it when it was encrypted by `deno compile`.
 pub fn extract_standalone(
   cli_args: Cow<Vec<OsString>>,
+  payload_cipher: Option<&dyn PayloadCipher>,
 ) -> Result<Option<StandaloneData>, AnyError> {
   let Some(data) = libsui::find_section("d3n0l4nd") else {
     return Ok(None);
   };
-
-  let DeserializedDataSection {
-    mut metadata,
-    npm_snapshot,
-    remote_modules,
-    mut vfs_dir,
-    vfs_files_data,
-  } = match deserialize_binary_data_section(data)? {
-    Some(data_section) => data_section,
-    None => return Ok(None),
-  };
+  let data = maybe_decrypt_binary_data_section(
+    SectionBytes::Static(data),
+    payload_cipher,
+  )?;
 
   let root_path = {
     let maybe_current_exe = std::env::current_exe().ok();
@@ -336,11 +326,51 @@ pub fn extract_standalone(
       .unwrap_or_else(|| Cow::Borrowed("binary"));
     std::env::temp_dir().join(format!("deno-compile-{}", current_exe_name))
   };
-  let cli_args = cli_args.into_owned();
-  metadata.argv.reserve(cli_args.len() - 1);
-  for arg in cli_args.into_iter().skip(1) {
-    metadata.argv.push(arg.into_string().unwrap());
+  let cli_args = cli_args
+    .iter()
+    .skip(1)
+    .map(|arg| arg.to_str().unwrap().to_string())
+    .collect();
+  load_standalone_data(data, root_path, cli_args)
+}
+
+/// Reads an archive written by [`DenoCompileBinaryWriter::write_archive`].
+/// The module loader and the file system of the program share a copy of the
+/// bytes, which is dropped along with them.
+pub fn extract_archive(data: &[u8]) -> Result<StandaloneData, AnyError> {
+  let data = maybe_decrypt_binary_data_section(
+    SectionBytes::shared(Arc::from(data)),
+    None,
+  )?;
+  // the files of different archives must not share a root, eg. for the
+  // code cache next to it
+  let root_path = std::env::temp_dir().join(format!(
+    "deno-archive-{}",
+    crate::util::checksum::gen(&[data.as_slice()])
+  ));
+  match load_standalone_data(data, root_path, vec![])? {
+    Some(data) => Ok(data),
+    None => bail!("The bytes are not an archive created by `deno compile`."),
   }
+}
+
+fn load_standalone_data(
+  data: SectionBytes,
+  root_path: PathBuf,
+  cli_args: Vec<String>,
+) -> Result<Option<StandaloneData>, AnyError> {
+  let DeserializedDataSection {
+    mut metadata,
+    npm_snapshot,
+    remote_modules,
+    mut vfs_dir,
+    vfs_files_data,
+  } = match deserialize_binary_data_section(&data)? {
+    Some(data_section) => data_section,
+    None => return Ok(None),
+  };
+
+  metadata.argv.extend(cli_args);
   let vfs = {
     // align the name of the directory with the root dir
     vfs_dir.name = root_path.file_name().unwrap().to_string_lossy().to_string();
@@ -350,7 +380,7 @@ pub fn extract_standalone(
       root_path: root_path.clone(),
       start_file_offset: 0,
     };
-    Arc::new(FileBackedVfs::new(Cow::Borrowed(vfs_files_data), fs_root))
+    Arc::new(FileBackedVfs::new(vfs_files_data, fs_root))
   };
   let fs: Arc<dyn deno_fs::FileSystem> =
     Arc::new(DenoCompileFileSystem::new(vfs.clone()));
@@ -377,6 +407,8 @@ pub struct DenoCompileBinaryWriter<'a> {
   npm_resolver: &'a dyn CliNpmResolver,
   workspace_resolver: &'a WorkspaceResolver,
   npm_system_info: NpmSystemInfo,
+  base_binary_path: Option<PathBuf>,
+  payload_cipher: Option<Arc<dyn PayloadCipher>>,
 }
 
 impl<'a> DenoCompileBinaryWriter<'a> {
@@ -402,17 +434,35 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       npm_resolver,
       workspace_resolver,
       npm_system_info,
+      base_binary_path: None,
+      payload_cipher: None,
     }
   }
 
+  /// Uses the executable at `path` instead of `denort` as the base of the
+  /// created executables.
+  pub fn set_base_binary_path(&mut self, path: PathBuf) {
+    self.base_binary_path = Some(path);
+  }
+
+  /// Encrypts the embedded program with `cipher`. The base executable has
+  /// to decrypt it with the same cipher.
+  pub fn set_payload_cipher(&mut self, cipher: Arc<dyn PayloadCipher>) {
+    self.payload_cipher = Some(cipher);
+  }
+
+  /// Writes the executable to `writer`. `env_vars` are embedded in addition
+  /// to the ones from the env files and take precedence over them.
+  #[allow(clippy::too_many_arguments)]
   pub async fn write_bin(
     &self,
-    writer: File,
+    writer: impl Write,
     graph: &ModuleGraph,
     root_dir_url: StandaloneRelativeFileBaseUrl<'_>,
     entrypoint: &ModuleSpecifier,
     include_files: &[ModuleSpecifier],
     compile_flags: &CompileFlags,
+    env_vars: IndexMap<String, String>,
   ) -> Result<(), AnyError> {
     // Select base binary based on target
     let mut original_binary = self.get_base_binary(compile_flags).await?;
@@ -437,17 +487,52 @@ impl<'a> DenoCompileBinaryWriter<'a> {
         )
       }
     }
-    self
-      .write_standalone_binary(
-        writer,
-        original_binary,
+    let data_section_bytes = self
+      .create_data_section(
         graph,
         root_dir_url,
         entrypoint,
         include_files,
         compile_flags,
+        env_vars,
       )
-      .await
+      .await?;
+    write_binary_bytes(
+      writer,
+      original_binary,
+      data_section_bytes,
+      compile_flags,
+    )
+    .context("Writing binary bytes")
+  }
+
+  /// Writes the program that [`Self::write_bin`] would embed in the
+  /// executable to `writer`, without the executable. Any Deno built from the
+  /// same version can run it, see `crate::run_archive`.
+  #[allow(clippy::too_many_arguments)]
+  pub async fn write_archive(
+    &self,
+    mut writer: impl Write,
+    graph: &ModuleGraph,
+    root_dir_url: StandaloneRelativeFileBaseUrl<'_>,
+    entrypoint: &ModuleSpecifier,
+    include_files: &[ModuleSpecifier],
+    compile_flags: &CompileFlags,
+    env_vars: IndexMap<String, String>,
+  ) -> Result<(), AnyError> {
+    let data_section_bytes = self
+      .create_data_section(
+        graph,
+        root_dir_url,
+        entrypoint,
+        include_files,
+        compile_flags,
+        env_vars,
+      )
+      .await?;
+    writer
+      .write_all(&data_section_bytes)
+      .context("Writing archive bytes")
   }
 
   async fn get_base_binary(
@@ -458,7 +543,12 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     //
     // Phase 2 of the 'min sized' deno compile RFC talks
     // about adding this as a flag.
-    if let Some(path) = get_dev_binary_path() {
+    if let Some(path) = self
+      .base_binary_path
+      .clone()
+      .map(OsString::from)
+      .or_else(get_dev_binary_path)
+    {
       return std::fs::read(&path).with_context(|| {
         format!("Could not find denort at '{}'", path.to_string_lossy())
       });
@@ -547,19 +637,18 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     Ok(())
   }
 
-  /// This functions creates a standalone deno binary by appending a bundle
-  /// and magic trailer to the currently executing binary.
+  /// Serializes the bundle that is appended to the base binary, see
+  /// `serialize_binary_data_section`.
   #[allow(clippy::too_many_arguments)]
-  async fn write_standalone_binary(
+  async fn create_data_section(
     &self,
-    writer: File,
-    original_bin: Vec<u8>,
     graph: &ModuleGraph,
     root_dir_url: StandaloneRelativeFileBaseUrl<'_>,
     entrypoint: &ModuleSpecifier,
     include_files: &[ModuleSpecifier],
     compile_flags: &CompileFlags,
-  ) -> Result<(), AnyError> {
+    env_vars: IndexMap<String, String>,
+  ) -> Result<Vec<u8>, AnyError> {
     let ca_data = match self.cli_options.ca_data() {
       Some(CaData::File(ca_file)) => Some(
         std::fs::read(ca_file).with_context(|| format!("Reading {ca_file}"))?,
@@ -706,7 +795,7 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     }
     remote_modules_store.add_redirects(&graph.redirects);
 
-    let env_vars_from_env_file = match self.cli_options.env_file_name() {
+    let mut env_vars_from_env_file = match self.cli_options.env_file_name() {
       Some(env_filenames) => {
         let mut aggregated_env_vars = IndexMap::new();
         for env_filename in env_filenames.iter().rev() {
@@ -719,8 +808,10 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       }
       None => Default::default(),
     };
+    env_vars_from_env_file.extend(env_vars);
 
     let metadata = Metadata {
+      deno_version: Some(crate::version::DENO_VERSION_INFO.deno.to_string()),
       argv: compile_flags.args.clone(),
       seed: self.cli_options.seed(),
       code_cache_key: code_cache_key_hasher.map(|h| h.finish()),
@@ -783,16 +874,19 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       otel_config: self.cli_options.otel_config(),
     };
 
-    write_binary_bytes(
-      writer,
-      original_bin,
+    let mut data_section_bytes = serialize_binary_data_section(
       &metadata,
       npm_snapshot.map(|s| s.into_serialized()),
       &remote_modules_store,
       vfs,
-      compile_flags,
     )
-    .context("Writing binary bytes")
+    .context("Serializing binary data section.")?;
+    if let Some(cipher) = &self.payload_cipher {
+      data_section_bytes =
+        encrypt_binary_data_section(&data_section_bytes, cipher.as_ref())
+          .context("Encrypting binary data section.")?;
+    }
+    Ok(data_section_bytes)
   }
 
   fn build_npm_vfs(&self, root_path: &Path) -> Result<VfsBuilder, AnyError> {
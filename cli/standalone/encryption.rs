@@ -0,0 +1,92 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use ring::aead;
+use ring::rand::SecureRandom;
+use ring::rand::SystemRandom;
+
+/// Encrypts the program embedded in executables created by `deno compile`,
+/// so its source can't be read by extracting the data section.
+///
+/// The executable has to decrypt the program with the same cipher when it
+/// starts, see `run_standalone`.
+pub trait PayloadCipher: Send + Sync {
+  fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>, AnyError>;
+  fn decrypt(&self, payload: &[u8]) -> Result<Vec<u8>, AnyError>;
+}
+
+/// AES-256-GCM with a random nonce, which is stored in front of the
+/// ciphertext.
+pub struct Aes256GcmCipher {
+  key: aead::LessSafeKey,
+}
+
+impl Aes256GcmCipher {
+  pub fn new(key: &[u8; 32]) -> Self {
+    let key = aead::UnboundKey::new(&aead::AES_256_GCM, key).unwrap();
+    Self {
+      key: aead::LessSafeKey::new(key),
+    }
+  }
+}
+
+impl PayloadCipher for Aes256GcmCipher {
+  fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>, AnyError> {
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    if SystemRandom::new().fill(&mut nonce).is_err() {
+      bail!("Failed to generate a nonce.");
+    }
+    let mut in_out = payload.to_vec();
+    if self
+      .key
+      .seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::empty(),
+        &mut in_out,
+      )
+      .is_err()
+    {
+      bail!("Failed to encrypt the payload.");
+    }
+    let mut encrypted = Vec::with_capacity(nonce.len() + in_out.len());
+    encrypted.extend_from_slice(&nonce);
+    encrypted.extend_from_slice(&in_out);
+    Ok(encrypted)
+  }
+
+  fn decrypt(&self, payload: &[u8]) -> Result<Vec<u8>, AnyError> {
+    if payload.len() < aead::NONCE_LEN {
+      bail!("The payload is too short.");
+    }
+    let (nonce, ciphertext) = payload.split_at(aead::NONCE_LEN);
+    let nonce = aead::Nonce::try_assume_unique_for_key(nonce).unwrap();
+    let mut in_out = ciphertext.to_vec();
+    let Ok(decrypted) =
+      self
+        .key
+        .open_in_place(nonce, aead::Aad::empty(), &mut in_out)
+    else {
+      bail!("Failed to decrypt the payload, the key is probably wrong.");
+    };
+    let len = decrypted.len();
+    in_out.truncate(len);
+    Ok(in_out)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn aes_256_gcm_round_trip() {
+    let cipher = Aes256GcmCipher::new(&[7; 32]);
+    let encrypted = cipher.encrypt(b"console.log(1);").unwrap();
+    assert!(!encrypted.windows(b"console".len()).any(|w| w == b"console"));
+    assert_eq!(cipher.decrypt(&encrypted).unwrap(), b"console.log(1);");
+
+    let other = Aes256GcmCipher::new(&[8; 32]);
+    assert!(other.decrypt(&encrypted).is_err());
+  }
+}
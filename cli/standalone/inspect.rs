@@ -0,0 +1,251 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use serde::Serialize;
+
+use crate::args::PermissionFlags;
+
+use super::serialization::read_binary_data_section_contents;
+use super::serialization::DataSectionContents;
+use super::serialization::MAGIC_BYTES;
+use super::virtual_fs::VfsEntry;
+use super::virtual_fs::VirtualDirectory;
+
+/// What `deno compile` embedded in an executable.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryMetadata {
+  /// Version of Deno that created the executable. `None` for executables
+  /// created by versions that didn't record it.
+  pub deno_version: Option<String>,
+  /// The main module, relative to the root of the embedded files.
+  pub entrypoint: String,
+  /// Modules that were fetched from remote URLs.
+  pub remote_modules: Vec<String>,
+  /// npm packages, eg. `"chalk@5.3.0"`.
+  pub npm_packages: Vec<String>,
+  /// Local modules, included files and `node_modules`.
+  pub files: Vec<BinaryFile>,
+  /// Permissions the program runs with.
+  pub permissions: PermissionFlags,
+  /// Arguments passed to the program before the ones it's invoked with.
+  pub argv: Vec<String>,
+  /// Names of the embedded environment variables. Their values are left
+  /// out as they often contain secrets.
+  pub env_vars: Vec<String>,
+  pub unstable_features: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryFile {
+  /// Path relative to the root of the embedded files, separated by `/`.
+  pub path: String,
+  pub size: u64,
+}
+
+/// Reads what `deno compile` embedded in the executable at `path`, without
+/// running it. Errors when the executable contains no program, which is
+/// also the case when the program is encrypted.
+pub fn inspect_binary(path: &Path) -> Result<BinaryMetadata, AnyError> {
+  let data = std::fs::read(path)
+    .with_context(|| format!("Reading {}", path.display()))?;
+  let Some(contents) = find_data_section(&data) else {
+    bail!(
+      "Could not find a program embedded by `deno compile` in '{}'.",
+      path.display()
+    );
+  };
+  let DataSectionContents {
+    metadata,
+    npm_snapshot,
+    remote_specifiers,
+    vfs_dir,
+  } = contents;
+
+  let mut files = Vec::new();
+  collect_files(&vfs_dir, "", &mut files);
+  let mut npm_packages = npm_snapshot
+    .map(|snapshot| {
+      snapshot
+        .as_serialized()
+        .packages
+        .iter()
+        .map(|pkg| pkg.id.as_serialized())
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+  npm_packages.sort();
+  Ok(BinaryMetadata {
+    deno_version: metadata.deno_version,
+    entrypoint: metadata.entrypoint_key,
+    remote_modules: remote_specifiers
+      .into_iter()
+      .map(|specifier| specifier.to_string())
+      .collect(),
+    npm_packages,
+    files,
+    permissions: metadata.permissions,
+    argv: metadata.argv,
+    env_vars: metadata.env_vars_from_env_file.into_keys().collect(),
+    unstable_features: metadata.unstable_config.features,
+  })
+}
+
+/// Finds the data section in the bytes of an executable. The magic bytes
+/// also appear in the code of the runtime, so every occurrence is tried
+/// until one parses as a complete data section.
+fn find_data_section(data: &[u8]) -> Option<DataSectionContents> {
+  let mut start = 0;
+  while let Some(offset) = data[start..]
+    .windows(MAGIC_BYTES.len())
+    .position(|window| window == MAGIC_BYTES)
+  {
+    let section_start = start + offset;
+    if let Ok(Some(contents)) =
+      read_binary_data_section_contents(&data[section_start..])
+    {
+      return Some(contents);
+    }
+    start = section_start + 1;
+  }
+  None
+}
+
+fn collect_files(
+  dir: &VirtualDirectory,
+  prefix: &str,
+  files: &mut Vec<BinaryFile>,
+) {
+  for entry in &dir.entries {
+    match entry {
+      VfsEntry::Dir(dir) => {
+        collect_files(dir, &format!("{}{}/", prefix, dir.name), files);
+      }
+      VfsEntry::File(file) => files.push(BinaryFile {
+        path: format!("{}{}", prefix, file.name),
+        size: file.len,
+      }),
+      VfsEntry::Symlink(_) => {}
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::collections::BTreeMap;
+
+  use deno_ast::MediaType;
+  use deno_config::workspace::PackageJsonDepResolution;
+  use deno_core::url::Url;
+  use indexmap::IndexMap;
+  use test_util::TempDir;
+
+  use super::*;
+  use crate::standalone::binary::Metadata;
+  use crate::standalone::binary::SerializedWorkspaceResolver;
+  use crate::standalone::serialization::serialize_binary_data_section;
+  use crate::standalone::serialization::RemoteModulesStoreBuilder;
+  use crate::standalone::virtual_fs::VfsBuilder;
+
+  #[test]
+  fn inspects_embedded_program() {
+    let temp_dir = TempDir::new();
+    let root = temp_dir.path().canonicalize().join("app");
+    root.join("data").create_dir_all();
+    root.join("main.ts").write("console.log(1);");
+    root.join("data/config.json").write("{}");
+    let mut vfs = VfsBuilder::new(root.to_path_buf()).unwrap();
+    vfs
+      .add_file_at_path(root.join("main.ts").as_path())
+      .unwrap();
+    vfs
+      .add_file_at_path(root.join("data/config.json").as_path())
+      .unwrap();
+    let mut remote_modules = RemoteModulesStoreBuilder::default();
+    remote_modules.add(
+      &Url::parse("https://deno.land/x/mod.ts").unwrap(),
+      MediaType::TypeScript,
+      b"export {};".to_vec(),
+    );
+    let metadata = Metadata {
+      deno_version: Some("2.1.2".to_string()),
+      argv: vec!["--verbose".to_string()],
+      seed: None,
+      code_cache_key: None,
+      permissions: PermissionFlags {
+        allow_net: Some(vec!["deno.land".to_string()]),
+        ..Default::default()
+      },
+      location: None,
+      v8_flags: vec![],
+      log_level: None,
+      ca_stores: None,
+      ca_data: None,
+      unsafely_ignore_certificate_errors: None,
+      env_vars_from_env_file: IndexMap::from([(
+        "TOKEN".to_string(),
+        "secret".to_string(),
+      )]),
+      workspace_resolver: SerializedWorkspaceResolver {
+        import_map: None,
+        jsr_pkgs: vec![],
+        package_jsons: BTreeMap::new(),
+        pkg_json_resolution: PackageJsonDepResolution::Enabled,
+      },
+      entrypoint_key: "main.ts".to_string(),
+      node_modules: None,
+      unstable_config: Default::default(),
+      otel_config: None,
+    };
+    let data_section =
+      serialize_binary_data_section(&metadata, None, &remote_modules, vfs)
+        .unwrap();
+    // the magic bytes also appear in the code of the runtime
+    let mut binary = b"runtime code d3n0l4nd runtime code".to_vec();
+    binary.extend(data_section);
+    temp_dir.write("app.exe", binary);
+
+    let metadata =
+      inspect_binary(temp_dir.path().join("app.exe").as_path()).unwrap();
+    assert_eq!(metadata.deno_version.as_deref(), Some("2.1.2"));
+    assert_eq!(metadata.entrypoint, "main.ts");
+    assert_eq!(metadata.remote_modules, vec!["https://deno.land/x/mod.ts"]);
+    assert!(metadata.npm_packages.is_empty());
+    let mut files = metadata.files;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(
+      files,
+      vec![
+        BinaryFile {
+          path: "data/config.json".to_string(),
+          size: 2,
+        },
+        BinaryFile {
+          path: "main.ts".to_string(),
+          size: 15,
+        },
+      ]
+    );
+    assert_eq!(
+      metadata.permissions.allow_net,
+      Some(vec!["deno.land".to_string()])
+    );
+    assert_eq!(metadata.argv, vec!["--verbose"]);
+    // only the names of the environment variables
+    assert_eq!(metadata.env_vars, vec!["TOKEN"]);
+  }
+
+  #[test]
+  fn errors_without_embedded_program() {
+    let temp_dir = TempDir::new();
+    temp_dir.write("plain.exe", "no program d3n0l4nd here");
+    let err =
+      inspect_binary(temp_dir.path().join("plain.exe").as_path()).unwrap_err();
+    assert!(err.to_string().starts_with("Could not find a program"));
+  }
+}
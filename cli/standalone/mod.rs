@@ -21,6 +21,7 @@ use deno_core::error::AnyError;
 use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::FutureExt;
 use deno_core::v8_set_flags;
+use deno_core::Extension;
 use deno_core::FastString;
 use deno_core::FeatureChecker;
 use deno_core::ModuleLoader;
@@ -101,13 +102,21 @@ use crate::worker::ModuleLoaderFactory;
 
 pub mod binary;
 mod code_cache;
+pub mod encryption;
 mod file_system;
+mod inspect;
 mod serialization;
 mod virtual_fs;
 
+pub use binary::extract_archive;
 pub use binary::extract_standalone;
 pub use binary::is_standalone_binary;
 pub use binary::DenoCompileBinaryWriter;
+pub use encryption::Aes256GcmCipher;
+pub use encryption::PayloadCipher;
+pub use inspect::inspect_binary;
+pub use inspect::BinaryFile;
+pub use inspect::BinaryMetadata;
 
 use self::binary::Metadata;
 use self::file_system::DenoCompileFileSystem;
@@ -583,7 +592,12 @@ impl RootCertStoreProvider for StandaloneRootCertStoreProvider {
   }
 }
 
-pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
+/// Runs the program in `data`. `custom_extensions` are registered on its
+/// main worker.
+pub async fn run(
+  data: StandaloneData,
+  custom_extensions: Vec<Extension>,
+) -> Result<i32, AnyError> {
   let StandaloneData {
     fs,
     metadata,
@@ -659,6 +673,7 @@ pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
             ),
             npmrc,
             lifecycle_scripts: Default::default(),
+            npm_store: None,
           },
         ))
         .await?;
@@ -715,6 +730,7 @@ pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
             ),
             npmrc: create_default_npmrc(),
             lifecycle_scripts: Default::default(),
+            npm_store: None,
           },
         ))
         .await?;
@@ -869,6 +885,7 @@ pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
       Permissions::from_options(desc_parser.as_ref(), &permissions)?;
     PermissionsContainer::new(desc_parser, permissions)
   };
+  let main_worker_permissions = permissions.clone();
   let feature_checker = Arc::new({
     let mut checker = FeatureChecker::default();
     checker.set_exit_cb(Box::new(crate::unstable_exit_cb));
@@ -907,6 +924,24 @@ pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
       strace_ops: None,
       is_inspecting: false,
       skip_op_registration: true,
+      host_channel: false,
+      serve_adapter: false,
+      startup_snapshot: None,
+      ephemeral_deno_dir: None,
+      worker_observer: None,
+      execution_limits: None,
+      unstable_api_errors: false,
+      fetch_interceptor: None,
+      connect_interceptor: None,
+      spawn_interceptor: None,
+      dlopen_interceptor: None,
+      kv_backend: None,
+      broadcast_channel: None,
+      worker_creation_policy: None,
+      web_worker_extensions: None,
+      virtual_env: None,
+      signal_forwarder: None,
+      resource_quotas: None,
       location: metadata.location,
       argv0: NpmPackageReqReference::from_specifier(&main_module)
         .ok()
@@ -919,6 +954,11 @@ pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
         .unsafely_ignore_certificate_errors,
       create_hmr_runner: None,
       create_coverage_collector: None,
+      inspector_controller: None,
+      determinism: None,
+      host_fns: vec![],
+      create_wasi_extension: None,
+      restored_state: None,
       node_ipc: None,
       serve_port: None,
       serve_host: None,
@@ -932,7 +972,13 @@ pub async fn run(data: StandaloneData) -> Result<i32, AnyError> {
   deno_core::JsRuntime::init_platform(None, true);
 
   let mut worker = worker_factory
-    .create_main_worker(WorkerExecutionMode::Run, main_module)
+    .create_custom_worker(
+      WorkerExecutionMode::Run,
+      main_module,
+      main_worker_permissions,
+      custom_extensions,
+      Default::default(),
+    )
     .await?;
 
   let exit_code = worker.run().await?;
@@ -4,6 +4,8 @@ use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::io::Write;
+use std::ops::Range;
+use std::sync::Arc;
 
 use deno_ast::MediaType;
 use deno_core::anyhow::bail;
@@ -23,9 +25,11 @@ use deno_semver::package::PackageReq;
 use crate::standalone::virtual_fs::VirtualDirectory;
 
 use super::binary::Metadata;
+use super::encryption::PayloadCipher;
 use super::virtual_fs::VfsBuilder;
 
-const MAGIC_BYTES: &[u8; 8] = b"d3n0l4nd";
+pub const MAGIC_BYTES: &[u8; 8] = b"d3n0l4nd";
+const ENCRYPTED_MAGIC_BYTES: &[u8; 8] = b"d3n0encr";
 
 /// Binary format:
 /// * d3n0l4nd
@@ -90,17 +94,169 @@ pub fn serialize_binary_data_section(
   Ok(bytes)
 }
 
+/// Encrypted binary format:
+/// * d3n0encr
+/// * <data section encrypted by the payload cipher>
+pub fn encrypt_binary_data_section(
+  data_section: &[u8],
+  cipher: &dyn PayloadCipher,
+) -> Result<Vec<u8>, AnyError> {
+  let encrypted = cipher.encrypt(data_section)?;
+  let mut bytes =
+    Vec::with_capacity(ENCRYPTED_MAGIC_BYTES.len() + encrypted.len());
+  bytes.extend_from_slice(ENCRYPTED_MAGIC_BYTES);
+  bytes.extend_from_slice(&encrypted);
+  Ok(bytes)
+}
+
+/// The bytes a data section is read from: the section of the current
+/// executable, or bytes shared by the loader and the file system of the
+/// program, eg. of an archive or a decrypted section.
+#[derive(Debug, Clone)]
+pub enum SectionBytes {
+  Static(&'static [u8]),
+  Shared(Arc<[u8]>, Range<usize>),
+}
+
+impl SectionBytes {
+  pub fn shared(bytes: Arc<[u8]>) -> Self {
+    let len = bytes.len();
+    Self::Shared(bytes, 0..len)
+  }
+
+  pub fn as_slice(&self) -> &[u8] {
+    match self {
+      Self::Static(bytes) => bytes,
+      Self::Shared(bytes, range) => &bytes[range.clone()],
+    }
+  }
+
+  /// The bytes at `range` of these bytes.
+  pub fn slice(&self, range: Range<usize>) -> Self {
+    assert!(range.end <= self.as_slice().len());
+    match self {
+      Self::Static(bytes) => Self::Static(&bytes[range]),
+      Self::Shared(bytes, shared_range) => Self::Shared(
+        bytes.clone(),
+        shared_range.start + range.start..shared_range.start + range.end,
+      ),
+    }
+  }
+
+  /// The bytes at `range` of these bytes, copied unless they live for the
+  /// rest of the process.
+  pub fn to_cow(&self, range: Range<usize>) -> Cow<'static, [u8]> {
+    match self {
+      Self::Static(bytes) => Cow::Borrowed(&bytes[range]),
+      Self::Shared(..) => Cow::Owned(self.as_slice()[range].to_vec()),
+    }
+  }
+
+  /// The range of `slice`, which must be borrowed from these bytes.
+  fn range_of(&self, slice: &[u8]) -> Range<usize> {
+    let bytes = self.as_slice().as_ptr_range();
+    let slice_range = slice.as_ptr_range();
+    assert!(bytes.start <= slice_range.start && slice_range.end <= bytes.end);
+    let start = slice_range.start as usize - bytes.start as usize;
+    start..start + slice.len()
+  }
+}
+
+/// Decrypts the data section if it's encrypted.
+pub fn maybe_decrypt_binary_data_section(
+  data: SectionBytes,
+  cipher: Option<&dyn PayloadCipher>,
+) -> Result<SectionBytes, AnyError> {
+  let Some(encrypted) = data
+    .as_slice()
+    .strip_prefix(ENCRYPTED_MAGIC_BYTES.as_slice())
+  else {
+    return Ok(data);
+  };
+  let Some(cipher) = cipher else {
+    bail!(
+      "The program embedded in this executable is encrypted, but the executable doesn't provide a key to decrypt it."
+    );
+  };
+  let decrypted = cipher
+    .decrypt(encrypted)
+    .context("Decrypting the embedded program.")?;
+  Ok(SectionBytes::shared(decrypted.into()))
+}
+
 pub struct DeserializedDataSection {
   pub metadata: Metadata,
   pub npm_snapshot: Option<ValidSerializedNpmResolutionSnapshot>,
   pub remote_modules: RemoteModulesStore,
   pub vfs_dir: VirtualDirectory,
-  pub vfs_files_data: &'static [u8],
+  pub vfs_files_data: SectionBytes,
 }
 
 pub fn deserialize_binary_data_section(
-  data: &'static [u8],
+  data: &SectionBytes,
 ) -> Result<Option<DeserializedDataSection>, AnyError> {
+  let Some(section) = read_data_section(data.as_slice())? else {
+    return Ok(None);
+  };
+  let remote_modules = RemoteModulesStore::build(
+    data.slice(data.range_of(section.remote_modules_data)),
+  )
+  .context("deserializing remote modules")?;
+  Ok(Some(DeserializedDataSection {
+    metadata: section.metadata,
+    npm_snapshot: section.npm_snapshot,
+    remote_modules,
+    vfs_dir: section.vfs_dir,
+    vfs_files_data: data.slice(data.range_of(section.vfs_files_data)),
+  }))
+}
+
+/// What a data section contains, for inspecting executables.
+pub struct DataSectionContents {
+  pub metadata: Metadata,
+  pub npm_snapshot: Option<ValidSerializedNpmResolutionSnapshot>,
+  /// The remote modules, without redirects.
+  pub remote_specifiers: Vec<Url>,
+  pub vfs_dir: VirtualDirectory,
+}
+
+pub fn read_binary_data_section_contents(
+  data: &[u8],
+) -> Result<Option<DataSectionContents>, AnyError> {
+  let Some(section) = read_data_section(data)? else {
+    return Ok(None);
+  };
+  let (_, specifiers) =
+    read_remote_modules_headers(section.remote_modules_data)
+      .context("deserializing remote modules")?;
+  let mut remote_specifiers = specifiers
+    .into_iter()
+    .filter_map(|(specifier, value)| match value {
+      RemoteModulesStoreSpecifierValue::Data(_) => Some(specifier),
+      RemoteModulesStoreSpecifierValue::Redirect(_) => None,
+    })
+    .collect::<Vec<_>>();
+  remote_specifiers.sort();
+  Ok(Some(DataSectionContents {
+    metadata: section.metadata,
+    npm_snapshot: section.npm_snapshot,
+    remote_specifiers,
+    vfs_dir: section.vfs_dir,
+  }))
+}
+
+/// A data section with the remote modules and the vfs files left serialized.
+struct RawDataSection<'a> {
+  metadata: Metadata,
+  npm_snapshot: Option<ValidSerializedNpmResolutionSnapshot>,
+  remote_modules_data: &'a [u8],
+  vfs_dir: VirtualDirectory,
+  vfs_files_data: &'a [u8],
+}
+
+fn read_data_section(
+  data: &[u8],
+) -> Result<Option<RawDataSection<'_>>, AnyError> {
   fn read_bytes_with_len(input: &[u8]) -> Result<(&[u8], &[u8]), AnyError> {
     let (input, len) = read_u64(input)?;
     let (input, data) = read_bytes(input, len as usize)?;
@@ -136,10 +292,8 @@ pub fn deserialize_binary_data_section(
     Some(deserialize_npm_snapshot(data).context("deserializing npm snapshot")?)
   };
   // 3. Remote modules
-  let (input, data) =
+  let (input, remote_modules_data) =
     read_bytes_with_len(input).context("reading remote modules data")?;
-  let remote_modules =
-    RemoteModulesStore::build(data).context("deserializing remote modules")?;
   // 4. VFS
   let (input, data) = read_bytes_with_len(input).context("vfs")?;
   let vfs_dir: VirtualDirectory =
@@ -153,10 +307,10 @@ pub fn deserialize_binary_data_section(
     bail!("Could not find magic bytes at the end of the data.");
   }
 
-  Ok(Some(DeserializedDataSection {
+  Ok(Some(RawDataSection {
     metadata,
     npm_snapshot,
-    remote_modules,
+    remote_modules_data,
     vfs_dir,
     vfs_files_data,
   }))
@@ -284,6 +438,46 @@ impl<'a> DenoCompileModuleData<'a> {
   }
 }
 
+fn read_remote_modules_headers(
+  input: &[u8],
+) -> Result<(&[u8], HashMap<Url, RemoteModulesStoreSpecifierValue>), AnyError> {
+  fn read_specifier(input: &[u8]) -> Result<(&[u8], (Url, u64)), AnyError> {
+    let (input, specifier) = read_string_lossy(input)?;
+    let specifier = Url::parse(&specifier)?;
+    let (input, offset) = read_u64(input)?;
+    Ok((input, (specifier, offset)))
+  }
+
+  fn read_redirect(input: &[u8]) -> Result<(&[u8], (Url, Url)), AnyError> {
+    let (input, from) = read_string_lossy(input)?;
+    let from = Url::parse(&from)?;
+    let (input, to) = read_string_lossy(input)?;
+    let to = Url::parse(&to)?;
+    Ok((input, (from, to)))
+  }
+
+  let (input, specifiers_len) = read_u32_as_usize(input)?;
+  let (mut input, redirects_len) = read_u32_as_usize(input)?;
+  let mut specifiers = HashMap::with_capacity(specifiers_len + redirects_len);
+  for _ in 0..specifiers_len {
+    let (current_input, (specifier, offset)) =
+      read_specifier(input).context("reading specifier")?;
+    input = current_input;
+    specifiers.insert(
+      specifier,
+      RemoteModulesStoreSpecifierValue::Data(offset as usize),
+    );
+  }
+
+  for _ in 0..redirects_len {
+    let (current_input, (from, to)) = read_redirect(input)?;
+    input = current_input;
+    specifiers.insert(from, RemoteModulesStoreSpecifierValue::Redirect(to));
+  }
+
+  Ok((input, specifiers))
+}
+
 enum RemoteModulesStoreSpecifierValue {
   Data(usize),
   Redirect(Url),
@@ -291,54 +485,14 @@ enum RemoteModulesStoreSpecifierValue {
 
 pub struct RemoteModulesStore {
   specifiers: HashMap<Url, RemoteModulesStoreSpecifierValue>,
-  files_data: &'static [u8],
+  files_data: SectionBytes,
 }
 
 impl RemoteModulesStore {
-  fn build(data: &'static [u8]) -> Result<Self, AnyError> {
-    fn read_specifier(input: &[u8]) -> Result<(&[u8], (Url, u64)), AnyError> {
-      let (input, specifier) = read_string_lossy(input)?;
-      let specifier = Url::parse(&specifier)?;
-      let (input, offset) = read_u64(input)?;
-      Ok((input, (specifier, offset)))
-    }
-
-    fn read_redirect(input: &[u8]) -> Result<(&[u8], (Url, Url)), AnyError> {
-      let (input, from) = read_string_lossy(input)?;
-      let from = Url::parse(&from)?;
-      let (input, to) = read_string_lossy(input)?;
-      let to = Url::parse(&to)?;
-      Ok((input, (from, to)))
-    }
-
-    fn read_headers(
-      input: &[u8],
-    ) -> Result<(&[u8], HashMap<Url, RemoteModulesStoreSpecifierValue>), AnyError>
-    {
-      let (input, specifiers_len) = read_u32_as_usize(input)?;
-      let (mut input, redirects_len) = read_u32_as_usize(input)?;
-      let mut specifiers =
-        HashMap::with_capacity(specifiers_len + redirects_len);
-      for _ in 0..specifiers_len {
-        let (current_input, (specifier, offset)) =
-          read_specifier(input).context("reading specifier")?;
-        input = current_input;
-        specifiers.insert(
-          specifier,
-          RemoteModulesStoreSpecifierValue::Data(offset as usize),
-        );
-      }
-
-      for _ in 0..redirects_len {
-        let (current_input, (from, to)) = read_redirect(input)?;
-        input = current_input;
-        specifiers.insert(from, RemoteModulesStoreSpecifierValue::Redirect(to));
-      }
-
-      Ok((input, specifiers))
-    }
-
-    let (files_data, specifiers) = read_headers(data)?;
+  fn build(data: SectionBytes) -> Result<Self, AnyError> {
+    let (files_data, specifiers) =
+      read_remote_modules_headers(data.as_slice())?;
+    let files_data = data.slice(data.range_of(files_data));
 
     Ok(Self {
       specifiers,
@@ -387,7 +541,7 @@ impl RemoteModulesStore {
           count += 1;
         }
         Some(RemoteModulesStoreSpecifierValue::Data(offset)) => {
-          let input = &self.files_data[*offset..];
+          let input = &self.files_data.as_slice()[*offset..];
           let (input, media_type_byte) = read_bytes(input, 1)?;
           let media_type = deserialize_media_type(media_type_byte[0])?;
           let (input, len) = read_u64(input)?;
@@ -395,7 +549,7 @@ impl RemoteModulesStore {
           return Ok(Some(DenoCompileModuleData {
             specifier,
             media_type,
-            data: Cow::Borrowed(data),
+            data: self.files_data.to_cow(self.files_data.range_of(data)),
           }));
         }
         None => {
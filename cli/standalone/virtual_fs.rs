@@ -32,6 +32,8 @@ use thiserror::Error;
 use crate::util;
 use crate::util::fs::canonicalize_path;
 
+use super::serialization::SectionBytes;
+
 #[derive(Debug, Copy, Clone)]
 pub enum VfsFileSubDataKind {
   /// Raw bytes of the file.
@@ -840,12 +842,12 @@ impl deno_io::fs::File for FileBackedVfsFile {
 
 #[derive(Debug)]
 pub struct FileBackedVfs {
-  vfs_data: Cow<'static, [u8]>,
+  vfs_data: SectionBytes,
   fs_root: VfsRoot,
 }
 
 impl FileBackedVfs {
-  pub fn new(data: Cow<'static, [u8]>, fs_root: VfsRoot) -> Self {
+  pub fn new(data: SectionBytes, fs_root: VfsRoot) -> Self {
     Self {
       vfs_data: data,
       fs_root,
@@ -922,10 +924,7 @@ impl FileBackedVfs {
     sub_data_kind: VfsFileSubDataKind,
   ) -> std::io::Result<Cow<'static, [u8]>> {
     let read_range = self.get_read_range(file, sub_data_kind, 0, file.len)?;
-    match &self.vfs_data {
-      Cow::Borrowed(data) => Ok(Cow::Borrowed(&data[read_range])),
-      Cow::Owned(data) => Ok(Cow::Owned(data[read_range].to_vec())),
-    }
+    Ok(self.vfs_data.to_cow(read_range))
   }
 
   pub fn read_file(
@@ -941,7 +940,7 @@ impl FileBackedVfs {
       buf.len() as u64,
     )?;
     let read_len = read_range.len();
-    buf[..read_len].copy_from_slice(&self.vfs_data[read_range]);
+    buf[..read_len].copy_from_slice(&self.vfs_data.as_slice()[read_range]);
     Ok(read_len)
   }
 
@@ -1172,7 +1171,7 @@ mod test {
     (
       dest_path.to_path_buf(),
       FileBackedVfs::new(
-        Cow::Owned(data),
+        SectionBytes::shared(data.into()),
         VfsRoot {
           dir: root_dir,
           root_path: dest_path.to_path_buf(),
@@ -1,15 +1,18 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use deno_ast::MediaType;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures;
 use deno_core::futures::future::LocalBoxFuture;
+use deno_core::parking_lot::Mutex;
 use deno_runtime::deno_node::NodeResolver;
 use deno_semver::package::PackageNv;
 use deno_task_shell::ExecutableCommand;
@@ -41,7 +44,17 @@ pub fn get_script_with_args(script: &str, argv: &[String]) -> String {
   script.trim().to_owned()
 }
 
-pub struct TaskStdio(Option<ShellPipeReader>, ShellPipeWriter);
+/// A writer shared between the tasks that are run concurrently.
+pub type TaskOutputSink = Arc<Mutex<dyn Write + Send>>;
+
+pub struct TaskStdio(Option<TaskStdioReader>, ShellPipeWriter);
+
+enum TaskStdioReader {
+  /// Collects the output into [`TaskResult`].
+  Buffered(ShellPipeReader),
+  /// Forwards the output to the sink as it's written.
+  Streamed(ShellPipeReader, TaskOutputSink),
+}
 
 impl TaskStdio {
   pub fn stdout() -> Self {
@@ -54,7 +67,27 @@ impl TaskStdio {
 
   pub fn piped() -> Self {
     let (r, w) = deno_task_shell::pipe();
-    Self(Some(r), w)
+    Self(Some(TaskStdioReader::Buffered(r)), w)
+  }
+
+  pub fn streamed(sink: TaskOutputSink) -> Self {
+    let (r, w) = deno_task_shell::pipe();
+    Self(Some(TaskStdioReader::Streamed(r, sink)), w)
+  }
+}
+
+struct SinkWriter(TaskOutputSink);
+
+impl Write for SinkWriter {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    let mut sink = self.0.lock();
+    sink.write_all(buf)?;
+    sink.flush()?;
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    self.0.lock().flush()
   }
 }
 
@@ -113,11 +146,19 @@ pub async fn run_task(
     TaskStdio(stderr_read, stderr_write),
   ) = (stdio.stdout, stdio.stderr);
 
-  fn read(reader: ShellPipeReader) -> JoinHandle<Result<Vec<u8>, AnyError>> {
-    tokio::task::spawn_blocking(move || {
-      let mut buf = Vec::new();
-      reader.pipe_to(&mut buf)?;
-      Ok(buf)
+  fn read(
+    reader: TaskStdioReader,
+  ) -> JoinHandle<Result<Option<Vec<u8>>, AnyError>> {
+    tokio::task::spawn_blocking(move || match reader {
+      TaskStdioReader::Buffered(reader) => {
+        let mut buf = Vec::new();
+        reader.pipe_to(&mut buf)?;
+        Ok(Some(buf))
+      }
+      TaskStdioReader::Streamed(reader, sink) => {
+        reader.pipe_to(&mut SinkWriter(sink))?;
+        Ok(None)
+      }
     })
   }
 
@@ -137,12 +178,12 @@ pub async fn run_task(
     Ok::<_, AnyError>(TaskResult {
       exit_code,
       stdout: if let Some(stdout) = stdout {
-        Some(stdout.await??)
+        stdout.await??
       } else {
         None
       },
       stderr: if let Some(stderr) = stderr {
-        Some(stderr.await??)
+        stderr.await??
       } else {
         None
       },
@@ -298,6 +339,46 @@ impl ShellCommand for NodeCommand {
   }
 }
 
+/// Runs `node <file>` with a deno process that has only the given
+/// permissions. Unlike [`NodeCommand`], it never falls back to `node`.
+pub struct SandboxedNodeCommand {
+  pub deno_executable: PathBuf,
+  /// The permission flags of the deno process, eg. `--deny-net`.
+  pub permission_args: Vec<String>,
+}
+
+impl ShellCommand for SandboxedNodeCommand {
+  fn execute(
+    &self,
+    mut context: ShellCommandContext,
+  ) -> LocalBoxFuture<'static, ExecuteResult> {
+    if context
+      .args
+      .first()
+      .map_or(true, |arg| arg.starts_with('-'))
+    {
+      let _ = context
+        .stderr
+        .write_line("node: only running a file is supported in a sandbox");
+      return Box::pin(futures::future::ready(ExecuteResult::from_exit_code(
+        1,
+      )));
+    }
+    let mut args = vec!["run".to_string()];
+    args.extend(self.permission_args.iter().cloned());
+    args.extend(context.args.iter().cloned());
+
+    let mut state = context.state;
+    state.apply_env_var(USE_PKG_JSON_HIDDEN_ENV_VAR_NAME, "1");
+    ExecutableCommand::new("deno".to_string(), self.deno_executable.clone())
+      .execute(ShellCommandContext {
+        args,
+        state,
+        ..context
+      })
+  }
+}
+
 pub struct NodeGypCommand;
 
 impl ShellCommand for NodeGypCommand {
@@ -693,4 +774,31 @@ fi"#;
       path.parent().unwrap().join("../example/bin/example")
     );
   }
+
+  #[tokio::test]
+  async fn run_task_streams_output_to_sink() {
+    let cwd = std::env::current_dir().unwrap();
+    let stdout: Arc<Mutex<Vec<u8>>> = Default::default();
+    let result = run_task(RunTaskOptions {
+      task_name: "echo",
+      script: "echo hello",
+      cwd: &cwd,
+      init_cwd: &cwd,
+      env_vars: HashMap::new(),
+      argv: &["world".to_string()],
+      custom_commands: HashMap::new(),
+      root_node_modules_dir: None,
+      stdio: Some(TaskIo {
+        stdout: TaskStdio::streamed(stdout.clone()),
+        stderr: TaskStdio::piped(),
+      }),
+      kill_signal: KillSignal::default(),
+    })
+    .await
+    .unwrap();
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.stdout, None);
+    assert_eq!(result.stderr, Some(Vec::new()));
+    assert_eq!(stdout.lock().as_slice(), b"hello world\n");
+  }
 }
@@ -0,0 +1,69 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::sync::Arc;
+
+use deno::coverage_report;
+use deno::ConfigFlag;
+use deno::CoverageReportOptions;
+use deno::DenoRuntimeBuilder;
+use deno::Flags;
+use deno_core::ModuleSpecifier;
+use test_util::TempDir;
+
+#[tokio::test]
+async fn coverage_dir_collects_a_report() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"function pick(value: boolean): string {
+  if (value) {
+    return "yes";
+  }
+  return "no";
+}
+pick(true);
+"#,
+  );
+  let main_path = temp_dir.path().join("main.ts").canonicalize();
+  let coverage_dir = temp_dir.path().join("cov");
+  let exit_code = DenoRuntimeBuilder::new(main_path.to_string())
+    .no_config()
+    .coverage_dir(coverage_dir.to_path_buf())
+    .run()
+    .await
+    .unwrap();
+  assert_eq!(exit_code, 0);
+
+  let coverage = coverage_report(
+    Arc::new(Flags {
+      config_flag: ConfigFlag::Disabled,
+      ..Default::default()
+    }),
+    CoverageReportOptions {
+      profiles: vec![coverage_dir.to_path_buf()],
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  let files = coverage.files().collect::<Vec<_>>();
+  assert_eq!(files.len(), 1);
+  let file = files[0];
+  assert_eq!(
+    file.specifier(),
+    &ModuleSpecifier::from_file_path(main_path.as_path()).unwrap()
+  );
+  assert!(file.lines().contains(&(2, 1)));
+  assert!(file.lines().contains(&(4, 0)));
+  assert!(file.lines_hit() < file.lines().len());
+  let pick = file.functions().iter().find(|f| f.name == "pick").unwrap();
+  assert_eq!(pick.execution_count, 1);
+
+  let lcov = coverage.lcov().unwrap();
+  assert!(lcov.starts_with(&format!("SF:{}\n", main_path)));
+  assert!(lcov.contains("DA:5,0\n"));
+  assert!(lcov.ends_with("end_of_record\n"));
+  assert!(coverage
+    .html()
+    .iter()
+    .any(|(path, _)| path.ends_with("main.ts.html")));
+}
@@ -0,0 +1,229 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use deno::DenoRuntimeBuilder;
+use deno::DeterminismOptions;
+use deno::DeterministicClock;
+use deno::PendingTimer;
+use deno_core::ascii_str;
+use deno_core::serde_v8;
+use deno_core::v8;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use test_util::TempDir;
+
+const START_TIME_MS: f64 = 1_000_000_000.0;
+
+async fn run_deterministic<T: DeserializeOwned>(
+  source: &str,
+  options: DeterminismOptions,
+) -> T {
+  let temp_dir = TempDir::new();
+  temp_dir.write("main.ts", source);
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .determinism(options)
+      .build()
+      .await
+      .unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  worker.get_global("result").unwrap().unwrap()
+}
+
+fn determinism_options(
+  seed: u64,
+  clock: DeterministicClock,
+) -> DeterminismOptions {
+  DeterminismOptions {
+    seed,
+    start_time: SystemTime::UNIX_EPOCH
+      + Duration::from_millis(START_TIME_MS as u64),
+    clock,
+    ..Default::default()
+  }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct VirtualRun {
+  start: f64,
+  random: Vec<f64>,
+  events: Vec<(String, f64)>,
+  elapsed: f64,
+  hostname: String,
+}
+
+#[tokio::test]
+async fn virtual_clock_runs_the_same_every_time() {
+  let source = r#"const start = Date.now();
+const events = [];
+setTimeout(() => events.push(["later", Date.now() - start]), 60 * 60 * 1000);
+setTimeout(() => events.push(["sooner", Date.now() - start]), 1000);
+await new Promise((resolve) => setTimeout(resolve, 2 * 60 * 60 * 1000));
+let hostname;
+try {
+  hostname = Deno.hostname();
+} catch (err) {
+  hostname = err.name;
+}
+globalThis.result = {
+  start,
+  random: [Math.random(), Math.random()],
+  events,
+  elapsed: performance.now(),
+  hostname,
+};
+"#;
+  let first: VirtualRun = run_deterministic(
+    source,
+    determinism_options(42, DeterministicClock::Virtual),
+  )
+  .await;
+  assert_eq!(first.start, START_TIME_MS);
+  assert_eq!(
+    first.events,
+    vec![
+      ("sooner".to_string(), 1000.0),
+      ("later".to_string(), 60.0 * 60.0 * 1000.0),
+    ]
+  );
+  assert_eq!(first.elapsed, 2.0 * 60.0 * 60.0 * 1000.0);
+  assert_eq!(first.hostname, "NotSupported");
+  assert_ne!(first.random[0], first.random[1]);
+
+  let second: VirtualRun = run_deterministic(
+    source,
+    determinism_options(42, DeterministicClock::Virtual),
+  )
+  .await;
+  assert_eq!(first, second);
+
+  let other_seed: VirtualRun = run_deterministic(
+    source,
+    determinism_options(7, DeterministicClock::Virtual),
+  )
+  .await;
+  assert_ne!(first.random, other_seed.random);
+}
+
+#[tokio::test]
+async fn frozen_clock_keeps_the_start_time() {
+  let source = r#"const start = Date.now();
+await new Promise((resolve) => setTimeout(resolve, 20));
+globalThis.result = [start, Date.now() - start];
+"#;
+  let result: (f64, f64) = run_deterministic(
+    source,
+    determinism_options(0, DeterministicClock::Frozen),
+  )
+  .await;
+  assert_eq!(result, (START_TIME_MS, 0.0));
+}
+
+#[tokio::test]
+async fn host_controls_the_timers_of_a_manual_clock() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"globalThis.fired = [];
+setTimeout(() => fired.push("timeout"), 1000);
+setInterval(() => fired.push("interval"), 500);
+"#,
+  );
+  let start_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .determinism(DeterminismOptions {
+        start_time,
+        clock: DeterministicClock::Manual,
+        ..Default::default()
+      })
+      .build()
+      .await
+      .unwrap();
+  // the paused timers don't keep the event loop alive
+  assert_eq!(worker.run().await.unwrap(), 0);
+  assert_eq!(
+    worker.get_global::<Vec<String>>("fired").unwrap(),
+    Some(vec![])
+  );
+  assert_eq!(
+    worker.pending_timers().await.unwrap(),
+    vec![
+      PendingTimer {
+        id: 2,
+        due_in: Duration::from_millis(500),
+        repeat: true,
+      },
+      PendingTimer {
+        id: 1,
+        due_in: Duration::from_millis(1000),
+        repeat: false,
+      },
+    ]
+  );
+
+  worker
+    .advance_time(Duration::from_millis(1200))
+    .await
+    .unwrap();
+  assert_eq!(
+    worker.get_global::<Vec<String>>("fired").unwrap(),
+    Some(vec![
+      "interval".to_string(),
+      "timeout".to_string(),
+      "interval".to_string(),
+    ])
+  );
+  assert_eq!(
+    worker.current_time().await.unwrap(),
+    start_time + Duration::from_millis(1200)
+  );
+  assert_eq!(
+    worker.pending_timers().await.unwrap(),
+    vec![PendingTimer {
+      id: 2,
+      due_in: Duration::from_millis(300),
+      repeat: true,
+    }]
+  );
+
+  worker
+    .execute_script_static(
+      "[test.js]",
+      "clearInterval(2); setTimeout(() => fired.push(Date.now()), 10_000);",
+    )
+    .unwrap();
+  worker.resume_timers().await.unwrap();
+  // the resumed timer fires once the event loop runs
+  let mut main_worker = worker.into_main_worker();
+  main_worker.run_event_loop(false).await.unwrap();
+  let last_fired = main_worker
+    .execute_script("[test.js]", ascii_str!("fired.at(-1)").into())
+    .unwrap();
+  let scope = &mut main_worker.js_runtime.handle_scope();
+  let last_fired = v8::Local::new(scope, last_fired);
+  assert_eq!(
+    serde_v8::from_v8::<f64>(scope, last_fired).unwrap(),
+    1_011_200.0
+  );
+}
+
+#[tokio::test]
+async fn clock_of_a_non_deterministic_worker_errors() {
+  let temp_dir = TempDir::new();
+  temp_dir.write("main.ts", "");
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .build()
+      .await
+      .unwrap();
+  assert_eq!(
+      worker.pause_timers().await.unwrap_err().to_string(),
+      "The worker is not deterministic. Set `DeterminismOptions` to control its clock."
+    );
+}
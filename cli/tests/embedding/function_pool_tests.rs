@@ -0,0 +1,125 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use deno::spawn_function_pool;
+use deno::DenoRuntimeBuilder;
+use deno::PermissionFlags;
+use deno::VirtualEnv;
+use deno::WorkerObserver;
+use deno_core::error::AnyError;
+use deno_core::serde_json::json;
+use test_util::TempDir;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn function_pool_isolates_calls() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "handler.ts",
+    "let calls = 0;\nexport function handler() { return ++calls; }\n",
+  );
+  let main_module = temp_dir.path().join("handler.ts").to_string();
+  let pool = spawn_function_pool(
+    move || DenoRuntimeBuilder::new(main_module.clone()).no_config(),
+    "handler",
+    1,
+  )
+  .await
+  .unwrap();
+  for _ in 0..3 {
+    // every call gets a worker that evaluated the module on its own
+    assert_eq!(pool.invoke(vec![]).await.unwrap(), json!(1));
+  }
+}
+
+#[tokio::test]
+async fn function_pool_drains_out_of_order() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "handler.ts",
+    "export async function handler(ms) {\n\
+     \x20 await new Promise((resolve) => setTimeout(resolve, ms));\n\
+     \x20 return ms;\n\
+     }\n",
+  );
+  let main_module = temp_dir.path().join("handler.ts").to_string();
+  let pool = spawn_function_pool(
+    move || DenoRuntimeBuilder::new(main_module.clone()).no_config(),
+    "handler",
+    3,
+  )
+  .await
+  .unwrap();
+  assert_eq!(pool.warm_workers(), 3);
+  // the calls finish in the reverse order of the one they were made in
+  let (slow, fast, medium) = tokio::join!(
+    pool.invoke(vec![json!(200)]),
+    pool.invoke(vec![json!(0)]),
+    pool.invoke(vec![json!(100)]),
+  );
+  assert_eq!(slow.unwrap(), json!(200));
+  assert_eq!(fast.unwrap(), json!(0));
+  assert_eq!(medium.unwrap(), json!(100));
+  // the pool keeps serving calls with the workers replacing the used ones
+  assert_eq!(pool.invoke(vec![json!(0)]).await.unwrap(), json!(0));
+  drop(pool);
+}
+
+struct ExitErrors(mpsc::UnboundedSender<String>);
+
+impl WorkerObserver for ExitErrors {
+  fn on_exit(&self, result: Result<i32, &AnyError>) {
+    if let Err(err) = result {
+      let _ = self.0.send(err.to_string());
+    }
+  }
+}
+
+#[tokio::test]
+async fn function_pool_returns_refill_errors() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "handler.ts",
+    "if (Deno.env.get(\"FAIL\")) throw new Error(\"boom\");\n\
+     export function handler() { return 1; }\n",
+  );
+  let main_module = temp_dir.path().join("handler.ts").to_string();
+  let env = VirtualEnv::new(HashMap::new());
+  let (errors_tx, mut errors_rx) = mpsc::unbounded_channel();
+  let pool = spawn_function_pool(
+    {
+      let env = env.clone();
+      move || {
+        DenoRuntimeBuilder::new(main_module.clone())
+          .no_config()
+          .permissions(PermissionFlags {
+            allow_env: Some(vec!["FAIL".to_string()]),
+            ..Default::default()
+          })
+          .env(env.clone())
+          .observer(ExitErrors(errors_tx.clone()))
+      }
+    },
+    "handler",
+    1,
+  )
+  .await
+  .unwrap();
+  env.set("FAIL", "1");
+  assert_eq!(pool.invoke(vec![]).await.unwrap(), json!(1));
+  // the worker replacing the used one throws while it's created
+  let error = tokio::time::timeout(Duration::from_secs(10), errors_rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(error.contains("boom"), "{error}");
+  env.remove("FAIL");
+  assert_eq!(pool.warm_workers(), 0);
+  // the worker that failed fails the next call, even though creating one
+  // now would succeed
+  let err = pool.invoke(vec![]).await.unwrap_err();
+  assert!(err.to_string().contains("boom"), "{err}");
+  assert_eq!(pool.invoke(vec![]).await.unwrap(), json!(1));
+}
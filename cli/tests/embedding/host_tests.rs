@@ -0,0 +1,48 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+
+use deno::DenoRuntimeBuilder;
+use deno_core::serde_json;
+use test_util::TempDir;
+
+#[tokio::test]
+async fn host_messages_wait_for_a_listener() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"const off = Deno.host.onMessage(() => {
+  throw new Error("unexpected message");
+});
+await new Promise((resolve) => setTimeout(resolve, 10));
+off();
+Deno.host.send("unsubscribed");
+await new Promise((resolve) => setTimeout(resolve, 100));
+Deno.host.send(await new Promise((resolve) => Deno.host.onMessage(resolve)));
+"#,
+  );
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .build()
+      .await
+      .unwrap();
+  let mut channel = worker.take_host_channel().unwrap();
+  let host = async {
+    assert_eq!(
+      channel.receiver.recv().await,
+      Some(serde_json::json!("unsubscribed"))
+    );
+    // received by the pending receive of the removed listener
+    channel.sender.send(serde_json::json!("queued")).unwrap();
+    channel.receiver.recv().await
+  };
+  let (exit_code, echoed) =
+    tokio::time::timeout(Duration::from_secs(10), async {
+      tokio::join!(worker.run(), host)
+    })
+    .await
+    .unwrap();
+  assert_eq!(exit_code.unwrap(), 0);
+  assert_eq!(echoed, Some(serde_json::json!("queued")));
+}
@@ -0,0 +1,84 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+// the test spawns `echo` and `rm`
+#![cfg(unix)]
+
+use std::sync::Arc;
+
+use deno::DenoRuntimeBuilder;
+use deno::PermissionFlags;
+use deno::SpawnInterceptor;
+use deno::SpawnRequest;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use test_util::TempDir;
+
+#[derive(Default, Clone)]
+struct RecordingSpawnInterceptor(Arc<std::sync::Mutex<Vec<(String, String)>>>);
+
+impl SpawnInterceptor for RecordingSpawnInterceptor {
+  fn intercept_spawn(
+    &self,
+    mut request: SpawnRequest,
+    api_name: &str,
+  ) -> Result<SpawnRequest, AnyError> {
+    let name = request.cmd.file_name().unwrap().to_string_lossy();
+    let name = name.to_string();
+    self
+      .0
+      .lock()
+      .unwrap()
+      .push((name.clone(), api_name.to_string()));
+    if name == "rm" {
+      bail!("Spawning rm is not allowed.");
+    }
+    request.args = vec!["rewritten".to_string()];
+    Ok(request)
+  }
+}
+
+#[tokio::test]
+async fn spawn_interceptor_rewrites_and_denies_subprocesses() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"const output = await new Deno.Command("echo", { args: ["original"] })
+  .output();
+let denied;
+try {
+  new Deno.Command("rm", { args: [import.meta.filename] }).outputSync();
+} catch (err) {
+  denied = `${err.name}: ${err.message}`;
+}
+globalThis.result = [new TextDecoder().decode(output.stdout), denied];
+"#,
+  );
+  let interceptor = RecordingSpawnInterceptor::default();
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .permissions(PermissionFlags {
+        allow_run: Some(vec![]),
+        ..Default::default()
+      })
+      .spawn_interceptor(interceptor.clone())
+      .build()
+      .await
+      .unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  assert_eq!(
+    worker.get_global::<(String, String)>("result").unwrap(),
+    Some((
+      "rewritten\n".to_string(),
+      "PermissionDenied: Spawning rm is not allowed.".to_string(),
+    ))
+  );
+  assert_eq!(
+    *interceptor.0.lock().unwrap(),
+    vec![
+      ("echo".to_string(), "Deno.Command().output()".to_string()),
+      ("rm".to_string(), "Deno.Command().outputSync()".to_string()),
+    ]
+  );
+  assert!(temp_dir.path().join("main.ts").exists());
+}
@@ -0,0 +1,94 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use deno::DenoRuntimeBuilder;
+use deno::LifecycleScriptsDecision;
+use deno::LifecycleScriptsOutcome;
+use deno::LifecycleScriptsPackage;
+use deno::LifecycleScriptsPolicy;
+use deno::LifecycleScriptsReport;
+use deno::NpmRegistriesConfig;
+use deno::NpmRegistry;
+use deno_core::url::Url;
+use test_util::TempDir;
+
+#[derive(Clone, Default)]
+struct SandboxScripts(Arc<Mutex<Option<LifecycleScriptsReport>>>);
+
+impl LifecycleScriptsPolicy for SandboxScripts {
+  fn decide(
+    &self,
+    _package: &LifecycleScriptsPackage,
+  ) -> LifecycleScriptsDecision {
+    LifecycleScriptsDecision::Sandbox
+  }
+
+  fn report(&self, report: &LifecycleScriptsReport) {
+    *self.0.lock().unwrap() = Some(report.clone());
+  }
+
+  fn deno_executable(&self) -> Option<PathBuf> {
+    Some(test_util::deno_exe_path().to_path_buf())
+  }
+}
+
+/// Installs `package` into the `node_modules` directory of a program
+/// importing it, returning the report of its lifecycle scripts.
+async fn install(temp_dir: &TempDir, package: &str) -> LifecycleScriptsReport {
+  temp_dir.write("deno.json", r#"{ "nodeModulesDir": "auto" }"#);
+  temp_dir.write("main.ts", format!("import \"npm:{package}\";\n"));
+  let policy = SandboxScripts::default();
+  let registries = NpmRegistriesConfig {
+    default_registry: Some(NpmRegistry::new(
+      Url::parse(&test_util::npm_registry_url()).unwrap(),
+    )),
+    ..Default::default()
+  };
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .config_file(temp_dir.path().join("deno.json").to_string())
+      .npm_registries(registries)
+      .lifecycle_scripts_policy(policy.clone())
+      .build()
+      .await
+      .unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  let report = policy.0.lock().unwrap().take();
+  report.unwrap()
+}
+
+#[tokio::test]
+async fn sandboxed_lifecycle_scripts_cannot_reach_the_network() {
+  let _server = test_util::http_server();
+  let temp_dir = TempDir::new();
+  let report =
+    install(&temp_dir, "@denotest/sandboxed-lifecycle-script@1.0.0").await;
+  assert_eq!(report.packages.len(), 1);
+  let entry = &report.packages[0];
+  assert_eq!(entry.package.scripts, vec!["postinstall".to_string()]);
+  assert_eq!(entry.decision, LifecycleScriptsDecision::Sandbox);
+  assert_eq!(entry.outcome, LifecycleScriptsOutcome::Succeeded);
+  assert_eq!(
+    temp_dir.read_to_string(
+      "node_modules/@denotest/sandboxed-lifecycle-script/network.txt"
+    ),
+    "NotCapable"
+  );
+}
+
+#[tokio::test]
+async fn sandbox_refuses_scripts_using_the_shell() {
+  let _server = test_util::http_server();
+  let temp_dir = TempDir::new();
+  let report = install(&temp_dir, "@denotest/say-hello-on-install@1.0.0").await;
+  assert_eq!(report.packages.len(), 1);
+  assert_eq!(
+    report.packages[0].outcome,
+    LifecycleScriptsOutcome::Refused {
+      script: "install".to_string(),
+    }
+  );
+}
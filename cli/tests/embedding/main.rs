@@ -0,0 +1,25 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+// Tests of the library target, which run programs through the public
+// embedding API instead of the `deno` executable.
+
+#[path = "coverage_tests.rs"]
+mod coverage;
+#[path = "determinism_tests.rs"]
+mod determinism;
+#[path = "function_pool_tests.rs"]
+mod function_pool;
+#[path = "host_tests.rs"]
+mod host;
+#[path = "interceptor_tests.rs"]
+mod interceptor;
+#[path = "lifecycle_scripts_tests.rs"]
+mod lifecycle_scripts;
+#[path = "remote_eval_tests.rs"]
+mod remote_eval;
+#[path = "unstable_tests.rs"]
+mod unstable;
+#[path = "wasi_tests.rs"]
+mod wasi;
+#[path = "worker_tests.rs"]
+mod worker;
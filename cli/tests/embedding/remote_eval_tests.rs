@@ -0,0 +1,162 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use bytes::Bytes;
+use deno::DenoRuntimeBuilder;
+use deno::InspectMode;
+use deno::InspectorController;
+use deno::RemoteEvalOptions;
+use deno::RemoteEvalServer;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use fastwebsockets::Frame;
+use fastwebsockets::OpCode;
+use fastwebsockets::WebSocket;
+use fastwebsockets::WebSocketError;
+use hyper::upgrade::Upgraded;
+use hyper_util::rt::TokioIo;
+use test_util::TempDir;
+use tokio::net::TcpStream;
+
+struct SpawnExecutor;
+
+impl<Fut> hyper::rt::Executor<Fut> for SpawnExecutor
+where
+  Fut: std::future::Future + Send + 'static,
+  Fut::Output: Send + 'static,
+{
+  fn execute(&self, fut: Fut) {
+    deno_core::unsync::spawn(fut);
+  }
+}
+
+async fn connect(
+  addr: SocketAddr,
+  path: &str,
+  authorization: Option<&str>,
+) -> Result<WebSocket<TokioIo<Upgraded>>, WebSocketError> {
+  let stream = TcpStream::connect(addr).await.unwrap();
+  let mut request = hyper::Request::builder()
+    .method("GET")
+    .uri(path)
+    .header("Host", addr.to_string())
+    .header(hyper::header::UPGRADE, "websocket")
+    .header(hyper::header::CONNECTION, "Upgrade")
+    .header(
+      "Sec-WebSocket-Key",
+      fastwebsockets::handshake::generate_key(),
+    )
+    .header("Sec-WebSocket-Version", "13");
+  if let Some(authorization) = authorization {
+    request = request.header(hyper::header::AUTHORIZATION, authorization);
+  }
+  let request = request.body(http_body_util::Empty::<Bytes>::new()).unwrap();
+  let (websocket, _) =
+    fastwebsockets::handshake::client(&SpawnExecutor, request, stream).await?;
+  Ok(websocket)
+}
+
+async fn eval(
+  websocket: &mut WebSocket<TokioIo<Upgraded>>,
+  id: u64,
+  expression: &str,
+) -> Vec<serde_json::Value> {
+  let request = json!({ "id": id, "expression": expression });
+  websocket
+    .write_frame(Frame::text(request.to_string().into_bytes().into()))
+    .await
+    .unwrap();
+  // console messages come before the result
+  let mut messages = Vec::new();
+  loop {
+    let frame = websocket.read_frame().await.unwrap();
+    assert_eq!(frame.opcode, OpCode::Text);
+    let message: serde_json::Value =
+      serde_json::from_slice(&frame.payload).unwrap();
+    let is_result = message.get("id").is_some();
+    messages.push(message);
+    if is_result {
+      return messages;
+    }
+  }
+}
+
+#[tokio::test]
+async fn remote_eval_authenticates_and_evaluates() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    "globalThis.state = { ready: true };\n\
+     await new Promise((resolve) => globalThis.stop = resolve);\n",
+  );
+  let controller = InspectorController::new();
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .inspector(controller.clone(), InspectMode::Run)
+      .build()
+      .await
+      .unwrap();
+  let server =
+    RemoteEvalServer::start(controller, RemoteEvalOptions::new("s3cret"))
+      .unwrap();
+  let addr = server.local_addr();
+  assert!(addr.ip().is_loopback());
+
+  let client = async {
+    for (path, authorization) in [
+      ("/", None),
+      ("/", Some("Bearer s3cre")),
+      ("/?token=s3cret", None),
+    ] {
+      assert!(matches!(
+        connect(addr, path, authorization).await,
+        Err(WebSocketError::InvalidStatusCode(401))
+      ));
+    }
+
+    let mut websocket =
+      connect(addr, "/", Some("Bearer s3cret")).await.unwrap();
+    assert_eq!(
+      eval(&mut websocket, 1, "console.log('tick', 42); state.ready").await,
+      vec![
+        json!({ "console": { "level": "log", "text": "tick 42" } }),
+        json!({ "id": 1, "result": "true" }),
+      ]
+    );
+    assert_eq!(
+      eval(&mut websocket, 2, "foo").await,
+      vec![json!({
+        "id": 2,
+        "error": "Uncaught ReferenceError: foo is not defined",
+      })]
+    );
+    assert_eq!(
+      eval(&mut websocket, 3, "stop()").await,
+      vec![json!({ "id": 3, "result": "undefined" })]
+    );
+  };
+  let (exit_code, ()) = tokio::time::timeout(Duration::from_secs(30), async {
+    tokio::join!(worker.run(), client)
+  })
+  .await
+  .unwrap();
+  assert_eq!(exit_code.unwrap(), 0);
+}
+
+#[test]
+fn remote_eval_only_binds_to_loopback_addresses() {
+  let controller = InspectorController::new();
+  let err = RemoteEvalServer::start(
+    controller,
+    RemoteEvalOptions {
+      addr: "0.0.0.0:0".parse().unwrap(),
+      token: "s3cret".to_string(),
+    },
+  )
+  .err()
+  .unwrap();
+  assert!(err.to_string().contains("loopback"), "{err}");
+}
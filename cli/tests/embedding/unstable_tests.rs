@@ -0,0 +1,24 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno::DenoRunError;
+use deno::DenoRuntimeBuilder;
+use test_util::TempDir;
+
+#[tokio::test]
+async fn unstable_apis_fail_the_run_without_their_flag() {
+  let temp_dir = TempDir::new();
+  temp_dir.write("main.ts", "await Deno.openKv(\":memory:\");\n");
+  let err =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .run()
+      .await
+      .unwrap_err();
+  assert_eq!(err.exit_code(), 70);
+  let err = match err {
+    DenoRunError::UnstableApi(err) => err,
+    err => panic!("expected an unstable API error, got {err:?}"),
+  };
+  assert_eq!(err.feature, "kv");
+  assert_eq!(err.api_name, "Deno.openKv");
+}
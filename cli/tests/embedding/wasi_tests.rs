@@ -0,0 +1,39 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+
+use deno::ChannelWriter;
+use deno::DenoRuntimeBuilder;
+use deno::WasiOptions;
+use test_util::TempDir;
+
+#[tokio::test]
+async fn components_write_to_the_stdout_of_the_worker() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    "import run from \"./hello_component.wasm\";\n\
+     globalThis.code = await run();\n",
+  );
+  std::fs::copy(
+    test_util::testdata_path().join("wasm/hello_component.wasm"),
+    temp_dir.path().join("hello_component.wasm"),
+  )
+  .unwrap();
+  let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .stdout(ChannelWriter::new(sender))
+      .wasi_components(WasiOptions::default())
+      .build()
+      .await
+      .unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  assert_eq!(worker.get_global::<i32>("code").unwrap(), Some(0));
+  let output = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(output, b"hello from a component\n");
+}
@@ -0,0 +1,127 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno::Bytes;
+use deno::DenoRuntimeBuilder;
+use deno::WorkerCreationPolicy;
+use deno::WorkerCreationRequest;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use test_util::TempDir;
+
+#[tokio::test]
+async fn main_module_sees_globals_set_before_running() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    "globalThis.greeting = `${config.greeting}, ${config.name}!`;\n",
+  );
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .build()
+      .await
+      .unwrap();
+  worker
+    .set_global(
+      "config",
+      &serde_json::json!({ "greeting": "Hello", "name": "Deno" }),
+    )
+    .unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  assert_eq!(
+    worker.get_global::<String>("greeting").unwrap().as_deref(),
+    Some("Hello, Deno!")
+  );
+  assert!(worker.get_global::<u32>("greeting").is_err());
+}
+
+#[tokio::test]
+async fn main_module_exchanges_buffers_with_the_host() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"const input = new Uint8Array(globalThis.input);
+globalThis.output = input.subarray(1, 3);
+globalThis.text = "not a buffer";
+"#,
+  );
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .build()
+      .await
+      .unwrap();
+  // shared with `data`, so it's copied
+  let data = Bytes::from(vec![1, 2, 3, 4]);
+  worker.transfer_buffer("input", data.clone()).unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  assert_eq!(data.as_ref(), &[1, 2, 3, 4]);
+  assert_eq!(
+    worker.take_buffer("output").unwrap().as_deref(),
+    Some(&[2, 3][..])
+  );
+  assert_eq!(
+    worker.take_buffer("text").unwrap_err().to_string(),
+    "The global \"text\" is not an ArrayBuffer."
+  );
+}
+
+struct OneEchoWorker;
+
+impl WorkerCreationPolicy for OneEchoWorker {
+  fn check_worker_creation(
+    &self,
+    mut request: WorkerCreationRequest,
+  ) -> Result<WorkerCreationRequest, AnyError> {
+    if request.running_workers >= 1 {
+      bail!("At most 1 worker may run at the same time.");
+    }
+    if request.specifier.as_str() == "host:echo.js" {
+      request.name = "echo".to_string();
+      request.module_source = Some(
+        "self.onmessage = (e) => self.postMessage(`${self.name}: ${e.data}`);"
+          .to_string(),
+      );
+    }
+    Ok(request)
+  }
+}
+
+#[tokio::test]
+async fn worker_creation_policy_serves_and_denies_workers() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"const worker = new Worker("host:echo.js", { type: "module" });
+const reply = await new Promise((resolve) => {
+  worker.onmessage = (e) => resolve(e.data);
+  worker.postMessage("hello");
+});
+let denied;
+try {
+  new Worker("host:echo.js", { type: "module" });
+} catch (err) {
+  denied = `${err.name}: ${err.message}`;
+}
+worker.terminate();
+globalThis.result = [reply, denied];
+"#,
+  );
+  let mut worker =
+    DenoRuntimeBuilder::new(temp_dir.path().join("main.ts").to_string())
+      .no_config()
+      .worker_creation_policy(OneEchoWorker)
+      .build()
+      .await
+      .unwrap();
+  assert_eq!(worker.run().await.unwrap(), 0);
+  assert_eq!(
+    worker.get_global::<(String, String)>("result").unwrap(),
+    Some((
+      "echo: hello".to_string(),
+      "PermissionDenied: At most 1 worker may run at the same time."
+        .to_string(),
+    ))
+  );
+}
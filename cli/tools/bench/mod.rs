@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::BenchFlags;
+use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::colors;
 use crate::display::write_json_to_stdout;
@@ -48,9 +49,15 @@ use tokio::sync::mpsc::UnboundedSender;
 mod mitata;
 mod reporters;
 
-use reporters::BenchReporter;
 use reporters::ConsoleReporter;
 use reporters::JsonReporter;
+use reporters::StructuredBenchReporter;
+
+pub use reporters::BenchFailureReport;
+pub use reporters::BenchMeasurement;
+pub use reporters::BenchMeasurementStats;
+pub use reporters::BenchReporter;
+pub use reporters::BenchRunReport;
 
 #[derive(Debug, Clone)]
 struct BenchSpecifierOptions {
@@ -86,7 +93,7 @@ pub enum BenchResult {
   Failed(Box<JsError>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct BenchReport {
   pub total: usize,
   pub failed: usize,
@@ -119,6 +126,7 @@ pub struct BenchStats {
   pub p999: f64,
   pub high_precision: bool,
   pub used_explicit_timers: bool,
+  pub stddev: f64,
 }
 
 impl BenchReport {
@@ -133,13 +141,14 @@ impl BenchReport {
 }
 
 fn create_reporter(
-  show_output: bool,
-  json: bool,
+  options: &BenchSpecifierOptions,
 ) -> Box<dyn BenchReporter + Send> {
-  if json {
+  if options.json {
     return Box::new(JsonReporter::new());
   }
-  Box::new(ConsoleReporter::new(show_output))
+  Box::new(ConsoleReporter::new(
+    options.log_level != Some(Level::Error),
+  ))
 }
 
 /// Run a single specifier as an executable bench module.
@@ -268,9 +277,9 @@ async fn bench_specifiers(
   permissions_desc_parser: &Arc<RuntimePermissionDescriptorParser>,
   specifiers: Vec<ModuleSpecifier>,
   options: BenchSpecifierOptions,
-) -> Result<(), AnyError> {
+  mut reporter: Box<dyn BenchReporter + Send>,
+) -> Result<Result<(), AnyError>, AnyError> {
   let (sender, mut receiver) = unbounded_channel::<BenchEvent>();
-  let log_level = options.log_level;
   let option_for_handles = options.clone();
 
   let join_handles = specifiers.into_iter().map(move |specifier| {
@@ -301,8 +310,6 @@ async fn bench_specifiers(
     spawn(async move {
       let mut used_only = false;
       let mut report = BenchReport::new();
-      let mut reporter =
-        create_reporter(log_level != Some(Level::Error), options.json);
       let mut benches = IndexMap::new();
 
       while let Some(event) = receiver.recv().await {
@@ -374,9 +381,7 @@ async fn bench_specifiers(
     join_result??;
   }
 
-  result??;
-
-  Ok(())
+  Ok(result?)
 }
 
 /// Checks if the path has a basename and extension Deno supports for benches.
@@ -408,6 +413,63 @@ pub async fn run_benchmarks(
   flags: Arc<Flags>,
   bench_flags: BenchFlags,
 ) -> Result<(), AnyError> {
+  run_benchmarks_with_reporter(flags, bench_flags, create_reporter).await?
+}
+
+/// Runs the benchmarks found in `paths` like `deno bench` does, but collects
+/// the measurements into a [`BenchRunReport`] instead of printing them.
+///
+/// Failing benchmarks don't make this return an error, they are listed in
+/// the failures of the report.
+pub async fn run_benchmarks_for_embedder(
+  mut flags: Arc<Flags>,
+  paths: Vec<String>,
+) -> Result<BenchRunReport, AnyError> {
+  let bench_flags = set_embedder_bench_flags(&mut flags, paths);
+  let (sender, receiver) = tokio::sync::oneshot::channel();
+  // the outcome of the benchmarks is part of the report
+  let _ = run_benchmarks_with_reporter(flags, bench_flags, |_| {
+    Box::new(StructuredBenchReporter::new(sender))
+  })
+  .await?;
+
+  // no report is sent when the benchmarks were only type checked
+  Ok(receiver.await.unwrap_or_default())
+}
+
+/// Runs the benchmarks found in `paths`, passing the events of the run to
+/// `reporter`. Errors when a benchmark failed, like `deno bench`.
+pub async fn run_benchmarks_with_custom_reporter(
+  mut flags: Arc<Flags>,
+  paths: Vec<String>,
+  reporter: Box<dyn BenchReporter + Send>,
+) -> Result<(), AnyError> {
+  let bench_flags = set_embedder_bench_flags(&mut flags, paths);
+  run_benchmarks_with_reporter(flags, bench_flags, |_| reporter).await?
+}
+
+fn set_embedder_bench_flags(
+  flags: &mut Arc<Flags>,
+  paths: Vec<String>,
+) -> BenchFlags {
+  let mut bench_flags = match &flags.subcommand {
+    DenoSubcommand::Bench(bench_flags) => bench_flags.clone(),
+    _ => BenchFlags::default(),
+  };
+  bench_flags.files.include = paths;
+  bench_flags.json = false;
+  bench_flags.watch = None;
+  Arc::make_mut(flags).subcommand = DenoSubcommand::Bench(bench_flags.clone());
+  bench_flags
+}
+
+async fn run_benchmarks_with_reporter(
+  flags: Arc<Flags>,
+  bench_flags: BenchFlags,
+  create_reporter: impl FnOnce(
+    &BenchSpecifierOptions,
+  ) -> Box<dyn BenchReporter + Send>,
+) -> Result<Result<(), AnyError>, AnyError> {
   let factory = CliFactory::from_flags(flags);
   let cli_options = factory.cli_options()?;
   let workspace_bench_options =
@@ -447,26 +509,27 @@ pub async fn run_benchmarks(
     .await?;
 
   if workspace_bench_options.no_run {
-    return Ok(());
+    return Ok(Ok(()));
   }
 
   let log_level = cli_options.log_level();
   let worker_factory =
     Arc::new(factory.create_cli_main_worker_factory().await?);
+  let options = BenchSpecifierOptions {
+    filter: TestFilter::from_flag(&workspace_bench_options.filter),
+    json: workspace_bench_options.json,
+    log_level,
+  };
+  let reporter = create_reporter(&options);
   bench_specifiers(
     worker_factory,
     &permissions,
     &permission_desc_parser,
     specifiers,
-    BenchSpecifierOptions {
-      filter: TestFilter::from_flag(&workspace_bench_options.filter),
-      json: workspace_bench_options.json,
-      log_level,
-    },
+    options,
+    reporter,
   )
-  .await?;
-
-  Ok(())
+  .await
 }
 
 // TODO(bartlomieju): heavy duplication of code with `cli/tools/test.rs`
@@ -580,18 +643,21 @@ pub async fn run_benchmarks_with_watch(
         }
 
         let log_level = cli_options.log_level();
+        let options = BenchSpecifierOptions {
+          filter: TestFilter::from_flag(&workspace_bench_options.filter),
+          json: workspace_bench_options.json,
+          log_level,
+        };
+        let reporter = create_reporter(&options);
         bench_specifiers(
           worker_factory,
           &permissions,
           &permission_desc_parser,
           specifiers,
-          BenchSpecifierOptions {
-            filter: TestFilter::from_flag(&workspace_bench_options.filter),
-            json: workspace_bench_options.json,
-            log_level,
-          },
+          options,
+          reporter,
         )
-        .await?;
+        .await??;
 
         Ok(())
       })
@@ -1,12 +1,15 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use serde::Serialize;
+use tokio::sync::oneshot;
 
 use crate::tools::test::TestFailureFormatOptions;
 use crate::version;
 
 use super::*;
 
+/// Receives the events of a benchmark run. Implement it to consume the
+/// results of [`super::run_benchmarks_with_custom_reporter`].
 pub trait BenchReporter {
   fn report_group_summary(&mut self);
   fn report_plan(&mut self, plan: &BenchPlan);
@@ -52,6 +55,175 @@ struct JsonReporterBench {
   results: Vec<BenchResult>,
 }
 
+/// Results of a benchmark run, in a form that can be serialized for
+/// performance tracking tools.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchRunReport {
+  pub runtime: String,
+  pub cpu: String,
+  pub benches: Vec<BenchMeasurement>,
+  pub failures: Vec<BenchFailureReport>,
+  /// Whether a benchmark used the `only` option, which fails the run.
+  pub used_only: bool,
+}
+
+impl Default for BenchRunReport {
+  fn default() -> Self {
+    let output = JsonReporterOutput::default();
+    Self {
+      runtime: output.runtime,
+      cpu: output.cpu,
+      benches: Vec::new(),
+      failures: Vec::new(),
+      used_only: false,
+    }
+  }
+}
+
+impl BenchRunReport {
+  pub fn success(&self) -> bool {
+    self.failures.is_empty() && !self.used_only
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchMeasurement {
+  pub origin: String,
+  pub group: Option<String>,
+  pub name: String,
+  pub baseline: bool,
+  pub stats: BenchMeasurementStats,
+}
+
+/// Statistics of a benchmark. Durations are in nanoseconds.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchMeasurementStats {
+  pub iterations: u64,
+  pub mean: f64,
+  pub stddev: f64,
+  pub min: f64,
+  pub max: f64,
+  pub p75: f64,
+  pub p99: f64,
+  pub p995: f64,
+  pub p999: f64,
+  /// Whether each iteration was timed on its own, rather than in batches for
+  /// very fast benchmarks.
+  pub high_precision: bool,
+  pub used_explicit_timers: bool,
+}
+
+impl From<&BenchStats> for BenchMeasurementStats {
+  fn from(stats: &BenchStats) -> Self {
+    Self {
+      iterations: stats.n,
+      mean: stats.avg,
+      stddev: stats.stddev,
+      min: stats.min,
+      max: stats.max,
+      p75: stats.p75,
+      p99: stats.p99,
+      p995: stats.p995,
+      p999: stats.p999,
+      high_precision: stats.high_precision,
+      used_explicit_timers: stats.used_explicit_timers,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchFailureReport {
+  pub origin: String,
+  /// `None` for errors thrown outside of any benchmark, eg. while loading
+  /// the module.
+  pub name: Option<String>,
+  pub message: String,
+  /// The full error, including the stack trace, without colors.
+  pub details: String,
+}
+
+impl BenchFailureReport {
+  fn new(origin: &str, name: Option<&str>, error: &JsError) -> Self {
+    let details =
+      format_test_error(error, &TestFailureFormatOptions::default());
+    Self {
+      origin: origin.to_string(),
+      name: name.map(ToString::to_string),
+      message: error.exception_message.clone(),
+      details: console_static_text::ansi::strip_ansi_codes(&details)
+        .into_owned(),
+    }
+  }
+}
+
+/// Collects the bench events into a [`BenchRunReport`] instead of printing
+/// them. The report is sent once the run ends.
+pub struct StructuredBenchReporter {
+  report: BenchRunReport,
+  sender: Option<oneshot::Sender<BenchRunReport>>,
+}
+
+impl StructuredBenchReporter {
+  pub fn new(sender: oneshot::Sender<BenchRunReport>) -> Self {
+    Self {
+      report: BenchRunReport::default(),
+      sender: Some(sender),
+    }
+  }
+}
+
+impl BenchReporter for StructuredBenchReporter {
+  fn report_group_summary(&mut self) {}
+
+  fn report_plan(&mut self, plan: &BenchPlan) {
+    if plan.used_only {
+      self.report.used_only = true;
+    }
+  }
+
+  fn report_end(&mut self, _report: &BenchReport) {
+    if let Some(sender) = self.sender.take() {
+      let _ = sender.send(std::mem::take(&mut self.report));
+    }
+  }
+
+  fn report_register(&mut self, _desc: &BenchDescription) {}
+
+  fn report_wait(&mut self, _desc: &BenchDescription) {}
+
+  fn report_output(&mut self, _output: &str) {}
+
+  fn report_result(&mut self, desc: &BenchDescription, result: &BenchResult) {
+    if desc.warmup {
+      return;
+    }
+
+    match result {
+      BenchResult::Ok(stats) => self.report.benches.push(BenchMeasurement {
+        origin: desc.origin.clone(),
+        group: desc.group.clone(),
+        name: desc.name.clone(),
+        baseline: desc.baseline,
+        stats: stats.into(),
+      }),
+      BenchResult::Failed(error) => self.report.failures.push(
+        BenchFailureReport::new(&desc.origin, Some(&desc.name), error),
+      ),
+    }
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: Box<JsError>) {
+    self
+      .report
+      .failures
+      .push(BenchFailureReport::new(origin, None, &error));
+  }
+}
+
 #[derive(Debug, Serialize)]
 pub struct JsonReporter(JsonReporterOutput);
 
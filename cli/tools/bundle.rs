@@ -0,0 +1,397 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Bundles a program into a single ES module with swc's bundler.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use deno_ast::swc;
+use deno_ast::swc::ast;
+use deno_ast::swc::common::FileName;
+use deno_ast::ModuleKind;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_graph::GraphKind;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+
+use crate::args::Flags;
+use crate::factory::CliFactory;
+
+/// Where to put the source map of a bundle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BundleSourceMap {
+  #[default]
+  None,
+  /// Appended to the code as a `sourceMappingURL` data url.
+  Inline,
+  /// Returned in [`BundleOutput::source_map`].
+  Separate,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BundleOptions {
+  /// Removes whitespace from the output. Identifiers are not mangled.
+  pub minify: bool,
+  pub source_map: BundleSourceMap,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleOutput {
+  pub code: String,
+  /// The source map, with [`BundleSourceMap::Separate`]. It maps to the
+  /// JavaScript emitted for each module.
+  pub source_map: Option<String>,
+}
+
+/// Bundles `entrypoint` and the modules it imports into a single ES module.
+///
+/// TypeScript and JSX are transpiled with the compiler options resolved
+/// from `flags`. npm packages, `node:` built-ins and external modules are
+/// left as imports in the bundle.
+pub async fn bundle(
+  flags: Arc<Flags>,
+  entrypoint: String,
+  options: BundleOptions,
+) -> Result<BundleOutput, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let graph = factory
+    .create_graph_for_embedder(GraphKind::CodeOnly, &[entrypoint])
+    .await?;
+
+  let emitter = factory.emitter()?;
+  let mut sources = HashMap::new();
+  for module in graph.modules() {
+    let code = match module {
+      Module::Js(module) if module.media_type.is_emittable() => {
+        emitter
+          .emit_parsed_source(
+            &module.specifier,
+            module.media_type,
+            ModuleKind::Esm,
+            &module.source,
+          )
+          .await?
+      }
+      Module::Js(module) => module.source.to_string(),
+      Module::Json(module) => format!("export default {};", module.source),
+      Module::Wasm(module) => {
+        bail!("Wasm modules can't be bundled: {}", module.specifier)
+      }
+      Module::Npm(_) | Module::Node(_) | Module::External(_) => continue,
+    };
+    sources.insert(module.specifier().clone(), code);
+  }
+
+  bundle_graph(&graph, &sources, &options)
+}
+
+fn bundle_graph(
+  graph: &ModuleGraph,
+  sources: &HashMap<ModuleSpecifier, String>,
+  options: &BundleOptions,
+) -> Result<BundleOutput, AnyError> {
+  let Some(root) = graph.roots.first() else {
+    bail!("Nothing to bundle.");
+  };
+  let globals = swc::common::Globals::new();
+  swc::common::GLOBALS.set(&globals, || {
+    let cm = Rc::new(swc::common::SourceMap::new(
+      swc::common::FilePathMapping::empty(),
+    ));
+    let loader = BundleLoader {
+      cm: cm.clone(),
+      sources,
+    };
+    let config = swc::bundler::Config {
+      module: swc::bundler::ModuleType::Es,
+      external_modules: external_imports(graph),
+      ..Default::default()
+    };
+    let mut bundler = swc::bundler::Bundler::new(
+      &globals,
+      cm.clone(),
+      loader,
+      BundleResolver(graph),
+      config,
+      Box::new(BundleHook),
+    );
+    let entries =
+      HashMap::from([("bundle".to_string(), FileName::Url(root.clone()))]);
+    let output = bundler
+      .bundle(entries)
+      .context("Unable to output during bundling.")?;
+
+    let mut buf = Vec::new();
+    let mut src_map_buf = Vec::new();
+    {
+      let mut wr: Box<dyn swc::codegen::text_writer::WriteJs> =
+        Box::new(swc::codegen::text_writer::JsWriter::new(
+          cm.clone(),
+          "\n",
+          &mut buf,
+          Some(&mut src_map_buf),
+        ));
+      if options.minify {
+        wr = Box::new(swc::codegen::text_writer::omit_trailing_semi(wr));
+      }
+      let mut emitter = swc::codegen::Emitter {
+        cfg: swc::codegen::Config::default().with_minify(options.minify),
+        cm: cm.clone(),
+        comments: None,
+        wr,
+      };
+      emitter
+        .emit_module(&output[0].module)
+        .context("Unable to emit during bundling.")?;
+    }
+    let mut code =
+      String::from_utf8(buf).context("Emitted code is an invalid string.")?;
+
+    let source_map = match options.source_map {
+      BundleSourceMap::None => None,
+      BundleSourceMap::Inline | BundleSourceMap::Separate => {
+        let mut map_buf = Vec::new();
+        cm.build_source_map(&src_map_buf)
+          .to_writer(&mut map_buf)
+          .context("Unable to write the source map.")?;
+        Some(map_buf)
+      }
+    };
+    let source_map = match (options.source_map, source_map) {
+      (BundleSourceMap::Inline, Some(map)) => {
+        code.push_str("//# sourceMappingURL=data:application/json;base64,");
+        code.push_str(&BASE64_STANDARD.encode(map));
+        None
+      }
+      (_, map) => map
+        .map(String::from_utf8)
+        .transpose()
+        .context("Source map is an invalid string.")?,
+    };
+
+    Ok(BundleOutput { code, source_map })
+  })
+}
+
+/// Import specifiers, as written, of modules the bundle keeps importing.
+fn external_imports(graph: &ModuleGraph) -> Vec<swc::atoms::Atom> {
+  let mut externals = Vec::new();
+  for module in graph.modules() {
+    let Module::Js(module) = module else {
+      continue;
+    };
+    for (specifier, dep) in &module.dependencies {
+      let is_external = dep
+        .maybe_code
+        .maybe_specifier()
+        .and_then(|resolved| graph.get(resolved))
+        .map(|module| {
+          matches!(
+            module,
+            Module::Npm(_) | Module::Node(_) | Module::External(_)
+          )
+        })
+        .unwrap_or(false);
+      if is_external {
+        let specifier = swc::atoms::Atom::from(specifier.as_str());
+        if !externals.contains(&specifier) {
+          externals.push(specifier);
+        }
+      }
+    }
+  }
+  externals
+}
+
+struct BundleLoader<'a> {
+  cm: Rc<swc::common::SourceMap>,
+  sources: &'a HashMap<ModuleSpecifier, String>,
+}
+
+impl swc::bundler::Load for BundleLoader<'_> {
+  fn load(
+    &self,
+    file_name: &FileName,
+  ) -> Result<swc::bundler::ModuleData, AnyError> {
+    let FileName::Url(specifier) = file_name else {
+      bail!("Unexpected file name when bundling: {}", file_name);
+    };
+    let Some(code) = self.sources.get(specifier) else {
+      bail!(
+        "Module \"{}\" unexpectedly missing when bundling.",
+        specifier
+      );
+    };
+    let fm = self
+      .cm
+      .new_source_file(file_name.clone().into(), code.clone());
+    let module = swc::parser::parse_file_as_module(
+      &fm,
+      swc::parser::Syntax::Es(swc::parser::EsSyntax {
+        import_attributes: true,
+        ..Default::default()
+      }),
+      ast::EsVersion::latest(),
+      None,
+      &mut Vec::new(),
+    )
+    .map_err(|err| {
+      anyhow!("Failed parsing {}: {}", specifier, err.into_kind().msg())
+    })?;
+    Ok(swc::bundler::ModuleData {
+      fm,
+      module,
+      helpers: Default::default(),
+    })
+  }
+}
+
+struct BundleResolver<'a>(&'a ModuleGraph);
+
+impl swc::bundler::Resolve for BundleResolver<'_> {
+  fn resolve(
+    &self,
+    referrer: &FileName,
+    specifier: &str,
+  ) -> Result<swc::loader::resolve::Resolution, AnyError> {
+    let FileName::Url(referrer) = referrer else {
+      bail!("Unexpected referrer when bundling: {}", referrer);
+    };
+    let Some(resolved) = self.0.resolve_dependency(specifier, referrer, false)
+    else {
+      bail!(
+        "Cannot resolve \"{}\" from \"{}\" when bundling.",
+        specifier,
+        referrer
+      );
+    };
+    Ok(swc::loader::resolve::Resolution {
+      filename: FileName::Url(self.0.resolve(resolved).clone()),
+      slug: None,
+    })
+  }
+}
+
+/// Provides `import.meta` of bundled modules. `url` is the url of the
+/// original module, `main` is only true for the entrypoint.
+struct BundleHook;
+
+impl swc::bundler::Hook for BundleHook {
+  fn get_import_meta_props(
+    &self,
+    span: swc::common::Span,
+    module_record: &swc::bundler::ModuleRecord,
+  ) -> Result<Vec<ast::KeyValueProp>, AnyError> {
+    let url = match &module_record.file_name {
+      FileName::Url(url) => url.to_string(),
+      file_name => file_name.to_string(),
+    };
+    let main = if module_record.is_entry {
+      ast::Expr::Member(ast::MemberExpr {
+        span,
+        obj: Box::new(ast::Expr::MetaProp(ast::MetaPropExpr {
+          span,
+          kind: ast::MetaPropKind::ImportMeta,
+        })),
+        prop: ast::MemberProp::Ident(ast::IdentName {
+          span,
+          sym: "main".into(),
+        }),
+      })
+    } else {
+      ast::Expr::Lit(ast::Lit::Bool(ast::Bool { span, value: false }))
+    };
+    Ok(vec![
+      ast::KeyValueProp {
+        key: ast::PropName::Ident(ast::IdentName {
+          span,
+          sym: "url".into(),
+        }),
+        value: Box::new(ast::Expr::Lit(ast::Lit::Str(ast::Str {
+          span,
+          value: url.into(),
+          raw: None,
+        }))),
+      },
+      ast::KeyValueProp {
+        key: ast::PropName::Ident(ast::IdentName {
+          span,
+          sym: "main".into(),
+        }),
+        value: Box::new(main),
+      },
+    ])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_graph::source::MemoryLoader;
+  use deno_graph::source::Source;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn bundles_into_one_module() {
+    let loader = MemoryLoader::new(
+      vec![
+        (
+          "file:///main.js",
+          Source::Module {
+            specifier: "file:///main.js",
+            maybe_headers: None,
+            content:
+              "import { a } from './a.js';\nimport 'node:fs';\nconsole.log(a);",
+          },
+        ),
+        (
+          "file:///a.js",
+          Source::Module {
+            specifier: "file:///a.js",
+            maybe_headers: None,
+            content: "export const a = 'from a';",
+          },
+        ),
+      ],
+      Vec::new(),
+    );
+    let mut graph = ModuleGraph::new(GraphKind::CodeOnly);
+    graph
+      .build(
+        vec![ModuleSpecifier::parse("file:///main.js").unwrap()],
+        &loader,
+        Default::default(),
+      )
+      .await;
+    let sources = graph
+      .modules()
+      .filter_map(|module| match module {
+        Module::Js(module) => {
+          Some((module.specifier.clone(), module.source.to_string()))
+        }
+        _ => None,
+      })
+      .collect::<HashMap<_, _>>();
+
+    let output = bundle_graph(
+      &graph,
+      &sources,
+      &BundleOptions {
+        minify: false,
+        source_map: BundleSourceMap::Separate,
+      },
+    )
+    .unwrap();
+    assert!(output.code.contains("'from a'"), "{}", output.code);
+    assert!(output.code.contains("node:fs"), "{}", output.code);
+    assert!(!output.code.contains("./a.js"), "{}", output.code);
+    assert!(output.source_map.unwrap().contains("file:///a.js"));
+  }
+}
@@ -7,12 +7,14 @@ use std::sync::Arc;
 use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
 use deno_core::error::AnyError;
+use deno_graph::GraphKind;
 use deno_graph::Module;
 use deno_graph::ModuleGraph;
 use deno_runtime::deno_node::NodeResolver;
 use deno_terminal::colors;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
 
 use crate::args::check_warn_tsconfig;
 use crate::args::CheckFlags;
@@ -32,16 +34,49 @@ use crate::graph_util::ModuleGraphBuilder;
 use crate::npm::CliNpmResolver;
 use crate::tsc;
 use crate::tsc::Diagnostics;
+use crate::tsc::TscDaemon;
 use crate::tsc::TypeCheckingCjsTracker;
 use crate::util::extract;
+use crate::util::file_watcher;
 use crate::util::path::to_percent_decoded_str;
 
 pub async fn check(
   flags: Arc<Flags>,
   check_flags: CheckFlags,
 ) -> Result<(), AnyError> {
+  if check_flags.daemon {
+    return check_daemon(flags, check_flags).await;
+  }
   let factory = CliFactory::from_flags(flags);
+  check_with_factory(&factory, &check_flags).await
+}
+
+/// Checks the files again whenever a module of their graphs changes, on a
+/// tsc isolate that's kept alive between the checks.
+async fn check_daemon(
+  flags: Arc<Flags>,
+  check_flags: CheckFlags,
+) -> Result<(), AnyError> {
+  let tsc_daemon = TscDaemon::start();
+  file_watcher::watch_func(
+    flags,
+    file_watcher::PrintConfig::new("Check", true),
+    move |flags, watcher_communicator, changed_paths| {
+      watcher_communicator.show_path_changed(changed_paths);
+      let factory =
+        CliFactory::from_flags_for_watcher(flags, watcher_communicator)
+          .with_tsc_daemon(tsc_daemon.clone());
+      let check_flags = check_flags.clone();
+      Ok(async move { check_with_factory(&factory, &check_flags).await })
+    },
+  )
+  .await
+}
 
+async fn check_with_factory(
+  factory: &CliFactory,
+  check_flags: &CheckFlags,
+) -> Result<(), AnyError> {
   let main_graph_container = factory.main_module_graph_container().await?;
 
   let specifiers =
@@ -79,6 +114,146 @@ pub async fn check(
     .await
 }
 
+/// A type checking diagnostic, see [`check_specifiers`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TscDiagnostic {
+  /// Specifier of the module, `None` for diagnostics about the program as a
+  /// whole, eg. an invalid compiler option.
+  pub file: Option<String>,
+  pub range: Option<TscDiagnosticRange>,
+  /// The TypeScript error code, eg. `2322` for `TS2322`.
+  pub code: u64,
+  pub category: tsc::DiagnosticCategory,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TscDiagnosticRange {
+  pub start: tsc::Position,
+  pub end: tsc::Position,
+}
+
+impl From<tsc::Diagnostic> for TscDiagnostic {
+  fn from(diagnostic: tsc::Diagnostic) -> Self {
+    let message = diagnostic
+      .message_text
+      .or_else(|| {
+        diagnostic
+          .message_chain
+          .as_ref()
+          .map(|c| c.format_message(0))
+      })
+      .unwrap_or_default();
+    let range = match (diagnostic.original_source_start, diagnostic.start) {
+      // only the start is mapped back for fast checked modules
+      (Some(start), _) => Some(TscDiagnosticRange {
+        start: start.clone(),
+        end: start,
+      }),
+      (None, Some(start)) => Some(TscDiagnosticRange {
+        end: diagnostic.end.unwrap_or_else(|| start.clone()),
+        start,
+      }),
+      (None, None) => None,
+    };
+    Self {
+      file: diagnostic.file_name,
+      range,
+      code: diagnostic.code,
+      category: diagnostic.category,
+      message,
+    }
+  }
+}
+
+/// Type checks the modules matching `files` like `deno check` does, but
+/// returns the diagnostics instead of failing with them. Errors are returned
+/// when the module graph can't be built, eg. because a module is missing.
+pub async fn check_specifiers(
+  flags: Arc<Flags>,
+  files: Vec<String>,
+) -> Result<Vec<TscDiagnostic>, AnyError> {
+  let factory = CliFactory::from_flags(with_type_check_mode(flags));
+  check_specifiers_with_factory(&factory, files).await
+}
+
+/// Type checks like [`check_specifiers`], but keeps a tsc isolate alive
+/// between the checks, so checking again after some modules changed only
+/// checks them and the modules depending on them.
+///
+/// ```rust,ignore
+/// let daemon = TypeCheckDaemon::new(flags);
+/// let diagnostics = daemon.check(vec!["main.ts".to_string()]).await?;
+/// // ... edit main.ts
+/// let diagnostics = daemon.check(vec!["main.ts".to_string()]).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypeCheckDaemon {
+  flags: Arc<Flags>,
+  tsc_daemon: TscDaemon,
+}
+
+impl TypeCheckDaemon {
+  pub fn new(flags: Arc<Flags>) -> Self {
+    Self {
+      flags: with_type_check_mode(flags),
+      tsc_daemon: TscDaemon::start(),
+    }
+  }
+
+  pub async fn check(
+    &self,
+    files: Vec<String>,
+  ) -> Result<Vec<TscDiagnostic>, AnyError> {
+    // the configuration and modules are read again for every check
+    let factory = CliFactory::from_flags(self.flags.clone())
+      .with_tsc_daemon(self.tsc_daemon.clone());
+    check_specifiers_with_factory(&factory, files).await
+  }
+}
+
+fn with_type_check_mode(mut flags: Arc<Flags>) -> Arc<Flags> {
+  if !flags.type_check_mode.is_true() {
+    Arc::make_mut(&mut flags).type_check_mode = TypeCheckMode::Local;
+  }
+  flags
+}
+
+async fn check_specifiers_with_factory(
+  factory: &CliFactory,
+  files: Vec<String>,
+) -> Result<Vec<TscDiagnostic>, AnyError> {
+  let cli_options = factory.cli_options()?;
+  let specifiers = factory
+    .main_module_graph_container()
+    .await?
+    .collect_specifiers(&files)?;
+  if specifiers.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let graph = factory
+    .create_graph_for_embedder_roots(GraphKind::All, specifiers)
+    .await?;
+  let (_, diagnostics) = factory
+    .type_checker()
+    .await?
+    .check_diagnostics(
+      graph,
+      CheckOptions {
+        build_fast_check_graph: true,
+        lib: cli_options.ts_type_lib_window(),
+        log_ignored_options: true,
+        reload: cli_options.reload_flag(),
+        type_check_mode: cli_options.type_check_mode(),
+      },
+    )
+    .await?;
+  Ok(diagnostics.into_iter().map(TscDiagnostic::from).collect())
+}
+
 /// Options for performing a check of a module graph. Note that the decision to
 /// emit or not is determined by the `ts_config` settings.
 pub struct CheckOptions {
@@ -105,6 +280,7 @@ pub struct TypeChecker {
   module_graph_builder: Arc<ModuleGraphBuilder>,
   node_resolver: Arc<NodeResolver>,
   npm_resolver: Arc<dyn CliNpmResolver>,
+  tsc_daemon: Option<TscDaemon>,
 }
 
 impl TypeChecker {
@@ -115,6 +291,7 @@ impl TypeChecker {
     module_graph_builder: Arc<ModuleGraphBuilder>,
     node_resolver: Arc<NodeResolver>,
     npm_resolver: Arc<dyn CliNpmResolver>,
+    tsc_daemon: Option<TscDaemon>,
   ) -> Self {
     Self {
       caches,
@@ -123,6 +300,7 @@ impl TypeChecker {
       module_graph_builder,
       node_resolver,
       npm_resolver,
+      tsc_daemon,
     }
   }
 
@@ -242,7 +420,7 @@ impl TypeChecker {
 
     let root_names = get_tsc_roots(&graph, check_js);
     let graph = Arc::new(graph);
-    let response = tsc::exec(tsc::Request {
+    let request = tsc::Request {
       config: ts_config,
       debug: self.cli_options.log_level() == Some(log::Level::Debug),
       graph: graph.clone(),
@@ -255,7 +433,15 @@ impl TypeChecker {
       maybe_tsbuildinfo,
       root_names,
       check_mode: type_check_mode,
-    })?;
+    };
+    let response = match (&self.tsc_daemon, self.cli_options.type_check_jobs())
+    {
+      (Some(tsc_daemon), _) => tsc_daemon.exec(request).await?,
+      (None, Some(jobs)) if jobs.get() > 1 => {
+        tsc::exec_parallel(request, jobs)?
+      }
+      (None, _) => tsc::exec(request)?,
+    };
 
     let mut diagnostics = response.diagnostics.filter(|d| {
       if self.is_remote_diagnostic(d) {
@@ -617,6 +803,9 @@ mod test {
 
   use super::get_leading_comments;
   use super::has_ts_check;
+  use super::TscDiagnostic;
+  use super::TscDiagnosticRange;
+  use crate::tsc;
 
   #[test]
   fn get_leading_comments_test() {
@@ -660,4 +849,38 @@ mod test {
       "// ts-check\nconsole.log(5);"
     ));
   }
+
+  #[test]
+  fn tsc_diagnostic_from_diagnostic() {
+    let position = |line, character| tsc::Position { line, character };
+    let diagnostic = TscDiagnostic::from(tsc::Diagnostic {
+      category: tsc::DiagnosticCategory::Error,
+      code: 2322,
+      start: Some(position(1, 6)),
+      end: Some(position(1, 7)),
+      original_source_start: None,
+      message_text: Some(
+        "Type 'string' is not assignable to type 'number'.".to_string(),
+      ),
+      message_chain: None,
+      source: None,
+      source_line: Some("const a: number = \"\";".to_string()),
+      file_name: Some("file:///a.ts".to_string()),
+      related_information: None,
+    });
+    assert_eq!(
+      diagnostic,
+      TscDiagnostic {
+        file: Some("file:///a.ts".to_string()),
+        range: Some(TscDiagnosticRange {
+          start: position(1, 6),
+          end: position(1, 7),
+        }),
+        code: 2322,
+        category: tsc::DiagnosticCategory::Error,
+        message: "Type 'string' is not assignable to type 'number'."
+          .to_string(),
+      }
+    );
+  }
 }
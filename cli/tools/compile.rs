@@ -2,10 +2,12 @@
 
 use crate::args::check_warn_tsconfig;
 use crate::args::CompileFlags;
+use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::factory::CliFactory;
 use crate::http_util::HttpClientProvider;
 use crate::standalone::binary::StandaloneRelativeFileBaseUrl;
+use crate::standalone::encryption::PayloadCipher;
 use crate::standalone::is_standalone_binary;
 use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
@@ -15,7 +17,9 @@ use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::resolve_url_or_path;
 use deno_graph::GraphKind;
+use deno_graph::ModuleGraph;
 use deno_terminal::colors;
+use indexmap::IndexMap;
 use rand::Rng;
 use std::path::Path;
 use std::path::PathBuf;
@@ -23,34 +27,136 @@ use std::sync::Arc;
 
 use super::installer::infer_name_from_url;
 
+/// Options for [`create_binary`]. They correspond to the flags of
+/// `deno compile`.
+#[derive(Clone, Default)]
+pub struct CompileOptions {
+  /// The target triple, eg. `"x86_64-pc-windows-msvc"`. Defaults to the
+  /// target of the current executable.
+  pub target: Option<String>,
+  /// Additional modules, files and directories to embed.
+  pub include: Vec<String>,
+  /// Icon of the executable. Only supported when targeting Windows.
+  pub icon: Option<PathBuf>,
+  /// Environment variables to embed. They take precedence over the ones
+  /// from `--env-file`.
+  pub env: IndexMap<String, String>,
+  /// Arguments passed to the program before the ones it's invoked with.
+  pub args: Vec<String>,
+  /// Hide the terminal window. Only supported when targeting Windows.
+  pub no_terminal: bool,
+  /// The executable the program is embedded into. Defaults to `denort` of
+  /// this version.
+  pub base_binary: Option<PathBuf>,
+  /// Encrypts the embedded program. The base executable has to run it with
+  /// the same cipher, `denort` can't run encrypted programs.
+  pub payload_cipher: Option<Arc<dyn PayloadCipher>>,
+}
+
+/// Creates a standalone executable for `entrypoint` like `deno compile`
+/// does, and returns its bytes instead of writing it to disk.
+pub async fn create_binary(
+  mut flags: Arc<Flags>,
+  entrypoint: String,
+  options: CompileOptions,
+) -> Result<Vec<u8>, AnyError> {
+  let compile_flags = CompileFlags {
+    source_file: entrypoint,
+    output: None,
+    args: options.args,
+    target: options.target,
+    no_terminal: options.no_terminal,
+    icon: options.icon.map(|icon| icon.to_string_lossy().into_owned()),
+    include: options.include,
+  };
+  Arc::make_mut(&mut flags).subcommand =
+    DenoSubcommand::Compile(compile_flags.clone());
+  let factory = CliFactory::from_flags(flags);
+  let entrypoint = factory.cli_options()?.resolve_main_module()?;
+  let compile_graph = create_compile_graph(&factory, &compile_flags).await?;
+  let mut binary_writer = factory.create_compile_binary_writer().await?;
+  if let Some(base_binary) = options.base_binary {
+    binary_writer.set_base_binary_path(base_binary);
+  }
+  if let Some(payload_cipher) = options.payload_cipher {
+    binary_writer.set_payload_cipher(payload_cipher);
+  }
+  let mut bytes = Vec::new();
+  binary_writer
+    .write_bin(
+      &mut bytes,
+      &compile_graph.graph,
+      StandaloneRelativeFileBaseUrl::from(&compile_graph.root_dir_url),
+      entrypoint,
+      &compile_graph.include_files,
+      &compile_flags,
+      options.env,
+    )
+    .await?;
+  Ok(bytes)
+}
+
+/// Creates an archive of the program at `entrypoint` and its dependencies,
+/// like [`create_binary`] without the executable, and returns its bytes.
+/// Run it with `run_archive`. The executable related options (`target`,
+/// `icon`, `no_terminal`, `base_binary` and `payload_cipher`) are ignored.
+pub async fn create_archive_with_options(
+  mut flags: Arc<Flags>,
+  entrypoint: String,
+  options: CompileOptions,
+) -> Result<Vec<u8>, AnyError> {
+  let compile_flags = CompileFlags {
+    source_file: entrypoint,
+    output: None,
+    args: options.args,
+    target: None,
+    no_terminal: false,
+    icon: None,
+    include: options.include,
+  };
+  Arc::make_mut(&mut flags).subcommand =
+    DenoSubcommand::Compile(compile_flags.clone());
+  let factory = CliFactory::from_flags(flags);
+  let entrypoint = factory.cli_options()?.resolve_main_module()?;
+  let compile_graph = create_compile_graph(&factory, &compile_flags).await?;
+  let binary_writer = factory.create_compile_binary_writer().await?;
+  let mut bytes = Vec::new();
+  binary_writer
+    .write_archive(
+      &mut bytes,
+      &compile_graph.graph,
+      StandaloneRelativeFileBaseUrl::from(&compile_graph.root_dir_url),
+      entrypoint,
+      &compile_graph.include_files,
+      &compile_flags,
+      options.env,
+    )
+    .await?;
+  Ok(bytes)
+}
+
+/// Creates an archive of the program at `entrypoint` with the default
+/// configuration, eg. from a build script. The npm packages it imports are
+/// resolved and included. Use [`create_archive_with_options`] to grant
+/// permissions or embed arguments and environment variables.
+pub async fn create_archive(entrypoint: &str) -> Result<Vec<u8>, AnyError> {
+  create_archive_with_options(
+    Default::default(),
+    entrypoint.to_string(),
+    Default::default(),
+  )
+  .await
+}
+
 pub async fn compile(
   flags: Arc<Flags>,
   compile_flags: CompileFlags,
 ) -> Result<(), AnyError> {
   let factory = CliFactory::from_flags(flags);
   let cli_options = factory.cli_options()?;
-  let module_graph_creator = factory.module_graph_creator().await?;
   let binary_writer = factory.create_compile_binary_writer().await?;
   let http_client = factory.http_client_provider();
   let entrypoint = cli_options.resolve_main_module()?;
-  let (module_roots, include_files) = get_module_roots_and_include_files(
-    entrypoint,
-    &compile_flags,
-    cli_options.initial_cwd(),
-  )?;
-
-  // this is not supported, so show a warning about it, but don't error in order
-  // to allow someone to still run `deno compile` when this is in a deno.json
-  if cli_options.unstable_sloppy_imports() {
-    log::warn!(
-      concat!(
-        "{} Sloppy imports are not supported in deno compile. ",
-        "The compiled executable may encounter runtime errors.",
-      ),
-      crate::colors::yellow("Warning"),
-    );
-  }
-
   let output_path = resolve_compile_executable_output_path(
     http_client,
     &compile_flags,
@@ -58,40 +164,11 @@ pub async fn compile(
   )
   .await?;
 
-  let graph = Arc::try_unwrap(
-    module_graph_creator
-      .create_graph_and_maybe_check(module_roots.clone())
-      .await?,
-  )
-  .unwrap();
-  let graph = if cli_options.type_check_mode().is_true() {
-    // In this case, the previous graph creation did type checking, which will
-    // create a module graph with types information in it. We don't want to
-    // store that in the binary so create a code only module graph from scratch.
-    module_graph_creator
-      .create_graph(GraphKind::CodeOnly, module_roots)
-      .await?
-  } else {
-    graph
-  };
-
-  let ts_config_for_emit = cli_options
-    .resolve_ts_config_for_emit(deno_config::deno_json::TsConfigType::Emit)?;
-  check_warn_tsconfig(&ts_config_for_emit);
-  let root_dir_url = resolve_root_dir_from_specifiers(
-    cli_options.workspace().root_dir(),
-    graph
-      .specifiers()
-      .map(|(s, _)| s)
-      .chain(
-        cli_options
-          .node_modules_dir_path()
-          .and_then(|p| ModuleSpecifier::from_directory_path(p).ok())
-          .iter(),
-      )
-      .chain(include_files.iter()),
-  );
-  log::debug!("Binary root dir: {}", root_dir_url);
+  let CompileGraph {
+    graph,
+    root_dir_url,
+    include_files,
+  } = create_compile_graph(&factory, &compile_flags).await?;
   log::info!(
     "{} {} to {}",
     colors::green("Compile"),
@@ -123,6 +200,7 @@ pub async fn compile(
       entrypoint,
       &include_files,
       &compile_flags,
+      Default::default(),
     )
     .await
     .with_context(|| {
@@ -164,6 +242,79 @@ pub async fn compile(
   Ok(())
 }
 
+struct CompileGraph {
+  graph: ModuleGraph,
+  root_dir_url: ModuleSpecifier,
+  /// Included files that aren't part of the module graph.
+  include_files: Vec<ModuleSpecifier>,
+}
+
+async fn create_compile_graph(
+  factory: &CliFactory,
+  compile_flags: &CompileFlags,
+) -> Result<CompileGraph, AnyError> {
+  let cli_options = factory.cli_options()?;
+  let module_graph_creator = factory.module_graph_creator().await?;
+  let entrypoint = cli_options.resolve_main_module()?;
+  let (module_roots, include_files) = get_module_roots_and_include_files(
+    entrypoint,
+    compile_flags,
+    cli_options.initial_cwd(),
+  )?;
+
+  // this is not supported, so show a warning about it, but don't error in order
+  // to allow someone to still run `deno compile` when this is in a deno.json
+  if cli_options.unstable_sloppy_imports() {
+    log::warn!(
+      concat!(
+        "{} Sloppy imports are not supported in deno compile. ",
+        "The compiled executable may encounter runtime errors.",
+      ),
+      crate::colors::yellow("Warning"),
+    );
+  }
+
+  let graph = Arc::try_unwrap(
+    module_graph_creator
+      .create_graph_and_maybe_check(module_roots.clone())
+      .await?,
+  )
+  .unwrap();
+  let graph = if cli_options.type_check_mode().is_true() {
+    // In this case, the previous graph creation did type checking, which will
+    // create a module graph with types information in it. We don't want to
+    // store that in the binary so create a code only module graph from scratch.
+    module_graph_creator
+      .create_graph(GraphKind::CodeOnly, module_roots)
+      .await?
+  } else {
+    graph
+  };
+
+  let ts_config_for_emit = cli_options
+    .resolve_ts_config_for_emit(deno_config::deno_json::TsConfigType::Emit)?;
+  check_warn_tsconfig(&ts_config_for_emit);
+  let root_dir_url = resolve_root_dir_from_specifiers(
+    cli_options.workspace().root_dir(),
+    graph
+      .specifiers()
+      .map(|(s, _)| s)
+      .chain(
+        cli_options
+          .node_modules_dir_path()
+          .and_then(|p| ModuleSpecifier::from_directory_path(p).ok())
+          .iter(),
+      )
+      .chain(include_files.iter()),
+  );
+  log::debug!("Binary root dir: {}", root_dir_url);
+  Ok(CompileGraph {
+    graph,
+    root_dir_url,
+    include_files,
+  })
+}
+
 /// This function writes out a final binary to specified path. If output path
 /// is not already standalone binary it will return error instead.
 fn validate_output_path(output_path: &Path) -> Result<(), AnyError> {
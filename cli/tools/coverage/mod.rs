@@ -169,19 +169,20 @@ impl CoverageCollector {
 }
 
 #[derive(Debug, Clone)]
-struct BranchCoverageItem {
-  line_index: usize,
-  block_number: usize,
-  branch_number: usize,
-  taken: Option<i64>,
-  is_hit: bool,
+pub struct BranchCoverageItem {
+  pub line_index: usize,
+  pub block_number: usize,
+  pub branch_number: usize,
+  /// How often the branch was taken, `None` if its block never ran.
+  pub taken: Option<i64>,
+  pub is_hit: bool,
 }
 
 #[derive(Debug, Clone)]
-struct FunctionCoverageItem {
-  name: String,
-  line_index: usize,
-  execution_count: i64,
+pub struct FunctionCoverageItem {
+  pub name: String,
+  pub line_index: usize,
+  pub execution_count: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -194,6 +195,38 @@ pub struct CoverageReport {
   output: Option<PathBuf>,
 }
 
+impl CoverageReport {
+  pub fn specifier(&self) -> &ModuleSpecifier {
+    &self.url
+  }
+
+  pub fn functions(&self) -> &[FunctionCoverageItem] {
+    &self.named_functions
+  }
+
+  pub fn branches(&self) -> &[BranchCoverageItem] {
+    &self.branches
+  }
+
+  /// The zero based index and hit count of every coverable line of the
+  /// original source.
+  pub fn lines(&self) -> &[(usize, i64)] {
+    &self.found_lines
+  }
+
+  pub fn lines_hit(&self) -> usize {
+    self
+      .found_lines
+      .iter()
+      .filter(|(_, count)| *count > 0)
+      .count()
+  }
+
+  pub fn branches_hit(&self) -> usize {
+    self.branches.iter().filter(|b| b.is_hit).count()
+  }
+}
+
 fn generate_coverage_report(
   script_coverage: &cdp::ScriptCoverage,
   script_source: String,
@@ -507,10 +540,6 @@ pub fn cover_files(
 
   let factory = CliFactory::from_flags(flags);
   let cli_options = factory.cli_options()?;
-  let in_npm_pkg_checker = factory.in_npm_pkg_checker()?;
-  let file_fetcher = factory.file_fetcher()?;
-  let emitter = factory.emitter()?;
-  let cjs_tracker = factory.cjs_tracker()?;
 
   assert!(!coverage_flags.files.include.is_empty());
 
@@ -518,18 +547,55 @@ pub fn cover_files(
   let coverage_root = cli_options
     .initial_cwd()
     .join(&coverage_flags.files.include[0]);
-  let script_coverages = collect_coverages(
-    cli_options,
+  let script_coverages = load_script_coverages(
+    &factory,
     coverage_flags.files,
-    cli_options.initial_cwd(),
+    coverage_flags.include,
+    coverage_flags.exclude,
   )?;
+
+  let mut reporter = reporter::create(coverage_flags.r#type);
+
+  let out_mode = match coverage_flags.output {
+    Some(ref path) => match File::create(path) {
+      Ok(_) => Some(PathBuf::from(path)),
+      Err(e) => {
+        return Err(anyhow!("Failed to create output file: {}", e));
+      }
+    },
+    None => None,
+  };
+
+  for (coverage_report, original_source) in
+    generate_coverage_reports(&factory, script_coverages, &out_mode)?
+  {
+    reporter.report(&coverage_report, &original_source)?;
+  }
+
+  reporter.done(&coverage_root);
+
+  Ok(())
+}
+
+/// Reads the coverage profiles matching `files`, filters them by the
+/// `include` and `exclude` regexes and merges the profiles of each script.
+fn load_script_coverages(
+  factory: &CliFactory,
+  files: FileFlags,
+  include: Vec<String>,
+  exclude: Vec<String>,
+) -> Result<Vec<cdp::ScriptCoverage>, AnyError> {
+  let cli_options = factory.cli_options()?;
+  let in_npm_pkg_checker = factory.in_npm_pkg_checker()?;
+  let script_coverages =
+    collect_coverages(cli_options, files, cli_options.initial_cwd())?;
   if script_coverages.is_empty() {
     return Err(generic_error("No coverage files found"));
   }
   let script_coverages = filter_coverages(
     script_coverages,
-    coverage_flags.include,
-    coverage_flags.exclude,
+    include,
+    exclude,
     in_npm_pkg_checker.as_ref(),
   );
   if script_coverages.is_empty() {
@@ -541,25 +607,26 @@ pub fn cover_files(
     .map(|cov| ProcessCoverage { result: vec![cov] })
     .collect();
 
-  let script_coverages = if let Some(c) = merge::merge_processes(proc_coverages)
-  {
+  Ok(if let Some(c) = merge::merge_processes(proc_coverages) {
     c.result
   } else {
     vec![]
-  };
-
-  let mut reporter = reporter::create(coverage_flags.r#type);
+  })
+}
 
-  let out_mode = match coverage_flags.output {
-    Some(ref path) => match File::create(path) {
-      Ok(_) => Some(PathBuf::from(path)),
-      Err(e) => {
-        return Err(anyhow!("Failed to create output file: {}", e));
-      }
-    },
-    None => None,
-  };
+/// Maps the script coverages back to the original sources. Returns the
+/// report of every file with coverable lines along with its source.
+fn generate_coverage_reports(
+  factory: &CliFactory,
+  script_coverages: Vec<cdp::ScriptCoverage>,
+  out_mode: &Option<PathBuf>,
+) -> Result<Vec<(CoverageReport, String)>, AnyError> {
+  let cli_options = factory.cli_options()?;
+  let file_fetcher = factory.file_fetcher()?;
+  let emitter = factory.emitter()?;
+  let cjs_tracker = factory.cjs_tracker()?;
 
+  let mut reports = Vec::with_capacity(script_coverages.len());
   for script_coverage in script_coverages {
     let module_specifier = deno_core::resolve_url_or_path(
       &script_coverage.url,
@@ -601,7 +668,12 @@ pub fn cover_files(
         let module_kind = ModuleKind::from_is_cjs(
           cjs_tracker.is_maybe_cjs(&file.specifier, file.media_type)?,
         );
-        Some(match emitter.maybe_cached_emit(&file.specifier, module_kind, &file.source) {
+        Some(match emitter.maybe_cached_emit(
+          &file.specifier,
+          file.media_type,
+          module_kind,
+          &file.source,
+        ) {
           Some(code) => code,
           None => {
             return Err(anyhow!(
@@ -626,15 +698,88 @@ pub fn cover_files(
       &script_coverage,
       runtime_code.as_str().to_owned(),
       &source_map,
-      &out_mode,
+      out_mode,
     );
 
     if !coverage_report.found_lines.is_empty() {
-      reporter.report(&coverage_report, &original_source)?;
+      reports.push((coverage_report, original_source.to_string()));
     }
   }
 
-  reporter.done(&coverage_root);
+  Ok(reports)
+}
 
-  Ok(())
+#[derive(Debug, Clone)]
+pub struct CoverageReportOptions {
+  /// Directories with coverage profiles, like the one passed to
+  /// `DenoRuntimeBuilder::coverage_dir`, or the profiles themselves.
+  pub profiles: Vec<PathBuf>,
+  /// Only URLs matching one of these regexes are included.
+  pub include: Vec<String>,
+  /// URLs matching one of these regexes are left out.
+  pub exclude: Vec<String>,
+}
+
+impl Default for CoverageReportOptions {
+  fn default() -> Self {
+    // same as the defaults of `deno coverage`
+    Self {
+      profiles: vec![],
+      include: vec![r"^file:".to_string()],
+      exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+    }
+  }
+}
+
+/// The coverage of every covered file, built by [`coverage_report`].
+#[derive(Debug, Clone)]
+pub struct CodeCoverage {
+  file_reports: Vec<(CoverageReport, String)>,
+}
+
+impl CodeCoverage {
+  pub fn files(&self) -> impl Iterator<Item = &CoverageReport> {
+    self.file_reports.iter().map(|(report, _)| report)
+  }
+
+  /// The report in LCOV format, as written by `deno coverage --lcov`.
+  pub fn lcov(&self) -> Result<String, AnyError> {
+    let mut out = Vec::new();
+    for (report, _) in &self.file_reports {
+      reporter::write_lcov(&mut out, report)?;
+    }
+    Ok(String::from_utf8(out)?)
+  }
+
+  /// The pages of the HTML report, as written by `deno coverage --html`,
+  /// with their paths relative to the report's directory.
+  pub fn html(&self) -> Vec<(PathBuf, String)> {
+    reporter::render_html(self.file_reports.clone())
+  }
+}
+
+/// Builds the coverage report of the profiles collected by workers, like
+/// `deno coverage` does.
+pub fn coverage_report(
+  flags: Arc<Flags>,
+  options: CoverageReportOptions,
+) -> Result<CodeCoverage, AnyError> {
+  if options.profiles.is_empty() {
+    return Err(generic_error("No matching coverage profiles found"));
+  }
+
+  let factory = CliFactory::from_flags(flags);
+  let files = FileFlags {
+    include: options
+      .profiles
+      .iter()
+      .map(|path| path.to_string_lossy().into_owned())
+      .collect(),
+    ignore: vec![],
+  };
+  let script_coverages =
+    load_script_coverages(&factory, files, options.include, options.exclude)?;
+  Ok(CodeCoverage {
+    file_reports: generate_coverage_reports(&factory, script_coverages, &None)?,
+  })
 }
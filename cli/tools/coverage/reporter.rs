@@ -234,81 +234,87 @@ impl CoverageReporter for LcovCoverageReporter {
         .map(|f| Box::new(f) as Box<dyn Write>),
       None => Ok(Box::new(io::stdout())),
     };
-    let mut out_writer = out_mode?;
-
-    let file_path = coverage_report
-      .url
-      .to_file_path()
-      .ok()
-      .and_then(|p| p.to_str().map(|p| p.to_string()))
-      .unwrap_or_else(|| coverage_report.url.to_string());
-    writeln!(out_writer, "SF:{file_path}")?;
-
-    for function in &coverage_report.named_functions {
-      writeln!(
-        out_writer,
-        "FN:{},{}",
-        function.line_index + 1,
-        function.name
-      )?;
-    }
+    write_lcov(&mut out_mode?, coverage_report)
+  }
+}
 
-    for function in &coverage_report.named_functions {
-      writeln!(
-        out_writer,
-        "FNDA:{},{}",
-        function.execution_count, function.name
-      )?;
-    }
+/// Writes the LCOV record of a single file.
+pub fn write_lcov(
+  out_writer: &mut dyn Write,
+  coverage_report: &CoverageReport,
+) -> Result<(), AnyError> {
+  let file_path = coverage_report
+    .url
+    .to_file_path()
+    .ok()
+    .and_then(|p| p.to_str().map(|p| p.to_string()))
+    .unwrap_or_else(|| coverage_report.url.to_string());
+  writeln!(out_writer, "SF:{file_path}")?;
+
+  for function in &coverage_report.named_functions {
+    writeln!(
+      out_writer,
+      "FN:{},{}",
+      function.line_index + 1,
+      function.name
+    )?;
+  }
 
-    let functions_found = coverage_report.named_functions.len();
-    writeln!(out_writer, "FNF:{functions_found}")?;
-    let functions_hit = coverage_report
-      .named_functions
-      .iter()
-      .filter(|f| f.execution_count > 0)
-      .count();
-    writeln!(out_writer, "FNH:{functions_hit}")?;
+  for function in &coverage_report.named_functions {
+    writeln!(
+      out_writer,
+      "FNDA:{},{}",
+      function.execution_count, function.name
+    )?;
+  }
 
-    for branch in &coverage_report.branches {
-      let taken = if let Some(taken) = &branch.taken {
-        taken.to_string()
-      } else {
-        "-".to_string()
-      };
+  let functions_found = coverage_report.named_functions.len();
+  writeln!(out_writer, "FNF:{functions_found}")?;
+  let functions_hit = coverage_report
+    .named_functions
+    .iter()
+    .filter(|f| f.execution_count > 0)
+    .count();
+  writeln!(out_writer, "FNH:{functions_hit}")?;
+
+  for branch in &coverage_report.branches {
+    let taken = if let Some(taken) = &branch.taken {
+      taken.to_string()
+    } else {
+      "-".to_string()
+    };
 
-      writeln!(
-        out_writer,
-        "BRDA:{},{},{},{}",
-        branch.line_index + 1,
-        branch.block_number,
-        branch.branch_number,
-        taken
-      )?;
-    }
+    writeln!(
+      out_writer,
+      "BRDA:{},{},{},{}",
+      branch.line_index + 1,
+      branch.block_number,
+      branch.branch_number,
+      taken
+    )?;
+  }
 
-    let branches_found = coverage_report.branches.len();
-    writeln!(out_writer, "BRF:{branches_found}")?;
-    let branches_hit =
-      coverage_report.branches.iter().filter(|b| b.is_hit).count();
-    writeln!(out_writer, "BRH:{branches_hit}")?;
-    for (index, count) in &coverage_report.found_lines {
-      writeln!(out_writer, "DA:{},{}", index + 1, count)?;
-    }
+  let branches_found = coverage_report.branches.len();
+  writeln!(out_writer, "BRF:{branches_found}")?;
+  let branches_hit =
+    coverage_report.branches.iter().filter(|b| b.is_hit).count();
+  writeln!(out_writer, "BRH:{branches_hit}")?;
+  for (index, count) in &coverage_report.found_lines {
+    writeln!(out_writer, "DA:{},{}", index + 1, count)?;
+  }
 
-    let lines_hit = coverage_report
-      .found_lines
-      .iter()
-      .filter(|(_, count)| *count != 0)
-      .count();
-    writeln!(out_writer, "LH:{lines_hit}")?;
+  let lines_hit = coverage_report
+    .found_lines
+    .iter()
+    .filter(|(_, count)| *count != 0)
+    .count();
+  writeln!(out_writer, "LH:{lines_hit}")?;
 
-    let lines_found = coverage_report.found_lines.len();
-    writeln!(out_writer, "LF:{lines_found}")?;
+  let lines_found = coverage_report.found_lines.len();
+  writeln!(out_writer, "LF:{lines_found}")?;
 
-    writeln!(out_writer, "end_of_record")?;
-    Ok(())
-  }
+  writeln!(out_writer, "end_of_record")?;
+  Ok(())
 }
 
 struct DetailedCoverageReporter {}
@@ -398,19 +404,7 @@ impl CoverageReporter for HtmlCoverageReporter {
   }
 
   fn done(&mut self, coverage_root: &Path) {
-    let summary = self.collect_summary(&self.file_reports);
-    let now = chrono::Utc::now().to_rfc2822();
-
-    for (node, stats) in &summary {
-      let report_path =
-        self.get_report_path(coverage_root, node, stats.file_text.is_none());
-      let main_content = if let Some(file_text) = &stats.file_text {
-        self.create_html_code_table(file_text, stats.report.unwrap())
-      } else {
-        self.create_html_summary_table(node, &summary)
-      };
-      let is_dir = stats.file_text.is_none();
-      let html = self.create_html(node, is_dir, stats, &now, &main_content);
+    for (report_path, html) in self.render_pages(&coverage_root.join("html")) {
       fs::create_dir_all(report_path.parent().unwrap()).unwrap();
       fs::write(report_path, html).unwrap();
     }
@@ -428,6 +422,14 @@ impl CoverageReporter for HtmlCoverageReporter {
   }
 }
 
+/// Renders the pages of the HTML report, with paths relative to the
+/// report's root directory.
+pub fn render_html(
+  file_reports: Vec<(CoverageReport, String)>,
+) -> Vec<(PathBuf, String)> {
+  HtmlCoverageReporter { file_reports }.render_pages(Path::new(""))
+}
+
 impl HtmlCoverageReporter {
   pub fn new() -> HtmlCoverageReporter {
     HtmlCoverageReporter {
@@ -435,23 +437,41 @@ impl HtmlCoverageReporter {
     }
   }
 
+  /// Renders every page of the report and returns them with their paths.
+  fn render_pages(&self, html_root: &Path) -> Vec<(PathBuf, String)> {
+    let summary = self.collect_summary(&self.file_reports);
+    let now = chrono::Utc::now().to_rfc2822();
+
+    let mut pages = Vec::with_capacity(summary.len());
+    for (node, stats) in &summary {
+      let report_path =
+        self.get_report_path(html_root, node, stats.file_text.is_none());
+      let main_content = if let Some(file_text) = &stats.file_text {
+        self.create_html_code_table(file_text, stats.report.unwrap())
+      } else {
+        self.create_html_summary_table(node, &summary)
+      };
+      let is_dir = stats.file_text.is_none();
+      let html = self.create_html(node, is_dir, stats, &now, &main_content);
+      pages.push((report_path, html));
+    }
+    pages
+  }
+
   /// Gets the report path for a single file
   pub fn get_report_path(
     &self,
-    coverage_root: &Path,
+    html_root: &Path,
     node: &str,
     is_dir: bool,
   ) -> PathBuf {
     if is_dir {
       // e.g. /path/to/coverage/html/src/index.html
-      coverage_root.join("html").join(node).join("index.html")
+      html_root.join(node).join("index.html")
     } else {
       // e.g. /path/to/coverage/html/src/main.ts.html
-      Path::new(&format!(
-        "{}.html",
-        coverage_root.join("html").join(node).to_str().unwrap()
-      ))
-      .to_path_buf()
+      Path::new(&format!("{}.html", html_root.join(node).to_str().unwrap()))
+        .to_path_buf()
     }
   }
 
@@ -1,5 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::CliOptions;
 use crate::args::DocFlags;
 use crate::args::DocHtmlFlag;
 use crate::args::DocSourceFileFlag;
@@ -7,7 +8,7 @@ use crate::args::Flags;
 use crate::colors;
 use crate::display;
 use crate::factory::CliFactory;
-use crate::graph_util::graph_exit_integrity_errors;
+use crate::graph_util::graph_integrity_error_messages;
 use crate::graph_util::graph_walk_errors;
 use crate::graph_util::GraphWalkErrorsOptions;
 use crate::tsc::get_types_declaration_file_text;
@@ -32,6 +33,7 @@ use doc::html::ShortPath;
 use doc::DocDiagnostic;
 use indexmap::IndexMap;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -41,7 +43,7 @@ const PRISM_CSS: &str = include_str!("./doc/prism.css");
 const PRISM_JS: &str = include_str!("./doc/prism.js");
 
 async fn generate_doc_nodes_for_builtin_types(
-  doc_flags: DocFlags,
+  private: bool,
   parser: &dyn EsParser,
   analyzer: &dyn ModuleAnalyzer,
 ) -> Result<IndexMap<ModuleSpecifier, Vec<doc::DocNode>>, AnyError> {
@@ -84,7 +86,7 @@ async fn generate_doc_nodes_for_builtin_types(
     parser,
     doc::DocParserOptions {
       diagnostics: false,
-      private: doc_flags.private,
+      private,
     },
   )?;
   let nodes = doc_parser.parse_module(&source_file_specifier)?.definitions;
@@ -92,6 +94,75 @@ async fn generate_doc_nodes_for_builtin_types(
   Ok(IndexMap::from([(source_file_specifier, nodes)]))
 }
 
+async fn generate_doc_nodes_for_paths(
+  factory: &CliFactory,
+  source_files: &[String],
+  parser: &dyn EsParser,
+  private: bool,
+  lint: bool,
+) -> Result<IndexMap<ModuleSpecifier, Vec<doc::DocNode>>, AnyError> {
+  let cli_options = factory.cli_options()?;
+  let module_graph_creator = factory.module_graph_creator().await?;
+  let fs = factory.fs();
+
+  let module_specifiers = collect_specifiers(
+    FilePatterns {
+      base: cli_options.initial_cwd().to_path_buf(),
+      include: Some(PathOrPatternSet::from_include_relative_path_or_patterns(
+        cli_options.initial_cwd(),
+        source_files,
+      )?),
+      exclude: Default::default(),
+    },
+    cli_options.vendor_dir_path().map(ToOwned::to_owned),
+    |_| true,
+  )?;
+  let graph = module_graph_creator
+    .create_graph(GraphKind::TypesOnly, module_specifiers.clone())
+    .await?;
+
+  if let Some(message) =
+    graph_integrity_error_messages(&graph).into_iter().next()
+  {
+    bail!("{}", message);
+  }
+  let errors = graph_walk_errors(
+    &graph,
+    fs,
+    &module_specifiers,
+    GraphWalkErrorsOptions {
+      check_js: false,
+      kind: GraphKind::TypesOnly,
+    },
+  );
+  for error in errors {
+    log::warn!("{} {}", colors::yellow("Warning"), error);
+  }
+
+  let doc_parser = doc::DocParser::new(
+    &graph,
+    parser,
+    doc::DocParserOptions {
+      private,
+      diagnostics: lint,
+    },
+  )?;
+
+  let mut doc_nodes_by_url = IndexMap::with_capacity(module_specifiers.len());
+
+  for module_specifier in module_specifiers {
+    let nodes = doc_parser.parse_with_reexports(&module_specifier)?;
+    doc_nodes_by_url.insert(module_specifier, nodes);
+  }
+
+  if lint {
+    let diagnostics = doc_parser.take_diagnostics();
+    check_diagnostics(&diagnostics)?;
+  }
+
+  Ok(doc_nodes_by_url)
+}
+
 pub async fn doc(
   flags: Arc<Flags>,
   doc_flags: DocFlags,
@@ -106,146 +177,36 @@ pub async fn doc(
   let doc_nodes_by_url = match doc_flags.source_files {
     DocSourceFileFlag::Builtin => {
       generate_doc_nodes_for_builtin_types(
-        doc_flags.clone(),
+        doc_flags.private,
         &capturing_parser,
         &analyzer,
       )
       .await?
     }
     DocSourceFileFlag::Paths(ref source_files) => {
-      let module_graph_creator = factory.module_graph_creator().await?;
-      let fs = factory.fs();
-
-      let module_specifiers = collect_specifiers(
-        FilePatterns {
-          base: cli_options.initial_cwd().to_path_buf(),
-          include: Some(
-            PathOrPatternSet::from_include_relative_path_or_patterns(
-              cli_options.initial_cwd(),
-              source_files,
-            )?,
-          ),
-          exclude: Default::default(),
-        },
-        cli_options.vendor_dir_path().map(ToOwned::to_owned),
-        |_| true,
-      )?;
-      let graph = module_graph_creator
-        .create_graph(GraphKind::TypesOnly, module_specifiers.clone())
-        .await?;
-
-      graph_exit_integrity_errors(&graph);
-      let errors = graph_walk_errors(
-        &graph,
-        fs,
-        &module_specifiers,
-        GraphWalkErrorsOptions {
-          check_js: false,
-          kind: GraphKind::TypesOnly,
-        },
-      );
-      for error in errors {
-        log::warn!("{} {}", colors::yellow("Warning"), error);
-      }
-
-      let doc_parser = doc::DocParser::new(
-        &graph,
+      generate_doc_nodes_for_paths(
+        &factory,
+        source_files,
         &capturing_parser,
-        doc::DocParserOptions {
-          private: doc_flags.private,
-          diagnostics: doc_flags.lint,
-        },
-      )?;
-
-      let mut doc_nodes_by_url =
-        IndexMap::with_capacity(module_specifiers.len());
-
-      for module_specifier in module_specifiers {
-        let nodes = doc_parser.parse_with_reexports(&module_specifier)?;
-        doc_nodes_by_url.insert(module_specifier, nodes);
-      }
-
-      if doc_flags.lint {
-        let diagnostics = doc_parser.take_diagnostics();
-        check_diagnostics(&diagnostics)?;
-      }
-
-      doc_nodes_by_url
+        doc_flags.private,
+        doc_flags.lint,
+      )
+      .await?
     }
   };
 
   if let Some(html_options) = &doc_flags.html {
-    let deno_ns = if doc_flags.source_files != DocSourceFileFlag::Builtin {
-      let deno_ns = generate_doc_nodes_for_builtin_types(
-        doc_flags.clone(),
-        &capturing_parser,
-        &analyzer,
-      )
-      .await?;
-      let (_, deno_ns) = deno_ns.into_iter().next().unwrap();
-
-      let short_path = Rc::new(ShortPath::new(
-        ModuleSpecifier::parse("file:///lib.deno.d.ts").unwrap(),
-        None,
-        None,
-        None,
-      ));
-
-      deno_doc::html::compute_namespaced_symbols(
-        &deno_ns
-          .into_iter()
-          .map(|node| deno_doc::html::DocNodeWithContext {
-            origin: short_path.clone(),
-            ns_qualifiers: Rc::new([]),
-            kind_with_drilldown:
-              deno_doc::html::DocNodeKindWithDrilldown::Other(node.kind()),
-            inner: Rc::new(node),
-            drilldown_name: None,
-            parent: None,
-          })
-          .collect::<Vec<_>>(),
-      )
-    } else {
-      Default::default()
-    };
-
-    let mut main_entrypoint = None;
-
-    let rewrite_map =
-      if let Some(config_file) = cli_options.start_dir.maybe_deno_json() {
-        let config = config_file.to_exports_config()?;
-
-        main_entrypoint = config.get_resolved(".").ok().flatten();
-
-        let rewrite_map = config
-          .clone()
-          .into_map()
-          .into_keys()
-          .map(|key| {
-            Ok((
-              config.get_resolved(&key)?.unwrap(),
-              key
-                .strip_prefix('.')
-                .unwrap_or(&key)
-                .strip_prefix('/')
-                .unwrap_or(&key)
-                .to_owned(),
-            ))
-          })
-          .collect::<Result<IndexMap<_, _>, AnyError>>()?;
-
-        Some(rewrite_map)
-      } else {
-        None
-      };
-
-    generate_docs_directory(
+    let files = generate_html_files(
+      cli_options,
       doc_nodes_by_url,
       html_options,
-      deno_ns,
-      rewrite_map,
-      main_entrypoint,
+      doc_flags.private,
+      doc_flags.source_files == DocSourceFileFlag::Builtin,
+      &capturing_parser,
+      &analyzer,
     )
+    .await?;
+    write_docs_directory(files, html_options)
   } else {
     let modules_len = doc_nodes_by_url.len();
     let doc_nodes =
@@ -271,6 +232,86 @@ pub async fn doc(
   }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct DocOptions {
+  /// Include the symbols that aren't exported.
+  pub private: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlDocOptions {
+  /// The name of the package, shown in the page titles.
+  pub name: Option<String>,
+  /// Include the symbols that aren't exported.
+  pub private: bool,
+  /// Remove the `.html` extension from links, for servers that resolve it.
+  pub strip_trailing_html: bool,
+}
+
+/// Parses the modules matching `entrypoints` into documentation nodes, like
+/// `deno doc --json` does. Symbols re-exported by the modules are included.
+pub async fn generate_docs(
+  flags: Arc<Flags>,
+  entrypoints: Vec<String>,
+  options: DocOptions,
+) -> Result<Vec<doc::DocNode>, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let parsed_source_cache = factory.parsed_source_cache();
+  let capturing_parser = parsed_source_cache.as_capturing_parser();
+  let doc_nodes_by_url = generate_doc_nodes_for_paths(
+    &factory,
+    &entrypoints,
+    &capturing_parser,
+    options.private,
+    false,
+  )
+  .await?;
+  Ok(doc_nodes_by_url.into_values().flatten().collect())
+}
+
+/// Renders the HTML documentation of the modules matching `entrypoints`,
+/// like `deno doc --html` does. Instead of being written to a directory, the
+/// files are returned keyed by their relative path.
+pub async fn generate_html_docs(
+  flags: Arc<Flags>,
+  entrypoints: Vec<String>,
+  options: HtmlDocOptions,
+) -> Result<HashMap<String, String>, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cli_options = factory.cli_options()?;
+  let module_info_cache = factory.module_info_cache()?;
+  let parsed_source_cache = factory.parsed_source_cache();
+  let capturing_parser = parsed_source_cache.as_capturing_parser();
+  let analyzer = module_info_cache.as_module_analyzer();
+
+  let doc_nodes_by_url = generate_doc_nodes_for_paths(
+    &factory,
+    &entrypoints,
+    &capturing_parser,
+    options.private,
+    false,
+  )
+  .await?;
+  let html_options = DocHtmlFlag {
+    name: options.name,
+    category_docs_path: None,
+    symbol_redirect_map_path: None,
+    default_symbol_map_path: None,
+    strip_trailing_html: options.strip_trailing_html,
+    output: String::new(),
+  };
+  generate_html_files(
+    cli_options,
+    doc_nodes_by_url,
+    &html_options,
+    options.private,
+    false,
+    &capturing_parser,
+    &analyzer,
+  )
+  .await
+}
+
 struct DocResolver {
   deno_ns: std::collections::HashMap<Vec<String>, Option<Rc<ShortPath>>>,
   strip_trailing_html: bool,
@@ -383,16 +424,94 @@ impl UsageComposer for DocComposer {
   }
 }
 
-fn generate_docs_directory(
+/// Renders the documentation pages, keyed by their path relative to the
+/// output directory.
+async fn generate_html_files(
+  cli_options: &CliOptions,
   doc_nodes_by_url: IndexMap<ModuleSpecifier, Vec<doc::DocNode>>,
   html_options: &DocHtmlFlag,
-  deno_ns: std::collections::HashMap<Vec<String>, Option<Rc<ShortPath>>>,
+  private: bool,
+  is_builtin: bool,
+  parser: &dyn EsParser,
+  analyzer: &dyn ModuleAnalyzer,
+) -> Result<HashMap<String, String>, AnyError> {
+  let deno_ns = if !is_builtin {
+    let deno_ns =
+      generate_doc_nodes_for_builtin_types(private, parser, analyzer).await?;
+    let (_, deno_ns) = deno_ns.into_iter().next().unwrap();
+
+    let short_path = Rc::new(ShortPath::new(
+      ModuleSpecifier::parse("file:///lib.deno.d.ts").unwrap(),
+      None,
+      None,
+      None,
+    ));
+
+    deno_doc::html::compute_namespaced_symbols(
+      &deno_ns
+        .into_iter()
+        .map(|node| deno_doc::html::DocNodeWithContext {
+          origin: short_path.clone(),
+          ns_qualifiers: Rc::new([]),
+          kind_with_drilldown: deno_doc::html::DocNodeKindWithDrilldown::Other(
+            node.kind(),
+          ),
+          inner: Rc::new(node),
+          drilldown_name: None,
+          parent: None,
+        })
+        .collect::<Vec<_>>(),
+    )
+  } else {
+    Default::default()
+  };
+
+  let mut main_entrypoint = None;
+
+  let rewrite_map =
+    if let Some(config_file) = cli_options.start_dir.maybe_deno_json() {
+      let config = config_file.to_exports_config()?;
+
+      main_entrypoint = config.get_resolved(".").ok().flatten();
+
+      let rewrite_map = config
+        .clone()
+        .into_map()
+        .into_keys()
+        .map(|key| {
+          Ok((
+            config.get_resolved(&key)?.unwrap(),
+            key
+              .strip_prefix('.')
+              .unwrap_or(&key)
+              .strip_prefix('/')
+              .unwrap_or(&key)
+              .to_owned(),
+          ))
+        })
+        .collect::<Result<IndexMap<_, _>, AnyError>>()?;
+
+      Some(rewrite_map)
+    } else {
+      None
+    };
+
+  render_html_files(
+    doc_nodes_by_url,
+    html_options,
+    deno_ns,
+    rewrite_map,
+    main_entrypoint,
+  )
+}
+
+fn render_html_files(
+  doc_nodes_by_url: IndexMap<ModuleSpecifier, Vec<doc::DocNode>>,
+  html_options: &DocHtmlFlag,
+  deno_ns: HashMap<Vec<String>, Option<Rc<ShortPath>>>,
   rewrite_map: Option<IndexMap<ModuleSpecifier, String>>,
   main_entrypoint: Option<ModuleSpecifier>,
-) -> Result<(), AnyError> {
-  let cwd = std::env::current_dir().context("Failed to get CWD")?;
-  let output_dir_resolved = cwd.join(&html_options.output);
-
+) -> Result<HashMap<String, String>, AnyError> {
   let category_docs =
     if let Some(category_docs_path) = &html_options.category_docs_path {
       let content = std::fs::read(category_docs_path)?;
@@ -450,6 +569,16 @@ fn generate_docs_directory(
   files.insert("prism.js".to_string(), PRISM_JS.to_string());
   files.insert("prism.css".to_string(), PRISM_CSS.to_string());
 
+  Ok(files)
+}
+
+fn write_docs_directory(
+  files: HashMap<String, String>,
+  html_options: &DocHtmlFlag,
+) -> Result<(), AnyError> {
+  let cwd = std::env::current_dir().context("Failed to get CWD")?;
+  let output_dir_resolved = cwd.join(&html_options.output);
+
   let path = &output_dir_resolved;
   let _ = std::fs::remove_dir_all(path);
   std::fs::create_dir(path)
@@ -610,6 +610,25 @@ pub fn format_file(
   }
 }
 
+/// Formats an in-memory buffer. The extension of `path_hint` determines how
+/// `source` is parsed, it doesn't need to exist on disk. Returns `None` when
+/// the source is already formatted or its file type isn't supported.
+pub fn format_source(
+  path_hint: &Path,
+  source: &str,
+  config: &FmtOptions,
+) -> Result<Option<String>, AnyError> {
+  format_ensure_stable(path_hint, source, |file_path, file_text| {
+    format_file(
+      file_path,
+      file_text,
+      &config.options,
+      &config.unstable,
+      None,
+    )
+  })
+}
+
 pub fn format_parsed_source(
   parsed_source: &ParsedSource,
   fmt_options: &FmtOptionsConfig,
@@ -1414,4 +1433,16 @@ mod test {
       "console.log(\"there's\");\nconsole.log('hi');\nconsole.log('bye');\n",
     );
   }
+
+  #[test]
+  fn test_format_source() {
+    let config = FmtOptions::default();
+    let formatted =
+      format_source(Path::new("mod.ts"), "const a=1", &config).unwrap();
+    assert_eq!(formatted.as_deref(), Some("const a = 1;\n"));
+    let formatted =
+      format_source(Path::new("mod.ts"), "const a = 1;\n", &config).unwrap();
+    assert_eq!(formatted, None);
+    assert!(format_source(Path::new("mod.ts"), "const a = ;", &config).is_err());
+  }
 }
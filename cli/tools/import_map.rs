@@ -0,0 +1,263 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Reading, merging and writing import maps, either standalone or inlined
+//! in the `imports` and `scopes` of a `deno.json`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_graph::GraphKind;
+use import_map::ImportMap;
+use indexmap::IndexMap;
+use jsonc_parser::cst::CstObject;
+use jsonc_parser::cst::CstRootNode;
+use jsonc_parser::json;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::factory::CliFactory;
+
+/// The contents of an import map.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportMapJson {
+  #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+  pub imports: IndexMap<String, String>,
+  #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+  pub scopes: IndexMap<String, IndexMap<String, String>>,
+}
+
+impl ImportMapJson {
+  /// Parses an import map. Comments and trailing commas are allowed.
+  pub fn from_json(text: &str) -> Result<Self, AnyError> {
+    let value = jsonc_parser::parse_to_serde_value(text, &Default::default())?
+      .unwrap_or_else(|| serde_json::Value::Object(Default::default()));
+    if !value.is_object() {
+      bail!("An import map must be a JSON object.");
+    }
+    serde_json::from_value(value).context("Invalid import map.")
+  }
+
+  /// Reads an import map file.
+  pub fn read(path: &Path) -> Result<Self, AnyError> {
+    let text = std::fs::read_to_string(path)
+      .with_context(|| format!("Reading '{}'", path.display()))?;
+    Self::from_json(&text)
+      .with_context(|| format!("Parsing '{}'", path.display()))
+  }
+
+  /// Reads the `imports` and `scopes` of a `deno.json`, or the import map
+  /// file its `importMap` property points to.
+  pub fn read_from_deno_json(path: &Path) -> Result<Self, AnyError> {
+    #[derive(Deserialize)]
+    struct DenoJson {
+      #[serde(flatten)]
+      import_map: ImportMapJson,
+      #[serde(rename = "importMap")]
+      import_map_path: Option<String>,
+    }
+
+    let text = std::fs::read_to_string(path)
+      .with_context(|| format!("Reading '{}'", path.display()))?;
+    let value = jsonc_parser::parse_to_serde_value(&text, &Default::default())
+      .with_context(|| format!("Parsing '{}'", path.display()))?
+      .unwrap_or_else(|| serde_json::Value::Object(Default::default()));
+    let deno_json: DenoJson = serde_json::from_value(value)
+      .with_context(|| format!("Parsing '{}'", path.display()))?;
+    match deno_json.import_map_path {
+      Some(import_map_path)
+        if deno_json.import_map.imports.is_empty()
+          && deno_json.import_map.scopes.is_empty() =>
+      {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        Self::read(&dir.join(import_map_path))
+      }
+      _ => Ok(deno_json.import_map),
+    }
+  }
+
+  /// Adds the entries of `other`. Entries of `other` win over existing
+  /// ones with the same key.
+  pub fn merge(&mut self, other: ImportMapJson) {
+    self.imports.extend(other.imports);
+    for (scope, imports) in other.scopes {
+      self.scopes.entry(scope).or_default().extend(imports);
+    }
+  }
+
+  /// Validates the import map, resolving its entries relative to
+  /// `base_url`.
+  pub fn to_import_map(&self, base_url: Url) -> Result<ImportMap, AnyError> {
+    let value = serde_json::to_value(self)?;
+    let import_map = import_map::parse_from_value(base_url, value)?;
+    for diagnostic in &import_map.diagnostics {
+      log::warn!("Import map diagnostic: {}", diagnostic);
+    }
+    Ok(import_map.import_map)
+  }
+
+  pub fn to_json(&self) -> String {
+    let mut text = serde_json::to_string_pretty(self).unwrap();
+    text.push('\n');
+    text
+  }
+
+  pub fn write(&self, path: &Path) -> Result<(), AnyError> {
+    std::fs::write(path, self.to_json())
+      .with_context(|| format!("Writing '{}'", path.display()))
+  }
+
+  /// Replaces the `imports` and `scopes` of the `deno.json` at `path`. The
+  /// rest of the file, including comments and formatting, is kept.
+  pub fn write_to_deno_json(&self, path: &Path) -> Result<(), AnyError> {
+    let text = std::fs::read_to_string(path)
+      .with_context(|| format!("Reading config file '{}'", path.display()))?;
+    let cst = CstRootNode::parse(&text, &Default::default())
+      .with_context(|| format!("Parsing config file '{}'", path.display()))?;
+    let root_object = cst.object_value_or_set();
+
+    set_specifier_map(&root_object, "imports", &self.imports);
+    if self.scopes.is_empty() {
+      if let Some(prop) = root_object.get("scopes") {
+        prop.remove();
+      }
+    } else {
+      let scopes = root_object.object_value_or_set("scopes");
+      for prop in scopes.properties() {
+        let name = prop.name().and_then(|name| name.decoded_value().ok());
+        if !name.is_some_and(|name| self.scopes.contains_key(&name)) {
+          prop.remove();
+        }
+      }
+      for (scope, imports) in &self.scopes {
+        set_specifier_map(&scopes, scope, imports);
+      }
+    }
+
+    std::fs::write(path, cst.to_string())
+      .with_context(|| format!("Failed writing to '{}'", path.display()))
+  }
+}
+
+/// Sets the object at `name` in `object` to `map`, keeping the position of
+/// existing entries. The object is removed when `map` is empty.
+fn set_specifier_map(
+  object: &CstObject,
+  name: &str,
+  map: &IndexMap<String, String>,
+) {
+  if map.is_empty() {
+    if let Some(prop) = object.get(name) {
+      prop.remove();
+    }
+    return;
+  }
+  let specifier_map = object.object_value_or_set(name);
+  for prop in specifier_map.properties() {
+    let key = prop.name().and_then(|name| name.decoded_value().ok());
+    if !key.is_some_and(|key| map.contains_key(&key)) {
+      prop.remove();
+    }
+  }
+  for (key, value) in map {
+    if let Some(prop) = specifier_map.get(key) {
+      prop.set_value(json!(value.clone()));
+    } else {
+      let index = specifier_map.properties().len();
+      specifier_map.insert(index, key, json!(value.clone()));
+    }
+  }
+}
+
+/// Builds the module graph of `entrypoints` and returns an import map that
+/// pins what they currently resolve to: redirected remote modules map to
+/// their final url and JSR and npm version requirements map to the
+/// resolved versions.
+pub async fn pin_remote_specifiers(
+  flags: Arc<Flags>,
+  entrypoints: Vec<String>,
+) -> Result<ImportMapJson, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let graph = factory
+    .create_graph_for_embedder(GraphKind::All, &entrypoints)
+    .await?;
+
+  let mut import_map = ImportMapJson::default();
+  for (from, to) in &graph.redirects {
+    if matches!(from.scheme(), "http" | "https") {
+      import_map.imports.insert(from.to_string(), to.to_string());
+    }
+  }
+  let mut pin_package = |scheme: &str, req: String, nv: String| {
+    import_map
+      .imports
+      .insert(format!("{scheme}:{req}"), format!("{scheme}:{nv}"));
+    import_map
+      .imports
+      .insert(format!("{scheme}:{req}/"), format!("{scheme}:{nv}/"));
+  };
+  for (req, nv) in graph.packages.mappings() {
+    pin_package("jsr", req.to_string(), nv.to_string());
+  }
+  if let Some(npm_resolver) = factory.npm_resolver().await?.as_managed() {
+    let snapshot = npm_resolver.snapshot();
+    for (req, nv) in snapshot.package_reqs() {
+      pin_package("npm", req.to_string(), nv.to_string());
+    }
+  }
+  import_map.imports.sort_keys();
+  Ok(import_map)
+}
+
+#[cfg(test)]
+mod tests {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  fn merge_and_write_to_deno_json() {
+    let mut import_map = ImportMapJson::from_json(
+      r#"{
+        // comment
+        "imports": { "a": "./a.ts", "b": "./b.ts" },
+      }"#,
+    )
+    .unwrap();
+    import_map.merge(ImportMapJson {
+      imports: IndexMap::from([("b".to_string(), "./b2.ts".to_string())]),
+      scopes: IndexMap::from([(
+        "./vendor/".to_string(),
+        IndexMap::from([("c".to_string(), "./c.ts".to_string())]),
+      )]),
+    });
+    assert_eq!(import_map.imports["b"], "./b2.ts");
+
+    let temp_dir = TempDir::new();
+    let deno_json = temp_dir.path().join("deno.json");
+    deno_json.write(
+      r#"{
+  // keep me
+  "tasks": {},
+  "imports": {
+    "a": "./old.ts",
+    "removed": "./removed.ts"
+  }
+}
+"#,
+    );
+    import_map.write_to_deno_json(deno_json.as_path()).unwrap();
+    let text = deno_json.read_to_string();
+    assert!(text.contains("// keep me"), "{}", text);
+    assert!(!text.contains("removed"), "{}", text);
+    assert_eq!(
+      ImportMapJson::read_from_deno_json(deno_json.as_path()).unwrap(),
+      import_map
+    );
+  }
+}
@@ -1,11 +1,13 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Write;
 use std::sync::Arc;
 
+use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
@@ -26,6 +28,7 @@ use deno_semver::npm::NpmPackageNvReference;
 use deno_semver::npm::NpmPackageReqReference;
 use deno_semver::package::PackageNv;
 use deno_terminal::colors;
+use serde::Serialize;
 
 use crate::args::Flags;
 use crate::args::InfoFlags;
@@ -800,3 +803,190 @@ fn maybe_size_to_text(maybe_size: Option<u64>) -> String {
   ))
   .to_string()
 }
+
+/// The resolved dependency graph of a program, see
+/// [`build_graph_for_embedder`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleGraphInfo {
+  pub roots: Vec<ModuleSpecifier>,
+  /// Every module in the graph, including those only imported dynamically.
+  pub modules: Vec<ModuleGraphModule>,
+  /// npm packages imported by the program, eg. `"chalk@5.3.0"`. Their own
+  /// dependencies are not included.
+  pub npm_packages: Vec<String>,
+  /// JSR packages, eg. `"@std/path@1.0.8"`.
+  pub jsr_packages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleGraphModule {
+  pub specifier: ModuleSpecifier,
+  pub media_type: MediaType,
+  /// Size of the source in bytes. `None` for npm packages, built-in node
+  /// modules and external modules.
+  pub size: Option<u64>,
+  pub dependencies: Vec<ModuleGraphDependency>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleGraphDependency {
+  /// The specifier as written in the source.
+  pub specifier: String,
+  /// The module the import resolved to.
+  pub code: Option<ModuleSpecifier>,
+  /// The module providing types for the import, eg. from `@deno-types`.
+  pub types: Option<ModuleSpecifier>,
+  pub is_dynamic: bool,
+}
+
+/// Builds the dependency graph of `entrypoint` the way `deno info` does,
+/// with the configuration resolved from `flags`. Errors when a module
+/// can't be loaded or resolved.
+pub async fn build_graph_for_embedder(
+  flags: Arc<Flags>,
+  entrypoint: String,
+) -> Result<ModuleGraphInfo, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let graph = factory
+    .create_graph_for_embedder(GraphKind::All, &[entrypoint])
+    .await?;
+  Ok(module_graph_info(&graph))
+}
+
+fn module_graph_info(graph: &ModuleGraph) -> ModuleGraphInfo {
+  fn to_dependency(
+    specifier: &str,
+    dep: &deno_graph::Dependency,
+  ) -> ModuleGraphDependency {
+    ModuleGraphDependency {
+      specifier: specifier.to_string(),
+      code: dep.maybe_code.maybe_specifier().cloned(),
+      types: dep.maybe_type.maybe_specifier().cloned(),
+      is_dynamic: dep.is_dynamic,
+    }
+  }
+
+  let mut npm_packages = BTreeSet::new();
+  let modules = graph
+    .modules()
+    .map(|module| {
+      let (media_type, size, dependencies) = match module {
+        Module::Js(module) => (
+          module.media_type,
+          Some(module.size() as u64),
+          module
+            .dependencies
+            .iter()
+            .map(|(specifier, dep)| to_dependency(specifier, dep))
+            .collect(),
+        ),
+        Module::Json(module) => {
+          (module.media_type, Some(module.size() as u64), Vec::new())
+        }
+        Module::Wasm(module) => (
+          MediaType::Wasm,
+          Some(module.size() as u64),
+          module
+            .dependencies
+            .iter()
+            .map(|(specifier, dep)| to_dependency(specifier, dep))
+            .collect(),
+        ),
+        Module::Npm(module) => {
+          npm_packages.insert(module.nv_reference.nv().to_string());
+          (MediaType::Unknown, None, Vec::new())
+        }
+        Module::Node(_) | Module::External(_) => {
+          (MediaType::Unknown, None, Vec::new())
+        }
+      };
+      ModuleGraphModule {
+        specifier: module.specifier().clone(),
+        media_type,
+        size,
+        dependencies,
+      }
+    })
+    .collect();
+  let jsr_packages = graph
+    .packages
+    .mappings()
+    .values()
+    .map(|nv| nv.to_string())
+    .collect::<BTreeSet<_>>();
+  ModuleGraphInfo {
+    roots: graph.roots.iter().cloned().collect(),
+    modules,
+    npm_packages: npm_packages.into_iter().collect(),
+    jsr_packages: jsr_packages.into_iter().collect(),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[tokio::test]
+  async fn module_graph_info_lists_modules() {
+    let loader = deno_graph::source::MemoryLoader::new(
+      vec![
+        (
+          "file:///main.ts",
+          deno_graph::source::Source::Module {
+            specifier: "file:///main.ts",
+            maybe_headers: None,
+            content: "import './a.ts';\nawait import('./b.json', { with: { type: 'json' } });",
+          },
+        ),
+        (
+          "file:///a.ts",
+          deno_graph::source::Source::Module {
+            specifier: "file:///a.ts",
+            maybe_headers: None,
+            content: "export const a = 1;",
+          },
+        ),
+        (
+          "file:///b.json",
+          deno_graph::source::Source::Module {
+            specifier: "file:///b.json",
+            maybe_headers: None,
+            content: "{}",
+          },
+        ),
+      ],
+      Vec::new(),
+    );
+    let root = ModuleSpecifier::parse("file:///main.ts").unwrap();
+    let mut graph = ModuleGraph::new(GraphKind::All);
+    graph
+      .build(vec![root.clone()], &loader, Default::default())
+      .await;
+
+    let info = module_graph_info(&graph);
+    assert_eq!(info.roots, vec![root]);
+    assert!(info.npm_packages.is_empty());
+    let main = info
+      .modules
+      .iter()
+      .find(|m| m.specifier.as_str() == "file:///main.ts")
+      .unwrap();
+    assert_eq!(main.media_type, MediaType::TypeScript);
+    let dynamic = main
+      .dependencies
+      .iter()
+      .map(|dep| (dep.specifier.as_str(), dep.is_dynamic))
+      .collect::<Vec<_>>();
+    assert_eq!(dynamic, vec![("./a.ts", false), ("./b.json", true)]);
+    let json = info
+      .modules
+      .iter()
+      .find(|m| m.specifier.as_str() == "file:///b.json")
+      .unwrap();
+    assert_eq!(json.media_type, MediaType::Json);
+    assert_eq!(json.size, Some(2));
+  }
+}
@@ -5,8 +5,13 @@ use std::sync::Arc;
 use crate::args::Flags;
 use crate::args::JupyterFlags;
 use crate::cdp;
-use crate::lsp::ReplCompletionItem;
 use crate::ops;
+use crate::tools::kernel::CompleteReply;
+use crate::tools::kernel::ExecuteOutcome;
+use crate::tools::kernel::InspectReply;
+use crate::tools::kernel::InterruptHandle;
+use crate::tools::kernel::Kernel;
+use crate::tools::kernel::ReplKernel;
 use crate::tools::repl;
 use crate::tools::test::create_single_test_event_channel;
 use crate::tools::test::reporters::PrettyTestReporter;
@@ -160,12 +165,17 @@ pub async fn kernel(
   let (startup_data_tx, startup_data_rx) =
     oneshot::channel::<server::StartupData>();
 
+  let kernel = ReplKernel::new(repl_session);
+  let repl_session_proxy_channels = JupyterReplProxy {
+    tx: tx1,
+    rx: rx2,
+    interrupt_handle: kernel.interrupt_handle(),
+  };
   let mut repl_session_proxy = JupyterReplSession {
-    repl_session,
+    kernel,
     rx: rx1,
     tx: tx2,
   };
-  let repl_session_proxy_channels = JupyterReplProxy { tx: tx1, rx: rx2 };
 
   let join_handle = std::thread::spawn(move || {
     let fut = server::JupyterServer::start(
@@ -182,8 +192,12 @@ pub async fn kernel(
     bail!("Failed to acquire startup data");
   };
   {
-    let op_state_rc =
-      repl_session_proxy.repl_session.worker.js_runtime.op_state();
+    let op_state_rc = repl_session_proxy
+      .kernel
+      .session()
+      .worker
+      .js_runtime
+      .op_state();
     let mut op_state = op_state_rc.borrow_mut();
     op_state.put(startup_data.iopub_connection.clone());
     op_state.put(startup_data.last_execution_request.clone());
@@ -205,152 +219,124 @@ pub async fn kernel(
 }
 
 pub enum JupyterReplRequest {
-  LspCompletions {
-    line_text: String,
-    position: usize,
-  },
-  JsGetProperties {
-    object_id: String,
-  },
-  JsEvaluate {
-    expr: String,
+  Execute {
+    code: String,
   },
-  JsGlobalLexicalScopeNames,
-  JsEvaluateLineWithObjectWrapping {
-    line: String,
+  Complete {
+    code: String,
+    cursor_pos: usize,
   },
-  JsCallFunctionOnArgs {
-    function_declaration: String,
-    args: Vec<cdp::RemoteObject>,
+  Inspect {
+    code: String,
+    cursor_pos: usize,
+    detail_level: u8,
   },
   JsCallFunctionOn {
-    arg0: cdp::CallArgument,
-    arg1: cdp::CallArgument,
+    arg0: Box<cdp::CallArgument>,
+    arg1: Box<cdp::CallArgument>,
   },
 }
 
 pub enum JupyterReplResponse {
-  LspCompletions(Vec<ReplCompletionItem>),
-  JsGetProperties(Option<cdp::GetPropertiesResponse>),
-  JsEvaluate(Option<cdp::EvaluateResponse>),
-  JsGlobalLexicalScopeNames(cdp::GlobalLexicalScopeNamesResponse),
-  JsEvaluateLineWithObjectWrapping(Result<repl::TsEvaluateResponse, AnyError>),
-  JsCallFunctionOnArgs(Result<cdp::CallFunctionOnResponse, AnyError>),
-  JsCallFunctionOn(Option<cdp::CallFunctionOnResponse>),
+  Execute(Result<ExecuteOutcome, AnyError>),
+  Complete(Result<CompleteReply, AnyError>),
+  Inspect(Result<InspectReply, AnyError>),
+  JsCallFunctionOn(Option<Box<cdp::CallFunctionOnResponse>>),
 }
 
+/// The kernel as seen from the server thread. It forwards the requests to
+/// the [`JupyterReplSession`] on the thread of the worker.
 pub struct JupyterReplProxy {
   tx: mpsc::UnboundedSender<JupyterReplRequest>,
   rx: mpsc::UnboundedReceiver<JupyterReplResponse>,
+  interrupt_handle: InterruptHandle,
 }
 
 impl JupyterReplProxy {
-  pub async fn lsp_completions(
+  async fn request(
     &mut self,
-    line_text: String,
-    position: usize,
-  ) -> Vec<ReplCompletionItem> {
-    let _ = self.tx.send(JupyterReplRequest::LspCompletions {
-      line_text,
-      position,
-    });
-    let Some(JupyterReplResponse::LspCompletions(resp)) = self.rx.recv().await
-    else {
+    request: JupyterReplRequest,
+  ) -> JupyterReplResponse {
+    let _ = self.tx.send(request);
+    let Some(resp) = self.rx.recv().await else {
       unreachable!()
     };
     resp
   }
 
-  pub async fn get_properties(
-    &mut self,
-    object_id: String,
-  ) -> Option<cdp::GetPropertiesResponse> {
-    let _ = self
-      .tx
-      .send(JupyterReplRequest::JsGetProperties { object_id });
-    let Some(JupyterReplResponse::JsGetProperties(resp)) = self.rx.recv().await
-    else {
-      unreachable!()
-    };
-    resp
-  }
-
-  pub async fn evaluate(
+  // TODO(bartlomieju): rename to "broadcast_result"?
+  pub async fn call_function_on(
     &mut self,
-    expr: String,
-  ) -> Option<cdp::EvaluateResponse> {
-    let _ = self.tx.send(JupyterReplRequest::JsEvaluate { expr });
-    let Some(JupyterReplResponse::JsEvaluate(resp)) = self.rx.recv().await
+    arg0: cdp::CallArgument,
+    arg1: cdp::CallArgument,
+  ) -> Option<cdp::CallFunctionOnResponse> {
+    let JupyterReplResponse::JsCallFunctionOn(resp) = self
+      .request(JupyterReplRequest::JsCallFunctionOn {
+        arg0: Box::new(arg0),
+        arg1: Box::new(arg1),
+      })
+      .await
     else {
       unreachable!()
     };
-    resp
+    resp.map(|resp| *resp)
   }
+}
 
-  pub async fn global_lexical_scope_names(
-    &mut self,
-  ) -> cdp::GlobalLexicalScopeNamesResponse {
-    let _ = self.tx.send(JupyterReplRequest::JsGlobalLexicalScopeNames);
-    let Some(JupyterReplResponse::JsGlobalLexicalScopeNames(resp)) =
-      self.rx.recv().await
+#[async_trait::async_trait(?Send)]
+impl Kernel for JupyterReplProxy {
+  async fn execute(&mut self, code: &str) -> Result<ExecuteOutcome, AnyError> {
+    let code = code.to_string();
+    let JupyterReplResponse::Execute(resp) =
+      self.request(JupyterReplRequest::Execute { code }).await
     else {
       unreachable!()
     };
     resp
   }
 
-  pub async fn evaluate_line_with_object_wrapping(
+  async fn complete(
     &mut self,
-    line: String,
-  ) -> Result<repl::TsEvaluateResponse, AnyError> {
-    let _ = self
-      .tx
-      .send(JupyterReplRequest::JsEvaluateLineWithObjectWrapping { line });
-    let Some(JupyterReplResponse::JsEvaluateLineWithObjectWrapping(resp)) =
-      self.rx.recv().await
+    code: &str,
+    cursor_pos: usize,
+  ) -> Result<CompleteReply, AnyError> {
+    let code = code.to_string();
+    let JupyterReplResponse::Complete(resp) = self
+      .request(JupyterReplRequest::Complete { code, cursor_pos })
+      .await
     else {
       unreachable!()
     };
     resp
   }
 
-  pub async fn call_function_on_args(
+  async fn inspect(
     &mut self,
-    function_declaration: String,
-    args: Vec<cdp::RemoteObject>,
-  ) -> Result<cdp::CallFunctionOnResponse, AnyError> {
-    let _ = self.tx.send(JupyterReplRequest::JsCallFunctionOnArgs {
-      function_declaration,
-      args,
-    });
-    let Some(JupyterReplResponse::JsCallFunctionOnArgs(resp)) =
-      self.rx.recv().await
+    code: &str,
+    cursor_pos: usize,
+    detail_level: u8,
+  ) -> Result<InspectReply, AnyError> {
+    let code = code.to_string();
+    let JupyterReplResponse::Inspect(resp) = self
+      .request(JupyterReplRequest::Inspect {
+        code,
+        cursor_pos,
+        detail_level,
+      })
+      .await
     else {
       unreachable!()
     };
     resp
   }
 
-  // TODO(bartlomieju): rename to "broadcast_result"?
-  pub async fn call_function_on(
-    &mut self,
-    arg0: cdp::CallArgument,
-    arg1: cdp::CallArgument,
-  ) -> Option<cdp::CallFunctionOnResponse> {
-    let _ = self
-      .tx
-      .send(JupyterReplRequest::JsCallFunctionOn { arg0, arg1 });
-    let Some(JupyterReplResponse::JsCallFunctionOn(resp)) =
-      self.rx.recv().await
-    else {
-      unreachable!()
-    };
-    resp
+  fn interrupt_handle(&self) -> InterruptHandle {
+    self.interrupt_handle.clone()
   }
 }
 
 pub struct JupyterReplSession {
-  repl_session: repl::ReplSession,
+  kernel: ReplKernel,
   rx: mpsc::UnboundedReceiver<JupyterReplRequest>,
   tx: mpsc::UnboundedSender<JupyterReplResponse>,
 }
@@ -371,7 +357,7 @@ impl JupyterReplSession {
           }
           poll_worker = true;
         },
-        _ = self.repl_session.run_event_loop(), if poll_worker => {
+        _ = self.kernel.run_event_loop(), if poll_worker => {
           poll_worker = false;
         }
       }
@@ -383,41 +369,24 @@ impl JupyterReplSession {
     msg: JupyterReplRequest,
   ) -> Result<(), AnyError> {
     let resp = match msg {
-      JupyterReplRequest::LspCompletions {
-        line_text,
-        position,
-      } => JupyterReplResponse::LspCompletions(
-        self.lsp_completions(&line_text, position).await,
-      ),
-      JupyterReplRequest::JsGetProperties { object_id } => {
-        JupyterReplResponse::JsGetProperties(
-          self.get_properties(object_id).await,
-        )
-      }
-      JupyterReplRequest::JsEvaluate { expr } => {
-        JupyterReplResponse::JsEvaluate(self.evaluate(expr).await)
-      }
-      JupyterReplRequest::JsGlobalLexicalScopeNames => {
-        JupyterReplResponse::JsGlobalLexicalScopeNames(
-          self.global_lexical_scope_names().await,
-        )
+      JupyterReplRequest::Execute { code } => {
+        JupyterReplResponse::Execute(self.kernel.execute(&code).await)
       }
-      JupyterReplRequest::JsEvaluateLineWithObjectWrapping { line } => {
-        JupyterReplResponse::JsEvaluateLineWithObjectWrapping(
-          self.evaluate_line_with_object_wrapping(&line).await,
+      JupyterReplRequest::Complete { code, cursor_pos } => {
+        JupyterReplResponse::Complete(
+          self.kernel.complete(&code, cursor_pos).await,
         )
       }
-      JupyterReplRequest::JsCallFunctionOnArgs {
-        function_declaration,
-        args,
-      } => JupyterReplResponse::JsCallFunctionOnArgs(
-        self
-          .call_function_on_args(function_declaration, &args)
-          .await,
+      JupyterReplRequest::Inspect {
+        code,
+        cursor_pos,
+        detail_level,
+      } => JupyterReplResponse::Inspect(
+        self.kernel.inspect(&code, cursor_pos, detail_level).await,
       ),
       JupyterReplRequest::JsCallFunctionOn { arg0, arg1 } => {
         JupyterReplResponse::JsCallFunctionOn(
-          self.call_function_on(arg0, arg1).await,
+          self.call_function_on(*arg0, *arg1).await.map(Box::new),
         )
       }
     };
@@ -425,114 +394,15 @@ impl JupyterReplSession {
     self.tx.send(resp).map_err(|e| e.into())
   }
 
-  pub async fn lsp_completions(
-    &mut self,
-    line_text: &str,
-    position: usize,
-  ) -> Vec<ReplCompletionItem> {
-    self
-      .repl_session
-      .language_server
-      .completions(line_text, position)
-      .await
-  }
-
-  pub async fn get_properties(
-    &mut self,
-    object_id: String,
-  ) -> Option<cdp::GetPropertiesResponse> {
-    let get_properties_response = self
-      .repl_session
-      .post_message_with_event_loop(
-        "Runtime.getProperties",
-        Some(cdp::GetPropertiesArgs {
-          object_id,
-          own_properties: None,
-          accessor_properties_only: None,
-          generate_preview: None,
-          non_indexed_properties_only: Some(true),
-        }),
-      )
-      .await
-      .ok()?;
-    serde_json::from_value(get_properties_response).ok()
-  }
-
-  pub async fn evaluate(
-    &mut self,
-    expr: String,
-  ) -> Option<cdp::EvaluateResponse> {
-    let evaluate_response: serde_json::Value = self
-      .repl_session
-      .post_message_with_event_loop(
-        "Runtime.evaluate",
-        Some(cdp::EvaluateArgs {
-          expression: expr,
-          object_group: None,
-          include_command_line_api: None,
-          silent: None,
-          context_id: Some(self.repl_session.context_id),
-          return_by_value: None,
-          generate_preview: None,
-          user_gesture: None,
-          await_promise: None,
-          throw_on_side_effect: Some(true),
-          timeout: Some(200),
-          disable_breaks: None,
-          repl_mode: None,
-          allow_unsafe_eval_blocked_by_csp: None,
-          unique_context_id: None,
-        }),
-      )
-      .await
-      .ok()?;
-    serde_json::from_value(evaluate_response).ok()
-  }
-
-  pub async fn global_lexical_scope_names(
-    &mut self,
-  ) -> cdp::GlobalLexicalScopeNamesResponse {
-    let evaluate_response = self
-      .repl_session
-      .post_message_with_event_loop(
-        "Runtime.globalLexicalScopeNames",
-        Some(cdp::GlobalLexicalScopeNamesArgs {
-          execution_context_id: Some(self.repl_session.context_id),
-        }),
-      )
-      .await
-      .unwrap();
-    serde_json::from_value(evaluate_response).unwrap()
-  }
-
-  pub async fn evaluate_line_with_object_wrapping(
-    &mut self,
-    line: &str,
-  ) -> Result<repl::TsEvaluateResponse, AnyError> {
-    self
-      .repl_session
-      .evaluate_line_with_object_wrapping(line)
-      .await
-  }
-
-  pub async fn call_function_on_args(
-    &mut self,
-    function_declaration: String,
-    args: &[cdp::RemoteObject],
-  ) -> Result<cdp::CallFunctionOnResponse, AnyError> {
-    self
-      .repl_session
-      .call_function_on_args(function_declaration, args)
-      .await
-  }
-
   // TODO(bartlomieju): rename to "broadcast_result"?
   pub async fn call_function_on(
     &mut self,
     arg0: cdp::CallArgument,
     arg1: cdp::CallArgument,
   ) -> Option<cdp::CallFunctionOnResponse> {
-    let response = self.repl_session
+    let repl_session = self.kernel.session();
+    let context_id = repl_session.context_id;
+    let response = repl_session
     .post_message_with_event_loop(
       "Runtime.callFunctionOn",
       Some(json!({
@@ -540,7 +410,7 @@ impl JupyterReplSession {
           await Deno[Deno.internal].jupyter.broadcastResult(execution_count, result);
     }"#,
         "arguments": [arg0, arg1],
-        "executionContextId": self.repl_session.context_id,
+        "executionContextId": context_id,
         "awaitPromise": true,
       })),
     )
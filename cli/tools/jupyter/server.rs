@@ -12,7 +12,9 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 use crate::cdp;
-use crate::tools::repl;
+use crate::tools::kernel::ExecuteOutcome;
+use crate::tools::kernel::InterruptHandle;
+use crate::tools::kernel::Kernel;
 use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::futures;
@@ -101,6 +103,7 @@ impl JupyterServer {
     };
 
     let cancel_handle = CancelHandle::new_rc();
+    let interrupt_handle = repl_session_proxy.interrupt_handle();
 
     let mut server = Self {
       execution_count: ExecutionCount::new(0),
@@ -142,8 +145,12 @@ impl JupyterServer {
     let control_fut = deno_core::unsync::spawn({
       let cancel_handle = cancel_handle.clone();
       async move {
-        if let Err(err) =
-          Self::handle_control(control_connection, cancel_handle).await
+        if let Err(err) = Self::handle_control(
+          control_connection,
+          cancel_handle,
+          interrupt_handle,
+        )
+        .await
         {
           log::error!(
             "Control error: {}\nBacktrace:\n{}",
@@ -212,6 +219,7 @@ impl JupyterServer {
   async fn handle_control(
     mut connection: KernelControlConnection,
     cancel_handle: Rc<CancelHandle>,
+    interrupt_handle: InterruptHandle,
   ) -> Result<(), AnyError> {
     loop {
       let msg = connection.read().await?;
@@ -227,7 +235,10 @@ impl JupyterServer {
           cancel_handle.cancel();
         }
         JupyterMessageContent::InterruptRequest(_) => {
-          log::error!("Interrupt request currently not supported");
+          interrupt_handle.interrupt();
+          connection
+            .send(messaging::InterruptReply::new().as_child_of(&msg))
+            .await?;
         }
         JupyterMessageContent::DebugRequest(_) => {
           log::error!("Debug request currently not supported");
@@ -272,137 +283,37 @@ impl JupyterServer {
           .await?;
       }
       JupyterMessageContent::CompleteRequest(req) => {
-        let user_code = req.code;
-        let cursor_pos = req.cursor_pos;
-
-        let lsp_completions = self
+        let reply = self
           .repl_session_proxy
-          .lsp_completions(user_code.clone(), cursor_pos)
-          .await;
-
-        if !lsp_completions.is_empty() {
-          let matches: Vec<String> = lsp_completions
-            .iter()
-            .map(|item| item.new_text.clone())
-            .collect();
-
-          let cursor_start = lsp_completions
-            .first()
-            .map(|item| item.range.start)
-            .unwrap_or(cursor_pos);
-
-          let cursor_end = lsp_completions
-            .last()
-            .map(|item| item.range.end)
-            .unwrap_or(cursor_pos);
-
-          connection
-            .send(
-              messaging::CompleteReply {
-                matches,
-                cursor_start,
-                cursor_end,
-                metadata: Default::default(),
-                status: ReplyStatus::Ok,
-                error: None,
-              }
-              .as_child_of(parent),
-            )
-            .await?;
-        } else {
-          let expr = get_expr_from_line_at_pos(&user_code, cursor_pos);
-          // check if the expression is in the form `obj.prop`
-          let (completions, cursor_start) = if let Some(index) = expr.rfind('.')
-          {
-            let sub_expr = &expr[..index];
-            let prop_name = &expr[index + 1..];
-            let candidates = get_expression_property_names(
-              &mut self.repl_session_proxy,
-              sub_expr,
-            )
-            .await
-            .into_iter()
-            .filter(|n| {
-              !n.starts_with("Symbol(")
-                && n.starts_with(prop_name)
-                && n != &*repl::REPL_INTERNALS_NAME
-            })
-            .collect();
-
-            if prop_name.len() > cursor_pos {
-              // TODO(bartlomieju): most likely not correct, but better than panicking because of sub with overflow
-              (candidates, cursor_pos)
-            } else {
-              (candidates, cursor_pos - prop_name.len())
-            }
-          } else {
-            // combine results of declarations and globalThis properties
-            let mut candidates = get_expression_property_names(
-              &mut self.repl_session_proxy,
-              "globalThis",
-            )
-            .await
-            .into_iter()
-            .chain(
-              get_global_lexical_scope_names(&mut self.repl_session_proxy)
-                .await,
-            )
-            .filter(|n| n.starts_with(expr) && n != &*repl::REPL_INTERNALS_NAME)
-            .collect::<Vec<_>>();
-
-            // sort and remove duplicates
-            candidates.sort();
-            candidates.dedup(); // make sure to sort first
-
-            if expr.len() > cursor_pos {
-              // TODO(bartlomieju): most likely not correct, but better than panicking because of sub with overflow
-              (candidates, cursor_pos)
-            } else {
-              (candidates, cursor_pos - expr.len())
+          .complete(&req.code, req.cursor_pos)
+          .await?;
+        connection
+          .send(
+            messaging::CompleteReply {
+              matches: reply.matches,
+              cursor_start: reply.cursor_start,
+              cursor_end: reply.cursor_end,
+              metadata: Default::default(),
+              status: ReplyStatus::Ok,
+              error: None,
             }
-          };
-
-          connection
-            .send(
-              messaging::CompleteReply {
-                matches: completions,
-                cursor_start,
-                cursor_end: cursor_pos,
-                metadata: Default::default(),
-                status: ReplyStatus::Ok,
-                error: None,
-              }
-              .as_child_of(parent),
-            )
-            .await?;
-        }
+            .as_child_of(parent),
+          )
+          .await?;
       }
 
-      JupyterMessageContent::InspectRequest(_req) => {
-        // TODO(bartlomieju?): implement introspection request
-        // The inspect request is used to get information about an object at cursor position.
-        // There are two detail levels: 0 is typically documentation, 1 is typically source code
-
-        // The response includes a MimeBundle to render the object:
-        // {
-        //   "status": "ok",
-        //   "found": true,
-        //   "data": {
-        //     "text/plain": "Plain documentation here",
-        //     "text/html": "<div>Rich documentation here</div>",
-        //     "application/json": {
-        //       "key1": "value1",
-        //       "key2": "value2"
-        //     }
-        //   },
-        // }
-
+      JupyterMessageContent::InspectRequest(req) => {
+        let detail_level = req.detail_level.unwrap_or(0).min(1) as u8;
+        let reply = self
+          .repl_session_proxy
+          .inspect(&req.code, req.cursor_pos, detail_level)
+          .await?;
         connection
           .send(
             messaging::InspectReply {
               status: ReplyStatus::Ok,
-              found: false,
-              data: Default::default(),
+              found: reply.found,
+              data: serde_json::from_value(serde_json::to_value(reply.data)?)?,
               metadata: Default::default(),
               error: None,
             }
@@ -499,13 +410,10 @@ impl JupyterServer {
       )
       .await?;
 
-    let result = self
-      .repl_session_proxy
-      .evaluate_line_with_object_wrapping(execute_request.code)
-      .await;
+    let result = self.repl_session_proxy.execute(&execute_request.code).await;
 
-    let evaluate_response = match result {
-      Ok(eval_response) => eval_response,
+    let outcome = match result {
+      Ok(outcome) => outcome,
       Err(err) => {
         self
           .send_iopub(
@@ -533,135 +441,61 @@ impl JupyterServer {
       }
     };
 
-    let cdp::EvaluateResponse {
-      result,
-      exception_details,
-    } = evaluate_response.value;
-
-    if exception_details.is_none() {
-      publish_result(
-        &mut self.repl_session_proxy,
-        &result,
-        self.execution_count,
-      )
-      .await?;
-
-      connection
-        .send(
-          messaging::ExecuteReply {
-            execution_count: self.execution_count,
-            status: ReplyStatus::Ok,
-            user_expressions: None,
-            payload: Default::default(),
-            error: None,
-          }
-          .as_child_of(parent_message),
+    match outcome {
+      ExecuteOutcome::Ok(value) => {
+        publish_result(
+          &mut self.repl_session_proxy,
+          &value.remote_object,
+          self.execution_count,
         )
         .await?;
-      // Let's sleep here for a few ms, so we give a chance to the task that is
-      // handling stdout and stderr streams to receive and flush the content.
-      // Otherwise, executing multiple cells one-by-one might lead to output
-      // from various cells be grouped together in another cell result.
-      tokio::time::sleep(std::time::Duration::from_millis(5)).await;
-    } else if let Some(exception_details) = exception_details {
-      // Determine the exception value and name
-      let (name, message, stack) = if let Some(exception) =
-        exception_details.exception
-      {
-        let result = self
-          .repl_session_proxy
-          .call_function_on_args(
-            r#"
-          function(object) {
-            if (object instanceof Error) {
-              const name = "name" in object ? String(object.name) : "";
-              const message = "message" in object ? String(object.message) : "";
-              const stack = "stack" in object ? String(object.stack) : "";
-              return JSON.stringify({ name, message, stack });
-            } else {
-              const message = String(object);
-              return JSON.stringify({ name: "", message, stack: "" });
+
+        connection
+          .send(
+            messaging::ExecuteReply {
+              execution_count: self.execution_count,
+              status: ReplyStatus::Ok,
+              user_expressions: None,
+              payload: Default::default(),
+              error: None,
             }
-          }
-        "#
-            .into(),
-            vec![exception],
+            .as_child_of(parent_message),
           )
           .await?;
-
-        match result.result.value {
-          Some(serde_json::Value::String(str)) => {
-            if let Ok(object) =
-              serde_json::from_str::<HashMap<String, String>>(&str)
-            {
-              let get = |k| object.get(k).cloned().unwrap_or_default();
-              (get("name"), get("message"), get("stack"))
-            } else {
-              log::error!("Unexpected result while parsing JSON {str}");
-              ("".into(), "".into(), "".into())
+        // Let's sleep here for a few ms, so we give a chance to the task that is
+        // handling stdout and stderr streams to receive and flush the content.
+        // Otherwise, executing multiple cells one-by-one might lead to output
+        // from various cells be grouped together in another cell result.
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+      }
+      ExecuteOutcome::Error(error) => {
+        self
+          .send_iopub(
+            messaging::ErrorOutput {
+              ename: error.name.clone(),
+              evalue: error.message.clone(),
+              traceback: error.traceback.clone(),
             }
-          }
-          _ => {
-            log::error!("Unexpected result while parsing exception {result:?}");
-            ("".into(), "".into(), "".into())
-          }
-        }
-      } else {
-        log::error!("Unexpectedly missing exception {exception_details:?}");
-        ("".into(), "".into(), "".into())
-      };
-
-      let stack = if stack.is_empty() {
-        format!(
-          "{}\n    at <unknown>",
-          serde_json::to_string(&message).unwrap()
-        )
-      } else {
-        stack
-      };
-      let traceback = format!("Stack trace:\n{stack}")
-        .split('\n')
-        .map(|s| s.to_owned())
-        .collect::<Vec<_>>();
-
-      let ename = if name.is_empty() {
-        "Unknown error".into()
-      } else {
-        name
-      };
-
-      let evalue = if message.is_empty() {
-        "(none)".into()
-      } else {
-        message
-      };
-
-      self
-        .send_iopub(
-          messaging::ErrorOutput {
-            ename: ename.clone(),
-            evalue: evalue.clone(),
-            traceback: traceback.clone(),
-          }
-          .as_child_of(parent_message),
-        )
-        .await?;
-      connection
-        .send(
-          messaging::ExecuteReply {
-            execution_count: self.execution_count,
-            status: ReplyStatus::Error,
-            error: Some(Box::new(ReplyError {
-              ename,
-              evalue,
-              traceback,
-            })),
-            user_expressions: None,
-            payload: Default::default(),
-          }
-          .as_child_of(parent_message),
-        )
-        .await?;
+            .as_child_of(parent_message),
+          )
+          .await?;
+        connection
+          .send(
+            messaging::ExecuteReply {
+              execution_count: self.execution_count,
+              status: ReplyStatus::Error,
+              error: Some(Box::new(ReplyError {
+                ename: error.name,
+                evalue: error.message,
+                traceback: error.traceback,
+              })),
+              user_expressions: None,
+              payload: Default::default(),
+            }
+            .as_child_of(parent_message),
+          )
+          .await?;
+      }
     }
 
     Ok(())
@@ -727,107 +561,3 @@ async fn publish_result(
 
   Ok(None)
 }
-
-// TODO(bartlomieju): dedup with repl::editor
-fn get_expr_from_line_at_pos(line: &str, cursor_pos: usize) -> &str {
-  let start = line[..cursor_pos].rfind(is_word_boundary).unwrap_or(0);
-  let end = line[cursor_pos..]
-    .rfind(is_word_boundary)
-    .map(|i| cursor_pos + i)
-    .unwrap_or(cursor_pos);
-
-  let word = &line[start..end];
-  let word = word.strip_prefix(is_word_boundary).unwrap_or(word);
-  let word = word.strip_suffix(is_word_boundary).unwrap_or(word);
-
-  word
-}
-
-// TODO(bartlomieju): dedup with repl::editor
-fn is_word_boundary(c: char) -> bool {
-  if matches!(c, '.' | '_' | '$') {
-    false
-  } else {
-    char::is_ascii_whitespace(&c) || char::is_ascii_punctuation(&c)
-  }
-}
-
-// TODO(bartlomieju): dedup with repl::editor
-async fn get_global_lexical_scope_names(
-  repl_session_proxy: &mut JupyterReplProxy,
-) -> Vec<String> {
-  repl_session_proxy.global_lexical_scope_names().await.names
-}
-
-// TODO(bartlomieju): dedup with repl::editor
-async fn get_expression_property_names(
-  repl_session_proxy: &mut JupyterReplProxy,
-  expr: &str,
-) -> Vec<String> {
-  // try to get the properties from the expression
-  if let Some(properties) =
-    get_object_expr_properties(repl_session_proxy, expr).await
-  {
-    return properties;
-  }
-
-  // otherwise fall back to the prototype
-  let expr_type = get_expression_type(repl_session_proxy, expr).await;
-  let object_expr = match expr_type.as_deref() {
-    // possibilities: https://chromedevtools.github.io/devtools-protocol/v8/Runtime/#type-RemoteObject
-    Some("object") => "Object.prototype",
-    Some("function") => "Function.prototype",
-    Some("string") => "String.prototype",
-    Some("boolean") => "Boolean.prototype",
-    Some("bigint") => "BigInt.prototype",
-    Some("number") => "Number.prototype",
-    _ => return Vec::new(), // undefined, symbol, and unhandled
-  };
-
-  get_object_expr_properties(repl_session_proxy, object_expr)
-    .await
-    .unwrap_or_default()
-}
-
-// TODO(bartlomieju): dedup with repl::editor
-async fn get_expression_type(
-  repl_session_proxy: &mut JupyterReplProxy,
-  expr: &str,
-) -> Option<String> {
-  evaluate_expression(repl_session_proxy, expr)
-    .await
-    .map(|res| res.result.kind)
-}
-
-// TODO(bartlomieju): dedup with repl::editor
-async fn get_object_expr_properties(
-  repl_session_proxy: &mut JupyterReplProxy,
-  object_expr: &str,
-) -> Option<Vec<String>> {
-  let evaluate_result =
-    evaluate_expression(repl_session_proxy, object_expr).await?;
-  let object_id = evaluate_result.result.object_id?;
-
-  let get_properties_response =
-    repl_session_proxy.get_properties(object_id.clone()).await?;
-  Some(
-    get_properties_response
-      .result
-      .into_iter()
-      .map(|prop| prop.name)
-      .collect(),
-  )
-}
-
-// TODO(bartlomieju): dedup with repl::editor
-async fn evaluate_expression(
-  repl_session_proxy: &mut JupyterReplProxy,
-  expr: &str,
-) -> Option<cdp::EvaluateResponse> {
-  let evaluate_response = repl_session_proxy.evaluate(expr.to_string()).await?;
-  if evaluate_response.exception_details.is_some() {
-    None
-  } else {
-    Some(evaluate_response)
-  }
-}
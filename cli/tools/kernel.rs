@@ -0,0 +1,452 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Notebook kernel on top of a REPL session, independent of the protocol
+//! used to talk to the front-end. `deno jupyter` serves it over ZeroMQ,
+//! embedders can drive it directly.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::v8;
+
+use crate::cdp;
+use crate::tools::repl;
+use crate::tools::repl::ReplSession;
+
+/// Result of executing a cell.
+#[derive(Debug, Clone)]
+pub enum ExecuteOutcome {
+  Ok(ExecuteValue),
+  /// The cell threw or was interrupted.
+  Error(KernelError),
+}
+
+/// The completion value of a cell.
+#[derive(Debug, Clone)]
+pub struct ExecuteValue {
+  /// The value formatted the way `deno repl` prints it.
+  pub text: String,
+  pub(crate) remote_object: cdp::RemoteObject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelError {
+  /// Name of the error class, eg. `"TypeError"`.
+  pub name: String,
+  pub message: String,
+  /// The stack trace, one line per entry.
+  pub traceback: Vec<String>,
+}
+
+impl KernelError {
+  fn interrupted() -> Self {
+    Self {
+      name: "Interrupted".to_string(),
+      message: "Execution was interrupted.".to_string(),
+      traceback: vec![],
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompleteReply {
+  pub matches: Vec<String>,
+  /// Byte offsets of the text the matches replace.
+  pub cursor_start: usize,
+  pub cursor_end: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InspectReply {
+  /// Whether there is an object at the cursor.
+  pub found: bool,
+  /// Representations of the object by mime type, eg. `"text/plain"`.
+  pub data: HashMap<String, serde_json::Value>,
+}
+
+/// Interrupts the cell a [`Kernel`] is executing. It can be used from any
+/// thread.
+#[derive(Clone)]
+pub struct InterruptHandle {
+  isolate_handle: v8::IsolateHandle,
+  interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+  pub fn interrupt(&self) {
+    self.interrupted.store(true, Ordering::SeqCst);
+    self.isolate_handle.terminate_execution();
+  }
+}
+
+/// The requests a notebook front-end sends to a kernel.
+#[async_trait::async_trait(?Send)]
+pub trait Kernel {
+  /// Executes `code` as a cell.
+  async fn execute(&mut self, code: &str) -> Result<ExecuteOutcome, AnyError>;
+
+  /// Completes `code` at the byte offset `cursor_pos`.
+  async fn complete(
+    &mut self,
+    code: &str,
+    cursor_pos: usize,
+  ) -> Result<CompleteReply, AnyError>;
+
+  /// Describes the object at the byte offset `cursor_pos` in `code`. A
+  /// `detail_level` of 1 or more includes the source of functions.
+  async fn inspect(
+    &mut self,
+    code: &str,
+    cursor_pos: usize,
+    detail_level: u8,
+  ) -> Result<InspectReply, AnyError>;
+
+  /// A handle to interrupt execution while the kernel is busy.
+  fn interrupt_handle(&self) -> InterruptHandle;
+}
+
+/// A [`Kernel`] backed by a [`ReplSession`], so cells behave like lines
+/// entered in `deno repl`.
+pub struct ReplKernel {
+  session: ReplSession,
+  interrupt_handle: InterruptHandle,
+}
+
+impl ReplKernel {
+  pub fn new(mut session: ReplSession) -> Self {
+    let isolate_handle =
+      session.worker.js_runtime.v8_isolate().thread_safe_handle();
+    Self {
+      session,
+      interrupt_handle: InterruptHandle {
+        isolate_handle,
+        interrupted: Default::default(),
+      },
+    }
+  }
+
+  pub fn session(&mut self) -> &mut ReplSession {
+    &mut self.session
+  }
+
+  /// Runs pending timers and other async work until there is none left.
+  pub async fn run_event_loop(&mut self) -> Result<(), AnyError> {
+    self.session.run_event_loop().await
+  }
+
+  /// Clears an interrupt, so that the isolate runs JavaScript again.
+  /// Returns whether there was one.
+  fn take_interrupt(&mut self) -> bool {
+    if !self
+      .interrupt_handle
+      .interrupted
+      .swap(false, Ordering::SeqCst)
+    {
+      return false;
+    }
+    self
+      .session
+      .worker
+      .js_runtime
+      .v8_isolate()
+      .cancel_terminate_execution();
+    true
+  }
+
+  async fn execute_inner(
+    &mut self,
+    code: &str,
+  ) -> Result<ExecuteOutcome, AnyError> {
+    let evaluate_response = self
+      .session
+      .evaluate_line_with_object_wrapping(code)
+      .await?;
+    let cdp::EvaluateResponse {
+      result,
+      exception_details,
+    } = evaluate_response.value;
+    match exception_details {
+      None => {
+        self
+          .session
+          .language_server
+          .commit_text(&evaluate_response.ts_code)
+          .await;
+        let text = self.session.get_eval_value(&result).await?;
+        Ok(ExecuteOutcome::Ok(ExecuteValue {
+          text,
+          remote_object: result,
+        }))
+      }
+      Some(exception_details) => Ok(ExecuteOutcome::Error(
+        self.describe_exception(exception_details).await?,
+      )),
+    }
+  }
+
+  async fn describe_exception(
+    &mut self,
+    exception_details: cdp::ExceptionDetails,
+  ) -> Result<KernelError, AnyError> {
+    let (name, message, stack) =
+      if let Some(exception) = exception_details.exception {
+        let result = self
+          .session
+          .call_function_on_args(
+            r#"
+          function(object) {
+            if (object instanceof Error) {
+              const name = "name" in object ? String(object.name) : "";
+              const message = "message" in object ? String(object.message) : "";
+              const stack = "stack" in object ? String(object.stack) : "";
+              return JSON.stringify({ name, message, stack });
+            } else {
+              const message = String(object);
+              return JSON.stringify({ name: "", message, stack: "" });
+            }
+          }
+        "#
+            .into(),
+            &[exception],
+          )
+          .await?;
+
+        match result.result.value {
+          Some(serde_json::Value::String(str)) => {
+            if let Ok(object) =
+              serde_json::from_str::<HashMap<String, String>>(&str)
+            {
+              let get = |k| object.get(k).cloned().unwrap_or_default();
+              (get("name"), get("message"), get("stack"))
+            } else {
+              log::error!("Unexpected result while parsing JSON {str}");
+              ("".into(), "".into(), "".into())
+            }
+          }
+          _ => {
+            log::error!("Unexpected result while parsing exception {result:?}");
+            ("".into(), "".into(), "".into())
+          }
+        }
+      } else {
+        log::error!("Unexpectedly missing exception {exception_details:?}");
+        ("".into(), "".into(), "".into())
+      };
+
+    let stack = if stack.is_empty() {
+      format!(
+        "{}\n    at <unknown>",
+        serde_json::to_string(&message).unwrap()
+      )
+    } else {
+      stack
+    };
+    let traceback = format!("Stack trace:\n{stack}")
+      .split('\n')
+      .map(|s| s.to_owned())
+      .collect::<Vec<_>>();
+
+    Ok(KernelError {
+      name: if name.is_empty() {
+        "Unknown error".into()
+      } else {
+        name
+      },
+      message: if message.is_empty() {
+        "(none)".into()
+      } else {
+        message
+      },
+      traceback,
+    })
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Kernel for ReplKernel {
+  async fn execute(&mut self, code: &str) -> Result<ExecuteOutcome, AnyError> {
+    // an interrupt that arrived while idle doesn't apply to this cell
+    self.take_interrupt();
+    let result = self.execute_inner(code).await;
+    if self.take_interrupt() {
+      return Ok(ExecuteOutcome::Error(KernelError::interrupted()));
+    }
+    result
+  }
+
+  async fn complete(
+    &mut self,
+    code: &str,
+    cursor_pos: usize,
+  ) -> Result<CompleteReply, AnyError> {
+    let cursor_pos = clamp_cursor_pos(code, cursor_pos);
+    let completions = self.session.complete(code, cursor_pos).await?;
+    Ok(CompleteReply {
+      matches: completions.candidates,
+      cursor_start: completions.start.min(cursor_pos),
+      cursor_end: cursor_pos,
+    })
+  }
+
+  async fn inspect(
+    &mut self,
+    code: &str,
+    cursor_pos: usize,
+    detail_level: u8,
+  ) -> Result<InspectReply, AnyError> {
+    let cursor_pos = clamp_cursor_pos(code, cursor_pos);
+    let expr = repl::get_expr_from_line_at_pos(code, cursor_pos);
+    if expr.is_empty() {
+      return Ok(InspectReply::default());
+    }
+    let response = self
+      .session
+      .post_message_with_event_loop(
+        "Runtime.evaluate",
+        Some(cdp::EvaluateArgs {
+          expression: expr.to_string(),
+          object_group: None,
+          include_command_line_api: None,
+          silent: None,
+          context_id: Some(self.session.context_id),
+          return_by_value: None,
+          generate_preview: None,
+          user_gesture: None,
+          await_promise: None,
+          throw_on_side_effect: Some(true),
+          timeout: Some(200),
+          disable_breaks: None,
+          repl_mode: None,
+          allow_unsafe_eval_blocked_by_csp: None,
+          unique_context_id: None,
+        }),
+      )
+      .await?;
+    let response: cdp::EvaluateResponse = serde_json::from_value(response)?;
+    if response.exception_details.is_some() {
+      return Ok(InspectReply::default());
+    }
+    // the description of a function is its source
+    let text = match response.result.description.clone() {
+      Some(source)
+        if detail_level > 0 && response.result.kind == "function" =>
+      {
+        source
+      }
+      _ => self.session.get_eval_value(&response.result).await?,
+    };
+    Ok(InspectReply {
+      found: true,
+      data: HashMap::from([(
+        "text/plain".to_string(),
+        serde_json::Value::String(text),
+      )]),
+    })
+  }
+
+  fn interrupt_handle(&self) -> InterruptHandle {
+    self.interrupt_handle.clone()
+  }
+}
+
+/// Moves `cursor_pos` back inside `code` and onto a char boundary, as it
+/// comes from the front-end.
+fn clamp_cursor_pos(code: &str, cursor_pos: usize) -> usize {
+  let mut cursor_pos = cursor_pos.min(code.len());
+  while !code.is_char_boundary(cursor_pos) {
+    cursor_pos -= 1;
+  }
+  cursor_pos
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::args::DenoSubcommand;
+  use crate::args::Flags;
+  use crate::args::ReplFlags;
+  use crate::factory::CliFactory;
+
+  async fn create_kernel() -> ReplKernel {
+    let flags = Flags {
+      subcommand: DenoSubcommand::Repl(ReplFlags::default()),
+      ..Default::default()
+    };
+    let factory = CliFactory::from_flags(Arc::new(flags));
+    let session = ReplSession::new(&factory, vec![]).await.unwrap();
+    ReplKernel::new(session)
+  }
+
+  #[test]
+  fn clamps_cursor_pos() {
+    assert_eq!(clamp_cursor_pos("abc", 2), 2);
+    assert_eq!(clamp_cursor_pos("abc", 10), 3);
+    // "é" is two bytes
+    assert_eq!(clamp_cursor_pos("é", 1), 0);
+  }
+
+  #[tokio::test]
+  async fn execute_cells() {
+    let mut kernel = create_kernel().await;
+    let ExecuteOutcome::Ok(value) =
+      kernel.execute("const a = 1; a + 1").await.unwrap()
+    else {
+      panic!("expected a value");
+    };
+    assert_eq!(value.text, "2");
+    // declarations persist between cells
+    let ExecuteOutcome::Ok(value) = kernel.execute("a").await.unwrap() else {
+      panic!("expected a value");
+    };
+    assert_eq!(value.text, "1");
+    let ExecuteOutcome::Error(error) =
+      kernel.execute("throw new TypeError('boom')").await.unwrap()
+    else {
+      panic!("expected an error");
+    };
+    assert_eq!(error.name, "TypeError");
+    assert_eq!(error.message, "boom");
+  }
+
+  #[tokio::test]
+  async fn complete_property() {
+    let mut kernel = create_kernel().await;
+    kernel
+      .execute("const obj = { foo: 1, bar: 2 }")
+      .await
+      .unwrap();
+    let reply = kernel.complete("obj.fo", 6).await.unwrap();
+    assert!(reply.matches.contains(&"foo".to_string()));
+    assert_eq!(reply.cursor_start, 4);
+    assert_eq!(reply.cursor_end, 6);
+  }
+
+  #[tokio::test]
+  async fn complete_cursor_inside_expression() {
+    let mut kernel = create_kernel().await;
+    // the expression at the cursor is longer than the text before it
+    let reply = kernel.complete("ab.cdef g", 1).await.unwrap();
+    assert!(reply.cursor_start <= reply.cursor_end);
+    assert_eq!(reply.cursor_end, 1);
+    let reply = kernel.complete("Deno", 100).await.unwrap();
+    assert_eq!(reply.cursor_end, 4);
+  }
+
+  #[tokio::test]
+  async fn inspect_value() {
+    let mut kernel = create_kernel().await;
+    kernel.execute("const num = 42").await.unwrap();
+    let reply = kernel.inspect("num", 3, 0).await.unwrap();
+    assert!(reply.found);
+    assert_eq!(
+      reply.data.get("text/plain"),
+      Some(&serde_json::Value::String("42".to_string()))
+    );
+    let reply = kernel.inspect("", 0, 0).await.unwrap();
+    assert!(!reply.found);
+  }
+}
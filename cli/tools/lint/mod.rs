@@ -54,12 +54,14 @@ use crate::util::sync::AtomicFlag;
 mod linter;
 mod reporters;
 mod rules;
+pub mod source;
 
 pub use linter::CliLinter;
 pub use linter::CliLinterOptions;
 pub use rules::collect_no_slow_type_diagnostics;
 pub use rules::ConfiguredRules;
 pub use rules::LintRuleProvider;
+pub use source::lint_source;
 
 const JSON_SCHEMA_VERSION: u8 = 1;
 
@@ -0,0 +1,170 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Linting of in-memory sources, for hosts that present lint results in
+//! their own UI.
+
+use std::path::Path;
+
+use deno_ast::SourcePos;
+use deno_ast::SourceRange;
+use deno_ast::SourceTextInfo;
+use deno_config::deno_json::LintRulesConfig;
+use deno_core::error::AnyError;
+use deno_lint::linter::LintConfig;
+use serde::Serialize;
+
+use super::CliLinter;
+use super::CliLinterOptions;
+use super::LintRuleProvider;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintDiagnostic {
+  /// The rule that reported the diagnostic, eg. `"no-unused-vars"`.
+  pub code: String,
+  pub message: String,
+  pub hint: Option<String>,
+  /// `None` for diagnostics about the file as a whole.
+  pub range: Option<LintRange>,
+  pub fixes: Vec<LintFix>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintRange {
+  pub start: LintPosition,
+  pub end: LintPosition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintPosition {
+  /// The 0-indexed line number.
+  pub line: usize,
+  /// The 0-indexed column index.
+  pub col: usize,
+  /// Offset in bytes from the start of the source.
+  pub byte_pos: usize,
+}
+
+/// A suggested fix. Its changes have to be applied together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFix {
+  pub description: String,
+  pub changes: Vec<LintFixChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFixChange {
+  pub range: LintRange,
+  pub new_text: String,
+}
+
+impl LintRange {
+  fn new(range: SourceRange, text_info: &SourceTextInfo) -> Self {
+    let position = |pos: SourcePos| {
+      let loc = text_info.line_and_column_index(pos);
+      LintPosition {
+        line: loc.line_index,
+        col: loc.column_index,
+        byte_pos: pos.as_byte_index(text_info.range().start),
+      }
+    };
+    Self {
+      start: position(range.start),
+      end: position(range.end),
+    }
+  }
+}
+
+/// Lints `source` with the given rules. `path_hint` determines the media type
+/// and the specifier the rules see, it doesn't need to exist on disk. Errors
+/// when the source can't be parsed.
+pub fn lint_source(
+  path_hint: &Path,
+  source: String,
+  rules: LintRulesConfig,
+) -> Result<Vec<LintDiagnostic>, AnyError> {
+  let file_path = if path_hint.is_absolute() {
+    path_hint.to_path_buf()
+  } else {
+    std::env::current_dir()?.join(path_hint)
+  };
+  let linter = CliLinter::new(CliLinterOptions {
+    configured_rules: LintRuleProvider::new(None, None)
+      .resolve_lint_rules(rules, None),
+    fix: false,
+    deno_lint_config: LintConfig {
+      default_jsx_factory: None,
+      default_jsx_fragment_factory: None,
+    },
+  });
+  let (parsed_source, diagnostics) =
+    linter.lint_file(&file_path, deno_ast::strip_bom(source), None)?;
+  let text_info = parsed_source.text_info_lazy();
+
+  let mut diagnostics = diagnostics
+    .into_iter()
+    .map(|d| LintDiagnostic {
+      code: d.details.code.to_string(),
+      message: d.details.message,
+      hint: d.details.hint,
+      range: d
+        .range
+        .as_ref()
+        .map(|range| LintRange::new(range.range, text_info)),
+      fixes: d
+        .details
+        .fixes
+        .iter()
+        .map(|fix| LintFix {
+          description: fix.description.to_string(),
+          changes: fix
+            .changes
+            .iter()
+            .map(|change| LintFixChange {
+              range: LintRange::new(change.range, text_info),
+              new_text: change.new_text.to_string(),
+            })
+            .collect(),
+        })
+        .collect(),
+    })
+    .collect::<Vec<_>>();
+  diagnostics.sort_by(|a, b| {
+    let a_start = a.range.map(|r| r.start.byte_pos);
+    let b_start = b.range.map(|r| r.start.byte_pos);
+    a_start.cmp(&b_start).then_with(|| a.code.cmp(&b.code))
+  });
+  Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn lints_source() {
+    let diagnostics = lint_source(
+      Path::new("mod.ts"),
+      "let a = 1;\nexport { a };\n".to_string(),
+      Default::default(),
+    )
+    .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.code, "prefer-const");
+    let range = diagnostic.range.unwrap();
+    assert_eq!(range.start.line, 0);
+    assert_eq!(range.start.byte_pos, 4);
+
+    assert!(lint_source(
+      Path::new("mod.ts"),
+      "let = ;".to_string(),
+      Default::default()
+    )
+    .is_err());
+  }
+}
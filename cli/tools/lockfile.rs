@@ -0,0 +1,248 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_graph::GraphKind;
+use deno_npm::resolution::SnapshotFromLockfileError;
+
+use crate::args::CliLockfile;
+use crate::args::CliLockfileReadFromPathOptions;
+use crate::args::Flags;
+use crate::cache;
+use crate::factory::CliFactory;
+use crate::graph_util::graph_integrity_error_messages;
+use crate::graph_util::graph_valid;
+use crate::graph_util::GraphValidOptions;
+use crate::util::fs::atomic_write_file_with_retries;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LockfileError {
+  /// A dependency doesn't match the checksum recorded in the lockfile.
+  /// The `deno` binary exits with code 10 for these.
+  #[error("{0}")]
+  IntegrityCheckFailed(String),
+  #[error(transparent)]
+  Other(AnyError),
+}
+
+impl From<AnyError> for LockfileError {
+  fn from(error: AnyError) -> Self {
+    if let Some(SnapshotFromLockfileError::IntegrityCheckFailed(e)) =
+      error.downcast_ref::<SnapshotFromLockfileError>()
+    {
+      return LockfileError::IntegrityCheckFailed(e.to_string());
+    }
+    LockfileError::Other(error)
+  }
+}
+
+/// Entries a module graph adds to a lockfile, by section of the lockfile.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockfileAdditions {
+  /// Package requirements, eg. `"jsr:@std/path@1"`.
+  pub specifiers: Vec<String>,
+  /// JSR packages, eg. `"@std/path@1.0.8"`.
+  pub jsr: Vec<String>,
+  /// npm packages, eg. `"chalk@5.3.0"`.
+  pub npm: Vec<String>,
+  pub redirects: Vec<String>,
+  /// Remote modules, by url.
+  pub remote: Vec<String>,
+}
+
+impl LockfileAdditions {
+  pub fn is_empty(&self) -> bool {
+    self.specifiers.is_empty()
+      && self.jsr.is_empty()
+      && self.npm.is_empty()
+      && self.redirects.is_empty()
+      && self.remote.is_empty()
+  }
+}
+
+/// A `deno.lock` file.
+#[derive(Debug, Clone)]
+pub struct Lockfile {
+  path: PathBuf,
+  content: String,
+}
+
+impl Lockfile {
+  /// Loads the lockfile at `path`. A missing file is an empty lockfile.
+  pub fn load(path: impl Into<PathBuf>) -> Result<Self, AnyError> {
+    let lockfile =
+      CliLockfile::read_from_path(CliLockfileReadFromPathOptions {
+        file_path: path.into(),
+        frozen: false,
+        skip_write: true,
+      })?;
+    let content = lockfile.lock().as_json_string();
+    Ok(Self {
+      path: lockfile.filename,
+      content,
+    })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn to_json(&self) -> String {
+    self.content.clone()
+  }
+
+  /// Builds the module graph of `entrypoints` with this lockfile, checking
+  /// the integrity of every dependency recorded in it. The lockfile isn't
+  /// written, see [`LockfileVerification::write`].
+  pub async fn verify(
+    &self,
+    mut flags: Arc<Flags>,
+    entrypoints: Vec<String>,
+  ) -> Result<LockfileVerification, LockfileError> {
+    {
+      let flags = Arc::make_mut(&mut flags);
+      flags.lock = Some(self.path.to_string_lossy().to_string());
+      flags.no_lock = false;
+      flags.frozen_lockfile = Some(false);
+      flags.internal.lockfile_skip_write = true;
+    }
+    let factory = CliFactory::from_flags(flags);
+    let cli_options = factory.cli_options()?;
+    let roots = entrypoints
+      .iter()
+      .map(|entrypoint| {
+        resolve_url_or_path(entrypoint, cli_options.initial_cwd())
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(AnyError::from)?;
+    let graph = factory
+      .module_graph_creator()
+      .await?
+      .create_graph(GraphKind::All, roots.clone())
+      .await?;
+    if let Some(message) =
+      graph_integrity_error_messages(&graph).into_iter().next()
+    {
+      return Err(LockfileError::IntegrityCheckFailed(message));
+    }
+    graph_valid(
+      &graph,
+      factory.fs(),
+      &roots,
+      GraphValidOptions {
+        check_js: false,
+        kind: GraphKind::All,
+        exit_integrity_errors: false,
+      },
+    )?;
+
+    let updated = match cli_options.maybe_lockfile() {
+      Some(lockfile) => lockfile.lock().as_json_string(),
+      None => self.content.clone(),
+    };
+    let additions = lockfile_additions(&self.content, &updated)?;
+    Ok(LockfileVerification {
+      path: self.path.clone(),
+      updated,
+      additions,
+    })
+  }
+}
+
+/// The result of [`Lockfile::verify`].
+#[derive(Debug, Clone)]
+pub struct LockfileVerification {
+  path: PathBuf,
+  updated: String,
+  additions: LockfileAdditions,
+}
+
+impl LockfileVerification {
+  /// What the module graph adds to the lockfile. Empty when the lockfile
+  /// is up to date, which is what `--frozen` requires.
+  pub fn additions(&self) -> &LockfileAdditions {
+    &self.additions
+  }
+
+  /// The lockfile with the additions.
+  pub fn to_json(&self) -> String {
+    self.updated.clone()
+  }
+
+  /// Writes the lockfile with the additions. The write is atomic, so
+  /// concurrent `deno` processes don't corrupt it.
+  pub fn write(&self) -> Result<(), AnyError> {
+    atomic_write_file_with_retries(
+      &self.path,
+      self.updated.as_bytes(),
+      cache::CACHE_PERM,
+    )
+    .context("Failed writing lockfile.")
+  }
+}
+
+fn lockfile_additions(
+  original: &str,
+  updated: &str,
+) -> Result<LockfileAdditions, AnyError> {
+  let original: serde_json::Value = serde_json::from_str(original)?;
+  let updated: serde_json::Value = serde_json::from_str(updated)?;
+  let added = |section: &str| -> Vec<String> {
+    let Some(updated) = updated.get(section).and_then(|v| v.as_object()) else {
+      return Vec::new();
+    };
+    let original = original.get(section).and_then(|v| v.as_object());
+    updated
+      .keys()
+      .filter(|key| {
+        !original.is_some_and(|original| original.contains_key(*key))
+      })
+      .cloned()
+      .collect()
+  };
+  Ok(LockfileAdditions {
+    specifiers: added("specifiers"),
+    jsr: added("jsr"),
+    npm: added("npm"),
+    redirects: added("redirects"),
+    remote: added("remote"),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn additions_between_lockfiles() {
+    let original = r#"{
+      "version": "4",
+      "specifiers": { "jsr:@std/path@1": "1.0.8" },
+      "jsr": { "@std/path@1.0.8": { "integrity": "a" } },
+      "remote": { "https://deno.land/x/a.ts": "b" }
+    }"#;
+    let updated = r#"{
+      "version": "4",
+      "specifiers": { "jsr:@std/path@1": "1.0.8", "npm:chalk@5": "5.3.0" },
+      "jsr": { "@std/path@1.0.8": { "integrity": "a" } },
+      "npm": { "chalk@5.3.0": { "integrity": "c" } },
+      "remote": { "https://deno.land/x/a.ts": "b" }
+    }"#;
+    let additions = lockfile_additions(original, updated).unwrap();
+    assert_eq!(
+      additions,
+      LockfileAdditions {
+        specifiers: vec!["npm:chalk@5".to_string()],
+        npm: vec!["chalk@5.3.0".to_string()],
+        ..Default::default()
+      }
+    );
+    assert!(lockfile_additions(updated, updated).unwrap().is_empty());
+  }
+}
@@ -1,17 +1,23 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 pub mod bench;
+pub mod bundle;
 pub mod check;
 pub mod clean;
 pub mod compile;
 pub mod coverage;
 pub mod doc;
 pub mod fmt;
+pub mod import_map;
 pub mod info;
 pub mod init;
 pub mod installer;
 pub mod jupyter;
+pub mod kernel;
 pub mod lint;
+pub mod lockfile;
+pub mod npm;
+pub mod permissions_analyze;
 pub mod registry;
 pub mod repl;
 pub mod run;
@@ -19,3 +25,4 @@ pub mod serve;
 pub mod task;
 pub mod test;
 pub mod upgrade;
+pub mod vendor;
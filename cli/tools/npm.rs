@@ -0,0 +1,199 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Installing npm packages independently of running a script.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_config::deno_json::NodeModulesDirMode;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_semver::package::PackageNv;
+use deno_semver::package::PackageReq;
+
+use crate::args::Flags;
+use crate::args::NpmRegistriesConfig;
+use crate::factory::CliFactory;
+use crate::factory::EmbedderOptions;
+use crate::npm::CliNpmResolver;
+
+/// Progress of [`install_packages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NpmInstallEvent {
+  /// The versions of `total` package requirements are being resolved.
+  Resolving { total: usize },
+  /// The requirements resolved to `total` packages, including transitive
+  /// dependencies, of which `missing` aren't installed yet.
+  Resolved { total: usize, missing: usize },
+  /// The missing packages were downloaded and installed.
+  Finished { installed: usize },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NpmInstallOptions {
+  /// Whether packages are installed into a `node_modules` directory. The
+  /// mode of the config file, if any, is used when `None`.
+  pub node_modules_dir: Option<NodeModulesDirMode>,
+  /// Registries to use instead of the ones configured in `.npmrc` files.
+  pub registries: Option<NpmRegistriesConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NpmInstallReport {
+  /// The version every requirement resolved to, in the order of the
+  /// requirements.
+  pub resolved: Vec<(PackageReq, PackageNv)>,
+  /// Packages that weren't installed before, including transitive
+  /// dependencies.
+  pub installed: Vec<PackageNv>,
+  /// The `node_modules` directory the packages were installed into, if
+  /// one is used.
+  pub node_modules_dir: Option<PathBuf>,
+}
+
+/// Resolves `reqs` and installs the packages, like `deno install npm:<req>`
+/// does, without adding them to a config file. The lockfile is updated if
+/// one is used.
+pub async fn install_packages(
+  mut flags: Arc<Flags>,
+  reqs: &[PackageReq],
+  options: NpmInstallOptions,
+  progress: impl Fn(NpmInstallEvent),
+) -> Result<NpmInstallReport, AnyError> {
+  if let Some(node_modules_dir) = options.node_modules_dir {
+    Arc::make_mut(&mut flags).node_modules_dir = Some(node_modules_dir);
+  }
+  let factory = CliFactory::from_flags_for_embedder(
+    flags,
+    EmbedderOptions {
+      npm_registries: options.registries,
+      ..Default::default()
+    },
+  );
+  let cli_options = factory.cli_options()?;
+  let npm_resolver = factory.npm_resolver().await?;
+  let Some(npm_resolver) = npm_resolver.as_managed() else {
+    bail!(
+      "npm packages can't be installed when the node_modules directory is managed manually."
+    );
+  };
+
+  progress(NpmInstallEvent::Resolving { total: reqs.len() });
+  let result = npm_resolver.resolve_package_reqs(reqs).await;
+  result.dependencies_result?;
+  let resolved = reqs
+    .iter()
+    .cloned()
+    .zip(result.results)
+    .map(|(req, nv)| Ok((req, nv?)))
+    .collect::<Result<Vec<_>, AnyError>>()?;
+
+  let snapshot = npm_resolver.snapshot();
+  let mut total = 0;
+  let mut missing = Vec::new();
+  for package in snapshot.all_packages_for_every_system() {
+    total += 1;
+    let is_installed = npm_resolver
+      .resolve_pkg_folder_from_pkg_id(&package.id)
+      .map(|folder| folder.exists())
+      .unwrap_or(false);
+    if !is_installed {
+      missing.push(package.id.nv.clone());
+    }
+  }
+  progress(NpmInstallEvent::Resolved {
+    total,
+    missing: missing.len(),
+  });
+
+  npm_resolver.cache_packages().await?;
+  if let Some(lockfile) = cli_options.maybe_lockfile() {
+    lockfile.write_if_changed()?;
+  }
+  progress(NpmInstallEvent::Finished {
+    installed: missing.len(),
+  });
+
+  missing.sort();
+  Ok(NpmInstallReport {
+    resolved,
+    installed: missing,
+    node_modules_dir: npm_resolver
+      .root_node_modules_path()
+      .map(ToOwned::to_owned),
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use std::cell::RefCell;
+
+  use deno_core::url::Url;
+  use test_util::TempDir;
+
+  use super::*;
+  use crate::args::ConfigFlag;
+  use crate::args::InternalFlags;
+  use crate::args::NpmRegistry;
+
+  #[tokio::test]
+  async fn installs_packages_once() {
+    let _http_server_guard = test_util::http_server();
+    let temp_dir = TempDir::new();
+    let flags = Arc::new(Flags {
+      config_flag: ConfigFlag::Disabled,
+      internal: InternalFlags {
+        cache_path: Some(temp_dir.path().join("deno_dir").to_path_buf()),
+        ..Default::default()
+      },
+      ..Default::default()
+    });
+    let options = NpmInstallOptions {
+      node_modules_dir: None,
+      registries: Some(NpmRegistriesConfig {
+        default_registry: Some(NpmRegistry::new(
+          Url::parse(&test_util::npm_registry_url()).unwrap(),
+        )),
+        ..Default::default()
+      }),
+    };
+    let req = PackageReq::from_str("@denotest/add@1").unwrap();
+    let nv = PackageNv::from_str("@denotest/add@1.0.0").unwrap();
+
+    let events = RefCell::new(Vec::new());
+    let report = install_packages(
+      flags.clone(),
+      &[req.clone()],
+      options.clone(),
+      |event| events.borrow_mut().push(event),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+      report,
+      NpmInstallReport {
+        resolved: vec![(req.clone(), nv.clone())],
+        installed: vec![nv.clone()],
+        node_modules_dir: None,
+      }
+    );
+    assert_eq!(
+      events.into_inner(),
+      vec![
+        NpmInstallEvent::Resolving { total: 1 },
+        NpmInstallEvent::Resolved {
+          total: 1,
+          missing: 1,
+        },
+        NpmInstallEvent::Finished { installed: 1 },
+      ]
+    );
+
+    // already installed in the cache
+    let report = install_packages(flags, &[req.clone()], options, |_| {})
+      .await
+      .unwrap();
+    assert_eq!(report.resolved, vec![(req, nv)]);
+    assert!(report.installed.is_empty());
+  }
+}
@@ -0,0 +1,385 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Static analysis of the permissions a program may request, so embedders
+//! can show a summary to users before running it.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use deno_ast::swc::ast;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith as _;
+use deno_ast::ParsedSource;
+use deno_ast::SourceRangedForSpanned as _;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_graph::GraphKind;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::cache::ParsedSourceCache;
+use crate::factory::CliFactory;
+
+/// A permission flag of the CLI, eg. `--allow-net` for
+/// [`PermissionDomain::Net`].
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionDomain {
+  Read,
+  Write,
+  Net,
+  Env,
+  Run,
+  Sys,
+  Ffi,
+  Import,
+}
+
+/// An API of the program that requires a permission.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiUsage {
+  /// The API as written, eg. `"Deno.readTextFile"`, `"fetch"` or
+  /// `"node:fs"`. For remote modules, their specifier.
+  pub api: String,
+  pub specifier: ModuleSpecifier,
+  /// One based line and column of the usage, `None` for imports of remote
+  /// modules.
+  pub line: Option<usize>,
+  pub column: Option<usize>,
+}
+
+/// The permissions a program may request, see [`analyze_permissions`].
+///
+/// The analysis looks for references to APIs, not calls, so it reports
+/// permissions the program may never request at runtime. It misses APIs
+/// reached through aliases or computed property names.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsAnalysis {
+  pub usages: BTreeMap<PermissionDomain, Vec<ApiUsage>>,
+  /// npm packages and external modules of the graph, whose code isn't
+  /// analyzed.
+  pub unanalyzed: Vec<ModuleSpecifier>,
+}
+
+impl PermissionsAnalysis {
+  /// The permissions the program may request.
+  pub fn domains(&self) -> Vec<PermissionDomain> {
+    self.usages.keys().copied().collect()
+  }
+
+  fn add(&mut self, domain: PermissionDomain, usage: ApiUsage) {
+    self.usages.entry(domain).or_default().push(usage);
+  }
+}
+
+/// Builds the module graph of `entrypoint` with the configuration resolved
+/// from `flags` and reports the permissions its code may request, without
+/// running it.
+pub async fn analyze_permissions(
+  flags: Arc<Flags>,
+  entrypoint: String,
+) -> Result<PermissionsAnalysis, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let graph = factory
+    .create_graph_for_embedder(GraphKind::All, &[entrypoint])
+    .await?;
+  analyze_graph(&graph, factory.parsed_source_cache())
+}
+
+fn analyze_graph(
+  graph: &ModuleGraph,
+  parsed_source_cache: &ParsedSourceCache,
+) -> Result<PermissionsAnalysis, AnyError> {
+  let mut analysis = PermissionsAnalysis::default();
+  for module in graph.modules() {
+    let specifier = module.specifier();
+    if matches!(specifier.scheme(), "http" | "https") {
+      analysis.add(
+        PermissionDomain::Import,
+        ApiUsage {
+          api: specifier.to_string(),
+          specifier: specifier.clone(),
+          line: None,
+          column: None,
+        },
+      );
+    }
+    match module {
+      Module::Js(module) => {
+        let parsed_source =
+          parsed_source_cache.get_parsed_source_from_js_module(module)?;
+        analyze_source(&parsed_source, &mut analysis);
+      }
+      Module::Node(module) => {
+        for domain in node_module_domains(&module.module_name) {
+          analysis.add(
+            *domain,
+            ApiUsage {
+              api: format!("node:{}", module.module_name),
+              specifier: module.specifier.clone(),
+              line: None,
+              column: None,
+            },
+          );
+        }
+      }
+      Module::Npm(_) | Module::External(_) => {
+        analysis.unanalyzed.push(specifier.clone());
+      }
+      Module::Json(_) | Module::Wasm(_) => {}
+    }
+  }
+  Ok(analysis)
+}
+
+fn analyze_source(
+  parsed_source: &ParsedSource,
+  analysis: &mut PermissionsAnalysis,
+) {
+  let mut collector = ApiCollector {
+    parsed_source,
+    analysis,
+  };
+  parsed_source.program().visit_with(&mut collector);
+}
+
+struct ApiCollector<'a> {
+  parsed_source: &'a ParsedSource,
+  analysis: &'a mut PermissionsAnalysis,
+}
+
+impl ApiCollector<'_> {
+  fn add(&mut self, api: String, range: deno_ast::SourceRange) {
+    let Some(domain) = api_domain(&api) else {
+      return;
+    };
+    let display = self
+      .parsed_source
+      .text_info_lazy()
+      .line_and_column_display(range.start);
+    self.analysis.add(
+      domain,
+      ApiUsage {
+        api,
+        specifier: self.parsed_source.specifier().clone(),
+        line: Some(display.line_number),
+        column: Some(display.column_number),
+      },
+    );
+  }
+}
+
+impl Visit for ApiCollector<'_> {
+  fn visit_member_expr(&mut self, member_expr: &ast::MemberExpr) {
+    if is_deno_namespace(&member_expr.obj) {
+      if let Some(name) = prop_name(&member_expr.prop) {
+        self.add(format!("Deno.{name}"), member_expr.range());
+      }
+    }
+    member_expr.visit_children_with(self);
+  }
+
+  fn visit_var_declarator(&mut self, var_declarator: &ast::VarDeclarator) {
+    // const { readTextFile } = Deno;
+    if let (ast::Pat::Object(pat), Some(init)) =
+      (&var_declarator.name, &var_declarator.init)
+    {
+      if is_deno_namespace(init) {
+        for prop in &pat.props {
+          let (name, range) = match prop {
+            ast::ObjectPatProp::KeyValue(prop) => match &prop.key {
+              ast::PropName::Ident(ident) => {
+                (ident.sym.to_string(), ident.range())
+              }
+              ast::PropName::Str(lit) => (lit.value.to_string(), lit.range()),
+              _ => continue,
+            },
+            ast::ObjectPatProp::Assign(prop) => {
+              (prop.key.sym.to_string(), prop.key.range())
+            }
+            ast::ObjectPatProp::Rest(_) => continue,
+          };
+          self.add(format!("Deno.{name}"), range);
+        }
+      }
+    }
+    var_declarator.visit_children_with(self);
+  }
+
+  fn visit_ident(&mut self, ident: &ast::Ident) {
+    // property names aren't `Ident`s, but without scope analysis local
+    // bindings shadowing these globals are reported too
+    if matches!(&*ident.sym, "fetch" | "WebSocket" | "EventSource") {
+      self.add(ident.sym.to_string(), ident.range());
+    }
+  }
+}
+
+fn is_deno_namespace(expr: &ast::Expr) -> bool {
+  match expr {
+    ast::Expr::Ident(ident) => ident.sym == *"Deno",
+    ast::Expr::Member(member_expr) => {
+      matches!(&*member_expr.obj, ast::Expr::Ident(ident) if ident.sym == *"globalThis")
+        && prop_name(&member_expr.prop).as_deref() == Some("Deno")
+    }
+    ast::Expr::Paren(paren_expr) => is_deno_namespace(&paren_expr.expr),
+    _ => false,
+  }
+}
+
+fn prop_name(prop: &ast::MemberProp) -> Option<String> {
+  match prop {
+    ast::MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+    ast::MemberProp::Computed(computed) => match &*computed.expr {
+      ast::Expr::Lit(ast::Lit::Str(lit)) => Some(lit.value.to_string()),
+      _ => None,
+    },
+    ast::MemberProp::PrivateName(_) => None,
+  }
+}
+
+fn api_domain(api: &str) -> Option<PermissionDomain> {
+  use PermissionDomain::*;
+
+  let domain = match api {
+    "fetch" | "WebSocket" | "EventSource" => Net,
+    "Deno.readFile"
+    | "Deno.readFileSync"
+    | "Deno.readTextFile"
+    | "Deno.readTextFileSync"
+    | "Deno.readDir"
+    | "Deno.readDirSync"
+    | "Deno.readLink"
+    | "Deno.readLinkSync"
+    | "Deno.stat"
+    | "Deno.statSync"
+    | "Deno.lstat"
+    | "Deno.lstatSync"
+    | "Deno.realPath"
+    | "Deno.realPathSync"
+    | "Deno.open"
+    | "Deno.openSync"
+    | "Deno.watchFs"
+    | "Deno.cwd"
+    | "Deno.chdir"
+    | "Deno.execPath" => Read,
+    "Deno.writeFile"
+    | "Deno.writeFileSync"
+    | "Deno.writeTextFile"
+    | "Deno.writeTextFileSync"
+    | "Deno.create"
+    | "Deno.createSync"
+    | "Deno.mkdir"
+    | "Deno.mkdirSync"
+    | "Deno.makeTempDir"
+    | "Deno.makeTempDirSync"
+    | "Deno.makeTempFile"
+    | "Deno.makeTempFileSync"
+    | "Deno.remove"
+    | "Deno.removeSync"
+    | "Deno.rename"
+    | "Deno.renameSync"
+    | "Deno.copyFile"
+    | "Deno.copyFileSync"
+    | "Deno.chmod"
+    | "Deno.chmodSync"
+    | "Deno.chown"
+    | "Deno.chownSync"
+    | "Deno.truncate"
+    | "Deno.truncateSync"
+    | "Deno.symlink"
+    | "Deno.symlinkSync"
+    | "Deno.link"
+    | "Deno.linkSync"
+    | "Deno.utime"
+    | "Deno.utimeSync" => Write,
+    "Deno.connect"
+    | "Deno.connectTls"
+    | "Deno.listen"
+    | "Deno.listenTls"
+    | "Deno.listenDatagram"
+    | "Deno.startTls"
+    | "Deno.resolveDns"
+    | "Deno.serve"
+    | "Deno.createHttpClient" => Net,
+    "Deno.env" => Env,
+    "Deno.Command" | "Deno.kill" => Run,
+    "Deno.hostname"
+    | "Deno.osRelease"
+    | "Deno.osUptime"
+    | "Deno.loadavg"
+    | "Deno.networkInterfaces"
+    | "Deno.systemMemoryInfo"
+    | "Deno.uid"
+    | "Deno.gid" => Sys,
+    "Deno.dlopen" => Ffi,
+    _ => return None,
+  };
+  Some(domain)
+}
+
+fn node_module_domains(module_name: &str) -> &'static [PermissionDomain] {
+  use PermissionDomain::*;
+
+  match module_name {
+    "fs" | "fs/promises" => &[Read, Write],
+    "child_process" => &[Run],
+    "dgram" | "dns" | "dns/promises" | "http" | "http2" | "https" | "net"
+    | "tls" => &[Net],
+    "os" => &[Env, Sys],
+    "process" => &[Env],
+    _ => &[],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_ast::MediaType;
+  use deno_ast::ParseParams;
+
+  use super::*;
+
+  #[test]
+  fn collects_api_usages() {
+    let specifier = ModuleSpecifier::parse("file:///main.ts").unwrap();
+    let parsed_source = deno_ast::parse_module(ParseParams {
+      specifier: specifier.clone(),
+      text: r#"const text = await Deno.readTextFile("./a.txt");
+const { env } = Deno;
+await fetch(env.get("URL"));
+const client = { fetch() {} };
+client.fetch();
+"#
+      .into(),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let mut analysis = PermissionsAnalysis::default();
+    analyze_source(&parsed_source, &mut analysis);
+
+    assert_eq!(
+      analysis.domains(),
+      vec![
+        PermissionDomain::Read,
+        PermissionDomain::Net,
+        PermissionDomain::Env
+      ]
+    );
+    let read = &analysis.usages[&PermissionDomain::Read];
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].api, "Deno.readTextFile");
+    assert_eq!((read[0].line, read[0].column), (Some(1), Some(20)));
+    let net = &analysis.usages[&PermissionDomain::Net];
+    assert_eq!(net.len(), 1);
+    assert_eq!(net[0].line, Some(3));
+  }
+}
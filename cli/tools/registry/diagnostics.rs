@@ -26,6 +26,8 @@ use deno_graph::FastCheckDiagnostic;
 use deno_semver::Version;
 
 use super::unfurl::SpecifierUnfurlerDiagnostic;
+use super::PublishDiagnosticLevel;
+use super::PublishReportDiagnostic;
 
 #[derive(Clone, Default)]
 pub struct PublishDiagnosticsCollector {
@@ -36,11 +38,8 @@ impl PublishDiagnosticsCollector {
   pub fn print_and_error(&self) -> Result<(), AnyError> {
     let mut errors = 0;
     let mut has_slow_types_errors = false;
-    let mut diagnostics = self.diagnostics.lock().take();
-
-    diagnostics.sort_by_cached_key(|d| d.sorting_key());
 
-    for diagnostic in diagnostics {
+    for diagnostic in self.take() {
       log::error!("{}", diagnostic.display());
       if matches!(diagnostic.level(), DiagnosticLevel::Error) {
         errors += 1;
@@ -75,6 +74,13 @@ impl PublishDiagnosticsCollector {
     }
   }
 
+  /// Removes the collected diagnostics, sorted by code and location.
+  pub fn take(&self) -> Vec<PublishDiagnostic> {
+    let mut diagnostics = self.diagnostics.lock().take();
+    diagnostics.sort_by_cached_key(|d| d.sorting_key());
+    diagnostics
+  }
+
   pub fn has_error(&self) -> bool {
     self
       .diagnostics
@@ -133,6 +139,47 @@ pub enum PublishDiagnostic {
 }
 
 impl PublishDiagnostic {
+  pub fn to_report(&self) -> PublishReportDiagnostic {
+    let (location, position) = match self.location() {
+      DiagnosticLocation::Module { specifier } => {
+        (Some(specifier.to_string()), None)
+      }
+      DiagnosticLocation::Path { path } => {
+        (Some(path.display().to_string()), None)
+      }
+      DiagnosticLocation::ModulePosition {
+        specifier,
+        source_pos,
+        text_info,
+      } => {
+        let position = match source_pos {
+          DiagnosticSourcePos::LineAndCol { line, column } => (line, column),
+          DiagnosticSourcePos::SourcePos(pos) => {
+            let lc = text_info.line_and_column_index(pos);
+            (lc.line_index, lc.column_index)
+          }
+          DiagnosticSourcePos::ByteIndex(index) => {
+            let lc = text_info
+              .line_and_column_index(text_info.range().start() + index);
+            (lc.line_index, lc.column_index)
+          }
+        };
+        (Some(specifier.to_string()), Some(position))
+      }
+    };
+    PublishReportDiagnostic {
+      level: match self.level() {
+        DiagnosticLevel::Error => PublishDiagnosticLevel::Error,
+        DiagnosticLevel::Warning => PublishDiagnosticLevel::Warning,
+      },
+      code: self.code().to_string(),
+      message: self.message().to_string(),
+      hint: self.hint().map(|hint| hint.to_string()),
+      location,
+      position,
+    }
+  }
+
   fn sorting_key(&self) -> (String, String, Option<SourcePos>) {
     let loc = self.location();
 
@@ -15,6 +15,7 @@ use deno_ast::ModuleSpecifier;
 use deno_config::deno_json::ConfigFile;
 use deno_config::workspace::JsrPackageConfig;
 use deno_config::workspace::Workspace;
+use deno_core::anyhow::anyhow;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
@@ -36,6 +37,8 @@ use tokio::process::Command;
 use crate::args::jsr_api_url;
 use crate::args::jsr_url;
 use crate::args::CliOptions;
+use crate::args::ConfigFlag;
+use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::args::PublishFlags;
 use crate::cache::LazyGraphSourceParser;
@@ -66,9 +69,17 @@ use auth::AuthMethod;
 pub use pm::add;
 pub use pm::cache_top_level_deps;
 pub use pm::outdated;
+pub use pm::outdated_dependencies;
 pub use pm::remove;
+pub use pm::update_dependencies;
 pub use pm::AddCommandName;
 pub use pm::AddRmPackageReq;
+pub use pm::ConfigFileChange;
+pub use pm::DependencyUpdate;
+pub use pm::OutdatedDependency;
+pub use pm::OutdatedOptions;
+pub use pm::UpdateDependenciesOptions;
+pub use pm::UpdateReport;
 use publish_order::PublishOrderGraph;
 use unfurl::SpecifierUnfurler;
 
@@ -87,8 +98,244 @@ pub async fn publish(
   let auth_method =
     get_auth_method(publish_flags.token, publish_flags.dry_run)?;
 
+  let directory_path = cli_factory.cli_options()?.initial_cwd().to_path_buf();
+  let diagnostics_collector = PublishDiagnosticsCollector::default();
+  let prepared_data = prepare_packages(
+    &cli_factory,
+    &directory_path,
+    publish_flags.allow_slow_types,
+    publish_flags.set_version.as_deref(),
+    &diagnostics_collector,
+  )
+  .await?;
+
+  diagnostics_collector.print_and_error()?;
+
+  if prepared_data.package_by_name.is_empty() {
+    bail!("No packages to publish");
+  }
+
+  ensure_clean_git_repo(&directory_path, publish_flags.allow_dirty).await?;
+
+  if publish_flags.dry_run {
+    for (_, package) in prepared_data.package_by_name {
+      log::info!(
+        "{} of {} with files:",
+        colors::green_bold("Simulating publish"),
+        colors::gray(package.display_name()),
+      );
+      for file in &package.tarball.files {
+        log::info!("   {} ({})", file.specifier, human_size(file.size as f64),);
+      }
+    }
+    log::warn!("{} Dry run complete", colors::green("Success"));
+    return Ok(());
+  }
+
+  perform_publish(
+    &cli_factory.http_client_provider().get_or_create()?,
+    prepared_data.publish_order_graph,
+    prepared_data.package_by_name,
+    auth_method,
+    !publish_flags.no_provenance,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Options of [`publish_packages`].
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+  /// Directory of the package or workspace to publish. Defaults to the
+  /// current working directory.
+  pub package_dir: Option<PathBuf>,
+  /// Prepares and validates the packages without uploading them.
+  pub dry_run: bool,
+  /// Publishes a provenance statement, only possible on GitHub Actions.
+  pub provenance: bool,
+  /// Access token for JSR. When `None`, GitHub Actions OIDC is used.
+  pub token: Option<String>,
+  pub allow_slow_types: bool,
+  /// Publishes even when the git repository has uncommitted changes.
+  pub allow_dirty: bool,
+  /// Overrides the version of the package, not possible for workspaces.
+  pub set_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PublishDiagnosticLevel {
+  Error,
+  Warning,
+}
+
+/// A problem found while preparing a package for publishing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishReportDiagnostic {
+  pub level: PublishDiagnosticLevel,
+  /// Eg. `"missing-explicit-return-type"`.
+  pub code: String,
+  pub message: String,
+  pub hint: Option<String>,
+  /// Url of the module, or path of the file, the diagnostic is about.
+  pub location: Option<String>,
+  /// 0-indexed line and column in the module.
+  pub position: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedFile {
+  pub specifier: Url,
+  pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedPackage {
+  /// Eg. `"@std/path"`.
+  pub name: String,
+  pub version: String,
+  pub files: Vec<PublishedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishReport {
+  /// The packages that were published, or would be with `dry_run`.
+  pub packages: Vec<PublishedPackage>,
+  /// Warnings that didn't prevent publishing.
+  pub diagnostics: Vec<PublishReportDiagnostic>,
+  pub dry_run: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PublishError {
+  /// The packages have problems that prevent publishing them. Warnings
+  /// are included.
+  #[error(
+    "Found {} problem(s)",
+    .0.iter().filter(|d| d.level == PublishDiagnosticLevel::Error).count()
+  )]
+  Diagnostics(Vec<PublishReportDiagnostic>),
+  #[error(transparent)]
+  Other(#[from] AnyError),
+}
+
+/// Publishes a package or workspace to JSR, like `deno publish` does, and
+/// returns what was published instead of printing it.
+pub async fn publish_packages(
+  mut flags: Arc<Flags>,
+  options: PublishOptions,
+) -> Result<PublishReport, PublishError> {
+  let initial_cwd = std::env::current_dir().context("Failed getting cwd.")?;
+  let directory_path = match &options.package_dir {
+    Some(package_dir) => initial_cwd.join(package_dir),
+    None => initial_cwd,
+  };
+  {
+    let flags = Arc::make_mut(&mut flags);
+    flags.subcommand = DenoSubcommand::Publish(PublishFlags {
+      token: options.token.clone(),
+      dry_run: options.dry_run,
+      allow_slow_types: options.allow_slow_types,
+      allow_dirty: options.allow_dirty,
+      no_provenance: !options.provenance,
+      set_version: options.set_version.clone(),
+    });
+    if options.package_dir.is_some() {
+      let config_path = ["deno.json", "deno.jsonc", "jsr.json", "jsr.jsonc"]
+        .iter()
+        .map(|name| directory_path.join(name))
+        .find(|path| path.is_file());
+      let Some(config_path) = config_path else {
+        return Err(PublishError::Other(anyhow!(
+          "Couldn't find a deno.json, deno.jsonc, jsr.json or jsr.jsonc configuration file in {}.",
+          directory_path.display()
+        )));
+      };
+      flags.config_flag =
+        ConfigFlag::Path(config_path.to_string_lossy().to_string());
+    }
+  }
+  let cli_factory = CliFactory::from_flags(flags);
+  let auth_method = get_auth_method(options.token, options.dry_run)?;
+
+  let diagnostics_collector = PublishDiagnosticsCollector::default();
+  let prepared_data = prepare_packages(
+    &cli_factory,
+    &directory_path,
+    options.allow_slow_types,
+    options.set_version.as_deref(),
+    &diagnostics_collector,
+  )
+  .await?;
+  let diagnostics = diagnostics_collector
+    .take()
+    .iter()
+    .map(|diagnostic| diagnostic.to_report())
+    .collect::<Vec<_>>();
+  if diagnostics
+    .iter()
+    .any(|d| d.level == PublishDiagnosticLevel::Error)
+  {
+    return Err(PublishError::Diagnostics(diagnostics));
+  }
+
+  if prepared_data.package_by_name.is_empty() {
+    return Err(PublishError::Other(anyhow!("No packages to publish")));
+  }
+  ensure_clean_git_repo(&directory_path, options.allow_dirty).await?;
+
+  let mut packages = prepared_data
+    .package_by_name
+    .values()
+    .map(|package| PublishedPackage {
+      name: format!("@{}/{}", package.scope, package.package),
+      version: package.version.clone(),
+      files: package
+        .tarball
+        .files
+        .iter()
+        .map(|file| PublishedFile {
+          specifier: file.specifier.clone(),
+          size: file.size,
+        })
+        .collect(),
+    })
+    .collect::<Vec<_>>();
+  packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+  if !options.dry_run {
+    perform_publish(
+      &cli_factory.http_client_provider().get_or_create()?,
+      prepared_data.publish_order_graph,
+      prepared_data.package_by_name,
+      auth_method,
+      options.provenance,
+    )
+    .await?;
+  }
+
+  Ok(PublishReport {
+    packages,
+    diagnostics,
+    dry_run: options.dry_run,
+  })
+}
+
+/// Resolves the packages to publish from the config file found in
+/// `directory_path`, and checks and packs them.
+async fn prepare_packages(
+  cli_factory: &CliFactory,
+  directory_path: &Path,
+  allow_slow_types: bool,
+  set_version: Option<&str>,
+  diagnostics_collector: &PublishDiagnosticsCollector,
+) -> Result<PreparePackagesData, AnyError> {
   let cli_options = cli_factory.cli_options()?;
-  let directory_path = cli_options.initial_cwd();
   let mut publish_configs = cli_options.start_dir.jsr_packages_for_publish();
   if publish_configs.is_empty() {
     match cli_options.start_dir.maybe_deno_json() {
@@ -108,13 +355,13 @@ pub async fn publish(
     }
   }
 
-  if let Some(version) = &publish_flags.set_version {
+  if let Some(version) = set_version {
     if publish_configs.len() > 1 {
       bail!("Cannot use --set-version when publishing a workspace. Change your cwd to an individual package instead.");
     }
     if let Some(publish_config) = publish_configs.get_mut(0) {
       let mut config_file = publish_config.config_file.as_ref().clone();
-      config_file.json.version = Some(version.clone());
+      config_file.json.version = Some(version.to_string());
       publish_config.config_file = Arc::new(config_file);
     }
   }
@@ -125,7 +372,6 @@ pub async fn publish(
     cli_options.unstable_bare_node_builtins(),
   ));
 
-  let diagnostics_collector = PublishDiagnosticsCollector::default();
   let publish_preparer = PublishPreparer::new(
     GraphDiagnosticsCollector::new(cli_factory.parsed_source_cache().clone()),
     cli_factory.module_graph_creator().await?.clone(),
@@ -135,57 +381,29 @@ pub async fn publish(
     specifier_unfurler,
   );
 
-  let prepared_data = publish_preparer
+  publish_preparer
     .prepare_packages_for_publishing(
-      publish_flags.allow_slow_types,
-      &diagnostics_collector,
+      allow_slow_types,
+      diagnostics_collector,
       publish_configs,
     )
-    .await?;
-
-  diagnostics_collector.print_and_error()?;
-
-  if prepared_data.package_by_name.is_empty() {
-    bail!("No packages to publish");
-  }
+    .await
+}
 
+async fn ensure_clean_git_repo(
+  directory_path: &Path,
+  allow_dirty: bool,
+) -> Result<(), AnyError> {
   if std::env::var("DENO_TESTING_DISABLE_GIT_CHECK")
     .ok()
     .is_none()
-    && !publish_flags.allow_dirty
+    && !allow_dirty
   {
-    if let Some(dirty_text) =
-      check_if_git_repo_dirty(cli_options.initial_cwd()).await
-    {
+    if let Some(dirty_text) = check_if_git_repo_dirty(directory_path).await {
       log::error!("\nUncommitted changes:\n\n{}\n", dirty_text);
       bail!("Aborting due to uncommitted changes. Check in source code or run with --allow-dirty");
     }
   }
-
-  if publish_flags.dry_run {
-    for (_, package) in prepared_data.package_by_name {
-      log::info!(
-        "{} of {} with files:",
-        colors::green_bold("Simulating publish"),
-        colors::gray(package.display_name()),
-      );
-      for file in &package.tarball.files {
-        log::info!("   {} ({})", file.specifier, human_size(file.size as f64),);
-      }
-    }
-    log::warn!("{} Dry run complete", colors::green("Success"));
-    return Ok(());
-  }
-
-  perform_publish(
-    &cli_factory.http_client_provider().get_or_create()?,
-    prepared_data.publish_order_graph,
-    prepared_data.package_by_name,
-    auth_method,
-    !publish_flags.no_provenance,
-  )
-  .await?;
-
   Ok(())
 }
 
@@ -1282,9 +1500,18 @@ fn ring_bell() {
 
 #[cfg(test)]
 mod tests {
+  use std::sync::Arc;
+
   use deno_ast::ModuleSpecifier;
+  use test_util::TempDir;
 
+  use crate::args::Flags;
+  use crate::args::InternalFlags;
   use crate::tools::registry::has_license_file;
+  use crate::tools::registry::publish_packages;
+  use crate::tools::registry::PublishDiagnosticLevel;
+  use crate::tools::registry::PublishError;
+  use crate::tools::registry::PublishOptions;
 
   use super::tar::PublishableTarball;
   use super::tar::PublishableTarballFile;
@@ -1414,4 +1641,108 @@ mod tests {
       "file:///test/tLICENSE"
     ]),);
   }
+
+  fn publish_flags(temp_dir: &TempDir) -> Arc<Flags> {
+    Arc::new(Flags {
+      internal: InternalFlags {
+        cache_path: Some(temp_dir.path().join("deno_dir").to_path_buf()),
+        ..Default::default()
+      },
+      ..Default::default()
+    })
+  }
+
+  #[tokio::test]
+  async fn publish_packages_dry_run() {
+    let temp_dir = TempDir::new();
+    let package_dir = temp_dir.path().join("package");
+    package_dir.create_dir_all();
+    package_dir.join("deno.json").write(
+      r#"{ "name": "@scope/pkg", "version": "1.0.0", "exports": "./mod.ts", "license": "MIT" }"#,
+    );
+    package_dir
+      .join("mod.ts")
+      .write("export function add(a: number, b: number): number {\n  return a + b;\n}\n");
+    let package_dir = package_dir.canonicalize();
+
+    let report = publish_packages(
+      publish_flags(&temp_dir),
+      PublishOptions {
+        package_dir: Some(package_dir.to_path_buf()),
+        dry_run: true,
+        allow_dirty: true,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.diagnostics, Vec::new());
+    assert_eq!(report.packages.len(), 1);
+    let package = &report.packages[0];
+    assert_eq!(package.name, "@scope/pkg");
+    assert_eq!(package.version, "1.0.0");
+    let mut specifiers = package
+      .files
+      .iter()
+      .map(|file| file.specifier.clone())
+      .collect::<Vec<_>>();
+    specifiers.sort();
+    assert_eq!(
+      specifiers,
+      vec![
+        ModuleSpecifier::from_file_path(package_dir.join("deno.json")).unwrap(),
+        ModuleSpecifier::from_file_path(package_dir.join("mod.ts")).unwrap(),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn publish_packages_returns_diagnostics() {
+    let temp_dir = TempDir::new();
+    let package_dir = temp_dir.path().join("package");
+    package_dir.create_dir_all();
+    package_dir.join("deno.json").write(
+      r#"{ "name": "@scope/pkg", "version": "1.0.0", "exports": "./mod.ts" }"#,
+    );
+    package_dir.join("mod.ts").write(
+      "export function add(a: number, b: number) {\n  return a + b;\n}\n",
+    );
+
+    let err = publish_packages(
+      publish_flags(&temp_dir),
+      PublishOptions {
+        package_dir: Some(package_dir.to_path_buf()),
+        dry_run: true,
+        allow_dirty: true,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+    let PublishError::Diagnostics(diagnostics) = err else {
+      panic!("expected diagnostics, got {err:?}");
+    };
+    let mut codes = diagnostics
+      .iter()
+      .map(|d| (d.level, d.code.as_str()))
+      .collect::<Vec<_>>();
+    codes.sort_by_key(|(_, code)| *code);
+    assert_eq!(
+      codes,
+      vec![
+        (
+          PublishDiagnosticLevel::Error,
+          "missing-explicit-return-type"
+        ),
+        (PublishDiagnosticLevel::Error, "missing-license"),
+      ]
+    );
+    let return_type = diagnostics
+      .iter()
+      .find(|d| d.code == "missing-explicit-return-type")
+      .unwrap();
+    assert!(return_type.location.as_ref().unwrap().ends_with("/mod.ts"));
+    assert_eq!(return_type.position.map(|(line, _)| line), Some(0));
+  }
 }
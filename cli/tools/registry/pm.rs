@@ -37,7 +37,15 @@ pub(crate) mod deps;
 mod outdated;
 
 pub use cache_deps::cache_top_level_deps;
+pub use deps::ConfigFileChange;
 pub use outdated::outdated;
+pub use outdated::outdated_dependencies;
+pub use outdated::update_dependencies;
+pub use outdated::DependencyUpdate;
+pub use outdated::OutdatedDependency;
+pub use outdated::OutdatedOptions;
+pub use outdated::UpdateDependenciesOptions;
+pub use outdated::UpdateReport;
 
 #[derive(Debug, Copy, Clone, Hash)]
 enum ConfigKind {
@@ -50,6 +58,7 @@ struct ConfigUpdater {
   cst: CstRootNode,
   root_object: CstObject,
   path: PathBuf,
+  original: String,
   modified: bool,
 }
 
@@ -72,6 +81,7 @@ impl ConfigUpdater {
       cst,
       root_object,
       path: config_file_path,
+      original: config_file_contents,
       modified: false,
     })
   }
@@ -239,13 +249,26 @@ impl ConfigUpdater {
     removed
   }
 
-  fn commit(&self) -> Result<(), AnyError> {
+  /// The change [`ConfigUpdater::commit`] would write, if any.
+  fn preview(&self) -> Option<ConfigFileChange> {
     if !self.modified {
-      return Ok(());
+      return None;
     }
 
     let new_text = self.contents();
-    std::fs::write(&self.path, new_text).with_context(|| {
+    (new_text != self.original).then(|| ConfigFileChange {
+      path: self.path.clone(),
+      original: self.original.clone(),
+      updated: new_text,
+    })
+  }
+
+  fn commit(&self) -> Result<(), AnyError> {
+    let Some(change) = self.preview() else {
+      return Ok(());
+    };
+
+    std::fs::write(&self.path, change.updated).with_context(|| {
       format!("failed writing to '{}'", self.path.display())
     })?;
     Ok(())
@@ -750,6 +750,28 @@ impl DepManager {
   }
 
   pub fn commit_changes(&mut self) -> Result<(), AnyError> {
+    for (_, updater) in self.apply_changes()? {
+      updater.commit()?;
+    }
+
+    Ok(())
+  }
+
+  /// Applies the pending changes like [`DepManager::commit_changes`], but
+  /// returns the resulting config file contents instead of writing them.
+  pub fn preview_changes(&mut self) -> Result<Vec<ConfigFileChange>, AnyError> {
+    let mut changes = self
+      .apply_changes()?
+      .into_values()
+      .filter_map(|updater| updater.preview())
+      .collect::<Vec<_>>();
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+  }
+
+  fn apply_changes(
+    &mut self,
+  ) -> Result<HashMap<std::path::PathBuf, ConfigUpdater>, AnyError> {
     let changes = std::mem::take(&mut self.pending_changes);
     let mut config_updaters = HashMap::new();
     for change in changes {
@@ -842,11 +864,22 @@ impl DepManager {
       }
     }
 
-    for (_, updater) in config_updaters {
-      updater.commit()?;
-    }
+    Ok(config_updaters)
+  }
+}
 
-    Ok(())
+/// The contents of a config file before and after updating dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFileChange {
+  pub path: std::path::PathBuf,
+  pub original: String,
+  pub updated: String,
+}
+
+impl ConfigFileChange {
+  /// A line diff of the change, formatted like `deno fmt --check` output.
+  pub fn diff(&self) -> String {
+    crate::util::diff::diff(&self.original, &self.updated)
   }
 }
 
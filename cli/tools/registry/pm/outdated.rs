@@ -1,11 +1,14 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_semver::package::PackageNv;
 use deno_semver::package::PackageReq;
+use deno_semver::Version;
 use deno_semver::VersionReq;
 use deno_terminal::colors;
 
@@ -19,7 +22,9 @@ use crate::jsr::JsrFetchResolver;
 use crate::npm::NpmFetchResolver;
 use crate::tools::registry::pm::deps::DepKind;
 
+use super::deps::ConfigFileChange;
 use super::deps::Dep;
+use super::deps::DepId;
 use super::deps::DepManager;
 use super::deps::DepManagerArgs;
 use super::deps::PackageLatestVersion;
@@ -158,6 +163,27 @@ pub async fn outdated(
   update_flags: OutdatedFlags,
 ) -> Result<(), AnyError> {
   let factory = CliFactory::from_flags(flags.clone());
+  let (mut deps, filter_set) =
+    create_dep_manager(&factory, &update_flags.filters, update_flags.recursive)
+      .await?;
+
+  match update_flags.kind {
+    crate::args::OutdatedKind::Update { latest } => {
+      update(deps, latest, &filter_set, flags).await?;
+    }
+    crate::args::OutdatedKind::PrintOutdated { compatible } => {
+      print_outdated(&mut deps, compatible)?;
+    }
+  }
+
+  Ok(())
+}
+
+async fn create_dep_manager(
+  factory: &CliFactory,
+  filters: &[String],
+  recursive: bool,
+) -> Result<(DepManager, filter::FilterSet), AnyError> {
   let cli_options = factory.cli_options()?;
   let workspace = cli_options.workspace();
   let http_client = factory.http_client_provider();
@@ -180,16 +206,15 @@ pub async fn outdated(
     Arc::new(JsrFetchResolver::new(file_fetcher.clone()));
 
   let args = dep_manager_args(
-    &factory,
+    factory,
     cli_options,
     npm_fetch_resolver.clone(),
     jsr_fetch_resolver.clone(),
   )
   .await?;
 
-  let filter_set = filter::FilterSet::from_filter_strings(
-    update_flags.filters.iter().map(|s| s.as_str()),
-  )?;
+  let filter_set =
+    filter::FilterSet::from_filter_strings(filters.iter().map(|s| s.as_str()))?;
 
   let filter_fn = |alias: Option<&str>, req: &PackageReq, _: DepKind| {
     if filter_set.is_empty() {
@@ -198,7 +223,7 @@ pub async fn outdated(
     let name = alias.unwrap_or(&req.name);
     filter_set.matches(name)
   };
-  let mut deps = if update_flags.recursive {
+  let mut deps = if recursive {
     super::deps::DepManager::from_workspace(workspace, filter_fn, args)?
   } else {
     super::deps::DepManager::from_workspace_dir(
@@ -210,16 +235,7 @@ pub async fn outdated(
 
   deps.resolve_versions().await?;
 
-  match update_flags.kind {
-    crate::args::OutdatedKind::Update { latest } => {
-      update(deps, latest, &filter_set, flags).await?;
-    }
-    crate::args::OutdatedKind::PrintOutdated { compatible } => {
-      print_outdated(&mut deps, compatible)?;
-    }
-  }
-
-  Ok(())
+  Ok((deps, filter_set))
 }
 
 fn choose_new_version_req(
@@ -260,12 +276,20 @@ fn choose_new_version_req(
   }
 }
 
-async fn update(
-  mut deps: DepManager,
+struct PendingUpdate {
+  dep_id: DepId,
+  package_name: String,
+  config_file: PathBuf,
+  current_version: Option<PackageNv>,
+  new_version_req: VersionReq,
+}
+
+/// Queues an update on `deps` for every dependency with a newer version.
+fn queue_updates(
+  deps: &mut DepManager,
   update_to_latest: bool,
   filter_set: &filter::FilterSet,
-  flags: Arc<Flags>,
-) -> Result<(), AnyError> {
+) -> Vec<PendingUpdate> {
   let mut updated = Vec::new();
 
   for (dep_id, resolved, latest_versions) in deps
@@ -285,52 +309,85 @@ async fn update(
       continue;
     };
 
-    updated.push((
+    updated.push(PendingUpdate {
       dep_id,
-      format!("{}:{}", dep.kind.scheme(), dep.req.name),
-      deps.resolved_version(dep.id).cloned(),
-      new_version_req.clone(),
-    ));
+      package_name: format!("{}:{}", dep.kind.scheme(), dep.req.name),
+      config_file: dep.location.file_path().into_owned(),
+      current_version: deps.resolved_version(dep.id).cloned(),
+      new_version_req: new_version_req.clone(),
+    });
 
     deps.update_dep(dep_id, new_version_req);
   }
 
+  updated
+}
+
+/// Installs the dependencies of the modified config files and resolves the
+/// versions the updated requirements now point to.
+async fn install_updated(
+  deps: DepManager,
+  updated: &[PendingUpdate],
+  flags: Arc<Flags>,
+) -> Result<Vec<Option<Version>>, AnyError> {
+  let factory = super::npm_install_after_modification(
+    flags,
+    Some(deps.jsr_fetch_resolver.clone()),
+  )
+  .await?;
+
+  let cli_options = factory.cli_options()?;
+  let args = dep_manager_args(
+    &factory,
+    cli_options,
+    deps.npm_fetch_resolver.clone(),
+    deps.jsr_fetch_resolver.clone(),
+  )
+  .await?;
+
+  let mut deps = deps.reloaded_after_modification(args);
+  deps.resolve_current_versions().await?;
+  Ok(
+    updated
+      .iter()
+      .map(|update| {
+        let version = deps
+          .resolved_version(update.dep_id)
+          .map(|nv| nv.version.clone());
+        if version.is_none() {
+          log::warn!(
+            "Failed to resolve version for new version requirement: {} -> {}",
+            update.package_name,
+            update.new_version_req
+          );
+        }
+        version
+      })
+      .collect(),
+  )
+}
+
+async fn update(
+  mut deps: DepManager,
+  update_to_latest: bool,
+  filter_set: &filter::FilterSet,
+  flags: Arc<Flags>,
+) -> Result<(), AnyError> {
+  let updated = queue_updates(&mut deps, update_to_latest, filter_set);
+
   deps.commit_changes()?;
 
   if !updated.is_empty() {
-    let factory = super::npm_install_after_modification(
-      flags.clone(),
-      Some(deps.jsr_fetch_resolver.clone()),
-    )
-    .await?;
+    let new_versions = install_updated(deps, &updated, flags).await?;
 
     let mut updated_to_versions = HashSet::new();
-    let cli_options = factory.cli_options()?;
-    let args = dep_manager_args(
-      &factory,
-      cli_options,
-      deps.npm_fetch_resolver.clone(),
-      deps.jsr_fetch_resolver.clone(),
-    )
-    .await?;
-
-    let mut deps = deps.reloaded_after_modification(args);
-    deps.resolve_current_versions().await?;
-    for (dep_id, package_name, maybe_current_version, new_version_req) in
-      updated
-    {
-      if let Some(nv) = deps.resolved_version(dep_id) {
+    for (update, new_version) in updated.into_iter().zip(new_versions) {
+      if let Some(new_version) = new_version {
         updated_to_versions.insert((
-          package_name,
-          maybe_current_version,
-          nv.version.clone(),
+          update.package_name,
+          update.current_version,
+          new_version,
         ));
-      } else {
-        log::warn!(
-          "Failed to resolve version for new version requirement: {} -> {}",
-          package_name,
-          new_version_req
-        );
       }
     }
 
@@ -405,6 +462,154 @@ async fn update(
   Ok(())
 }
 
+/// A dependency for which a newer version than the resolved one exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutdatedDependency {
+  /// The package name prefixed with its scheme, e.g. `jsr:@std/path`.
+  pub package: String,
+  /// The `deno.json` or `package.json` the dependency is declared in.
+  pub config_file: PathBuf,
+  pub version_req: VersionReq,
+  pub current: Version,
+  /// The newest version matching the current version requirement.
+  pub semver_compatible: Option<Version>,
+  pub latest: Option<Version>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OutdatedOptions {
+  /// Package name patterns, as accepted by `deno outdated`, e.g. `@std/*`
+  /// or `!chalk`. All dependencies are included when empty.
+  pub filters: Vec<String>,
+  /// Whether to include the dependencies of every workspace member instead
+  /// of only the current directory's.
+  pub recursive: bool,
+}
+
+/// Returns the jsr and npm dependencies of the workspace that have newer
+/// versions available, like `deno outdated`.
+pub async fn outdated_dependencies(
+  flags: Arc<Flags>,
+  options: OutdatedOptions,
+) -> Result<Vec<OutdatedDependency>, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let (deps, _) =
+    create_dep_manager(&factory, &options.filters, options.recursive).await?;
+
+  let mut outdated = Vec::new();
+  let mut seen = std::collections::BTreeSet::new();
+  for (dep_id, resolved, latest_versions) in
+    deps.deps_with_resolved_latest_versions()
+  {
+    let dep = deps.get_dep(dep_id);
+    let Some(resolved) = resolved else { continue };
+    let is_outdated =
+      [&latest_versions.semver_compatible, &latest_versions.latest]
+        .into_iter()
+        .flatten()
+        .any(|nv| nv > &resolved);
+    if !is_outdated {
+      continue;
+    }
+    let config_file = dep.location.file_path().into_owned();
+    if !seen.insert((
+      dep.kind,
+      dep.req.name.clone(),
+      resolved.version.clone(),
+      config_file.clone(),
+    )) {
+      continue;
+    }
+    outdated.push(OutdatedDependency {
+      package: format!("{}:{}", dep.kind.scheme(), dep.req.name),
+      config_file,
+      version_req: dep.req.version_req.clone(),
+      current: resolved.version,
+      semver_compatible: latest_versions.semver_compatible.map(|nv| nv.version),
+      latest: latest_versions.latest.map(|nv| nv.version),
+    });
+  }
+  outdated.sort_by(|a, b| {
+    (&a.package, &a.config_file).cmp(&(&b.package, &b.config_file))
+  });
+  Ok(outdated)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateDependenciesOptions {
+  /// Package name patterns, as accepted by `deno outdated --update`. A
+  /// pattern may pin a version requirement, e.g. `@std/path@^1.0.0`.
+  pub filters: Vec<String>,
+  pub recursive: bool,
+  /// Whether to update to the latest version even if it's semver
+  /// incompatible with the current requirement.
+  pub latest: bool,
+  /// Only compute the config file changes, without writing them or
+  /// installing the updated dependencies.
+  pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyUpdate {
+  /// The package name prefixed with its scheme, e.g. `npm:chalk`.
+  pub package: String,
+  pub config_file: PathBuf,
+  /// The version resolved before the update.
+  pub previous: Option<Version>,
+  /// The version requirement written to the config file.
+  pub version_req: VersionReq,
+  /// The version resolved after the update. Always `None` for dry runs.
+  pub resolved: Option<Version>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateReport {
+  pub updates: Vec<DependencyUpdate>,
+  /// The modified config files, which for dry runs are left untouched on
+  /// disk.
+  pub changes: Vec<ConfigFileChange>,
+}
+
+/// Updates the version requirements of outdated dependencies in `deno.json`
+/// and `package.json` files, like `deno outdated --update`, and installs
+/// the new versions.
+pub async fn update_dependencies(
+  flags: Arc<Flags>,
+  options: UpdateDependenciesOptions,
+) -> Result<UpdateReport, AnyError> {
+  let factory = CliFactory::from_flags(flags.clone());
+  let (mut deps, filter_set) =
+    create_dep_manager(&factory, &options.filters, options.recursive).await?;
+
+  let updated = queue_updates(&mut deps, options.latest, &filter_set);
+  let changes = deps.preview_changes()?;
+
+  let new_versions = if options.dry_run || updated.is_empty() {
+    vec![None; updated.len()]
+  } else {
+    for change in &changes {
+      std::fs::write(&change.path, &change.updated).with_context(|| {
+        format!("failed writing to '{}'", change.path.display())
+      })?;
+    }
+    install_updated(deps, &updated, flags).await?
+  };
+
+  let updates = updated
+    .into_iter()
+    .zip(new_versions)
+    .map(|(update, resolved)| DependencyUpdate {
+      package: update.package_name,
+      config_file: update.config_file,
+      previous: update.current_version.map(|nv| nv.version),
+      version_req: update.new_version_req,
+      resolved,
+    })
+    .collect();
+
+  Ok(UpdateReport { updates, changes })
+}
+
 async fn dep_manager_args(
   factory: &CliFactory,
   cli_options: &CliOptions,
@@ -167,7 +167,8 @@ fn is_word_boundary(c: char) -> bool {
   }
 }
 
-fn get_expr_from_line_at_pos(line: &str, cursor_pos: usize) -> &str {
+/// Returns the expression at `cursor_pos`, eg. `Deno.env` in `Deno.env.g`.
+pub fn get_expr_from_line_at_pos(line: &str, cursor_pos: usize) -> &str {
   let start = line[..cursor_pos].rfind(is_word_boundary).unwrap_or(0);
   let end = line[cursor_pos..]
     .rfind(is_word_boundary)
@@ -181,22 +182,16 @@ fn get_expr_from_line_at_pos(line: &str, cursor_pos: usize) -> &str {
   word
 }
 
-impl Completer for EditorHelper {
-  type Candidate = String;
-
-  fn complete(
-    &self,
-    line: &str,
-    pos: usize,
-    _ctx: &Context<'_>,
-  ) -> Result<(usize, Vec<String>), ReadlineError> {
+impl EditorHelper {
+  /// Returns the start of the completed word and the candidates for it.
+  pub fn complete_line(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
     let lsp_completions = self.sync_sender.lsp_completions(line, pos);
     if !lsp_completions.is_empty() {
       // assumes all lsp completions have the same start position
-      return Ok((
+      return (
         lsp_completions[0].range.start,
         lsp_completions.into_iter().map(|c| c.new_text).collect(),
-      ));
+      );
     }
 
     let expr = get_expr_from_line_at_pos(line, pos);
@@ -215,7 +210,8 @@ impl Completer for EditorHelper {
         })
         .collect();
 
-      Ok((pos - prop_name.len(), candidates))
+      // the expression may continue after the cursor
+      (pos.saturating_sub(prop_name.len()), candidates)
     } else {
       // combine results of declarations and globalThis properties
       let mut candidates = self
@@ -229,11 +225,24 @@ impl Completer for EditorHelper {
       candidates.sort();
       candidates.dedup(); // make sure to sort first
 
-      Ok((pos - expr.len(), candidates))
+      (pos.saturating_sub(expr.len()), candidates)
     }
   }
 }
 
+impl Completer for EditorHelper {
+  type Candidate = String;
+
+  fn complete(
+    &self,
+    line: &str,
+    pos: usize,
+    _ctx: &Context<'_>,
+  ) -> Result<(usize, Vec<String>), ReadlineError> {
+    Ok(self.complete_line(line, pos))
+  }
+}
+
 impl Validator for EditorHelper {
   fn validate(
     &self,
@@ -16,7 +16,6 @@ use deno_core::error::AnyError;
 use deno_core::futures::StreamExt;
 use deno_core::serde_json;
 use deno_core::unsync::spawn_blocking;
-use deno_runtime::WorkerExecutionMode;
 use rustyline::error::ReadlineError;
 
 mod channel;
@@ -24,17 +23,14 @@ mod editor;
 mod session;
 
 use channel::rustyline_channel;
-use channel::RustylineSyncMessage;
 use channel::RustylineSyncMessageHandler;
-use channel::RustylineSyncResponse;
+pub use editor::get_expr_from_line_at_pos;
 use editor::EditorHelper;
 use editor::ReplEditor;
 pub use session::EvaluationOutput;
+pub use session::ReplCompletions;
+pub use session::ReplOutput;
 pub use session::ReplSession;
-pub use session::TsEvaluateResponse;
-pub use session::REPL_INTERNALS_NAME;
-
-use super::test::create_single_test_event_channel;
 
 struct Repl {
   session: ReplSession,
@@ -105,22 +101,12 @@ async fn read_line_and_poll(
         return result.unwrap();
       }
       result = message_handler.recv() => {
-        match result {
-          Some(RustylineSyncMessage::PostMessage { method, params }) => {
-            let result = repl_session
-              .post_message_with_event_loop(&method, params)
-              .await;
-            message_handler.send(RustylineSyncResponse::PostMessage(result)).unwrap();
-          },
-          Some(RustylineSyncMessage::LspCompletions {
-            line_text,
-            position,
-          }) => {
-            let result = repl_session.language_server.completions(&line_text, position).await;
-            message_handler.send(RustylineSyncResponse::LspCompletions(result)).unwrap();
-          }
-          None => {}, // channel closed
-        }
+        if let Some(message) = result {
+          repl_session
+            .handle_sync_message(message, message_handler)
+            .await
+            .unwrap();
+        } // else channel closed
 
         poll_worker = true;
       }
@@ -161,38 +147,12 @@ pub async fn run(
 ) -> Result<i32, AnyError> {
   let factory = CliFactory::from_flags(flags);
   let cli_options = factory.cli_options()?;
-  let main_module = cli_options.resolve_main_module()?;
-  let permissions = factory.root_permissions_container()?;
-  let npm_resolver = factory.npm_resolver().await?.clone();
-  let resolver = factory.resolver().await?.clone();
   let file_fetcher = factory.file_fetcher()?;
-  let worker_factory = factory.create_cli_main_worker_factory().await?;
   let history_file_path = factory
     .deno_dir()
     .ok()
     .and_then(|dir| dir.repl_history_file_path());
-  let (worker, test_event_receiver) = create_single_test_event_channel();
-  let test_event_sender = worker.sender;
-  let mut worker = worker_factory
-    .create_custom_worker(
-      WorkerExecutionMode::Repl,
-      main_module.clone(),
-      permissions.clone(),
-      vec![crate::ops::testing::deno_test::init_ops(test_event_sender)],
-      Default::default(),
-    )
-    .await?;
-  worker.setup_repl().await?;
-  let worker = worker.into_main_worker();
-  let session = ReplSession::initialize(
-    cli_options,
-    npm_resolver,
-    resolver,
-    worker,
-    main_module.clone(),
-    test_event_receiver,
-  )
-  .await?;
+  let session = ReplSession::new(&factory, vec![]).await?;
   let rustyline_channel = rustyline_channel();
 
   let helper = EditorHelper {
@@ -5,9 +5,11 @@ use std::sync::Arc;
 use crate::args::CliOptions;
 use crate::cdp;
 use crate::colors;
+use crate::factory::CliFactory;
 use crate::lsp::ReplLanguageServer;
 use crate::npm::CliNpmResolver;
 use crate::resolver::CliResolver;
+use crate::tools::test::create_single_test_event_channel;
 use crate::tools::test::report_tests;
 use crate::tools::test::reporters::PrettyTestReporter;
 use crate::tools::test::reporters::TestReporter;
@@ -40,13 +42,18 @@ use deno_core::futures::StreamExt;
 use deno_core::serde_json;
 use deno_core::serde_json::Value;
 use deno_core::unsync::spawn;
+use deno_core::unsync::spawn_blocking;
 use deno_core::url::Url;
+use deno_core::Extension;
 use deno_core::LocalInspectorSession;
 use deno_core::PollEventLoopOptions;
 use deno_graph::Position;
 use deno_graph::PositionRange;
 use deno_graph::SpecifierWithRange;
+use deno_runtime::deno_io::Stdio;
+use deno_runtime::deno_permissions::PermissionsContainer;
 use deno_runtime::worker::MainWorker;
+use deno_runtime::WorkerExecutionMode;
 use deno_semver::npm::NpmPackageReqReference;
 use node_resolver::NodeResolutionKind;
 use node_resolver::ResolutionMode;
@@ -55,6 +62,12 @@ use regex::Match;
 use regex::Regex;
 use tokio::sync::Mutex;
 
+use super::channel::rustyline_channel;
+use super::channel::RustylineSyncMessage;
+use super::channel::RustylineSyncMessageHandler;
+use super::channel::RustylineSyncResponse;
+use super::editor::EditorHelper;
+
 fn comment_source_to_position_range(
   comment_start: SourcePos,
   m: &Match,
@@ -155,6 +168,27 @@ impl std::fmt::Display for EvaluationOutput {
   }
 }
 
+/// Result of [`ReplSession::evaluate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplOutput {
+  /// The value or the error, formatted the way `deno repl` prints it.
+  pub text: String,
+  /// Whether the line threw or couldn't be parsed.
+  pub is_error: bool,
+  /// Whether the line called `close()`, after which the session should be
+  /// dropped.
+  pub closed: bool,
+}
+
+/// Result of [`ReplSession::complete`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplCompletions {
+  /// Byte offset in the line where the completed word starts. A candidate
+  /// replaces the text between it and the cursor.
+  pub start: usize,
+  pub candidates: Vec<String>,
+}
+
 pub fn result_to_evaluation_output(
   r: Result<EvaluationOutput, AnyError>,
 ) -> EvaluationOutput {
@@ -196,6 +230,61 @@ pub struct ReplSession {
 }
 
 impl ReplSession {
+  /// Creates a session like `deno repl` does, with the configuration and
+  /// root permissions of `factory` and `extensions` added to the worker.
+  pub async fn new(
+    factory: &CliFactory,
+    extensions: Vec<Extension>,
+  ) -> Result<Self, AnyError> {
+    let permissions = factory.root_permissions_container()?.clone();
+    Self::with_worker_options(
+      factory,
+      permissions,
+      extensions,
+      Stdio::default(),
+    )
+    .await
+  }
+
+  pub(crate) async fn with_worker_options(
+    factory: &CliFactory,
+    permissions: PermissionsContainer,
+    extensions: Vec<Extension>,
+    stdio: Stdio,
+  ) -> Result<Self, AnyError> {
+    let cli_options = factory.cli_options()?;
+    let main_module = cli_options.resolve_main_module()?;
+    let npm_resolver = factory.npm_resolver().await?.clone();
+    let resolver = factory.resolver().await?.clone();
+    let worker_factory = factory.create_cli_main_worker_factory().await?;
+    let (worker, test_event_receiver) = create_single_test_event_channel();
+    let test_event_sender = worker.sender;
+    let mut worker = worker_factory
+      .create_custom_worker(
+        WorkerExecutionMode::Repl,
+        main_module.clone(),
+        permissions,
+        std::iter::once(crate::ops::testing::deno_test::init_ops(
+          test_event_sender,
+        ))
+        .chain(extensions)
+        .collect(),
+        stdio,
+      )
+      .await?;
+    worker.setup_repl().await?;
+    let worker = worker.into_main_worker();
+    Self::initialize(
+      cli_options,
+      npm_resolver,
+      resolver,
+      worker,
+      main_module.clone(),
+      test_event_receiver,
+    )
+    .await
+  }
+
   pub async fn initialize(
     cli_options: &CliOptions,
     npm_resolver: Arc<dyn CliNpmResolver>,
@@ -343,6 +432,79 @@ impl ReplSession {
     self.worker.run_event_loop(true).await
   }
 
+  /// Evaluates `line` the way `deno repl` does when it is entered, including
+  /// TypeScript, JSX and running the `Deno.test`s it registers. Timers and
+  /// other async work it starts progress in
+  /// [`ReplSession::run_event_loop`].
+  pub async fn evaluate(&mut self, line: &str) -> Result<ReplOutput, AnyError> {
+    let output = self.evaluate_line_and_get_output(line).await;
+    let closed = self.closing().await?;
+    Ok(match output {
+      EvaluationOutput::Value(text) => ReplOutput {
+        text,
+        is_error: false,
+        closed,
+      },
+      EvaluationOutput::Error(text) => ReplOutput {
+        text,
+        is_error: true,
+        closed,
+      },
+    })
+  }
+
+  /// Completes `line` at the byte offset `position` like tab completion in
+  /// `deno repl` does.
+  pub async fn complete(
+    &mut self,
+    line: &str,
+    position: usize,
+  ) -> Result<ReplCompletions, AnyError> {
+    // The completion logic of the editor is synchronous, so it runs on a
+    // blocking thread while its messages are handled here.
+    let (sync_sender, mut message_handler) = rustyline_channel();
+    let helper = EditorHelper {
+      context_id: self.context_id,
+      sync_sender,
+    };
+    let line = line.to_string();
+    let mut completions =
+      spawn_blocking(move || helper.complete_line(&line, position));
+    loop {
+      tokio::select! {
+        result = &mut completions => {
+          let (start, candidates) = result?;
+          return Ok(ReplCompletions { start, candidates });
+        }
+        Some(message) = message_handler.recv() => {
+          self.handle_sync_message(message, &message_handler).await?;
+        }
+      }
+    }
+  }
+
+  /// Answers a message sent by the editor from its blocking thread.
+  pub(super) async fn handle_sync_message(
+    &mut self,
+    message: RustylineSyncMessage,
+    message_handler: &RustylineSyncMessageHandler,
+  ) -> Result<(), AnyError> {
+    let response = match message {
+      RustylineSyncMessage::PostMessage { method, params } => {
+        RustylineSyncResponse::PostMessage(
+          self.post_message_with_event_loop(&method, params).await,
+        )
+      }
+      RustylineSyncMessage::LspCompletions {
+        line_text,
+        position,
+      } => RustylineSyncResponse::LspCompletions(
+        self.language_server.completions(&line_text, position).await,
+      ),
+    };
+    message_handler.send(response)
+  }
+
   pub async fn evaluate_line_and_get_output(
     &mut self,
     line: &str,
@@ -1,18 +1,23 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use deno_core::anyhow::bail;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::futures::StreamExt;
+use deno_core::parking_lot::Mutex;
 use deno_core::serde_json::json;
 use deno_core::serde_json::{self};
 use deno_core::url::Url;
 use deno_core::LocalInspectorSession;
+use deno_core::ModuleSpecifier;
 use deno_terminal::colors;
 use tokio::select;
+use tokio::sync::mpsc;
 
 use crate::cdp;
 use crate::emit::Emitter;
@@ -57,52 +62,34 @@ fn should_retry(status: &cdp::Status) -> bool {
 /// of an ES module cannot be hot-replaced. In such situation the runner will
 /// force a full restart of a program by notifying the `FileWatcher`.
 pub struct HmrRunner {
-  session: LocalInspectorSession,
+  session: HmrSession,
   watcher_communicator: Arc<WatcherCommunicator>,
-  script_ids: HashMap<String, String>,
   emitter: Arc<Emitter>,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::worker::HmrRunner for HmrRunner {
-  // TODO(bartlomieju): this code is duplicated in `cli/tools/coverage/mod.rs`
   async fn start(&mut self) -> Result<(), AnyError> {
-    self.enable_debugger().await
+    self.session.enable_debugger().await
   }
 
-  // TODO(bartlomieju): this code is duplicated in `cli/tools/coverage/mod.rs`
   async fn stop(&mut self) -> Result<(), AnyError> {
     self
       .watcher_communicator
       .change_restart_mode(WatcherRestartMode::Automatic);
-    self.disable_debugger().await
+    self.session.disable_debugger().await
   }
 
   async fn run(&mut self) -> Result<(), AnyError> {
     self
       .watcher_communicator
       .change_restart_mode(WatcherRestartMode::Manual);
-    let mut session_rx = self.session.take_notification_rx();
+    let mut session_rx = self.session.session.take_notification_rx();
     loop {
       select! {
         biased;
         Some(notification) = session_rx.next() => {
-          let notification = serde_json::from_value::<cdp::Notification>(notification)?;
-          if notification.method == "Runtime.exceptionThrown" {
-            let exception_thrown = serde_json::from_value::<cdp::ExceptionThrown>(notification.params)?;
-            let (message, description) = exception_thrown.exception_details.get_message_and_description();
-            break Err(generic_error(format!("{} {}", message, description)));
-          } else if notification.method == "Debugger.scriptParsed" {
-            let params = serde_json::from_value::<cdp::ScriptParsed>(notification.params)?;
-            if params.url.starts_with("file://") {
-              let file_url = Url::parse(&params.url).unwrap();
-              let file_path = file_url.to_file_path().unwrap();
-              if let Ok(canonicalized_file_path) = file_path.canonicalize() {
-                let canonicalized_file_url = Url::from_file_path(canonicalized_file_path).unwrap();
-                self.script_ids.insert(canonicalized_file_url.to_string(), params.script_id);
-              }
-            }
-          }
+          self.session.handle_notification(notification)?;
         }
         changed_paths = self.watcher_communicator.watch_for_changed_paths() => {
           let changed_paths = changed_paths?;
@@ -135,7 +122,7 @@ impl crate::worker::HmrRunner for HmrRunner {
               continue;
             };
 
-            let Some(id) = self.script_ids.get(module_url.as_str()).cloned() else {
+            let Some(id) = self.session.script_ids.get(module_url.as_str()).cloned() else {
               let _ = self.watcher_communicator.force_restart();
               continue;
             };
@@ -146,10 +133,10 @@ impl crate::worker::HmrRunner for HmrRunner {
 
             let mut tries = 1;
             loop {
-              let result = self.set_script_source(&id, source_code.as_str()).await?;
+              let result = self.session.set_script_source(&id, source_code.as_str()).await?;
 
               if matches!(result.status, cdp::Status::Ok) {
-                self.dispatch_hmr_event(module_url.as_str()).await?;
+                self.session.dispatch_hmr_event(module_url.as_str()).await?;
                 self.watcher_communicator.print(format!("Replaced changed module {}", module_url.as_str()));
                 break;
               }
@@ -166,7 +153,7 @@ impl crate::worker::HmrRunner for HmrRunner {
             }
           }
         }
-        _ = self.session.receive_from_v8_session() => {}
+        _ = self.session.session.receive_from_v8_session() => {}
       }
     }
   }
@@ -179,13 +166,239 @@ impl HmrRunner {
     watcher_communicator: Arc<WatcherCommunicator>,
   ) -> Self {
     Self {
-      session,
+      session: HmrSession::new(session),
       emitter,
       watcher_communicator,
+    }
+  }
+}
+
+/// Receives the outcome of the updates pushed through an [`HmrController`].
+/// Every method has an empty default implementation.
+pub trait HmrObserver: Send + Sync {
+  /// The module was replaced and the `hmr` event was dispatched.
+  fn on_accepted(&self, _specifier: &ModuleSpecifier) {}
+
+  /// The module couldn't be replaced, eg. because a top-level binding
+  /// changed or the worker didn't load it. The worker keeps running the
+  /// previous version.
+  fn on_declined(&self, _specifier: &ModuleSpecifier, _reason: &str) {}
+}
+
+struct HmrUpdate {
+  specifier: ModuleSpecifier,
+  source: String,
+}
+
+/// Hot replaces modules of a running main worker with sources pushed by the
+/// host, eg. from an in-app editor. Unlike `--watch-hmr`, files on disk are
+/// not watched and a declined update doesn't restart the program.
+///
+/// Clones control the same worker.
+#[derive(Clone)]
+pub struct HmrController {
+  sender: mpsc::UnboundedSender<HmrUpdate>,
+  // taken by the runner of the worker
+  receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<HmrUpdate>>>>,
+  observer: Option<Arc<dyn HmrObserver>>,
+}
+
+impl Default for HmrController {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl HmrController {
+  pub fn new() -> Self {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    Self {
+      sender,
+      receiver: Arc::new(Mutex::new(Some(receiver))),
+      observer: None,
+    }
+  }
+
+  /// Notifies `observer` about the outcome of every update.
+  pub fn with_observer(mut self, observer: impl HmrObserver + 'static) -> Self {
+    self.observer = Some(Arc::new(observer));
+    self
+  }
+
+  /// Replaces the module at the absolute `path` with `source`, which is
+  /// transpiled first if it's TypeScript or JSX. Updates are applied in
+  /// order while the worker's event loop runs.
+  pub fn update(
+    &self,
+    path: &Path,
+    source: impl Into<String>,
+  ) -> Result<(), AnyError> {
+    // script ids are keyed by canonicalized paths
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let Ok(specifier) = ModuleSpecifier::from_file_path(&path) else {
+      bail!("Expected an absolute path, got '{}'.", path.display());
+    };
+    self
+      .sender
+      .send(HmrUpdate {
+        specifier,
+        source: source.into(),
+      })
+      .map_err(|_| generic_error("The worker is no longer running."))
+  }
+}
+
+/// Applies the updates of an [`HmrController`] to the worker it was
+/// created for.
+pub struct EmbedderHmrRunner {
+  session: HmrSession,
+  emitter: Arc<Emitter>,
+  receiver: Option<mpsc::UnboundedReceiver<HmrUpdate>>,
+  observer: Option<Arc<dyn HmrObserver>>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::worker::HmrRunner for EmbedderHmrRunner {
+  async fn start(&mut self) -> Result<(), AnyError> {
+    self.session.enable_debugger().await
+  }
+
+  async fn stop(&mut self) -> Result<(), AnyError> {
+    self.session.disable_debugger().await
+  }
+
+  async fn run(&mut self) -> Result<(), AnyError> {
+    let mut session_rx = self.session.session.take_notification_rx();
+    loop {
+      select! {
+        biased;
+        Some(notification) = session_rx.next() => {
+          self.session.handle_notification(notification)?;
+        }
+        update = next_update(&mut self.receiver) => {
+          match update {
+            Some(update) => self.apply_update(update).await?,
+            // every controller was dropped
+            None => self.receiver = None,
+          }
+        }
+        _ = self.session.session.receive_from_v8_session() => {}
+      }
+    }
+  }
+}
+
+impl EmbedderHmrRunner {
+  pub fn new(
+    emitter: Arc<Emitter>,
+    session: LocalInspectorSession,
+    controller: &HmrController,
+  ) -> Self {
+    Self {
+      session: HmrSession::new(session),
+      emitter,
+      // only the first worker created with the controller receives updates
+      receiver: controller.receiver.lock().take(),
+      observer: controller.observer.clone(),
+    }
+  }
+
+  async fn apply_update(&mut self, update: HmrUpdate) -> Result<(), AnyError> {
+    let HmrUpdate { specifier, source } = update;
+    let Some(id) = self.session.script_ids.get(specifier.as_str()).cloned()
+    else {
+      self.decline(&specifier, "module is not loaded");
+      return Ok(());
+    };
+    let source = match self.emitter.emit_for_hmr(&specifier, source) {
+      Ok(source) => source,
+      Err(err) => {
+        self.decline(&specifier, &err.to_string());
+        return Ok(());
+      }
+    };
+
+    let mut tries = 1;
+    loop {
+      let result = self.session.set_script_source(&id, &source).await?;
+      if matches!(result.status, cdp::Status::Ok) {
+        self.session.dispatch_hmr_event(specifier.as_str()).await?;
+        if let Some(observer) = &self.observer {
+          observer.on_accepted(&specifier);
+        }
+        return Ok(());
+      }
+      if should_retry(&result.status) && tries <= 2 {
+        tries += 1;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        continue;
+      }
+      self.decline(&specifier, explain(&result.status));
+      return Ok(());
+    }
+  }
+
+  fn decline(&self, specifier: &ModuleSpecifier, reason: &str) {
+    if let Some(observer) = &self.observer {
+      observer.on_declined(specifier, reason);
+    }
+  }
+}
+
+async fn next_update(
+  receiver: &mut Option<mpsc::UnboundedReceiver<HmrUpdate>>,
+) -> Option<HmrUpdate> {
+  match receiver {
+    Some(receiver) => receiver.recv().await,
+    None => std::future::pending().await,
+  }
+}
+
+/// The inspector session of a runner and the ids of the scripts it parsed.
+struct HmrSession {
+  session: LocalInspectorSession,
+  script_ids: HashMap<String, String>,
+}
+
+impl HmrSession {
+  fn new(session: LocalInspectorSession) -> Self {
+    Self {
+      session,
       script_ids: HashMap::new(),
     }
   }
 
+  fn handle_notification(
+    &mut self,
+    notification: serde_json::Value,
+  ) -> Result<(), AnyError> {
+    let notification =
+      serde_json::from_value::<cdp::Notification>(notification)?;
+    if notification.method == "Runtime.exceptionThrown" {
+      let exception_thrown =
+        serde_json::from_value::<cdp::ExceptionThrown>(notification.params)?;
+      let (message, description) = exception_thrown
+        .exception_details
+        .get_message_and_description();
+      return Err(generic_error(format!("{} {}", message, description)));
+    } else if notification.method == "Debugger.scriptParsed" {
+      let params =
+        serde_json::from_value::<cdp::ScriptParsed>(notification.params)?;
+      if params.url.starts_with("file://") {
+        let file_url = Url::parse(&params.url).unwrap();
+        let file_path = file_url.to_file_path().unwrap();
+        if let Ok(canonicalized_file_path) = file_path.canonicalize() {
+          let canonicalized_file_url =
+            Url::from_file_path(canonicalized_file_path).unwrap();
+          self
+            .script_ids
+            .insert(canonicalized_file_url.to_string(), params.script_id);
+        }
+      }
+    }
+    Ok(())
+  }
+
   // TODO(bartlomieju): this code is duplicated in `cli/tools/coverage/mod.rs`
   async fn enable_debugger(&mut self) -> Result<(), AnyError> {
     self
@@ -1,11 +1,14 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::io::Read;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use deno_config::deno_json::NodeModulesDirMode;
 use deno_core::error::AnyError;
+use deno_core::Extension;
 use deno_runtime::WorkerExecutionMode;
+use deno_telemetry::HostSpan;
 
 use crate::args::EvalFlags;
 use crate::args::Flags;
@@ -16,6 +19,13 @@ use crate::util;
 use crate::util::file_watcher::WatcherRestartMode;
 
 pub mod hmr;
+pub mod profiler;
+
+/// Creates the custom extensions that are added to the main worker.
+///
+/// Extensions can't be cloned, so this is called again for every worker
+/// that is created, eg. on each restart in watch mode.
+pub type ExtensionsFactory = Rc<dyn Fn() -> Vec<Extension>>;
 
 pub fn check_permission_before_script(flags: &Flags) {
   if !flags.has_permission() && flags.has_permission_in_argv() {
@@ -44,11 +54,20 @@ pub async fn run_script(
   mode: WorkerExecutionMode,
   flags: Arc<Flags>,
   watch: Option<WatchFlagsWithPaths>,
+) -> Result<i32, AnyError> {
+  run_script_with_extensions(mode, flags, watch, Rc::new(Vec::new)).await
+}
+
+pub async fn run_script_with_extensions(
+  mode: WorkerExecutionMode,
+  flags: Arc<Flags>,
+  watch: Option<WatchFlagsWithPaths>,
+  extensions_factory: ExtensionsFactory,
 ) -> Result<i32, AnyError> {
   check_permission_before_script(&flags);
 
   if let Some(watch_flags) = watch {
-    return run_with_watch(mode, flags, watch_flags).await;
+    return run_with_watch(mode, flags, watch_flags, extensions_factory).await;
   }
 
   // TODO(bartlomieju): actually I think it will also fail if there's an import
@@ -76,7 +95,13 @@ pub async fn run_script(
 
   let worker_factory = factory.create_cli_main_worker_factory().await?;
   let mut worker = worker_factory
-    .create_main_worker(mode, main_module.clone())
+    .create_custom_worker(
+      mode,
+      main_module.clone(),
+      factory.root_permissions_container()?.clone(),
+      extensions_factory(),
+      Default::default(),
+    )
     .await?;
 
   let exit_code = worker.run().await?;
@@ -115,6 +140,7 @@ async fn run_with_watch(
   mode: WorkerExecutionMode,
   flags: Arc<Flags>,
   watch_flags: WatchFlagsWithPaths,
+  extensions_factory: ExtensionsFactory,
 ) -> Result<i32, AnyError> {
   util::file_watcher::watch_recv(
     flags,
@@ -126,6 +152,7 @@ async fn run_with_watch(
     WatcherRestartMode::Automatic,
     move |flags, watcher_communicator, changed_paths| {
       watcher_communicator.show_path_changed(changed_paths.clone());
+      let extensions_factory = extensions_factory.clone();
       Ok(async move {
         let factory = CliFactory::from_flags_for_watcher(
           flags,
@@ -145,7 +172,13 @@ async fn run_with_watch(
         let mut worker = factory
           .create_cli_main_worker_factory()
           .await?
-          .create_main_worker(mode, main_module.clone())
+          .create_custom_worker(
+            mode,
+            main_module.clone(),
+            factory.root_permissions_container()?.clone(),
+            extensions_factory(),
+            Default::default(),
+          )
           .await?;
 
         if watch_flags.hmr {
@@ -204,7 +237,10 @@ pub async fn maybe_npm_install(factory: &CliFactory) -> Result<(), AnyError> {
     == Some(NodeModulesDirMode::Auto)
   {
     if let Some(npm_resolver) = factory.npm_resolver().await?.as_managed() {
-      npm_resolver.ensure_top_level_package_json_install().await?;
+      let mut span = HostSpan::start("deno.npm_install");
+      let result = npm_resolver.ensure_top_level_package_json_install().await;
+      span.set_result(&result);
+      result?;
     }
   }
   Ok(())
@@ -0,0 +1,168 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+
+use crate::cdp;
+use crate::inspector::InspectorController;
+use crate::inspector::InspectorSession;
+
+/// Captures CPU profiles and heap snapshots of a running main worker over
+/// an inspector session, eg. to record the performance of user scripts in
+/// CI.
+///
+/// ```ignore
+/// let mut profiler = Profiler::connect(&controller).await?;
+/// profiler.start_cpu_profile(None).await?;
+/// // ...let the worker run
+/// let profile = profiler.stop_cpu_profile().await?;
+/// std::fs::write("main.cpuprofile", profile.to_bytes()?)?;
+/// ```
+pub struct Profiler {
+  session: InspectorSession,
+}
+
+impl Profiler {
+  pub fn new(session: InspectorSession) -> Self {
+    Self { session }
+  }
+
+  /// Opens a new session with the worker of `controller`.
+  pub async fn connect(
+    controller: &InspectorController,
+  ) -> Result<Self, AnyError> {
+    Ok(Self::new(controller.connect().await?))
+  }
+
+  /// Starts sampling the call stacks of the worker, every
+  /// `sampling_interval` if set. V8 samples every millisecond by default.
+  pub async fn start_cpu_profile(
+    &mut self,
+    sampling_interval: Option<Duration>,
+  ) -> Result<(), AnyError> {
+    self.session.post_message("Profiler.enable", None).await?;
+    if let Some(interval) = sampling_interval {
+      let args = cdp::SetSamplingIntervalArgs {
+        interval: interval.as_micros() as u64,
+      };
+      self
+        .session
+        .post_message(
+          "Profiler.setSamplingInterval",
+          Some(serde_json::to_value(args)?),
+        )
+        .await?;
+    }
+    self.session.post_message("Profiler.start", None).await?;
+    Ok(())
+  }
+
+  /// Stops the profile started with [`Profiler::start_cpu_profile`] and
+  /// returns it.
+  pub async fn stop_cpu_profile(&mut self) -> Result<CpuProfile, AnyError> {
+    let result = self.session.post_message("Profiler.stop", None).await?;
+    let response: cdp::StopProfilerResponse = serde_json::from_value(result)?;
+    self.session.post_message("Profiler.disable", None).await?;
+    Ok(CpuProfile {
+      profile: response.profile,
+    })
+  }
+
+  /// Takes a snapshot of the worker's heap, in the `.heapsnapshot` format
+  /// read by Chrome DevTools.
+  pub async fn take_heap_snapshot(&mut self) -> Result<Vec<u8>, AnyError> {
+    const CHUNK_METHOD: &str = "HeapProfiler.addHeapSnapshotChunk";
+
+    self
+      .session
+      .post_message("HeapProfiler.enable", None)
+      .await?;
+    let args = cdp::TakeHeapSnapshotArgs {
+      report_progress: false,
+    };
+    // the chunks are sent as notifications before the response
+    self
+      .session
+      .post_message(
+        "HeapProfiler.takeHeapSnapshot",
+        Some(serde_json::to_value(args)?),
+      )
+      .await?;
+    let mut snapshot = Vec::new();
+    for notification in self.session.take_notifications(CHUNK_METHOD) {
+      let chunk: cdp::AddHeapSnapshotChunk =
+        serde_json::from_value(notification.params)?;
+      snapshot.extend_from_slice(chunk.chunk.as_bytes());
+    }
+    self
+      .session
+      .post_message("HeapProfiler.disable", None)
+      .await?;
+    Ok(snapshot)
+  }
+
+  /// Returns the session, eg. to send other commands.
+  pub fn into_session(self) -> InspectorSession {
+    self.session
+  }
+}
+
+/// A CPU profile taken with [`Profiler::stop_cpu_profile`].
+#[derive(Debug, Clone)]
+pub struct CpuProfile {
+  pub profile: cdp::Profile,
+}
+
+impl CpuProfile {
+  /// Serializes the profile in the `.cpuprofile` format read by Chrome
+  /// DevTools and other profile viewers.
+  pub fn to_bytes(&self) -> Result<Vec<u8>, AnyError> {
+    Ok(serde_json::to_vec(&self.profile)?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cpu_profile_round_trips() {
+    let json = serde_json::json!({
+      "nodes": [{
+        "id": 1,
+        "callFrame": {
+          "functionName": "(root)",
+          "scriptId": "0",
+          "url": "",
+          "lineNumber": -1,
+          "columnNumber": -1,
+        },
+        "hitCount": 0,
+        "children": [2],
+      }, {
+        "id": 2,
+        "callFrame": {
+          "functionName": "main",
+          "scriptId": "12",
+          "url": "file:///main.ts",
+          "lineNumber": 3,
+          "columnNumber": 9,
+        },
+        "hitCount": 4,
+        "positionTicks": [{ "line": 5, "ticks": 4 }],
+      }],
+      "startTime": 100.0,
+      "endTime": 200.0,
+      "samples": [2, 2, 2, 2],
+      "timeDeltas": [10, 25, 25, 25],
+    });
+    let profile = CpuProfile {
+      profile: serde_json::from_value(json.clone()).unwrap(),
+    };
+    let bytes = profile.to_bytes().unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(value, json);
+  }
+}
@@ -23,13 +23,16 @@ use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::stream::futures_unordered;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
+use deno_core::parking_lot::Mutex;
 use deno_core::url::Url;
 use deno_path_util::normalize_path;
 use deno_runtime::deno_node::NodeResolver;
+use deno_runtime::tokio_util::create_and_run_current_thread;
 use deno_task_shell::KillSignal;
 use deno_task_shell::ShellCommand;
 use indexmap::IndexMap;
 use regex::Regex;
+use tokio::sync::oneshot;
 
 use crate::args::CliOptions;
 use crate::args::Flags;
@@ -39,7 +42,11 @@ use crate::factory::CliFactory;
 use crate::npm::CliNpmResolver;
 use crate::task_runner;
 use crate::task_runner::run_future_forwarding_signals;
+use crate::task_runner::TaskIo;
+use crate::task_runner::TaskOutputSink;
+use crate::task_runner::TaskStdio;
 use crate::util::fs::canonicalize_path;
+use crate::util::sync::AsyncFlag;
 
 #[derive(Debug)]
 struct PackageTaskInfo {
@@ -226,6 +233,8 @@ pub async fn execute_script(
     env_vars,
     cli_options,
     concurrency: no_of_concurrent_tasks.into(),
+    stdout: None,
+    stderr: None,
   };
 
   let kill_signal = KillSignal::default();
@@ -257,6 +266,178 @@ pub async fn execute_script(
   .await
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskSource {
+  DenoJson,
+  /// A script in the `scripts` field of a `package.json`.
+  PackageJson,
+}
+
+/// A task that can be run from the current directory, as listed by
+/// `deno task` without arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+  pub name: String,
+  pub command: String,
+  pub description: Option<String>,
+  /// Tasks that are run before this one.
+  pub dependencies: Vec<String>,
+  pub source: TaskSource,
+  /// Whether the task is inherited from the workspace root.
+  pub is_workspace_root: bool,
+}
+
+/// Lists the tasks of the `deno.json` and `package.json` in the current
+/// directory, including the ones inherited from the workspace root.
+pub fn list_tasks(flags: Arc<Flags>) -> Result<Vec<TaskInfo>, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cli_options = factory.cli_options()?;
+  let start_dir = &cli_options.start_dir;
+  let tasks_config = start_dir.to_tasks_config()?;
+  Ok(available_tasks(start_dir, &tasks_config))
+}
+
+#[derive(Default)]
+pub struct TaskRunOptions {
+  /// Arguments appended to the task's command.
+  pub args: Vec<String>,
+  /// Directory the task is run in instead of the one of its config file.
+  pub cwd: Option<PathBuf>,
+  /// Environment variables set in addition to the ones of the process.
+  pub env: HashMap<String, String>,
+  /// Don't inherit the environment variables of the process.
+  pub clear_env: bool,
+  /// Receives the task's stdout as it's written, instead of the process'
+  /// stdout.
+  pub stdout: Option<Box<dyn std::io::Write + Send>>,
+  pub stderr: Option<Box<dyn std::io::Write + Send>>,
+}
+
+/// How a task run by [`spawn_task`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskExitStatus {
+  /// The task, or the first of its dependencies that failed, exited with
+  /// this code.
+  Exited(i32),
+  /// The task was stopped with [`TaskHandle::cancel`].
+  Cancelled,
+}
+
+impl TaskExitStatus {
+  pub fn success(&self) -> bool {
+    *self == TaskExitStatus::Exited(0)
+  }
+}
+
+/// Handle to a task running on its own thread, created by [`spawn_task`].
+pub struct TaskHandle {
+  cancel_flag: AsyncFlag,
+  result_rx: oneshot::Receiver<Result<TaskExitStatus, AnyError>>,
+}
+
+impl TaskHandle {
+  /// Sends SIGTERM to the processes of the task. Dependencies that haven't
+  /// started yet aren't run.
+  pub fn cancel(&self) {
+    self.cancel_flag.raise();
+  }
+
+  /// Waits for the task and its dependencies to finish.
+  pub async fn join(self) -> Result<TaskExitStatus, AnyError> {
+    match self.result_rx.await {
+      Ok(result) => result,
+      Err(_) => bail!("The task thread exited unexpectedly."),
+    }
+  }
+}
+
+/// Spawns a thread that runs the task `task_name` and its dependencies like
+/// `deno task` does, without forwarding the signals of the process.
+pub fn spawn_task(
+  mut flags: Arc<Flags>,
+  task_name: impl Into<String>,
+  options: TaskRunOptions,
+) -> TaskHandle {
+  let task_name = task_name.into();
+  let cancel_flag = AsyncFlag::default();
+  let (result_tx, result_rx) = oneshot::channel();
+  Arc::make_mut(&mut flags).argv = options.args.clone();
+  std::thread::spawn({
+    let cancel_flag = cancel_flag.clone();
+    move || {
+      create_and_run_current_thread(async move {
+        let result =
+          run_task_until_cancelled(flags, task_name, options, cancel_flag)
+            .await;
+        let _ = result_tx.send(result);
+      })
+    }
+  });
+  TaskHandle {
+    cancel_flag,
+    result_rx,
+  }
+}
+
+async fn run_task_until_cancelled(
+  flags: Arc<Flags>,
+  task_name: String,
+  options: TaskRunOptions,
+  cancel_flag: AsyncFlag,
+) -> Result<TaskExitStatus, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cli_options = factory.cli_options()?;
+  let task_info = PackageTaskInfo {
+    tasks_config: cli_options.start_dir.to_tasks_config()?,
+    matched_tasks: vec![task_name.clone()],
+  };
+  let task_flags = TaskFlags {
+    cwd: options.cwd.map(|cwd| cwd.to_string_lossy().into_owned()),
+    task: Some(task_name),
+    is_run: true,
+    recursive: false,
+    filter: None,
+    eval: false,
+  };
+  let mut env_vars = if options.clear_env {
+    HashMap::new()
+  } else {
+    task_runner::real_env_vars()
+  };
+  env_vars.extend(options.env);
+
+  fn sink(writer: Box<dyn std::io::Write + Send>) -> TaskOutputSink {
+    Arc::new(Mutex::new(writer))
+  }
+
+  let npm_resolver = factory.npm_resolver().await?;
+  let node_resolver = factory.node_resolver().await?;
+  let task_runner = TaskRunner {
+    task_flags: &task_flags,
+    npm_resolver: npm_resolver.as_ref(),
+    node_resolver: node_resolver.as_ref(),
+    env_vars,
+    cli_options,
+    concurrency: std::thread::available_parallelism()
+      .map(usize::from)
+      .unwrap_or(2),
+    stdout: options.stdout.map(sink),
+    stderr: options.stderr.map(sink),
+  };
+
+  let kill_signal = KillSignal::default();
+  let run = task_runner.run_tasks(&task_info, &kill_signal);
+  tokio::pin!(run);
+  tokio::select! {
+    result = &mut run => result.map(TaskExitStatus::Exited),
+    _ = cancel_flag.wait_raised() => {
+      kill_signal.send(deno_task_shell::SignalKind::SIGTERM);
+      run.await?;
+      Ok(TaskExitStatus::Cancelled)
+    }
+  }
+}
+
 struct RunSingleOptions<'a> {
   task_name: &'a str,
   script: &'a str,
@@ -272,6 +453,8 @@ struct TaskRunner<'a> {
   env_vars: HashMap<String, String>,
   cli_options: &'a CliOptions,
   concurrency: usize,
+  stdout: Option<TaskOutputSink>,
+  stderr: Option<TaskOutputSink>,
 }
 
 impl<'a> TaskRunner<'a> {
@@ -527,13 +710,29 @@ impl<'a> TaskRunner<'a> {
         init_cwd: self.cli_options.initial_cwd(),
         argv: self.cli_options.argv(),
         root_node_modules_dir: self.npm_resolver.root_node_modules_path(),
-        stdio: None,
+        stdio: self.stdio(),
         kill_signal,
       })
       .await?
       .exit_code,
     )
   }
+
+  fn stdio(&self) -> Option<TaskIo> {
+    if self.stdout.is_none() && self.stderr.is_none() {
+      return None;
+    }
+    Some(TaskIo {
+      stdout: match &self.stdout {
+        Some(sink) => TaskStdio::streamed(sink.clone()),
+        None => TaskStdio::stdout(),
+      },
+      stderr: match &self.stderr {
+        Some(sink) => TaskStdio::streamed(sink.clone()),
+        None => TaskStdio::stderr(),
+      },
+    })
+  }
 }
 
 #[derive(Debug)]
@@ -664,7 +863,6 @@ fn print_available_tasks(
   tasks_config: &WorkspaceTasksConfig,
 ) -> Result<(), std::io::Error> {
   writeln!(writer, "{}", colors::green("Available tasks:"))?;
-  let is_cwd_root_dir = tasks_config.root.is_none();
 
   if tasks_config.is_empty() {
     writeln!(
@@ -675,77 +873,27 @@ fn print_available_tasks(
     return Ok(());
   }
 
-  struct AvailableTaskDescription {
-    is_root: bool,
-    is_deno: bool,
-    name: String,
-    task: TaskDefinition,
-  }
-  let mut seen_task_names = HashSet::with_capacity(tasks_config.tasks_count());
-  let mut task_descriptions = Vec::with_capacity(tasks_config.tasks_count());
-
-  for maybe_config in [&tasks_config.member, &tasks_config.root] {
-    let Some(config) = maybe_config else {
-      continue;
-    };
-
-    if let Some(config) = config.deno_json.as_ref() {
-      let is_root = !is_cwd_root_dir
-        && config.folder_url == *workspace_dir.workspace.root_dir().as_ref();
-
-      for (name, definition) in &config.tasks {
-        if !seen_task_names.insert(name) {
-          continue; // already seen
-        }
-        task_descriptions.push(AvailableTaskDescription {
-          is_root,
-          is_deno: true,
-          name: name.to_string(),
-          task: definition.clone(),
-        });
-      }
-    }
-
-    if let Some(config) = config.package_json.as_ref() {
-      let is_root = !is_cwd_root_dir
-        && config.folder_url == *workspace_dir.workspace.root_dir().as_ref();
-      for (name, script) in &config.tasks {
-        if !seen_task_names.insert(name) {
-          continue; // already seen
-        }
-
-        task_descriptions.push(AvailableTaskDescription {
-          is_root,
-          is_deno: false,
-          name: name.to_string(),
-          task: deno_config::deno_json::TaskDefinition {
-            command: script.to_string(),
-            dependencies: vec![],
-            description: None,
-          },
-        });
-      }
-    }
-  }
+  let task_descriptions = available_tasks(workspace_dir, tasks_config);
 
   for desc in task_descriptions {
+    let is_deno = desc.source == TaskSource::DenoJson;
     writeln!(
       writer,
       "- {}{}",
       colors::cyan(desc.name),
-      if desc.is_root {
-        if desc.is_deno {
+      if desc.is_workspace_root {
+        if is_deno {
           format!(" {}", colors::italic_gray("(workspace)"))
         } else {
           format!(" {}", colors::italic_gray("(workspace package.json)"))
         }
-      } else if desc.is_deno {
+      } else if is_deno {
         "".to_string()
       } else {
         format!(" {}", colors::italic_gray("(package.json)"))
       }
     )?;
-    if let Some(description) = &desc.task.description {
+    if let Some(description) = &desc.description {
       let slash_slash = colors::italic_gray("//");
       for line in description.lines() {
         writeln!(
@@ -758,11 +906,10 @@ fn print_available_tasks(
     writeln!(
       writer,
       "    {}",
-      strip_ansi_codes_and_escape_control_chars(&desc.task.command)
+      strip_ansi_codes_and_escape_control_chars(&desc.command)
     )?;
-    if !desc.task.dependencies.is_empty() {
+    if !desc.dependencies.is_empty() {
       let dependencies = desc
-        .task
         .dependencies
         .into_iter()
         .map(|d| strip_ansi_codes_and_escape_control_chars(&d))
@@ -780,6 +927,61 @@ fn print_available_tasks(
   Ok(())
 }
 
+fn available_tasks(
+  workspace_dir: &Arc<WorkspaceDirectory>,
+  tasks_config: &WorkspaceTasksConfig,
+) -> Vec<TaskInfo> {
+  let is_cwd_root_dir = tasks_config.root.is_none();
+  let mut seen_task_names = HashSet::with_capacity(tasks_config.tasks_count());
+  let mut task_descriptions = Vec::with_capacity(tasks_config.tasks_count());
+
+  for maybe_config in [&tasks_config.member, &tasks_config.root] {
+    let Some(config) = maybe_config else {
+      continue;
+    };
+
+    if let Some(config) = config.deno_json.as_ref() {
+      let is_root = !is_cwd_root_dir
+        && config.folder_url == *workspace_dir.workspace.root_dir().as_ref();
+
+      for (name, definition) in &config.tasks {
+        if !seen_task_names.insert(name) {
+          continue; // already seen
+        }
+        task_descriptions.push(TaskInfo {
+          name: name.to_string(),
+          command: definition.command.clone(),
+          description: definition.description.clone(),
+          dependencies: definition.dependencies.clone(),
+          source: TaskSource::DenoJson,
+          is_workspace_root: is_root,
+        });
+      }
+    }
+
+    if let Some(config) = config.package_json.as_ref() {
+      let is_root = !is_cwd_root_dir
+        && config.folder_url == *workspace_dir.workspace.root_dir().as_ref();
+      for (name, script) in &config.tasks {
+        if !seen_task_names.insert(name) {
+          continue; // already seen
+        }
+
+        task_descriptions.push(TaskInfo {
+          name: name.to_string(),
+          command: script.to_string(),
+          description: None,
+          dependencies: vec![],
+          source: TaskSource::PackageJson,
+          is_workspace_root: is_root,
+        });
+      }
+    }
+  }
+
+  task_descriptions
+}
+
 fn strip_ansi_codes_and_escape_control_chars(s: &str) -> String {
   strip_ansi_codes(s)
     .chars()
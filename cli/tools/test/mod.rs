@@ -1,11 +1,13 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CliOptions;
+use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::args::TestFlags;
 use crate::args::TestReporterConfig;
 use crate::colors;
 use crate::display;
+use crate::errors::format_js_error;
 use crate::factory::CliFactory;
 use crate::file_fetcher::File;
 use crate::file_fetcher::FileFetcher;
@@ -52,7 +54,6 @@ use deno_runtime::deno_io::Stdio;
 use deno_runtime::deno_io::StdioPipe;
 use deno_runtime::deno_permissions::Permissions;
 use deno_runtime::deno_permissions::PermissionsContainer;
-use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::permissions::RuntimePermissionDescriptorParser;
 use deno_runtime::tokio_util::create_and_run_current_thread;
 use deno_runtime::worker::MainWorker;
@@ -65,6 +66,7 @@ use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use regex::Regex;
 use serde::Deserialize;
+use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
@@ -101,8 +103,14 @@ use reporters::CompoundTestReporter;
 use reporters::DotTestReporter;
 use reporters::JunitTestReporter;
 use reporters::PrettyTestReporter;
+use reporters::StructuredTestReporter;
 use reporters::TapTestReporter;
+pub use reporters::TestCaseReport;
+pub use reporters::TestCaseStatus;
+pub use reporters::TestFailureReport;
+pub use reporters::TestReport;
 use reporters::TestReporter;
+pub use reporters::TestSuiteReport;
 
 /// How many times we're allowed to spin the event loop before considering something a leak.
 const MAX_SANITIZER_LOOP_SPINS: usize = 16;
@@ -213,7 +221,7 @@ impl TestFilter {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct TestLocation {
   pub file_name: String,
@@ -1195,6 +1203,28 @@ async fn test_specifiers(
   specifiers: Vec<ModuleSpecifier>,
   options: TestSpecifiersOptions,
 ) -> Result<(), AnyError> {
+  let reporter = get_test_reporter(&options);
+  test_specifiers_with_reporter(
+    worker_factory,
+    permissions,
+    permission_desc_parser,
+    specifiers,
+    options,
+    reporter,
+  )
+  .await?
+}
+
+/// The outer result holds errors of the test run itself, the inner one the
+/// outcome of the tests as determined by the reporter.
+async fn test_specifiers_with_reporter(
+  worker_factory: Arc<CliMainWorkerFactory>,
+  permissions: &Permissions,
+  permission_desc_parser: &Arc<RuntimePermissionDescriptorParser>,
+  specifiers: Vec<ModuleSpecifier>,
+  options: TestSpecifiersOptions,
+  reporter: Box<dyn TestReporter>,
+) -> Result<Result<(), AnyError>, AnyError> {
   let specifiers = if let Some(seed) = options.specifier.shuffle {
     let mut rng = SmallRng::seed_from_u64(seed);
     let mut specifiers = specifiers;
@@ -1214,7 +1244,6 @@ async fn test_specifiers(
     cancel_sender.send(TestEvent::Sigint).ok();
   });
   HAS_TEST_RUN_SIGINT_HANDLER.store(true, Ordering::Relaxed);
-  let reporter = get_test_reporter(&options);
   let fail_fast_tracker = FailFastTracker::new(options.fail_fast);
 
   let join_handles = specifiers.into_iter().map(move |specifier| {
@@ -1250,9 +1279,8 @@ async fn test_specifiers(
   for join_result in join_results {
     join_result??;
   }
-  result??;
 
-  Ok(())
+  Ok(result?)
 }
 
 /// Gives receiver back in case it was ended with `TestEvent::ForceEndReport`.
@@ -1543,6 +1571,50 @@ pub async fn run_tests(
   flags: Arc<Flags>,
   test_flags: TestFlags,
 ) -> Result<(), AnyError> {
+  run_tests_with_reporter(flags, test_flags, get_test_reporter).await?
+}
+
+/// Runs the tests found in `paths` like `deno test` does, but collects the
+/// results into a [`TestReport`] instead of printing them.
+///
+/// Failing tests don't make this return an error, use
+/// [`TestReport::success`] for that. Errors are returned when the tests could
+/// not be run, eg. because type checking failed.
+pub async fn run_tests_for_embedder(
+  mut flags: Arc<Flags>,
+  paths: Vec<String>,
+) -> Result<TestReport, AnyError> {
+  let mut test_flags = match &flags.subcommand {
+    DenoSubcommand::Test(test_flags) => test_flags.clone(),
+    _ => TestFlags::default(),
+  };
+  test_flags.files.include = paths;
+  test_flags.watch = None;
+  Arc::make_mut(&mut flags).subcommand =
+    DenoSubcommand::Test(test_flags.clone());
+
+  let (sender, receiver) = tokio::sync::oneshot::channel();
+  // the outcome of the tests is part of the report
+  let _ = run_tests_with_reporter(flags, test_flags, |options| {
+    Box::new(StructuredTestReporter::new(
+      options.cwd.clone(),
+      TestFailureFormatOptions {
+        hide_stacktraces: options.hide_stacktraces,
+      },
+      sender,
+    ))
+  })
+  .await?;
+
+  // no report is sent when the tests were only type checked
+  Ok(receiver.await.unwrap_or_default())
+}
+
+async fn run_tests_with_reporter(
+  flags: Arc<Flags>,
+  test_flags: TestFlags,
+  create_reporter: impl FnOnce(&TestSpecifiersOptions) -> Box<dyn TestReporter>,
+) -> Result<Result<(), AnyError>, AnyError> {
   let factory = CliFactory::from_flags(flags);
   let cli_options = factory.cli_options()?;
   let workspace_test_options =
@@ -1591,44 +1663,44 @@ pub async fn run_tests(
     .await?;
 
   if workspace_test_options.no_run {
-    return Ok(());
+    return Ok(Ok(()));
   }
 
   let worker_factory =
     Arc::new(factory.create_cli_main_worker_factory().await?);
 
+  let options = TestSpecifiersOptions {
+    cwd: Url::from_directory_path(cli_options.initial_cwd()).map_err(|_| {
+      generic_error(format!(
+        "Unable to construct URL from the path of cwd: {}",
+        cli_options.initial_cwd().to_string_lossy(),
+      ))
+    })?,
+    concurrent_jobs: workspace_test_options.concurrent_jobs,
+    fail_fast: workspace_test_options.fail_fast,
+    log_level,
+    filter: workspace_test_options.filter.is_some(),
+    reporter: workspace_test_options.reporter,
+    junit_path: workspace_test_options.junit_path,
+    hide_stacktraces: workspace_test_options.hide_stacktraces,
+    specifier: TestSpecifierOptions {
+      filter: TestFilter::from_flag(&workspace_test_options.filter),
+      shuffle: workspace_test_options.shuffle,
+      trace_leaks: workspace_test_options.trace_leaks,
+    },
+  };
+  let reporter = create_reporter(&options);
+
   // Run tests
-  test_specifiers(
+  test_specifiers_with_reporter(
     worker_factory,
     &permissions,
     permission_desc_parser,
     specifiers_for_typecheck_and_test,
-    TestSpecifiersOptions {
-      cwd: Url::from_directory_path(cli_options.initial_cwd()).map_err(
-        |_| {
-          generic_error(format!(
-            "Unable to construct URL from the path of cwd: {}",
-            cli_options.initial_cwd().to_string_lossy(),
-          ))
-        },
-      )?,
-      concurrent_jobs: workspace_test_options.concurrent_jobs,
-      fail_fast: workspace_test_options.fail_fast,
-      log_level,
-      filter: workspace_test_options.filter.is_some(),
-      reporter: workspace_test_options.reporter,
-      junit_path: workspace_test_options.junit_path,
-      hide_stacktraces: workspace_test_options.hide_stacktraces,
-      specifier: TestSpecifierOptions {
-        filter: TestFilter::from_flag(&workspace_test_options.filter),
-        shuffle: workspace_test_options.shuffle,
-        trace_leaks: workspace_test_options.trace_leaks,
-      },
-    },
+    options,
+    reporter,
   )
-  .await?;
-
-  Ok(())
+  .await
 }
 
 pub async fn run_tests_with_watch(
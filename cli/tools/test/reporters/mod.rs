@@ -7,12 +7,19 @@ mod compound;
 mod dot;
 mod junit;
 mod pretty;
+mod structured;
 mod tap;
 
 pub use compound::CompoundTestReporter;
 pub use dot::DotTestReporter;
 pub use junit::JunitTestReporter;
 pub use pretty::PrettyTestReporter;
+pub use structured::StructuredTestReporter;
+pub use structured::TestCaseReport;
+pub use structured::TestCaseStatus;
+pub use structured::TestFailureReport;
+pub use structured::TestReport;
+pub use structured::TestSuiteReport;
 pub use tap::TapTestReporter;
 
 pub trait TestReporter {
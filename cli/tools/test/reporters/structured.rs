@@ -0,0 +1,398 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use super::fmt::to_relative_path_or_remote_url;
+use super::*;
+
+/// Results of a test run, grouped by test module.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestReport {
+  pub suites: Vec<TestSuiteReport>,
+  pub passed: usize,
+  pub failed: usize,
+  pub ignored: usize,
+  pub duration_ms: u64,
+  /// Whether a test used the `only` option, which fails the run.
+  pub used_only: bool,
+  /// Everything the tests wrote to stdout and stderr.
+  pub output: String,
+}
+
+impl TestReport {
+  pub fn success(&self) -> bool {
+    self.failed == 0
+      && !self.used_only
+      && self.suites.iter().all(|s| s.uncaught_errors.is_empty())
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestSuiteReport {
+  /// The test module, relative to the current working directory if local.
+  pub name: String,
+  pub specifier: String,
+  pub filtered_out: usize,
+  pub cases: Vec<TestCaseReport>,
+  /// Errors thrown outside of any test, eg. while loading the module.
+  pub uncaught_errors: Vec<TestFailureReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestCaseReport {
+  pub name: String,
+  pub location: TestLocation,
+  pub status: TestCaseStatus,
+  /// `None` for tests that never reported a result.
+  pub duration_ms: Option<u64>,
+  pub failure: Option<TestFailureReport>,
+  pub steps: Vec<TestCaseReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TestCaseStatus {
+  /// The run ended before the test reported a result, eg. with `--fail-fast`.
+  Pending,
+  Passed,
+  Ignored,
+  Failed,
+  Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestFailureReport {
+  pub message: String,
+  /// The full failure, including the stack trace, without colors.
+  pub details: String,
+}
+
+impl TestFailureReport {
+  fn new(failure: &TestFailure, options: &TestFailureFormatOptions) -> Self {
+    Self {
+      message: failure.overview(),
+      details: strip_ansi_codes(&failure.format(options)).into_owned(),
+    }
+  }
+
+  fn from_js_error(
+    error: &JsError,
+    options: &TestFailureFormatOptions,
+  ) -> Self {
+    Self {
+      message: error.exception_message.clone(),
+      details: strip_ansi_codes(&format_test_error(error, options))
+        .into_owned(),
+    }
+  }
+}
+
+fn strip_ansi_codes(text: &str) -> Cow<str> {
+  console_static_text::ansi::strip_ansi_codes(text)
+}
+
+/// Collects the test events into a [`TestReport`] instead of printing them.
+/// The report is sent once the run is flushed.
+pub struct StructuredTestReporter {
+  cwd: Url,
+  failure_format_options: TestFailureFormatOptions,
+  report: TestReport,
+  suites: IndexMap<String, TestSuiteReport>,
+  // Tests and steps by id, assembled into a tree when flushing.
+  cases: IndexMap<usize, TestCaseReport>,
+  steps_by_parent: HashMap<usize, Vec<usize>>,
+  output: Vec<u8>,
+  sender: Option<oneshot::Sender<TestReport>>,
+}
+
+impl StructuredTestReporter {
+  pub fn new(
+    cwd: Url,
+    failure_format_options: TestFailureFormatOptions,
+    sender: oneshot::Sender<TestReport>,
+  ) -> Self {
+    Self {
+      cwd,
+      failure_format_options,
+      report: TestReport::default(),
+      suites: IndexMap::new(),
+      cases: IndexMap::new(),
+      steps_by_parent: HashMap::new(),
+      output: Vec::new(),
+      sender: Some(sender),
+    }
+  }
+
+  fn suite(&mut self, origin: &str) -> &mut TestSuiteReport {
+    let cwd = &self.cwd;
+    self
+      .suites
+      .entry(origin.to_string())
+      .or_insert_with(|| TestSuiteReport {
+        name: to_relative_path_or_remote_url(cwd, origin),
+        specifier: origin.to_string(),
+        filtered_out: 0,
+        cases: Vec::new(),
+        uncaught_errors: Vec::new(),
+      })
+  }
+
+  fn set_result(
+    &mut self,
+    id: usize,
+    status: TestCaseStatus,
+    failure: Option<&TestFailure>,
+    elapsed: u64,
+  ) {
+    let failure =
+      failure.map(|f| TestFailureReport::new(f, &self.failure_format_options));
+    if let Some(case) = self.cases.get_mut(&id) {
+      case.status = status;
+      case.duration_ms = Some(elapsed);
+      case.failure = failure;
+    }
+  }
+
+  fn take_case_tree(&mut self, id: usize) -> Option<TestCaseReport> {
+    let mut case = self.cases.swap_remove(&id)?;
+    for step_id in self.steps_by_parent.remove(&id).unwrap_or_default() {
+      if let Some(step) = self.take_case_tree(step_id) {
+        case.steps.push(step);
+      }
+    }
+    Some(case)
+  }
+}
+
+impl TestReporter for StructuredTestReporter {
+  fn report_register(&mut self, description: &TestDescription) {
+    self.suite(&description.origin);
+    self.cases.insert(
+      description.id,
+      TestCaseReport {
+        name: description.name.clone(),
+        location: description.location.clone(),
+        status: TestCaseStatus::Pending,
+        duration_ms: None,
+        failure: None,
+        steps: Vec::new(),
+      },
+    );
+  }
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    self.suite(&plan.origin).filtered_out = plan.filtered_out;
+    if plan.used_only {
+      self.report.used_only = true;
+    }
+  }
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_slow(&mut self, _description: &TestDescription, _elapsed: u64) {}
+
+  fn report_output(&mut self, output: &[u8]) {
+    self.output.extend_from_slice(output);
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    let (status, failure) = match result {
+      TestResult::Ok => {
+        self.report.passed += 1;
+        (TestCaseStatus::Passed, None)
+      }
+      TestResult::Ignored => {
+        self.report.ignored += 1;
+        (TestCaseStatus::Ignored, None)
+      }
+      TestResult::Failed(failure) => {
+        self.report.failed += 1;
+        (TestCaseStatus::Failed, Some(failure))
+      }
+      TestResult::Cancelled => {
+        self.report.failed += 1;
+        (TestCaseStatus::Cancelled, None)
+      }
+    };
+    self.set_result(description.id, status, failure, elapsed);
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: Box<JsError>) {
+    let failure =
+      TestFailureReport::from_js_error(&error, &self.failure_format_options);
+    self.suite(origin).uncaught_errors.push(failure);
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    self.cases.insert(
+      description.id,
+      TestCaseReport {
+        name: description.name.clone(),
+        location: description.location.clone(),
+        status: TestCaseStatus::Pending,
+        duration_ms: None,
+        failure: None,
+        steps: Vec::new(),
+      },
+    );
+    self
+      .steps_by_parent
+      .entry(description.parent_id)
+      .or_default()
+      .push(description.id);
+  }
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    description: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let (status, failure) = match result {
+      TestStepResult::Ok => (TestCaseStatus::Passed, None),
+      TestStepResult::Ignored => (TestCaseStatus::Ignored, None),
+      TestStepResult::Failed(failure) => {
+        (TestCaseStatus::Failed, Some(failure))
+      }
+    };
+    self.set_result(description.id, status, failure, elapsed);
+  }
+
+  fn report_summary(
+    &mut self,
+    _elapsed: &Duration,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+  }
+
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    for id in tests_pending {
+      self.set_result(*id, TestCaseStatus::Cancelled, None, 0);
+    }
+  }
+
+  fn report_completed(&mut self) {}
+
+  fn flush_report(
+    &mut self,
+    elapsed: &Duration,
+    tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) -> anyhow::Result<()> {
+    for (id, description) in tests {
+      if let Some(case) = self.take_case_tree(*id) {
+        self.suite(&description.origin).cases.push(case);
+      }
+    }
+    let mut report = std::mem::take(&mut self.report);
+    report.suites = std::mem::take(&mut self.suites).into_values().collect();
+    report.duration_ms = elapsed.as_millis() as u64;
+    report.output = String::from_utf8_lossy(&self.output).into_owned();
+    if let Some(sender) = self.sender.take() {
+      // the receiver is gone if the caller stopped waiting for the report
+      let _ = sender.send(report);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn location() -> TestLocation {
+    TestLocation {
+      file_name: "file:///dir/a_test.ts".to_string(),
+      line_number: 1,
+      column_number: 1,
+    }
+  }
+
+  fn step(id: usize, name: &str, parent_id: usize) -> TestStepDescription {
+    TestStepDescription {
+      id,
+      name: name.to_string(),
+      origin: "file:///dir/a_test.ts".to_string(),
+      location: location(),
+      level: 1,
+      parent_id,
+      root_id: 0,
+      root_name: "root".to_string(),
+    }
+  }
+
+  #[test]
+  fn builds_tree_of_steps() {
+    let (sender, mut receiver) = oneshot::channel();
+    let mut reporter = StructuredTestReporter::new(
+      Url::parse("file:///dir/").unwrap(),
+      Default::default(),
+      sender,
+    );
+    let test = TestDescription {
+      id: 0,
+      name: "root".to_string(),
+      ignore: false,
+      only: false,
+      origin: "file:///dir/a_test.ts".to_string(),
+      location: location(),
+      sanitize_ops: true,
+      sanitize_resources: true,
+    };
+    let mut tests = IndexMap::new();
+    tests.insert(0, test.clone());
+    reporter.report_register(&test);
+    reporter.report_step_register(&step(1, "first", 0));
+    reporter.report_step_register(&step(2, "nested", 1));
+    reporter.report_step_register(&step(3, "second", 0));
+    reporter.report_step_result(
+      &step(2, "nested", 1),
+      &TestStepResult::Failed(TestFailure::Incomplete),
+      3,
+      &tests,
+      &IndexMap::new(),
+    );
+    reporter.report_result(&test, &TestResult::Ok, 5);
+    reporter
+      .flush_report(&Duration::from_millis(7), &tests, &IndexMap::new())
+      .unwrap();
+
+    let report = receiver.try_recv().unwrap();
+    assert_eq!(report.passed, 1);
+    assert_eq!(report.duration_ms, 7);
+    assert_eq!(report.suites.len(), 1);
+    assert_eq!(report.suites[0].name, "./a_test.ts");
+    let root = &report.suites[0].cases[0];
+    assert_eq!(root.status, TestCaseStatus::Passed);
+    assert_eq!(root.duration_ms, Some(5));
+    let step_names = root.steps.iter().map(|s| &s.name).collect::<Vec<_>>();
+    assert_eq!(step_names, vec!["first", "second"]);
+    assert_eq!(root.steps[1].status, TestCaseStatus::Pending);
+    let nested = &root.steps[0].steps[0];
+    assert_eq!(nested.status, TestCaseStatus::Failed);
+    assert_eq!(
+      nested.failure.as_ref().unwrap().message,
+      "Didn't complete before parent"
+    );
+  }
+}
@@ -0,0 +1,164 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_graph::GraphKind;
+
+use crate::args::Flags;
+use crate::cache::HttpCache;
+use crate::cache::LocalHttpCache;
+use crate::factory::CliFactory;
+
+/// Progress of [`vendor_dependencies`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VendorEvent {
+  /// The module graph was built, `total` remote modules will be vendored.
+  Started {
+    total: usize,
+  },
+  /// A remote module was copied into the vendor folder. `completed` counts
+  /// this module.
+  Vendored {
+    specifier: ModuleSpecifier,
+    completed: usize,
+    total: usize,
+  },
+  Finished {
+    total: usize,
+  },
+}
+
+/// Copies the remote modules `entrypoints` depend on into `out_dir`,
+/// downloading those that aren't cached yet.
+///
+/// The folder has the layout of the one used with `"vendor": true` in
+/// `deno.json`, so using `vendor/` next to the config file as `out_dir`
+/// makes Deno load the modules from there.
+pub async fn vendor_dependencies(
+  flags: Arc<Flags>,
+  entrypoints: Vec<String>,
+  out_dir: PathBuf,
+  progress: impl Fn(VendorEvent),
+) -> Result<(), AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let graph = factory
+    .create_graph_for_embedder(GraphKind::All, &entrypoints)
+    .await?;
+
+  // redirects are vendored as well, so that the original urls resolve
+  let specifiers = graph
+    .redirects
+    .keys()
+    .chain(graph.modules().map(|module| module.specifier()))
+    .filter(|specifier| matches!(specifier.scheme(), "http" | "https"))
+    .collect::<BTreeSet<_>>();
+  let total = specifiers.len();
+  progress(VendorEvent::Started { total });
+
+  std::fs::create_dir_all(&out_dir)
+    .with_context(|| format!("Creating '{}'", out_dir.display()))?;
+  let local_cache = LocalHttpCache::new(
+    out_dir,
+    factory.global_http_cache()?.clone(),
+    deno_cache_dir::GlobalToLocalCopy::Allow,
+  );
+  for (index, specifier) in specifiers.into_iter().enumerate() {
+    // reading through the local cache copies the module from the global one
+    let cache_key = local_cache.cache_item_key(specifier)?;
+    let entry = local_cache
+      .get(&cache_key, None)
+      .map_err(|err| match err {
+        deno_cache_dir::CacheReadFileError::Io(err) => AnyError::from(err),
+        deno_cache_dir::CacheReadFileError::ChecksumIntegrity(err) => {
+          anyhow!(
+            "Integrity check failed. Actual: {}, expected: {}",
+            err.actual,
+            err.expected
+          )
+        }
+      })
+      .with_context(|| format!("Vendoring {}", specifier))?;
+    if entry.is_none() {
+      log::warn!(
+        "{} is missing from the cache and wasn't vendored.",
+        specifier
+      );
+      continue;
+    }
+    progress(VendorEvent::Vendored {
+      specifier: specifier.clone(),
+      completed: index + 1,
+      total,
+    });
+  }
+  progress(VendorEvent::Finished { total });
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use std::cell::RefCell;
+
+  use test_util::TempDir;
+
+  use super::*;
+  use crate::args::ConfigFlag;
+  use crate::args::InternalFlags;
+
+  #[tokio::test]
+  async fn vendors_remote_modules() {
+    let _http_server_guard = test_util::http_server();
+    let temp_dir = TempDir::new();
+    temp_dir.write(
+      "main.ts",
+      "import \"http://localhost:4545/run/002_hello.ts\";\n",
+    );
+    let flags = Flags {
+      config_flag: ConfigFlag::Disabled,
+      internal: InternalFlags {
+        cache_path: Some(temp_dir.path().join("deno_dir").to_path_buf()),
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    let out_dir = temp_dir.path().join("vendor");
+    let events = RefCell::new(Vec::new());
+    vendor_dependencies(
+      Arc::new(flags),
+      vec![temp_dir.path().join("main.ts").to_string()],
+      out_dir.to_path_buf(),
+      |event| events.borrow_mut().push(event),
+    )
+    .await
+    .unwrap();
+
+    let specifier =
+      ModuleSpecifier::parse("http://localhost:4545/run/002_hello.ts").unwrap();
+    assert_eq!(
+      events.into_inner(),
+      vec![
+        VendorEvent::Started { total: 1 },
+        VendorEvent::Vendored {
+          specifier,
+          completed: 1,
+          total: 1,
+        },
+        VendorEvent::Finished { total: 1 },
+      ]
+    );
+    assert_eq!(
+      out_dir
+        .join("localhost_4545")
+        .join("run")
+        .join("002_hello.ts")
+        .read_to_string(),
+      "console.log(\"Hello World\");\n"
+    );
+  }
+}
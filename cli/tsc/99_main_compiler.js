@@ -133,6 +133,14 @@ delete Object.prototype.__proto__;
   /** @type {Map<string, boolean>} */
   const isNodeSourceFileCache = new Map();
 
+  // Used by isolates kept alive between checks (see `cli/tsc/daemon.rs`),
+  // where cached source files may be out of date.
+  let revalidateSourceFiles = false;
+
+  /** The program of the previous check of an isolate kept alive.
+   * @type {ts.EmitAndSemanticDiagnosticsBuilderProgram | undefined} */
+  let lastProgram;
+
   // Maps asset specifiers to the first scope that the asset was loaded into.
   /** @type {Map<string, string | null>} */
   const assetScopes = new Map();
@@ -613,7 +621,10 @@ delete Object.prototype.__proto__;
       specifier = normalizedToOriginalMap.get(specifier) ?? specifier;
 
       let sourceFile = sourceFileCache.get(specifier);
-      if (sourceFile) {
+      if (
+        sourceFile &&
+        (!revalidateSourceFiles || specifier.startsWith(ASSETS_URL_PREFIX))
+      ) {
         return sourceFile;
       }
 
@@ -623,6 +634,9 @@ delete Object.prototype.__proto__;
         return undefined;
       }
       const { data, scriptKind, version, isCjs } = fileInfo;
+      if (sourceFile?.version === version) {
+        return sourceFile;
+      }
       assert(
         data != null,
         `"data" is unexpectedly null for "${specifier}".`,
@@ -947,6 +961,7 @@ delete Object.prototype.__proto__;
    * @property {boolean} debug
    * @property {string[]} rootNames
    * @property {boolean} localOnly
+   * @property {boolean} reuseProgram
    */
 
   /**
@@ -1003,7 +1018,9 @@ delete Object.prototype.__proto__;
   /** The API that is called by Rust when executing a request.
    * @param {Request} request
    */
-  function exec({ config, debug: debugFlag, rootNames, localOnly }) {
+  function exec(
+    { config, debug: debugFlag, rootNames, localOnly, reuseProgram },
+  ) {
     setLogDebug(debugFlag, "TS");
     performanceStart();
 
@@ -1023,12 +1040,24 @@ delete Object.prototype.__proto__;
     // URLs which Deno supports. So we need to either ignore the diagnostic, or
     // inject it ourselves.
     Object.assign(options, { allowNonTsExtensions: true });
-    const program = ts.createIncrementalProgram({
-      rootNames,
-      options,
-      host,
-      configFileParsingDiagnostics,
-    });
+    revalidateSourceFiles = reuseProgram;
+    // the previous program lets tsc only check the files that changed and
+    // the ones depending on them again
+    const program = reuseProgram && lastProgram
+      ? ts.createEmitAndSemanticDiagnosticsBuilderProgram(
+        rootNames,
+        options,
+        host,
+        lastProgram,
+        configFileParsingDiagnostics,
+      )
+      : ts.createIncrementalProgram({
+        rootNames,
+        options,
+        host,
+        configFileParsingDiagnostics,
+      });
+    lastProgram = reuseProgram ? program : undefined;
 
     const checkFiles = localOnly
       ? rootNames
@@ -0,0 +1,109 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::sync::mpsc;
+use std::thread;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::located_script_name;
+use deno_core::JsRuntime;
+use deno_core::RuntimeOptions;
+use tokio::sync::oneshot;
+
+use super::compiler_snapshot;
+use super::exec_source;
+use super::map_root_names;
+use super::op_create_hash;
+use super::op_emit;
+use super::op_is_node_file;
+use super::op_load;
+use super::op_resolve;
+use super::op_respond;
+use super::take_response;
+use super::Request;
+use super::Response;
+use super::State;
+use crate::worker::create_isolate_create_params;
+
+deno_core::extension!(
+  deno_cli_tsc_daemon,
+  ops = [
+    op_create_hash,
+    op_emit,
+    op_is_node_file,
+    op_load,
+    op_resolve,
+    op_respond,
+  ],
+  state = |state| {
+    state.put(State::default());
+  },
+);
+
+type ExecRequest = (Request, oneshot::Sender<Result<Response, AnyError>>);
+
+/// Executes requests on a tsc isolate that's kept alive on its own thread.
+///
+/// The isolate keeps the source files it parsed and the program of the
+/// previous request, so a request for a graph where some modules changed
+/// only checks them and the modules that depend on them again. The isolate
+/// is shut down once every clone is dropped.
+#[derive(Debug, Clone)]
+pub struct TscDaemon {
+  sender: mpsc::Sender<ExecRequest>,
+}
+
+impl TscDaemon {
+  pub fn start() -> Self {
+    let (sender, receiver) = mpsc::channel::<ExecRequest>();
+    thread::spawn(move || {
+      let mut runtime = JsRuntime::new(RuntimeOptions {
+        startup_snapshot: Some(compiler_snapshot()),
+        extensions: vec![deno_cli_tsc_daemon::init_ops()],
+        create_params: create_isolate_create_params(),
+        ..Default::default()
+      });
+      while let Ok((request, response_tx)) = receiver.recv() {
+        let _ = response_tx.send(exec(&mut runtime, request));
+      }
+    });
+    Self { sender }
+  }
+
+  /// Like [`exec`](super::exec), but on the isolate of the daemon.
+  pub async fn exec(&self, request: Request) -> Result<Response, AnyError> {
+    let (response_tx, response_rx) = oneshot::channel();
+    self
+      .sender
+      .send((request, response_tx))
+      .map_err(|_| anyhow!("The type checking daemon stopped."))?;
+    response_rx
+      .await
+      .map_err(|_| anyhow!("The type checking daemon stopped."))?
+  }
+}
+
+fn exec(
+  runtime: &mut JsRuntime,
+  request: Request,
+) -> Result<Response, AnyError> {
+  let (root_names, root_map, remapped_specifiers) =
+    map_root_names(&request.root_names);
+  let exec_source = exec_source(&request, &root_names, true);
+  let op_state = runtime.op_state();
+  op_state.borrow_mut().put(State::new(
+    request.graph,
+    request.hash_data,
+    request.maybe_npm,
+    request.maybe_tsbuildinfo,
+    root_map,
+    remapped_specifiers,
+    std::env::current_dir().context("Unable to get CWD")?,
+  ));
+
+  runtime.execute_script(located_script_name!(), exec_source)?;
+
+  let state = op_state.borrow_mut().take::<State>();
+  take_response(state)
+}
@@ -307,6 +307,18 @@ impl Diagnostics {
     self.0.is_empty()
   }
 
+  /// Merges the diagnostics of separate checks, dropping the duplicates
+  /// reported by more than one of them.
+  pub fn merge(all: impl IntoIterator<Item = Diagnostics>) -> Self {
+    let mut merged: Vec<Diagnostic> = Vec::new();
+    for diagnostic in all.into_iter().flatten() {
+      if !merged.contains(&diagnostic) {
+        merged.push(diagnostic);
+      }
+    }
+    Self(merged)
+  }
+
   /// Modifies all the diagnostics to have their display positions
   /// modified to point at the original source.
   pub fn apply_fast_check_source_maps(&mut self, graph: &ModuleGraph) {
@@ -353,6 +365,15 @@ impl Diagnostics {
   }
 }
 
+impl IntoIterator for Diagnostics {
+  type Item = Diagnostic;
+  type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.0.into_iter()
+  }
+}
+
 impl<'de> Deserialize<'de> for Diagnostics {
   fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
   where
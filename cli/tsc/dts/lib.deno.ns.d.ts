@@ -328,6 +328,13 @@ declare namespace Deno {
      * @category Errors */
     export class NotCapable extends Error {}
 
+    /**
+     * Raised when opening a file, a socket or spawning a subprocess exceeds
+     * a resource quota that the embedder set for the worker.
+     *
+     * @category Errors */
+    export class QuotaExceeded extends Error {}
+
     export {}; // only export exports
   }
 
@@ -45,15 +45,20 @@ use node_resolver::NodeResolutionKind;
 use node_resolver::ResolutionMode;
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
+use std::collections::hash_map;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use thiserror::Error;
 
+mod daemon;
 mod diagnostics;
 
+pub use self::daemon::TscDaemon;
 pub use self::diagnostics::Diagnostic;
 pub use self::diagnostics::DiagnosticCategory;
 pub use self::diagnostics::Diagnostics;
@@ -377,7 +382,7 @@ impl TypeCheckingCjsTracker {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RequestNpmState {
   pub cjs_tracker: Arc<TypeCheckingCjsTracker>,
   pub node_resolver: Arc<NodeResolver>,
@@ -1039,31 +1044,8 @@ fn op_respond_inner(state: &mut OpState, args: RespondArgs) {
 /// contains information, like any emitted files, diagnostics, statistics and
 /// optionally an updated TypeScript build info.
 pub fn exec(request: Request) -> Result<Response, AnyError> {
-  // tsc cannot handle root specifiers that don't have one of the "acceptable"
-  // extensions.  Therefore, we have to check the root modules against their
-  // extensions and remap any that are unacceptable to tsc and add them to the
-  // op state so when requested, we can remap to the original specifier.
-  let mut root_map = HashMap::new();
-  let mut remapped_specifiers = HashMap::new();
-  let root_names: Vec<String> = request
-    .root_names
-    .iter()
-    .map(|(s, mt)| match s.scheme() {
-      "data" | "blob" => {
-        let specifier_str = hash_url(s, *mt);
-        remapped_specifiers.insert(specifier_str.clone(), s.clone());
-        specifier_str
-      }
-      _ => {
-        if let Some(new_specifier) = mapped_specifier_for_tsc(s, *mt) {
-          root_map.insert(new_specifier.clone(), s.clone());
-          new_specifier
-        } else {
-          s.to_string()
-        }
-      }
-    })
-    .collect();
+  let (root_names, root_map, remapped_specifiers) =
+    map_root_names(&request.root_names);
 
   deno_core::extension!(deno_cli_tsc,
     ops = [
@@ -1094,13 +1076,7 @@ pub fn exec(request: Request) -> Result<Response, AnyError> {
     },
   );
 
-  let request_value = json!({
-    "config": request.config,
-    "debug": request.debug,
-    "rootNames": root_names,
-    "localOnly": request.check_mode == TypeCheckMode::Local,
-  });
-  let exec_source = format!("globalThis.exec({request_value})");
+  let exec_source = exec_source(&request, &root_names, false);
 
   let mut runtime = JsRuntime::new(RuntimeOptions {
     startup_snapshot: Some(compiler_snapshot()),
@@ -1117,8 +1093,61 @@ pub fn exec(request: Request) -> Result<Response, AnyError> {
 
   let op_state = runtime.op_state();
   let mut op_state = op_state.borrow_mut();
-  let state = op_state.take::<State>();
+  take_response(op_state.take::<State>())
+}
 
+/// Maps the roots to the names tsc is given, returning them with the maps
+/// back to the original specifiers.
+fn map_root_names(
+  roots: &[(ModuleSpecifier, MediaType)],
+) -> (
+  Vec<String>,
+  HashMap<String, ModuleSpecifier>,
+  HashMap<String, ModuleSpecifier>,
+) {
+  // tsc cannot handle root specifiers that don't have one of the "acceptable"
+  // extensions.  Therefore, we have to check the root modules against their
+  // extensions and remap any that are unacceptable to tsc and add them to the
+  // op state so when requested, we can remap to the original specifier.
+  let mut root_map = HashMap::new();
+  let mut remapped_specifiers = HashMap::new();
+  let root_names: Vec<String> = roots
+    .iter()
+    .map(|(s, mt)| match s.scheme() {
+      "data" | "blob" => {
+        let specifier_str = hash_url(s, *mt);
+        remapped_specifiers.insert(specifier_str.clone(), s.clone());
+        specifier_str
+      }
+      _ => {
+        if let Some(new_specifier) = mapped_specifier_for_tsc(s, *mt) {
+          root_map.insert(new_specifier.clone(), s.clone());
+          new_specifier
+        } else {
+          s.to_string()
+        }
+      }
+    })
+    .collect();
+  (root_names, root_map, remapped_specifiers)
+}
+
+fn exec_source(
+  request: &Request,
+  root_names: &[String],
+  reuse_program: bool,
+) -> String {
+  let request_value = json!({
+    "config": request.config,
+    "debug": request.debug,
+    "rootNames": root_names,
+    "localOnly": request.check_mode == TypeCheckMode::Local,
+    "reuseProgram": reuse_program,
+  });
+  format!("globalThis.exec({request_value})")
+}
+
+fn take_response(state: State) -> Result<Response, AnyError> {
   if let Some(response) = state.maybe_response {
     let diagnostics = response.diagnostics;
     let maybe_tsbuildinfo = state.maybe_tsbuildinfo;
@@ -1134,6 +1163,167 @@ pub fn exec(request: Request) -> Result<Response, AnyError> {
   }
 }
 
+/// Like [`exec`], but checks groups of roots that don't share local modules
+/// in separate isolates, on up to `jobs` threads, and merges their
+/// diagnostics and statistics.
+///
+/// The build info is only returned when a single isolate checks the whole
+/// request, as the build infos of separate programs can't be merged.
+pub fn exec_parallel(
+  request: Request,
+  jobs: NonZeroUsize,
+) -> Result<Response, AnyError> {
+  let chunks = partition_roots(&request.graph, &request.root_names, jobs.get());
+  if chunks.len() <= 1 {
+    return exec(request);
+  }
+  log::debug!("Type checking in {} isolates.", chunks.len());
+
+  let requests = chunks.into_iter().map(|root_names| Request {
+    config: request.config.clone(),
+    debug: request.debug,
+    graph: request.graph.clone(),
+    hash_data: request.hash_data,
+    maybe_npm: request.maybe_npm.clone(),
+    maybe_tsbuildinfo: None,
+    root_names,
+    check_mode: request.check_mode,
+  });
+  let responses = std::thread::scope(|scope| {
+    let handles = requests
+      .map(|request| scope.spawn(move || exec(request)))
+      .collect::<Vec<_>>();
+    handles
+      .into_iter()
+      .map(|handle| {
+        handle
+          .join()
+          .unwrap_or_else(|err| std::panic::resume_unwind(err))
+      })
+      .collect::<Result<Vec<_>, _>>()
+  })?;
+
+  let mut stats = Stats::default();
+  let mut diagnostics = Vec::with_capacity(responses.len());
+  for response in responses {
+    for (key, value) in response.stats.0 {
+      match stats.0.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, total)) => *total += value,
+        None => stats.0.push((key, value)),
+      }
+    }
+    diagnostics.push(response.diagnostics);
+  }
+  Ok(Response {
+    diagnostics: Diagnostics::merge(diagnostics),
+    maybe_tsbuildinfo: None,
+    stats,
+  })
+}
+
+/// Splits the roots into at most `max_chunks` groups to check separately.
+/// Roots that reach the same local module stay in the same group, so that
+/// the module is only checked once, and the groups are balanced by the
+/// number of local modules they reach. Declaration files and modules
+/// augmenting the global scope can change the types of every other module,
+/// so they are added to every group.
+fn partition_roots(
+  graph: &ModuleGraph,
+  roots: &[(ModuleSpecifier, MediaType)],
+  max_chunks: usize,
+) -> Vec<Vec<(ModuleSpecifier, MediaType)>> {
+  fn find(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+      parents[index] = parents[parents[index]];
+      index = parents[index];
+    }
+    index
+  }
+
+  // union-find of the roots, joining the roots that reach the same module
+  let mut parents = (0..roots.len()).collect::<Vec<_>>();
+  let mut sizes = vec![0; roots.len()];
+  let mut owners = HashMap::new();
+  let mut globals = Vec::<(ModuleSpecifier, MediaType)>::new();
+  for (index, (root, _)) in roots.iter().enumerate() {
+    let mut walk = graph.walk(
+      std::iter::once(root),
+      deno_graph::WalkOptions {
+        check_js: true,
+        follow_dynamic: true,
+        kind: GraphKind::All,
+        prefer_fast_check_graph: true,
+      },
+    );
+    while let Some((specifier, _)) = walk.next() {
+      if let Some(deno_graph::Module::Js(module)) = graph.get(specifier) {
+        if affects_global_scope(module)
+          && !globals.iter().any(|(global, _)| global == specifier)
+        {
+          globals.push((specifier.clone(), module.media_type));
+        }
+      }
+      if specifier.scheme() != "file" {
+        // remote modules and npm packages may be checked more than once
+        walk.skip_previous_dependencies();
+        continue;
+      }
+      match owners.entry(specifier) {
+        hash_map::Entry::Occupied(entry) => {
+          let owner = find(&mut parents, *entry.get());
+          let current = find(&mut parents, index);
+          parents[owner] = current;
+          // the dependencies were already reached by the owner
+          walk.skip_previous_dependencies();
+        }
+        hash_map::Entry::Vacant(entry) => {
+          entry.insert(index);
+          sizes[index] += 1;
+        }
+      }
+    }
+  }
+
+  let mut groups = BTreeMap::<usize, (usize, Vec<_>)>::new();
+  for (index, root) in roots.iter().enumerate() {
+    if globals.iter().any(|(global, _)| global == &root.0) {
+      // added to every chunk below
+      continue;
+    }
+    let group = groups.entry(find(&mut parents, index)).or_default();
+    group.0 += sizes[index];
+    group.1.push(root.clone());
+  }
+  let mut groups = groups.into_values().collect::<Vec<_>>();
+  // assign the largest groups first, each to the smallest chunk
+  groups.sort_by(|a, b| b.0.cmp(&a.0));
+  let mut chunks = vec![(0, Vec::new()); max_chunks.min(groups.len())];
+  for (size, roots) in groups {
+    let chunk = chunks.iter_mut().min_by_key(|(total, _)| *total).unwrap();
+    chunk.0 += size;
+    chunk.1.extend(roots);
+  }
+  chunks
+    .into_iter()
+    .map(|(_, mut roots)| {
+      for global in &globals {
+        if !roots.iter().any(|(root, _)| root == &global.0) {
+          roots.push(global.clone());
+        }
+      }
+      roots
+    })
+    .collect()
+}
+
+/// Whether `module` can change the types of modules that don't import it.
+fn affects_global_scope(module: &deno_graph::JsModule) -> bool {
+  matches!(
+    module.media_type,
+    MediaType::Dts | MediaType::Dmts | MediaType::Dcts
+  ) || module.source.contains("declare global")
+}
+
 #[cfg(test)]
 mod tests {
   use super::Diagnostic;
@@ -1480,4 +1670,105 @@ mod tests {
       .expect("exec should not have errored");
     assert!(actual.diagnostics.is_empty());
   }
+
+  #[tokio::test]
+  async fn test_partition_roots() {
+    use deno_graph::source::MemoryLoader;
+    use deno_graph::source::Source;
+
+    let module = |specifier, content| {
+      (
+        specifier,
+        Source::Module {
+          specifier,
+          maybe_headers: None,
+          content,
+        },
+      )
+    };
+    let loader = MemoryLoader::new(
+      vec![
+        module("file:///a.ts", "import './shared.ts';"),
+        module("file:///b.ts", "import './shared.ts';"),
+        module("file:///shared.ts", "export {};"),
+        module("file:///c.ts", "export {};"),
+      ],
+      Vec::new(),
+    );
+    let roots = ["file:///a.ts", "file:///b.ts", "file:///c.ts"]
+      .into_iter()
+      .map(|s| (ModuleSpecifier::parse(s).unwrap(), MediaType::TypeScript))
+      .collect::<Vec<_>>();
+    let mut graph = ModuleGraph::new(GraphKind::TypesOnly);
+    graph
+      .build(
+        roots.iter().map(|(s, _)| s.clone()).collect(),
+        &loader,
+        Default::default(),
+      )
+      .await;
+
+    let chunks = partition_roots(&graph, &roots, 4);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0], roots[..2].to_vec());
+    assert_eq!(chunks[1], roots[2..].to_vec());
+    assert_eq!(partition_roots(&graph, &roots, 1).len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_partition_roots_globals() {
+    use deno_graph::source::MemoryLoader;
+    use deno_graph::source::Source;
+
+    let module = |specifier, content| {
+      (
+        specifier,
+        Source::Module {
+          specifier,
+          maybe_headers: None,
+          content,
+        },
+      )
+    };
+    let loader = MemoryLoader::new(
+      vec![
+        module("file:///a.ts", "import './augment.ts';"),
+        module(
+          "file:///augment.ts",
+          "declare global { var value: string; }\nexport {};",
+        ),
+        module("file:///b.ts", "console.log(value);"),
+        module("file:///types.d.ts", "declare const other: number;"),
+      ],
+      Vec::new(),
+    );
+    let roots = [
+      ("file:///a.ts", MediaType::TypeScript),
+      ("file:///b.ts", MediaType::TypeScript),
+      ("file:///types.d.ts", MediaType::Dts),
+    ]
+    .into_iter()
+    .map(|(s, media_type)| (ModuleSpecifier::parse(s).unwrap(), media_type))
+    .collect::<Vec<_>>();
+    let mut graph = ModuleGraph::new(GraphKind::TypesOnly);
+    graph
+      .build(
+        roots.iter().map(|(s, _)| s.clone()).collect(),
+        &loader,
+        Default::default(),
+      )
+      .await;
+
+    let augment = (
+      ModuleSpecifier::parse("file:///augment.ts").unwrap(),
+      MediaType::TypeScript,
+    );
+    let chunks = partition_roots(&graph, &roots, 4);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(
+      chunks[0],
+      vec![roots[0].clone(), augment.clone(), roots[2].clone()]
+    );
+    assert_eq!(chunks[1], vec![roots[1].clone(), augment, roots[2].clone()]);
+  }
 }
@@ -0,0 +1,192 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::io;
+use std::time::Duration;
+
+/// Measures the CPU time consumed by the thread that created it. Unlike
+/// `CLOCK_THREAD_CPUTIME_ID`, the clock can be read from any thread.
+pub struct ThreadCpuClock {
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  clock_id: libc::clockid_t,
+  #[cfg(target_os = "macos")]
+  thread: libc::mach_port_t,
+  #[cfg(windows)]
+  thread: winapi::um::winnt::HANDLE,
+}
+
+// SAFETY: the clock id, mach port and thread handle are valid on any thread.
+unsafe impl Send for ThreadCpuClock {}
+
+impl ThreadCpuClock {
+  /// Creates a clock for the calling thread.
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  pub fn current() -> io::Result<Self> {
+    let mut clock_id: libc::clockid_t = 0;
+    // SAFETY: libc call with a valid out pointer
+    let result = unsafe {
+      libc::pthread_getcpuclockid(libc::pthread_self(), &mut clock_id)
+    };
+    if result != 0 {
+      return Err(io::Error::from_raw_os_error(result));
+    }
+    Ok(Self { clock_id })
+  }
+
+  #[cfg(target_os = "macos")]
+  pub fn current() -> io::Result<Self> {
+    // SAFETY: libc call, the port is owned by the pthread
+    let thread = unsafe { libc::pthread_mach_thread_np(libc::pthread_self()) };
+    Ok(Self { thread })
+  }
+
+  #[cfg(windows)]
+  pub fn current() -> io::Result<Self> {
+    use winapi::shared::minwindef::FALSE;
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+    use winapi::um::processthreadsapi::OpenThread;
+    use winapi::um::winnt::THREAD_QUERY_LIMITED_INFORMATION;
+
+    // SAFETY: winapi calls, the pseudo handle returned by
+    // `GetCurrentThread` can't be used from other threads
+    let thread = unsafe {
+      OpenThread(
+        THREAD_QUERY_LIMITED_INFORMATION,
+        FALSE,
+        GetCurrentThreadId(),
+      )
+    };
+    if thread.is_null() {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(Self { thread })
+  }
+
+  #[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+  )))]
+  pub fn current() -> io::Result<Self> {
+    Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "Measuring thread CPU time is not supported on this platform.",
+    ))
+  }
+
+  /// CPU time the thread has consumed since it started.
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  pub fn elapsed(&self) -> io::Result<Duration> {
+    let mut time = libc::timespec {
+      tv_sec: 0,
+      tv_nsec: 0,
+    };
+    // SAFETY: libc call with a valid out pointer
+    if unsafe { libc::clock_gettime(self.clock_id, &mut time) } != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+  }
+
+  #[cfg(target_os = "macos")]
+  #[allow(deprecated)] // the `mach2` crate isn't a dependency
+  pub fn elapsed(&self) -> io::Result<Duration> {
+    // SAFETY: zeroed is a valid value for this plain C struct
+    let mut info: libc::thread_basic_info = unsafe { std::mem::zeroed() };
+    let mut count = libc::THREAD_BASIC_INFO_COUNT;
+    // SAFETY: libc call with an out pointer of the requested flavor
+    let result = unsafe {
+      libc::thread_info(
+        self.thread,
+        libc::THREAD_BASIC_INFO as libc::thread_flavor_t,
+        &mut info as *mut _ as libc::thread_info_t,
+        &mut count,
+      )
+    };
+    if result != libc::KERN_SUCCESS {
+      return Err(io::Error::other(format!(
+        "thread_info failed (error {result})"
+      )));
+    }
+    let to_duration = |time: libc::time_value_t| {
+      Duration::new(time.seconds as u64, time.microseconds as u32 * 1000)
+    };
+    Ok(to_duration(info.user_time) + to_duration(info.system_time))
+  }
+
+  #[cfg(windows)]
+  pub fn elapsed(&self) -> io::Result<Duration> {
+    use winapi::shared::minwindef::FILETIME;
+    use winapi::um::processthreadsapi::GetThreadTimes;
+
+    // SAFETY: zeroed is a valid value for this plain C struct
+    let zeroed = || unsafe { std::mem::zeroed::<FILETIME>() };
+    let mut creation_time = zeroed();
+    let mut exit_time = zeroed();
+    let mut kernel_time = zeroed();
+    let mut user_time = zeroed();
+    // SAFETY: winapi call with valid out pointers
+    let result = unsafe {
+      GetThreadTimes(
+        self.thread,
+        &mut creation_time,
+        &mut exit_time,
+        &mut kernel_time,
+        &mut user_time,
+      )
+    };
+    if result == 0 {
+      return Err(io::Error::last_os_error());
+    }
+    // in units of 100 nanoseconds
+    let to_duration = |time: FILETIME| {
+      let ticks =
+        ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+      Duration::from_nanos(ticks * 100)
+    };
+    Ok(to_duration(kernel_time) + to_duration(user_time))
+  }
+
+  #[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+  )))]
+  pub fn elapsed(&self) -> io::Result<Duration> {
+    unreachable!()
+  }
+}
+
+#[cfg(windows)]
+impl Drop for ThreadCpuClock {
+  fn drop(&mut self) {
+    // SAFETY: the handle was opened by `ThreadCpuClock::current`
+    unsafe {
+      winapi::um::handleapi::CloseHandle(self.thread);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn measures_other_thread() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = std::thread::spawn(move || {
+      tx.send(ThreadCpuClock::current().unwrap()).unwrap();
+      let start = std::time::Instant::now();
+      let mut n = 0u64;
+      while start.elapsed() < Duration::from_millis(50) {
+        n = std::hint::black_box(n.wrapping_add(1));
+      }
+    });
+    let clock = rx.recv().unwrap();
+    let before = clock.elapsed().unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(clock.elapsed().unwrap() > before);
+    handle.join().unwrap();
+  }
+}
@@ -2,25 +2,32 @@
 
 use crate::args::Flags;
 use crate::colors;
+use crate::errors::format_error_with_formatter;
+use crate::errors::format_js_error;
 use crate::util::fs::canonicalize_path;
 
 use deno_config::glob::PathOrPatternSet;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
+use deno_core::futures::stream;
 use deno_core::futures::Future;
 use deno_core::futures::FutureExt;
+use deno_core::futures::Stream;
 use deno_core::parking_lot::Mutex;
-use deno_runtime::fmt_errors::format_js_error;
 use log::info;
 use notify::event::Event as NotifyEvent;
 use notify::event::EventKind;
+use notify::Config as NotifyConfig;
 use notify::Error as NotifyError;
+use notify::PollWatcher;
 use notify::RecommendedWatcher;
 use notify::RecursiveMode;
 use notify::Watcher;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::io::IsTerminal;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -39,16 +46,20 @@ struct DebouncedReceiver {
   // lose items if a `recv()` never completes
   received_items: HashSet<PathBuf>,
   receiver: UnboundedReceiver<Vec<PathBuf>>,
+  debounce: Duration,
 }
 
 impl DebouncedReceiver {
-  fn new_with_sender() -> (Arc<mpsc::UnboundedSender<Vec<PathBuf>>>, Self) {
+  fn new_with_sender(
+    debounce: Duration,
+  ) -> (Arc<mpsc::UnboundedSender<Vec<PathBuf>>>, Self) {
     let (sender, receiver) = mpsc::unbounded_channel();
     (
       Arc::new(sender),
       Self {
         receiver,
         received_items: HashSet::new(),
+        debounce,
       },
     )
   }
@@ -65,7 +76,7 @@ impl DebouncedReceiver {
         items = self.receiver.recv() => {
           self.received_items.extend(items?);
         }
-        _ = sleep(DEBOUNCE_INTERVAL) => {
+        _ = sleep(self.debounce) => {
           return Some(self.received_items.drain().collect());
         }
       }
@@ -79,6 +90,10 @@ where
 {
   let result = watch_future.await;
   if let Err(err) = result {
+    if let Some(error_string) = format_error_with_formatter(&err) {
+      log::error!("{}", error_string);
+      return false;
+    }
     let error_string = match err.downcast_ref::<JsError>() {
       Some(e) => format_js_error(e),
       None => format!("{err:?}"),
@@ -258,7 +273,7 @@ where
   let (restart_tx, mut restart_rx) = tokio::sync::mpsc::unbounded_channel();
   let (changed_paths_tx, changed_paths_rx) = tokio::sync::broadcast::channel(4);
   let (watcher_sender, mut watcher_receiver) =
-    DebouncedReceiver::new_with_sender();
+    DebouncedReceiver::new_with_sender(DEBOUNCE_INTERVAL);
 
   let PrintConfig {
     banner,
@@ -307,13 +322,25 @@ where
       tokio::task::yield_now().await;
     }
 
-    let mut watcher = new_watcher(watcher_sender.clone())?;
-    consume_paths_to_watch(&mut watcher, &mut paths_to_watch_rx, &exclude_set);
+    let mut watcher = new_watcher(
+      watcher_sender.clone(),
+      FileWatcherBackend::Native,
+      Default::default(),
+    )?;
+    consume_paths_to_watch(
+      watcher.as_mut(),
+      &mut paths_to_watch_rx,
+      &exclude_set,
+    );
 
     let receiver_future = async {
       loop {
         let maybe_paths = paths_to_watch_rx.recv().await;
-        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap(), &exclude_set);
+        add_paths_to_watcher(
+          watcher.as_mut(),
+          &maybe_paths.unwrap(),
+          &exclude_set,
+        );
       }
     };
     let operation_future = error_handler(operation(
@@ -337,7 +364,7 @@ where
         continue;
       },
       success = operation_future => {
-        consume_paths_to_watch(&mut watcher, &mut paths_to_watch_rx, &exclude_set);
+        consume_paths_to_watch(watcher.as_mut(), &mut paths_to_watch_rx, &exclude_set);
         // TODO(bartlomieju): print exit code here?
         info!(
           "{} {} {}. Restarting on file change...",
@@ -354,7 +381,11 @@ where
     let receiver_future = async {
       loop {
         let maybe_paths = paths_to_watch_rx.recv().await;
-        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap(), &exclude_set);
+        add_paths_to_watcher(
+          watcher.as_mut(),
+          &maybe_paths.unwrap(),
+          &exclude_set,
+        );
       }
     };
 
@@ -373,34 +404,46 @@ where
 
 fn new_watcher(
   sender: Arc<mpsc::UnboundedSender<Vec<PathBuf>>>,
-) -> Result<RecommendedWatcher, AnyError> {
-  Ok(Watcher::new(
-    move |res: Result<NotifyEvent, NotifyError>| {
-      let Ok(event) = res else {
-        return;
-      };
-
-      if !matches!(
-        event.kind,
-        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
-      ) {
-        return;
-      }
+  backend: FileWatcherBackend,
+  ignore_set: Arc<PathOrPatternSet>,
+) -> Result<Box<dyn Watcher + Send>, AnyError> {
+  let event_handler = move |res: Result<NotifyEvent, NotifyError>| {
+    let Ok(event) = res else {
+      return;
+    };
+
+    if !matches!(
+      event.kind,
+      EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+      return;
+    }
 
-      let paths = event
-        .paths
-        .iter()
-        .filter_map(|path| canonicalize_path(path).ok())
-        .collect();
+    let paths = event
+      .paths
+      .iter()
+      .filter_map(|path| canonicalize_path(path).ok())
+      .collect::<Vec<_>>();
+    if !paths.is_empty() && paths.iter().all(|p| ignore_set.matches_path(p)) {
+      return;
+    }
 
-      sender.send(paths).unwrap();
-    },
-    Default::default(),
-  )?)
+    // the receiver is gone when a `FileWatcher` is dropped
+    let _ = sender.send(paths);
+  };
+  Ok(match backend {
+    FileWatcherBackend::Native => {
+      Box::new(RecommendedWatcher::new(event_handler, Default::default())?)
+    }
+    FileWatcherBackend::Poll(interval) => Box::new(PollWatcher::new(
+      event_handler,
+      NotifyConfig::default().with_poll_interval(interval),
+    )?),
+  })
 }
 
 fn add_paths_to_watcher(
-  watcher: &mut RecommendedWatcher,
+  watcher: &mut dyn Watcher,
   paths: &[PathBuf],
   paths_to_exclude: &PathOrPatternSet,
 ) {
@@ -419,7 +462,7 @@ fn add_paths_to_watcher(
 }
 
 fn consume_paths_to_watch(
-  watcher: &mut RecommendedWatcher,
+  watcher: &mut dyn Watcher,
   receiver: &mut UnboundedReceiver<Vec<PathBuf>>,
   exclude_set: &PathOrPatternSet,
 ) {
@@ -438,3 +481,174 @@ fn consume_paths_to_watch(
     }
   }
 }
+
+/// How a [`FileWatcher`] finds out about changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileWatcherBackend {
+  /// The notification API of the operating system, eg. inotify or FSEvents.
+  #[default]
+  Native,
+  /// Scans the watched paths at the given interval. Works on file systems
+  /// without notifications, like network drives and some containers.
+  Poll(Duration),
+}
+
+/// Builds a [`FileWatcher`], the watcher `--watch` uses without its restart
+/// logic.
+#[derive(Clone, Debug)]
+pub struct FileWatcherBuilder {
+  paths: Vec<PathBuf>,
+  debounce: Duration,
+  ignore: Vec<String>,
+  backend: FileWatcherBackend,
+}
+
+impl Default for FileWatcherBuilder {
+  fn default() -> Self {
+    Self {
+      paths: Vec::new(),
+      debounce: DEBOUNCE_INTERVAL,
+      ignore: Vec::new(),
+      backend: FileWatcherBackend::default(),
+    }
+  }
+}
+
+impl FileWatcherBuilder {
+  /// Watches `path` and, for directories, everything below it.
+  pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+    self.paths.push(path.into());
+    self
+  }
+
+  pub fn paths(
+    mut self,
+    paths: impl IntoIterator<Item = impl Into<PathBuf>>,
+  ) -> Self {
+    self.paths.extend(paths.into_iter().map(Into::into));
+    self
+  }
+
+  /// How long to wait for more changes before reporting them together.
+  /// Defaults to 200ms.
+  pub fn debounce(mut self, debounce: Duration) -> Self {
+    self.debounce = debounce;
+    self
+  }
+
+  /// Ignores changes to paths matching `pattern`, a path or glob relative
+  /// to the current directory, the same as `--watch-exclude`.
+  pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+    self.ignore.push(pattern.into());
+    self
+  }
+
+  pub fn backend(mut self, backend: FileWatcherBackend) -> Self {
+    self.backend = backend;
+    self
+  }
+
+  pub fn build(self) -> Result<FileWatcher, AnyError> {
+    let ignore_set = if self.ignore.is_empty() {
+      PathOrPatternSet::default()
+    } else {
+      let cwd = std::env::current_dir()?;
+      PathOrPatternSet::from_exclude_relative_path_or_patterns(
+        &cwd,
+        &self.ignore,
+      )
+      .context("Failed resolving watch ignore patterns.")?
+    };
+    let ignore_set = Arc::new(ignore_set);
+    let (sender, receiver) = DebouncedReceiver::new_with_sender(self.debounce);
+    let watcher = new_watcher(sender, self.backend, ignore_set.clone())?;
+    let mut file_watcher = FileWatcher {
+      watcher,
+      ignore_set,
+      receiver,
+    };
+    for path in &self.paths {
+      file_watcher.watch(path)?;
+    }
+    Ok(file_watcher)
+  }
+}
+
+/// Watches files for changes. Changes that happen in quick succession are
+/// reported together, with canonicalized paths.
+pub struct FileWatcher {
+  watcher: Box<dyn Watcher + Send>,
+  ignore_set: Arc<PathOrPatternSet>,
+  receiver: DebouncedReceiver,
+}
+
+impl FileWatcher {
+  pub fn builder() -> FileWatcherBuilder {
+    FileWatcherBuilder::default()
+  }
+
+  /// Starts watching another path. Ignored paths are skipped.
+  pub fn watch(&mut self, path: &Path) -> Result<(), AnyError> {
+    if self.ignore_set.matches_path(path) {
+      return Ok(());
+    }
+    self
+      .watcher
+      .watch(path, RecursiveMode::Recursive)
+      .with_context(|| format!("Failed watching '{}'.", path.display()))
+  }
+
+  pub fn unwatch(&mut self, path: &Path) -> Result<(), AnyError> {
+    self
+      .watcher
+      .unwatch(path)
+      .with_context(|| format!("Failed unwatching '{}'.", path.display()))
+  }
+
+  /// Waits for the next batch of changed paths. It's safe to use in
+  /// `select!`, changes are kept when the future is dropped.
+  pub async fn next_change(&mut self) -> Option<Vec<PathBuf>> {
+    self.receiver.recv().await
+  }
+
+  /// The batches of changed paths as a stream.
+  pub fn into_stream(self) -> impl Stream<Item = Vec<PathBuf>> + Send {
+    stream::unfold(self, |mut watcher| async move {
+      let paths = watcher.next_change().await?;
+      Some((paths, watcher))
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_util::TempDir;
+
+  #[tokio::test]
+  async fn file_watcher_reports_changes() {
+    let temp_dir = TempDir::new();
+    let watched = temp_dir.path().join("watched.txt");
+    let ignored = temp_dir.path().join("ignored.txt");
+    watched.write("a");
+    ignored.write("a");
+    let mut watcher = FileWatcher::builder()
+      .path(temp_dir.path().to_path_buf())
+      .ignore(ignored.to_string_lossy())
+      .debounce(Duration::from_millis(50))
+      .backend(FileWatcherBackend::Poll(Duration::from_millis(50)))
+      .build()
+      .unwrap();
+
+    // let the first scan finish
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    ignored.write("bb");
+    watched.write("bb");
+    let paths =
+      tokio::time::timeout(Duration::from_secs(10), watcher.next_change())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(paths, vec![canonicalize_path(watched.as_path()).unwrap()]);
+  }
+}
@@ -1,9 +1,69 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::io::Write;
+use std::sync::Arc;
+use std::sync::RwLock;
 
 use super::draw_thread::DrawThread;
 
+static LOG_SINK: RwLock<Option<Arc<dyn LogSink>>> = RwLock::new(None);
+
+/// A log record of the runtime or the CLI.
+#[derive(Debug, Clone)]
+pub struct LogEvent {
+  pub level: log::Level,
+  /// The module that logged the record, eg. `deno::npm`.
+  pub target: String,
+  pub message: String,
+  /// Key-value pairs of the record, eg. from `log::info!(path; "...")`.
+  pub fields: Vec<(String, String)>,
+}
+
+/// Receives the log output of the runtime and the CLI instead of stderr,
+/// eg. to forward it to the logging system of an embedder.
+pub trait LogSink: Send + Sync {
+  fn log(&self, event: LogEvent);
+}
+
+/// Routes the log output of the process to `sink`, or back to stderr when
+/// `None`. The level is still configured with `DENO_LOG` or the log level
+/// of the first runtime.
+pub fn set_log_sink(sink: Option<Arc<dyn LogSink>>) {
+  *LOG_SINK.write().unwrap() = sink;
+}
+
+fn current_log_sink() -> Option<Arc<dyn LogSink>> {
+  LOG_SINK.read().unwrap().clone()
+}
+
+#[derive(Default)]
+struct LogFields(Vec<(String, String)>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for LogFields {
+  fn visit_pair(
+    &mut self,
+    key: log::kv::Key<'kvs>,
+    value: log::kv::Value<'kvs>,
+  ) -> Result<(), log::kv::Error> {
+    self.0.push((key.to_string(), value.to_string()));
+    Ok(())
+  }
+}
+
+impl LogEvent {
+  fn from_record(record: &log::Record) -> Self {
+    let mut fields = LogFields::default();
+    // collecting into a Vec never fails
+    let _ = record.key_values().visit(&mut fields);
+    Self {
+      level: record.level(),
+      target: record.target().to_string(),
+      message: record.args().to_string(),
+      fields: fields.0,
+    }
+  }
+}
+
 struct CliLogger(env_logger::Logger);
 
 impl CliLogger {
@@ -23,6 +83,11 @@ impl log::Log for CliLogger {
 
   fn log(&self, record: &log::Record) {
     if self.enabled(record.metadata()) {
+      if let Some(sink) = current_log_sink() {
+        sink.log(LogEvent::from_record(record));
+        deno_telemetry::handle_log(record);
+        return;
+      }
       // it was considered to hold the draw thread's internal lock
       // across logging, but if outputting to stderr blocks then that
       // could potentially block other threads that access the draw
@@ -101,3 +166,28 @@ pub fn init(maybe_level: Option<log::Level>) {
   }
   r.expect("Could not install logger.");
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn log_event_from_record() {
+    let fields = ("specifier", "npm:chalk");
+    let event = LogEvent::from_record(
+      &log::Record::builder()
+        .level(log::Level::Warn)
+        .target("deno::npm")
+        .args(format_args!("Failed resolving {}", "chalk"))
+        .key_values(&fields)
+        .build(),
+    );
+    assert_eq!(event.level, log::Level::Warn);
+    assert_eq!(event.target, "deno::npm");
+    assert_eq!(event.message, "Failed resolving chalk");
+    assert_eq!(
+      event.fields,
+      vec![("specifier".to_string(), "npm:chalk".to_string())]
+    );
+  }
+}
@@ -4,6 +4,7 @@
 pub mod archive;
 pub mod checksum;
 pub mod console;
+pub mod cpu_time;
 pub mod diff;
 pub mod display;
 pub mod draw_thread;
@@ -15,6 +16,7 @@ pub mod path;
 pub mod progress_bar;
 pub mod result;
 pub mod retry;
+pub mod stdio;
 pub mod sync;
 pub mod text_encoding;
 pub mod unix;
@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Instant;
@@ -23,7 +24,7 @@ mod renderer;
 // Inspired by Indicatif, but this custom implementation allows
 // for more control over what's going on under the hood.
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProgressMessagePrompt {
   Download,
   Blocking,
@@ -44,9 +45,75 @@ impl ProgressMessagePrompt {
   }
 }
 
+/// The progress of a download or another task, see [`ProgressReporter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+  /// A task started. `message` is the url of a module or npm package, or
+  /// the name of the package being initialized.
+  Start {
+    id: usize,
+    prompt: ProgressMessagePrompt,
+    message: String,
+  },
+  /// `position` bytes of the task are done, out of `total_size` once it's
+  /// known.
+  Update {
+    id: usize,
+    position: u64,
+    total_size: Option<u64>,
+  },
+  /// The task finished, successfully or not.
+  Finish { id: usize },
+}
+
+/// Receives the progress of module and npm package downloads, instead of
+/// drawing progress bars to the terminal.
+pub trait ProgressReporter: Send + Sync {
+  fn on_progress(&self, event: &ProgressEvent);
+}
+
+impl<F: Fn(&ProgressEvent) + Send + Sync> ProgressReporter for F {
+  fn on_progress(&self, event: &ProgressEvent) {
+    self(event)
+  }
+}
+
+struct Reporter {
+  reporter: Arc<dyn ProgressReporter>,
+  next_id: AtomicUsize,
+}
+
+impl std::fmt::Debug for Reporter {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Reporter")
+      .field("next_id", &self.next_id)
+      .finish()
+  }
+}
+
+#[derive(Debug)]
+struct ReportedEntry {
+  id: usize,
+  reporter: Arc<Reporter>,
+  pos: AtomicU64,
+  total_size: AtomicU64,
+}
+
+impl ReportedEntry {
+  fn report_update(&self) {
+    let total_size = self.total_size.load(Ordering::Relaxed);
+    self.reporter.reporter.on_progress(&ProgressEvent::Update {
+      id: self.id,
+      position: self.pos.load(Ordering::Relaxed),
+      total_size: (total_size > 0).then_some(total_size),
+    });
+  }
+}
+
 #[derive(Debug)]
 pub struct UpdateGuard {
   maybe_entry: Option<Arc<ProgressBarEntry>>,
+  maybe_reported: Option<ReportedEntry>,
 }
 
 impl Drop for UpdateGuard {
@@ -54,6 +121,12 @@ impl Drop for UpdateGuard {
     if let Some(entry) = &self.maybe_entry {
       entry.finish();
     }
+    if let Some(entry) = &self.maybe_reported {
+      entry
+        .reporter
+        .reporter
+        .on_progress(&ProgressEvent::Finish { id: entry.id });
+    }
   }
 }
 
@@ -62,12 +135,20 @@ impl UpdateGuard {
     if let Some(entry) = &self.maybe_entry {
       entry.set_position(value);
     }
+    if let Some(entry) = &self.maybe_reported {
+      entry.pos.store(value, Ordering::Relaxed);
+      entry.report_update();
+    }
   }
 
   pub fn set_total_size(&self, value: u64) {
     if let Some(entry) = &self.maybe_entry {
       entry.set_total_size(value);
     }
+    if let Some(entry) = &self.maybe_reported {
+      entry.total_size.store(value, Ordering::Relaxed);
+      entry.report_update();
+    }
   }
 }
 
@@ -266,6 +347,7 @@ impl DrawThreadRenderer for ProgressBarInner {
 #[derive(Clone, Debug)]
 pub struct ProgressBar {
   inner: ProgressBarInner,
+  maybe_reporter: Option<Arc<Reporter>>,
 }
 
 impl ProgressBar {
@@ -291,6 +373,20 @@ impl ProgressBar {
           Arc::new(renderer::TextOnlyProgressBarRenderer::default())
         }
       }),
+      maybe_reporter: None,
+    }
+  }
+
+  /// Sends the progress to `reporter` instead of drawing it.
+  pub fn with_reporter(reporter: Arc<dyn ProgressReporter>) -> Self {
+    Self {
+      inner: ProgressBarInner::new(Arc::new(
+        renderer::TextOnlyProgressBarRenderer::default(),
+      )),
+      maybe_reporter: Some(Arc::new(Reporter {
+        reporter,
+        next_id: Default::default(),
+      })),
     }
   }
 
@@ -303,19 +399,40 @@ impl ProgressBar {
     kind: ProgressMessagePrompt,
     msg: &str,
   ) -> UpdateGuard {
+    if let Some(reporter) = &self.maybe_reporter {
+      let id = reporter.next_id.fetch_add(1, Ordering::Relaxed);
+      reporter.reporter.on_progress(&ProgressEvent::Start {
+        id,
+        prompt: kind,
+        message: msg.to_string(),
+      });
+      return UpdateGuard {
+        maybe_entry: None,
+        maybe_reported: Some(ReportedEntry {
+          id,
+          reporter: reporter.clone(),
+          pos: Default::default(),
+          total_size: Default::default(),
+        }),
+      };
+    }
     // only check if progress bars are supported once we go
     // to update so that we lazily initialize the progress bar
     if ProgressBar::are_supported() {
       let entry = self.inner.add_entry(kind, msg.to_string());
       UpdateGuard {
         maybe_entry: Some(entry),
+        maybe_reported: None,
       }
     } else {
       // if we're not running in TTY, fallback to using logger crate
       if !msg.is_empty() {
         log::log!(log::Level::Info, "{} {}", kind.as_text(), msg);
       }
-      UpdateGuard { maybe_entry: None }
+      UpdateGuard {
+        maybe_entry: None,
+        maybe_reported: None,
+      }
     }
   }
 
@@ -338,3 +455,52 @@ impl Drop for ClearGuard {
     self.pb.decrement_clear();
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn reports_progress() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let progress_bar = ProgressBar::with_reporter(Arc::new({
+      let events = events.clone();
+      move |event: &ProgressEvent| events.lock().push(event.clone())
+    }));
+    let guard = progress_bar.update("https://deno.land/x/mod.ts");
+    guard.set_total_size(10);
+    guard.set_position(4);
+    drop(guard);
+    drop(
+      progress_bar
+        .update_with_prompt(ProgressMessagePrompt::Initialize, "chalk@5.0.0"),
+    );
+    assert_eq!(
+      *events.lock(),
+      vec![
+        ProgressEvent::Start {
+          id: 0,
+          prompt: ProgressMessagePrompt::Download,
+          message: "https://deno.land/x/mod.ts".to_string(),
+        },
+        ProgressEvent::Update {
+          id: 0,
+          position: 0,
+          total_size: Some(10),
+        },
+        ProgressEvent::Update {
+          id: 0,
+          position: 4,
+          total_size: Some(10),
+        },
+        ProgressEvent::Finish { id: 0 },
+        ProgressEvent::Start {
+          id: 1,
+          prompt: ProgressMessagePrompt::Initialize,
+          message: "chalk@5.0.0".to_string(),
+        },
+        ProgressEvent::Finish { id: 1 },
+      ]
+    );
+  }
+}
@@ -0,0 +1,213 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::io::Read;
+use std::io::Write;
+
+use deno_runtime::deno_io::pipe;
+use deno_runtime::deno_io::PipeRead;
+use deno_runtime::deno_io::PipeWrite;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Creates a pipe whose read end is drained into `writer` on a background
+/// thread. The returned write end can be used as the stdout or stderr of a
+/// worker. The thread exits once all handles to the write end are closed.
+pub fn pipe_to_writer(
+  mut writer: Box<dyn Write + Send>,
+) -> std::io::Result<PipeWrite> {
+  let (mut reader, pipe_writer) = pipe()?;
+  std::thread::spawn(move || {
+    let mut buf = [0; 4096];
+    loop {
+      match reader.read(&mut buf) {
+        Ok(0) => break,
+        Ok(n) => {
+          // flush after every chunk so the output shows up while the
+          // script is still running
+          if writer.write_all(&buf[..n]).is_err() || writer.flush().is_err() {
+            break;
+          }
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+        Err(_) => break,
+      }
+    }
+  });
+  Ok(pipe_writer)
+}
+
+/// Like [`pipe_to_writer`], but for a writer driven by the Tokio runtime of
+/// the caller. The chunks are passed from the background thread to a task
+/// of that runtime, instead of blocking on it, so this works with
+/// current-thread runtimes too.
+pub fn pipe_to_async_writer(
+  mut writer: Box<dyn AsyncWrite + Send + Unpin>,
+) -> std::io::Result<PipeWrite> {
+  let (mut reader, pipe_writer) = pipe()?;
+  let (tx, mut rx) = unbounded_channel::<Vec<u8>>();
+  Handle::current().spawn(async move {
+    while let Some(chunk) = rx.recv().await {
+      if writer.write_all(&chunk).await.is_err()
+        || writer.flush().await.is_err()
+      {
+        return;
+      }
+    }
+    let _ = writer.shutdown().await;
+  });
+  std::thread::spawn(move || {
+    let mut buf = [0; 4096];
+    loop {
+      match reader.read(&mut buf) {
+        Ok(0) => break,
+        Ok(n) => {
+          // fails once the writer failed
+          if tx.send(buf[..n].to_vec()).is_err() {
+            break;
+          }
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+        Err(_) => break,
+      }
+    }
+  });
+  Ok(pipe_writer)
+}
+
+/// Creates a pipe whose write end is fed from `reader` on a background
+/// thread. `reader` is read by a task of the Tokio runtime of the caller,
+/// which can be a current-thread runtime. The returned read end can be used
+/// as the stdin of a worker, which sees EOF once `reader` ends.
+pub fn pipe_from_async_reader(
+  mut reader: Box<dyn AsyncRead + Send + Unpin>,
+) -> std::io::Result<PipeRead> {
+  let (pipe_reader, mut writer) = pipe()?;
+  let (tx, mut rx) = unbounded_channel::<Vec<u8>>();
+  Handle::current().spawn(async move {
+    let mut buf = [0; 4096];
+    loop {
+      match reader.read(&mut buf).await {
+        Ok(0) => break,
+        Ok(n) => {
+          // fails once the worker closed its stdin
+          if tx.send(buf[..n].to_vec()).is_err() {
+            break;
+          }
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+        Err(_) => break,
+      }
+    }
+  });
+  std::thread::spawn(move || {
+    while let Some(chunk) = rx.blocking_recv() {
+      if writer.write_all(&chunk).is_err() {
+        break;
+      }
+    }
+  });
+  Ok(pipe_reader)
+}
+
+/// A [`Write`] implementation that sends every written chunk over a channel.
+pub struct ChannelWriter(UnboundedSender<Vec<u8>>);
+
+impl ChannelWriter {
+  pub fn new(sender: UnboundedSender<Vec<u8>>) -> Self {
+    Self(sender)
+  }
+}
+
+impl Write for ChannelWriter {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self.0.send(buf.to_vec()).map_err(|_| {
+      std::io::Error::new(
+        std::io::ErrorKind::BrokenPipe,
+        "the output receiver was dropped",
+      )
+    })?;
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn pipe_to_channel_writer() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut pipe_writer =
+      pipe_to_writer(Box::new(ChannelWriter::new(tx))).unwrap();
+    pipe_writer.write_all(b"hello ").unwrap();
+    pipe_writer.write_all(b"world").unwrap();
+    drop(pipe_writer);
+
+    let mut output = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+      output.extend(chunk);
+    }
+    assert_eq!(output, b"hello world");
+  }
+
+  #[tokio::test]
+  async fn pipe_async_writer_on_current_thread() {
+    let (output_writer, mut output) = tokio::io::duplex(4);
+    let mut pipe_writer =
+      pipe_to_async_writer(Box::new(output_writer)).unwrap();
+    // larger than the duplex buffer, so the writer has to wait for this
+    // thread to read
+    pipe_writer.write_all(b"hello world").unwrap();
+    drop(pipe_writer);
+    let mut received = Vec::new();
+    output.read_to_end(&mut received).await.unwrap();
+    assert_eq!(received, b"hello world");
+
+    let (input, mut input_writer) = tokio::io::duplex(4);
+    let mut pipe_reader = pipe_from_async_reader(Box::new(input)).unwrap();
+    input_writer.write_all(b"ping").await.unwrap();
+    drop(input_writer);
+    let mut input = Vec::new();
+    tokio::task::spawn_blocking(move || {
+      pipe_reader.read_to_end(&mut input).unwrap();
+      input
+    })
+    .await
+    .map(|input| assert_eq!(input, b"ping"))
+    .unwrap();
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn pipe_between_async_reader_and_writer() {
+    let (input, mut input_writer) = tokio::io::duplex(64);
+    let mut pipe_reader = pipe_from_async_reader(Box::new(input)).unwrap();
+    input_writer.write_all(b"ping").await.unwrap();
+    drop(input_writer);
+    let input = tokio::task::spawn_blocking(move || {
+      let mut input = Vec::new();
+      pipe_reader.read_to_end(&mut input).unwrap();
+      input
+    })
+    .await
+    .unwrap();
+    assert_eq!(input, b"ping");
+
+    let (output_writer, mut output) = tokio::io::duplex(64);
+    let mut pipe_writer =
+      pipe_to_async_writer(Box::new(output_writer)).unwrap();
+    pipe_writer.write_all(b"pong").unwrap();
+    drop(pipe_writer);
+    let mut received = Vec::new();
+    output.read_to_end(&mut received).await.unwrap();
+    assert_eq!(received, b"pong");
+  }
+}
@@ -0,0 +1,438 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Runs WebAssembly components of the `wasi:cli/command` world, which V8
+//! can't instantiate, on wasmtime with a WASI preview 2 context.
+//!
+//! When enabled, the module graph loads a component as a JavaScript module
+//! exporting `run()`, which runs the component and resolves to its exit
+//! code:
+//!
+//! ```ts
+//! import run from "./tool.wasm";
+//! const code = await run();
+//! ```
+//!
+//! Components use the stdio of the worker that runs them. Their preopened
+//! directories, environment variables and sockets are checked against the
+//! permissions of the worker each time a component runs.
+//!
+//! The machine code of components is stored in the module cache of the
+//! worker, if it has one, so they are only compiled once.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op2;
+use deno_core::parking_lot::Mutex;
+use deno_core::Extension;
+use deno_core::ModuleSpecifier;
+use deno_core::OpState;
+use deno_core::ResourceId;
+use deno_runtime::deno_io::fs::FileResource;
+use deno_runtime::deno_permissions::PermissionsContainer;
+use deno_runtime::ops::os::VirtualEnv;
+use wasmtime::component::Component;
+use wasmtime::component::Linker;
+use wasmtime::Engine;
+use wasmtime::Store;
+use wasmtime_wasi::bindings::sync::Command;
+use wasmtime_wasi::pipe::AsyncReadStream;
+use wasmtime_wasi::pipe::ClosedInputStream;
+use wasmtime_wasi::pipe::SinkOutputStream;
+use wasmtime_wasi::AsyncStdinStream;
+use wasmtime_wasi::DirPerms;
+use wasmtime_wasi::FilePerms;
+use wasmtime_wasi::I32Exit;
+use wasmtime_wasi::OutputFile;
+use wasmtime_wasi::ResourceTable;
+use wasmtime_wasi::WasiCtx;
+use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi::WasiView;
+
+use crate::cache::ModuleCache;
+use crate::cache::ModuleCacheKey;
+use crate::cache::WasmComponentLoader;
+
+const API_NAME: &str = "WASI component";
+
+deno_core::extension!(deno_wasi,
+  ops = [op_wasi_run_component],
+  options = {
+    components: Arc<WasiComponents>,
+  },
+  state = |state, options| {
+    state.put(options.components);
+  },
+);
+
+/// The WASI context components run with. Components read and write the
+/// stdio of the worker, and may use the sockets its `net` permission
+/// allows.
+#[derive(Debug, Clone, Default)]
+pub struct WasiOptions {
+  /// Directories of the host, which must not be set along with a custom
+  /// file system for the worker.
+  pub preopened_dirs: Vec<WasiPreopenedDir>,
+  /// Environment variables, each checked with the `env` permission.
+  pub env: Vec<(String, String)>,
+  /// Passes the environment of the worker, its virtual one if it has any,
+  /// to components. Requires the `env` permission for all variables.
+  pub inherit_env: bool,
+  pub args: Vec<String>,
+}
+
+/// A directory of the host a component can access, checked with the `read`
+/// permission, and the `write` one when `writable`.
+#[derive(Debug, Clone)]
+pub struct WasiPreopenedDir {
+  pub host_path: PathBuf,
+  /// Path of the directory inside the component, eg. `"/data"`.
+  pub guest_path: String,
+  pub writable: bool,
+}
+
+/// Components found while loading module graphs, shared by the graph loader
+/// and the workers of a factory.
+pub struct WasiComponents {
+  options: WasiOptions,
+  engine: Engine,
+  module_cache: Option<Arc<ModuleCache>>,
+  components: Mutex<HashMap<ModuleSpecifier, Arc<[u8]>>>,
+  compiled: Mutex<HashMap<ModuleSpecifier, Component>>,
+}
+
+impl WasiComponents {
+  pub fn new(
+    options: WasiOptions,
+    module_cache: Option<Arc<ModuleCache>>,
+  ) -> Self {
+    Self {
+      options,
+      engine: create_engine(),
+      module_cache,
+      components: Default::default(),
+      compiled: Default::default(),
+    }
+  }
+
+  fn component(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<Component, AnyError> {
+    if let Some(component) = self.compiled.lock().get(specifier) {
+      return Ok(component.clone());
+    }
+    let Some(source) = self.components.lock().get(specifier).cloned() else {
+      return Err(type_error(format!(
+        "Module is not a WASI component: {specifier}"
+      )));
+    };
+    let (component, _) =
+      compile_component(&self.engine, self.module_cache.as_deref(), &source)?;
+    self
+      .compiled
+      .lock()
+      .insert(specifier.clone(), component.clone());
+    Ok(component)
+  }
+
+  fn create_ctx(&self, state: &mut OpState) -> Result<WasiCtx, AnyError> {
+    let mut builder = WasiCtxBuilder::new();
+    builder.args(&self.options.args);
+    match clone_stdio_file(state, 0) {
+      Some(file) => builder.stdin(AsyncStdinStream::new(AsyncReadStream::new(
+        tokio::fs::File::from_std(file),
+      ))),
+      None => builder.stdin(ClosedInputStream),
+    };
+    match clone_stdio_file(state, 1) {
+      Some(file) => builder.stdout(OutputFile::new(file)),
+      None => builder.stdout(SinkOutputStream),
+    };
+    match clone_stdio_file(state, 2) {
+      Some(file) => builder.stderr(OutputFile::new(file)),
+      None => builder.stderr(SinkOutputStream),
+    };
+    let inherited_env: Vec<(String, String)> = if self.options.inherit_env {
+      match state.try_borrow::<VirtualEnv>() {
+        Some(env) => env.vars().into_iter().collect(),
+        None => std::env::vars().collect(),
+      }
+    } else {
+      Vec::new()
+    };
+
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    if self.options.inherit_env {
+      permissions.check_env_all()?;
+      builder.envs(&inherited_env[..]);
+    }
+    for (key, value) in &self.options.env {
+      permissions.check_env(key)?;
+      builder.env(key, value);
+    }
+    let socket_permissions = permissions.clone();
+    builder.socket_addr_check(move |addr, _| {
+      let host = (addr.ip().to_string(), Some(addr.port()));
+      let allowed = socket_permissions
+        .clone()
+        .check_net(&host, API_NAME)
+        .is_ok();
+      Box::pin(std::future::ready(allowed))
+    });
+    for dir in &self.options.preopened_dirs {
+      permissions.check_read_path(&dir.host_path, Some(API_NAME))?;
+      let (dir_perms, file_perms) = if dir.writable {
+        permissions.check_write_path(&dir.host_path, API_NAME)?;
+        (DirPerms::all(), FilePerms::all())
+      } else {
+        (DirPerms::READ, FilePerms::READ)
+      };
+      builder.preopened_dir(
+        &dir.host_path,
+        &dir.guest_path,
+        dir_perms,
+        file_perms,
+      )?;
+    }
+    Ok(builder.build())
+  }
+}
+
+impl WasmComponentLoader for WasiComponents {
+  /// Returns the source of the JavaScript module that replaces `source` in
+  /// the module graph when it's a component, and remembers the component.
+  fn load_component(
+    &self,
+    specifier: &ModuleSpecifier,
+    source: &Arc<[u8]>,
+  ) -> Option<Arc<[u8]>> {
+    if !is_wasm_component(source) {
+      return None;
+    }
+    let mut components = self.components.lock();
+    if components.get(specifier) != Some(source) {
+      self.compiled.lock().remove(specifier);
+      components.insert(specifier.clone(), source.clone());
+    }
+    Some(COMPONENT_MODULE_SOURCE.as_bytes().into())
+  }
+}
+
+/// Duplicates the file behind a stdio resource of the worker, which may be
+/// closed while the component runs. `None` when the resource is closed.
+fn clone_stdio_file(state: &OpState, rid: ResourceId) -> Option<std::fs::File> {
+  let handle = FileResource::get_file(state, rid).ok()?.backing_fd()?;
+  #[cfg(unix)]
+  let owned = {
+    // SAFETY: the resource keeps the handle open while it's duplicated
+    unsafe { std::os::fd::BorrowedFd::borrow_raw(handle) }.try_clone_to_owned()
+  };
+  #[cfg(windows)]
+  let owned = {
+    // SAFETY: the resource keeps the handle open while it's duplicated
+    unsafe { std::os::windows::io::BorrowedHandle::borrow_raw(handle) }
+      .try_clone_to_owned()
+  };
+  owned.ok().map(std::fs::File::from)
+}
+
+/// The module a component is loaded as. `run` is defined by
+/// `js/40_wasi.js`, which only main workers load.
+const COMPONENT_MODULE_SOURCE: &str = r#"const runComponent =
+  globalThis[Symbol.for("Deno.runWasiComponent")];
+if (runComponent === undefined) {
+  throw new TypeError("WASI components can only be run by main workers.");
+}
+export default function run() {
+  return runComponent(import.meta.url);
+}
+export { run };
+"#;
+
+/// The engine components are compiled and run with.
+pub fn create_engine() -> Engine {
+  Engine::default()
+}
+
+/// Compiles a component, or loads its machine code from `module_cache` if
+/// it was compiled before. Returns whether the machine code was cached.
+pub fn compile_component(
+  engine: &Engine,
+  module_cache: Option<&ModuleCache>,
+  source: &[u8],
+) -> Result<(Component, bool), AnyError> {
+  let Some(module_cache) = module_cache else {
+    return Ok((Component::new(engine, source)?, false));
+  };
+  let key =
+    ModuleCacheKey::wasm(source, engine.precompile_compatibility_hash());
+  if let Some(code) = module_cache.get_wasm_component(&key) {
+    // SAFETY: the entries of the key are only written below, with the
+    // machine code wasmtime serialized with the settings of `engine`, which
+    // are part of the key. Deserializing checks the settings again.
+    match unsafe { Component::deserialize(engine, &code) } {
+      Ok(component) => return Ok((component, true)),
+      Err(err) => {
+        log::debug!("Error loading compiled WebAssembly component: {err:#}");
+      }
+    }
+  }
+  let component = Component::new(engine, source)?;
+  module_cache.set_wasm_component(&key, &component.serialize()?);
+  Ok((component, false))
+}
+
+/// Components start with the same magic number as core modules, followed
+/// by their own version and a layer of 1 where core modules have 0.
+pub fn is_wasm_component(bytes: &[u8]) -> bool {
+  bytes.starts_with(b"\0asm") && bytes.get(6..8) == Some(&[0x01, 0x00])
+}
+
+/// Creates the extension of main workers that runs the components loaded
+/// through `components`, used with `js/40_wasi.js`.
+pub fn create_wasi_extension(components: Arc<WasiComponents>) -> Extension {
+  deno_wasi::init_ops(components)
+}
+
+struct WasiState {
+  ctx: WasiCtx,
+  table: ResourceTable,
+}
+
+impl WasiView for WasiState {
+  fn table(&mut self) -> &mut ResourceTable {
+    &mut self.table
+  }
+
+  fn ctx(&mut self) -> &mut WasiCtx {
+    &mut self.ctx
+  }
+}
+
+#[op2(async)]
+#[smi]
+async fn op_wasi_run_component(
+  state: Rc<RefCell<OpState>>,
+  #[string] specifier: String,
+) -> Result<i32, AnyError> {
+  let specifier = ModuleSpecifier::parse(&specifier)?;
+  let (components, ctx) = {
+    let mut state = state.borrow_mut();
+    let components = state.borrow::<Arc<WasiComponents>>().clone();
+    let ctx = components.create_ctx(&mut state)?;
+    (components, ctx)
+  };
+  let component = components.component(&specifier)?;
+  deno_core::unsync::spawn_blocking(move || {
+    run_component(&components.engine, &component, ctx)
+  })
+  .await?
+}
+
+/// Runs the `wasi:cli/run` export of a component, returning its exit code.
+fn run_component(
+  engine: &Engine,
+  component: &Component,
+  ctx: WasiCtx,
+) -> Result<i32, AnyError> {
+  let mut store = Store::new(
+    engine,
+    WasiState {
+      ctx,
+      table: ResourceTable::new(),
+    },
+  );
+  let mut linker = Linker::new(engine);
+  wasmtime_wasi::add_to_linker_sync(&mut linker)?;
+  let command = Command::instantiate(&mut store, component, &linker)?;
+  match command.wasi_cli_run().call_run(&mut store) {
+    Ok(Ok(())) => Ok(0),
+    Ok(Err(())) => Ok(1),
+    Err(err) => match err.downcast_ref::<I32Exit>() {
+      Some(exit) => Ok(exit.0),
+      None => Err(err),
+    },
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn wasm_component_detection() {
+    let core_module = b"\0asm\x01\x00\x00\x00";
+    let component = b"\0asm\x0d\x00\x01\x00";
+    assert!(!is_wasm_component(core_module));
+    assert!(is_wasm_component(component));
+    assert!(!is_wasm_component(b"\0asm"));
+  }
+
+  #[test]
+  fn loads_components_as_js_modules() {
+    let components = WasiComponents::new(Default::default(), None);
+    let specifier = ModuleSpecifier::parse("file:///tool.wasm").unwrap();
+    let core_module: Arc<[u8]> = b"\0asm\x01\x00\x00\x00".as_slice().into();
+    assert!(components
+      .load_component(&specifier, &core_module)
+      .is_none());
+    let component: Arc<[u8]> = b"\0asm\x0d\x00\x01\x00".as_slice().into();
+    let module = components.load_component(&specifier, &component).unwrap();
+    assert!(std::str::from_utf8(&module)
+      .unwrap()
+      .contains("import.meta.url"));
+    assert!(components.components.lock().contains_key(&specifier));
+  }
+
+  #[test]
+  fn runs_components_with_the_given_stdio() {
+    let source = std::fs::read(
+      test_util::testdata_path().join("wasm/hello_component.wasm"),
+    )
+    .unwrap();
+    let components = WasiComponents::new(Default::default(), None);
+    let component = Component::new(&components.engine, source).unwrap();
+    let stdout = wasmtime_wasi::pipe::MemoryOutputPipe::new(1024);
+    let ctx = WasiCtxBuilder::new().stdout(stdout.clone()).build();
+    let code = run_component(&components.engine, &component, ctx).unwrap();
+    assert_eq!(code, 0);
+    assert_eq!(&stdout.contents()[..], b"hello from a component\n");
+  }
+
+  #[test]
+  fn caches_the_machine_code_of_components() {
+    let temp_dir = test_util::TempDir::new();
+    let module_cache = Arc::new(
+      ModuleCache::new(temp_dir.path().join("module_cache").to_path_buf())
+        .unwrap(),
+    );
+    let source = std::fs::read(
+      test_util::testdata_path().join("wasm/hello_component.wasm"),
+    )
+    .unwrap();
+    assert!(!module_cache.precompile_wasm(&source).unwrap());
+    assert!(module_cache.precompile_wasm(&source).unwrap());
+    assert!(module_cache
+      .precompile_wasm(b"\0asm\x01\x00\x00\x00")
+      .is_err());
+
+    // the workers of other factories load the machine code
+    let components =
+      WasiComponents::new(Default::default(), Some(module_cache.clone()));
+    let (component, cached) =
+      compile_component(&components.engine, Some(&module_cache), &source)
+        .unwrap();
+    assert!(cached);
+    let stdout = wasmtime_wasi::pipe::MemoryOutputPipe::new(1024);
+    let ctx = WasiCtxBuilder::new().stdout(stdout.clone()).build();
+    let code = run_component(&components.engine, &component, ctx).unwrap();
+    assert_eq!(code, 0);
+    assert_eq!(&stdout.contents()[..], b"hello from a component\n");
+  }
+}
@@ -1,37 +1,68 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::future::Future;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
+use bytes::Bytes;
 use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::futures::future::poll_fn;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::stream::FuturesUnordered;
 use deno_core::futures::FutureExt;
+use deno_core::futures::StreamExt;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::stats::RuntimeActivity;
+use deno_core::stats::RuntimeActivityStatsFilter;
 use deno_core::url::Url;
 use deno_core::v8;
 use deno_core::CompiledWasmModuleStore;
 use deno_core::Extension;
 use deno_core::FeatureChecker;
+use deno_core::JsRuntime;
+use deno_core::ModuleId;
 use deno_core::ModuleLoader;
+use deno_core::OpMetricsSummaryTracker;
 use deno_core::PollEventLoopOptions;
 use deno_core::SharedArrayBufferStore;
 use deno_runtime::code_cache;
 use deno_runtime::deno_broadcast_channel::InMemoryBroadcastChannel;
+use deno_runtime::deno_fetch::FetchInterceptor;
+use deno_runtime::deno_ffi::DlopenInterceptor;
 use deno_runtime::deno_fs;
+use deno_runtime::deno_kv::dynamic::DynamicDbHandler;
+use deno_runtime::deno_net::ConnectInterceptor;
 use deno_runtime::deno_node::NodeExtInitServices;
 use deno_runtime::deno_node::NodeRequireLoader;
 use deno_runtime::deno_node::NodeRequireLoaderRc;
 use deno_runtime::deno_node::NodeResolver;
 use deno_runtime::deno_node::PackageJsonResolver;
 use deno_runtime::deno_permissions::PermissionsContainer;
+use deno_runtime::deno_permissions::ResourceQuotas;
 use deno_runtime::deno_tls::RootCertStoreProvider;
 use deno_runtime::deno_web::BlobStore;
-use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::inspector_server::InspectorServer;
+use deno_runtime::ops::os::VirtualEnv;
 use deno_runtime::ops::process::NpmProcessStateProviderRc;
+use deno_runtime::ops::process::SpawnInterceptor;
+use deno_runtime::ops::runtime::UnhandledRejection;
+use deno_runtime::ops::signal::SignalForwarder;
 use deno_runtime::ops::worker_host::CreateWebWorkerCb;
+use deno_runtime::ops::worker_host::WorkerCreationPolicy;
 use deno_runtime::web_worker::WebWorker;
 use deno_runtime::web_worker::WebWorkerOptions;
 use deno_runtime::web_worker::WebWorkerServiceOptions;
@@ -42,20 +73,34 @@ use deno_runtime::BootstrapOptions;
 use deno_runtime::WorkerExecutionMode;
 use deno_runtime::WorkerLogLevel;
 use deno_semver::npm::NpmPackageReqReference;
+use deno_telemetry::HostSpan;
 use deno_telemetry::OtelConfig;
 use deno_terminal::colors;
 use node_resolver::NodeResolutionKind;
 use node_resolver::ResolutionMode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
 use tokio::select;
+use tokio::sync::broadcast;
 
 use crate::args::CliLockfile;
 use crate::args::DenoSubcommand;
 use crate::args::StorageKeyResolver;
+use crate::determinism::DeterminismOptions;
+use crate::determinism::PendingTimer;
 use crate::errors;
+use crate::host::HostChannel;
+use crate::host_fn::HostFn;
+use crate::host_fn::HostFnResult;
+use crate::inspector::InspectorController;
 use crate::npm::CliNpmResolver;
+use crate::serve_adapter::ServeAdapter;
 use crate::util::checksum;
+use crate::util::cpu_time::ThreadCpuClock;
 use crate::util::file_watcher::WatcherCommunicator;
 use crate::util::file_watcher::WatcherRestartMode;
+use crate::util::sync::AsyncFlag;
 use crate::version;
 
 pub struct CreateModuleLoaderResult {
@@ -98,6 +143,34 @@ pub trait CoverageCollector: Send + Sync {
   async fn stop_collecting(&mut self) -> Result<(), AnyError>;
 }
 
+/// Receives lifecycle events of main workers, eg. to drive a progress UI or
+/// to record telemetry. Every method has an empty default implementation.
+pub trait WorkerObserver: Send + Sync {
+  /// The main module and its dependencies are about to be resolved and
+  /// loaded.
+  fn on_module_load_start(&self, _main_module: &ModuleSpecifier) {}
+
+  /// Loading the main module finished, `error` is set if it failed.
+  fn on_module_load_finish(
+    &self,
+    _main_module: &ModuleSpecifier,
+    _error: Option<&AnyError>,
+  ) {
+  }
+
+  /// The main module was evaluated and the event loop is about to run for
+  /// the first time.
+  fn on_first_tick(&self, _main_module: &ModuleSpecifier) {}
+
+  /// A promise rejection wasn't handled and stopped the worker. Called right
+  /// before [`WorkerObserver::on_exit`].
+  fn on_unhandled_rejection(&self, _error: &JsError) {}
+
+  /// The worker finished running, with its exit code or the error that
+  /// stopped it.
+  fn on_exit(&self, _result: Result<i32, &AnyError>) {}
+}
+
 pub type CreateHmrRunnerCb = Box<
   dyn Fn(deno_core::LocalInspectorSession) -> Box<dyn HmrRunner> + Send + Sync,
 >;
@@ -108,6 +181,15 @@ pub type CreateCoverageCollectorCb = Box<
     + Sync,
 >;
 
+/// Creates the extension that runs the WASI components imported by a main
+/// worker, see `cli/wasi.rs`.
+pub type CreateWasiExtensionCb = Box<dyn Fn() -> Extension + Send + Sync>;
+
+/// Creates the extensions of every web worker, on the thread of the worker
+/// as extensions can't be sent between threads.
+pub type WebWorkerExtensionsFactory =
+  Arc<dyn Fn() -> Vec<Extension> + Send + Sync>;
+
 pub struct CliMainWorkerOptions {
   pub argv: Vec<String>,
   pub log_level: WorkerLogLevel,
@@ -126,8 +208,61 @@ pub struct CliMainWorkerOptions {
   pub seed: Option<u64>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub skip_op_registration: bool,
+  /// Adds the `Deno.host` message channel to main workers.
+  pub host_channel: bool,
+  /// Lets the host serve requests with the `fetch` handler of main workers.
+  pub serve_adapter: bool,
+  /// Overrides the CLI's startup snapshot for main workers.
+  pub startup_snapshot: Option<&'static [u8]>,
+  /// Temporary directory used as `DENO_DIR`. It also holds the Cache API
+  /// storage and is deleted once the factory and all workers are dropped.
+  pub ephemeral_deno_dir: Option<Arc<tempfile::TempDir>>,
+  pub worker_observer: Option<Arc<dyn WorkerObserver>>,
+  /// Limits enforced on every main worker while its main module runs.
+  pub execution_limits: Option<ExecutionLimits>,
+  /// Terminates workers that use an unstable API without its
+  /// `--unstable-*` flag, instead of exiting the process. Main workers then
+  /// fail with an [`UnstableApiError`].
+  pub unstable_api_errors: bool,
+  /// Sees the `fetch()` requests of main and web workers.
+  pub fetch_interceptor: Option<Arc<dyn FetchInterceptor>>,
+  /// Decides whether main and web workers may open TCP and TLS connections.
+  pub connect_interceptor: Option<Arc<dyn ConnectInterceptor>>,
+  /// Decides whether main and web workers may spawn subprocesses.
+  pub spawn_interceptor: Option<Arc<dyn SpawnInterceptor>>,
+  /// Decides which dynamic libraries main and web workers may open.
+  pub dlopen_interceptor: Option<Arc<dyn DlopenInterceptor>>,
+  /// Opens the local `Deno.openKv()` databases of main and web workers.
+  pub kv_backend: Option<Arc<dyn DynamicDbHandler + Send + Sync>>,
+  /// The `BroadcastChannel` bus of main and web workers, for the host to
+  /// use it too.
+  pub broadcast_channel: Option<InMemoryBroadcastChannel>,
+  /// Decides whether main and web workers may create web workers.
+  pub worker_creation_policy: Option<Arc<dyn WorkerCreationPolicy>>,
+  /// Creates the extensions of every web worker.
+  pub web_worker_extensions: Option<WebWorkerExtensionsFactory>,
+  /// Environment variables of main and web workers, instead of the process
+  /// environment.
+  pub virtual_env: Option<VirtualEnv>,
+  /// Delivers signals to the signal listeners of main and web workers,
+  /// instead of the OS.
+  pub signal_forwarder: Option<SignalForwarder>,
+  /// Limits on the files, subprocesses and sockets each main and web worker
+  /// may use.
+  pub resource_quotas: Option<ResourceQuotas>,
   pub create_hmr_runner: Option<CreateHmrRunnerCb>,
   pub create_coverage_collector: Option<CreateCoverageCollectorCb>,
+  /// Opens inspector sessions with the first main worker from the host.
+  pub inspector_controller: Option<InspectorController>,
+  /// Makes main workers behave the same on every run.
+  pub determinism: Option<DeterminismOptions>,
+  /// Functions of the host exposed to the scripts of main workers.
+  pub host_fns: Vec<HostFn>,
+  /// Runs the WASI components imported by main workers.
+  pub create_wasi_extension: Option<CreateWasiExtensionCb>,
+  /// Exposed to main workers as `Deno.host.restoredState`, when resuming
+  /// from a [`WorkerStateSnapshot`].
+  pub restored_state: Option<serde_json::Value>,
   pub node_ipc: Option<i64>,
   pub serve_port: Option<u16>,
   pub serve_host: Option<String>,
@@ -157,6 +292,13 @@ struct SharedWorkerState {
 }
 
 impl SharedWorkerState {
+  fn cache_storage_dir(&self) -> PathBuf {
+    match &self.options.ephemeral_deno_dir {
+      Some(deno_dir) => deno_dir.path().join("deno_cache"),
+      None => get_cache_storage_dir(),
+    }
+  }
+
   pub fn create_node_init_services(
     &self,
     node_require_loader: NodeRequireLoaderRc,
@@ -178,9 +320,130 @@ pub struct CliMainWorker {
   main_module: ModuleSpecifier,
   worker: MainWorker,
   shared: Arc<SharedWorkerState>,
+  host_channel: Option<HostChannel>,
+  // exports of `js/40_host.js`, set when the host channel is enabled
+  host_exports: Option<v8::Global<v8::Object>>,
+  serve_adapter: Option<ServeAdapter>,
+  // exports of `js/40_serve_adapter.js`, set when the serve adapter is
+  // enabled
+  serve_exports: Option<v8::Global<v8::Object>>,
+  limit_enforcer: Option<LimitEnforcer>,
+  unstable_api_guard: Option<UnstableApiGuard>,
+  // exports of `js/40_determinism.js`, set for deterministic workers
+  clock: Option<v8::Global<v8::Object>>,
+  pause_flag: Option<AsyncFlag>,
+  stats: Option<Arc<Mutex<WorkerStats>>>,
+  progress: Option<ProgressReporter>,
 }
 
 impl CliMainWorker {
+  /// Takes the Rust side of the `Deno.host` message channel. Returns `None`
+  /// if the channel is disabled or was already taken. A script listening
+  /// with `Deno.host.onMessage()` keeps running until the sender is dropped.
+  pub fn take_host_channel(&mut self) -> Option<HostChannel> {
+    self.host_channel.take()
+  }
+
+  /// Takes the [`ServeAdapter`] feeding requests to the `fetch` handler of
+  /// the main module. Returns `None` if the adapter is disabled or was
+  /// already taken. It should be taken before running the worker, which
+  /// keeps serving until the adapter and all its clones are dropped.
+  pub fn take_serve_adapter(&mut self) -> Option<ServeAdapter> {
+    self.serve_adapter.take()
+  }
+
+  /// Exposes `f` to scripts as `Deno.host.call(name, args)`, replacing a
+  /// function registered under the same name. Fails unless the worker was
+  /// created with the host channel or host functions enabled.
+  pub fn register_host_fn<A, R>(
+    &mut self,
+    name: impl Into<String>,
+    f: impl Fn(A) -> HostFnResult<R> + Send + Sync + 'static,
+  ) -> Result<(), AnyError>
+  where
+    A: DeserializeOwned,
+    R: Serialize,
+  {
+    self.register(HostFn::new(name, f))
+  }
+
+  /// Like [`CliMainWorker::register_host_fn`], for a function returning a
+  /// future. Scripts get a promise.
+  pub fn register_async_host_fn<A, R, F>(
+    &mut self,
+    name: impl Into<String>,
+    f: impl Fn(A) -> F + Send + Sync + 'static,
+  ) -> Result<(), AnyError>
+  where
+    A: DeserializeOwned,
+    R: Serialize,
+    F: Future<Output = HostFnResult<R>> + 'static,
+  {
+    self.register(HostFn::new_async(name, f))
+  }
+
+  /// Removes the host function registered under `name`, including the ones
+  /// passed when creating the worker. Returns whether there was one.
+  pub fn unregister_host_fn(&mut self, name: &str) -> bool {
+    let op_state = self.worker.js_runtime.op_state();
+    let mut op_state = op_state.borrow_mut();
+    crate::host_fn::unregister_host_fn(&mut op_state, name)
+  }
+
+  fn register(&mut self, host_fn: HostFn) -> Result<(), AnyError> {
+    let op_state = self.worker.js_runtime.op_state();
+    let mut op_state = op_state.borrow_mut();
+    crate::host_fn::register_host_fn(&mut op_state, host_fn)
+  }
+
+  /// Collects the current [`WorkerStats`] of this worker.
+  pub fn stats(&mut self) -> WorkerStats {
+    collect_worker_stats(&mut self.worker.js_runtime)
+  }
+
+  /// Gets a handle that reads the stats of this worker from any thread. The
+  /// stats are refreshed between event loop turns of the `run` methods, at
+  /// most every 100ms.
+  pub fn stats_handle(&mut self) -> WorkerStatsHandle {
+    let stats = collect_worker_stats(&mut self.worker.js_runtime);
+    let stats = self
+      .stats
+      .get_or_insert_with(|| Arc::new(Mutex::new(stats)))
+      .clone();
+    WorkerStatsHandle(stats)
+  }
+
+  /// Reports [`WorkerProgress`] to `sender` while the `run` methods evaluate
+  /// the main module and run the event loop, at most every 100ms and once
+  /// the event loop is idle.
+  pub fn set_progress_sender(
+    &mut self,
+    sender: broadcast::Sender<WorkerProgress>,
+  ) {
+    self
+      .worker
+      .js_runtime
+      .v8_isolate()
+      .set_promise_hook(count_promise_job);
+    self.progress = Some(ProgressReporter {
+      sender,
+      started: Instant::now(),
+      event_loop_turns: 0,
+      promise_jobs_baseline: PROMISE_JOBS.get(),
+    });
+  }
+
+  /// Subscribes to the [`WorkerProgress`] of this worker, see
+  /// [`CliMainWorker::set_progress_sender`].
+  pub fn subscribe_progress(&mut self) -> broadcast::Receiver<WorkerProgress> {
+    if let Some(progress) = &self.progress {
+      return progress.sender.subscribe();
+    }
+    let (sender, receiver) = broadcast::channel(PROGRESS_CHANNEL_CAPACITY);
+    self.set_progress_sender(sender);
+    receiver
+  }
+
   pub fn into_main_worker(self) -> MainWorker {
     self.worker
   }
@@ -191,6 +454,63 @@ impl CliMainWorker {
   }
 
   pub async fn run(&mut self) -> Result<i32, AnyError> {
+    let exit_code = self.run_with_pause_flag(None).await?;
+    Ok(exit_code.expect("the worker can't pause without a pause flag"))
+  }
+
+  /// Runs the main module, returning `None` once the worker paused for
+  /// `pause_flag`.
+  async fn run_with_pause_flag(
+    &mut self,
+    pause_flag: Option<AsyncFlag>,
+  ) -> Result<Option<i32>, AnyError> {
+    let watchdog = self.start_limit_watchdog()?;
+    let started = SystemTime::now();
+    let mut span = HostSpan::start("deno.execute");
+    span.set_attribute("deno.main_module", self.main_module.to_string());
+    let result = self.run_main_module(pause_flag).await;
+    let result = match self.check_limits(watchdog, result) {
+      Ok(None) => return Ok(None),
+      Ok(Some(exit_code)) => Ok(exit_code),
+      Err(err) => Err(err),
+    };
+    span.set_result(&result);
+    drop(span);
+    self.export_op_metrics(started);
+    self.notify_exit(result.as_ref().copied());
+    result.map(Some)
+  }
+
+  /// Exports the op calls made by the worker as telemetry, when it tracks
+  /// them.
+  fn export_op_metrics(&mut self, started: SystemTime) {
+    let Some(summary) = self
+      .worker
+      .js_runtime
+      .op_state()
+      .borrow()
+      .try_borrow::<Rc<OpMetricsSummaryTracker>>()
+      .cloned()
+    else {
+      return;
+    };
+    let op_names = self.worker.js_runtime.op_names();
+    let per_op = summary.per_op();
+    deno_telemetry::export_op_metrics(
+      started,
+      op_names.iter().zip(per_op.iter()).map(|(name, metrics)| {
+        let calls = metrics.ops_dispatched_sync
+          + metrics.ops_dispatched_async
+          + metrics.ops_dispatched_fast;
+        (*name, calls)
+      }),
+    );
+  }
+
+  async fn run_main_module(
+    &mut self,
+    pause_flag: Option<AsyncFlag>,
+  ) -> Result<Option<i32>, AnyError> {
     let mut maybe_coverage_collector =
       self.maybe_setup_coverage_collector().await?;
     let mut maybe_hmr_runner = self.maybe_setup_hmr_runner().await?;
@@ -199,11 +519,13 @@ impl CliMainWorker {
 
     self.execute_main_module().await?;
     self.worker.dispatch_load_event()?;
+    self.notify_first_tick();
 
     loop {
       if let Some(hmr_runner) = maybe_hmr_runner.as_mut() {
+        // not set when the runner is driven by an embedder
         let watcher_communicator =
-          self.shared.maybe_file_watcher_communicator.clone().unwrap();
+          self.shared.maybe_file_watcher_communicator.clone();
 
         let hmr_future = hmr_runner.run().boxed_local();
         let event_loop_future = self.worker.run_event_loop(false).boxed_local();
@@ -218,13 +540,29 @@ impl CliMainWorker {
           }
         }
         if let Err(e) = result {
-          watcher_communicator
-            .change_restart_mode(WatcherRestartMode::Automatic);
+          if let Some(watcher_communicator) = watcher_communicator {
+            watcher_communicator
+              .change_restart_mode(WatcherRestartMode::Automatic);
+          }
           return Err(e);
         }
+      } else if let Some(pause_flag) = &pause_flag {
+        // the event loop future is only dropped while it waits for ops or
+        // timers, so the worker pauses between two ticks
+        let paused = select! {
+          biased;
+
+          result = self.run_event_loop(maybe_coverage_collector.is_none()) => {
+            result?;
+            false
+          }
+          _ = pause_flag.wait_raised() => true,
+        };
+        if paused {
+          return Ok(None);
+        }
       } else {
         self
-          .worker
           .run_event_loop(maybe_coverage_collector.is_none())
           .await?;
       }
@@ -262,9 +600,175 @@ impl CliMainWorker {
         .await?;
     }
 
+    Ok(Some(self.worker.exit_code()))
+  }
+
+  /// Stops a worker whose run was interrupted, eg. by dropping the future of
+  /// [`CliMainWorker::run`]: dispatches the `beforeunload` and `unload`
+  /// events, ignoring whether `beforeunload` was cancelled, and then runs the
+  /// event loop until the pending ops complete or `grace` passed. Returns the
+  /// exit code.
+  ///
+  /// The worker should be dropped afterwards, which terminates the ops that
+  /// are still pending. The grace period can't interrupt synchronously
+  /// running JavaScript, use [`CliMainWorker::isolate_handle`] for that.
+  pub async fn shutdown(&mut self, grace: Duration) -> Result<i32, AnyError> {
+    let result = self.dispatch_shutdown(grace).await;
+    self.notify_exit(result.as_ref().copied());
+    result
+  }
+
+  async fn dispatch_shutdown(
+    &mut self,
+    grace: Duration,
+  ) -> Result<i32, AnyError> {
+    self.worker.dispatch_beforeunload_event()?;
+    self.worker.dispatch_unload_event()?;
+    self.worker.dispatch_process_exit_event()?;
+    match tokio::time::timeout(grace, self.run_event_loop(false)).await {
+      Ok(result) => result?,
+      Err(_) => log::debug!("Pending ops did not complete within {:?}", grace),
+    }
     Ok(self.worker.exit_code())
   }
 
+  /// Runs the main module like [`CliMainWorker::run`], but also reads the
+  /// `export_name` export of the module namespace once the module has been
+  /// evaluated and returns it deserialized as JSON.
+  ///
+  /// If the export is a function it is called without arguments and its
+  /// return value is used instead. Promises are awaited in both cases.
+  pub async fn run_with_result(
+    &mut self,
+    export_name: &str,
+  ) -> Result<serde_json::Value, AnyError> {
+    let watchdog = self.start_limit_watchdog()?;
+    let result = self.run_main_module_with_result(export_name).await;
+    let result = self.check_limits(watchdog, result);
+    let exit_code = self.worker.exit_code();
+    self.notify_exit(result.as_ref().map(|_| exit_code));
+    result
+  }
+
+  async fn run_main_module_with_result(
+    &mut self,
+    export_name: &str,
+  ) -> Result<serde_json::Value, AnyError> {
+    log::debug!("main_module {}", self.main_module);
+
+    let id = self.preload_main_module().await?;
+    self.evaluate_module(id).await?;
+    self.worker.dispatch_load_event()?;
+    self.notify_first_tick();
+
+    let value = self.resolve_module_export(id, export_name).await?;
+
+    loop {
+      self.run_event_loop(false).await?;
+
+      let web_continue = self.worker.dispatch_beforeunload_event()?;
+      if !web_continue {
+        let node_continue = self.worker.dispatch_process_beforeexit_event()?;
+        if !node_continue {
+          break;
+        }
+      }
+    }
+
+    self.worker.dispatch_unload_event()?;
+    self.worker.dispatch_process_exit_event()?;
+
+    Ok(value)
+  }
+
+  async fn resolve_module_export(
+    &mut self,
+    id: ModuleId,
+    export_name: &str,
+  ) -> Result<serde_json::Value, AnyError> {
+    let value = self.get_module_export(id, export_name)?;
+    let maybe_function = {
+      let scope = &mut self.worker.js_runtime.handle_scope();
+      let value = v8::Local::new(scope, &value);
+      v8::Local::<v8::Function>::try_from(value)
+        .ok()
+        .map(|function| v8::Global::new(scope, function))
+    };
+
+    let promise = match maybe_function {
+      Some(function) => self.worker.js_runtime.call(&function).boxed_local(),
+      None => self.worker.js_runtime.resolve(value).boxed_local(),
+    };
+    self.resolve_to_json(promise).await
+  }
+
+  fn get_module_export(
+    &mut self,
+    id: ModuleId,
+    export_name: &str,
+  ) -> Result<v8::Global<v8::Value>, AnyError> {
+    let namespace = self.worker.js_runtime.get_module_namespace(id)?;
+    let scope = &mut self.worker.js_runtime.handle_scope();
+    let namespace = v8::Local::new(scope, namespace);
+    let key = v8::String::new(scope, export_name).unwrap();
+    if namespace.has(scope, key.into()) != Some(true) {
+      bail!(
+        "Module \"{}\" does not provide an export named \"{}\".",
+        self.main_module,
+        export_name
+      );
+    }
+    let value = namespace.get(scope, key.into()).unwrap();
+    Ok(v8::Global::new(scope, value))
+  }
+
+  /// Drives the event loop until `promise` settles and deserializes its
+  /// value as JSON.
+  async fn resolve_to_json(
+    &mut self,
+    promise: impl std::future::Future<
+      Output = Result<v8::Global<v8::Value>, AnyError>,
+    >,
+  ) -> Result<serde_json::Value, AnyError> {
+    let promise = std::pin::pin!(promise);
+    let value = self
+      .worker
+      .js_runtime
+      .with_event_loop_promise(promise, PollEventLoopOptions::default())
+      .await?;
+
+    let scope = &mut self.worker.js_runtime.handle_scope();
+    let value = v8::Local::new(scope, value);
+    Ok(serde_v8::from_v8(scope, value)?)
+  }
+
+  /// Evaluates the main module and runs the event loop until it is idle,
+  /// returning a handle that can call the module's exports afterwards.
+  ///
+  /// Unlike [`CliMainWorker::run`] no unload events are dispatched, so the
+  /// module stays usable for as long as the handle is alive. When creating
+  /// the handle fails, the error is passed to [`WorkerObserver::on_exit`].
+  pub async fn into_module_handle(mut self) -> Result<ModuleHandle, AnyError> {
+    log::debug!("main_module {}", self.main_module);
+
+    let result = async {
+      let id = self.preload_main_module().await?;
+      self.evaluate_module(id).await?;
+      self.worker.dispatch_load_event()?;
+      self.notify_first_tick();
+      self.run_event_loop(false).await?;
+      Ok::<_, AnyError>(id)
+    }
+    .await;
+    match result {
+      Ok(id) => Ok(ModuleHandle { worker: self, id }),
+      Err(err) => {
+        self.notify_exit(Err(&err));
+        Err(err)
+      }
+    }
+  }
+
   pub async fn run_for_watcher(self) -> Result<(), AnyError> {
     /// The FileWatcherModuleExecutor provides module execution with safe dispatching of life-cycle events by tracking the
     /// state of any pending events and emitting accordingly on drop in the case of a future
@@ -327,8 +831,151 @@ impl CliMainWorker {
   }
 
   pub async fn execute_main_module(&mut self) -> Result<(), AnyError> {
-    let id = self.worker.preload_main_module(&self.main_module).await?;
-    self.worker.evaluate_module(id).await
+    let id = self.preload_main_module().await?;
+    self.evaluate_module(id).await?;
+    self.start_serving(id)
+  }
+
+  /// Passes the requests of the [`ServeAdapter`] to the `fetch` handler of
+  /// the module `id`, when the adapter is enabled.
+  fn start_serving(&mut self, id: ModuleId) -> Result<(), AnyError> {
+    let Some(serve_exports) = &self.serve_exports else {
+      return Ok(());
+    };
+    let namespace = self.worker.js_runtime.get_module_namespace(id)?;
+    let scope = &mut self.worker.js_runtime.handle_scope();
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let serve_exports = v8::Local::new(tc_scope, serve_exports);
+    let key = v8::String::new(tc_scope, "serve").unwrap();
+    let serve = serve_exports.get(tc_scope, key.into()).unwrap();
+    let serve = v8::Local::<v8::Function>::try_from(serve)?;
+    let namespace = v8::Local::new(tc_scope, namespace);
+    let undefined = v8::undefined(tc_scope);
+    serve.call(tc_scope, undefined.into(), &[namespace.into()]);
+    if let Some(exception) = tc_scope.exception() {
+      let error = JsError::from_v8_exception(tc_scope, exception);
+      return Err(error.into());
+    }
+    Ok(())
+  }
+
+  /// Evaluates the module like `MainWorker::evaluate_module`, sampling the
+  /// stats and progress while top-level await is pending.
+  async fn evaluate_module(&mut self, id: ModuleId) -> Result<(), AnyError> {
+    let options = &self.shared.options;
+    if (self.stats.is_none() && self.progress.is_none())
+      || options.inspect_brk
+      || options.inspect_wait
+    {
+      return self.worker.evaluate_module(id).await;
+    }
+    let mut receiver = self.worker.js_runtime.mod_evaluate(id);
+    select! {
+      biased;
+
+      maybe_result = &mut receiver => maybe_result,
+
+      event_loop_result = self.run_event_loop(false) => {
+        event_loop_result?;
+        receiver.await
+      }
+    }
+  }
+
+  async fn preload_main_module(&mut self) -> Result<ModuleId, AnyError> {
+    let observer = self.shared.options.worker_observer.as_ref();
+    if let Some(observer) = observer {
+      observer.on_module_load_start(&self.main_module);
+    }
+    let result = self.worker.preload_main_module(&self.main_module).await;
+    if let Some(observer) = observer {
+      observer.on_module_load_finish(&self.main_module, result.as_ref().err());
+    }
+    result
+  }
+
+  /// Runs the event loop like `MainWorker::run_event_loop`, refreshing the
+  /// stats read through [`CliMainWorker::stats_handle`] and reporting the
+  /// progress.
+  async fn run_event_loop(
+    &mut self,
+    wait_for_inspector: bool,
+  ) -> Result<(), AnyError> {
+    if self.stats.is_none() && self.progress.is_none() {
+      return self.worker.run_event_loop(wait_for_inspector).await;
+    }
+    let js_runtime = &mut self.worker.js_runtime;
+    let stats = self.stats.as_deref();
+    let mut progress = self.progress.as_mut();
+    let mut last_sample = Instant::now();
+    let result = poll_fn(|cx| {
+      if let Some(progress) = progress.as_mut() {
+        progress.event_loop_turns += 1;
+      }
+      if last_sample.elapsed() >= STATS_SAMPLE_INTERVAL {
+        sample_worker_stats(js_runtime, stats, progress.as_deref());
+        last_sample = Instant::now();
+      }
+      js_runtime.poll_event_loop(
+        cx,
+        PollEventLoopOptions {
+          wait_for_inspector,
+          ..Default::default()
+        },
+      )
+    })
+    .await;
+    sample_worker_stats(js_runtime, stats, progress.as_deref());
+    result
+  }
+
+  fn start_limit_watchdog(
+    &self,
+  ) -> Result<Option<LimitWatchdogGuard>, AnyError> {
+    match &self.limit_enforcer {
+      Some(enforcer) => enforcer.start_watchdog(),
+      None => Ok(None),
+    }
+  }
+
+  /// Replaces the result of a run that was terminated because it exceeded
+  /// one of its [`ExecutionLimits`] with a [`WorkerLimitError`], or because
+  /// it used an unstable API with an [`UnstableApiError`].
+  fn check_limits<T>(
+    &self,
+    watchdog: Option<LimitWatchdogGuard>,
+    result: Result<T, AnyError>,
+  ) -> Result<T, AnyError> {
+    drop(watchdog);
+    let result = match &self.limit_enforcer {
+      Some(enforcer) => enforcer.check(result),
+      None => result,
+    };
+    match &self.unstable_api_guard {
+      Some(guard) => guard.check(result),
+      None => result,
+    }
+  }
+
+  fn notify_first_tick(&self) {
+    if let Some(observer) = &self.shared.options.worker_observer {
+      observer.on_first_tick(&self.main_module);
+    }
+  }
+
+  fn notify_exit(&mut self, result: Result<i32, &AnyError>) {
+    let Some(observer) = &self.shared.options.worker_observer else {
+      return;
+    };
+    if let Some(js_error) =
+      result.err().and_then(|err| err.downcast_ref::<JsError>())
+    {
+      let op_state = self.worker.js_runtime.op_state();
+      if op_state.borrow().has::<UnhandledRejection>() {
+        observer.on_unhandled_rejection(js_error);
+      }
+    }
+    observer.on_exit(result);
   }
 
   pub async fn execute_side_module(&mut self) -> Result<(), AnyError> {
@@ -384,6 +1031,12 @@ impl CliMainWorker {
     Ok(Some(coverage_collector))
   }
 
+  /// Gets a handle that can be used to terminate JavaScript execution of
+  /// this worker from another thread.
+  pub fn isolate_handle(&mut self) -> v8::IsolateHandle {
+    self.worker.js_runtime.v8_isolate().thread_safe_handle()
+  }
+
   pub fn execute_script_static(
     &mut self,
     name: &'static str,
@@ -391,6 +1044,864 @@ impl CliMainWorker {
   ) -> Result<v8::Global<v8::Value>, AnyError> {
     self.worker.js_runtime.execute_script(name, source_code)
   }
+
+  /// Defines `globalThis[name]` in the main realm as `value` converted to a
+  /// JavaScript value, eg. configuration of the host. Call it before running
+  /// the main module for the module to see it while it's evaluated.
+  pub fn set_global(
+    &mut self,
+    name: &str,
+    value: &impl Serialize,
+  ) -> Result<(), AnyError> {
+    set_global(&mut self.worker.js_runtime, name, value)
+  }
+
+  /// Reads `globalThis[name]` of the main realm, `None` if it's undefined.
+  pub fn get_global<T: DeserializeOwned>(
+    &mut self,
+    name: &str,
+  ) -> Result<Option<T>, AnyError> {
+    get_global(&mut self.worker.js_runtime, name)
+  }
+
+  /// Defines `globalThis[name]` in the main realm as an `ArrayBuffer` of
+  /// `data`, without serializing it. The buffer takes over the memory of
+  /// `data` unless it's shared with other `Bytes`, in which case it's copied
+  /// once.
+  pub fn transfer_buffer(
+    &mut self,
+    name: &str,
+    data: Bytes,
+  ) -> Result<(), AnyError> {
+    transfer_buffer(&mut self.worker.js_runtime, name, data)
+  }
+
+  /// Takes the `ArrayBuffer` or the view of one, eg. a `Uint8Array`, at
+  /// `globalThis[name]` of the main realm, copying its contents once, and
+  /// deletes the global. `None` if it's undefined.
+  pub fn take_buffer(&mut self, name: &str) -> Result<Option<Bytes>, AnyError> {
+    take_buffer(&mut self.worker.js_runtime, name)
+  }
+
+  /// Advances the clock of a deterministic worker by `duration`, running
+  /// the timers that become due in the order they are due. Microtasks run
+  /// after every timer, but the event loop isn't polled.
+  pub async fn advance_time(
+    &mut self,
+    duration: Duration,
+  ) -> Result<(), AnyError> {
+    let ms = serde_json::json!(duration.as_secs_f64() * 1000.0);
+    self.call_clock("advance", vec![ms]).await?;
+    Ok(())
+  }
+
+  /// Stops the timers of a deterministic worker from firing until
+  /// [`CliMainWorker::resume_timers`] is called. The clock can still be
+  /// advanced with [`CliMainWorker::advance_time`].
+  pub async fn pause_timers(&mut self) -> Result<(), AnyError> {
+    self.call_clock("pause", vec![]).await?;
+    Ok(())
+  }
+
+  pub async fn resume_timers(&mut self) -> Result<(), AnyError> {
+    self.call_clock("resume", vec![]).await?;
+    Ok(())
+  }
+
+  /// The timers of a deterministic worker that haven't fired yet, in the
+  /// order they will fire.
+  pub async fn pending_timers(
+    &mut self,
+  ) -> Result<Vec<PendingTimer>, AnyError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct JsPendingTimer {
+      id: u32,
+      due_in: f64,
+      repeat: bool,
+    }
+
+    let timers = self.call_clock("pendingTimers", vec![]).await?;
+    let timers: Vec<JsPendingTimer> = serde_json::from_value(timers)?;
+    Ok(
+      timers
+        .into_iter()
+        .map(|timer| PendingTimer {
+          id: timer.id,
+          due_in: Duration::from_secs_f64(timer.due_in.max(0.0) / 1000.0),
+          repeat: timer.repeat,
+        })
+        .collect(),
+    )
+  }
+
+  /// The time reported by `Date.now()` in a deterministic worker.
+  pub async fn current_time(&mut self) -> Result<SystemTime, AnyError> {
+    let now = self.call_clock("currentTime", vec![]).await?;
+    let ms = now.as_f64().unwrap_or_default();
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs_f64(ms / 1000.0))
+  }
+
+  async fn call_clock(
+    &mut self,
+    name: &str,
+    args: Vec<serde_json::Value>,
+  ) -> Result<serde_json::Value, AnyError> {
+    let Some(clock) = self.clock.clone() else {
+      bail!(
+        "The worker is not deterministic. Set `DeterminismOptions` to control its clock."
+      );
+    };
+    let (function, args) = self.prepare_internal_call(&clock, name, args)?;
+    let value = self
+      .worker
+      .js_runtime
+      .call_with_args(&function, &args)
+      .await?;
+    let scope = &mut self.worker.js_runtime.handle_scope();
+    let value = v8::Local::new(scope, value);
+    Ok(serde_v8::from_v8(scope, value)?)
+  }
+
+  /// Looks up the function `name` in the exports of an internal module and
+  /// converts `args` for calling it.
+  fn prepare_internal_call(
+    &mut self,
+    exports: &v8::Global<v8::Object>,
+    name: &str,
+    args: Vec<serde_json::Value>,
+  ) -> Result<(v8::Global<v8::Function>, Vec<v8::Global<v8::Value>>), AnyError>
+  {
+    let scope = &mut self.worker.js_runtime.handle_scope();
+    let exports = v8::Local::new(scope, exports);
+    let key = v8::String::new(scope, name).unwrap();
+    let function = exports.get(scope, key.into()).unwrap();
+    let function = v8::Local::<v8::Function>::try_from(function)?;
+    let args = args
+      .iter()
+      .map(|arg| {
+        let arg = serde_v8::to_v8(scope, arg)?;
+        Ok(v8::Global::new(scope, arg))
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
+    Ok((v8::Global::new(scope, function), args))
+  }
+
+  /// Gets a handle that pauses this worker while it runs with
+  /// [`CliMainWorker::run_until_paused`].
+  pub fn pause_handle(&mut self) -> PauseHandle {
+    PauseHandle(self.pause_flag.get_or_insert_with(Default::default).clone())
+  }
+
+  /// Runs the main module like [`CliMainWorker::run`], but once a
+  /// [`PauseHandle`] is paused, stops when the event loop waits for ops or
+  /// timers and returns a [`WorkerStateSnapshot`] instead of the exit code. The
+  /// main module is always evaluated before the worker pauses.
+  ///
+  /// The state of the snapshot is the value returned by the callback the
+  /// script registered with `Deno.host.onSnapshot()`, which is called
+  /// synchronously as the event loop isn't polled anymore. A worker built
+  /// with the snapshot, see `DenoRuntimeBuilder::resume_from`, can read it
+  /// from `Deno.host.restoredState` to continue where the paused one
+  /// stopped. Anything the script doesn't put in its state, eg. pending
+  /// promises or module scope variables, is lost. The worker should be
+  /// dropped once paused.
+  pub async fn run_until_paused(&mut self) -> Result<RunOutcome, AnyError> {
+    let pause_flag =
+      self.pause_flag.get_or_insert_with(Default::default).clone();
+    match self.run_with_pause_flag(Some(pause_flag)).await? {
+      Some(exit_code) => Ok(RunOutcome::Exited(exit_code)),
+      None => Ok(RunOutcome::Paused(self.snapshot()?)),
+    }
+  }
+
+  fn snapshot(&mut self) -> Result<WorkerStateSnapshot, AnyError> {
+    let state = match &self.host_exports {
+      Some(host_exports) => {
+        let scope = &mut self.worker.js_runtime.handle_scope();
+        let tc_scope = &mut v8::TryCatch::new(scope);
+        let host_exports = v8::Local::new(tc_scope, host_exports);
+        let key = v8::String::new(tc_scope, "takeSnapshot").unwrap();
+        let take_snapshot = host_exports.get(tc_scope, key.into()).unwrap();
+        let take_snapshot = v8::Local::<v8::Function>::try_from(take_snapshot)?;
+        let undefined = v8::undefined(tc_scope);
+        let state = take_snapshot.call(tc_scope, undefined.into(), &[]);
+        if let Some(exception) = tc_scope.exception() {
+          let error = JsError::from_v8_exception(tc_scope, exception);
+          return Err(error.into());
+        }
+        serde_v8::from_v8(tc_scope, state.unwrap())?
+      }
+      None => serde_json::Value::Null,
+    };
+    Ok(WorkerStateSnapshot {
+      main_module: self.main_module.clone(),
+      state,
+    })
+  }
+}
+
+/// Pauses a main worker running with [`CliMainWorker::run_until_paused`],
+/// eg. before the host shuts down.
+#[derive(Clone)]
+pub struct PauseHandle(AsyncFlag);
+
+impl PauseHandle {
+  pub fn pause(&self) {
+    self.0.raise();
+  }
+}
+
+/// How [`CliMainWorker::run_until_paused`] stopped.
+#[derive(Debug)]
+pub enum RunOutcome {
+  /// The main module ran to completion with this exit code.
+  Exited(i32),
+  Paused(WorkerStateSnapshot),
+}
+
+/// The recoverable state of a paused main worker. It can be serialized,
+/// eg. to a database, to resume the worker after the host restarted.
+///
+/// Only the state of the script is kept, not its module graph or heap: the
+/// resumed worker loads `main_module` again, with the sources it has then.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerStateSnapshot {
+  pub main_module: ModuleSpecifier,
+  /// The value returned by the `Deno.host.onSnapshot()` callback of the
+  /// script, or `null` if it didn't register one.
+  pub state: serde_json::Value,
+}
+
+fn set_global(
+  js_runtime: &mut JsRuntime,
+  name: &str,
+  value: &impl Serialize,
+) -> Result<(), AnyError> {
+  let scope = &mut js_runtime.handle_scope();
+  let key = global_key(scope, name)?;
+  let value = serde_v8::to_v8(scope, value)?;
+  let global = scope.get_current_context().global(scope);
+  if global.set(scope, key, value) != Some(true) {
+    bail!("Setting the global \"{}\" failed.", name);
+  }
+  Ok(())
+}
+
+fn get_global<T: DeserializeOwned>(
+  js_runtime: &mut JsRuntime,
+  name: &str,
+) -> Result<Option<T>, AnyError> {
+  let scope = &mut js_runtime.handle_scope();
+  let key = global_key(scope, name)?;
+  let global = scope.get_current_context().global(scope);
+  let Some(value) = global.get(scope, key) else {
+    bail!("Reading the global \"{}\" failed.", name);
+  };
+  if value.is_undefined() {
+    return Ok(None);
+  }
+  Ok(Some(serde_v8::from_v8(scope, value)?))
+}
+
+fn transfer_buffer(
+  js_runtime: &mut JsRuntime,
+  name: &str,
+  data: Bytes,
+) -> Result<(), AnyError> {
+  let scope = &mut js_runtime.handle_scope();
+  let key = global_key(scope, name)?;
+  let backing_store =
+    v8::ArrayBuffer::new_backing_store_from_vec(Vec::from(data)).make_shared();
+  let buffer = v8::ArrayBuffer::with_backing_store(scope, &backing_store);
+  let global = scope.get_current_context().global(scope);
+  if global.set(scope, key, buffer.into()) != Some(true) {
+    bail!("Setting the global \"{}\" failed.", name);
+  }
+  Ok(())
+}
+
+fn take_buffer(
+  js_runtime: &mut JsRuntime,
+  name: &str,
+) -> Result<Option<Bytes>, AnyError> {
+  let scope = &mut js_runtime.handle_scope();
+  let key = global_key(scope, name)?;
+  let global = scope.get_current_context().global(scope);
+  let Some(value) = global.get(scope, key) else {
+    bail!("Reading the global \"{}\" failed.", name);
+  };
+  if value.is_undefined() {
+    return Ok(None);
+  }
+  let view = if let Ok(view) = v8::Local::<v8::ArrayBufferView>::try_from(value)
+  {
+    view
+  } else if let Ok(buffer) = v8::Local::<v8::ArrayBuffer>::try_from(value) {
+    let Some(view) =
+      v8::Uint8Array::new(scope, buffer, 0, buffer.byte_length())
+    else {
+      bail!("Reading the global \"{}\" failed.", name);
+    };
+    view.into()
+  } else {
+    bail!("The global \"{}\" is not an ArrayBuffer.", name);
+  };
+  let mut data = vec![0; view.byte_length()];
+  view.copy_contents(&mut data);
+  global.delete(scope, key);
+  Ok(Some(Bytes::from(data)))
+}
+
+fn global_key<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  name: &str,
+) -> Result<v8::Local<'s, v8::Value>, AnyError> {
+  match v8::String::new(scope, name) {
+    Some(key) => Ok(key.into()),
+    None => bail!("The global name \"{}\" is too long.", name),
+  }
+}
+
+/// Gets the exports object of an internal module from its namespace.
+fn module_exports(
+  js_runtime: &mut JsRuntime,
+  namespace: v8::Global<v8::Value>,
+) -> Result<v8::Global<v8::Object>, AnyError> {
+  let scope = &mut js_runtime.handle_scope();
+  let namespace = v8::Local::new(scope, namespace);
+  let namespace = v8::Local::<v8::Object>::try_from(namespace)?;
+  Ok(v8::Global::new(scope, namespace))
+}
+
+/// An evaluated main module whose exports can be called repeatedly without
+/// evaluating the module again. Created by
+/// [`CliMainWorker::into_module_handle`].
+pub struct ModuleHandle {
+  worker: CliMainWorker,
+  id: ModuleId,
+}
+
+impl ModuleHandle {
+  /// Reads the `export_name` export and deserializes it as JSON, awaiting it
+  /// first if it is a promise. The [`ExecutionLimits`] of the worker apply
+  /// to the wait, separately from other calls.
+  pub async fn get_export(
+    &mut self,
+    export_name: &str,
+  ) -> Result<serde_json::Value, AnyError> {
+    let value = self.worker.get_module_export(self.id, export_name)?;
+    let watchdog = self.worker.start_limit_watchdog()?;
+    let promise = self.worker.worker.js_runtime.resolve(value);
+    let result = self.worker.resolve_to_json(promise).await;
+    self.worker.check_limits(watchdog, result)
+  }
+
+  /// Calls the exported function `export_name` with `args` and returns its
+  /// (awaited) return value deserialized as JSON. The [`ExecutionLimits`] of
+  /// the worker apply to every call separately.
+  pub async fn call_export(
+    &mut self,
+    export_name: &str,
+    args: Vec<serde_json::Value>,
+  ) -> Result<serde_json::Value, AnyError> {
+    let value = self.worker.get_module_export(self.id, export_name)?;
+    let (function, args) = {
+      let scope = &mut self.worker.worker.js_runtime.handle_scope();
+      let value = v8::Local::new(scope, value);
+      let Ok(function) = v8::Local::<v8::Function>::try_from(value) else {
+        bail!("Export \"{}\" is not a function.", export_name);
+      };
+      let args = args
+        .iter()
+        .map(|arg| {
+          let arg = serde_v8::to_v8(scope, arg)?;
+          Ok(v8::Global::new(scope, arg))
+        })
+        .collect::<Result<Vec<_>, AnyError>>()?;
+      (v8::Global::new(scope, function), args)
+    };
+
+    let watchdog = self.worker.start_limit_watchdog()?;
+    let promise = self
+      .worker
+      .worker
+      .js_runtime
+      .call_with_args(&function, &args);
+    let result = self.worker.resolve_to_json(promise).await;
+    self.worker.check_limits(watchdog, result)
+  }
+
+  /// Runs the event loop until there is no more pending work.
+  pub async fn run_event_loop(&mut self) -> Result<(), AnyError> {
+    self.worker.run_event_loop(false).await
+  }
+
+  pub fn into_worker(self) -> CliMainWorker {
+    self.worker
+  }
+}
+
+// How often the stats read through a `WorkerStatsHandle` are refreshed.
+const STATS_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Resources used by a main worker at a point in time.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerStats {
+  /// Size of the V8 heap in bytes.
+  pub heap_total: usize,
+  /// Bytes of the V8 heap in use.
+  pub heap_used: usize,
+  /// Size the V8 heap may grow to in bytes.
+  pub heap_limit: usize,
+  /// Bytes allocated outside of the V8 heap for JavaScript objects, eg. the
+  /// contents of `ArrayBuffer`s.
+  pub external_memory: usize,
+  /// Number of open resources by name, eg. `"fsFile"` or `"tcpStream"`.
+  pub resources: BTreeMap<String, usize>,
+  /// Number of pending async ops by op name.
+  pub pending_ops: BTreeMap<String, usize>,
+  /// Number of active timers and intervals.
+  pub timers: usize,
+}
+
+/// Reads the stats of a running main worker, see
+/// [`CliMainWorker::stats_handle`].
+#[derive(Clone)]
+pub struct WorkerStatsHandle(Arc<Mutex<WorkerStats>>);
+
+impl WorkerStatsHandle {
+  /// The most recently collected stats.
+  pub fn get(&self) -> WorkerStats {
+    self.0.lock().clone()
+  }
+}
+
+// Lagging receivers miss the oldest events, which are superseded anyway.
+const PROGRESS_CHANNEL_CAPACITY: usize = 16;
+
+/// Progress of a main worker, sent while it evaluates its main module,
+/// including top-level await, and runs its event loop. See
+/// [`CliMainWorker::subscribe_progress`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerProgress {
+  /// Milliseconds since progress reporting was enabled.
+  pub elapsed_ms: u64,
+  /// Number of times the event loop was polled, ie. the macrotask turns.
+  pub event_loop_turns: u64,
+  /// Number of promise reactions that ran, ie. the microtasks. Counted for
+  /// all workers on the worker's thread.
+  pub promise_jobs: u64,
+  /// Number of pending async ops by op name, eg. `"op_fetch_send"` while
+  /// waiting on the network.
+  pub pending_ops: BTreeMap<String, usize>,
+  /// Number of active timers and intervals.
+  pub timers: usize,
+}
+
+thread_local! {
+  static PROMISE_JOBS: Cell<u64> = const { Cell::new(0) };
+}
+
+extern "C" fn count_promise_job(
+  hook_type: v8::PromiseHookType,
+  _promise: v8::Local<v8::Promise>,
+  _parent: v8::Local<v8::Value>,
+) {
+  if matches!(hook_type, v8::PromiseHookType::Before) {
+    PROMISE_JOBS.set(PROMISE_JOBS.get() + 1);
+  }
+}
+
+struct ProgressReporter {
+  sender: broadcast::Sender<WorkerProgress>,
+  started: Instant,
+  event_loop_turns: u64,
+  promise_jobs_baseline: u64,
+}
+
+impl ProgressReporter {
+  fn report(&self, stats: &WorkerStats) {
+    // there might be no receivers yet
+    let _ = self.sender.send(WorkerProgress {
+      elapsed_ms: self.started.elapsed().as_millis() as u64,
+      event_loop_turns: self.event_loop_turns,
+      promise_jobs: PROMISE_JOBS.get() - self.promise_jobs_baseline,
+      pending_ops: stats.pending_ops.clone(),
+      timers: stats.timers,
+    });
+  }
+}
+
+fn sample_worker_stats(
+  js_runtime: &mut JsRuntime,
+  stats: Option<&Mutex<WorkerStats>>,
+  progress: Option<&ProgressReporter>,
+) {
+  let sample = collect_worker_stats(js_runtime);
+  if let Some(progress) = progress {
+    progress.report(&sample);
+  }
+  if let Some(stats) = stats {
+    *stats.lock() = sample;
+  }
+}
+
+fn collect_worker_stats(js_runtime: &mut JsRuntime) -> WorkerStats {
+  let mut heap = v8::HeapStatistics::default();
+  js_runtime.v8_isolate().get_heap_statistics(&mut heap);
+  let mut stats = WorkerStats {
+    heap_total: heap.total_heap_size(),
+    heap_used: heap.used_heap_size(),
+    heap_limit: heap.heap_size_limit(),
+    external_memory: heap.external_memory(),
+    ..Default::default()
+  };
+
+  let filter = RuntimeActivityStatsFilter::default()
+    .with_ops()
+    .with_resources()
+    .with_timers();
+  let activity = js_runtime.runtime_activity_stats_factory().capture(&filter);
+  for activity in activity.dump().active {
+    match activity {
+      RuntimeActivity::AsyncOp(_, _, name) => {
+        *stats.pending_ops.entry(name.to_string()).or_default() += 1;
+      }
+      RuntimeActivity::Resource(_, _, name) => {
+        *stats.resources.entry(name.to_string()).or_default() += 1;
+      }
+      RuntimeActivity::Timer(..) | RuntimeActivity::Interval(..) => {
+        stats.timers += 1;
+      }
+    }
+  }
+  stats
+}
+
+/// Resource limits of a main worker spawned by a [`WorkerPool`].
+#[derive(Debug, Clone, Default)]
+pub struct WorkerLimits {
+  /// Maximum size of the worker's V8 heap in bytes.
+  pub max_heap_size: Option<usize>,
+  /// Maximum wall-clock time the worker may run for.
+  pub timeout: Option<Duration>,
+}
+
+/// Limits on the resources a main worker may use while running its main
+/// module. Exceeding one of them terminates the isolate and fails the run
+/// with a [`WorkerLimitError`].
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionLimits {
+  /// Maximum wall-clock time the main module may run for.
+  pub wall_time: Option<Duration>,
+  /// Maximum CPU time the thread running the worker may use. Time spent
+  /// waiting for timers or I/O doesn't count.
+  pub cpu_time: Option<Duration>,
+  /// Maximum size of the worker's V8 heap in bytes.
+  pub heap_bytes: Option<usize>,
+}
+
+impl From<WorkerLimits> for ExecutionLimits {
+  fn from(limits: WorkerLimits) -> Self {
+    Self {
+      wall_time: limits.timeout,
+      cpu_time: None,
+      heap_bytes: limits.max_heap_size,
+    }
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkerLimitError {
+  #[error("Worker exceeded its heap limit of {0} bytes.")]
+  HeapLimit(usize),
+  #[error("Worker exceeded its time limit of {0:?}.")]
+  Timeout(Duration),
+  #[error("Worker exceeded its CPU time limit of {0:?}.")]
+  CpuTime(Duration),
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+  "Unstable API '{api_name}'. The `--unstable-{feature}` flag must be provided."
+)]
+pub struct UnstableApiError {
+  pub feature: String,
+  pub api_name: String,
+}
+
+/// Terminates a worker when it uses an unstable API that isn't enabled,
+/// through the exit callback of its own [`FeatureChecker`].
+#[derive(Clone, Default)]
+struct UnstableApiGuard {
+  isolate_handle: Arc<std::sync::OnceLock<v8::IsolateHandle>>,
+  error: Arc<Mutex<Option<UnstableApiError>>>,
+}
+
+impl UnstableApiGuard {
+  /// Copies the enabled features of `checker` into a checker for one
+  /// worker. Unless `log_errors` is set, the error is only recorded for
+  /// [`UnstableApiGuard::check`].
+  fn feature_checker(
+    &self,
+    checker: &FeatureChecker,
+    log_errors: bool,
+  ) -> Arc<FeatureChecker> {
+    let mut worker_checker = FeatureChecker::default();
+    for granular_flag in crate::UNSTABLE_GRANULAR_FLAGS {
+      if checker.check(granular_flag.name) {
+        worker_checker.enable_feature(granular_flag.name);
+      }
+    }
+    let guard = self.clone();
+    worker_checker.set_exit_cb(Box::new(move |feature, api_name| {
+      let error = UnstableApiError {
+        feature: feature.to_string(),
+        api_name: api_name.to_string(),
+      };
+      if log_errors {
+        log::error!("{error}");
+      }
+      guard.error.lock().get_or_insert(error);
+      if let Some(isolate_handle) = guard.isolate_handle.get() {
+        isolate_handle.terminate_execution();
+      }
+    }));
+    Arc::new(worker_checker)
+  }
+
+  fn install(&self, js_runtime: &mut JsRuntime) {
+    let _ = self
+      .isolate_handle
+      .set(js_runtime.v8_isolate().thread_safe_handle());
+  }
+
+  fn check<T>(&self, result: Result<T, AnyError>) -> Result<T, AnyError> {
+    match self.error.lock().take() {
+      Some(error) => Err(error.into()),
+      None => result,
+    }
+  }
+}
+
+// How often the CPU time of a worker is compared to its limit.
+const CPU_TIME_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Stops the watchdog thread of a run when dropped.
+type LimitWatchdogGuard = std::sync::mpsc::Sender<()>;
+
+/// Terminates the isolate of a worker that exceeds its [`ExecutionLimits`]
+/// and remembers which limit was exceeded.
+struct LimitEnforcer {
+  limits: ExecutionLimits,
+  isolate_handle: v8::IsolateHandle,
+  exceeded: Arc<Mutex<Option<WorkerLimitError>>>,
+}
+
+impl LimitEnforcer {
+  fn install(worker: &mut MainWorker, limits: ExecutionLimits) -> Self {
+    let isolate_handle = worker.js_runtime.v8_isolate().thread_safe_handle();
+    let exceeded = Arc::new(Mutex::new(None));
+    if let Some(heap_bytes) = limits.heap_bytes {
+      let isolate_handle = isolate_handle.clone();
+      let exceeded = exceeded.clone();
+      worker.js_runtime.add_near_heap_limit_callback(
+        move |current_limit, _initial_limit| {
+          terminate_for_limit(
+            &isolate_handle,
+            &exceeded,
+            WorkerLimitError::HeapLimit(heap_bytes),
+          );
+          // give V8 some room to unwind, instead of aborting the process
+          current_limit * 2
+        },
+      );
+    }
+    Self {
+      limits,
+      isolate_handle,
+      exceeded,
+    }
+  }
+
+  /// Starts a thread that enforces the time limits. Has to be called on the
+  /// thread that runs the worker, because that's the thread whose CPU time
+  /// is measured.
+  fn start_watchdog(&self) -> Result<Option<LimitWatchdogGuard>, AnyError> {
+    let wall_time = self.limits.wall_time;
+    let cpu_time = self.limits.cpu_time;
+    if wall_time.is_none() && cpu_time.is_none() {
+      return Ok(None);
+    }
+    let cpu_clock = match cpu_time {
+      Some(cpu_time) => {
+        let clock = ThreadCpuClock::current()?;
+        let start = clock.elapsed()?;
+        Some((clock, start, cpu_time))
+      }
+      None => None,
+    };
+    let isolate_handle = self.isolate_handle.clone();
+    let exceeded = self.exceeded.clone();
+    let (guard_tx, guard_rx) = std::sync::mpsc::channel::<()>();
+    let started = Instant::now();
+    // a separate thread, because a busy isolate blocks the current one
+    std::thread::spawn(move || loop {
+      let mut poll_interval = wall_time
+        .map(|wall_time| wall_time.saturating_sub(started.elapsed()))
+        .unwrap_or(CPU_TIME_POLL_INTERVAL);
+      if cpu_clock.is_some() {
+        poll_interval = poll_interval.min(CPU_TIME_POLL_INTERVAL);
+      }
+      if !matches!(
+        guard_rx.recv_timeout(poll_interval),
+        Err(RecvTimeoutError::Timeout)
+      ) {
+        return;
+      }
+      if let Some(wall_time) = wall_time {
+        if started.elapsed() >= wall_time {
+          let error = WorkerLimitError::Timeout(wall_time);
+          terminate_for_limit(&isolate_handle, &exceeded, error);
+          return;
+        }
+      }
+      if let Some((clock, start, cpu_time)) = &cpu_clock {
+        let Ok(elapsed) = clock.elapsed() else {
+          return;
+        };
+        if elapsed.saturating_sub(*start) >= *cpu_time {
+          let error = WorkerLimitError::CpuTime(*cpu_time);
+          terminate_for_limit(&isolate_handle, &exceeded, error);
+          return;
+        }
+      }
+    });
+    Ok(Some(guard_tx))
+  }
+
+  fn check<T>(&self, result: Result<T, AnyError>) -> Result<T, AnyError> {
+    match self.exceeded.lock().take() {
+      Some(error) => Err(error.into()),
+      None => result,
+    }
+  }
+}
+
+fn terminate_for_limit(
+  isolate_handle: &v8::IsolateHandle,
+  exceeded: &Mutex<Option<WorkerLimitError>>,
+  error: WorkerLimitError,
+) {
+  exceeded.lock().get_or_insert(error);
+  isolate_handle.terminate_execution();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolWorkerId(usize);
+
+/// Runs several main workers concurrently on the current thread. Every
+/// worker is a separate isolate with its own permissions, extensions and
+/// [`WorkerLimits`]. Workers only make progress while the pool is polled
+/// through [`WorkerPool::next`] or [`WorkerPool::join_all`].
+pub struct WorkerPool {
+  factory: CliMainWorkerFactory,
+  next_id: usize,
+  host_channels: HashMap<PoolWorkerId, HostChannel>,
+  running: FuturesUnordered<
+    LocalBoxFuture<'static, (PoolWorkerId, Result<i32, AnyError>)>,
+  >,
+}
+
+impl WorkerPool {
+  pub fn new(factory: CliMainWorkerFactory) -> Self {
+    Self {
+      factory,
+      next_id: 0,
+      host_channels: HashMap::new(),
+      running: FuturesUnordered::new(),
+    }
+  }
+
+  /// The permissions the pool was configured with. Use
+  /// `create_child_permissions` on them to derive narrower permissions for
+  /// individual workers.
+  pub fn root_permissions(&self) -> &PermissionsContainer {
+    &self.factory.shared.root_permissions
+  }
+
+  /// Creates a worker for `main_module` and queues it to run.
+  pub async fn spawn(
+    &mut self,
+    main_module: ModuleSpecifier,
+    permissions: PermissionsContainer,
+    extensions: Vec<Extension>,
+    limits: WorkerLimits,
+  ) -> Result<PoolWorkerId, AnyError> {
+    let mut worker = self
+      .factory
+      .create_worker_with_limits(
+        WorkerExecutionMode::Run,
+        main_module,
+        permissions,
+        extensions,
+        Default::default(),
+        Some(limits.into()),
+      )
+      .await?;
+
+    let id = PoolWorkerId(self.next_id);
+    self.next_id += 1;
+    if let Some(host_channel) = worker.take_host_channel() {
+      self.host_channels.insert(id, host_channel);
+    }
+    self.running.push(
+      async move {
+        let result = worker.run().await;
+        (id, result)
+      }
+      .boxed_local(),
+    );
+    Ok(id)
+  }
+
+  /// Takes the Rust side of the `Deno.host` message channel of a worker, see
+  /// [`CliMainWorker::take_host_channel`].
+  pub fn take_host_channel(&mut self, id: PoolWorkerId) -> Option<HostChannel> {
+    self.host_channels.remove(&id)
+  }
+
+  /// Number of workers that haven't finished yet.
+  pub fn len(&self) -> usize {
+    self.running.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.running.is_empty()
+  }
+
+  /// Drives all workers until the next one finishes and returns its exit
+  /// code. Returns `None` once no workers are left.
+  pub async fn next(
+    &mut self,
+  ) -> Option<(PoolWorkerId, Result<i32, AnyError>)> {
+    self.running.next().await
+  }
+
+  /// Runs all workers to completion, in the order they finish.
+  pub async fn join_all(
+    mut self,
+  ) -> Vec<(PoolWorkerId, Result<i32, AnyError>)> {
+    let mut results = Vec::with_capacity(self.running.len());
+    while let Some(result) = self.next().await {
+      results.push(result);
+    }
+    results
+  }
 }
 
 // TODO(bartlomieju): this should be moved to some other place, added to avoid string
@@ -429,7 +1940,10 @@ impl CliMainWorkerFactory {
     Self {
       shared: Arc::new(SharedWorkerState {
         blob_store,
-        broadcast_channel: Default::default(),
+        broadcast_channel: options
+          .broadcast_channel
+          .clone()
+          .unwrap_or_default(),
         code_cache,
         compiled_wasm_module_store: Default::default(),
         feature_checker,
@@ -475,8 +1989,36 @@ impl CliMainWorkerFactory {
     permissions: PermissionsContainer,
     custom_extensions: Vec<Extension>,
     stdio: deno_runtime::deno_io::Stdio,
+  ) -> Result<CliMainWorker, AnyError> {
+    self
+      .create_worker_with_limits(
+        mode,
+        main_module,
+        permissions,
+        custom_extensions,
+        stdio,
+        self.shared.options.execution_limits.clone(),
+      )
+      .await
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  async fn create_worker_with_limits(
+    &self,
+    mode: WorkerExecutionMode,
+    main_module: ModuleSpecifier,
+    permissions: PermissionsContainer,
+    mut custom_extensions: Vec<Extension>,
+    stdio: deno_runtime::deno_io::Stdio,
+    limits: Option<ExecutionLimits>,
   ) -> Result<CliMainWorker, AnyError> {
     let shared = &self.shared;
+    let create_params = match limits.as_ref().and_then(|l| l.heap_bytes) {
+      Some(heap_bytes) => {
+        Some(v8::CreateParams::default().heap_limits(0, heap_bytes))
+      }
+      None => create_isolate_create_params(),
+    };
     let CreateModuleLoaderResult {
       module_loader,
       node_require_loader,
@@ -536,12 +2078,21 @@ impl CliMainWorkerFactory {
     });
     let cache_storage_dir = maybe_storage_key.map(|key| {
       // TODO(@satyarohith): storage quota management
-      get_cache_storage_dir().join(checksum::gen(&[key.as_bytes()]))
+      shared
+        .cache_storage_dir()
+        .join(checksum::gen(&[key.as_bytes()]))
     });
 
+    let unstable_api_guard = shared
+      .options
+      .unstable_api_errors
+      .then(UnstableApiGuard::default);
     // TODO(bartlomieju): this is cruft, update FeatureChecker to spit out
     // list of enabled features.
-    let feature_checker = shared.feature_checker.clone();
+    let feature_checker = match &unstable_api_guard {
+      Some(guard) => guard.feature_checker(&shared.feature_checker, false),
+      None => shared.feature_checker.clone(),
+    };
     let mut unstable_features =
       Vec::with_capacity(crate::UNSTABLE_GRANULAR_FLAGS.len());
     for granular_flag in crate::UNSTABLE_GRANULAR_FLAGS {
@@ -550,6 +2101,44 @@ impl CliMainWorkerFactory {
       }
     }
 
+    let host_channel = if shared.options.host_channel {
+      let (host_channel, extension) =
+        crate::host::create_host_channel(shared.options.restored_state.clone());
+      custom_extensions.push(extension);
+      Some(host_channel)
+    } else {
+      None
+    };
+    let serve_adapter = if shared.options.serve_adapter {
+      let (serve_adapter, extension) =
+        crate::serve_adapter::create_serve_adapter();
+      custom_extensions.push(extension);
+      Some(serve_adapter)
+    } else {
+      None
+    };
+    if let Some(determinism) = &shared.options.determinism {
+      custom_extensions.push(crate::determinism::create_determinism_extension(
+        determinism.clone(),
+      ));
+    }
+    // `Deno.host.call()` reaches the functions registered after the worker
+    // was created
+    let host_fns_enabled =
+      shared.options.host_channel || !shared.options.host_fns.is_empty();
+    if host_fns_enabled {
+      custom_extensions.push(crate::host_fn::create_host_fn_extension(
+        shared.options.host_fns.clone(),
+      ));
+    }
+    if let Some(create_wasi_extension) = &shared.options.create_wasi_extension {
+      custom_extensions.push(create_wasi_extension());
+    }
+    // ops of extensions that aren't part of the snapshot still need to be
+    // registered
+    let skip_op_registration =
+      shared.options.skip_op_registration && custom_extensions.is_empty();
+
     let services = WorkerServiceOptions {
       root_cert_store_provider: Some(shared.root_cert_store_provider.clone()),
       module_loader,
@@ -561,6 +2150,15 @@ impl CliMainWorkerFactory {
       blob_store: shared.blob_store.clone(),
       broadcast_channel: shared.broadcast_channel.clone(),
       fetch_dns_resolver: Default::default(),
+      fetch_interceptor: shared.options.fetch_interceptor.clone(),
+      connect_interceptor: shared.options.connect_interceptor.clone(),
+      spawn_interceptor: shared.options.spawn_interceptor.clone(),
+      dlopen_interceptor: shared.options.dlopen_interceptor.clone(),
+      kv_backend: shared.options.kv_backend.clone(),
+      worker_creation_policy: shared.options.worker_creation_policy.clone(),
+      virtual_env: shared.options.virtual_env.clone(),
+      signal_forwarder: shared.options.signal_forwarder.clone(),
+      resource_quotas: shared.options.resource_quotas.clone(),
       shared_array_buffer_store: Some(shared.shared_array_buffer_store.clone()),
       compiled_wasm_module_store: Some(
         shared.compiled_wasm_module_store.clone(),
@@ -599,14 +2197,17 @@ impl CliMainWorkerFactory {
         otel_config: shared.otel_config.clone(),
       },
       extensions: custom_extensions,
-      startup_snapshot: crate::js::deno_isolate_init(),
-      create_params: create_isolate_create_params(),
+      startup_snapshot: shared
+        .options
+        .startup_snapshot
+        .or_else(crate::js::deno_isolate_init),
+      create_params,
       unsafely_ignore_certificate_errors: shared
         .options
         .unsafely_ignore_certificate_errors
         .clone(),
       seed: shared.options.seed,
-      format_js_error_fn: Some(Arc::new(format_js_error)),
+      format_js_error_fn: Some(Arc::new(errors::format_js_error)),
       create_web_worker_cb,
       maybe_inspector_server,
       should_break_on_first_statement: shared.options.inspect_brk,
@@ -616,9 +2217,8 @@ impl CliMainWorkerFactory {
       cache_storage_dir,
       origin_storage_dir,
       stdio,
-      skip_op_registration: shared.options.skip_op_registration,
-      enable_stack_trace_arg_in_ops: crate::args::has_trace_permissions_enabled(
-      ),
+      skip_op_registration,
+      enable_stack_trace_arg_in_ops: enable_stack_trace_arg_in_ops(),
     };
 
     let mut worker = MainWorker::bootstrap_from_options(
@@ -644,10 +2244,74 @@ impl CliMainWorkerFactory {
       );
     }
 
+    if host_fns_enabled {
+      worker.js_runtime.lazy_load_es_module_with_code(
+        "ext:cli/40_host_fn.js",
+        deno_core::ascii_str_include!("js/40_host_fn.js"),
+      )?;
+    }
+
+    if shared.options.create_wasi_extension.is_some() {
+      worker.js_runtime.lazy_load_es_module_with_code(
+        "ext:cli/40_wasi.js",
+        deno_core::ascii_str_include!("js/40_wasi.js"),
+      )?;
+    }
+
+    let host_exports = if host_channel.is_some() {
+      let namespace = worker.js_runtime.lazy_load_es_module_with_code(
+        "ext:cli/40_host.js",
+        deno_core::ascii_str_include!("js/40_host.js"),
+      )?;
+      Some(module_exports(&mut worker.js_runtime, namespace)?)
+    } else {
+      None
+    };
+
+    let serve_exports = if serve_adapter.is_some() {
+      let namespace = worker.js_runtime.lazy_load_es_module_with_code(
+        "ext:cli/40_serve_adapter.js",
+        deno_core::ascii_str_include!("js/40_serve_adapter.js"),
+      )?;
+      Some(module_exports(&mut worker.js_runtime, namespace)?)
+    } else {
+      None
+    };
+
+    let clock = if shared.options.determinism.is_some() {
+      let namespace = worker.js_runtime.lazy_load_es_module_with_code(
+        "ext:cli/40_determinism.js",
+        deno_core::ascii_str_include!("js/40_determinism.js"),
+      )?;
+      Some(module_exports(&mut worker.js_runtime, namespace)?)
+    } else {
+      None
+    };
+
+    if let Some(controller) = &shared.options.inspector_controller {
+      controller.register(&mut worker.js_runtime);
+    }
+
+    let limit_enforcer =
+      limits.map(|limits| LimitEnforcer::install(&mut worker, limits));
+    if let Some(guard) = &unstable_api_guard {
+      guard.install(&mut worker.js_runtime);
+    }
+
     Ok(CliMainWorker {
       main_module,
       worker,
       shared: shared.clone(),
+      host_channel,
+      host_exports,
+      serve_adapter,
+      serve_exports,
+      limit_enforcer,
+      unstable_api_guard,
+      clock,
+      pause_flag: None,
+      stats: None,
+      progress: None,
     })
   }
 
@@ -735,12 +2399,21 @@ fn create_web_worker_callback(
       .resolve_storage_key(&args.main_module);
     let cache_storage_dir = maybe_storage_key.map(|key| {
       // TODO(@satyarohith): storage quota management
-      get_cache_storage_dir().join(checksum::gen(&[key.as_bytes()]))
+      shared
+        .cache_storage_dir()
+        .join(checksum::gen(&[key.as_bytes()]))
     });
 
+    let unstable_api_guard = shared
+      .options
+      .unstable_api_errors
+      .then(UnstableApiGuard::default);
     // TODO(bartlomieju): this is cruft, update FeatureChecker to spit out
     // list of enabled features.
-    let feature_checker = shared.feature_checker.clone();
+    let feature_checker = match &unstable_api_guard {
+      Some(guard) => guard.feature_checker(&shared.feature_checker, true),
+      None => shared.feature_checker.clone(),
+    };
     let mut unstable_features =
       Vec::with_capacity(crate::UNSTABLE_GRANULAR_FLAGS.len());
     for granular_flag in crate::UNSTABLE_GRANULAR_FLAGS {
@@ -766,6 +2439,15 @@ fn create_web_worker_callback(
       feature_checker,
       npm_process_state_provider: Some(shared.npm_process_state_provider()),
       permissions: args.permissions,
+      fetch_interceptor: shared.options.fetch_interceptor.clone(),
+      connect_interceptor: shared.options.connect_interceptor.clone(),
+      spawn_interceptor: shared.options.spawn_interceptor.clone(),
+      dlopen_interceptor: shared.options.dlopen_interceptor.clone(),
+      kv_backend: shared.options.kv_backend.clone(),
+      worker_creation_policy: shared.options.worker_creation_policy.clone(),
+      virtual_env: shared.options.virtual_env.clone(),
+      signal_forwarder: shared.options.signal_forwarder.clone(),
+      resource_quotas: shared.options.resource_quotas.clone(),
     };
     let options = WebWorkerOptions {
       name: args.name,
@@ -798,7 +2480,12 @@ fn create_web_worker_callback(
         serve_host: shared.options.serve_host.clone(),
         otel_config: shared.otel_config.clone(),
       },
-      extensions: vec![],
+      extensions: shared
+        .options
+        .web_worker_extensions
+        .as_ref()
+        .map(|create_extensions| create_extensions())
+        .unwrap_or_default(),
       startup_snapshot: crate::js::deno_isolate_init(),
       create_params: create_isolate_create_params(),
       unsafely_ignore_certificate_errors: shared
@@ -807,7 +2494,7 @@ fn create_web_worker_callback(
         .clone(),
       seed: shared.options.seed,
       create_web_worker_cb,
-      format_js_error_fn: Some(Arc::new(format_js_error)),
+      format_js_error_fn: Some(Arc::new(errors::format_js_error)),
       worker_type: args.worker_type,
       get_error_class_fn: Some(&errors::get_error_class_name),
       stdio: stdio.clone(),
@@ -815,14 +2502,25 @@ fn create_web_worker_callback(
       strace_ops: shared.options.strace_ops.clone(),
       close_on_idle: args.close_on_idle,
       maybe_worker_metadata: args.maybe_worker_metadata,
-      enable_stack_trace_arg_in_ops: crate::args::has_trace_permissions_enabled(
-      ),
+      enable_stack_trace_arg_in_ops: enable_stack_trace_arg_in_ops(),
     };
 
-    WebWorker::bootstrap_from_options(services, options)
+    let (mut worker, handle) =
+      WebWorker::bootstrap_from_options(services, options);
+    if let Some(guard) = &unstable_api_guard {
+      guard.install(&mut worker.js_runtime);
+    }
+    (worker, handle)
   })
 }
 
+/// Stack traces are passed to ops when permission checks are traced or
+/// audited, so they can point at the JavaScript code requesting access.
+fn enable_stack_trace_arg_in_ops() -> bool {
+  crate::args::has_trace_permissions_enabled()
+    || deno_runtime::deno_permissions::audit::has_auditor()
+}
+
 /// By default V8 uses 1.4Gb heap limit which is meant for browser tabs.
 /// Instead probe for the total memory on the system and use it instead
 /// as a default.
@@ -871,6 +2569,15 @@ mod tests {
         npm_process_state_provider: Default::default(),
         root_cert_store_provider: Default::default(),
         fetch_dns_resolver: Default::default(),
+        fetch_interceptor: Default::default(),
+        connect_interceptor: Default::default(),
+        spawn_interceptor: Default::default(),
+        dlopen_interceptor: Default::default(),
+        kv_backend: Default::default(),
+        worker_creation_policy: Default::default(),
+        virtual_env: Default::default(),
+        signal_forwarder: Default::default(),
+        resource_quotas: Default::default(),
         shared_array_buffer_store: Default::default(),
         compiled_wasm_module_store: Default::default(),
         v8_code_cache: Default::default(),
@@ -911,6 +2618,64 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn collects_worker_stats() {
+    let mut worker = create_test_worker();
+    worker
+      .execute_script(
+        "[test.js]",
+        deno_core::ascii_str!(
+          "globalThis.buf = new ArrayBuffer(1024 * 1024);
+          setTimeout(() => {}, 10);"
+        )
+        .into(),
+      )
+      .unwrap();
+    let stats = collect_worker_stats(&mut worker.js_runtime);
+    assert!(stats.heap_used > 0);
+    assert!(stats.heap_used <= stats.heap_total);
+    assert!(stats.external_memory >= 1024 * 1024);
+    assert_eq!(stats.timers, 1);
+    worker.run_event_loop(false).await.unwrap();
+    let stats = collect_worker_stats(&mut worker.js_runtime);
+    assert_eq!(stats.timers, 0);
+  }
+
+  #[tokio::test]
+  async fn reports_progress() {
+    let mut worker = create_test_worker();
+    worker
+      .js_runtime
+      .v8_isolate()
+      .set_promise_hook(count_promise_job);
+    let (sender, mut receiver) = broadcast::channel(PROGRESS_CHANNEL_CAPACITY);
+    let reporter = ProgressReporter {
+      sender,
+      started: Instant::now(),
+      event_loop_turns: 1,
+      promise_jobs_baseline: PROMISE_JOBS.get(),
+    };
+    worker
+      .execute_script(
+        "[test.js]",
+        deno_core::ascii_str!(
+          "Promise.resolve().then(() => {});
+          setTimeout(() => {}, 10);"
+        )
+        .into(),
+      )
+      .unwrap();
+    sample_worker_stats(&mut worker.js_runtime, None, Some(&reporter));
+    let progress = receiver.try_recv().unwrap();
+    assert_eq!(progress.event_loop_turns, 1);
+    assert_eq!(progress.timers, 1);
+    worker.run_event_loop(false).await.unwrap();
+    sample_worker_stats(&mut worker.js_runtime, None, Some(&reporter));
+    let progress = receiver.try_recv().unwrap();
+    assert!(progress.promise_jobs >= 1);
+    assert_eq!(progress.timers, 0);
+  }
+
   #[tokio::test]
   async fn execute_mod_resolve_error() {
     // "foo" is not a valid module specifier so this should return an error.
@@ -932,4 +2697,43 @@ mod tests {
     let result = worker.execute_main_module(&module_specifier).await;
     assert!(result.is_ok());
   }
+
+  #[tokio::test]
+  async fn set_and_get_global() {
+    let mut worker = create_test_worker();
+    let config = serde_json::json!({ "region": "eu", "retries": 3 });
+    set_global(&mut worker.js_runtime, "config", &config).unwrap();
+    worker
+      .execute_script(
+        "[test.js]",
+        deno_core::ascii_str!("globalThis.retries = config.retries + 1;")
+          .into(),
+      )
+      .unwrap();
+    let retries = get_global::<u32>(&mut worker.js_runtime, "retries").unwrap();
+    assert_eq!(retries, Some(4));
+    let missing =
+      get_global::<serde_json::Value>(&mut worker.js_runtime, "missing")
+        .unwrap();
+    assert_eq!(missing, None);
+  }
+
+  #[tokio::test]
+  async fn transfer_and_take_buffer() {
+    let mut worker = create_test_worker();
+    let data = Bytes::from(vec![1, 2, 3]);
+    transfer_buffer(&mut worker.js_runtime, "input", data).unwrap();
+    worker
+      .execute_script(
+        "[test.js]",
+        deno_core::ascii_str!(
+          "globalThis.output = new Uint8Array(input).map((byte) => byte * 2);"
+        )
+        .into(),
+      )
+      .unwrap();
+    let output = take_buffer(&mut worker.js_runtime, "output").unwrap();
+    assert_eq!(output.as_deref(), Some(&[2, 4, 6][..]));
+    assert_eq!(take_buffer(&mut worker.js_runtime, "output").unwrap(), None);
+  }
 }
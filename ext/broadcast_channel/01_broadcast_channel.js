@@ -4,6 +4,7 @@
 
 import { core, primordials } from "ext:core/mod.js";
 import {
+  op_broadcast_has_host_subscribers,
   op_broadcast_recv,
   op_broadcast_send,
   op_broadcast_subscribe,
@@ -13,6 +14,8 @@ const {
   ArrayPrototypeIndexOf,
   ArrayPrototypePush,
   ArrayPrototypeSplice,
+  JSONParse,
+  JSONStringify,
   ObjectPrototypeIsPrototypeOf,
   Symbol,
   SymbolFor,
@@ -44,15 +47,16 @@ async function recv() {
       break;
     }
 
-    const { 0: name, 1: data } = message;
-    dispatch(null, name, new Uint8Array(data));
+    const { 0: name, 1: data, 2: json } = message;
+    dispatch(null, name, new Uint8Array(data), json);
   }
 
   core.close(rid);
   rid = null;
 }
 
-function dispatch(source, name, data) {
+// Messages published by the host are only sent as JSON.
+function dispatch(source, name, data, json = null) {
   for (let i = 0; i < channels.length; ++i) {
     const channel = channels[i];
 
@@ -63,7 +67,8 @@ function dispatch(source, name, data) {
     const go = () => {
       if (channel[_closed]) return;
       const event = new MessageEvent("message", {
-        data: core.deserialize(data), // TODO(bnoordhuis) Cache immutables.
+        // TODO(bnoordhuis) Cache immutables.
+        data: json !== null ? JSONParse(json) : core.deserialize(data),
         origin: "http://127.0.0.1",
       });
       setIsTrusted(event, true);
@@ -121,10 +126,13 @@ class BroadcastChannel extends EventTarget {
     // Send to other listeners in this VM.
     dispatch(this, this[_name], new Uint8Array(data));
 
-    // Send to listeners in other VMs.
+    // Send to listeners in other VMs, and as JSON to the host if it listens.
+    const json = op_broadcast_has_host_subscribers()
+      ? toJson(message)
+      : null;
     defer(() => {
       if (!this[_closed]) {
-        op_broadcast_send(rid, this[_name], data);
+        op_broadcast_send(rid, this[_name], data, json);
       }
     });
   }
@@ -158,6 +166,15 @@ class BroadcastChannel extends EventTarget {
   }
 }
 
+function toJson(message) {
+  try {
+    return JSONStringify(message) ?? null;
+  } catch {
+    // not representable as JSON, eg. a BigInt or a cycle
+    return null;
+  }
+}
+
 defineEventHandler(BroadcastChannel.prototype, "message");
 defineEventHandler(BroadcastChannel.prototype, "messageerror");
 const BroadcastChannelPrototype = BroadcastChannel.prototype;
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use uuid::Uuid;
@@ -11,8 +12,14 @@ use uuid::Uuid;
 use crate::BroadcastChannel;
 use crate::BroadcastChannelError;
 
+/// The channels of the scripts of every worker sharing it, and of the host
+/// through [`InMemoryBroadcastChannel::publish`] and
+/// [`InMemoryBroadcastChannel::subscribe_host`].
 #[derive(Clone)]
-pub struct InMemoryBroadcastChannel(Arc<Mutex<broadcast::Sender<Message>>>);
+pub struct InMemoryBroadcastChannel {
+  tx: Arc<Mutex<broadcast::Sender<Message>>>,
+  host_subscribers: Arc<Mutex<Vec<HostSubscriber>>>,
+}
 
 pub struct InMemoryBroadcastChannelResource {
   rx: tokio::sync::Mutex<(
@@ -27,13 +34,90 @@ pub struct InMemoryBroadcastChannelResource {
 struct Message {
   name: Arc<String>,
   data: Arc<Vec<u8>>,
+  json: Option<Arc<String>>,
   uuid: Uuid,
 }
 
+struct HostSubscriber {
+  name: String,
+  tx: mpsc::Sender<serde_json::Value>,
+}
+
+/// Receives the messages sent on a channel, see
+/// [`InMemoryBroadcastChannel::subscribe_host`].
+pub struct HostSubscription {
+  rx: mpsc::Receiver<serde_json::Value>,
+}
+
+impl HostSubscription {
+  /// Returns `None` once every clone of the channel was dropped.
+  pub async fn recv(&mut self) -> Option<serde_json::Value> {
+    self.rx.recv().await
+  }
+}
+
 impl Default for InMemoryBroadcastChannel {
   fn default() -> Self {
     let (tx, _) = broadcast::channel(256);
-    Self(Arc::new(Mutex::new(tx)))
+    Self {
+      tx: Arc::new(Mutex::new(tx)),
+      host_subscribers: Default::default(),
+    }
+  }
+}
+
+impl InMemoryBroadcastChannel {
+  /// Sends `value` to the `BroadcastChannel`s named `name` of the scripts and
+  /// to the host subscriptions of the channel.
+  ///
+  /// Waits while a host subscription has `capacity` messages it didn't
+  /// receive yet. Scripts that fall more than 256 messages behind miss
+  /// messages instead.
+  pub async fn publish(&self, name: &str, value: &serde_json::Value) {
+    let message = Message {
+      name: Arc::new(name.to_string()),
+      data: Default::default(),
+      json: Some(Arc::new(value.to_string())),
+      uuid: Uuid::nil(),
+    };
+    // fails when no script subscribed, which is fine
+    let _ = self.tx.lock().send(message);
+    self.send_to_host(name, value).await;
+  }
+
+  /// Receives the messages sent on the channel `name` by scripts and by
+  /// [`publish`](Self::publish), until the subscription is dropped.
+  ///
+  /// Only receives the messages of scripts that can be represented as JSON.
+  /// Senders wait while `capacity` messages weren't received yet, it must be
+  /// greater than zero.
+  pub fn subscribe_host(
+    &self,
+    name: impl Into<String>,
+    capacity: usize,
+  ) -> HostSubscription {
+    let (tx, rx) = mpsc::channel(capacity);
+    self.host_subscribers.lock().push(HostSubscriber {
+      name: name.into(),
+      tx,
+    });
+    HostSubscription { rx }
+  }
+
+  async fn send_to_host(&self, name: &str, value: &serde_json::Value) {
+    let senders = {
+      let mut subscribers = self.host_subscribers.lock();
+      subscribers.retain(|subscriber| !subscriber.tx.is_closed());
+      subscribers
+        .iter()
+        .filter(|subscriber| subscriber.name == name)
+        .map(|subscriber| subscriber.tx.clone())
+        .collect::<Vec<_>>()
+    };
+    for tx in senders {
+      // the subscription was dropped in the meantime
+      let _ = tx.send(value.clone()).await;
+    }
   }
 }
 
@@ -43,7 +127,7 @@ impl BroadcastChannel for InMemoryBroadcastChannel {
 
   fn subscribe(&self) -> Result<Self::Resource, BroadcastChannelError> {
     let (cancel_tx, cancel_rx) = mpsc::unbounded_channel();
-    let broadcast_rx = self.0.lock().subscribe();
+    let broadcast_rx = self.tx.lock().subscribe();
     let rx = tokio::sync::Mutex::new((broadcast_rx, cancel_rx));
     let uuid = Uuid::new_v4();
     Ok(Self::Resource {
@@ -65,14 +149,31 @@ impl BroadcastChannel for InMemoryBroadcastChannel {
     resource: &Self::Resource,
     name: String,
     data: Vec<u8>,
+    json: Option<String>,
   ) -> Result<(), BroadcastChannelError> {
-    let name = Arc::new(name);
-    let data = Arc::new(data);
-    let uuid = resource.uuid;
-    self.0.lock().send(Message { name, data, uuid })?;
+    let host_value = json
+      .as_ref()
+      .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok());
+    let message = Message {
+      name: Arc::new(name),
+      data: Arc::new(data),
+      // scripts only receive the JSON of messages published by the host
+      json: None,
+      uuid: resource.uuid,
+    };
+    self.tx.lock().send(message.clone())?;
+    if let Some(value) = host_value {
+      self.send_to_host(&message.name, &value).await;
+    }
     Ok(())
   }
 
+  fn has_host_subscribers(&self) -> bool {
+    let mut subscribers = self.host_subscribers.lock();
+    subscribers.retain(|subscriber| !subscriber.tx.is_closed());
+    !subscribers.is_empty()
+  }
+
   async fn recv(
     &self,
     resource: &Self::Resource,
@@ -92,7 +193,8 @@ impl BroadcastChannel for InMemoryBroadcastChannel {
         Ok(message) => {
           let name = String::clone(&message.name);
           let data = Vec::clone(&message.data);
-          return Ok(Some((name, data)));
+          let json = message.json.as_deref().cloned();
+          return Ok(Some((name, data, json)));
         }
       }
     }
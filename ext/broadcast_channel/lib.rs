@@ -2,6 +2,7 @@
 
 mod in_memory_broadcast_channel;
 
+pub use in_memory_broadcast_channel::HostSubscription;
 pub use in_memory_broadcast_channel::InMemoryBroadcastChannel;
 pub use in_memory_broadcast_channel::InMemoryBroadcastChannelResource;
 
@@ -67,15 +68,23 @@ pub trait BroadcastChannel: Clone {
     resource: &Self::Resource,
     name: String,
     data: Vec<u8>,
+    json: Option<String>,
   ) -> Result<(), BroadcastChannelError>;
 
   async fn recv(
     &self,
     resource: &Self::Resource,
   ) -> Result<Option<Message>, BroadcastChannelError>;
+
+  /// Whether the host receives messages, so scripts send them as JSON too.
+  fn has_host_subscribers(&self) -> bool {
+    false
+  }
 }
 
-pub type Message = (String, Vec<u8>);
+/// The name of the channel, the data serialized with the structured clone
+/// algorithm, and the data as JSON for messages published by the host.
+pub type Message = (String, Vec<u8>, Option<String>);
 
 #[op2(fast)]
 #[smi]
@@ -115,6 +124,7 @@ pub async fn op_broadcast_send<BC>(
   #[smi] rid: ResourceId,
   #[string] name: String,
   #[buffer] buf: JsBuffer,
+  #[string] json: Option<String>,
 ) -> Result<(), BroadcastChannelError>
 where
   BC: BroadcastChannel + 'static,
@@ -125,7 +135,15 @@ where
     .get::<BC::Resource>(rid)
     .map_err(BroadcastChannelError::Resource)?;
   let bc = state.borrow().borrow::<BC>().clone();
-  bc.send(&resource, name, buf.to_vec()).await
+  bc.send(&resource, name, buf.to_vec(), json).await
+}
+
+#[op2(fast)]
+pub fn op_broadcast_has_host_subscribers<BC>(state: &mut OpState) -> bool
+where
+  BC: BroadcastChannel + 'static,
+{
+  state.borrow::<BC>().has_host_subscribers()
 }
 
 #[op2(async)]
@@ -154,6 +172,7 @@ deno_core::extension!(deno_broadcast_channel,
     op_broadcast_unsubscribe<BC>,
     op_broadcast_send<BC>,
     op_broadcast_recv<BC>,
+    op_broadcast_has_host_subscribers<BC>,
   ],
   esm = [ "01_broadcast_channel.js" ],
   options = {
@@ -41,7 +41,12 @@ use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
+use deno_permissions::quota::take_quota;
 use deno_permissions::PermissionCheckError;
+use deno_permissions::QuotaExceededError;
+use deno_permissions::QuotaKind;
 use deno_tls::rustls::RootCertStore;
 use deno_tls::Proxy;
 use deno_tls::RootCertStoreProvider;
@@ -79,6 +84,8 @@ use tower_http::decompression::Decompression;
 // Re-export data_url
 pub use data_url;
 pub use proxy::basic_auth;
+pub use proxy::ProxyConfig;
+pub use proxy::ProxyRule;
 
 pub use fs_fetch_handler::FsFetchHandler;
 
@@ -105,6 +112,9 @@ pub struct Options {
   pub client_cert_chain_and_key: TlsKeys,
   pub file_fetch_handler: Rc<dyn FetchHandler>,
   pub resolver: dns::Resolver,
+  /// Sees every HTTP(S) request made with `fetch()` or `node:http` before it
+  /// is sent.
+  pub interceptor: Option<Arc<dyn FetchInterceptor>>,
 }
 
 impl Options {
@@ -130,6 +140,7 @@ impl Default for Options {
       client_cert_chain_and_key: TlsKeys::Null,
       file_fetch_handler: Rc::new(DefaultFileFetchHandler),
       resolver: dns::Resolver::default(),
+      interceptor: None,
     }
   }
 }
@@ -168,6 +179,8 @@ pub enum FetchError {
   Resource(deno_core::error::AnyError),
   #[error(transparent)]
   Permission(#[from] PermissionCheckError),
+  #[error(transparent)]
+  Quota(#[from] QuotaExceededError),
   #[error("NetworkError when attempting to fetch resource")]
   NetworkError,
   #[error("Fetching files only supports the GET method: received {0}")]
@@ -200,6 +213,10 @@ pub enum FetchError {
   ClientSend(#[from] ClientSendError),
   #[error(transparent)]
   RequestBuilderHook(deno_core::error::AnyError),
+  #[error("Request to {0} was denied: {1}")]
+  Denied(Uri, String),
+  #[error(transparent)]
+  Interceptor(deno_core::error::AnyError),
   #[error(transparent)]
   Io(#[from] std::io::Error),
   // Only used for node upgrade
@@ -223,6 +240,57 @@ pub trait FetchHandler: dyn_clone::DynClone {
 
 dyn_clone::clone_trait_object!(FetchHandler);
 
+/// The outcome of intercepting a request with a [`FetchInterceptor`].
+pub enum FetchInterception {
+  /// Sends the request, which may have been modified.
+  Send(http::Request<ReqBody>),
+  /// Resolves the `fetch()` call with this response instead of sending the
+  /// request.
+  Respond(http::Response<ResBody>),
+  /// Rejects the `fetch()` call with a `TypeError` containing the reason.
+  Deny(String),
+}
+
+pub type FetchInterceptFuture = Pin<
+  Box<
+    dyn Future<Output = Result<FetchInterception, deno_core::error::AnyError>>,
+  >,
+>;
+
+/// Inspects, mocks, rewrites or denies the HTTP(S) requests made with
+/// `fetch()` or `node:http`. Net permissions are checked against the original
+/// URL before the interceptor is called.
+pub trait FetchInterceptor: Send + Sync {
+  fn intercept(&self, request: http::Request<ReqBody>) -> FetchInterceptFuture;
+}
+
+/// Sends `request` with `client`, after passing it to the interceptor, if
+/// any.
+pub async fn send_intercepted(
+  client: Client,
+  request: http::Request<ReqBody>,
+  maybe_interceptor: Option<Arc<dyn FetchInterceptor>>,
+) -> Result<http::Response<ResBody>, FetchError> {
+  let request = match maybe_interceptor {
+    Some(interceptor) => {
+      let uri = request.uri().clone();
+      match interceptor
+        .intercept(request)
+        .await
+        .map_err(FetchError::Interceptor)?
+      {
+        FetchInterception::Send(request) => request,
+        FetchInterception::Respond(response) => return Ok(response),
+        FetchInterception::Deny(reason) => {
+          return Err(FetchError::Denied(uri, reason))
+        }
+      }
+    }
+    None => request,
+  };
+  client.send(request).map_err(FetchError::from).await
+}
+
 /// A default implementation which will error for every request.
 #[derive(Clone)]
 pub struct DefaultFileFetchHandler;
@@ -272,6 +340,7 @@ pub fn create_client_from_options(
         .map_err(HttpClientCreateError::RootCertStore)?,
       ca_certs: vec![],
       proxy: options.proxy.clone(),
+      proxy_config: None,
       dns_resolver: options.resolver.clone(),
       unsafely_ignore_certificate_errors: options
         .unsafely_ignore_certificate_errors
@@ -541,14 +610,15 @@ where
         request_builder_hook(&mut request)
           .map_err(FetchError::RequestBuilderHook)?;
       }
+      let maybe_interceptor = options.interceptor.clone();
+      // the request counts as a socket until its response is closed
+      let permit = acquire_quota(state, QuotaKind::Sockets)?;
 
       let cancel_handle = CancelHandle::new_rc();
       let cancel_handle_ = cancel_handle.clone();
 
       let fut = async move {
-        client
-          .send(request)
-          .map_err(Into::into)
+        send_intercepted(client, request, maybe_interceptor)
           .or_cancel(cancel_handle_)
           .await
       };
@@ -557,6 +627,7 @@ where
         future: Box::pin(fut),
         url,
       });
+      hold_quota(state, request_rid, permit);
 
       let cancel_handle_rid =
         state.resource_table.add(FetchCancelHandle(cancel_handle));
@@ -624,11 +695,14 @@ pub async fn op_fetch_send(
   state: Rc<RefCell<OpState>>,
   #[smi] rid: ResourceId,
 ) -> Result<FetchResponse, FetchError> {
-  let request = state
-    .borrow_mut()
-    .resource_table
-    .take::<FetchRequestResource>(rid)
-    .map_err(FetchError::Resource)?;
+  let (request, permit) = {
+    let mut state = state.borrow_mut();
+    let request = state
+      .resource_table
+      .take::<FetchRequestResource>(rid)
+      .map_err(FetchError::Resource)?;
+    (request, take_quota(&mut state, rid))
+  };
 
   let request = Rc::try_unwrap(request)
     .ok()
@@ -678,10 +752,14 @@ pub async fn op_fetch_send(
     (None, None)
   };
 
-  let response_rid = state
-    .borrow_mut()
-    .resource_table
-    .add(FetchResponseResource::new(res, content_length));
+  let response_rid = {
+    let mut state = state.borrow_mut();
+    let response_rid = state
+      .resource_table
+      .add(FetchResponseResource::new(res, content_length));
+    hold_quota(&mut state, response_rid, permit);
+    response_rid
+  };
 
   Ok(FetchResponse {
     status: status.as_u16(),
@@ -899,6 +977,7 @@ where
         .map_err(HttpClientCreateError::RootCertStore)?,
       ca_certs,
       proxy: args.proxy,
+      proxy_config: None,
       dns_resolver: if args.use_hickory_resolver {
         dns::Resolver::hickory()
           .map_err(deno_core::error::AnyError::new)
@@ -938,6 +1017,8 @@ pub struct CreateHttpClientOptions {
   pub root_cert_store: Option<RootCertStore>,
   pub ca_certs: Vec<Vec<u8>>,
   pub proxy: Option<Proxy>,
+  /// Proxies used instead of the ones of the environment variables.
+  pub proxy_config: Option<ProxyConfig>,
   pub dns_resolver: dns::Resolver,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub client_cert_chain_and_key: Option<TlsKey>,
@@ -954,6 +1035,7 @@ impl Default for CreateHttpClientOptions {
       root_cert_store: None,
       ca_certs: vec![],
       proxy: None,
+      proxy_config: None,
       dns_resolver: dns::Resolver::default(),
       unsafely_ignore_certificate_errors: None,
       client_cert_chain_and_key: None,
@@ -1025,7 +1107,11 @@ pub fn create_http_client(
     builder = client_builder_hook(builder);
   }
 
-  let mut proxies = proxy::from_env();
+  let mut proxies = match &options.proxy_config {
+    Some(proxy_config) => proxy::from_config(proxy_config)
+      .ok_or(HttpClientCreateError::InvalidProxyUrl)?,
+    None => proxy::from_env(),
+  };
   if let Some(proxy) = options.proxy {
     let mut intercept = proxy::Intercept::all(&proxy.url)
       .ok_or_else(|| HttpClientCreateError::InvalidProxyUrl)?;
@@ -52,6 +52,8 @@ pub(crate) struct Proxies {
 pub(crate) struct Intercept {
   filter: Filter,
   target: Target,
+  /// Only intercepts requests to these hosts when set.
+  hosts: Option<Arc<NoProxy>>,
 }
 
 #[derive(Clone)]
@@ -107,6 +109,53 @@ pub(crate) fn from_env() -> Proxies {
   Proxies { intercepts, no }
 }
 
+/// Proxies of an HTTP client, used instead of the `ALL_PROXY`,
+/// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` environment variables.
+#[derive(Debug, Clone, Default)]
+pub struct ProxyConfig {
+  /// Proxies of the requests to specific hosts, the first matching rule is
+  /// used.
+  pub rules: Vec<ProxyRule>,
+  /// Proxy of the other `https:` requests.
+  pub https: Option<deno_tls::Proxy>,
+  /// Proxy of the other `http:` requests.
+  pub http: Option<deno_tls::Proxy>,
+  /// Hosts requested without a proxy, written like the entries of
+  /// `NO_PROXY`, eg. `"example.com"`, `".example.com"` or `"10.0.0.0/8"`.
+  pub no_proxy: Vec<String>,
+}
+
+/// A proxy of the requests to `hosts`, written like the entries of
+/// `NO_PROXY`.
+#[derive(Debug, Clone)]
+pub struct ProxyRule {
+  pub hosts: Vec<String>,
+  pub proxy: deno_tls::Proxy,
+}
+
+/// Returns `None` when the url of a proxy is invalid.
+pub(crate) fn from_config(config: &ProxyConfig) -> Option<Proxies> {
+  let mut intercepts = Vec::new();
+  for rule in &config.rules {
+    // a rule without hosts matches no request
+    let Some(hosts) = NoProxy::from_string(&rule.hosts.join(",")) else {
+      continue;
+    };
+    let mut intercept = Intercept::from_proxy(&rule.proxy, Filter::All)?;
+    intercept.hosts = Some(Arc::new(hosts));
+    intercepts.push(intercept);
+  }
+  if let Some(proxy) = &config.https {
+    intercepts.push(Intercept::from_proxy(proxy, Filter::Https)?);
+  }
+  if let Some(proxy) = &config.http {
+    intercepts.push(Intercept::from_proxy(proxy, Filter::Http)?);
+  }
+  let no = NoProxy::from_string(&config.no_proxy.join(","));
+
+  Some(Proxies { intercepts, no })
+}
+
 pub fn basic_auth(user: &str, pass: Option<&str>) -> HeaderValue {
   use base64::prelude::BASE64_STANDARD;
   use base64::write::EncoderWriter;
@@ -129,7 +178,11 @@ pub fn basic_auth(user: &str, pass: Option<&str>) -> HeaderValue {
 fn parse_env_var(name: &str, filter: Filter) -> Option<Intercept> {
   let val = env::var(name).ok()?;
   let target = Target::parse(&val)?;
-  Some(Intercept { filter, target })
+  Some(Intercept {
+    filter,
+    target,
+    hosts: None,
+  })
 }
 
 impl Intercept {
@@ -138,9 +191,22 @@ impl Intercept {
     Some(Intercept {
       filter: Filter::All,
       target,
+      hosts: None,
     })
   }
 
+  fn from_proxy(proxy: &deno_tls::Proxy, filter: Filter) -> Option<Self> {
+    let mut intercept = Intercept {
+      filter,
+      target: Target::parse(&proxy.url)?,
+      hosts: None,
+    };
+    if let Some(basic_auth) = &proxy.basic_auth {
+      intercept.set_auth(&basic_auth.username, &basic_auth.password);
+    }
+    Some(intercept)
+  }
+
   pub(crate) fn set_auth(&mut self, user: &str, pass: &str) {
     match self.target {
       Target::Http { ref mut auth, .. } => {
@@ -400,6 +466,11 @@ impl Proxies {
     }
 
     for intercept in &self.intercepts {
+      if let Some(hosts) = &intercept.hosts {
+        if !dst.host().is_some_and(|host| hosts.contains(host)) {
+          continue;
+        }
+      }
       return match (
         intercept.filter,
         dst.scheme().map(Scheme::as_str).unwrap_or(""),
@@ -882,3 +953,59 @@ fn test_no_proxy_ip_ranges() {
     assert!(no_proxy.contains(host), "should contain {:?}", host);
   }
 }
+
+#[test]
+fn test_proxy_from_config() {
+  fn proxy(url: &str) -> deno_tls::Proxy {
+    deno_tls::Proxy {
+      url: url.to_string(),
+      basic_auth: None,
+    }
+  }
+  fn intercepted_by(proxies: &Proxies, dst: &str) -> Option<String> {
+    let intercept = proxies.intercept(&dst.parse::<Uri>().unwrap())?;
+    match &intercept.target {
+      Target::Http { dst, .. }
+      | Target::Https { dst, .. }
+      | Target::Socks { dst, .. } => Some(dst.host().unwrap().to_string()),
+    }
+  }
+
+  let proxies = from_config(&ProxyConfig {
+    rules: vec![
+      ProxyRule {
+        hosts: vec![".internal.example".to_string()],
+        proxy: proxy("socks5://internal-proxy:1080"),
+      },
+      ProxyRule {
+        hosts: vec![],
+        proxy: proxy("http://unused-proxy:3128"),
+      },
+    ],
+    https: Some(proxy("http://https-proxy:3128")),
+    http: None,
+    no_proxy: vec!["localhost".to_string(), "10.0.0.0/8".to_string()],
+  })
+  .unwrap();
+  assert_eq!(
+    intercepted_by(&proxies, "https://registry.internal.example/"),
+    Some("internal-proxy".to_string())
+  );
+  assert_eq!(
+    intercepted_by(&proxies, "http://registry.internal.example/"),
+    Some("internal-proxy".to_string())
+  );
+  assert_eq!(
+    intercepted_by(&proxies, "https://deno.land/"),
+    Some("https-proxy".to_string())
+  );
+  assert_eq!(intercepted_by(&proxies, "http://deno.land/"), None);
+  assert_eq!(intercepted_by(&proxies, "https://localhost/"), None);
+  assert_eq!(intercepted_by(&proxies, "https://10.1.2.3/"), None);
+
+  assert!(from_config(&ProxyConfig {
+    https: Some(proxy("ftp://proxy")),
+    ..Default::default()
+  })
+  .is_none());
+}
@@ -119,6 +119,7 @@ async fn rust_test_client_with_resolver(
         url: format!("{}://{}", proto, p),
         basic_auth: None,
       }),
+      proxy_config: None,
       unsafely_ignore_certificate_errors: Some(vec![]),
       client_cert_chain_and_key: None,
       pool_max_idle_per_host: None,
@@ -18,7 +18,10 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::c_void;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug, thiserror::Error)]
 pub enum DlfcnError {
@@ -32,12 +35,94 @@ pub enum DlfcnError {
   Dlopen(#[from] dlopen2::Error),
   #[error(transparent)]
   Permission(#[from] deno_permissions::PermissionCheckError),
+  #[error("{0}")]
+  DlopenDenied(deno_core::error::AnyError),
   #[error(transparent)]
   Other(deno_core::error::AnyError),
 }
 
+/// Symbols implemented by the embedder, given to scripts in place of a
+/// dynamic library.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualLibrary {
+  // addresses, as raw pointers aren't `Send`
+  symbols: HashMap<String, usize>,
+}
+
+impl VirtualLibrary {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Provides the symbol `name` at `ptr`, usually an `extern "C"` function.
+  ///
+  /// # Safety
+  ///
+  /// `ptr` must stay valid for as long as scripts use the library, and
+  /// point to a function or static matching the definition scripts pass to
+  /// `Deno.dlopen()`.
+  pub unsafe fn with_symbol(
+    mut self,
+    name: impl Into<String>,
+    ptr: *const c_void,
+  ) -> Self {
+    self.symbols.insert(name.into(), ptr as usize);
+    self
+  }
+}
+
+/// The library a `Deno.dlopen()` call opens, as decided by a
+/// [`DlopenInterceptor`].
+#[derive(Debug, Clone)]
+pub enum DlopenTarget {
+  /// Opens the dynamic library at the path.
+  Library(PathBuf),
+  /// Resolves the symbols from the embedder instead of a dynamic library.
+  Virtual(VirtualLibrary),
+}
+
+/// Decides which libraries scripts may open with `Deno.dlopen()`. It is
+/// consulted after the FFI permission check passed, so embedders can vet
+/// native code that permissions can't tell apart.
+pub trait DlopenInterceptor: Send + Sync {
+  /// Returns the library to open for the requested `path`, either `path`
+  /// itself, another vetted library or a [`VirtualLibrary`], or an error
+  /// to deny opening it.
+  fn intercept_dlopen(
+    &self,
+    path: &Path,
+  ) -> Result<DlopenTarget, deno_core::error::AnyError>;
+}
+
+pub(crate) struct DlopenInterceptorState(
+  pub(crate) Option<Arc<dyn DlopenInterceptor>>,
+);
+
+enum LibraryHandle {
+  Native(Library),
+  Virtual(VirtualLibrary),
+}
+
+impl LibraryHandle {
+  fn symbol(&self, symbol: &str) -> Result<*mut c_void, dlopen2::Error> {
+    match self {
+      // SAFETY: The obtained T symbol is the size of a pointer.
+      LibraryHandle::Native(lib) => unsafe {
+        lib.symbol::<*mut c_void>(symbol)
+      },
+      LibraryHandle::Virtual(lib) => match lib.symbols.get(symbol) {
+        Some(address) => Ok(*address as *mut c_void),
+        None => Err(dlopen2::Error::SymbolGettingError(std::io::Error::new(
+          std::io::ErrorKind::NotFound,
+          "Symbol not provided by the virtual library",
+        ))),
+      },
+    }
+  }
+}
+
 pub struct DynamicLibraryResource {
-  lib: Library,
+  lib: LibraryHandle,
   pub symbols: HashMap<String, Box<Symbol>>,
 }
 
@@ -56,9 +141,7 @@ impl DynamicLibraryResource {
     // By default, Err returned by this function does not tell
     // which symbol wasn't exported. So we'll modify the error
     // message to include the name of symbol.
-    //
-    // SAFETY: The obtained T symbol is the size of a pointer.
-    match unsafe { self.lib.symbol::<*mut c_void>(&symbol) } {
+    match self.lib.symbol(&symbol) {
       Ok(value) => Ok(Ok(value)),
       Err(error) => Err(DlfcnError::RegisterSymbol { symbol, error }),
     }?
@@ -139,12 +222,27 @@ where
     permissions.check_partial_with_path(&args.path)?
   };
 
-  let lib = Library::open(&path).map_err(|e| {
-    dlopen2::Error::OpeningLibraryError(std::io::Error::new(
-      std::io::ErrorKind::Other,
-      format_error(e, &path),
-    ))
-  })?;
+  let maybe_interceptor = state
+    .borrow()
+    .try_borrow::<DlopenInterceptorState>()
+    .and_then(|it| it.0.clone());
+  let target = match maybe_interceptor {
+    Some(interceptor) => interceptor
+      .intercept_dlopen(&path)
+      .map_err(DlfcnError::DlopenDenied)?,
+    None => DlopenTarget::Library(path),
+  };
+  let lib = match target {
+    DlopenTarget::Library(path) => {
+      LibraryHandle::Native(Library::open(&path).map_err(|e| {
+        dlopen2::Error::OpeningLibraryError(std::io::Error::new(
+          std::io::ErrorKind::Other,
+          format_error(e, &path),
+        ))
+      })?)
+    }
+    DlopenTarget::Virtual(lib) => LibraryHandle::Virtual(lib),
+  };
   let mut resource = DynamicLibraryResource {
     lib,
     symbols: HashMap::new(),
@@ -164,11 +262,10 @@ where
         // By default, Err returned by this function does not tell
         // which symbol wasn't exported. So we'll modify the error
         // message to include the name of symbol.
-        let fn_ptr =
-          // SAFETY: The obtained T symbol is the size of a pointer.
-          match unsafe { resource.lib.symbol::<*const c_void>(symbol) } {
-            Ok(value) => Ok(value),
-            Err(error) => if foreign_fn.optional {
+        let fn_ptr = match resource.lib.symbol(symbol) {
+          Ok(value) => Ok(value),
+          Err(error) => {
+            if foreign_fn.optional {
               let null: v8::Local<v8::Value> = v8::null(scope).into();
               let func_key = v8::String::new(scope, &symbol_key).unwrap();
               obj.set(scope, func_key.into(), null);
@@ -178,8 +275,9 @@ where
                 symbol: symbol.to_owned(),
                 error,
               })
-            },
-          }?;
+            }
+          }
+        }?;
 
         let ptr = libffi::middle::CodePtr::from_ptr(fn_ptr as _);
         let cif = libffi::middle::Cif::new(
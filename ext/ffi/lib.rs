@@ -4,6 +4,7 @@ use std::mem::size_of;
 use std::os::raw::c_char;
 use std::os::raw::c_short;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 mod call;
 mod callback;
@@ -21,6 +22,7 @@ use callback::op_ffi_unsafe_callback_close;
 use callback::op_ffi_unsafe_callback_create;
 use callback::op_ffi_unsafe_callback_ref;
 use dlfcn::op_ffi_load;
+use dlfcn::DlopenInterceptorState;
 use dlfcn::ForeignFunction;
 use r#static::op_ffi_get_static;
 use repr::*;
@@ -31,6 +33,9 @@ pub use call::CallError;
 pub use callback::CallbackError;
 use deno_permissions::PermissionCheckError;
 pub use dlfcn::DlfcnError;
+pub use dlfcn::DlopenInterceptor;
+pub use dlfcn::DlopenTarget;
+pub use dlfcn::VirtualLibrary;
 pub use ir::IRError;
 pub use r#static::StaticError;
 pub use repr::ReprError;
@@ -107,4 +112,10 @@ deno_core::extension!(deno_ffi,
     op_ffi_unsafe_callback_ref,
   ],
   esm = [ "00_ffi.js" ],
+  options = {
+    dlopen_interceptor: Option<Arc<dyn DlopenInterceptor>>,
+  },
+  state = |state, options| {
+    state.put(DlopenInterceptorState(options.dlopen_interceptor));
+  },
 );
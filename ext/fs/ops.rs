@@ -31,7 +31,11 @@ use deno_core::ToV8;
 use deno_io::fs::FileResource;
 use deno_io::fs::FsError;
 use deno_io::fs::FsStat;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
 use deno_permissions::PermissionCheckError;
+use deno_permissions::QuotaExceededError;
+use deno_permissions::QuotaKind;
 use rand::rngs::ThreadRng;
 use rand::thread_rng;
 use rand::Rng;
@@ -49,6 +53,8 @@ pub enum FsOpsErrorKind {
   #[error(transparent)]
   Permission(#[from] PermissionCheckError),
   #[error(transparent)]
+  Quota(#[from] QuotaExceededError),
+  #[error(transparent)]
   Resource(deno_core::error::AnyError),
   #[error("File name or path {0:?} is not valid UTF-8")]
   InvalidUtf8(std::ffi::OsString),
@@ -207,6 +213,7 @@ where
   let options = options.unwrap_or_else(OpenOptions::read);
 
   let fs = state.borrow::<FileSystemRc>().clone();
+  let permit = acquire_quota(state, QuotaKind::OpenFiles)?;
   let mut access_check =
     sync_permission_check::<P>(state.borrow_mut(), "Deno.openSync()");
   let file = fs
@@ -216,6 +223,7 @@ where
   let rid = state
     .resource_table
     .add(FileResource::new(file, "fsFile".to_string()));
+  hold_quota(state, rid, permit);
   Ok(rid)
 }
 
@@ -235,15 +243,17 @@ where
   let mut access_check =
     async_permission_check::<P>(state.clone(), "Deno.open()");
   let fs = state.borrow().borrow::<FileSystemRc>().clone();
+  let permit = acquire_quota(&mut state.borrow_mut(), QuotaKind::OpenFiles)?;
   let file = fs
     .open_async(path.clone(), options, Some(&mut access_check))
     .await
     .map_err(|error| map_permission_error("open", error, &path))?;
 
+  let mut state = state.borrow_mut();
   let rid = state
-    .borrow_mut()
     .resource_table
     .add(FileResource::new(file, "fsFile".to_string()));
+  hold_quota(&mut state, rid, permit);
   Ok(rid)
 }
 
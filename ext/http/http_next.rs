@@ -43,6 +43,8 @@ use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_net::ops_tls::TlsStream;
 use deno_net::raw::NetworkStream;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::QuotaKind;
 use deno_websocket::ws_create_server_stream;
 use hyper::body::Incoming;
 use hyper::header::HeaderMap;
@@ -1080,19 +1082,32 @@ where
   };
 
   let listen_properties_clone: HttpListenProperties = listen_properties.clone();
+  let state_ = state.clone();
   let handle = spawn(async move {
     loop {
       let conn = HTTP::accept_connection_from_listener(&listener)
         .try_or_cancel(listen_cancel_clone.clone())
         .await
         .map_err(HttpNextError::HttpPropertyExtractor)?;
-      serve_http_on::<HTTP>(
+      // connections exceeding the quota are dropped, without stopping the
+      // server
+      let Ok(permit) =
+        acquire_quota(&mut state_.borrow_mut(), QuotaKind::Sockets)
+      else {
+        continue;
+      };
+      let connection = serve_http_on::<HTTP>(
         conn,
         &listen_properties_clone,
         lifetime.clone(),
         tx.clone(),
         options,
       );
+      // the connection counts as a socket until it's closed
+      spawn(async move {
+        let _permit = permit;
+        let _ = connection.await;
+      });
     }
     #[allow(unreachable_code)]
     Ok::<_, HttpNextError>(())
@@ -2,6 +2,7 @@
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::remote::RemoteDbHandlerPermissions;
 use crate::sqlite::SqliteDbHandler;
@@ -52,6 +53,38 @@ impl MultiBackendDbHandler {
       ),
     ])
   }
+
+  /// Like [`remote_or_sqlite`](Self::remote_or_sqlite), but opens databases
+  /// that aren't remote with `handler` instead of sqlite, eg. a handler of
+  /// an embedder that keeps them in its own storage.
+  pub fn remote_or<P: RemoteDbHandlerPermissions + 'static>(
+    handler: Arc<dyn DynamicDbHandler + Send + Sync>,
+    http_options: crate::remote::HttpOptions,
+  ) -> Self {
+    Self::new(vec![
+      (
+        &["https://", "http://"],
+        Box::new(crate::remote::RemoteDbHandler::<P>::new(http_options)),
+      ),
+      (&[""], Box::new(SharedDbHandler(handler))),
+    ])
+  }
+}
+
+/// Lets a handler be shared by the workers of a process.
+struct SharedDbHandler(Arc<dyn DynamicDbHandler + Send + Sync>);
+
+#[async_trait(?Send)]
+impl DatabaseHandler for SharedDbHandler {
+  type DB = RcDynamicDb;
+
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<Self::DB, AnyError> {
+    self.0.dyn_open(state, path).await
+  }
 }
 
 #[async_trait(?Send)]
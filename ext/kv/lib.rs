@@ -56,6 +56,7 @@ use serde::Serialize;
 
 pub use crate::config::*;
 pub use crate::interface::*;
+pub use denokv_proto;
 
 pub const UNSTABLE_FEATURE_NAME: &str = "kv";
 
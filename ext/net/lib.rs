@@ -118,6 +118,46 @@ impl DefaultTlsOptions {
 /// would override previously used alias.
 pub struct UnsafelyIgnoreCertificateErrors(pub Option<Vec<String>>);
 
+/// Where a script is about to connect or send a datagram to.
+#[derive(Debug, Clone, Copy)]
+pub enum ConnectTarget<'a> {
+  /// A TCP connection, also used by TLS and WebSocket connections.
+  Tcp { hostname: &'a str, port: u16 },
+  /// A UDP datagram.
+  Udp { hostname: &'a str, port: u16 },
+  /// A Unix domain socket, either a stream or a datagram.
+  Unix(&'a Path),
+}
+
+/// Decides whether scripts may open outbound connections, upgrade them to TLS
+/// or send datagrams. It is consulted after the permission checks passed, so
+/// embedders can enforce egress policies that permissions can't express.
+pub trait ConnectInterceptor: Send + Sync {
+  /// Returns an error to deny connecting to `target`.
+  fn check_connect(
+    &self,
+    target: ConnectTarget,
+    api_name: &str,
+  ) -> Result<(), AnyError>;
+}
+
+struct ConnectInterceptorState(Option<Arc<dyn ConnectInterceptor>>);
+
+/// Passes `target` to the connect interceptor of the worker, if any.
+pub fn check_connect(
+  state: &OpState,
+  target: ConnectTarget,
+  api_name: &str,
+) -> Result<(), AnyError> {
+  let maybe_interceptor = state
+    .try_borrow::<ConnectInterceptorState>()
+    .and_then(|it| it.0.as_ref());
+  match maybe_interceptor {
+    Some(interceptor) => interceptor.check_connect(target, api_name),
+    None => Ok(()),
+  }
+}
+
 deno_core::extension!(deno_net,
   deps = [ deno_web ],
   parameters = [ P: NetPermissions ],
@@ -163,6 +203,7 @@ deno_core::extension!(deno_net,
   options = {
     root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
     unsafely_ignore_certificate_errors: Option<Vec<String>>,
+    connect_interceptor: Option<Arc<dyn ConnectInterceptor>>,
   },
   state = |state, options| {
     state.put(DefaultTlsOptions {
@@ -171,6 +212,7 @@ deno_core::extension!(deno_net,
     state.put(UnsafelyIgnoreCertificateErrors(
       options.unsafely_ignore_certificate_errors,
     ));
+    state.put(ConnectInterceptorState(options.connect_interceptor));
   },
 );
 
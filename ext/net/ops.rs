@@ -1,10 +1,12 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::check_connect;
 use crate::io::TcpStreamResource;
 use crate::raw::NetworkListenerResource;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
 use crate::tcp::TcpListener;
+use crate::ConnectTarget;
 use crate::NetPermissions;
 use deno_core::op2;
 use deno_core::CancelFuture;
@@ -18,6 +20,9 @@ use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
+use deno_permissions::QuotaKind;
 use hickory_proto::rr::rdata::caa::Value;
 use hickory_proto::rr::record_data::RData;
 use hickory_proto::rr::record_type::RecordType;
@@ -83,6 +88,8 @@ pub enum NetError {
   AcceptTaskOngoing,
   #[error(transparent)]
   Permission(#[from] deno_permissions::PermissionCheckError),
+  #[error(transparent)]
+  Quota(#[from] deno_permissions::QuotaExceededError),
   #[error("{0}")]
   Resource(deno_core::error::AnyError),
   #[error("No resolved address found")]
@@ -121,6 +128,8 @@ pub enum NetError {
   RootCertStore(deno_core::anyhow::Error),
   #[error("{0}")]
   Reunite(tokio::net::tcp::ReuniteError),
+  #[error("{0}")]
+  ConnectDenied(deno_core::anyhow::Error),
 }
 
 pub(crate) fn accept_err(e: std::io::Error) -> NetError {
@@ -146,11 +155,19 @@ pub async fn op_net_accept_tcp(
     .try_borrow_mut()
     .ok_or_else(|| NetError::AcceptTaskOngoing)?;
   let cancel = RcRef::map(resource, |r| &r.cancel);
-  let (tcp_stream, _socket_addr) = listener
-    .accept()
-    .try_or_cancel(cancel)
-    .await
-    .map_err(accept_err)?;
+  let (tcp_stream, permit) = loop {
+    let (tcp_stream, _socket_addr) = listener
+      .accept()
+      .try_or_cancel(&cancel)
+      .await
+      .map_err(accept_err)?;
+    // connections exceeding the quota are dropped, without failing the accept
+    if let Ok(permit) =
+      acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)
+    {
+      break (tcp_stream, permit);
+    }
+  };
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
@@ -158,6 +175,7 @@ pub async fn op_net_accept_tcp(
   let rid = state
     .resource_table
     .add(TcpStreamResource::new(tcp_stream.into_split()));
+  hold_quota(&mut state, rid, permit);
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
 }
 
@@ -199,6 +217,12 @@ where
       &(&addr.hostname, Some(addr.port)),
       "Deno.DatagramConn.send()",
     )?;
+    let target = ConnectTarget::Udp {
+      hostname: &addr.hostname,
+      port: addr.port,
+    };
+    check_connect(&s, target, "Deno.DatagramConn.send()")
+      .map_err(NetError::ConnectDenied)?;
   }
   let addr = resolve_addr(&addr.hostname, addr.port)
     .await?
@@ -368,7 +392,14 @@ where
     state_
       .borrow_mut::<NP>()
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connect()")?;
+    let target = ConnectTarget::Tcp {
+      hostname: &addr.hostname,
+      port: addr.port,
+    };
+    check_connect(&state_, target, "Deno.connect()")
+      .map_err(NetError::ConnectDenied)?;
   }
+  let permit = acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)?;
 
   let addr = resolve_addr(&addr.hostname, addr.port)
     .await?
@@ -382,6 +413,7 @@ where
   let rid = state_
     .resource_table
     .add(TcpStreamResource::new(tcp_stream.into_split()));
+  hold_quota(&mut state_, rid, permit);
 
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
 }
@@ -418,6 +450,7 @@ where
   state
     .borrow_mut::<NP>()
     .check_net(&(&addr.hostname, Some(addr.port)), "Deno.listen()")?;
+  let permit = acquire_quota(state, QuotaKind::Sockets)?;
   let addr = resolve_addr_sync(&addr.hostname, addr.port)?
     .next()
     .ok_or_else(|| NetError::NoResolvedAddress)?;
@@ -430,6 +463,7 @@ where
   let local_addr = listener.local_addr()?;
   let listener_resource = NetworkListenerResource::new(listener);
   let rid = state.resource_table.add(listener_resource);
+  hold_quota(state, rid, permit);
 
   Ok((rid, IpAddr::from(local_addr)))
 }
@@ -446,6 +480,7 @@ where
   state
     .borrow_mut::<NP>()
     .check_net(&(&addr.hostname, Some(addr.port)), "Deno.listenDatagram()")?;
+  let permit = acquire_quota(state, QuotaKind::Sockets)?;
   let addr = resolve_addr_sync(&addr.hostname, addr.port)?
     .next()
     .ok_or_else(|| NetError::NoResolvedAddress)?;
@@ -497,6 +532,7 @@ where
     cancel: Default::default(),
   };
   let rid = state.resource_table.add(socket_resource);
+  hold_quota(state, rid, permit);
 
   Ok((rid, IpAddr::from(local_addr)))
 }
@@ -1,5 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::check_connect;
 use crate::io::TcpStreamResource;
 use crate::ops::IpAddr;
 use crate::ops::NetError;
@@ -8,6 +9,7 @@ use crate::raw::NetworkListenerResource;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
 use crate::tcp::TcpListener;
+use crate::ConnectTarget;
 use crate::DefaultTlsOptions;
 use crate::NetPermissions;
 use crate::UnsafelyIgnoreCertificateErrors;
@@ -22,6 +24,10 @@ use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
+use deno_permissions::quota::take_quota;
+use deno_permissions::QuotaKind;
 use deno_tls::create_client_config;
 use deno_tls::load_certs;
 use deno_tls::load_private_keys;
@@ -281,7 +287,7 @@ where
     .collect::<Vec<_>>();
 
   let hostname_dns = ServerName::try_from(hostname.to_string())
-    .map_err(|_| NetError::InvalidHostname(hostname))?;
+    .map_err(|_| NetError::InvalidHostname(hostname.clone()))?;
 
   let unsafely_ignore_certificate_errors = state
     .borrow()
@@ -294,11 +300,15 @@ where
     .root_cert_store()
     .map_err(NetError::RootCertStore)?;
 
-  let resource_rc = state
-    .borrow_mut()
-    .resource_table
-    .take::<TcpStreamResource>(rid)
-    .map_err(NetError::Resource)?;
+  let (resource_rc, permit) = {
+    let mut state_ = state.borrow_mut();
+    let resource_rc = state_
+      .resource_table
+      .take::<TcpStreamResource>(rid)
+      .map_err(NetError::Resource)?;
+    // the TLS stream takes over the socket quota of the TCP stream
+    (resource_rc, take_quota(&mut state_, rid))
+  };
   // This TCP connection might be used somewhere else. If it's the case, we cannot proceed with the
   // process of starting a TLS connection on top of this TCP connection, so we just return a Busy error.
   // See also: https://github.com/denoland/deno/pull/16242
@@ -310,6 +320,13 @@ where
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
+  let target = ConnectTarget::Tcp {
+    hostname: &hostname,
+    port: remote_addr.port(),
+  };
+  check_connect(&state.borrow(), target, "Deno.startTls()")
+    .map_err(NetError::ConnectDenied)?;
+
   let mut tls_config = create_client_config(
     root_cert_store,
     ca_certs,
@@ -330,14 +347,16 @@ where
     TLS_BUFFER_SIZE,
   );
 
-  let rid = {
+  let tls_rid = {
     let mut state_ = state.borrow_mut();
-    state_
+    let tls_rid = state_
       .resource_table
-      .add(TlsStreamResource::new(tls_stream.into_split()))
+      .add(TlsStreamResource::new(tls_stream.into_split()));
+    hold_quota(&mut state_, tls_rid, permit);
+    tls_rid
   };
 
-  Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
+  Ok((tls_rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
 }
 
 #[op2(async, stack_trace)]
@@ -363,7 +382,7 @@ where
     permissions
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connectTls()")
       .map_err(NetError::Permission)?;
-    if let Some(path) = cert_file {
+    let cert_file = if let Some(path) = cert_file {
       Some(
         permissions
           .check_read(path, "Deno.connectTls()")
@@ -371,8 +390,16 @@ where
       )
     } else {
       None
-    }
+    };
+    let target = ConnectTarget::Tcp {
+      hostname: &addr.hostname,
+      port: addr.port,
+    };
+    check_connect(&s, target, "Deno.connectTls()")
+      .map_err(NetError::ConnectDenied)?;
+    cert_file
   };
+  let permit = acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)?;
 
   let mut ca_certs = args
     .ca_certs
@@ -428,9 +455,11 @@ where
 
   let rid = {
     let mut state_ = state.borrow_mut();
-    state_
+    let rid = state_
       .resource_table
-      .add(TlsStreamResource::new(tls_stream.into_split()))
+      .add(TlsStreamResource::new(tls_stream.into_split()));
+    hold_quota(&mut state_, rid, permit);
+    rid
   };
 
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
@@ -466,6 +495,7 @@ where
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.listenTls()")
       .map_err(NetError::Permission)?;
   }
+  let permit = acquire_quota(state, QuotaKind::Sockets)?;
 
   let bind_addr = resolve_addr_sync(&addr.hostname, addr.port)?
     .next()
@@ -506,6 +536,7 @@ where
   let tls_listener_resource = NetworkListenerResource::new(listener);
 
   let rid = state.resource_table.add(tls_listener_resource);
+  hold_quota(state, rid, permit);
 
   Ok((rid, IpAddr::from(local_addr)))
 }
@@ -527,21 +558,31 @@ pub async fn op_net_accept_tls(
     .try_borrow_mut()
     .ok_or_else(|| NetError::AcceptTaskOngoing)?;
 
-  let (tls_stream, remote_addr) =
-    match listener.accept().try_or_cancel(&cancel_handle).await {
-      Ok(tuple) => tuple,
-      Err(err) if err.kind() == ErrorKind::Interrupted => {
-        return Err(NetError::ListenerClosed);
-      }
-      Err(err) => return Err(err.into()),
-    };
+  let (tls_stream, remote_addr, permit) = loop {
+    let (tls_stream, remote_addr) =
+      match listener.accept().try_or_cancel(&cancel_handle).await {
+        Ok(tuple) => tuple,
+        Err(err) if err.kind() == ErrorKind::Interrupted => {
+          return Err(NetError::ListenerClosed);
+        }
+        Err(err) => return Err(err.into()),
+      };
+    // connections exceeding the quota are dropped, without failing the accept
+    if let Ok(permit) =
+      acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)
+    {
+      break (tls_stream, remote_addr, permit);
+    }
+  };
 
   let local_addr = tls_stream.local_addr()?;
   let rid = {
     let mut state_ = state.borrow_mut();
-    state_
+    let rid = state_
       .resource_table
-      .add(TlsStreamResource::new(tls_stream.into_split()))
+      .add(TlsStreamResource::new(tls_stream.into_split()));
+    hold_quota(&mut state_, rid, permit);
+    rid
   };
 
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
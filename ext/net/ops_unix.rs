@@ -1,8 +1,10 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::check_connect;
 use crate::io::UnixStreamResource;
 use crate::ops::NetError;
 use crate::raw::NetworkListenerResource;
+use crate::ConnectTarget;
 use crate::NetPermissions;
 use deno_core::op2;
 use deno_core::AsyncRefCell;
@@ -13,6 +15,9 @@ use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
+use deno_permissions::QuotaKind;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
@@ -68,11 +73,19 @@ pub async fn op_net_accept_unix(
     .try_borrow_mut()
     .ok_or(NetError::ListenerBusy)?;
   let cancel = RcRef::map(resource, |r| &r.cancel);
-  let (unix_stream, _socket_addr) = listener
-    .accept()
-    .try_or_cancel(cancel)
-    .await
-    .map_err(crate::ops::accept_err)?;
+  let (unix_stream, permit) = loop {
+    let (unix_stream, _socket_addr) = listener
+      .accept()
+      .try_or_cancel(&cancel)
+      .await
+      .map_err(crate::ops::accept_err)?;
+    // connections exceeding the quota are dropped, without failing the accept
+    if let Ok(permit) =
+      acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)
+    {
+      break (unix_stream, permit);
+    }
+  };
 
   let local_addr = unix_stream.local_addr()?;
   let remote_addr = unix_stream.peer_addr()?;
@@ -82,6 +95,7 @@ pub async fn op_net_accept_unix(
   let resource = UnixStreamResource::new(unix_stream.into_split());
   let mut state = state.borrow_mut();
   let rid = state.resource_table.add(resource);
+  hold_quota(&mut state, rid, permit);
   Ok((rid, local_addr_path, remote_addr_path))
 }
 
@@ -104,8 +118,15 @@ where
       .borrow_mut::<NP>()
       .check_write_path(&address_path, "Deno.connect()")
       .map_err(NetError::Permission)?;
+    check_connect(
+      &state_,
+      ConnectTarget::Unix(&address_path),
+      "Deno.connect()",
+    )
+    .map_err(NetError::ConnectDenied)?;
     address_path
   };
+  let permit = acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)?;
   let unix_stream = UnixStream::connect(&address_path).await?;
   let local_addr = unix_stream.local_addr()?;
   let remote_addr = unix_stream.peer_addr()?;
@@ -115,6 +136,7 @@ where
   let mut state_ = state.borrow_mut();
   let resource = UnixStreamResource::new(unix_stream.into_split());
   let rid = state_.resource_table.add(resource);
+  hold_quota(&mut state_, rid, permit);
   Ok((rid, local_addr_path, remote_addr_path))
 }
 
@@ -153,9 +175,17 @@ where
 {
   let address_path = {
     let mut s = state.borrow_mut();
-    s.borrow_mut::<NP>()
+    let address_path = s
+      .borrow_mut::<NP>()
       .check_write(&address_path, "Deno.DatagramConn.send()")
-      .map_err(NetError::Permission)?
+      .map_err(NetError::Permission)?;
+    check_connect(
+      &s,
+      ConnectTarget::Unix(&address_path),
+      "Deno.DatagramConn.send()",
+    )
+    .map_err(NetError::ConnectDenied)?;
+    address_path
   };
 
   let resource = state
@@ -189,11 +219,13 @@ where
   _ = permissions
     .check_write_path(&address_path, &api_call_expr)
     .map_err(NetError::Permission)?;
+  let permit = acquire_quota(state, QuotaKind::Sockets)?;
   let listener = UnixListener::bind(address_path)?;
   let local_addr = listener.local_addr()?;
   let pathname = local_addr.as_pathname().map(pathstring).transpose()?;
   let listener_resource = NetworkListenerResource::new(listener);
   let rid = state.resource_table.add(listener_resource);
+  hold_quota(state, rid, permit);
   Ok((rid, pathname))
 }
 
@@ -211,6 +243,7 @@ where
   _ = permissions
     .check_write_path(&address_path, "Deno.listenDatagram()")
     .map_err(NetError::Permission)?;
+  let permit = acquire_quota(state, QuotaKind::Sockets)?;
   let socket = UnixDatagram::bind(address_path)?;
   let local_addr = socket.local_addr()?;
   let pathname = local_addr.as_pathname().map(pathstring).transpose()?;
@@ -219,6 +252,7 @@ where
     cancel: Default::default(),
   };
   let rid = state.resource_table.add(datagram_resource);
+  hold_quota(state, rid, permit);
   Ok((rid, pathname))
 }
 
@@ -13,7 +13,6 @@ use deno_core::futures::Future;
 use deno_core::futures::FutureExt;
 use deno_core::futures::Stream;
 use deno_core::futures::StreamExt;
-use deno_core::futures::TryFutureExt;
 use deno_core::op2;
 use deno_core::serde::Serialize;
 use deno_core::unsync::spawn;
@@ -30,12 +29,17 @@ use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_fetch::get_or_create_client_from_state;
+use deno_fetch::send_intercepted;
 use deno_fetch::FetchCancelHandle;
 use deno_fetch::FetchError;
 use deno_fetch::FetchRequestResource;
 use deno_fetch::FetchReturn;
 use deno_fetch::HttpClientResource;
 use deno_fetch::ResBody;
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
+use deno_permissions::quota::take_quota;
+use deno_permissions::QuotaKind;
 use http::header::HeaderMap;
 use http::header::HeaderName;
 use http::header::HeaderValue;
@@ -136,10 +140,12 @@ where
   let cancel_handle = CancelHandle::new_rc();
   let cancel_handle_ = cancel_handle.clone();
 
+  let maybe_interceptor =
+    state.borrow::<deno_fetch::Options>().interceptor.clone();
+  // the request counts as a socket until its response is closed
+  let permit = acquire_quota(state, QuotaKind::Sockets)?;
   let fut = async move {
-    client
-      .send(request)
-      .map_err(Into::into)
+    send_intercepted(client, request, maybe_interceptor)
       .or_cancel(cancel_handle_)
       .await
   };
@@ -148,6 +154,7 @@ where
     future: Box::pin(fut),
     url,
   });
+  hold_quota(state, request_rid, permit);
 
   let cancel_handle_rid =
     state.resource_table.add(FetchCancelHandle(cancel_handle));
@@ -178,11 +185,14 @@ pub async fn op_node_http_fetch_send(
   state: Rc<RefCell<OpState>>,
   #[smi] rid: ResourceId,
 ) -> Result<NodeHttpFetchResponse, FetchError> {
-  let request = state
-    .borrow_mut()
-    .resource_table
-    .take::<FetchRequestResource>(rid)
-    .map_err(FetchError::Resource)?;
+  let (request, permit) = {
+    let mut state = state.borrow_mut();
+    let request = state
+      .resource_table
+      .take::<FetchRequestResource>(rid)
+      .map_err(FetchError::Resource)?;
+    (request, take_quota(&mut state, rid))
+  };
 
   let request = Rc::try_unwrap(request)
     .ok()
@@ -232,10 +242,14 @@ pub async fn op_node_http_fetch_send(
     (None, None)
   };
 
-  let response_rid = state
-    .borrow_mut()
-    .resource_table
-    .add(NodeHttpFetchResponseResource::new(res, content_length));
+  let response_rid = {
+    let mut state = state.borrow_mut();
+    let response_rid = state
+      .resource_table
+      .add(NodeHttpFetchResponseResource::new(res, content_length));
+    hold_quota(&mut state, response_rid, permit);
+    response_rid
+  };
 
   Ok(NodeHttpFetchResponse {
     status: status.as_u16(),
@@ -32,16 +32,18 @@ use opentelemetry_otlp::MetricExporter;
 use opentelemetry_otlp::Protocol;
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_otlp::WithHttpConfig;
-use opentelemetry_sdk::export::trace::SpanData;
+pub use opentelemetry_sdk::export::trace::SpanData;
 use opentelemetry_sdk::logs::BatchLogProcessor;
 use opentelemetry_sdk::logs::LogProcessor;
 use opentelemetry_sdk::logs::LogRecord;
 use opentelemetry_sdk::metrics::data::Metric;
-use opentelemetry_sdk::metrics::data::ResourceMetrics;
+pub use opentelemetry_sdk::metrics::data::ResourceMetrics;
 use opentelemetry_sdk::metrics::data::ScopeMetrics;
 use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
 use opentelemetry_sdk::metrics::Temporality;
 use opentelemetry_sdk::trace::BatchSpanProcessor;
+use opentelemetry_sdk::trace::IdGenerator;
+use opentelemetry_sdk::trace::RandomIdGenerator;
 use opentelemetry_sdk::trace::SpanProcessor;
 use opentelemetry_sdk::Resource;
 use opentelemetry_semantic_conventions::resource::PROCESS_RUNTIME_NAME;
@@ -52,9 +54,12 @@ use opentelemetry_semantic_conventions::resource::TELEMETRY_SDK_VERSION;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::env;
 use std::fmt::Debug;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::RwLock;
 use std::task::Context;
 use std::task::Poll;
 use std::thread;
@@ -412,6 +417,16 @@ static BUILT_IN_INSTRUMENTATION_SCOPE: OnceCell<
 > = OnceCell::new();
 
 pub fn init(config: OtelConfig) -> anyhow::Result<()> {
+  // The scope is also used for the spans and metrics handed to the exporter
+  // set with `set_exporter`, so it's set even when OTLP export is disabled.
+  let builtin_instrumentation_scope =
+    opentelemetry::InstrumentationScope::builder("deno")
+      .with_version(config.runtime_version.clone())
+      .build();
+  BUILT_IN_INSTRUMENTATION_SCOPE
+    .set(builtin_instrumentation_scope)
+    .map_err(|_| anyhow!("failed to init otel"))?;
+
   // Parse the `OTEL_EXPORTER_OTLP_PROTOCOL` variable. The opentelemetry_*
   // crates don't do this automatically.
   // TODO(piscisaureus): enable GRPC support.
@@ -508,14 +523,6 @@ pub fn init(config: OtelConfig) -> anyhow::Result<()> {
     })
     .map_err(|_| anyhow!("failed to init otel"))?;
 
-  let builtin_instrumentation_scope =
-    opentelemetry::InstrumentationScope::builder("deno")
-      .with_version(config.runtime_version.clone())
-      .build();
-  BUILT_IN_INSTRUMENTATION_SCOPE
-    .set(builtin_instrumentation_scope)
-    .map_err(|_| anyhow!("failed to init otel"))?;
-
   Ok(())
 }
 
@@ -535,6 +542,185 @@ pub fn flush() {
   }
 }
 
+/// Receives the spans and metrics of the runtime in the process, eg. to
+/// forward them to a tracing system that isn't reachable over OTLP. Set
+/// with [`set_exporter`].
+pub trait TelemetryExporter: Send + Sync {
+  fn export_span(&self, span: &SpanData);
+  fn export_metrics(&self, metrics: &ResourceMetrics);
+}
+
+static OTEL_EXPORTER: RwLock<Option<Arc<dyn TelemetryExporter>>> =
+  RwLock::new(None);
+
+/// Sets the exporter that receives spans and metrics in addition to the
+/// OTLP exporter configured with the `OTEL_EXPORTER_OTLP_*` variables.
+/// Nothing is exported before [`init`] was called.
+pub fn set_exporter(exporter: Option<Arc<dyn TelemetryExporter>>) {
+  *OTEL_EXPORTER.write().unwrap() = exporter;
+}
+
+fn exporter() -> Option<Arc<dyn TelemetryExporter>> {
+  OTEL_EXPORTER.read().unwrap().clone()
+}
+
+fn is_exporting() -> bool {
+  BUILT_IN_INSTRUMENTATION_SCOPE.get().is_some()
+    && (OTEL_PROCESSORS.get().is_some() || exporter().is_some())
+}
+
+fn export_span(span: SpanData) {
+  if let Some(exporter) = exporter() {
+    exporter.export_span(&span);
+  }
+  if let Some(Processors { spans, .. }) = OTEL_PROCESSORS.get() {
+    spans.on_end(span);
+  }
+}
+
+fn export_metrics(metrics: ResourceMetrics) {
+  if let Some(exporter) = exporter() {
+    exporter.export_metrics(&metrics);
+  }
+  if let Some(Processors {
+    metrics: processor, ..
+  }) = OTEL_PROCESSORS.get()
+  {
+    processor.submit(metrics);
+  }
+}
+
+thread_local! {
+  // the host spans that are open on this thread, innermost last
+  static HOST_SPAN_STACK: RefCell<Vec<SpanContext>> = const {
+    RefCell::new(Vec::new())
+  };
+}
+
+/// A span recorded by the runtime itself, eg. around type checking. It ends
+/// when dropped. Spans started while another one is open on the same thread
+/// become its children.
+///
+/// Does nothing unless telemetry was initialized with [`init`].
+pub struct HostSpan(Option<SpanData>);
+
+impl HostSpan {
+  pub fn start(name: &'static str) -> Self {
+    if !is_exporting() {
+      return Self(None);
+    }
+    let parent = HOST_SPAN_STACK.with(|stack| stack.borrow().last().cloned());
+    let id_generator = RandomIdGenerator::default();
+    let (trace_id, parent_span_id) = match parent {
+      Some(parent) => (parent.trace_id(), parent.span_id()),
+      None => (id_generator.new_trace_id(), SpanId::INVALID),
+    };
+    let span_context = SpanContext::new(
+      trace_id,
+      id_generator.new_span_id(),
+      TraceFlags::SAMPLED,
+      false,
+      Default::default(),
+    );
+    HOST_SPAN_STACK.with(|stack| stack.borrow_mut().push(span_context.clone()));
+    let now = SystemTime::now();
+    Self(Some(SpanData {
+      span_context,
+      parent_span_id,
+      span_kind: SpanKind::Internal,
+      name: Cow::Borrowed(name),
+      start_time: now,
+      end_time: now,
+      attributes: Vec::new(),
+      dropped_attributes_count: 0,
+      events: Default::default(),
+      links: Default::default(),
+      status: SpanStatus::Unset,
+      instrumentation_scope: BUILT_IN_INSTRUMENTATION_SCOPE
+        .get()
+        .unwrap()
+        .clone(),
+    }))
+  }
+
+  pub fn set_attribute(&mut self, key: &'static str, value: impl Into<String>) {
+    if let Some(span) = &mut self.0 {
+      span.attributes.push(KeyValue::new(key, value.into()));
+    }
+  }
+
+  /// Sets the status of the span from the result of the operation it
+  /// records.
+  pub fn set_result<T, E: std::fmt::Display>(&mut self, result: &Result<T, E>) {
+    if let Some(span) = &mut self.0 {
+      span.status = match result {
+        Ok(_) => SpanStatus::Ok,
+        Err(err) => SpanStatus::Error {
+          description: Cow::Owned(err.to_string()),
+        },
+      };
+    }
+  }
+}
+
+impl Drop for HostSpan {
+  fn drop(&mut self) {
+    let Some(mut span) = self.0.take() else {
+      return;
+    };
+    let span_id = span.span_context.span_id();
+    HOST_SPAN_STACK.with(|stack| {
+      stack
+        .borrow_mut()
+        .retain(|context| context.span_id() != span_id)
+    });
+    span.end_time = SystemTime::now();
+    export_span(span);
+  }
+}
+
+/// Exports the number of times the ops of a worker were called since
+/// `start_time` as the `deno.op.calls` metric, one data point per op.
+pub fn export_op_metrics<'a>(
+  start_time: SystemTime,
+  op_calls: impl IntoIterator<Item = (&'a str, u64)>,
+) {
+  if !is_exporting() {
+    return;
+  }
+  let time = SystemTime::now();
+  let data_points = op_calls
+    .into_iter()
+    .filter(|(_, calls)| *calls > 0)
+    .map(
+      |(name, calls)| opentelemetry_sdk::metrics::data::DataPoint {
+        value: calls,
+        start_time: Some(start_time),
+        time: Some(time),
+        attributes: vec![KeyValue::new("deno.op.name", name.to_string())],
+        exemplars: vec![],
+      },
+    )
+    .collect();
+  let sum = opentelemetry_sdk::metrics::data::Sum {
+    data_points,
+    temporality: Temporality::Cumulative,
+    is_monotonic: true,
+  };
+  export_metrics(ResourceMetrics {
+    resource: Resource::default(),
+    scope_metrics: vec![ScopeMetrics {
+      scope: BUILT_IN_INSTRUMENTATION_SCOPE.get().unwrap().clone(),
+      metrics: vec![Metric {
+        name: Cow::Borrowed("deno.op.calls"),
+        description: Cow::Borrowed("Number of op calls made by the worker"),
+        unit: Cow::Borrowed("{call}"),
+        data: Box::new(sum),
+      }],
+    }],
+  });
+}
+
 pub fn handle_log(record: &log::Record) {
   use log::Level;
 
@@ -811,10 +997,7 @@ fn op_otel_span_start<'s>(
   end_time: f64,
 ) -> Result<(), anyhow::Error> {
   if let Some(temporary_span) = state.try_take::<TemporarySpan>() {
-    let Some(Processors { spans, .. }) = OTEL_PROCESSORS.get() else {
-      return Ok(());
-    };
-    spans.on_end(temporary_span.0);
+    export_span(temporary_span.0);
   };
 
   let Some(InstrumentationScope(instrumentation_scope)) =
@@ -969,11 +1152,7 @@ fn op_otel_span_flush(state: &mut OpState) {
     return;
   };
 
-  let Some(Processors { spans, .. }) = OTEL_PROCESSORS.get() else {
-    return;
-  };
-
-  spans.on_end(temporary_span.0);
+  export_span(temporary_span.0);
 }
 
 // Holds data being built from JS before
@@ -1547,10 +1726,6 @@ fn op_otel_metrics_submit(state: &mut OpState) {
     return;
   };
 
-  let Some(Processors { metrics, .. }) = OTEL_PROCESSORS.get() else {
-    return;
-  };
-
   if let Some(current_metric) = temp.metric {
     let metric = Metric::from(current_metric);
     temp.scope_metrics.last_mut().unwrap().metrics.push(metric);
@@ -1559,7 +1734,7 @@ fn op_otel_metrics_submit(state: &mut OpState) {
   let resource = Resource::new(temp.resource_attributes);
   let scope_metrics = temp.scope_metrics;
 
-  metrics.submit(ResourceMetrics {
+  export_metrics(ResourceMetrics {
     resource,
     scope_metrics,
   });
@@ -16,7 +16,9 @@ use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ToJsBuffer;
+use deno_net::check_connect;
 use deno_net::raw::NetworkStream;
+use deno_net::ConnectTarget;
 use deno_tls::create_client_config;
 use deno_tls::rustls::ClientConfig;
 use deno_tls::rustls::ClientConnection;
@@ -50,7 +52,11 @@ use tokio::io::ReadHalf;
 use tokio::io::WriteHalf;
 use tokio::net::TcpStream;
 
+use deno_permissions::quota::acquire_quota;
+use deno_permissions::quota::hold_quota;
 use deno_permissions::PermissionCheckError;
+use deno_permissions::QuotaExceededError;
+use deno_permissions::QuotaKind;
 use fastwebsockets::CloseCode;
 use fastwebsockets::FragmentCollectorRead;
 use fastwebsockets::Frame;
@@ -89,6 +95,10 @@ pub enum WebsocketError {
   ConnectionFailed(#[from] HandshakeError),
   #[error(transparent)]
   Canceled(#[from] deno_core::Canceled),
+  #[error("{0}")]
+  ConnectDenied(deno_core::error::AnyError),
+  #[error(transparent)]
+  Quota(#[from] QuotaExceededError),
 }
 
 #[derive(Clone)]
@@ -159,10 +169,15 @@ pub fn op_ws_check_permission_and_cancel_handle<WP>(
 where
   WP: WebSocketPermissions + 'static,
 {
-  state.borrow_mut::<WP>().check_net_url(
-    &url::Url::parse(&url).map_err(WebsocketError::Url)?,
-    &api_name,
-  )?;
+  let url = url::Url::parse(&url).map_err(WebsocketError::Url)?;
+  state.borrow_mut::<WP>().check_net_url(&url, &api_name)?;
+  if let (Some(hostname), Some(port)) =
+    (url.host_str(), url.port_or_known_default())
+  {
+    let target = ConnectTarget::Tcp { hostname, port };
+    check_connect(state, target, &api_name)
+      .map_err(WebsocketError::ConnectDenied)?;
+  }
 
   if cancel_handle {
     let rid = state
@@ -481,6 +496,7 @@ where
 
   let uri: Uri = url.parse()?;
 
+  let permit = acquire_quota(&mut state.borrow_mut(), QuotaKind::Sockets)?;
   let handshake = handshake_websocket(&state, &uri, &protocols, headers)
     .map_err(WebsocketError::ConnectionFailed);
   let (stream, response) = match cancel_resource {
@@ -496,6 +512,7 @@ where
 
   let mut state = state.borrow_mut();
   let rid = state.resource_table.add(ServerWebSocket::new(stream));
+  hold_quota(&mut state, rid, permit);
 
   let protocol = match response.get("Sec-WebSocket-Protocol") {
     Some(header) => header.to_str().unwrap(),
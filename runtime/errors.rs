@@ -56,6 +56,7 @@ use deno_permissions::ChildPermissionError;
 use deno_permissions::NetDescriptorFromUrlParseError;
 use deno_permissions::PathResolveError;
 use deno_permissions::PermissionCheckError;
+use deno_permissions::QuotaExceededError;
 use deno_permissions::RunDescriptorParseError;
 use deno_permissions::SysDescriptorParseError;
 use deno_tls::TlsError;
@@ -590,6 +591,9 @@ fn get_ffi_dlfcn_error_class(e: &DlfcnError) -> &'static str {
     DlfcnError::RegisterSymbol { .. } => "Error",
     DlfcnError::Dlopen(_) => "Error",
     DlfcnError::Permission(e) => get_permission_check_error_class(e),
+    DlfcnError::DlopenDenied(e) => {
+      get_error_class_name(e).unwrap_or("PermissionDenied")
+    }
     DlfcnError::Other(e) => get_error_class_name(e).unwrap_or("Error"),
   }
 }
@@ -694,6 +698,7 @@ fn get_fetch_error(error: &FetchError) -> &'static str {
   match error {
     FetchError::Resource(e) => get_error_class_name(e).unwrap_or("Error"),
     FetchError::Permission(e) => get_permission_check_error_class(e),
+    FetchError::Quota(_) => "QuotaExceeded",
     FetchError::NetworkError => "TypeError",
     FetchError::FsNotGet(_) => "TypeError",
     FetchError::InvalidUrl(_) => "TypeError",
@@ -710,6 +715,10 @@ fn get_fetch_error(error: &FetchError) -> &'static str {
     FetchError::Method(_) => "TypeError",
     FetchError::ClientSend(_) => "TypeError",
     FetchError::RequestBuilderHook(_) => "TypeError",
+    FetchError::Denied(..) => "TypeError",
+    FetchError::Interceptor(e) => {
+      get_error_class_name(e).unwrap_or("TypeError")
+    }
     FetchError::Io(e) => get_io_error_class(e),
     FetchError::Hyper(e) => get_hyper_error_class(e),
   }
@@ -738,6 +747,10 @@ fn get_websocket_error(error: &WebsocketError) -> &'static str {
       let io_err: io::Error = e.to_owned().into();
       get_io_error_class(&io_err)
     }
+    WebsocketError::ConnectDenied(e) => {
+      get_error_class_name(e).unwrap_or("PermissionDenied")
+    }
+    WebsocketError::Quota(_) => "QuotaExceeded",
   }
 }
 
@@ -767,6 +780,7 @@ fn get_fs_ops_error(error: &FsOpsError) -> &'static str {
     Io(e) => get_io_error_class(e),
     OperationError(e) => get_fs_error(&e.err),
     Permission(e) => get_permission_check_error_class(e),
+    Quota(_) => "QuotaExceeded",
     Resource(e) | Other(e) => get_error_class_name(e).unwrap_or("Error"),
     InvalidUtf8(_) => "InvalidData",
     StripPrefix(_) => "Error",
@@ -839,6 +853,7 @@ fn get_net_error(error: &NetError) -> &'static str {
       get_error_class_name(e).unwrap_or("Error")
     }
     NetError::Permission(e) => get_permission_check_error_class(e),
+    NetError::Quota(_) => "QuotaExceeded",
     NetError::NoResolvedAddress => "Error",
     NetError::AddrParse(_) => "Error",
     NetError::Map(e) => get_net_map_error(e),
@@ -859,6 +874,9 @@ fn get_net_error(error: &NetError) -> &'static str {
     NetError::Tls(e) => get_tls_error_class(e),
     NetError::ListenTlsRequiresKey => "InvalidData",
     NetError::Reunite(_) => "Error",
+    NetError::ConnectDenied(e) => {
+      get_error_class_name(e).unwrap_or("PermissionDenied")
+    }
   }
 }
 
@@ -893,6 +911,9 @@ fn get_create_worker_error(error: &CreateWorkerError) -> &'static str {
     }
     CreateWorkerError::Io(e) => get_io_error_class(e),
     CreateWorkerError::MessagePort(e) => get_web_message_port_error_class(e),
+    CreateWorkerError::Denied(e) => {
+      get_error_class_name(e).unwrap_or("PermissionDenied")
+    }
   }
 }
 
@@ -964,6 +985,7 @@ fn get_process_error(error: &ProcessError) -> &'static str {
       get_io_error_class(e)
     }
     ProcessError::Permission(e) => get_permission_check_error_class(e),
+    ProcessError::Quota(_) => "QuotaExceeded",
     ProcessError::Resource(e) => get_error_class_name(e).unwrap_or("Error"),
     ProcessError::BorrowMut(_) => "Error",
     ProcessError::Which(_) => "Error",
@@ -971,6 +993,9 @@ fn get_process_error(error: &ProcessError) -> &'static str {
     ProcessError::Signal(e) => get_signal_error(e),
     ProcessError::MissingCmd => "Error",
     ProcessError::InvalidPid => "TypeError",
+    ProcessError::SpawnDenied(e) => {
+      get_error_class_name(e).unwrap_or("PermissionDenied")
+    }
     #[cfg(unix)]
     ProcessError::Nix(e) => get_nix_error_class(e),
     ProcessError::RunPermission(e) => match e {
@@ -1581,6 +1606,10 @@ pub fn get_error_class_name(e: &AnyError) -> Option<&'static str> {
       e.downcast_ref::<PermissionError>()
         .map(get_permission_error_class)
     })
+    .or_else(|| {
+      e.downcast_ref::<QuotaExceededError>()
+        .map(|_| "QuotaExceeded")
+    })
     .or_else(|| e.downcast_ref::<FsError>().map(get_fs_error))
     .or_else(|| {
       e.downcast_ref::<node::BlocklistError>()
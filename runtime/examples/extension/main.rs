@@ -51,6 +51,15 @@ async fn main() -> Result<(), AnyError> {
       npm_process_state_provider: Default::default(),
       root_cert_store_provider: Default::default(),
       fetch_dns_resolver: Default::default(),
+      fetch_interceptor: Default::default(),
+      connect_interceptor: Default::default(),
+      spawn_interceptor: Default::default(),
+      dlopen_interceptor: Default::default(),
+      kv_backend: Default::default(),
+      worker_creation_policy: Default::default(),
+      virtual_env: Default::default(),
+      signal_forwarder: Default::default(),
+      resource_quotas: Default::default(),
       shared_array_buffer_store: Default::default(),
       compiled_wasm_module_store: Default::default(),
       v8_code_cache: Default::default(),
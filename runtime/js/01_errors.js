@@ -158,6 +158,13 @@ class NotADirectory extends Error {
   }
 }
 
+class QuotaExceeded extends Error {
+  constructor(msg) {
+    super(msg);
+    this.name = "QuotaExceeded";
+  }
+}
+
 const errors = {
   NotFound,
   PermissionDenied,
@@ -184,6 +191,7 @@ const errors = {
   NetworkUnreachable,
   NotADirectory,
   NotCapable,
+  QuotaExceeded,
 };
 
 export { errors };
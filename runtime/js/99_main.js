@@ -14,6 +14,7 @@ import {
   op_bootstrap_pid,
   op_main_module,
   op_ppid,
+  op_report_unhandled_rejection,
   op_set_format_exception_callback,
   op_snapshot_options,
   op_worker_close,
@@ -312,6 +313,7 @@ core.registerErrorClass("FilesystemLoop", errors.FilesystemLoop);
 core.registerErrorClass("IsADirectory", errors.IsADirectory);
 core.registerErrorClass("NetworkUnreachable", errors.NetworkUnreachable);
 core.registerErrorClass("NotADirectory", errors.NotADirectory);
+core.registerErrorClass("QuotaExceeded", errors.QuotaExceeded);
 core.registerErrorBuilder(
   "DOMExceptionOperationError",
   function DOMExceptionOperationError(msg) {
@@ -405,6 +407,7 @@ function processUnhandledPromiseRejection(promise, reason) {
     return true;
   }
 
+  op_report_unhandled_rejection();
   return false;
 }
 
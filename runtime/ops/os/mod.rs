@@ -3,6 +3,7 @@
 use crate::sys_info;
 use crate::worker::ExitCode;
 use deno_core::op2;
+use deno_core::parking_lot::Mutex;
 use deno_core::v8;
 use deno_core::OpState;
 use deno_node::NODE_ENV_VAR_ALLOWLIST;
@@ -11,6 +12,7 @@ use deno_permissions::PermissionsContainer;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::env;
+use std::sync::Arc;
 
 deno_core::extension!(
   deno_os,
@@ -35,9 +37,13 @@ deno_core::extension!(
   ],
   options = {
     exit_code: ExitCode,
+    virtual_env: Option<VirtualEnv>,
   },
   state = |state, options| {
     state.put::<ExitCode>(options.exit_code);
+    if let Some(virtual_env) = options.virtual_env {
+      state.put::<VirtualEnv>(virtual_env);
+    }
   },
 );
 
@@ -62,13 +68,56 @@ deno_core::extension!(
     op_uid,
     op_runtime_memory_usage,
   ],
+  options = {
+    virtual_env: Option<VirtualEnv>,
+  },
   middleware = |op| match op.name {
     "op_exit" | "op_set_exit_code" | "op_get_exit_code" =>
       op.with_implementation_from(&deno_core::op_void_sync()),
     _ => op,
   },
+  state = |state, options| {
+    if let Some(virtual_env) = options.virtual_env {
+      state.put::<VirtualEnv>(virtual_env);
+    }
+  },
 );
 
+/// Environment variables that replace the process environment for the
+/// `Deno.env` APIs and the subprocesses of a worker. Clones share the same
+/// variables, so the embedder can read what the worker wrote.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualEnv(Arc<Mutex<HashMap<String, String>>>);
+
+impl VirtualEnv {
+  pub fn new(vars: HashMap<String, String>) -> Self {
+    Self(Arc::new(Mutex::new(vars)))
+  }
+
+  pub fn get(&self, key: &str) -> Option<String> {
+    self.0.lock().get(key).cloned()
+  }
+
+  pub fn set(&self, key: impl Into<String>, value: impl Into<String>) {
+    self.0.lock().insert(key.into(), value.into());
+  }
+
+  pub fn remove(&self, key: &str) -> Option<String> {
+    self.0.lock().remove(key)
+  }
+
+  /// A snapshot of all the variables.
+  pub fn vars(&self) -> HashMap<String, String> {
+    self.0.lock().clone()
+  }
+}
+
+impl From<HashMap<String, String>> for VirtualEnv {
+  fn from(vars: HashMap<String, String>) -> Self {
+    Self::new(vars)
+  }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum OsError {
   #[error(transparent)]
@@ -119,7 +168,10 @@ fn op_set_env(
   if value.contains('\0') {
     return Err(OsError::EnvInvalidValue(value.to_string()));
   }
-  env::set_var(key, value);
+  match state.try_borrow::<VirtualEnv>() {
+    Some(virtual_env) => virtual_env.set(key, value),
+    None => env::set_var(key, value),
+  }
   Ok(())
 }
 
@@ -129,7 +181,10 @@ fn op_env(
   state: &mut OpState,
 ) -> Result<HashMap<String, String>, deno_core::error::AnyError> {
   state.borrow_mut::<PermissionsContainer>().check_env_all()?;
-  Ok(env::vars().collect())
+  match state.try_borrow::<VirtualEnv>() {
+    Some(virtual_env) => Ok(virtual_env.vars()),
+    None => Ok(env::vars().collect()),
+  }
 }
 
 #[op2(stack_trace)]
@@ -152,6 +207,10 @@ fn op_get_env(
     return Err(OsError::EnvInvalidKey(key.to_string()));
   }
 
+  if let Some(virtual_env) = state.try_borrow::<VirtualEnv>() {
+    return Ok(virtual_env.get(&key));
+  }
+
   let r = match env::var(key) {
     Err(env::VarError::NotPresent) => None,
     v => Some(v?),
@@ -168,7 +227,12 @@ fn op_delete_env(
   if key.is_empty() || key.contains(&['=', '\0'] as &[char]) {
     return Err(OsError::EnvInvalidKey(key.to_string()));
   }
-  env::remove_var(key);
+  match state.try_borrow::<VirtualEnv>() {
+    Some(virtual_env) => {
+      virtual_env.remove(&key);
+    }
+    None => env::remove_var(key),
+  }
   Ok(())
 }
 
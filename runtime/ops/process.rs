@@ -14,7 +14,9 @@ use deno_io::ChildStderrResource;
 use deno_io::ChildStdinResource;
 use deno_io::ChildStdoutResource;
 use deno_io::IntoRawIoHandle;
+use deno_permissions::quota::acquire_quota;
 use deno_permissions::PermissionsContainer;
+use deno_permissions::QuotaKind;
 use deno_permissions::RunQueryDescriptor;
 use serde::Deserialize;
 use serde::Serialize;
@@ -27,11 +29,13 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process::ExitStatus;
 use std::rc::Rc;
+use std::sync::Arc;
 use tokio::process::Command;
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
+use crate::ops::os::VirtualEnv;
 use crate::ops::signal::SignalError;
 #[cfg(unix)]
 use std::os::unix::prelude::ExitStatusExt;
@@ -150,12 +154,43 @@ deno_core::extension!(
     deprecated::op_run_status,
     deprecated::op_kill,
   ],
-  options = { get_npm_process_state: Option<NpmProcessStateProviderRc>  },
+  options = {
+    get_npm_process_state: Option<NpmProcessStateProviderRc>,
+    spawn_interceptor: Option<Arc<dyn SpawnInterceptor>>,
+  },
   state = |state, options| {
     state.put::<NpmProcessStateProviderRc>(options.get_npm_process_state.unwrap_or(deno_fs::sync::MaybeArc::new(EmptyNpmProcessStateProvider)));
+    state.put(SpawnInterceptorState(options.spawn_interceptor));
   },
 );
 
+/// A subprocess that is about to be spawned.
+#[derive(Debug, Clone)]
+pub struct SpawnRequest {
+  /// The resolved path of the binary.
+  pub cmd: PathBuf,
+  pub args: Vec<String>,
+  /// The complete environment of the subprocess.
+  pub env: HashMap<OsString, OsString>,
+  pub cwd: PathBuf,
+}
+
+/// Decides whether scripts may spawn subprocesses. It is consulted after the
+/// run permission check passed, so embedders can enforce policies that
+/// `--allow-run` can't express.
+pub trait SpawnInterceptor: Send + Sync {
+  /// Returns the subprocess to spawn, either `request` as is or rewritten,
+  /// eg. to run the binary in a sandbox, or an error to deny spawning it.
+  /// Rewritten requests aren't checked against the permissions again.
+  fn intercept_spawn(
+    &self,
+    request: SpawnRequest,
+    api_name: &str,
+  ) -> Result<SpawnRequest, deno_core::error::AnyError>;
+}
+
+struct SpawnInterceptorState(Option<Arc<dyn SpawnInterceptor>>);
+
 /// Second member stores the pid separately from the RefCell. It's needed for
 /// `op_spawn_kill`, where the RefCell is borrowed mutably by `op_spawn_wait`.
 struct ChildResource(RefCell<tokio::process::Child>, u32);
@@ -208,6 +243,8 @@ pub enum ProcessError {
   #[error(transparent)]
   Permission(#[from] deno_permissions::PermissionCheckError),
   #[error(transparent)]
+  Quota(#[from] deno_permissions::QuotaExceededError),
+  #[error(transparent)]
   RunPermission(#[from] CheckRunPermissionError),
   #[error(transparent)]
   Resource(deno_core::error::AnyError),
@@ -223,6 +260,8 @@ pub enum ProcessError {
   Signal(#[from] SignalError),
   #[error("Missing cmd")]
   MissingCmd, // only for Deno.run
+  #[error("{0}")]
+  SpawnDenied(deno_core::error::AnyError),
 }
 
 #[derive(Deserialize)]
@@ -355,6 +394,8 @@ fn create_command(
     state,
     api_name,
   )?;
+  let (cmd, cmd_args, run_env) =
+    intercept_spawn(state, cmd, args.args, run_env, api_name)?;
   let mut command = std::process::Command::new(cmd);
 
   #[cfg(windows)]
@@ -367,16 +408,16 @@ fn create_command(
       log::warn!("detached processes are not currently supported on Windows");
     }
     if args.windows_raw_arguments {
-      for arg in args.args.iter() {
+      for arg in cmd_args.iter() {
         command.raw_arg(arg);
       }
     } else {
-      command.args(args.args);
+      command.args(cmd_args);
     }
   }
 
   #[cfg(not(windows))]
-  command.args(args.args);
+  command.args(cmd_args);
 
   command.current_dir(run_env.cwd);
   command.env_clear();
@@ -546,6 +587,7 @@ fn spawn_child(
     command.kill_on_drop(true);
   }
 
+  let permit = acquire_quota(state, QuotaKind::Subprocesses)?;
   let mut child = match command.spawn() {
     Ok(child) => child,
     Err(err) => {
@@ -593,6 +635,7 @@ fn spawn_child(
     }
   };
 
+  permit.keep();
   let pid = child.id().expect("Process ID should be set.");
 
   let stdin_rid = child
@@ -633,12 +676,11 @@ fn compute_run_cmd_and_check_permissions(
   state: &mut OpState,
   api_name: &str,
 ) -> Result<(PathBuf, RunEnv), ProcessError> {
-  let run_env =
-    compute_run_env(arg_cwd, arg_envs, arg_clear_env).map_err(|e| {
-      ProcessError::SpawnFailed {
-        command: arg_cmd.to_string(),
-        error: Box::new(e),
-      }
+  let virtual_env = state.try_borrow::<VirtualEnv>();
+  let run_env = compute_run_env(arg_cwd, arg_envs, arg_clear_env, virtual_env)
+    .map_err(|e| ProcessError::SpawnFailed {
+      command: arg_cmd.to_string(),
+      error: Box::new(e),
     })?;
   let cmd =
     resolve_cmd(arg_cmd, &run_env).map_err(|e| ProcessError::SpawnFailed {
@@ -662,15 +704,47 @@ struct RunEnv {
   cwd: PathBuf,
 }
 
+/// Passes the subprocess to the spawn interceptor of the worker, if any.
+fn intercept_spawn(
+  state: &OpState,
+  cmd: PathBuf,
+  args: Vec<String>,
+  run_env: RunEnv,
+  api_name: &str,
+) -> Result<(PathBuf, Vec<String>, RunEnv), ProcessError> {
+  let maybe_interceptor = state
+    .try_borrow::<SpawnInterceptorState>()
+    .and_then(|it| it.0.as_ref());
+  let Some(interceptor) = maybe_interceptor else {
+    return Ok((cmd, args, run_env));
+  };
+  let request = SpawnRequest {
+    cmd,
+    args,
+    env: run_env.envs,
+    cwd: run_env.cwd,
+  };
+  let request = interceptor
+    .intercept_spawn(request, api_name)
+    .map_err(ProcessError::SpawnDenied)?;
+  let run_env = RunEnv {
+    envs: request.env,
+    cwd: request.cwd,
+  };
+  Ok((request.cmd, request.args, run_env))
+}
+
 /// Computes the current environment, which will then be used to inform
 /// permissions and finally spawning. This is very important to compute
 /// ahead of time so that the environment used to verify permissions is
 /// the same environment used to spawn the sub command. This protects against
 /// someone doing timing attacks by changing the environment on a worker.
+/// Workers with a virtual environment pass it instead of the process one.
 fn compute_run_env(
   arg_cwd: Option<&str>,
   arg_envs: &[(String, String)],
   arg_clear_env: bool,
+  virtual_env: Option<&VirtualEnv>,
 ) -> Result<RunEnv, ProcessError> {
   #[allow(clippy::disallowed_methods)]
   let cwd =
@@ -684,7 +758,17 @@ fn compute_run_env(
       .map(|(k, v)| (OsString::from(k), OsString::from(v)))
       .collect()
   } else {
-    let mut envs = std::env::vars_os()
+    let base_envs: Box<dyn Iterator<Item = (OsString, OsString)>> =
+      match virtual_env {
+        Some(virtual_env) => Box::new(
+          virtual_env
+            .vars()
+            .into_iter()
+            .map(|(k, v)| (OsString::from(k), OsString::from(v))),
+        ),
+        None => Box::new(std::env::vars_os()),
+      };
+    let mut envs = base_envs
       .map(|(k, v)| {
         (
           if cfg!(windows) {
@@ -852,10 +936,12 @@ fn op_spawn_sync(
   let stderr = matches!(args.stdio.stderr, StdioOrRid::Stdio(Stdio::Piped));
   let (mut command, _, _, _) =
     create_command(state, args, "Deno.Command().outputSync()")?;
+  let permit = acquire_quota(state, QuotaKind::Subprocesses)?;
   let output = command.output().map_err(|e| ProcessError::SpawnFailed {
     command: command.get_program().to_string_lossy().to_string(),
     error: Box::new(e.into()),
   })?;
+  permit.keep();
 
   Ok(SpawnOutput {
     status: output.status.try_into()?,
@@ -942,9 +1028,16 @@ mod deprecated {
       state,
       "Deno.run()",
     )?;
+    let (cmd, cmd_args, run_env) = intercept_spawn(
+      state,
+      cmd,
+      args.iter().skip(1).cloned().collect(),
+      run_env,
+      "Deno.run()",
+    )?;
 
     let mut c = Command::new(cmd);
-    for arg in args.iter().skip(1) {
+    for arg in cmd_args {
       c.arg(arg);
     }
     c.current_dir(run_env.cwd);
@@ -985,7 +1078,9 @@ mod deprecated {
     c.kill_on_drop(true);
 
     // Spawn the command.
+    let permit = acquire_quota(state, QuotaKind::Subprocesses)?;
     let mut child = c.spawn()?;
+    permit.keep();
     let pid = child.id();
 
     let stdin_rid = match child.stdin.take() {
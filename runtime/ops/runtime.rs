@@ -6,7 +6,7 @@ use deno_core::OpState;
 
 deno_core::extension!(
   deno_runtime,
-  ops = [op_main_module, op_ppid],
+  ops = [op_main_module, op_ppid, op_report_unhandled_rejection],
   options = { main_module: ModuleSpecifier },
   state = |state, options| {
     state.put::<ModuleSpecifier>(options.main_module);
@@ -20,6 +20,16 @@ fn op_main_module(state: &mut OpState) -> String {
   main_url.to_string()
 }
 
+/// Put in the op state of a worker once a promise rejection wasn't handled
+/// by its scripts, which makes the worker fail with the rejection.
+#[derive(Debug, Clone, Copy)]
+pub struct UnhandledRejection;
+
+#[op2(fast)]
+fn op_report_unhandled_rejection(state: &mut OpState) {
+  state.put(UnhandledRejection);
+}
+
 /// This is an op instead of being done at initialization time because
 /// it's expensive to retrieve the ppid on Windows.
 #[op2(fast)]
@@ -1,5 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 use deno_core::op2;
+use deno_core::parking_lot::Mutex;
 use deno_core::AsyncRefCell;
 use deno_core::CancelFuture;
 use deno_core::CancelHandle;
@@ -12,10 +13,10 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 #[cfg(unix)]
 use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::rc::Rc;
 #[cfg(unix)]
 use std::sync::atomic::AtomicBool;
-#[cfg(unix)]
 use std::sync::Arc;
 
 #[cfg(unix)]
@@ -32,15 +33,22 @@ use tokio::signal::windows::ctrl_c;
 use tokio::signal::windows::CtrlBreak;
 #[cfg(windows)]
 use tokio::signal::windows::CtrlC;
+use tokio::sync::mpsc;
 
 deno_core::extension!(
   deno_signal,
   ops = [op_signal_bind, op_signal_unbind, op_signal_poll],
-  state = |state| {
+  options = {
+    signal_forwarder: Option<SignalForwarder>,
+  },
+  state = |state, options| {
     #[cfg(unix)]
     {
       state.put(SignalState::default());
     }
+    if let Some(signal_forwarder) = options.signal_forwarder {
+      state.put::<SignalForwarder>(signal_forwarder);
+    }
   }
 );
 
@@ -56,6 +64,70 @@ pub enum SignalError {
   Io(#[from] std::io::Error),
 }
 
+/// The signal a listener is bound to, and where it's notified.
+type ForwardedSignalListener = (libc::c_int, mpsc::UnboundedSender<()>);
+
+/// Delivers signals to the `Deno.addSignalListener` listeners of the workers
+/// it's passed to, instead of the OS. Listeners of those workers don't
+/// install process-wide signal handlers, except for the signals passed to
+/// [`SignalForwarder::with_os_signals`], so the embedder keeps its own
+/// handling, eg. of `SIGINT`. Clones share the same listeners.
+#[derive(Debug, Clone, Default)]
+pub struct SignalForwarder {
+  listeners: Arc<Mutex<Vec<ForwardedSignalListener>>>,
+  os_signals: Arc<HashSet<libc::c_int>>,
+}
+
+impl SignalForwarder {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Listeners of `signals`, eg. `["SIGUSR1"]`, are still bound to the OS
+  /// signals, in addition to the ones sent through the forwarder.
+  pub fn with_os_signals(
+    signals: &[&str],
+  ) -> Result<Self, crate::signal::InvalidSignalStrError> {
+    let os_signals = signals
+      .iter()
+      .map(|signal| crate::signal::signal_str_to_int(signal))
+      .collect::<Result<_, _>>()?;
+    Ok(Self {
+      listeners: Default::default(),
+      os_signals: Arc::new(os_signals),
+    })
+  }
+
+  /// Delivers `signal`, eg. `"SIGTERM"`, to the listeners of the workers.
+  /// Returns the number of listeners it was delivered to.
+  pub fn send(
+    &self,
+    signal: &str,
+  ) -> Result<usize, crate::signal::InvalidSignalStrError> {
+    let signo = crate::signal::signal_str_to_int(signal)?;
+    let mut listeners = self.listeners.lock();
+    // drop the listeners that were unbound
+    listeners.retain(|(_, sender)| !sender.is_closed());
+    let mut count = 0;
+    for (listener_signo, sender) in listeners.iter() {
+      if *listener_signo == signo && sender.send(()).is_ok() {
+        count += 1;
+      }
+    }
+    Ok(count)
+  }
+
+  fn binds_os_signal(&self, signo: libc::c_int) -> bool {
+    self.os_signals.contains(&signo)
+  }
+
+  fn listen(&self, signo: libc::c_int) -> mpsc::UnboundedReceiver<()> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    self.listeners.lock().push((signo, sender));
+    receiver
+  }
+}
+
 #[cfg(unix)]
 #[derive(Default)]
 struct SignalState {
@@ -88,12 +160,30 @@ impl SignalState {
   }
 }
 
+/// A signal of the OS, or one sent through a [`SignalForwarder`].
+enum SignalStream {
+  #[cfg(unix)]
+  Os(Signal),
+  #[cfg(windows)]
+  Os(WindowsSignal),
+  Forwarded(mpsc::UnboundedReceiver<()>),
+}
+
+impl SignalStream {
+  async fn recv(&mut self) -> Option<()> {
+    match self {
+      SignalStream::Os(signal) => signal.recv().await,
+      SignalStream::Forwarded(receiver) => receiver.recv().await,
+    }
+  }
+}
+
 #[cfg(unix)]
 /// The resource for signal stream.
 /// The second element is the waker of polling future.
 struct SignalStreamResource {
-  signal: AsyncRefCell<Signal>,
-  enable_default_handler: Arc<AtomicBool>,
+  signal: AsyncRefCell<SignalStream>,
+  enable_default_handler: Option<Arc<AtomicBool>>,
   cancel: CancelHandle,
 }
 
@@ -142,7 +232,7 @@ impl WindowsSignal {
 
 #[cfg(windows)]
 struct SignalStreamResource {
-  signal: AsyncRefCell<WindowsSignal>,
+  signal: AsyncRefCell<SignalStream>,
   cancel: CancelHandle,
 }
 
@@ -169,7 +259,21 @@ fn op_signal_bind(
     return Err(SignalError::SignalNotAllowed(sig.to_string()));
   }
 
-  let signal = AsyncRefCell::new(signal(SignalKind::from_raw(signo))?);
+  if let Some(signal_forwarder) = state.try_borrow::<SignalForwarder>() {
+    if !signal_forwarder.binds_os_signal(signo) {
+      let resource = SignalStreamResource {
+        signal: AsyncRefCell::new(SignalStream::Forwarded(
+          signal_forwarder.listen(signo),
+        )),
+        cancel: Default::default(),
+        enable_default_handler: None,
+      };
+      return Ok(state.resource_table.add(resource));
+    }
+  }
+
+  let signal =
+    AsyncRefCell::new(SignalStream::Os(signal(SignalKind::from_raw(signo))?));
 
   let (enable_default_handler, has_default_handler) = state
     .borrow_mut::<SignalState>()
@@ -178,7 +282,7 @@ fn op_signal_bind(
   let resource = SignalStreamResource {
     signal,
     cancel: Default::default(),
-    enable_default_handler: enable_default_handler.clone(),
+    enable_default_handler: Some(enable_default_handler.clone()),
   };
   let rid = state.resource_table.add(resource);
 
@@ -202,8 +306,19 @@ fn op_signal_bind(
   #[string] sig: &str,
 ) -> Result<ResourceId, SignalError> {
   let signo = crate::signal::signal_str_to_int(sig)?;
+  if let Some(signal_forwarder) = state.try_borrow::<SignalForwarder>() {
+    if !signal_forwarder.binds_os_signal(signo) {
+      let resource = SignalStreamResource {
+        signal: AsyncRefCell::new(SignalStream::Forwarded(
+          signal_forwarder.listen(signo),
+        )),
+        cancel: Default::default(),
+      };
+      return Ok(state.resource_table.add(resource));
+    }
+  }
   let resource = SignalStreamResource {
-    signal: AsyncRefCell::new(match signo {
+    signal: AsyncRefCell::new(SignalStream::Os(match signo {
       // SIGINT
       2 => ctrl_c()
         .expect("There was an issue creating ctrl+c event stream.")
@@ -213,7 +328,7 @@ fn op_signal_bind(
         .expect("There was an issue creating ctrl+break event stream.")
         .into(),
       _ => unimplemented!(),
-    }),
+    })),
     cancel: Default::default(),
   };
   let rid = state.resource_table.add(resource);
@@ -247,10 +362,8 @@ pub fn op_signal_unbind(
   let resource = state.resource_table.take::<SignalStreamResource>(rid)?;
 
   #[cfg(unix)]
-  {
-    resource
-      .enable_default_handler
-      .store(true, std::sync::atomic::Ordering::Release);
+  if let Some(enable_default_handler) = &resource.enable_default_handler {
+    enable_default_handler.store(true, std::sync::atomic::Ordering::Release);
   }
 
   resource.close();
@@ -10,6 +10,7 @@ use crate::web_worker::WorkerControlEvent;
 use crate::web_worker::WorkerId;
 use crate::web_worker::WorkerMetadata;
 use crate::worker::FormatJsErrorFn;
+use deno_core::error::AnyError;
 use deno_core::op2;
 use deno_core::serde::Deserialize;
 use deno_core::CancelFuture;
@@ -54,6 +55,35 @@ struct CreateWebWorkerCbHolder(Arc<CreateWebWorkerCb>);
 #[derive(Clone)]
 struct FormatJsErrorFnHolder(Option<Arc<FormatJsErrorFn>>);
 
+/// A `new Worker()` call, passed to a [`WorkerCreationPolicy`].
+#[derive(Debug, Clone)]
+pub struct WorkerCreationRequest {
+  pub specifier: ModuleSpecifier,
+  pub name: String,
+  /// The permissions the worker gets, derived from the permissions of the
+  /// creating worker and the `deno.permissions` option.
+  pub permissions: PermissionsContainer,
+  /// Web workers created by the creating worker that are still running.
+  pub running_workers: usize,
+  /// JavaScript source of the main module, to run it instead of loading
+  /// `specifier`.
+  pub module_source: Option<String>,
+}
+
+/// Decides whether scripts may create a web worker. Denying it throws the
+/// error from `new Worker()`.
+///
+/// The request may be returned changed, eg. with other permissions or with
+/// the source of the main module served by the host.
+pub trait WorkerCreationPolicy: Send + Sync {
+  fn check_worker_creation(
+    &self,
+    request: WorkerCreationRequest,
+  ) -> Result<WorkerCreationRequest, AnyError>;
+}
+
+struct WorkerCreationPolicyState(Option<Arc<dyn WorkerCreationPolicy>>);
+
 pub struct WorkerThread {
   worker_handle: WebWorkerHandle,
   cancel_handle: Rc<CancelHandle>,
@@ -93,6 +123,7 @@ deno_core::extension!(
   options = {
     create_web_worker_cb: Arc<CreateWebWorkerCb>,
     format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
+    worker_creation_policy: Option<Arc<dyn WorkerCreationPolicy>>,
   },
   state = |state, options| {
     state.put::<WorkersTable>(WorkersTable::default());
@@ -103,6 +134,7 @@ deno_core::extension!(
     let format_js_error_fn_holder =
       FormatJsErrorFnHolder(options.format_js_error_fn);
     state.put::<FormatJsErrorFnHolder>(format_js_error_fn_holder);
+    state.put(WorkerCreationPolicyState(options.worker_creation_policy));
   },
 );
 
@@ -130,6 +162,8 @@ pub enum CreateWorkerError {
   MessagePort(#[from] MessagePortError),
   #[error("{0}")]
   Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Denied(AnyError),
 }
 
 /// Create worker as the host
@@ -175,8 +209,25 @@ fn op_create_worker(
   let format_js_error_fn = state.borrow::<FormatJsErrorFnHolder>().clone();
   let worker_id = WorkerId::new();
 
-  let module_specifier = deno_core::resolve_url(&specifier)?;
-  let worker_name = args_name.unwrap_or_default();
+  let mut module_specifier = deno_core::resolve_url(&specifier)?;
+  let mut worker_name = args_name.unwrap_or_default();
+  let mut worker_permissions = worker_permissions;
+  let mut maybe_module_source = None;
+  if let Some(policy) = &state.borrow::<WorkerCreationPolicyState>().0 {
+    let request = policy
+      .check_worker_creation(WorkerCreationRequest {
+        specifier: module_specifier,
+        name: worker_name,
+        permissions: worker_permissions,
+        running_workers: state.borrow::<WorkersTable>().len(),
+        module_source: None,
+      })
+      .map_err(CreateWorkerError::Denied)?;
+    module_specifier = request.specifier;
+    worker_name = request.name;
+    worker_permissions = request.permissions;
+    maybe_module_source = request.module_source;
+  }
 
   let (handle_sender, handle_receiver) =
     std::sync::mpsc::sync_channel::<SendableWebWorkerHandle>(1);
@@ -224,6 +275,7 @@ fn op_create_worker(
       worker,
       module_specifier,
       maybe_source_code,
+      maybe_module_source,
       format_js_error_fn.0,
     )
   })?;
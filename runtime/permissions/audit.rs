@@ -0,0 +1,63 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::JsStackFrame;
+use deno_core::parking_lot::Mutex;
+use once_cell::sync::Lazy;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// A single permission check, as reported to a [`PermissionAuditor`].
+#[derive(Debug, Clone)]
+pub struct PermissionAuditEntry {
+  /// Name of the permission, eg. `"read"` or `"net"`.
+  pub name: &'static str,
+  pub api_name: Option<String>,
+  /// The resource access was checked for, eg. a path or a host.
+  pub resource: Option<String>,
+  pub granted: bool,
+  /// Whether the user was prompted as part of the check.
+  pub prompted: bool,
+  /// Stack of the JavaScript call that requested the permission. Only
+  /// available for workers created with stack traces enabled in ops.
+  pub stack: Option<Vec<JsStackFrame>>,
+}
+
+/// Receives every permission check, both granted and denied.
+pub trait PermissionAuditor: Send + Sync {
+  fn record(&self, entry: PermissionAuditEntry);
+}
+
+impl<F: Fn(PermissionAuditEntry) + Send + Sync> PermissionAuditor for F {
+  fn record(&self, entry: PermissionAuditEntry) {
+    self(entry)
+  }
+}
+
+static PERMISSION_AUDITOR: Lazy<Mutex<Option<Arc<dyn PermissionAuditor>>>> =
+  Lazy::new(|| Mutex::new(None));
+
+// Checked before taking the lock, so that checks stay cheap without auditor.
+static HAS_PERMISSION_AUDITOR: AtomicBool = AtomicBool::new(false);
+
+/// Sets the process wide auditor, or removes it when `None`.
+pub fn set_auditor(auditor: Option<Arc<dyn PermissionAuditor>>) {
+  let mut maybe_auditor = PERMISSION_AUDITOR.lock();
+  HAS_PERMISSION_AUDITOR.store(auditor.is_some(), Ordering::SeqCst);
+  *maybe_auditor = auditor;
+}
+
+#[inline]
+pub fn has_auditor() -> bool {
+  HAS_PERMISSION_AUDITOR.load(Ordering::Relaxed)
+}
+
+pub(crate) fn record(entry: impl FnOnce() -> PermissionAuditEntry) {
+  if !has_auditor() {
+    return;
+  }
+  let maybe_auditor = PERMISSION_AUDITOR.lock().clone();
+  if let Some(auditor) = maybe_auditor {
+    auditor.record(entry());
+  }
+}
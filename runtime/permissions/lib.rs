@@ -27,15 +27,31 @@ use std::path::PathBuf;
 use std::string::ToString;
 use std::sync::Arc;
 
+pub mod audit;
 pub mod prompter;
+pub mod quota;
+pub mod rules;
 use prompter::permission_prompt;
 use prompter::PERMISSION_EMOJI;
 
+pub use audit::PermissionAuditEntry;
+pub use audit::PermissionAuditor;
 pub use prompter::set_prompt_callbacks;
 pub use prompter::set_prompter;
+pub use prompter::AsyncPermissionPrompter;
+pub use prompter::AsyncPromptAdapter;
 pub use prompter::PermissionPrompter;
+pub use prompter::PromptCache;
+pub use prompter::PromptCachePolicy;
 pub use prompter::PromptCallback;
+pub use prompter::PromptRequest;
 pub use prompter::PromptResponse;
+pub use prompter::RememberedPrompt;
+pub use quota::QuotaExceededError;
+pub use quota::QuotaKind;
+pub use quota::QuotaObserver;
+pub use quota::ResourceQuotas;
+pub use rules::PermissionRules;
 
 #[derive(Debug, thiserror::Error)]
 #[error("Requires {access}, {}", format_permission_error(.name))]
@@ -56,7 +72,7 @@ fn format_permission_error(name: &'static str) -> String {
 /// is in the "fully-granted" state.
 macro_rules! skip_check_if_is_permission_fully_granted {
   ($this:ident) => {
-    if $this.is_allow_all() {
+    if $this.is_allow_all() && !audit::has_auditor() {
       return Ok(());
     }
   };
@@ -161,8 +177,15 @@ impl PermissionState {
     api_name: Option<&str>,
     info: Option<&str>,
     prompt: bool,
+    prompt_cache: Option<&PromptCache>,
   ) -> (Result<(), PermissionDeniedError>, bool, bool) {
-    self.check2(name, api_name, || info.map(|s| s.to_string()), prompt)
+    self.check2(
+      name,
+      api_name,
+      || info.map(|s| s.to_string()),
+      prompt,
+      prompt_cache,
+    )
   }
 
   #[inline]
@@ -172,34 +195,57 @@ impl PermissionState {
     api_name: Option<&str>,
     info: impl Fn() -> Option<String>,
     prompt: bool,
+    prompt_cache: Option<&PromptCache>,
   ) -> (Result<(), PermissionDeniedError>, bool, bool) {
-    match self {
+    // the prompt consumes the stack trace, so grab it for the audit first
+    let stack = audit::has_auditor()
+      .then(prompter::current_stacktrace)
+      .flatten();
+    let output = match self {
       PermissionState::Granted => {
-        Self::log_perm_access(name, info);
+        Self::log_perm_access(name, &info);
         (Ok(()), false, false)
       }
       PermissionState::Prompt if prompt => {
+        let resource = info();
         let msg = format!(
           "{} access{}",
           name,
-          info()
+          resource
+            .as_ref()
             .map(|info| { format!(" to {info}") })
             .unwrap_or_default(),
         );
-        match permission_prompt(&msg, name, api_name, true) {
+        match permission_prompt(
+          &msg,
+          name,
+          resource.as_deref(),
+          api_name,
+          true,
+          prompt_cache,
+        ) {
           PromptResponse::Allow => {
-            Self::log_perm_access(name, info);
+            Self::log_perm_access(name, &info);
             (Ok(()), true, false)
           }
           PromptResponse::AllowAll => {
-            Self::log_perm_access(name, info);
+            Self::log_perm_access(name, &info);
             (Ok(()), true, true)
           }
-          PromptResponse::Deny => (Err(Self::error(name, info)), true, false),
+          PromptResponse::Deny => (Err(Self::error(name, &info)), true, false),
         }
       }
-      _ => (Err(Self::error(name, info)), false, false),
-    }
+      _ => (Err(Self::error(name, &info)), false, false),
+    };
+    audit::record(|| PermissionAuditEntry {
+      name,
+      api_name: api_name.map(|api_name| api_name.to_string()),
+      resource: info(),
+      granted: output.0.is_ok(),
+      prompted: output.1,
+      stack,
+    });
+    output
   }
 }
 
@@ -220,6 +266,7 @@ pub struct UnitPermission {
   pub description: &'static str,
   pub state: PermissionState,
   pub prompt: bool,
+  prompt_cache: Option<PromptCache>,
 }
 
 impl UnitPermission {
@@ -233,8 +280,10 @@ impl UnitPermission {
         == permission_prompt(
           &format!("access to {}", self.description),
           self.name,
+          None,
           Some("Deno.permissions.query()"),
           false,
+          self.prompt_cache.as_ref(),
         )
       {
         self.state = PermissionState::Granted;
@@ -253,8 +302,13 @@ impl UnitPermission {
   }
 
   pub fn check(&mut self) -> Result<(), PermissionDeniedError> {
-    let (result, prompted, _is_allow_all) =
-      self.state.check(self.name, None, None, self.prompt);
+    let (result, prompted, _is_allow_all) = self.state.check(
+      self.name,
+      None,
+      None,
+      self.prompt,
+      self.prompt_cache.as_ref(),
+    );
     if prompted {
       if result.is_ok() {
         self.state = PermissionState::Granted;
@@ -361,6 +415,7 @@ pub struct UnaryPermission<TQuery: QueryDescriptor + ?Sized> {
   prompt_denied_global: bool,
   prompt_denied_list: HashSet<TQuery::DenyDesc>,
   prompt: bool,
+  prompt_cache: Option<PromptCache>,
 }
 
 impl<TQuery: QueryDescriptor> Default for UnaryPermission<TQuery> {
@@ -373,6 +428,7 @@ impl<TQuery: QueryDescriptor> Default for UnaryPermission<TQuery> {
       prompt_denied_global: Default::default(),
       prompt_denied_list: Default::default(),
       prompt: Default::default(),
+      prompt_cache: Default::default(),
     }
   }
 }
@@ -387,6 +443,7 @@ impl<TQuery: QueryDescriptor> Clone for UnaryPermission<TQuery> {
       prompt_denied_global: self.prompt_denied_global,
       prompt_denied_list: self.prompt_denied_list.clone(),
       prompt: self.prompt,
+      prompt_cache: self.prompt_cache.clone(),
     }
   }
 }
@@ -426,6 +483,7 @@ impl<TQuery: QueryDescriptor> UnaryPermission<TQuery> {
         api_name,
         || desc.map(|d| format_display_name(d.display_name())),
         self.prompt,
+        self.prompt_cache.as_ref(),
       );
     if prompted {
       if result.is_ok() {
@@ -489,15 +547,17 @@ impl<TQuery: QueryDescriptor> UnaryPermission<TQuery> {
     }
     let mut message = String::with_capacity(40);
     message.push_str(&format!("{} access", TQuery::flag_name()));
-    if let Some(desc) = desc {
-      message
-        .push_str(&format!(" to {}", format_display_name(desc.display_name())));
+    let resource = desc.map(|desc| format_display_name(desc.display_name()));
+    if let Some(resource) = &resource {
+      message.push_str(&format!(" to {}", resource));
     }
     match permission_prompt(
       &message,
       TQuery::flag_name(),
+      resource.as_deref(),
       Some("Deno.permissions.request()"),
       true,
+      self.prompt_cache.as_ref(),
     ) {
       PromptResponse::Allow => {
         self.insert_granted(desc);
@@ -643,6 +703,7 @@ impl<TQuery: QueryDescriptor> UnaryPermission<TQuery> {
     perms.flag_denied_global = self.flag_denied_global;
     perms.prompt_denied_global = self.prompt_denied_global;
     perms.prompt = self.prompt;
+    perms.prompt_cache = self.prompt_cache.clone();
     perms.flag_denied_list.clone_from(&self.flag_denied_list);
     perms
       .prompt_denied_list
@@ -1966,6 +2027,7 @@ impl UnaryPermission<RunQueryDescriptor> {
         api_name,
         || None,
         /* prompt */ false,
+        None,
       );
     result.is_ok()
   }
@@ -2280,6 +2342,19 @@ impl Permissions {
       all: Permissions::new_all(false),
     }
   }
+
+  /// Remembers answers to prompts for these permissions in `prompt_cache`.
+  pub fn set_prompt_cache(&mut self, prompt_cache: Option<PromptCache>) {
+    self.read.prompt_cache.clone_from(&prompt_cache);
+    self.write.prompt_cache.clone_from(&prompt_cache);
+    self.net.prompt_cache.clone_from(&prompt_cache);
+    self.env.prompt_cache.clone_from(&prompt_cache);
+    self.sys.prompt_cache.clone_from(&prompt_cache);
+    self.run.prompt_cache.clone_from(&prompt_cache);
+    self.ffi.prompt_cache.clone_from(&prompt_cache);
+    self.import.prompt_cache.clone_from(&prompt_cache);
+    self.all.prompt_cache = prompt_cache;
+  }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -2330,6 +2405,7 @@ pub enum PermissionCheckError {
 pub struct PermissionsContainer {
   descriptor_parser: Arc<dyn PermissionDescriptorParser>,
   inner: Arc<Mutex<Permissions>>,
+  rules: Option<Arc<PermissionRules>>,
 }
 
 impl PermissionsContainer {
@@ -2340,15 +2416,45 @@ impl PermissionsContainer {
     Self {
       descriptor_parser,
       inner: Arc::new(Mutex::new(perms)),
+      rules: None,
     }
   }
 
+  /// Checks `rules` before the permissions, see [`PermissionRules`].
+  pub fn with_rules(mut self, rules: PermissionRules) -> Self {
+    self.rules = Some(Arc::new(rules));
+    self
+  }
+
   pub fn allow_all(
     descriptor_parser: Arc<dyn PermissionDescriptorParser>,
   ) -> Self {
     Self::new(descriptor_parser, Permissions::allow_all())
   }
 
+  /// Remembers answers to prompts in `prompt_cache`. Deep clones of the
+  /// container get a copy of it.
+  pub fn with_prompt_cache(self, prompt_cache: PromptCache) -> Self {
+    self.inner.lock().set_prompt_cache(Some(prompt_cache));
+    self
+  }
+
+  /// Creates a container with a copy of the permissions and remembered prompt
+  /// answers, so that permissions granted or revoked through one of them
+  /// don't change the other.
+  pub fn deep_clone(&self) -> Self {
+    let mut perms = self.inner.lock().clone();
+    if let Some(prompt_cache) = &perms.all.prompt_cache {
+      let prompt_cache = prompt_cache.deep_clone();
+      perms.set_prompt_cache(Some(prompt_cache));
+    }
+    Self {
+      descriptor_parser: self.descriptor_parser.clone(),
+      inner: Arc::new(Mutex::new(perms)),
+      rules: self.rules.clone(),
+    }
+  }
+
   pub fn create_child_permissions(
     &self,
     child_permissions_arg: ChildPermissionsArg,
@@ -2456,10 +2562,81 @@ impl PermissionsContainer {
       },
     )?;
 
-    Ok(PermissionsContainer::new(
-      self.descriptor_parser.clone(),
-      worker_perms,
-    ))
+    let mut container =
+      PermissionsContainer::new(self.descriptor_parser.clone(), worker_perms);
+    if let Some(rules) = &self.rules {
+      container = container.with_rules(rules.deny_only());
+    }
+    Ok(container)
+  }
+
+  /// Returns whether the rules granted an access, or an error when they
+  /// denied it.
+  fn check_rules(
+    &self,
+    name: &'static str,
+    api_name: Option<&str>,
+    info: impl Fn() -> String,
+    check: impl FnOnce(&PermissionRules) -> Option<bool>,
+  ) -> Result<bool, PermissionDeniedError> {
+    let Some(granted) = self.rules.as_deref().and_then(check) else {
+      return Ok(false);
+    };
+    audit::record(|| PermissionAuditEntry {
+      name,
+      api_name: api_name.map(|api_name| api_name.to_string()),
+      resource: Some(info()),
+      granted,
+      prompted: false,
+      stack: None,
+    });
+    if granted {
+      Ok(true)
+    } else {
+      Err(PermissionState::error(name, || Some(info())))
+    }
+  }
+
+  fn check_read_rules(
+    &self,
+    path: &Path,
+    display: &str,
+    api_name: Option<&str>,
+  ) -> Result<bool, PermissionDeniedError> {
+    self.check_rules(
+      "read",
+      api_name,
+      || format!("\"{display}\""),
+      |rules| rules.check_read(path),
+    )
+  }
+
+  fn check_write_rules(
+    &self,
+    path: &Path,
+    display: &str,
+    api_name: Option<&str>,
+  ) -> Result<bool, PermissionDeniedError> {
+    self.check_rules(
+      "write",
+      api_name,
+      || format!("\"{display}\""),
+      |rules| rules.check_write(path),
+    )
+  }
+
+  fn check_net_rules(
+    &self,
+    desc: &NetDescriptor,
+    protocol: Option<rules::NetProtocol>,
+    api_name: &str,
+  ) -> Result<bool, PermissionDeniedError> {
+    self.check_rules(
+      "net",
+      Some(api_name),
+      || format!("\"{desc}\""),
+      |rules| rules.check_net(&desc.0, desc.1, protocol),
+    )
   }
 
   #[inline(always)]
@@ -2525,6 +2702,12 @@ impl PermissionsContainer {
     path: &str,
     api_name: Option<&str>,
   ) -> Result<PathBuf, PermissionCheckError> {
+    if self.rules.is_some() {
+      let desc = self.descriptor_parser.parse_path_query(path)?;
+      if self.check_read_rules(&desc.resolved, path, api_name)? {
+        return Ok(desc.resolved);
+      }
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.read;
     if inner.is_allow_all() {
@@ -2543,6 +2726,9 @@ impl PermissionsContainer {
     path: &'a Path,
     api_name: Option<&str>,
   ) -> Result<Cow<'a, Path>, PermissionCheckError> {
+    if self.check_read_rules(path, &path.to_string_lossy(), api_name)? {
+      return Ok(Cow::Borrowed(path));
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.read;
     if inner.is_allow_all() {
@@ -2567,12 +2753,16 @@ impl PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
+    let display = format!("<{}>", display);
+    if self.check_read_rules(path, &display, Some(api_name))? {
+      return Ok(());
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.read;
     skip_check_if_is_permission_fully_granted!(inner);
     inner.check(
       &PathQueryDescriptor {
-        requested: format!("<{}>", display),
+        requested: display,
         resolved: path.to_path_buf(),
       }
       .into_read(),
@@ -2586,6 +2776,13 @@ impl PermissionsContainer {
     &self,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
+    if self
+      .rules
+      .as_ref()
+      .is_some_and(|rules| rules.has_read_deny())
+    {
+      return Err(PermissionState::error("read", || None).into());
+    }
     self.inner.lock().read.check_all(Some(api_name))?;
     Ok(())
   }
@@ -2612,6 +2809,12 @@ impl PermissionsContainer {
     path: &str,
     api_name: Option<&str>,
   ) -> Result<PathBuf, PermissionCheckError> {
+    if self.rules.is_some() {
+      let desc = self.descriptor_parser.parse_path_query(path)?;
+      if self.check_write_rules(&desc.resolved, path, api_name)? {
+        return Ok(desc.resolved);
+      }
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.write;
     if inner.is_allow_all() {
@@ -2630,6 +2833,9 @@ impl PermissionsContainer {
     path: &'a Path,
     api_name: &str,
   ) -> Result<Cow<'a, Path>, PermissionCheckError> {
+    if self.check_write_rules(path, &path.to_string_lossy(), Some(api_name))? {
+      return Ok(Cow::Borrowed(path));
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.write;
     if inner.is_allow_all() {
@@ -2650,6 +2856,13 @@ impl PermissionsContainer {
     &self,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
+    if self
+      .rules
+      .as_ref()
+      .is_some_and(|rules| rules.has_write_deny())
+    {
+      return Err(PermissionState::error("write", || None).into());
+    }
     self.inner.lock().write.check_all(Some(api_name))?;
     Ok(())
   }
@@ -2663,12 +2876,16 @@ impl PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
+    let display = format!("<{}>", display);
+    if self.check_write_rules(path, &display, Some(api_name))? {
+      return Ok(());
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.write;
     skip_check_if_is_permission_fully_granted!(inner);
     inner.check(
       &PathQueryDescriptor {
-        requested: format!("<{}>", display),
+        requested: display,
         resolved: path.to_path_buf(),
       }
       .into_write(),
@@ -2683,6 +2900,12 @@ impl PermissionsContainer {
     path: &str,
     api_name: &str,
   ) -> Result<PathBuf, PermissionCheckError> {
+    if self.rules.is_some() {
+      let desc = self.descriptor_parser.parse_path_query(path)?;
+      if self.check_write_rules(&desc.resolved, path, Some(api_name))? {
+        return Ok(desc.resolved);
+      }
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.write;
     if inner.is_allow_all() {
@@ -2875,6 +3098,13 @@ impl PermissionsContainer {
     url: &Url,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
+    if self.rules.is_some() {
+      let desc = self.descriptor_parser.parse_net_descriptor_from_url(url)?;
+      let protocol = rules::NetProtocol::from_scheme(url.scheme());
+      if self.check_net_rules(&desc, protocol, api_name)? {
+        return Ok(());
+      }
+    }
     let mut inner = self.inner.lock();
     if inner.net.is_allow_all() {
       return Ok(());
@@ -2890,6 +3120,13 @@ impl PermissionsContainer {
     host: &(T, Option<u16>),
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
+    if self.rules.is_some() {
+      let desc = NetDescriptor(Host::parse(host.0.as_ref())?, host.1);
+      let protocol = rules::NetProtocol::from_api_name(api_name);
+      if self.check_net_rules(&desc, protocol, api_name)? {
+        return Ok(());
+      }
+    }
     let mut inner = self.inner.lock();
     let inner = &mut inner.net;
     skip_check_if_is_permission_fully_granted!(inner);
@@ -3329,6 +3566,7 @@ const fn unit_permission_from_flag_bools(
       PermissionState::Prompt
     },
     prompt,
+    prompt_cache: None,
   }
 }
 
@@ -4971,6 +5209,53 @@ mod tests {
       .is_err());
   }
 
+  #[test]
+  fn test_deep_clone() {
+    set_prompter(Box::new(TestPrompter));
+    let parser = TestPermissionDescriptorParser;
+    let perms = Permissions::from_options(
+      &parser,
+      &PermissionsOptions {
+        allow_env: Some(vec![]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let perms = PermissionsContainer::new(Arc::new(parser), perms);
+    let copy = perms.deep_clone();
+    assert_eq!(copy.revoke_env(None), PermissionState::Prompt);
+    assert_eq!(copy.query_env(None), PermissionState::Prompt);
+    assert_eq!(perms.query_env(None), PermissionState::Granted);
+  }
+
+  #[test]
+  fn test_prompt_cache_per_container() {
+    set_prompter(Box::new(TestPrompter));
+    let prompt_value = PERMISSION_PROMPT_STUB_VALUE_SETTER.lock();
+    let create_perms = || {
+      let perms = Permissions::from_options(
+        &TestPermissionDescriptorParser,
+        &PermissionsOptions {
+          prompt: true,
+          ..Default::default()
+        },
+      )
+      .unwrap();
+      PermissionsContainer::new(Arc::new(TestPermissionDescriptorParser), perms)
+        .with_prompt_cache(PromptCache::new(PromptCachePolicy::Session))
+    };
+    let mut perms = create_perms();
+    let mut other_perms = create_perms();
+
+    prompt_value.set(true);
+    assert!(perms.check_env("HOME").is_ok());
+    prompt_value.set(false);
+    // answered for every variable by the session policy
+    assert!(perms.check_env("PATH").is_ok());
+    assert!(perms.deep_clone().check_env("USER").is_ok());
+    assert!(other_perms.check_env("PATH").is_err());
+  }
+
   #[test]
   fn test_create_child_permissions_with_prompt() {
     set_prompter(Box::new(TestPrompter));
@@ -5174,4 +5459,38 @@ mod tests {
       );
     }
   }
+
+  #[test]
+  fn test_permission_auditor() {
+    let entries = Arc::new(Mutex::new(Vec::new()));
+    audit::set_auditor(Some(Arc::new({
+      let entries = entries.clone();
+      move |entry: PermissionAuditEntry| entries.lock().push(entry)
+    })));
+    let parser = TestPermissionDescriptorParser;
+    let mut perms = Permissions::allow_all();
+    let mut denied = Permissions::none_without_prompt();
+    let query = |path: &str| parser.parse_path_query(path).unwrap().into_read();
+    assert!(perms
+      .read
+      .check(&query("/audit_granted"), Some("Deno.readFileSync()"))
+      .is_ok());
+    assert!(denied.read.check(&query("/audit_denied"), None).is_err());
+    audit::set_auditor(None);
+
+    let entries = entries.lock();
+    let find_entry = |path: &str| {
+      entries
+        .iter()
+        .find(|entry| entry.resource.as_deref() == Some(path))
+        .unwrap()
+    };
+    let granted = find_entry("\"/audit_granted\"");
+    assert_eq!(granted.name, "read");
+    assert_eq!(granted.api_name.as_deref(), Some("Deno.readFileSync()"));
+    assert!(granted.granted);
+    assert!(!granted.prompted);
+    let denied = find_entry("\"/audit_denied\"");
+    assert!(!denied.granted);
+  }
 }
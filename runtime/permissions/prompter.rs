@@ -2,15 +2,20 @@
 
 use crate::is_standalone;
 use deno_core::error::JsStackFrame;
+use deno_core::futures::future::BoxFuture;
 use deno_core::parking_lot::Mutex;
 use deno_terminal::colors;
 use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::io::BufRead;
 use std::io::IsTerminal;
 use std::io::StderrLock;
 use std::io::StdinLock;
 use std::io::Write as IoWrite;
+use std::sync::Arc;
 
 /// Helper function to make control characters visible so users can see the underlying filename.
 fn escape_control_characters(s: &str) -> std::borrow::Cow<str> {
@@ -37,7 +42,8 @@ pub const PERMISSION_EMOJI: &str = "⚠️";
 // 10kB of permission prompting should be enough for anyone
 const MAX_PERMISSION_PROMPT_LENGTH: usize = 10 * 1024;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PromptResponse {
   Allow,
   Deny,
@@ -60,22 +66,37 @@ pub fn set_current_stacktrace(trace: Vec<JsStackFrame>) {
   *MAYBE_CURRENT_STACKTRACE.lock() = Some(trace);
 }
 
+pub(crate) fn current_stacktrace() -> Option<Vec<JsStackFrame>> {
+  MAYBE_CURRENT_STACKTRACE.lock().clone()
+}
+
+/// Prompts for the `flag` permission. `resource` is the value access is
+/// requested to, eg. a quoted path, used to look up answers remembered in
+/// `cache`.
 pub fn permission_prompt(
   message: &str,
   flag: &str,
+  resource: Option<&str>,
   api_name: Option<&str>,
   is_unary: bool,
+  cache: Option<&PromptCache>,
 ) -> PromptResponse {
+  let stack = MAYBE_CURRENT_STACKTRACE.lock().take();
+  if let Some(response) = cache.and_then(|cache| cache.get(flag, resource)) {
+    return response;
+  }
   if let Some(before_callback) = MAYBE_BEFORE_PROMPT_CALLBACK.lock().as_mut() {
     before_callback();
   }
-  let stack = MAYBE_CURRENT_STACKTRACE.lock().take();
   let r = PERMISSION_PROMPTER
     .lock()
     .prompt(message, flag, api_name, is_unary, stack);
   if let Some(after_callback) = MAYBE_AFTER_PROMPT_CALLBACK.lock().as_mut() {
     after_callback();
   }
+  if let Some(cache) = cache {
+    cache.insert(flag, resource, r);
+  }
   r
 }
 
@@ -93,6 +114,169 @@ pub fn set_prompter(prompter: Box<dyn PermissionPrompter>) {
 
 pub type PromptCallback = Box<dyn FnMut() + Send + Sync>;
 
+/// Which answers to permission prompts are remembered, so the prompter
+/// isn't asked again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PromptCachePolicy {
+  /// Every prompt reaches the prompter.
+  #[default]
+  Never,
+  /// An answer applies to later prompts for the same permission and
+  /// resource, eg. reading the same file.
+  PerResource,
+  /// An answer applies to every later prompt for the same permission, for
+  /// as long as the cache is used.
+  Session,
+}
+
+/// A remembered answer to a permission prompt. `resource` is `None` for
+/// answers that apply to the whole permission.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RememberedPrompt {
+  /// Name of the permission, eg. `"read"` or `"net"`.
+  pub name: String,
+  /// The resource as shown in prompts, eg. `"\"/etc/hosts\""`.
+  pub resource: Option<String>,
+  pub response: PromptResponse,
+}
+
+#[derive(Default)]
+struct PromptCacheInner {
+  policy: PromptCachePolicy,
+  responses: HashMap<(String, Option<String>), PromptResponse>,
+}
+
+/// Answers to permission prompts, remembered by permission name and
+/// resource so the prompter isn't asked again. Attach it to the permissions
+/// of a worker with [`crate::PermissionsContainer::with_prompt_cache`];
+/// clones share the remembered answers.
+#[derive(Clone, Default)]
+pub struct PromptCache(Arc<Mutex<PromptCacheInner>>);
+
+impl std::fmt::Debug for PromptCache {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("PromptCache")
+      .field("policy", &self.0.lock().policy)
+      .finish_non_exhaustive()
+  }
+}
+
+impl PartialEq for PromptCache {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
+}
+
+impl Eq for PromptCache {}
+
+impl PromptCache {
+  pub fn new(policy: PromptCachePolicy) -> Self {
+    Self(Arc::new(Mutex::new(PromptCacheInner {
+      policy,
+      responses: HashMap::new(),
+    })))
+  }
+
+  /// Creates a cache with a copy of the remembered answers, so that answers
+  /// remembered by one of them don't apply to the other.
+  pub fn deep_clone(&self) -> Self {
+    let inner = self.0.lock();
+    Self(Arc::new(Mutex::new(PromptCacheInner {
+      policy: inner.policy,
+      responses: inner.responses.clone(),
+    })))
+  }
+
+  /// Sets which answers of the prompter are remembered. Answers remembered
+  /// before, including seeded ones, are kept.
+  pub fn set_policy(&self, policy: PromptCachePolicy) {
+    self.0.lock().policy = policy;
+  }
+
+  /// Seeds remembered answers, eg. the ones a user gave in a previous run.
+  /// They are used whatever the policy is.
+  pub fn remember(
+    &self,
+    responses: impl IntoIterator<Item = RememberedPrompt>,
+  ) {
+    let mut inner = self.0.lock();
+    for remembered in responses {
+      inner
+        .responses
+        .insert((remembered.name, remembered.resource), remembered.response);
+    }
+  }
+
+  /// Returns the remembered answers, eg. to persist them after a run.
+  pub fn remembered(&self) -> Vec<RememberedPrompt> {
+    let mut responses = self
+      .0
+      .lock()
+      .responses
+      .iter()
+      .map(|((name, resource), response)| RememberedPrompt {
+        name: name.clone(),
+        resource: resource.clone(),
+        response: *response,
+      })
+      .collect::<Vec<_>>();
+    responses
+      .sort_by(|a, b| (&a.name, &a.resource).cmp(&(&b.name, &b.resource)));
+    responses
+  }
+
+  /// Forgets every remembered answer.
+  pub fn clear(&self) {
+    self.0.lock().responses.clear();
+  }
+
+  fn get(&self, name: &str, resource: Option<&str>) -> Option<PromptResponse> {
+    let inner = self.0.lock();
+    if inner.responses.is_empty() {
+      return None;
+    }
+    let key = |resource: Option<&str>| {
+      (
+        name.to_string(),
+        resource.map(|resource| resource.to_string()),
+      )
+    };
+    if let Some(response) = inner.responses.get(&key(resource)) {
+      return Some(*response);
+    }
+    inner.responses.get(&key(None)).copied()
+  }
+
+  fn insert(
+    &self,
+    name: &str,
+    resource: Option<&str>,
+    response: PromptResponse,
+  ) {
+    let mut inner = self.0.lock();
+    let resource = match (inner.policy, response) {
+      (PromptCachePolicy::Never, _) => return,
+      // "allow all" grants every resource
+      (_, PromptResponse::AllowAll) | (PromptCachePolicy::Session, _) => None,
+      // denying access to every resource doesn't deny a single one
+      (PromptCachePolicy::PerResource, PromptResponse::Deny)
+        if resource.is_none() =>
+      {
+        return
+      }
+      (PromptCachePolicy::PerResource, _) => resource,
+    };
+    inner.responses.insert(
+      (
+        name.to_string(),
+        resource.map(|resource| resource.to_string()),
+      ),
+      response,
+    );
+  }
+}
+
 pub trait PermissionPrompter: Send + Sync {
   fn prompt(
     &mut self,
@@ -104,6 +288,55 @@ pub trait PermissionPrompter: Send + Sync {
   ) -> PromptResponse;
 }
 
+/// A permission prompt passed to an [`AsyncPermissionPrompter`].
+#[derive(Debug, Clone)]
+pub struct PromptRequest {
+  pub message: String,
+  pub name: String,
+  pub api_name: Option<String>,
+  pub is_unary: bool,
+  pub stack: Option<Vec<JsStackFrame>>,
+}
+
+/// A prompter that answers permission prompts asynchronously, eg. by showing
+/// a dialog in a GUI. Wrap it in an [`AsyncPromptAdapter`] to pass it to
+/// [`set_prompter`].
+pub trait AsyncPermissionPrompter: Send + Sync {
+  fn prompt(
+    &self,
+    request: PromptRequest,
+  ) -> BoxFuture<'static, PromptResponse>;
+}
+
+/// Adapts an [`AsyncPermissionPrompter`] to a [`PermissionPrompter`].
+///
+/// Permission checks are synchronous, so the thread of the worker that
+/// requested the permission waits until the returned future resolves. The
+/// future is polled on that thread without a runtime, so it should only
+/// wait on something driven elsewhere, like a channel answered from the
+/// embedder's event loop.
+pub struct AsyncPromptAdapter<P: AsyncPermissionPrompter>(pub P);
+
+impl<P: AsyncPermissionPrompter> PermissionPrompter for AsyncPromptAdapter<P> {
+  fn prompt(
+    &mut self,
+    message: &str,
+    name: &str,
+    api_name: Option<&str>,
+    is_unary: bool,
+    stack: Option<Vec<JsStackFrame>>,
+  ) -> PromptResponse {
+    let request = PromptRequest {
+      message: message.to_string(),
+      name: name.to_string(),
+      api_name: api_name.map(|api_name| api_name.to_string()),
+      is_unary,
+      stack,
+    };
+    deno_core::futures::executor::block_on(self.0.prompt(request))
+  }
+}
+
 pub struct TtyPrompter;
 #[cfg(unix)]
 fn clear_stdin(
@@ -514,6 +747,42 @@ pub mod tests {
 
   static STUB_PROMPT_VALUE: AtomicBool = AtomicBool::new(true);
 
+  #[test]
+  fn prompt_cache_policies() {
+    let cache = PromptCache::new(PromptCachePolicy::PerResource);
+    cache.insert("read", Some("\"/a\""), PromptResponse::Allow);
+    cache.insert("read", None, PromptResponse::Deny);
+    assert_eq!(
+      cache.get("read", Some("\"/a\"")),
+      Some(PromptResponse::Allow)
+    );
+    assert_eq!(cache.get("read", Some("\"/b\"")), None);
+    cache.insert("net", Some("\"deno.land\""), PromptResponse::AllowAll);
+    assert_eq!(
+      cache.get("net", Some("\"example.com\"")),
+      Some(PromptResponse::AllowAll)
+    );
+
+    cache.set_policy(PromptCachePolicy::Session);
+    cache.insert("env", Some("\"HOME\""), PromptResponse::Deny);
+    assert_eq!(
+      cache.get("env", Some("\"PATH\"")),
+      Some(PromptResponse::Deny)
+    );
+
+    cache.set_policy(PromptCachePolicy::Never);
+    cache.insert("sys", Some("\"hostname\""), PromptResponse::Allow);
+    assert_eq!(cache.get("sys", Some("\"hostname\"")), None);
+
+    let copy = cache.deep_clone();
+    copy.clear();
+    assert_eq!(copy.get("env", Some("\"PATH\"")), None);
+    assert_eq!(
+      cache.get("env", Some("\"PATH\"")),
+      Some(PromptResponse::Deny)
+    );
+  }
+
   pub static PERMISSION_PROMPT_STUB_VALUE_SETTER: Lazy<
     Mutex<PermissionPromptStubValueSetter>,
   > = Lazy::new(|| Mutex::new(PermissionPromptStubValueSetter));
@@ -0,0 +1,258 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::OpState;
+use deno_core::ResourceId;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A resource a worker is given a quota of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuotaKind {
+  /// Files open at the same time.
+  OpenFiles,
+  /// Subprocesses spawned over the lifetime of the worker.
+  Subprocesses,
+  /// TCP, TLS, UDP and Unix sockets and listeners open at the same time,
+  /// including WebSockets, the connections accepted by `Deno.serve()` and
+  /// the requests of `fetch()` and `node:http` until their response is
+  /// closed. Accepted connections exceeding the quota are dropped.
+  Sockets,
+}
+
+impl fmt::Display for QuotaKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      QuotaKind::OpenFiles => f.write_str("open files"),
+      QuotaKind::Subprocesses => f.write_str("spawned subprocesses"),
+      QuotaKind::Sockets => f.write_str("open sockets"),
+    }
+  }
+}
+
+/// Thrown to JavaScript as `Deno.errors.QuotaExceeded`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Exceeded the quota of {limit} {kind}")]
+pub struct QuotaExceededError {
+  pub kind: QuotaKind,
+  pub limit: usize,
+}
+
+/// Notified when a worker exceeds one of its [`ResourceQuotas`], right
+/// before the error is thrown to JavaScript.
+pub trait QuotaObserver: Send + Sync {
+  fn on_quota_exceeded(&self, error: &QuotaExceededError);
+}
+
+impl<F: Fn(&QuotaExceededError) + Send + Sync> QuotaObserver for F {
+  fn on_quota_exceeded(&self, error: &QuotaExceededError) {
+    self(error)
+  }
+}
+
+/// Limits on the resources each worker may use. Every worker, including
+/// web workers, gets the full quotas for itself.
+#[derive(Clone, Default)]
+pub struct ResourceQuotas {
+  pub max_open_files: Option<usize>,
+  pub max_subprocesses: Option<usize>,
+  pub max_sockets: Option<usize>,
+  pub observer: Option<Arc<dyn QuotaObserver>>,
+}
+
+impl ResourceQuotas {
+  fn limit(&self, kind: QuotaKind) -> Option<usize> {
+    match kind {
+      QuotaKind::OpenFiles => self.max_open_files,
+      QuotaKind::Subprocesses => self.max_subprocesses,
+      QuotaKind::Sockets => self.max_sockets,
+    }
+  }
+}
+
+/// A unit taken from a quota, given back when dropped.
+#[derive(Default)]
+pub struct QuotaPermit(Option<Rc<Cell<usize>>>);
+
+impl QuotaPermit {
+  /// Never gives the unit back, for quotas on a total like the spawned
+  /// subprocesses.
+  pub fn keep(mut self) {
+    self.0.take();
+  }
+}
+
+impl Drop for QuotaPermit {
+  fn drop(&mut self) {
+    if let Some(count) = self.0.take() {
+      count.set(count.get() - 1);
+    }
+  }
+}
+
+/// The usage of the quotas of a worker, kept in its `OpState`.
+pub struct QuotaState {
+  quotas: ResourceQuotas,
+  usage: HashMap<QuotaKind, Rc<Cell<usize>>>,
+  held: HashMap<ResourceId, QuotaPermit>,
+}
+
+impl QuotaState {
+  pub fn new(quotas: ResourceQuotas) -> Self {
+    Self {
+      quotas,
+      usage: Default::default(),
+      held: Default::default(),
+    }
+  }
+}
+
+/// Takes a unit of `kind` from the quota of the worker before a resource is
+/// opened. The permit is passed to [`hold_quota`] once the resource was
+/// added to the resource table, or dropped if opening it failed.
+pub fn acquire_quota(
+  state: &mut OpState,
+  kind: QuotaKind,
+) -> Result<QuotaPermit, QuotaExceededError> {
+  let Some(quota_state) = state.try_borrow::<QuotaState>() else {
+    return Ok(QuotaPermit::default());
+  };
+  let Some(limit) = quota_state.quotas.limit(kind) else {
+    return Ok(QuotaPermit::default());
+  };
+  // give back the units of resources that were closed since
+  let closed = quota_state
+    .held
+    .keys()
+    .filter(|rid| !state.resource_table.has(**rid))
+    .copied()
+    .collect::<Vec<_>>();
+  let quota_state = state.borrow_mut::<QuotaState>();
+  for rid in closed {
+    quota_state.held.remove(&rid);
+  }
+  let count = quota_state.usage.entry(kind).or_default();
+  if count.get() >= limit {
+    let error = QuotaExceededError { kind, limit };
+    if let Some(observer) = &quota_state.quotas.observer {
+      observer.on_quota_exceeded(&error);
+    }
+    return Err(error);
+  }
+  count.set(count.get() + 1);
+  Ok(QuotaPermit(Some(count.clone())))
+}
+
+/// Keeps `permit` taken until the resource `rid` is closed.
+pub fn hold_quota(state: &mut OpState, rid: ResourceId, permit: QuotaPermit) {
+  if permit.0.is_none() {
+    return;
+  }
+  if let Some(quota_state) = state.try_borrow_mut::<QuotaState>() {
+    quota_state.held.insert(rid, permit);
+  }
+}
+
+/// Takes the permit held for `rid` along with the resource, for resources
+/// that are replaced by another one, eg. a TCP connection upgraded to TLS.
+/// The permit is passed to [`hold_quota`] for the new resource, or dropped
+/// if creating it failed.
+pub fn take_quota(state: &mut OpState, rid: ResourceId) -> QuotaPermit {
+  state
+    .try_borrow_mut::<QuotaState>()
+    .and_then(|quota_state| quota_state.held.remove(&rid))
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::borrow::Cow;
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::atomic::Ordering;
+
+  struct TestResource;
+
+  impl deno_core::Resource for TestResource {
+    fn name(&self) -> Cow<str> {
+      "test".into()
+    }
+  }
+
+  #[test]
+  fn quota_released_when_resource_closed() {
+    let exceeded = Arc::new(AtomicUsize::new(0));
+    let mut state = OpState::new(None, None);
+    state.put(QuotaState::new(ResourceQuotas {
+      max_open_files: Some(1),
+      observer: Some(Arc::new({
+        let exceeded = exceeded.clone();
+        move |_: &QuotaExceededError| {
+          exceeded.fetch_add(1, Ordering::SeqCst);
+        }
+      })),
+      ..Default::default()
+    }));
+
+    // failing to open the resource gives the unit back
+    drop(acquire_quota(&mut state, QuotaKind::OpenFiles).unwrap());
+    let permit = acquire_quota(&mut state, QuotaKind::OpenFiles).unwrap();
+    let rid = state.resource_table.add(TestResource);
+    hold_quota(&mut state, rid, permit);
+
+    let err = acquire_quota(&mut state, QuotaKind::OpenFiles)
+      .err()
+      .unwrap();
+    assert_eq!(
+      err,
+      QuotaExceededError {
+        kind: QuotaKind::OpenFiles,
+        limit: 1
+      }
+    );
+    assert_eq!(err.to_string(), "Exceeded the quota of 1 open files");
+    assert_eq!(exceeded.load(Ordering::SeqCst), 1);
+    // other kinds have their own quota
+    assert!(acquire_quota(&mut state, QuotaKind::Sockets).is_ok());
+
+    state.resource_table.take_any(rid).unwrap().close();
+    assert!(acquire_quota(&mut state, QuotaKind::OpenFiles).is_ok());
+  }
+
+  #[test]
+  fn quota_taken_for_replaced_resource() {
+    let mut state = OpState::new(None, None);
+    state.put(QuotaState::new(ResourceQuotas {
+      max_sockets: Some(1),
+      ..Default::default()
+    }));
+    let permit = acquire_quota(&mut state, QuotaKind::Sockets).unwrap();
+    let rid = state.resource_table.add(TestResource);
+    hold_quota(&mut state, rid, permit);
+
+    state.resource_table.take_any(rid).unwrap();
+    let permit = take_quota(&mut state, rid);
+    assert!(acquire_quota(&mut state, QuotaKind::Sockets).is_err());
+    let new_rid = state.resource_table.add(TestResource);
+    hold_quota(&mut state, new_rid, permit);
+    assert!(acquire_quota(&mut state, QuotaKind::Sockets).is_err());
+
+    state.resource_table.take_any(new_rid).unwrap().close();
+    assert!(acquire_quota(&mut state, QuotaKind::Sockets).is_ok());
+  }
+
+  #[test]
+  fn quota_kept_for_totals() {
+    let mut state = OpState::new(None, None);
+    state.put(QuotaState::new(ResourceQuotas {
+      max_subprocesses: Some(1),
+      ..Default::default()
+    }));
+    acquire_quota(&mut state, QuotaKind::Subprocesses)
+      .unwrap()
+      .keep();
+    assert!(acquire_quota(&mut state, QuotaKind::Subprocesses).is_err());
+  }
+}
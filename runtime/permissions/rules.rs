@@ -0,0 +1,351 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_path_util::normalize_path;
+use std::fmt;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::Host;
+use super::HostParseError;
+
+/// The protocol a network access is made with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetProtocol {
+  Http,
+  Https,
+  Ws,
+  Wss,
+  Tcp,
+  Tls,
+  Udp,
+}
+
+impl NetProtocol {
+  pub(crate) fn from_scheme(scheme: &str) -> Option<Self> {
+    match scheme {
+      "http" => Some(NetProtocol::Http),
+      "https" => Some(NetProtocol::Https),
+      "ws" => Some(NetProtocol::Ws),
+      "wss" => Some(NetProtocol::Wss),
+      _ => None,
+    }
+  }
+
+  /// The protocol of checks that only have a host, based on the API that
+  /// requested them.
+  pub(crate) fn from_api_name(api_name: &str) -> Option<Self> {
+    match api_name {
+      "Deno.connect()" | "Deno.listen()" => Some(NetProtocol::Tcp),
+      "Deno.connectTls()" | "Deno.listenTls()" | "Deno.startTls()" => {
+        Some(NetProtocol::Tls)
+      }
+      "Deno.listenDatagram()" => Some(NetProtocol::Udp),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetRuleError {
+  #[error(
+    "invalid host pattern '{0}': only a leading '*.' wildcard is supported"
+  )]
+  InvalidPattern(String),
+  #[error("{0}")]
+  Host(#[from] HostParseError),
+  #[error("invalid empty port range {start}-{end}")]
+  EmptyPortRange { start: u16, end: u16 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+  Any,
+  Exact(Host),
+  /// Subdomains of the domain, but not the domain itself.
+  Subdomains(String),
+}
+
+/// Network access matched by host, port and protocol.
+///
+/// ```
+/// # use deno_permissions::rules::{NetProtocol, NetRule};
+/// let rule = NetRule::new("*.example.com")?
+///   .with_ports(8000..=8999)?
+///   .with_protocols([NetProtocol::Https, NetProtocol::Wss]);
+/// # Ok::<(), deno_permissions::rules::NetRuleError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetRule {
+  host: HostPattern,
+  ports: Option<RangeInclusive<u16>>,
+  protocols: Vec<NetProtocol>,
+}
+
+impl NetRule {
+  /// Matches `host` on any port and protocol. The host is a domain, an IP
+  /// address, `*.` followed by a domain to match its subdomains, or `*` to
+  /// match any host.
+  pub fn new(host: &str) -> Result<Self, NetRuleError> {
+    let host = if host == "*" {
+      HostPattern::Any
+    } else if let Some(domain) = host.strip_prefix("*.") {
+      if domain.contains('*') {
+        return Err(NetRuleError::InvalidPattern(host.to_string()));
+      }
+      match Host::parse(domain)? {
+        Host::Fqdn(fqdn) => HostPattern::Subdomains(fqdn.to_string()),
+        Host::Ip(_) => {
+          return Err(NetRuleError::InvalidPattern(host.to_string()))
+        }
+      }
+    } else if host.contains('*') {
+      return Err(NetRuleError::InvalidPattern(host.to_string()));
+    } else {
+      HostPattern::Exact(Host::parse(host)?)
+    };
+    Ok(Self {
+      host,
+      ports: None,
+      protocols: Vec::new(),
+    })
+  }
+
+  /// Only matches ports in `ports`.
+  pub fn with_ports(
+    mut self,
+    ports: RangeInclusive<u16>,
+  ) -> Result<Self, NetRuleError> {
+    if ports.is_empty() {
+      return Err(NetRuleError::EmptyPortRange {
+        start: *ports.start(),
+        end: *ports.end(),
+      });
+    }
+    self.ports = Some(ports);
+    Ok(self)
+  }
+
+  /// Only matches access with one of `protocols`. Accesses whose protocol
+  /// isn't known, eg. DNS lookups, don't match.
+  pub fn with_protocols(
+    mut self,
+    protocols: impl IntoIterator<Item = NetProtocol>,
+  ) -> Self {
+    self.protocols = protocols.into_iter().collect();
+    self
+  }
+
+  fn matches(
+    &self,
+    host: &Host,
+    port: Option<u16>,
+    protocol: Option<NetProtocol>,
+  ) -> bool {
+    let host_matches = match &self.host {
+      HostPattern::Any => true,
+      HostPattern::Exact(pattern) => pattern == host,
+      HostPattern::Subdomains(domain) => match host {
+        Host::Fqdn(fqdn) => fqdn
+          .to_string()
+          .strip_suffix(domain.as_str())
+          .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+        Host::Ip(_) => false,
+      },
+    };
+    let port_matches = match (&self.ports, port) {
+      (None, _) => true,
+      (Some(ports), Some(port)) => ports.contains(&port),
+      (Some(_), None) => false,
+    };
+    let protocol_matches = self.protocols.is_empty()
+      || protocol.is_some_and(|protocol| self.protocols.contains(&protocol));
+    host_matches && port_matches && protocol_matches
+  }
+}
+
+impl fmt::Display for NetRule {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.host {
+      HostPattern::Any => write!(f, "*")?,
+      HostPattern::Exact(Host::Fqdn(fqdn)) => write!(f, "{fqdn}")?,
+      HostPattern::Exact(Host::Ip(IpAddr::V4(ip))) => write!(f, "{ip}")?,
+      HostPattern::Exact(Host::Ip(IpAddr::V6(ip))) => write!(f, "[{ip}]")?,
+      HostPattern::Subdomains(domain) => write!(f, "*.{domain}")?,
+    }
+    if let Some(ports) = &self.ports {
+      if ports.start() == ports.end() {
+        write!(f, ":{}", ports.start())?;
+      } else {
+        write!(f, ":{}-{}", ports.start(), ports.end())?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PathRuleError {
+  #[error("path rules must be absolute: '{0}'")]
+  NotAbsolute(PathBuf),
+}
+
+/// File system access to a path and everything below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRule(PathBuf);
+
+impl PathRule {
+  pub fn new(prefix: impl AsRef<Path>) -> Result<Self, PathRuleError> {
+    let prefix = prefix.as_ref();
+    if !prefix.is_absolute() {
+      return Err(PathRuleError::NotAbsolute(prefix.to_path_buf()));
+    }
+    Ok(Self(normalize_path(prefix)))
+  }
+
+  pub fn prefix(&self) -> &Path {
+    &self.0
+  }
+
+  fn matches(&self, path: &Path) -> bool {
+    path.starts_with(&self.0)
+  }
+}
+
+/// Typed allow and deny rules, checked before the permissions of a
+/// [`PermissionsContainer`](super::PermissionsContainer).
+///
+/// Access matching a deny rule is denied, access matching an allow rule is
+/// granted without prompting, and any other access is checked against the
+/// permissions as usual. Queries through `Deno.permissions` don't take the
+/// rules into account.
+#[derive(Debug, Clone, Default)]
+pub struct PermissionRules {
+  pub allow_net: Vec<NetRule>,
+  pub deny_net: Vec<NetRule>,
+  pub allow_read: Vec<PathRule>,
+  pub deny_read: Vec<PathRule>,
+  pub allow_write: Vec<PathRule>,
+  pub deny_write: Vec<PathRule>,
+}
+
+impl PermissionRules {
+  /// The deny rules, which web workers inherit. Allow rules are not
+  /// inherited, so that workers can't get more access than they are given.
+  pub(crate) fn deny_only(&self) -> Self {
+    Self {
+      deny_net: self.deny_net.clone(),
+      deny_read: self.deny_read.clone(),
+      deny_write: self.deny_write.clone(),
+      ..Default::default()
+    }
+  }
+
+  /// `Some(true)` when an allow rule matches, `Some(false)` when a deny
+  /// rule does.
+  pub(crate) fn check_net(
+    &self,
+    host: &Host,
+    port: Option<u16>,
+    protocol: Option<NetProtocol>,
+  ) -> Option<bool> {
+    check(&self.allow_net, &self.deny_net, |rule| {
+      rule.matches(host, port, protocol)
+    })
+  }
+
+  pub(crate) fn check_read(&self, path: &Path) -> Option<bool> {
+    check(&self.allow_read, &self.deny_read, |rule| rule.matches(path))
+  }
+
+  pub(crate) fn check_write(&self, path: &Path) -> Option<bool> {
+    check(&self.allow_write, &self.deny_write, |rule| {
+      rule.matches(path)
+    })
+  }
+
+  pub(crate) fn has_read_deny(&self) -> bool {
+    !self.deny_read.is_empty()
+  }
+
+  pub(crate) fn has_write_deny(&self) -> bool {
+    !self.deny_write.is_empty()
+  }
+}
+
+fn check<T>(
+  allow: &[T],
+  deny: &[T],
+  matches: impl Fn(&T) -> bool,
+) -> Option<bool> {
+  if deny.iter().any(&matches) {
+    Some(false)
+  } else if allow.iter().any(&matches) {
+    Some(true)
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn net_rule_matches() {
+    let rule = NetRule::new("*.example.com")
+      .unwrap()
+      .with_ports(8000..=8999)
+      .unwrap()
+      .with_protocols([NetProtocol::Https]);
+    let host = Host::must_parse("api.example.com");
+    assert!(rule.matches(&host, Some(8443), Some(NetProtocol::Https)));
+    assert!(!rule.matches(&host, Some(443), Some(NetProtocol::Https)));
+    assert!(!rule.matches(&host, Some(8443), Some(NetProtocol::Http)));
+    assert!(!rule.matches(&host, Some(8443), None));
+    let apex = Host::must_parse("example.com");
+    assert!(!rule.matches(&apex, Some(8443), Some(NetProtocol::Https)));
+    let other = Host::must_parse("badexample.com");
+    assert!(!rule.matches(&other, Some(8443), Some(NetProtocol::Https)));
+    assert_eq!(rule.to_string(), "*.example.com:8000-8999");
+  }
+
+  #[test]
+  fn net_rule_validation() {
+    assert!(NetRule::new("*").is_ok());
+    assert!(NetRule::new("[::1]").is_ok());
+    assert!(matches!(
+      NetRule::new("api.*.com"),
+      Err(NetRuleError::InvalidPattern(_))
+    ));
+    assert!(matches!(
+      NetRule::new("*.127.0.0.1"),
+      Err(NetRuleError::InvalidPattern(_))
+    ));
+    #[allow(clippy::reversed_empty_ranges)]
+    let empty = 9000..=8000;
+    assert!(matches!(
+      NetRule::new("example.com").unwrap().with_ports(empty),
+      Err(NetRuleError::EmptyPortRange { .. })
+    ));
+    assert!(matches!(
+      PathRule::new("relative/dir"),
+      Err(PathRuleError::NotAbsolute(_))
+    ));
+  }
+
+  #[test]
+  fn deny_rules_take_precedence() {
+    let root = if cfg!(windows) { "C:\\data" } else { "/data" };
+    let rules = PermissionRules {
+      allow_read: vec![PathRule::new(root).unwrap()],
+      deny_read: vec![PathRule::new(Path::new(root).join("secrets")).unwrap()],
+      ..Default::default()
+    };
+    let root = Path::new(root);
+    assert_eq!(rules.check_read(&root.join("a.txt")), Some(true));
+    assert_eq!(rules.check_read(&root.join("secrets/key")), Some(false));
+    assert_eq!(rules.check_read(Path::new("/other")), None);
+  }
+}
@@ -290,8 +290,8 @@ pub fn create_runtime_snapshot(
     deno_broadcast_channel::deno_broadcast_channel::init_ops_and_esm(
       deno_broadcast_channel::InMemoryBroadcastChannel::default(),
     ),
-    deno_ffi::deno_ffi::init_ops_and_esm::<Permissions>(),
-    deno_net::deno_net::init_ops_and_esm::<Permissions>(None, None),
+    deno_ffi::deno_ffi::init_ops_and_esm::<Permissions>(None),
+    deno_net::deno_net::init_ops_and_esm::<Permissions>(None, None, None),
     deno_tls::deno_tls::init_ops_and_esm(),
     deno_kv::deno_kv::init_ops_and_esm(
       deno_kv::sqlite::SqliteDbHandler::<Permissions>::new(None, None),
@@ -312,12 +312,13 @@ pub fn create_runtime_snapshot(
     ops::worker_host::deno_worker_host::init_ops(
       Arc::new(|_| unreachable!("not used in snapshot.")),
       None,
+      None,
     ),
     ops::fs_events::deno_fs_events::init_ops(),
-    ops::os::deno_os::init_ops(Default::default()),
+    ops::os::deno_os::init_ops(Default::default(), None),
     ops::permissions::deno_permissions::init_ops(),
-    ops::process::deno_process::init_ops(None),
-    ops::signal::deno_signal::init_ops(),
+    ops::process::deno_process::init_ops(None, None),
+    ops::signal::deno_signal::init_ops(None),
     ops::tty::deno_tty::init_ops(),
     ops::http::deno_http_runtime::init_ops(),
     ops::bootstrap::deno_bootstrap::init_ops(Some(snapshot_options)),
@@ -32,9 +32,12 @@ use deno_cron::local::LocalCronHandler;
 use deno_fs::FileSystem;
 use deno_http::DefaultHttpPropertyExtractor;
 use deno_io::Stdio;
+use deno_kv::dynamic::DynamicDbHandler;
 use deno_kv::dynamic::MultiBackendDbHandler;
 use deno_node::NodeExtInitServices;
+use deno_permissions::quota::QuotaState;
 use deno_permissions::PermissionsContainer;
+use deno_permissions::ResourceQuotas;
 use deno_terminal::colors;
 use deno_tls::RootCertStoreProvider;
 use deno_tls::TlsKeys;
@@ -351,6 +354,27 @@ pub struct WebWorkerServiceOptions {
   pub permissions: PermissionsContainer,
   pub root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
   pub shared_array_buffer_store: Option<SharedArrayBufferStore>,
+  /// Sees every HTTP(S) request made with `fetch()` before it is sent.
+  pub fetch_interceptor: Option<Arc<dyn deno_fetch::FetchInterceptor>>,
+  /// Decides whether outbound TCP and TLS connections may be opened.
+  pub connect_interceptor: Option<Arc<dyn deno_net::ConnectInterceptor>>,
+  /// Decides whether subprocesses may be spawned, and may rewrite them.
+  pub spawn_interceptor: Option<Arc<dyn ops::process::SpawnInterceptor>>,
+  /// Decides which dynamic libraries may be opened with `Deno.dlopen()`.
+  pub dlopen_interceptor: Option<Arc<dyn deno_ffi::DlopenInterceptor>>,
+  /// Opens the `Deno.openKv()` databases that aren't remote, instead of
+  /// sqlite.
+  pub kv_backend: Option<Arc<dyn DynamicDbHandler + Send + Sync>>,
+  /// Decides whether scripts may create web workers, and how.
+  pub worker_creation_policy:
+    Option<Arc<dyn ops::worker_host::WorkerCreationPolicy>>,
+  /// Replaces the process environment for `Deno.env` and subprocesses.
+  pub virtual_env: Option<ops::os::VirtualEnv>,
+  /// Delivers signals to `Deno.addSignalListener` listeners instead of the
+  /// OS.
+  pub signal_forwarder: Option<ops::signal::SignalForwarder>,
+  /// Limits on the files, subprocesses and sockets the worker may use.
+  pub resource_quotas: Option<ResourceQuotas>,
 }
 
 pub struct WebWorkerOptions {
@@ -422,10 +446,14 @@ impl WebWorker {
       options = {
         permissions: PermissionsContainer,
         enable_testing_features: bool,
+        resource_quotas: Option<ResourceQuotas>,
       },
       state = |state, options| {
         state.put::<PermissionsContainer>(options.permissions);
         state.put(ops::TestingFeaturesEnabled(options.enable_testing_features));
+        if let Some(resource_quotas) = options.resource_quotas {
+          state.put(QuotaState::new(resource_quotas));
+        }
       },
     );
 
@@ -436,6 +464,29 @@ impl WebWorker {
       CreateCache(Arc::new(create_cache_fn))
     });
 
+    let kv_http_options = deno_kv::remote::HttpOptions {
+      user_agent: options.bootstrap.user_agent.clone(),
+      root_cert_store_provider: services.root_cert_store_provider.clone(),
+      unsafely_ignore_certificate_errors: options
+        .unsafely_ignore_certificate_errors
+        .clone(),
+      client_cert_chain_and_key: TlsKeys::Null,
+      proxy: None,
+    };
+    let kv_handler = match services.kv_backend.clone() {
+      Some(backend) => {
+        MultiBackendDbHandler::remote_or::<PermissionsContainer>(
+          backend,
+          kv_http_options,
+        )
+      }
+      None => MultiBackendDbHandler::remote_or_sqlite::<PermissionsContainer>(
+        None,
+        options.seed,
+        kv_http_options,
+      ),
+    };
+
     // NOTE(bartlomieju): ordering is important here, keep it in sync with
     // `runtime/worker.rs` and `runtime/snapshot.rs`!
 
@@ -459,6 +510,7 @@ impl WebWorker {
             .unsafely_ignore_certificate_errors
             .clone(),
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          interceptor: services.fetch_interceptor.clone(),
           ..Default::default()
         },
       ),
@@ -475,26 +527,17 @@ impl WebWorker {
       deno_broadcast_channel::deno_broadcast_channel::init_ops_and_esm(
         services.broadcast_channel,
       ),
-      deno_ffi::deno_ffi::init_ops_and_esm::<PermissionsContainer>(),
+      deno_ffi::deno_ffi::init_ops_and_esm::<PermissionsContainer>(
+        services.dlopen_interceptor.clone(),
+      ),
       deno_net::deno_net::init_ops_and_esm::<PermissionsContainer>(
         services.root_cert_store_provider.clone(),
         options.unsafely_ignore_certificate_errors.clone(),
+        services.connect_interceptor.clone(),
       ),
       deno_tls::deno_tls::init_ops_and_esm(),
       deno_kv::deno_kv::init_ops_and_esm(
-        MultiBackendDbHandler::remote_or_sqlite::<PermissionsContainer>(
-          None,
-          options.seed,
-          deno_kv::remote::HttpOptions {
-            user_agent: options.bootstrap.user_agent.clone(),
-            root_cert_store_provider: services.root_cert_store_provider,
-            unsafely_ignore_certificate_errors: options
-              .unsafely_ignore_certificate_errors
-              .clone(),
-            client_cert_chain_and_key: TlsKeys::Null,
-            proxy: None,
-          },
-        ),
+        kv_handler,
         deno_kv::KvConfig::builder().build(),
       ),
       deno_cron::deno_cron::init_ops_and_esm(LocalCronHandler::new()),
@@ -515,14 +558,18 @@ impl WebWorker {
       ops::worker_host::deno_worker_host::init_ops_and_esm(
         options.create_web_worker_cb,
         options.format_js_error_fn,
+        services.worker_creation_policy.clone(),
       ),
       ops::fs_events::deno_fs_events::init_ops_and_esm(),
-      ops::os::deno_os_worker::init_ops_and_esm(),
+      ops::os::deno_os_worker::init_ops_and_esm(services.virtual_env.clone()),
       ops::permissions::deno_permissions::init_ops_and_esm(),
       ops::process::deno_process::init_ops_and_esm(
         services.npm_process_state_provider,
+        services.spawn_interceptor.clone(),
+      ),
+      ops::signal::deno_signal::init_ops_and_esm(
+        services.signal_forwarder.clone(),
       ),
-      ops::signal::deno_signal::init_ops_and_esm(),
       ops::tty::deno_tty::init_ops_and_esm(),
       ops::http::deno_http_runtime::init_ops_and_esm(),
       ops::bootstrap::deno_bootstrap::init_ops_and_esm(
@@ -535,6 +582,7 @@ impl WebWorker {
       deno_permissions_web_worker::init_ops_and_esm(
         services.permissions,
         enable_testing_features,
+        services.resource_quotas,
       ),
       runtime::init_ops_and_esm(),
       ops::web_worker::deno_web_worker::init_ops_and_esm(),
@@ -751,6 +799,19 @@ impl WebWorker {
     self.js_runtime.load_main_es_module(module_specifier).await
   }
 
+  /// Like [`preload_main_module`](Self::preload_main_module), but runs
+  /// `source` instead of loading the module.
+  pub async fn preload_main_module_from_code(
+    &mut self,
+    module_specifier: &ModuleSpecifier,
+    source: String,
+  ) -> Result<ModuleId, AnyError> {
+    self
+      .js_runtime
+      .load_main_es_module_from_code(module_specifier, source)
+      .await
+  }
+
   /// Loads and instantiates specified JavaScript module as "side" module.
   pub async fn preload_side_module(
     &mut self,
@@ -941,6 +1002,7 @@ pub fn run_web_worker(
   mut worker: WebWorker,
   specifier: ModuleSpecifier,
   mut maybe_source_code: Option<String>,
+  maybe_module_source: Option<String>,
   format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
 ) -> Result<(), AnyError> {
   let name = worker.name.to_string();
@@ -959,7 +1021,15 @@ pub fn run_web_worker(
     } else {
       // TODO(bartlomieju): add "type": "classic", ie. ability to load
       // script instead of module
-      match worker.preload_main_module(&specifier).await {
+      let preload_result = match maybe_module_source {
+        Some(source) => {
+          worker
+            .preload_main_module_from_code(&specifier, source)
+            .await
+        }
+        None => worker.preload_main_module(&specifier).await,
+      };
+      match preload_result {
         Ok(id) => {
           worker.start_polling_for_messages();
           worker.execute_main_module(id).await
@@ -38,9 +38,12 @@ use deno_cron::local::LocalCronHandler;
 use deno_fs::FileSystem;
 use deno_http::DefaultHttpPropertyExtractor;
 use deno_io::Stdio;
+use deno_kv::dynamic::DynamicDbHandler;
 use deno_kv::dynamic::MultiBackendDbHandler;
 use deno_node::NodeExtInitServices;
+use deno_permissions::quota::QuotaState;
 use deno_permissions::PermissionsContainer;
+use deno_permissions::ResourceQuotas;
 use deno_tls::RootCertStoreProvider;
 use deno_tls::TlsKeys;
 use deno_web::BlobStore;
@@ -144,6 +147,27 @@ pub struct WorkerServiceOptions {
   pub permissions: PermissionsContainer,
   pub root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
   pub fetch_dns_resolver: deno_fetch::dns::Resolver,
+  /// Sees every HTTP(S) request made with `fetch()` before it is sent.
+  pub fetch_interceptor: Option<Arc<dyn deno_fetch::FetchInterceptor>>,
+  /// Decides whether outbound TCP and TLS connections may be opened.
+  pub connect_interceptor: Option<Arc<dyn deno_net::ConnectInterceptor>>,
+  /// Decides whether subprocesses may be spawned, and may rewrite them.
+  pub spawn_interceptor: Option<Arc<dyn ops::process::SpawnInterceptor>>,
+  /// Decides which dynamic libraries may be opened with `Deno.dlopen()`.
+  pub dlopen_interceptor: Option<Arc<dyn deno_ffi::DlopenInterceptor>>,
+  /// Opens the `Deno.openKv()` databases that aren't remote, instead of
+  /// sqlite.
+  pub kv_backend: Option<Arc<dyn DynamicDbHandler + Send + Sync>>,
+  /// Decides whether scripts may create web workers, and how.
+  pub worker_creation_policy:
+    Option<Arc<dyn ops::worker_host::WorkerCreationPolicy>>,
+  /// Replaces the process environment for `Deno.env` and subprocesses.
+  pub virtual_env: Option<ops::os::VirtualEnv>,
+  /// Delivers signals to `Deno.addSignalListener` listeners instead of the
+  /// OS.
+  pub signal_forwarder: Option<ops::signal::SignalForwarder>,
+  /// Limits on the files, subprocesses and sockets the worker may use.
+  pub resource_quotas: Option<ResourceQuotas>,
 
   /// The store to use for transferring SharedArrayBuffers between isolates.
   /// If multiple isolates should have the possibility of sharing
@@ -324,10 +348,14 @@ impl MainWorker {
       options = {
         permissions: PermissionsContainer,
         enable_testing_features: bool,
+        resource_quotas: Option<ResourceQuotas>,
       },
       state = |state, options| {
         state.put::<PermissionsContainer>(options.permissions);
         state.put(ops::TestingFeaturesEnabled(options.enable_testing_features));
+        if let Some(resource_quotas) = options.resource_quotas {
+          state.put(QuotaState::new(resource_quotas));
+        }
       },
     );
 
@@ -345,6 +373,29 @@ impl MainWorker {
       CreateCache(Arc::new(create_cache_fn))
     });
 
+    let kv_http_options = deno_kv::remote::HttpOptions {
+      user_agent: options.bootstrap.user_agent.clone(),
+      root_cert_store_provider: services.root_cert_store_provider.clone(),
+      unsafely_ignore_certificate_errors: options
+        .unsafely_ignore_certificate_errors
+        .clone(),
+      client_cert_chain_and_key: TlsKeys::Null,
+      proxy: None,
+    };
+    let kv_handler = match services.kv_backend.clone() {
+      Some(backend) => {
+        MultiBackendDbHandler::remote_or::<PermissionsContainer>(
+          backend,
+          kv_http_options,
+        )
+      }
+      None => MultiBackendDbHandler::remote_or_sqlite::<PermissionsContainer>(
+        options.origin_storage_dir.clone(),
+        options.seed,
+        kv_http_options,
+      ),
+    };
+
     // NOTE(bartlomieju): ordering is important here, keep it in sync with
     // `runtime/web_worker.rs` and `runtime/snapshot.rs`!
     let mut extensions = vec![
@@ -368,6 +419,7 @@ impl MainWorker {
             .clone(),
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
           resolver: services.fetch_dns_resolver,
+          interceptor: services.fetch_interceptor.clone(),
           ..Default::default()
         },
       ),
@@ -386,26 +438,17 @@ impl MainWorker {
       deno_broadcast_channel::deno_broadcast_channel::init_ops_and_esm(
         services.broadcast_channel.clone(),
       ),
-      deno_ffi::deno_ffi::init_ops_and_esm::<PermissionsContainer>(),
+      deno_ffi::deno_ffi::init_ops_and_esm::<PermissionsContainer>(
+        services.dlopen_interceptor.clone(),
+      ),
       deno_net::deno_net::init_ops_and_esm::<PermissionsContainer>(
         services.root_cert_store_provider.clone(),
         options.unsafely_ignore_certificate_errors.clone(),
+        services.connect_interceptor.clone(),
       ),
       deno_tls::deno_tls::init_ops_and_esm(),
       deno_kv::deno_kv::init_ops_and_esm(
-        MultiBackendDbHandler::remote_or_sqlite::<PermissionsContainer>(
-          options.origin_storage_dir.clone(),
-          options.seed,
-          deno_kv::remote::HttpOptions {
-            user_agent: options.bootstrap.user_agent.clone(),
-            root_cert_store_provider: services.root_cert_store_provider.clone(),
-            unsafely_ignore_certificate_errors: options
-              .unsafely_ignore_certificate_errors
-              .clone(),
-            client_cert_chain_and_key: TlsKeys::Null,
-            proxy: None,
-          },
-        ),
+        kv_handler,
         deno_kv::KvConfig::builder().build(),
       ),
       deno_cron::deno_cron::init_ops_and_esm(LocalCronHandler::new()),
@@ -426,14 +469,21 @@ impl MainWorker {
       ops::worker_host::deno_worker_host::init_ops_and_esm(
         options.create_web_worker_cb.clone(),
         options.format_js_error_fn.clone(),
+        services.worker_creation_policy.clone(),
       ),
       ops::fs_events::deno_fs_events::init_ops_and_esm(),
-      ops::os::deno_os::init_ops_and_esm(exit_code.clone()),
+      ops::os::deno_os::init_ops_and_esm(
+        exit_code.clone(),
+        services.virtual_env.clone(),
+      ),
       ops::permissions::deno_permissions::init_ops_and_esm(),
       ops::process::deno_process::init_ops_and_esm(
         services.npm_process_state_provider,
+        services.spawn_interceptor.clone(),
+      ),
+      ops::signal::deno_signal::init_ops_and_esm(
+        services.signal_forwarder.clone(),
       ),
-      ops::signal::deno_signal::init_ops_and_esm(),
       ops::tty::deno_tty::init_ops_and_esm(),
       ops::http::deno_http_runtime::init_ops_and_esm(),
       ops::bootstrap::deno_bootstrap::init_ops_and_esm(
@@ -446,6 +496,7 @@ impl MainWorker {
       deno_permissions_worker::init_ops_and_esm(
         services.permissions,
         enable_testing_features,
+        services.resource_quotas,
       ),
       runtime::init_ops_and_esm(),
       // NOTE(bartlomieju): this is done, just so that ops from this extension
@@ -0,0 +1,6 @@
+const fs = require("node:fs");
+
+fetch("http://localhost:4545/").then(
+  () => fs.writeFileSync("network.txt", "reachable"),
+  (err) => fs.writeFileSync("network.txt", err.name),
+);
@@ -0,0 +1,79 @@
+;; A `wasi:cli/command` component writing a line to stdout, the source of
+;; `hello_component.wasm`.
+(component
+  (import "wasi:io/error@0.2.0" (instance $io-error
+    (export "error" (type (sub resource)))
+  ))
+  (alias export $io-error "error" (type $error))
+  (import "wasi:io/streams@0.2.0" (instance $streams
+    (alias outer 1 $error (type $outer-error))
+    (export $stream-error-payload "error" (type (eq $outer-error)))
+    (export $output-stream "output-stream" (type (sub resource)))
+    (type $stream-error-type (variant
+      (case "last-operation-failed" (own $stream-error-payload))
+      (case "closed")
+    ))
+    (export $stream-error "stream-error" (type (eq $stream-error-type)))
+    (export "[method]output-stream.blocking-write-and-flush"
+      (func
+        (param "self" (borrow $output-stream))
+        (param "contents" (list u8))
+        (result (result (error $stream-error)))
+      )
+    )
+  ))
+  (alias export $streams "output-stream" (type $output-stream))
+  (import "wasi:cli/stdout@0.2.0" (instance $stdout
+    (alias outer 1 $output-stream (type $outer-output-stream))
+    (export $stdout-stream "output-stream" (type (eq $outer-output-stream)))
+    (export "get-stdout" (func (result (own $stdout-stream))))
+  ))
+
+  (core module $memory-module
+    (memory (export "memory") 1)
+  )
+  (core instance $memory-instance (instantiate $memory-module))
+  (alias core export $memory-instance "memory" (core memory $memory))
+
+  (core func $get-stdout
+    (canon lower (func $stdout "get-stdout"))
+  )
+  (core func $write
+    (canon lower
+      (func $streams "[method]output-stream.blocking-write-and-flush")
+      (memory $memory)
+    )
+  )
+  (core func $drop-output-stream (canon resource.drop $output-stream))
+  (core instance $host
+    (export "memory" (memory $memory))
+    (export "get-stdout" (func $get-stdout))
+    (export "write" (func $write))
+    (export "drop-output-stream" (func $drop-output-stream))
+  )
+
+  (core module $main
+    (import "host" "memory" (memory 1))
+    (import "host" "get-stdout" (func $get-stdout (result i32)))
+    (import "host" "write" (func $write (param i32 i32 i32 i32)))
+    (import "host" "drop-output-stream" (func $drop-output-stream (param i32)))
+    (data (i32.const 16) "hello from a component\n")
+    (func (export "run") (result i32)
+      (local $stdout i32)
+      (local.set $stdout (call $get-stdout))
+      (call $write (local.get $stdout) (i32.const 16) (i32.const 23) (i32.const 0))
+      (call $drop-output-stream (local.get $stdout))
+      ;; the discriminant of the result of the write
+      (i32.load8_u (i32.const 0))
+    )
+  )
+  (core instance $main-instance
+    (instantiate $main (with "host" (instance $host)))
+  )
+
+  (func $run (result (result))
+    (canon lift (core func $main-instance "run"))
+  )
+  (instance $run-instance (export "run" (func $run)))
+  (export "wasi:cli/run@0.2.0" (instance $run-instance))
+)